@@ -1032,9 +1032,15 @@ impl Agent {
                     });
 
                     // --- Tool Execution with Timeout ---
-                    const TOOL_TIMEOUT_SECS: u64 = 60; // Timeout for tool execution
+                    const DEFAULT_TOOL_TIMEOUT_SECS: u64 = 60; // Timeout for tool execution
+                    // Agents whose tools wait on a person, like the CLI's confirmation
+                    // prompts, raise the timeout through the `tool_timeout_secs` state value
+                    let tool_timeout_secs = state
+                        .get("tool_timeout_secs")
+                        .and_then(Value::as_u64)
+                        .unwrap_or(DEFAULT_TOOL_TIMEOUT_SECS);
                     let tool_execution_result = tokio::time::timeout(
-                        Duration::from_secs(TOOL_TIMEOUT_SECS),
+                        Duration::from_secs(tool_timeout_secs),
                         registered_tool
                             .executor
                             .execute(params, tool_call.id.clone()),
@@ -1049,13 +1055,13 @@ impl Agent {
                             // Tool execution timed out
                             let timeout_msg = format!(
                                 "Tool '{}' timed out after {} seconds.",
-                                tool_call.function.name, TOOL_TIMEOUT_SECS
+                                tool_call.function.name, tool_timeout_secs
                             );
                             warn!(agent_name = %agent.name, chat_id = %agent.session_id, user_id = %agent.user_id, tool_name = %tool_call.function.name, "{}", timeout_msg);
                             // Return an error indicating timeout, wrapped in anyhow
                             Err(anyhow::anyhow!(format!(
                                 "Tool '{}' timed out after {} seconds.",
-                                tool_call.function.name, TOOL_TIMEOUT_SECS
+                                tool_call.function.name, tool_timeout_secs
                             )))
                         }
                     };
@@ -1209,6 +1215,21 @@ impl Agent {
         Ok(())
     }
 
+    /// Run one of the agent's registered tools directly, e.g. from a tool that batches
+    /// several calls together
+    pub async fn execute_tool(
+        &self,
+        name: &str,
+        params: Value,
+        tool_call_id: String,
+    ) -> Result<Value> {
+        let tools = self.tools.read().await;
+        let registered_tool = tools
+            .get(name)
+            .ok_or_else(|| anyhow::anyhow!("Attempted to call non-existent tool: {}", name))?;
+        registered_tool.executor.execute(params, tool_call_id).await
+    }
+
     /// Get a read lock on the tools map (Exposes RegisteredTool now)
     pub async fn get_tools_map(
         &self,
@@ -0,0 +1,232 @@
+use anyhow::Result;
+use chrono::Local;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+use litellm::AgentMessage;
+
+use crate::agents::modes::ModeConfiguration;
+use crate::tools::{
+    categories::{
+        cli_tools::{
+            BashTool, BatchTool, ConfirmedTool, EditTool, GlobTool, GrepTool, LSTool, ReplaceTool,
+            ToolConfirmationSender, ViewTool,
+        },
+        response_tools::Done,
+    },
+    IntoToolCallExecutor, ToolExecutor,
+};
+use crate::{agent::ModeProvider, Agent, AgentError, AgentExt, AgentThread};
+
+pub const DEFAULT_CLI_MODEL: &str = "o4-mini";
+
+/// Tools wait for the user to confirm them, so they get far longer than the default timeout
+const CLI_TOOL_TIMEOUT_SECS: u64 = 30 * 60;
+
+/// The CLI agent has a single mode: every tool is available on every turn
+struct CliModeProvider {
+    prompt: String,
+    model: String,
+    confirmations: ToolConfirmationSender,
+}
+
+#[async_trait::async_trait]
+impl ModeProvider for CliModeProvider {
+    async fn get_configuration_for_state(
+        &self,
+        _state: &HashMap<String, Value>,
+    ) -> Result<ModeConfiguration> {
+        let confirmations = self.confirmations.clone();
+        let tool_loader: Box<
+            dyn Fn(&Arc<Agent>) -> Pin<Box<dyn Future<Output = Result<()>> + Send>> + Send + Sync,
+        > = Box::new(move |agent_arc: &Arc<Agent>| {
+            let agent_clone = Arc::clone(agent_arc);
+            let confirmations = confirmations.clone();
+            Box::pin(async move {
+                agent_clone.clear_tools().await;
+                let always_available = Some(|_state: &HashMap<String, Value>| -> bool { true });
+
+                // Read-only tools run without asking
+                let view_tool = ViewTool::new(agent_clone.clone());
+                let ls_tool = LSTool::new(agent_clone.clone());
+                let glob_tool = GlobTool::new(agent_clone.clone());
+                let grep_tool = GrepTool::new(agent_clone.clone());
+                let batch_tool = BatchTool::new(agent_clone.clone());
+                let done_tool = Done::new(agent_clone.clone());
+
+                agent_clone
+                    .add_tool(
+                        view_tool.get_name(),
+                        view_tool.into_tool_call_executor(),
+                        always_available,
+                    )
+                    .await;
+                agent_clone
+                    .add_tool(
+                        ls_tool.get_name(),
+                        ls_tool.into_tool_call_executor(),
+                        always_available,
+                    )
+                    .await;
+                agent_clone
+                    .add_tool(
+                        glob_tool.get_name(),
+                        glob_tool.into_tool_call_executor(),
+                        always_available,
+                    )
+                    .await;
+                agent_clone
+                    .add_tool(
+                        grep_tool.get_name(),
+                        grep_tool.into_tool_call_executor(),
+                        always_available,
+                    )
+                    .await;
+                // Batched calls run through the registered tools, so they are confirmed too
+                agent_clone
+                    .add_tool(
+                        batch_tool.get_name(),
+                        batch_tool.into_tool_call_executor(),
+                        always_available,
+                    )
+                    .await;
+
+                // Tools that run commands or write files ask the user first
+                let bash_tool = ConfirmedTool::new(
+                    BashTool::new(agent_clone.clone()).into_tool_call_executor(),
+                    confirmations.clone(),
+                );
+                let edit_tool = ConfirmedTool::new(
+                    EditTool::new(agent_clone.clone()).into_tool_call_executor(),
+                    confirmations.clone(),
+                );
+                let replace_tool = ConfirmedTool::new(
+                    ReplaceTool::new(agent_clone.clone()).into_tool_call_executor(),
+                    confirmations,
+                );
+                agent_clone
+                    .add_tool(bash_tool.get_name(), bash_tool, always_available)
+                    .await;
+                agent_clone
+                    .add_tool(edit_tool.get_name(), edit_tool, always_available)
+                    .await;
+                agent_clone
+                    .add_tool(replace_tool.get_name(), replace_tool, always_available)
+                    .await;
+
+                agent_clone
+                    .add_tool(
+                        done_tool.get_name(),
+                        done_tool.into_tool_call_executor(),
+                        always_available,
+                    )
+                    .await;
+
+                Ok(())
+            })
+        });
+
+        Ok(ModeConfiguration {
+            prompt: self.prompt.clone(),
+            model: self.model.clone(),
+            tool_loader,
+            terminating_tools: vec![Done::get_name()],
+        })
+    }
+}
+
+/// Agent behind `buster chat`. It works on the user's local project: tools run on the
+/// user's machine, and those that change it are confirmed through `confirmations`.
+pub struct BusterCliAgent {
+    agent: Arc<Agent>,
+}
+
+impl AgentExt for BusterCliAgent {
+    fn get_agent_arc(&self) -> &Arc<Agent> {
+        &self.agent
+    }
+}
+
+impl BusterCliAgent {
+    pub async fn new(
+        user_id: Uuid,
+        session_id: Uuid,
+        api_key: Option<String>,
+        base_url: Option<String>,
+        cwd: Option<String>,
+        model: Option<String>,
+        confirmations: ToolConfirmationSender,
+    ) -> Result<Self> {
+        let model = model.unwrap_or_else(|| DEFAULT_CLI_MODEL.to_string());
+        let cwd = cwd.unwrap_or_else(|| ".".to_string());
+        let prompt = CLI_AGENT_PROMPT.replace("{CWD}", &cwd).replace(
+            "{TODAYS_DATE}",
+            &Local::now().format("%Y-%m-%d").to_string(),
+        );
+
+        let mode_provider = Arc::new(CliModeProvider {
+            prompt,
+            model: model.clone(),
+            confirmations,
+        });
+
+        let agent = Arc::new(Agent::new(
+            model,
+            user_id,
+            session_id,
+            "buster_cli_agent".to_string(),
+            api_key,
+            base_url,
+            mode_provider,
+        ));
+        agent
+            .set_state_value("cwd".to_string(), Value::String(cwd))
+            .await;
+        agent
+            .set_state_value(
+                "tool_timeout_secs".to_string(),
+                Value::from(CLI_TOOL_TIMEOUT_SECS),
+            )
+            .await;
+
+        Ok(Self { agent })
+    }
+
+    pub async fn run(
+        &self,
+        thread: &mut AgentThread,
+    ) -> Result<broadcast::Receiver<Result<AgentMessage, AgentError>>> {
+        self.stream_process_thread(thread).await
+    }
+
+    pub async fn shutdown(&self) -> Result<()> {
+        self.get_agent_arc().shutdown().await
+    }
+}
+
+const CLI_AGENT_PROMPT: &str = r##"You are Buster, an assistant that works inside the user's data project from their terminal.
+Today's date is {TODAYS_DATE}. The current working directory is {CWD}.
+
+The project usually contains:
+- A dbt project (`dbt_project.yml`, models under `models/`).
+- A `buster.yml` file that configures where semantic models live.
+- Semantic model YAML files describing tables: their dimensions, measures, metrics, filters and relationships.
+- Metric and dashboard files under `metrics/` and `dashboards/`, synced with `buster pull` and `buster push`.
+
+## Tools
+- Use `LS`, `GlobTool`, `GrepTool` and `View` to explore the project before changing anything.
+- Use `BatchTool` to run several independent read-only calls at once.
+- `Edit` and `Replace` change files, and `Bash` runs shell commands (e.g. `dbt compile`, `buster parse`, `buster deploy --dry-run`). The user confirms each of these calls before it runs. If they decline, do not retry the same call; ask them how to proceed.
+- Read a file before editing it, and keep edits minimal and consistent with the surrounding YAML or SQL.
+- Paths are relative to the current working directory unless they are absolute.
+
+## Responding
+- When you have finished, or need something from the user, call `done` with your reply in `final_response`.
+- Keep replies short. Say what you changed and which files you touched; do not repeat file contents back.
+- Format replies in markdown.
+"##;
@@ -1,5 +1,7 @@
+pub mod buster_cli_agent;
 pub mod buster_multi_agent;
 pub mod modes;
 
+pub use buster_cli_agent::BusterCliAgent;
 pub use buster_multi_agent::BusterMultiAgent;
-pub use modes::*;
\ No newline at end of file
+pub use modes::*;
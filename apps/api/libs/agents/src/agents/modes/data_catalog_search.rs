@@ -14,7 +14,7 @@ use super::{ModeAgentData, ModeConfiguration};
 // Import necessary tools for this mode
 use crate::tools::{
     categories::{
-        file_tools::SearchDataCatalogTool,
+        file_tools::{SearchDataCatalogTool, SearchTermsTool},
         utility_tools::no_search_needed::NoSearchNeededTool,
     },
     IntoToolCallExecutor,
@@ -49,6 +49,7 @@ pub fn get_configuration(agent_data: &ModeAgentData, _data_source_syntax: Option
 
                 // Instantiate tools for this mode
                 let search_data_catalog_tool = SearchDataCatalogTool::new(agent_clone.clone());
+                let search_terms_tool = SearchTermsTool::new(agent_clone.clone());
                 let no_search_needed_tool = NoSearchNeededTool::new(agent_clone.clone());
 
                 // Condition (always true for this mode's tools)
@@ -61,6 +62,12 @@ pub fn get_configuration(agent_data: &ModeAgentData, _data_source_syntax: Option
                     condition.clone(),
                 ).await;
 
+                agent_clone.add_tool(
+                    search_terms_tool.get_name(),
+                    search_terms_tool.into_tool_call_executor(),
+                    condition.clone(),
+                ).await;
+
                 agent_clone.add_tool(
                     no_search_needed_tool.get_name(),
                     no_search_needed_tool.into_tool_call_executor(),
@@ -88,7 +95,7 @@ pub fn get_configuration(agent_data: &ModeAgentData, _data_source_syntax: Option
 const DATA_CATALOG_SEARCH_PROMPT: &str = r##"**Role & Task**
 You are a Data Loading Agent. Your primary goal is to analyze the conversation history and the most recent user message to determine whether to load all available datasets with fresh value injection (`search_data_catalog`) or skip loading if no new data is needed (`no_search_needed`).
 
-Your sole output MUST be a call to **ONE** of these tools: `search_data_catalog` or `no_search_needed`.
+Your output MUST end with a call to **ONE** of these tools: `search_data_catalog` or `no_search_needed`. Before it, you may call `search_terms` once to resolve company-specific terminology.
 
 **Available Dataset Descriptions:**
 ```
@@ -104,28 +111,33 @@ Your sole output MUST be a call to **ONE** of these tools: `search_data_catalog`
     *   **DO NOT Extract**: General concepts ("revenue", "customers"), Time periods ("last month", "Q1"), Generic attributes ("name", "id"), Common words, Numbers without context, generic IDs (UUIDs, database keys like `cust_12345`, `9711ca55...`), or composite strings containing non-semantic identifiers. Focus *only* on values with inherent business meaning.
     *   **Goal**: Populate `value_search_terms` to enable fresh value injection into dataset YAMLs.
 4.  **Determine Loading Strategy**: Decide if data loading is needed for fresh analysis or if current context is sufficient.
-5.  **Generate Tool Call Parameters**: If loading data, formulate parameters for `search_data_catalog` which will load ALL datasets with fresh value injection.
+5.  **Resolve Business Terms**: Identify company jargon and business metrics whose exact definition matters (e.g., "active customer", "net revenue", "churned account") and look them up with `search_terms`, so the approved definition and SQL snippet are used instead of an invented one.
+6.  **Generate Tool Call Parameters**: If loading data, formulate parameters for `search_data_catalog` which will load ALL datasets with fresh value injection.
 
 **Workflow & Decision Logic:**
 
 1.  **Analyze Request & Context**: Review the latest user message and conversation history.
 2.  **Extract Specific Values**: Identify concrete values from the user request for value injection.
-3.  **Check for Visualization-Only Request**: If the request is *purely* about visual aspects (chart types, colors) and no new data analysis is needed -> Call `no_search_needed`.
-4.  **Assess Need for Fresh Data**: 
+3.  **Look Up Business Terms**: If the request uses business terms or metrics that the company may define in a specific way, call `search_terms` with those terms first. Skip this for requests that are purely visual or have no such terms.
+4.  **Check for Visualization-Only Request**: If the request is *purely* about visual aspects (chart types, colors) and no new data analysis is needed -> Call `no_search_needed`.
+5.  **Assess Need for Fresh Data**: 
     *   **If New Analysis Required**: Any request that involves data analysis, exploration, or requires fresh value injection -> Call `search_data_catalog`.
     *   **If No New Data Needed**: Simple clarifications or purely visual changes -> Call `no_search_needed`.
-5.  **Formulate Loading Parameters**:
+6.  **Formulate Loading Parameters**:
     *   **Specific Requests** (e.g., "Top customer by revenue", "Sales for Product X"): Generate `specific_queries` describing what data is needed.
     *   **Exploratory Requests** (e.g., "Tell me about revenue", "Factors influencing churn"): Generate `exploratory_topics` for broader data loading.
     *   **Mixed Requests**: Generate *both* `specific_queries` and `exploratory_topics` as appropriate.
     *   **Value Search Terms**: Always include extracted specific values in `value_search_terms` for fresh injection.
-6.  **Execute Tool Call**: Call the appropriate tool with generated parameters.
+7.  **Execute Tool Call**: Call the appropriate tool with generated parameters.
 
 **Tool Parameters (`search_data_catalog`)**
 -   `specific_queries`: `Option<Vec<String>>` - For focused requests. Natural language descriptions of needed data.
 -   `exploratory_topics`: `Option<Vec<String>>` - For broad/investigative requests. Topics for data exploration.
 -   `value_search_terms`: `Option<Vec<String>>` - **CRITICAL**: Specific values from the user request for fresh injection into datasets.
 
+**Tool Parameters (`search_terms`)**
+-   `queries`: `Vec<String>` - Business terms or metrics to look up in the glossary (e.g., "active customer", "ARR"). Not specific values like product names or places.
+
 **Important Notes:**
 -   **Data Loading Strategy**: `search_data_catalog` now loads ALL available datasets with fresh value injection rather than filtering specific ones.
 -   **Fresh Value Injection**: Always use `value_search_terms` when specific values are mentioned to get the most current data.
@@ -134,7 +146,8 @@ Your sole output MUST be a call to **ONE** of these tools: `search_data_catalog`
 **Rules**
 -   **Value Extraction is Mandatory**: Always attempt to extract specific values from the user request.
 -   **Use `value_search_terms` When Applicable**: If specific values are extracted, *always* include them for fresh injection.
--   **Output = Tool Call**: Only output a single tool call.
+-   **Output = Tool Call**: Only output tool calls: at most one `search_terms` call, followed by a single `search_data_catalog` or `no_search_needed` call.
+-   **Glossary Definitions Win**: When `search_terms` returns a definition or SQL snippet, carry it into `specific_queries` and later analysis as written.
 -   **Default to Loading for Analysis**: If the request involves any data analysis, load fresh data.
 
 **Examples**
@@ -149,6 +162,13 @@ Your sole output MUST be a call to **ONE** of these tools: `search_data_catalog`
     -   Tool: `search_data_catalog`
     -   Params: `{"specific_queries": ["Find datasets showing sales trends for specific products in specific regions."], "value_search_terms": ["Red Bull", "California"]}`
 
+-   **Request with Business Terms**: User: "How many active customers did we have last quarter?"
+    -   *Reasoning*: "Active customer" is company jargon with a specific definition.
+    -   Tool: `search_terms`
+    -   Params: `{"queries": ["active customer"]}`
+    -   Then Tool: `search_data_catalog`
+    -   Params: `{"specific_queries": ["Find datasets with customer activity data to count active customers by quarter."]}`
+
 -   **Exploratory Request**: User: "Tell me about our customer churn patterns."
     -   *Reasoning*: Broad analytical request requiring data exploration.
     -   Tool: `search_data_catalog`
@@ -38,29 +38,45 @@ impl BatchTool {
 
 #[async_trait]
 impl ToolExecutor for BatchTool {
-    type Output = Vec<String>;
+    type Output = Vec<BatchToolResult>;
     type Params = BatchToolParams;
 
     fn get_name(&self) -> String {
         "BatchTool".to_string()
     }
 
-    async fn execute(&self, params: Self::Params, _tool_call_id: String) -> Result<Self::Output, anyhow::Error> {
-        let mut results = Vec::new();
-        
-        // This is a simplified implementation since we can't actually dispatch to other tools here
-        // In a real implementation we would execute each tool call in the batch
-        
-        for invocation in params.invocations {
-            results.push(format!("Tool '{}' would be executed with {} parameters", 
-                invocation.tool_name, 
-                match invocation.input {
-                    Value::Object(map) => map.len(),
-                    _ => 0
-                }
-            ));
+    async fn execute(&self, params: Self::Params, tool_call_id: String) -> Result<Self::Output, anyhow::Error> {
+        let mut results = Vec::with_capacity(params.invocations.len());
+
+        // Invocations run one after another through the agent's registered tools, so they
+        // go through the same checks (like confirmation prompts) as direct calls
+        for (index, invocation) in params.invocations.into_iter().enumerate() {
+            let outcome = if invocation.tool_name == self.get_name() {
+                Err(anyhow::anyhow!("BatchTool cannot invoke itself"))
+            } else {
+                self.agent
+                    .execute_tool(
+                        &invocation.tool_name,
+                        invocation.input,
+                        format!("{}_{}", tool_call_id, index),
+                    )
+                    .await
+            };
+
+            results.push(match outcome {
+                Ok(result) => BatchToolResult {
+                    tool_name: invocation.tool_name,
+                    result,
+                    error: None,
+                },
+                Err(e) => BatchToolResult {
+                    tool_name: invocation.tool_name,
+                    result: Value::Null,
+                    error: Some(e.to_string()),
+                },
+            });
         }
-        
+
         Ok(results)
     }
 
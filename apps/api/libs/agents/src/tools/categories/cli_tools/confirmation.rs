@@ -0,0 +1,109 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::Value;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::tools::ToolExecutor;
+
+/// The user's answer to a confirmation prompt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToolDecision {
+    Allow,
+    Deny,
+}
+
+/// Sent to the UI before a tool that changes the user's machine runs. The tool waits until
+/// the UI answers through `respond`.
+#[derive(Debug)]
+pub struct ToolConfirmationRequest {
+    pub tool_call_id: String,
+    pub tool_name: String,
+    /// What the call will do, e.g. the command or the file it writes
+    pub summary: String,
+    pub params: Value,
+    pub respond: oneshot::Sender<ToolDecision>,
+}
+
+pub type ToolConfirmationSender = mpsc::UnboundedSender<ToolConfirmationRequest>;
+
+/// Wraps a tool so every call is confirmed by the user before it runs. Declined calls
+/// return an error, which the agent sends back to the model as the tool's result.
+pub struct ConfirmedTool<T> {
+    inner: T,
+    confirmations: ToolConfirmationSender,
+}
+
+impl<T> ConfirmedTool<T> {
+    pub fn new(inner: T, confirmations: ToolConfirmationSender) -> Self {
+        Self {
+            inner,
+            confirmations,
+        }
+    }
+}
+
+#[async_trait]
+impl<T> ToolExecutor for ConfirmedTool<T>
+where
+    T: ToolExecutor<Params = Value, Output = Value> + Send + Sync,
+{
+    type Output = Value;
+    type Params = Value;
+
+    async fn execute(&self, params: Self::Params, tool_call_id: String) -> Result<Self::Output> {
+        let (respond, decision) = oneshot::channel();
+        self.confirmations
+            .send(ToolConfirmationRequest {
+                tool_call_id: tool_call_id.clone(),
+                tool_name: self.get_name(),
+                summary: summarize_params(&params),
+                params: params.clone(),
+                respond,
+            })
+            .map_err(|_| anyhow::anyhow!("No one is available to confirm this tool call"))?;
+
+        match decision.await {
+            Ok(ToolDecision::Allow) => self.inner.execute(params, tool_call_id).await,
+            Ok(ToolDecision::Deny) | Err(_) => Err(anyhow::anyhow!(
+                "The user declined to run {}. Ask them how they would like to proceed.",
+                self.get_name()
+            )),
+        }
+    }
+
+    async fn get_schema(&self) -> Value {
+        self.inner.get_schema().await
+    }
+
+    fn get_name(&self) -> String {
+        self.inner.get_name()
+    }
+}
+
+/// A one-line description of a call for the confirmation prompt
+fn summarize_params(params: &Value) -> String {
+    ["command", "file_path", "path"]
+        .iter()
+        .find_map(|key| params.get(key).and_then(Value::as_str))
+        .map(str::to_string)
+        .unwrap_or_else(|| params.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_summarize_params() {
+        assert_eq!(
+            summarize_params(&json!({"command": "dbt compile", "timeout": 1000})),
+            "dbt compile"
+        );
+        assert_eq!(
+            summarize_params(&json!({"file_path": "models/orders.yml", "content": "..."})),
+            "models/orders.yml"
+        );
+        assert_eq!(summarize_params(&json!({"a": 1})), r#"{"a":1}"#);
+    }
+}
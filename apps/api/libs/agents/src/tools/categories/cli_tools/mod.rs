@@ -1,4 +1,6 @@
 pub mod bash_tool;
+pub mod batch_tool;
+pub mod confirmation;
 pub mod glob_tool;
 pub mod grep_tool;
 pub mod ls_tool;
@@ -7,6 +9,8 @@ pub mod edit_file_tool; // Will be renamed to edit_tool
 pub mod write_file_tool; // Will be renamed to replace_tool
 
 pub use bash_tool::BashTool;
+pub use batch_tool::BatchTool;
+pub use confirmation::{ConfirmedTool, ToolConfirmationRequest, ToolConfirmationSender, ToolDecision};
 pub use glob_tool::GlobTool;
 pub use grep_tool::GrepTool;
 pub use ls_tool::LSTool;
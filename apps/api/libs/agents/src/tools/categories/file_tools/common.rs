@@ -9,7 +9,11 @@ use database::{
     types::{data_metadata::DataMetadata, DashboardYml, MetricYml, VersionHistory},
 };
 use indexmap::IndexMap;
-use query_engine::{data_source_query_routes::query_engine::query_engine, data_types::DataType};
+use query_engine::{
+    data_types::DataType,
+    metric_parameters::{bind_metric_sql, bind_metric_sql_for_data_source, ParameterOverrides},
+    query_cache::{cached_query_engine_for_user, QueryCacheOptions},
+};
 use serde_json::Value;
 use serde_yaml;
 use tracing::{debug, error, warn};
@@ -97,7 +101,15 @@ pub async fn validate_sql(
     }
 
     // Try to execute the query
-    let query_result = match query_engine(data_source_id, sql, Some(15)).await {
+    let query_result = match cached_query_engine_for_user(
+        user_id,
+        data_source_id,
+        sql,
+        Some(15),
+        QueryCacheOptions::default(),
+    )
+    .await
+    {
         Ok(result) => result,
         Err(e) => return Err(anyhow!("SQL validation failed: {}", e)),
     };
@@ -167,7 +179,11 @@ pub const METRIC_YML_SCHEMA: &str = r##"
 #   - NOTE: Remember to use fully qualified names: DATABASE_NAME.SCHEMA_NAME.TABLE_NAME for tables and table_alias.column for columns. This applies to all table and column references, including those within Common Table Expressions (CTEs) and when selecting from CTEs.
 #   - Example:
 #     sql: |
-#       SELECT ... 
+#       SELECT ...
+# `parameters`: [Optional] Typed inputs users can change without editing the SQL.
+#   - Each has `name`, `type` (string, number, boolean, date, date_range, multi_select), optional `label`, `options` (required for multi_select) and `default`.
+#   - RULE: Every parameter MUST have a `default`; the SQL is validated with the defaults.
+#   - Reference as `{{name}}` where a value goes, `IN ({{name}})` for multi_select, and `{{name.start}}` / `{{name.end}}` for date_range. Never put them inside quotes.
 # `chartConfig`: Visualization settings.
 #   - RULE: Must contain `selectedChartType` (bar, line, scatter, pie, combo, metric, table).
 #   - RULE: Must contain `columnLabelFormats` defining format for ALL columns in the SQL result.
@@ -795,6 +811,19 @@ pub const DASHBOARD_YML_SCHEMA: &str = r##"
 #       - 6
 #       - 6
 #
+# Optional fields:
+#
+# filters:                # Dashboard-wide filters, e.g. one date picker for every metric
+#   - id: period          # Letters, digits and underscores
+#     label: Period
+#     type: date_range    # date_range, select or number_range
+#     default: last_30_days
+#     targets:            # Each target names a result column OR a metric parameter
+#       - metricId: metric-uuid-1
+#         column: order_date
+#       - metricId: metric-uuid-2
+#         parameter: period
+#
 # Rules:
 # 1. Each row can have up to 4 items
 # 2. Each row must have a unique ID
@@ -888,9 +917,15 @@ pub async fn process_metric_file(
         return Err(format!("Invalid metric structure: {}", e));
     }
 
+    // Parameters are validated with their default values
+    let bound_sql = match bind_metric_sql(&metric_yml, &ParameterOverrides::new(), &data_source_dialect) {
+        Ok(sql) => sql,
+        Err(e) => return Err(format!("Invalid metric parameters: {}", e)),
+    };
+
     // Validate SQL and get results + validated dataset IDs
     let (message, results, metadata, validated_dataset_ids) =
-        match validate_sql(&metric_yml.sql, &data_source_id, &data_source_dialect, user_id).await {
+        match validate_sql(&bound_sql, &data_source_id, &data_source_dialect, user_id).await {
             Ok(results) => results,
             Err(e) => return Err(format!("Invalid SQL query: {}", e)),
         };
@@ -1609,9 +1644,14 @@ async fn process_metric_file_update(
     let mut results = Vec::new();
 
     // Check if SQL or metadata has changed
-    if file.content.sql != new_yml.sql {
+    if file.content.sql != new_yml.sql || file.content.parameters != new_yml.parameters {
         // SQL changed or metadata missing, perform validation
-        match validate_sql(&new_yml.sql, data_source_id, "sql", user_id).await {
+        let bound_sql =
+            bind_metric_sql_for_data_source(&new_yml, &ParameterOverrides::new(), data_source_id)
+                .await
+                .map_err(|e| anyhow!("Invalid metric parameters: {}", e))?;
+
+        match validate_sql(&bound_sql, data_source_id, "sql", user_id).await {
             Ok((message, validation_results, metadata, validated_ids)) => {
                 // Update file record
                 file.content = new_yml.clone();
@@ -26,6 +26,7 @@ use crate::{
         file_tools::{
             common::{process_metric_file, METRIC_YML_SCHEMA},
             file_types::file::FileWithId,
+            metric_evaluation::spawn_metric_evaluation,
         },
         ToolExecutor,
     },
@@ -231,6 +232,16 @@ impl ToolExecutor for CreateMetricFilesTool {
                     }
                 }
 
+                // Scored in the background so the agent doesn't wait on the judge
+                for record in &metric_records {
+                    spawn_metric_evaluation(
+                        record.id,
+                        record.content.clone(),
+                        data_source_syntax.clone(),
+                        user_id,
+                    );
+                }
+
                 let metric_ymls: Vec<MetricYml> = successful_processing
                     .iter()
                     .map(|(_, yml, _, _, _)| yml.clone())
@@ -0,0 +1,418 @@
+use std::env;
+
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use database::{
+    models::SqlEvaluation,
+    pool::get_pg_pool,
+    schema::{metric_files, sql_evaluations},
+    types::MetricYml,
+};
+use diesel::{insert_into, ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+use litellm::{AgentMessage, ChatCompletionRequest, LiteLLMClient, Metadata, ResponseFormat};
+use query_engine::metric_parameters::{bind_metric_sql, ParameterOverrides};
+use serde::{Deserialize, Serialize};
+use sql_analyzer::{analyze_query, check_query, types::TableKind, IssueSeverity, QueryIssue};
+use tracing::{debug, error, warn};
+use uuid::Uuid;
+
+/// Score penalty for each static check error
+const ERROR_PENALTY: f64 = 0.4;
+/// Score penalty for each static check warning
+const WARNING_PENALTY: f64 = 0.15;
+/// Weight of the LLM judge in the combined score
+const JUDGE_WEIGHT: f64 = 0.6;
+/// Highest combined score a metric with a static check error can get, so it always shows as "Low"
+const ERROR_SCORE_CAP: f64 = 0.45;
+
+/// The LLM judge's assessment of a metric
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct JudgeVerdict {
+    pub score: f64,
+    pub explanation: String,
+}
+
+/// The outcome of evaluating a metric, stored on its `metric_files` row
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricEvaluation {
+    pub score: f64,
+    pub summary: String,
+    pub tables: Vec<String>,
+    pub issues: Vec<QueryIssue>,
+    /// Why the static checks could not run, e.g. SQL the parser does not understand
+    pub static_check_error: Option<String>,
+    pub judge: Option<JudgeVerdict>,
+}
+
+/// The label the UI shows for an evaluation score
+pub fn score_label(score: f64) -> &'static str {
+    if score >= 0.8 {
+        "High"
+    } else if score >= 0.5 {
+        "Moderate"
+    } else {
+        "Low"
+    }
+}
+
+fn static_score(issues: &[QueryIssue]) -> f64 {
+    let penalty: f64 = issues
+        .iter()
+        .map(|issue| match issue.severity {
+            IssueSeverity::Error => ERROR_PENALTY,
+            IssueSeverity::Warning => WARNING_PENALTY,
+        })
+        .sum();
+    (1.0 - penalty).max(0.0)
+}
+
+fn combined_score(issues: &[QueryIssue], judge: Option<&JudgeVerdict>) -> f64 {
+    let static_score = static_score(issues);
+    let score = match judge {
+        Some(judge) => (1.0 - JUDGE_WEIGHT) * static_score + JUDGE_WEIGHT * judge.score,
+        None => static_score,
+    };
+    let score = if issues.iter().any(|i| i.severity == IssueSeverity::Error) {
+        score.min(ERROR_SCORE_CAP)
+    } else {
+        score
+    };
+    score.clamp(0.0, 1.0)
+}
+
+fn evaluation_summary(
+    issues: &[QueryIssue],
+    static_check_error: Option<&str>,
+    judge: Option<&JudgeVerdict>,
+) -> String {
+    let mut lines = Vec::new();
+    if let Some(judge) = judge {
+        lines.push(judge.explanation.trim().to_string());
+    }
+    match static_check_error {
+        Some(e) => lines.push(format!("Static checks could not run: {}", e)),
+        None if issues.is_empty() => lines.push("Static checks found no issues.".to_string()),
+        None => {
+            lines.push("Static checks:".to_string());
+            for issue in issues {
+                let severity = match issue.severity {
+                    IssueSeverity::Error => "error",
+                    IssueSeverity::Warning => "warning",
+                };
+                lines.push(format!("- {}: {}", severity, issue.message));
+            }
+        }
+    }
+    lines.join("\n")
+}
+
+/// Reads `{"score": 0-1, "explanation": "..."}`, also accepting a 0-100 or 1-10 score
+fn parse_judge_verdict(content: &str) -> Result<JudgeVerdict> {
+    #[derive(Deserialize)]
+    struct RawVerdict {
+        score: f64,
+        explanation: String,
+    }
+
+    let raw: RawVerdict = serde_json::from_str(content.trim())
+        .map_err(|e| anyhow!("Failed to parse judge verdict: {}", e))?;
+    let score = if raw.score > 10.0 {
+        raw.score / 100.0
+    } else if raw.score > 1.0 {
+        raw.score / 10.0
+    } else {
+        raw.score
+    };
+    Ok(JudgeVerdict {
+        score: score.clamp(0.0, 1.0),
+        explanation: raw.explanation,
+    })
+}
+
+const JUDGE_PROMPT: &str = r#"You review SQL written for business metrics. Judge whether the SQL correctly computes what the metric's name and description promise.
+
+Consider:
+- Does the SQL measure what the name and description say, over the stated time frame?
+- Are aggregations, filters, joins and grouping correct for that question?
+- Could joins repeat rows and inflate sums or counts?
+- Are NULLs, duplicates and date boundaries handled sensibly?
+
+Metric name: {NAME}
+Description: {DESCRIPTION}
+Time frame: {TIME_FRAME}
+Dialect: {DIALECT}
+
+SQL:
+```sql
+{SQL}
+```
+
+Static analysis findings:
+{ISSUES}
+
+Return ONLY a JSON object: {"score": <number between 0 and 1>, "explanation": "<two or three sentences a reviewer can act on>"}
+A score of 1 means the SQL is clearly correct; below 0.5 means it likely returns wrong numbers."#;
+
+async fn judge_metric(
+    metric_yml: &MetricYml,
+    sql: &str,
+    data_source_dialect: &str,
+    issues: &[QueryIssue],
+    user_id: &Uuid,
+) -> Result<JudgeVerdict> {
+    let issues_text = if issues.is_empty() {
+        "None".to_string()
+    } else {
+        issues
+            .iter()
+            .map(|issue| format!("- {}", issue.message))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let prompt = JUDGE_PROMPT
+        .replace("{NAME}", &metric_yml.name)
+        .replace(
+            "{DESCRIPTION}",
+            metric_yml.description.as_deref().unwrap_or("(none)"),
+        )
+        .replace("{TIME_FRAME}", &metric_yml.time_frame)
+        .replace("{DIALECT}", data_source_dialect)
+        .replace("{SQL}", sql)
+        .replace("{ISSUES}", &issues_text);
+
+    let model = if env::var("ENVIRONMENT").unwrap_or_else(|_| "development".to_string()) == "local"
+    {
+        "gpt-4.1-nano".to_string()
+    } else {
+        "gemini-2.0-flash-001".to_string()
+    };
+
+    let request = ChatCompletionRequest {
+        model,
+        messages: vec![AgentMessage::User {
+            id: None,
+            content: prompt,
+            name: None,
+        }],
+        stream: Some(false),
+        response_format: Some(ResponseFormat {
+            type_: "json_object".to_string(),
+            json_schema: None,
+        }),
+        store: Some(true),
+        metadata: Some(Metadata {
+            generation_name: "metric_evaluation".to_string(),
+            user_id: user_id.to_string(),
+            session_id: Uuid::new_v4().to_string(),
+            trace_id: Uuid::new_v4().to_string(),
+        }),
+        max_completion_tokens: Some(512),
+        temperature: Some(0.0),
+        ..Default::default()
+    };
+
+    let response = LiteLLMClient::new(None, None)
+        .chat_completion(request)
+        .await?;
+
+    let content = response
+        .choices
+        .first()
+        .and_then(|c| c.message.get_content())
+        .ok_or_else(|| anyhow!("LLM response for metric evaluation was empty"))?;
+
+    parse_judge_verdict(&content)
+}
+
+/// Evaluates a metric's SQL with static checks and an LLM judge.
+///
+/// The SQL is checked as it runs with the parameters' default values. If the judge
+/// is unavailable the score comes from the static checks alone.
+pub async fn evaluate_metric(
+    metric_yml: &MetricYml,
+    data_source_dialect: &str,
+    user_id: &Uuid,
+) -> Result<MetricEvaluation> {
+    let sql = bind_metric_sql(metric_yml, &ParameterOverrides::new(), data_source_dialect)?;
+
+    let tables = match analyze_query(sql.clone(), data_source_dialect).await {
+        Ok(summary) => summary
+            .tables
+            .into_iter()
+            .filter(|t| t.kind == TableKind::Base)
+            .map(|t| t.table_identifier)
+            .collect(),
+        Err(e) => {
+            debug!("Could not analyze metric SQL for evaluation: {}", e);
+            Vec::new()
+        }
+    };
+
+    let (issues, static_check_error) = match check_query(&sql, data_source_dialect) {
+        Ok(issues) => (issues, None),
+        Err(e) => (Vec::new(), Some(e.to_string())),
+    };
+
+    let judge = match judge_metric(metric_yml, &sql, data_source_dialect, &issues, user_id).await {
+        Ok(verdict) => Some(verdict),
+        Err(e) => {
+            warn!(
+                "Metric evaluation judge failed, using static checks only: {}",
+                e
+            );
+            None
+        }
+    };
+
+    Ok(MetricEvaluation {
+        score: combined_score(&issues, judge.as_ref()),
+        summary: evaluation_summary(&issues, static_check_error.as_deref(), judge.as_ref()),
+        tables,
+        issues,
+        static_check_error,
+        judge,
+    })
+}
+
+/// Stores an evaluation on the metric and records it in `sql_evaluations`.
+///
+/// Nothing is written if the metric's content changed since it was evaluated,
+/// so a slow evaluation never overwrites the result for a newer version.
+pub async fn store_metric_evaluation(
+    metric_file_id: &Uuid,
+    metric_yml: &MetricYml,
+    evaluation: &MetricEvaluation,
+) -> Result<()> {
+    let mut conn = get_pg_pool().get().await?;
+    let evaluation_obj = serde_json::to_value(evaluation)?;
+
+    let updated = diesel::update(metric_files::table)
+        .filter(metric_files::id.eq(metric_file_id))
+        .filter(metric_files::content.eq(metric_yml.clone()))
+        .filter(metric_files::deleted_at.is_null())
+        .set((
+            metric_files::evaluation_obj.eq(Some(evaluation_obj.clone())),
+            metric_files::evaluation_summary.eq(Some(evaluation.summary.clone())),
+            metric_files::evaluation_score.eq(Some(evaluation.score)),
+        ))
+        .execute(&mut conn)
+        .await?;
+
+    if updated == 0 {
+        debug!(%metric_file_id, "Metric changed during evaluation, discarding result");
+        return Ok(());
+    }
+
+    let now = Utc::now();
+    let mut history_obj = evaluation_obj;
+    history_obj["metric_file_id"] = serde_json::json!(metric_file_id);
+
+    insert_into(sql_evaluations::table)
+        .values(SqlEvaluation {
+            id: Uuid::new_v4(),
+            evaluation_obj: history_obj,
+            evaluation_summary: evaluation.summary.clone(),
+            score: score_label(evaluation.score).to_string(),
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+        })
+        .execute(&mut conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Evaluates and stores a metric in the background, logging failures
+pub fn spawn_metric_evaluation(
+    metric_file_id: Uuid,
+    metric_yml: MetricYml,
+    data_source_dialect: String,
+    user_id: Uuid,
+) {
+    tokio::spawn(async move {
+        let result = async {
+            let evaluation = evaluate_metric(&metric_yml, &data_source_dialect, &user_id).await?;
+            store_metric_evaluation(&metric_file_id, &metric_yml, &evaluation).await
+        }
+        .await;
+
+        if let Err(e) = result {
+            error!(%metric_file_id, "Failed to evaluate metric: {}", e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sql_analyzer::QueryIssueKind;
+
+    fn issue(severity: IssueSeverity) -> QueryIssue {
+        QueryIssue {
+            kind: QueryIssueKind::FanOutJoin,
+            severity,
+            message: "SUM is computed across a join".to_string(),
+        }
+    }
+
+    fn verdict(score: f64) -> JudgeVerdict {
+        JudgeVerdict {
+            score,
+            explanation: "Looks right.".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_combined_score() {
+        assert_eq!(combined_score(&[], None), 1.0);
+        assert!((combined_score(&[], Some(&verdict(0.5))) - 0.7).abs() < 1e-9);
+        assert!((combined_score(&[issue(IssueSeverity::Warning)], None) - 0.85).abs() < 1e-9);
+
+        // An error keeps the metric flagged however confident the judge is
+        let with_error = combined_score(&[issue(IssueSeverity::Error)], Some(&verdict(1.0)));
+        assert_eq!(score_label(with_error), "Low");
+
+        let many = vec![issue(IssueSeverity::Error); 5];
+        assert_eq!(combined_score(&many, None), 0.0);
+    }
+
+    #[test]
+    fn test_parse_judge_verdict() {
+        let verdict =
+            parse_judge_verdict(r#"{"score": 0.9, "explanation": "Correct grouping."}"#).unwrap();
+        assert_eq!(verdict.score, 0.9);
+        assert_eq!(verdict.explanation, "Correct grouping.");
+
+        assert_eq!(
+            parse_judge_verdict(r#"{"score": 85, "explanation": "x"}"#)
+                .unwrap()
+                .score,
+            0.85
+        );
+        assert_eq!(
+            parse_judge_verdict(r#"{"score": 7, "explanation": "x"}"#)
+                .unwrap()
+                .score,
+            0.7
+        );
+        assert!(parse_judge_verdict("not json").is_err());
+    }
+
+    #[test]
+    fn test_evaluation_summary() {
+        let summary =
+            evaluation_summary(&[issue(IssueSeverity::Warning)], None, Some(&verdict(0.8)));
+        assert_eq!(
+            summary,
+            "Looks right.\nStatic checks:\n- warning: SUM is computed across a join"
+        );
+
+        assert_eq!(
+            evaluation_summary(&[], Some("SQL parsing failed"), None),
+            "Static checks could not run: SQL parsing failed"
+        );
+    }
+}
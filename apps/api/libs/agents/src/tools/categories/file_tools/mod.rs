@@ -2,10 +2,12 @@ pub mod common;
 pub mod create_dashboards;
 pub mod create_metrics;
 pub mod file_types;
+pub mod metric_evaluation;
 // pub mod filter_dashboards;
 pub mod modify_dashboards;
 pub mod modify_metrics;
 pub mod search_data_catalog;
+pub mod search_terms;
 
 pub use create_dashboards::CreateDashboardFilesTool;
 pub use create_metrics::CreateMetricFilesTool;
@@ -13,6 +15,7 @@ pub use create_metrics::CreateMetricFilesTool;
 pub use modify_dashboards::ModifyDashboardFilesTool;
 pub use modify_metrics::ModifyMetricFilesTool;
 pub use search_data_catalog::SearchDataCatalogTool;
+pub use search_terms::SearchTermsTool;
 
 use crate::tools::ToolExecutor;
 
@@ -13,7 +13,11 @@ use diesel::{upsert::excluded, ExpressionMethods, QueryDsl};
 use diesel_async::RunQueryDsl;
 use futures::future::join_all;
 use indexmap::IndexMap;
-use query_engine::{data_source_query_routes::query_engine::query_engine, data_types::DataType};
+use query_engine::{
+    data_source_query_routes::query_engine::query_engine,
+    data_types::DataType,
+    metric_parameters::{bind_metric_sql, ParameterOverrides},
+};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use tracing::{debug, error, info};
@@ -26,6 +30,7 @@ use super::{
         validate_sql, ModificationResult, ModifyFilesOutput, FailedFileModification,
     },
     file_types::file::FileWithId,
+    metric_evaluation::spawn_metric_evaluation,
     FileModificationTool,
 };
 use crate::{
@@ -102,7 +107,8 @@ async fn process_metric_file_update(
             );
 
             // Check if SQL has changed to avoid unnecessary validation
-            let sql_changed = file.content.sql != new_yml.sql;
+            let sql_changed = file.content.sql != new_yml.sql
+                || file.content.parameters != new_yml.parameters;
             
             // If SQL hasn't changed, we can use existing metadata and skip validation
             if !sql_changed && file.data_metadata.is_some() {
@@ -154,7 +160,11 @@ async fn process_metric_file_update(
                 );
             }
 
-            match validate_sql(&new_yml.sql, &data_source_id, &data_source_dialect, user_id).await {
+            // Parameters are validated with their default values
+            let bound_sql = bind_metric_sql(&new_yml, &ParameterOverrides::new(), &data_source_dialect)
+                .map_err(|e| anyhow!("Invalid metric parameters: {}", e))?;
+
+            match validate_sql(&bound_sql, &data_source_id, &data_source_dialect, user_id).await {
                 Ok((message, validation_results, metadata, validated_dataset_ids)) => {
                     // Update file record
                     file.content = new_yml.clone();
@@ -421,6 +431,16 @@ impl ToolExecutor for ModifyMetricFilesTool {
                    }
                     // --- End Insert --- 
 
+                    // Re-score the new versions in the background
+                    let user_id = self.agent.get_user_id();
+                    for metric_file in &batch.files {
+                        spawn_metric_evaluation(
+                            metric_file.id,
+                            metric_file.content.clone(),
+                            data_source_dialect.clone(),
+                            user_id,
+                        );
+                    }
                 }
                 Err(e) => {
                     error!("Failed to update metric files in database: {}", e);
@@ -12,7 +12,7 @@ use database::{
 use diesel::prelude::*;
 use diesel_async::RunQueryDsl;
 use futures::stream::{self, StreamExt};
-use litellm::{AgentMessage, ChatCompletionRequest, EmbeddingRequest, LiteLLMClient, Metadata, ResponseFormat};
+use litellm::{AgentMessage, ChatCompletionRequest, Metadata, ResponseFormat};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use tracing::{debug, error, info, warn};
@@ -69,25 +69,14 @@ struct SearchableDimension {
 
 // NEW: Helper function to generate embeddings for search terms
 async fn generate_embedding_for_text(text: &str) -> Result<Vec<f32>> {
-    let litellm_client = LiteLLMClient::new(None, None);
+    let embedder = stored_values::get_embedder()?;
     
-    let embedding_request = EmbeddingRequest {
-        model: "text-embedding-3-small".to_string(),
-        input: vec![text.to_string()], // Single input as a vector
-        dimensions: Some(1536),
-        encoding_format: Some("float".to_string()),
-        user: None,
-    };
-    
-    let embedding_response = litellm_client
-        .generate_embeddings(embedding_request)
-        .await?;
-    
-    if embedding_response.data.is_empty() {
-        return Err(anyhow::anyhow!("No embeddings returned from API"));
-    }
+    let embeddings = embedder.embed(&[text.to_string()]).await?;
     
-    Ok(embedding_response.data[0].embedding.clone())
+    embeddings
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("No embeddings returned from embedder"))
 }
 
 // Helper function to identify time-based terms that might cause issues
@@ -261,7 +250,7 @@ impl ToolExecutor for SearchDataCatalogTool {
             }
         });
 
-        // --- VALUE SEARCH (keep the embedding-based search and injection) ---
+        // --- VALUE SEARCH (hybrid lexical + embedding search and injection) ---
         
         // Extract value search terms
         let value_search_terms = params.value_search_terms.clone().unwrap_or_default();
@@ -292,30 +281,31 @@ impl ToolExecutor for SearchDataCatalogTool {
 
         debug!(count = term_embeddings.len(), "Generated embeddings for value search terms via batch");
 
-        // Begin value searches concurrently using pre-generated embeddings
+        // Begin hybrid value searches concurrently. Terms without an embedding still get
+        // lexical matches, which is what finds exact codes like SKUs or ISO country codes.
         let mut value_search_futures = Vec::new();
-        if !term_embeddings.is_empty() {
-            for (term, embedding) in term_embeddings.iter() {
-                let term_clone = term.clone();
-                let embedding_clone = embedding.clone();
-                let data_source_id_clone = target_data_source_id;
-
-                let future = tokio::spawn(async move {
-                    let results = stored_values::search::search_values_by_embedding(
-                        data_source_id_clone,
-                        &embedding_clone,
-                        20, // Limit to 20 values per term
-                    ).await;
-                    
-                    (term_clone, results)
-                });
+        for term in valid_value_search_terms.iter() {
+            let term_clone = term.clone();
+            let embedding_clone = term_embeddings.get(term).cloned();
+            let data_source_id_clone = target_data_source_id;
+
+            let future = tokio::spawn(async move {
+                let results = stored_values::search::search_values_hybrid(
+                    data_source_id_clone,
+                    &term_clone,
+                    embedding_clone.as_deref(),
+                    &[],
+                    20, // Limit to 20 values per term
+                ).await;
                 
-                value_search_futures.push(future);
-            }
+                (term_clone, results)
+            });
+            
+            value_search_futures.push(future);
         }
         
         // Await value searches to complete
-        let value_search_results_vec: Vec<(String, Result<Vec<stored_values::search::StoredValueResult>>)> = 
+        let value_search_results_vec: Vec<(String, Result<Vec<stored_values::search::ScoredValueResult>>)> = 
             futures::future::join_all(value_search_futures)
                 .await
                 .into_iter()
@@ -329,7 +319,7 @@ impl ToolExecutor for SearchDataCatalogTool {
                 Ok(values) => {
                     let found_values: Vec<FoundValueInfo> = values.into_iter()
                         .map(|val| {
-                            to_found_value_info(val, 0.0)
+                            to_found_value_info(val.value, val.score)
                         })
                         .collect();
                     
@@ -492,40 +482,33 @@ async fn get_search_data_catalog_description() -> String {
 }
 
 // NEW: Helper function to generate embeddings for multiple texts in a batch
+// Uses the same embedder as the stored values so query and value vectors are comparable
 async fn generate_embeddings_batch(texts: Vec<String>) -> Result<Vec<(String, Vec<f32>)>> {
     if texts.is_empty() {
         return Ok(vec![]);
     }
     
-    let litellm_client = LiteLLMClient::new(None, None);
-    
-    let embedding_request = EmbeddingRequest {
-        model: "text-embedding-3-small".to_string(),
-        input: texts.clone(),
-        dimensions: Some(1536),
-        encoding_format: Some("float".to_string()),
-        user: None,
-    };
+    let embedder = stored_values::get_embedder()?;
     
     debug!(count = texts.len(), "Generating embeddings in batch");
     
-    let embedding_response = litellm_client
-        .generate_embeddings(embedding_request)
+    let embeddings = embedder
+        .embed(&texts)
         .await
         .context("Failed to generate embeddings batch")?;
         
-    if embedding_response.data.len() != texts.len() {
+    if embeddings.len() != texts.len() {
         warn!(
             "Mismatch between input text count ({}) and returned embedding count ({})",
             texts.len(),
-            embedding_response.data.len()
+            embeddings.len()
         );
     }
 
     let mut results = Vec::with_capacity(texts.len());
     for (index, text) in texts.into_iter().enumerate() {
-        if let Some(embedding_data) = embedding_response.data.get(index) {
-            results.push((text, embedding_data.embedding.clone()));
+        if let Some(embedding) = embeddings.get(index) {
+            results.push((text, embedding.clone()));
         } else {
             error!(term = %text, index = index, "Could not find corresponding embedding in batch response");
         }
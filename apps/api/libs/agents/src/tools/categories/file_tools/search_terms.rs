@@ -0,0 +1,198 @@
+use std::{sync::Arc, time::Instant};
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use database::helpers::{organization::get_user_organization_id, terms::search_terms};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use stored_values::get_embedder;
+use tracing::{debug, error, warn};
+use uuid::Uuid;
+
+use crate::{agent::Agent, tools::ToolExecutor};
+
+/// Terms returned per query
+const TERMS_PER_QUERY: i64 = 5;
+/// Upper bound on queries resolved in one call
+const MAX_QUERIES: usize = 10;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchTermsParams {
+    queries: Vec<String>,
+}
+
+/// An approved glossary definition
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GlossaryTerm {
+    pub id: Uuid,
+    pub name: String,
+    pub definition: Option<String>,
+    pub sql_snippet: Option<String>,
+    pub datasets: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TermQueryResult {
+    pub query: String,
+    pub terms: Vec<GlossaryTerm>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchTermsOutput {
+    pub message: String,
+    pub duration: i64,
+    pub results: Vec<TermQueryResult>,
+}
+
+pub struct SearchTermsTool {
+    agent: Arc<Agent>,
+}
+
+impl SearchTermsTool {
+    pub fn new(agent: Arc<Agent>) -> Self {
+        Self { agent }
+    }
+}
+
+/// Trims, drops blanks and case-insensitive duplicates, and caps the number of queries
+fn normalize_queries(queries: Vec<String>) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for query in queries {
+        let query = query.trim().to_string();
+        if query.is_empty() || normalized.iter().any(|q| q.eq_ignore_ascii_case(&query)) {
+            continue;
+        }
+        normalized.push(query);
+        if normalized.len() == MAX_QUERIES {
+            break;
+        }
+    }
+    normalized
+}
+
+#[async_trait]
+impl ToolExecutor for SearchTermsTool {
+    type Output = SearchTermsOutput;
+    type Params = SearchTermsParams;
+
+    async fn execute(&self, params: Self::Params, _tool_call_id: String) -> Result<Self::Output> {
+        let start_time = Instant::now();
+        let user_id = self.agent.get_user_id();
+
+        let queries = normalize_queries(params.queries);
+        if queries.is_empty() {
+            return Ok(SearchTermsOutput {
+                message: "No search queries provided.".to_string(),
+                duration: start_time.elapsed().as_millis() as i64,
+                results: vec![],
+            });
+        }
+
+        let organization_id = get_user_organization_id(&user_id)
+            .await?
+            .ok_or_else(|| anyhow!("User {} is not a member of any organization", user_id))?;
+
+        // Full-text matching still works without embeddings, so a failure only narrows recall
+        let embeddings = match get_embedder() {
+            Ok(embedder) => embedder.embed(&queries).await,
+            Err(e) => Err(e),
+        };
+        let embeddings: Vec<Option<Vec<f32>>> = match embeddings {
+            Ok(embeddings) => embeddings.into_iter().map(Some).collect(),
+            Err(e) => {
+                warn!(error = %e, "Falling back to full-text term search");
+                vec![None; queries.len()]
+            }
+        };
+
+        let mut results = Vec::with_capacity(queries.len());
+        for (query, embedding) in queries.into_iter().zip(embeddings) {
+            let terms = match search_terms(
+                &organization_id,
+                &query,
+                embedding.as_deref(),
+                TERMS_PER_QUERY,
+            )
+            .await
+            {
+                Ok(found) => found
+                    .into_iter()
+                    .map(|term| GlossaryTerm {
+                        id: term.id,
+                        name: term.name,
+                        definition: term.definition,
+                        sql_snippet: term.sql_snippet,
+                        datasets: term.dataset_names,
+                    })
+                    .collect(),
+                Err(e) => {
+                    error!(query = %query, error = %e, "Failed to search terms");
+                    vec![]
+                }
+            };
+            debug!(query = %query, count = terms.len(), "Resolved glossary terms");
+            results.push(TermQueryResult { query, terms });
+        }
+
+        let found_count: usize = results.iter().map(|r| r.terms.len()).sum();
+        let message = if found_count == 0 {
+            "No glossary terms matched. Define these concepts from the dataset documentation instead.".to_string()
+        } else {
+            format!(
+                "Found {} glossary terms. Use the approved definitions and SQL snippets as written.",
+                found_count
+            )
+        };
+
+        Ok(SearchTermsOutput {
+            message,
+            duration: start_time.elapsed().as_millis() as i64,
+            results,
+        })
+    }
+
+    fn get_name(&self) -> String {
+        "search_terms".to_string()
+    }
+
+    async fn get_schema(&self) -> Value {
+        serde_json::json!({
+          "name": "search_terms",
+          "description": "Looks up the organization's business glossary. Returns approved definitions and SQL snippets for company-specific terms (e.g., 'active customer', 'net revenue', 'churned account') so they are calculated the way the business defines them rather than guessed.",
+          "parameters": {
+            "type": "object",
+            "required": ["queries"],
+            "properties": {
+              "queries": {
+                "type": "array",
+                "description": "Business terms, metrics or jargon from the user request whose exact definition matters, e.g., 'active customer', 'ARR', 'qualified lead'. Do not include specific values like names or places.",
+                "items": {
+                  "type": "string",
+                  "description": "A single business term or short phrase."
+                }
+              }
+            },
+            "additionalProperties": false
+          }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_queries() {
+        let queries = normalize_queries(vec![
+            " Active customer ".to_string(),
+            "".to_string(),
+            "active CUSTOMER".to_string(),
+            "Net revenue".to_string(),
+        ]);
+        assert_eq!(queries, vec!["Active customer", "Net revenue"]);
+
+        let many = normalize_queries((0..20).map(|i| format!("term {}", i)).collect());
+        assert_eq!(many.len(), MAX_QUERIES);
+    }
+}
@@ -498,6 +498,8 @@ pub enum DataSourceType {
     Snowflake,
     SqlServer,
     Supabase,
+    DuckDb,
+    ClickHouse,
 }
 
 impl DataSourceType {
@@ -512,6 +514,8 @@ impl DataSourceType {
             "snowflake" => Some(DataSourceType::Snowflake),
             "sqlserver" => Some(DataSourceType::SqlServer),
             "supabase" => Some(DataSourceType::Supabase),
+            "duckdb" => Some(DataSourceType::DuckDb),
+            "clickhouse" => Some(DataSourceType::ClickHouse),
             _ => None,
         }
     }
@@ -527,6 +531,8 @@ impl DataSourceType {
             DataSourceType::Snowflake => "snowflake",
             DataSourceType::SqlServer => "sqlserver",
             DataSourceType::Supabase => "supabase",
+            DataSourceType::DuckDb => "duckdb",
+            DataSourceType::ClickHouse => "clickhouse",
         }
     }
 
@@ -541,6 +547,8 @@ impl DataSourceType {
             DataSourceType::Snowflake => "snowflake",
             DataSourceType::SqlServer => "sqlserver",
             DataSourceType::Supabase => "supabase",
+            DataSourceType::DuckDb => "duckdb",
+            DataSourceType::ClickHouse => "clickhouse",
         })
     }
 }
@@ -565,6 +573,8 @@ impl ToSql<Text, Pg> for DataSourceType {
             DataSourceType::Snowflake => out.write_all(b"snowflake")?,
             DataSourceType::SqlServer => out.write_all(b"sqlserver")?,
             DataSourceType::Supabase => out.write_all(b"supabase")?,
+            DataSourceType::DuckDb => out.write_all(b"duckdb")?,
+            DataSourceType::ClickHouse => out.write_all(b"clickhouse")?,
         }
         Ok(IsNull::No)
     }
@@ -582,6 +592,106 @@ impl FromSql<Text, Pg> for DataSourceType {
             b"snowflake" => Ok(DataSourceType::Snowflake),
             b"sqlserver" => Ok(DataSourceType::SqlServer),
             b"supabase" => Ok(DataSourceType::Supabase),
+            b"duckdb" => Ok(DataSourceType::DuckDb),
+            b"clickhouse" => Ok(DataSourceType::ClickHouse),
+            _ => Err("Unrecognized enum variant".into()),
+        }
+    }
+}
+
+#[derive(
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    diesel::AsExpression,
+    diesel::FromSqlRow,
+)]
+#[diesel(sql_type = Text)]
+#[serde(rename_all = "lowercase")]
+pub enum ColumnMaskPolicy {
+    // Ordered from least to most restrictive
+    Partial,
+    Hash,
+    Redact,
+    Hide,
+}
+
+impl ToSql<Text, Pg> for ColumnMaskPolicy {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        match *self {
+            ColumnMaskPolicy::Partial => out.write_all(b"partial")?,
+            ColumnMaskPolicy::Hash => out.write_all(b"hash")?,
+            ColumnMaskPolicy::Redact => out.write_all(b"redact")?,
+            ColumnMaskPolicy::Hide => out.write_all(b"hide")?,
+        }
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<Text, Pg> for ColumnMaskPolicy {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        match bytes.as_bytes() {
+            b"partial" => Ok(ColumnMaskPolicy::Partial),
+            b"hash" => Ok(ColumnMaskPolicy::Hash),
+            b"redact" => Ok(ColumnMaskPolicy::Redact),
+            b"hide" => Ok(ColumnMaskPolicy::Hide),
+            _ => Err("Unrecognized enum variant".into()),
+        }
+    }
+}
+
+#[derive(
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    diesel::AsExpression,
+    diesel::FromSqlRow,
+)]
+#[diesel(sql_type = Text)]
+#[serde(rename_all = "snake_case")]
+pub enum CatalogDriftKind {
+    NewTable,
+    DroppedTable,
+    NewColumn,
+    DroppedColumn,
+    TypeChanged,
+    NullabilityChanged,
+}
+
+impl ToSql<Text, Pg> for CatalogDriftKind {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        match *self {
+            CatalogDriftKind::NewTable => out.write_all(b"new_table")?,
+            CatalogDriftKind::DroppedTable => out.write_all(b"dropped_table")?,
+            CatalogDriftKind::NewColumn => out.write_all(b"new_column")?,
+            CatalogDriftKind::DroppedColumn => out.write_all(b"dropped_column")?,
+            CatalogDriftKind::TypeChanged => out.write_all(b"type_changed")?,
+            CatalogDriftKind::NullabilityChanged => out.write_all(b"nullability_changed")?,
+        }
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<Text, Pg> for CatalogDriftKind {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        match bytes.as_bytes() {
+            b"new_table" => Ok(CatalogDriftKind::NewTable),
+            b"dropped_table" => Ok(CatalogDriftKind::DroppedTable),
+            b"new_column" => Ok(CatalogDriftKind::NewColumn),
+            b"dropped_column" => Ok(CatalogDriftKind::DroppedColumn),
+            b"type_changed" => Ok(CatalogDriftKind::TypeChanged),
+            b"nullability_changed" => Ok(CatalogDriftKind::NullabilityChanged),
             _ => Err("Unrecognized enum variant".into()),
         }
     }
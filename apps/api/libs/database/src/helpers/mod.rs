@@ -5,4 +5,5 @@ pub mod report_files;
 pub mod chats;
 pub mod organization;
 pub mod test_utils;
-pub mod datasets;
\ No newline at end of file
+pub mod datasets;
+pub mod terms;
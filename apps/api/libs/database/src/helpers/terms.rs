@@ -0,0 +1,311 @@
+use anyhow::{Context, Result};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::{models::Term, pool::get_sqlx_pool};
+
+/// Rank offset used when fusing the full-text and embedding rankings
+const RRF_K: i64 = 60;
+
+/// A glossary term matched by [`search_terms`]
+#[derive(FromRow, Debug, Clone)]
+pub struct TermSearchResult {
+    pub id: Uuid,
+    pub name: String,
+    pub definition: Option<String>,
+    pub sql_snippet: Option<String>,
+    pub dataset_names: Vec<String>,
+    pub score: f64,
+}
+
+/// Text a term is indexed and embedded under
+pub fn term_search_content(term: &Term) -> String {
+    match term.definition.as_deref().map(str::trim) {
+        Some(definition) if !definition.is_empty() => format!("{}: {}", term.name, definition),
+        _ => term.name.clone(),
+    }
+}
+
+fn vector_literal(embedding: &[f32]) -> String {
+    format!(
+        "'[{}]'::halfvec",
+        embedding
+            .iter()
+            .map(|f| f.to_string())
+            .collect::<Vec<String>>()
+            .join(",")
+    )
+}
+
+/// Writes the search row for a term, replacing any previous one.
+///
+/// `embedding` may be `None` when it could not be generated; the term is then
+/// only reachable through full-text search until it is saved again.
+pub async fn upsert_term_search(term: &Term, embedding: Option<&[f32]>) -> Result<()> {
+    let embedding_sql = match embedding {
+        Some(embedding) if !embedding.is_empty() => vector_literal(embedding),
+        _ => "NULL".to_string(),
+    };
+
+    let query_sql = format!(
+        r#"
+        INSERT INTO terms_search (term_id, content, definition, embedding, organization_id)
+        VALUES ($1, $2, $3, {embedding_sql}, $4)
+        ON CONFLICT (term_id) DO UPDATE SET
+            content = EXCLUDED.content,
+            definition = EXCLUDED.definition,
+            embedding = EXCLUDED.embedding,
+            updated_at = now(),
+            deleted_at = NULL
+        "#
+    );
+
+    let mut conn = get_sqlx_pool().acquire().await?;
+
+    sqlx::query(&query_sql)
+        .bind(term.id)
+        .bind(term_search_content(term))
+        .bind(term.definition.clone().unwrap_or_default())
+        .bind(term.organization_id)
+        .execute(&mut *conn)
+        .await
+        .context("Failed to upsert term search row")?;
+
+    Ok(())
+}
+
+/// Soft deletes the search row of a term
+pub async fn delete_term_search(term_id: &Uuid) -> Result<()> {
+    let mut conn = get_sqlx_pool().acquire().await?;
+
+    sqlx::query("UPDATE terms_search SET deleted_at = now() WHERE term_id = $1")
+        .bind(term_id)
+        .execute(&mut *conn)
+        .await
+        .context("Failed to delete term search row")?;
+
+    Ok(())
+}
+
+/// Sizes the embedding column for the embedder `model_id` and discards every term vector
+/// when they came from a different model. Returns whether the vectors were discarded.
+pub async fn reset_term_search_embeddings(model_id: &str, dimensions: usize) -> Result<bool> {
+    let mut tx = get_sqlx_pool().begin().await?;
+
+    // Locks the row so concurrent servers migrate one at a time
+    let recorded_model: String =
+        sqlx::query_scalar("SELECT embedding_model FROM terms_search_embedding_config FOR UPDATE")
+            .fetch_one(&mut *tx)
+            .await
+            .context("Failed to read term search embedding model")?;
+
+    if recorded_model == model_id {
+        return Ok(false);
+    }
+
+    // Vectors from different models can't be compared, so all of them are discarded
+    sqlx::query("DROP INDEX IF EXISTS terms_search_embedding_idx")
+        .execute(&mut *tx)
+        .await
+        .context("Failed to drop term search embedding index")?;
+    sqlx::query(&format!(
+        "ALTER TABLE terms_search ALTER COLUMN embedding TYPE halfvec({}) USING NULL",
+        dimensions
+    ))
+    .execute(&mut *tx)
+    .await
+    .context("Failed to resize term search embedding column")?;
+    sqlx::query(
+        "UPDATE terms_search_embedding_config SET embedding_model = $1, dimensions = $2, updated_at = now()",
+    )
+    .bind(model_id)
+    .bind(dimensions as i32)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to record term search embedding model")?;
+
+    tx.commit().await?;
+    Ok(true)
+}
+
+/// Search rows that have no embedding yet, as `(term_id, content)`
+pub async fn term_search_rows_without_embedding(limit: i64) -> Result<Vec<(Uuid, String)>> {
+    let mut conn = get_sqlx_pool().acquire().await?;
+
+    sqlx::query_as(
+        r#"
+        SELECT term_id, content
+        FROM terms_search
+        WHERE embedding IS NULL AND deleted_at IS NULL
+        ORDER BY term_id
+        LIMIT $1
+        "#,
+    )
+    .bind(limit)
+    .fetch_all(&mut *conn)
+    .await
+    .context("Failed to load terms to embed")
+}
+
+/// Stores the embedding of a term's search row
+pub async fn set_term_search_embedding(term_id: &Uuid, embedding: &[f32]) -> Result<()> {
+    let mut conn = get_sqlx_pool().acquire().await?;
+
+    sqlx::query(&format!(
+        "UPDATE terms_search SET embedding = {} WHERE term_id = $1",
+        vector_literal(embedding)
+    ))
+    .bind(term_id)
+    .execute(&mut *conn)
+    .await
+    .context("Failed to store term search embedding")?;
+
+    Ok(())
+}
+
+/// Creates the similarity index on term embeddings if it is missing
+pub async fn create_term_search_embedding_index() -> Result<()> {
+    let mut conn = get_sqlx_pool().acquire().await?;
+
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS terms_search_embedding_idx ON terms_search USING hnsw (embedding halfvec_cosine_ops)",
+    )
+    .execute(&mut *conn)
+    .await
+    .context("Failed to create term search embedding index")?;
+
+    Ok(())
+}
+
+fn search_terms_sql(embedding: Option<&[f32]>) -> String {
+    let semantic_sql = match embedding {
+        Some(embedding) if !embedding.is_empty() => format!(
+            r#"
+            SELECT term_id, row_number() OVER (ORDER BY embedding <=> {vector}) AS rank
+            FROM terms_search
+            WHERE organization_id = $1 AND deleted_at IS NULL AND embedding IS NOT NULL
+            ORDER BY embedding <=> {vector}
+            LIMIT $3
+            "#,
+            vector = vector_literal(embedding)
+        ),
+        _ => "SELECT NULL::uuid AS term_id, NULL::bigint AS rank WHERE false".to_string(),
+    };
+
+    format!(
+        r#"
+        WITH full_text AS (
+            SELECT term_id,
+                row_number() OVER (
+                    ORDER BY ts_rank_cd(fts, websearch_to_tsquery('simple', $2)) DESC
+                ) AS rank
+            FROM terms_search
+            WHERE organization_id = $1
+                AND deleted_at IS NULL
+                AND fts @@ websearch_to_tsquery('simple', $2)
+            ORDER BY rank
+            LIMIT $3
+        ),
+        semantic AS ({semantic_sql})
+        SELECT
+            terms.id,
+            terms.name,
+            terms.definition,
+            terms.sql_snippet,
+            ARRAY(
+                SELECT datasets.name
+                FROM terms_to_datasets
+                JOIN datasets ON datasets.id = terms_to_datasets.dataset_id
+                WHERE terms_to_datasets.term_id = terms.id
+                    AND terms_to_datasets.deleted_at IS NULL
+                    AND datasets.deleted_at IS NULL
+                ORDER BY datasets.name
+            ) AS dataset_names,
+            (
+                COALESCE(1.0 / ({RRF_K} + full_text.rank), 0.0)
+                + COALESCE(1.0 / ({RRF_K} + semantic.rank), 0.0)
+                + CASE WHEN lower(terms.name) = lower($2) THEN 1.0 ELSE 0.0 END
+            )::float8 AS score
+        FROM full_text
+        FULL OUTER JOIN semantic ON semantic.term_id = full_text.term_id
+        JOIN terms ON terms.id = COALESCE(full_text.term_id, semantic.term_id)
+        WHERE terms.organization_id = $1 AND terms.deleted_at IS NULL
+        ORDER BY score DESC, terms.name
+        LIMIT $4
+        "#
+    )
+}
+
+/// Searches an organization's glossary by full text and, when given, embedding similarity.
+///
+/// The two rankings are merged with reciprocal rank fusion; an exact
+/// (case-insensitive) name match always ranks first.
+pub async fn search_terms(
+    organization_id: &Uuid,
+    query: &str,
+    embedding: Option<&[f32]>,
+    limit: i64,
+) -> Result<Vec<TermSearchResult>> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let query_sql = search_terms_sql(embedding);
+
+    let mut conn = get_sqlx_pool().acquire().await?;
+
+    let results = sqlx::query_as::<_, TermSearchResult>(&query_sql)
+        .bind(organization_id)
+        .bind(query)
+        // Each ranking looks further than the final limit so fusion has something to merge
+        .bind(limit * 4)
+        .bind(limit)
+        .fetch_all(&mut *conn)
+        .await
+        .context("Failed to search terms")?;
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn term(definition: Option<&str>) -> Term {
+        Term {
+            id: Uuid::new_v4(),
+            name: "Active customer".to_string(),
+            definition: definition.map(String::from),
+            sql_snippet: None,
+            organization_id: Uuid::new_v4(),
+            created_by: Uuid::new_v4(),
+            updated_by: Uuid::new_v4(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            deleted_at: None,
+        }
+    }
+
+    #[test]
+    fn test_term_search_content() {
+        assert_eq!(term_search_content(&term(None)), "Active customer");
+        assert_eq!(term_search_content(&term(Some("  "))), "Active customer");
+        assert_eq!(
+            term_search_content(&term(Some("Ordered in the last 90 days"))),
+            "Active customer: Ordered in the last 90 days"
+        );
+    }
+
+    #[test]
+    fn test_search_terms_sql() {
+        let full_text_only = search_terms_sql(None);
+        assert!(full_text_only.contains("WHERE false"));
+        assert!(!full_text_only.contains("halfvec"));
+
+        let hybrid = search_terms_sql(Some(&[0.5, -1.0]));
+        assert!(hybrid.contains("embedding <=> '[0.5,-1]'::halfvec"));
+        assert!(hybrid.contains("FULL OUTER JOIN semantic"));
+    }
+}
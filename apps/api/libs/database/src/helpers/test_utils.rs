@@ -73,6 +73,8 @@ impl TestDb {
             sql: "SELECT * FROM test".to_string(),
             time_frame: "last 30 days".to_string(),
             chart_config: create_default_chart_config(),
+            cache_ttl: None,
+            parameters: vec![],
         };
 
         let metric_file = MetricFile {
@@ -111,6 +113,7 @@ impl TestDb {
             name: "Test Dashboard".to_string(),
             description: Some("Test dashboard description".to_string()),
             rows: Vec::new(),
+            filters: vec![],
         };
 
         let dashboard_file = DashboardFile {
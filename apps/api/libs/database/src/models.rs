@@ -13,11 +13,18 @@ use uuid::Uuid;
 pub struct ApiKey {
     pub id: Uuid,
     pub owner_id: Uuid,
-    pub key: String,
     pub organization_id: Uuid,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub deleted_at: Option<DateTime<Utc>>,
+    /// SHA-256 of the key; the key itself is only shown once, when it's created
+    pub key_hash: String,
+    pub key_prefix: String,
+    pub scopes: Vec<String>,
+    /// IP addresses and CIDR ranges the key can be used from; empty allows any
+    pub allowed_ips: Vec<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Queryable, Insertable, Identifiable, Debug, Clone, Serialize)]
@@ -785,6 +792,56 @@ pub enum StepProgress {
     Failed,
 }
 
+#[derive(Queryable, Insertable, Identifiable, Associations, Debug, Clone, Serialize)]
+#[diesel(belongs_to(Dataset, foreign_key = dataset_id))]
+#[diesel(table_name = dataset_column_policies)]
+pub struct DatasetColumnPolicy {
+    pub id: Uuid,
+    pub dataset_id: Uuid,
+    pub column_name: String,
+    pub policy: ColumnMaskPolicy,
+    pub visible_chars: Option<i32>,
+    pub permission_group_id: Option<Uuid>,
+    pub dataset_group_id: Option<Uuid>,
+    pub created_by: Uuid,
+    pub updated_by: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Queryable, Insertable, Identifiable, Associations, Debug, Clone, Serialize)]
+#[diesel(belongs_to(Dataset, foreign_key = dataset_id))]
+#[diesel(table_name = dataset_row_filters)]
+pub struct DatasetRowFilter {
+    pub id: Uuid,
+    pub dataset_id: Uuid,
+    pub filter_template: String,
+    pub description: Option<String>,
+    pub created_by: Uuid,
+    pub updated_by: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Queryable, Insertable, Identifiable, Associations, Debug, Clone, Serialize)]
+#[diesel(belongs_to(DataSource, foreign_key = data_source_id))]
+#[diesel(table_name = catalog_drift)]
+pub struct CatalogDrift {
+    pub id: Uuid,
+    pub data_source_id: Uuid,
+    pub dataset_id: Option<Uuid>,
+    pub kind: CatalogDriftKind,
+    pub schema_name: String,
+    pub table_name: String,
+    pub column_name: Option<String>,
+    pub expected: Option<String>,
+    pub actual: Option<String>,
+    pub detected_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
 #[derive(Queryable, Insertable, Associations, Debug)]
 #[diesel(belongs_to(MetricFile, foreign_key = metric_file_id))]
 #[diesel(belongs_to(DashboardFile, foreign_key = dashboard_file_id))]
@@ -834,4 +891,147 @@ pub struct StoredValuesSyncJob {
     pub created_at: DateTime<Utc>,
     pub status: String,
     pub error_message: Option<String>,
+    pub dataset_column_id: Option<Uuid>,
+    /// Cron expression with seconds, e.g. `0 0 3 * * *` for 03:00 UTC daily
+    pub schedule: String,
+    pub next_run_at: Option<DateTime<Utc>>,
+    /// Distinct non-null values the warehouse reported at the last successful sync
+    pub value_count: Option<i64>,
+    /// Warehouse-computed fingerprint of the distinct values at the last successful sync
+    pub values_hash: Option<String>,
+    /// Columns with more distinct values than this are not synced
+    pub max_cardinality: Option<i64>,
+    /// When the current or last run claimed the job
+    pub claimed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(
+    Queryable,
+    Insertable,
+    Identifiable,
+    Associations,
+    Debug,
+    Clone,
+    Serialize,
+    Selectable,
+    AsChangeset,
+)]
+#[diesel(belongs_to(User, foreign_key = owner_id))]
+#[diesel(table_name = subscriptions)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Subscription {
+    pub id: Uuid,
+    pub organization_id: Uuid,
+    /// Deliveries run with this user's access to the asset and its data
+    pub owner_id: Uuid,
+    pub asset_id: Uuid,
+    /// `MetricFile` or `DashboardFile`
+    pub asset_type: AssetType,
+    pub name: String,
+    /// Cron expression, e.g. `0 9 * * MON` for Mondays at 09:00
+    pub schedule: String,
+    /// IANA time zone the schedule is in, e.g. `America/New_York`
+    pub timezone: String,
+    /// `email`, `slack` or `webhook`
+    pub channel: String,
+    /// `summary` or `csv`
+    pub format: String,
+    /// Email addresses, for the `email` channel
+    pub recipients: Vec<String>,
+    /// Slack incoming webhook or webhook URL
+    pub target_url: Option<String>,
+    /// Key webhook payloads are signed with
+    #[serde(skip_serializing)]
+    pub signing_secret: Option<String>,
+    pub enabled: bool,
+    pub next_run_at: Option<DateTime<Utc>>,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Queryable, Insertable, Identifiable, Associations, Debug, Clone, Serialize)]
+#[diesel(belongs_to(Subscription))]
+#[diesel(table_name = subscription_deliveries)]
+pub struct SubscriptionDelivery {
+    pub id: Uuid,
+    pub subscription_id: Uuid,
+    /// `in_progress`, `success` or `error`
+    pub status: String,
+    /// Sent on request rather than on schedule
+    pub manual: bool,
+    pub row_count: Option<i64>,
+    pub error_message: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+#[derive(
+    Queryable,
+    Insertable,
+    Identifiable,
+    Associations,
+    Debug,
+    Clone,
+    Serialize,
+    Selectable,
+    AsChangeset,
+)]
+#[diesel(belongs_to(MetricFile))]
+#[diesel(table_name = metric_alerts)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct MetricAlert {
+    pub id: Uuid,
+    pub organization_id: Uuid,
+    /// Checks run with this user's access to the metric and its data
+    pub owner_id: Uuid,
+    pub metric_file_id: Uuid,
+    pub name: String,
+    /// The result column that's checked
+    pub column_name: String,
+    /// Column the result is ordered by to find the latest value; without one, the last row is
+    /// the latest
+    pub time_column: Option<String>,
+    /// `above`, `below`, `percent_change` or `anomaly`
+    pub condition: String,
+    /// A value for `above` and `below` (the metric's goal line when unset), a percentage for
+    /// `percent_change` or a z-score for `anomaly`
+    pub threshold: Option<f64>,
+    /// Cron expression for when the metric is checked
+    pub schedule: String,
+    pub timezone: String,
+    /// `email` or `webhook`
+    pub channel: String,
+    pub recipients: Vec<String>,
+    pub target_url: Option<String>,
+    #[serde(skip_serializing)]
+    pub signing_secret: Option<String>,
+    pub notify_on_resolve: bool,
+    pub enabled: bool,
+    /// `ok`, `triggered` or `error`
+    pub state: String,
+    pub last_value: Option<f64>,
+    pub last_message: Option<String>,
+    pub last_checked_at: Option<DateTime<Utc>>,
+    pub last_triggered_at: Option<DateTime<Utc>>,
+    pub next_run_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Queryable, Insertable, Identifiable, Associations, Debug, Clone, Serialize)]
+#[diesel(belongs_to(MetricAlert, foreign_key = alert_id))]
+#[diesel(table_name = metric_alert_events)]
+pub struct MetricAlertEvent {
+    pub id: Uuid,
+    pub alert_id: Uuid,
+    /// `triggered`, `resolved` or `error`
+    pub kind: String,
+    pub value: Option<f64>,
+    pub message: String,
+    pub notified: bool,
+    pub notification_error: Option<String>,
+    pub created_at: DateTime<Utc>,
 }
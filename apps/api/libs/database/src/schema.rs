@@ -58,11 +58,16 @@ diesel::table! {
     api_keys (id) {
         id -> Uuid,
         owner_id -> Uuid,
-        key -> Text,
         organization_id -> Uuid,
         created_at -> Timestamptz,
         updated_at -> Timestamptz,
         deleted_at -> Nullable<Timestamptz>,
+        key_hash -> Text,
+        key_prefix -> Text,
+        scopes -> Array<Text>,
+        allowed_ips -> Array<Text>,
+        expires_at -> Nullable<Timestamptz>,
+        last_used_at -> Nullable<Timestamptz>,
     }
 }
 
@@ -86,6 +91,22 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    catalog_drift (id) {
+        id -> Uuid,
+        data_source_id -> Uuid,
+        dataset_id -> Nullable<Uuid>,
+        kind -> Text,
+        schema_name -> Text,
+        table_name -> Text,
+        column_name -> Nullable<Text>,
+        expected -> Nullable<Text>,
+        actual -> Nullable<Text>,
+        detected_at -> Timestamptz,
+        resolved_at -> Nullable<Timestamptz>,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use super::sql_types::WorkspaceSharingEnum;
@@ -252,6 +273,23 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    dataset_column_policies (id) {
+        id -> Uuid,
+        dataset_id -> Uuid,
+        column_name -> Text,
+        policy -> Text,
+        visible_chars -> Nullable<Int4>,
+        permission_group_id -> Nullable<Uuid>,
+        dataset_group_id -> Nullable<Uuid>,
+        created_by -> Uuid,
+        updated_by -> Uuid,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        deleted_at -> Nullable<Timestamptz>,
+    }
+}
+
 diesel::table! {
     dataset_groups (id) {
         id -> Uuid,
@@ -318,6 +356,20 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    dataset_row_filters (id) {
+        id -> Uuid,
+        dataset_id -> Uuid,
+        filter_template -> Text,
+        description -> Nullable<Text>,
+        created_by -> Uuid,
+        updated_by -> Uuid,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        deleted_at -> Nullable<Timestamptz>,
+    }
+}
+
 diesel::table! {
     datasets_to_dataset_groups (dataset_id, dataset_group_id) {
         dataset_id -> Uuid,
@@ -411,6 +463,50 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    metric_alert_events (id) {
+        id -> Uuid,
+        alert_id -> Uuid,
+        kind -> Text,
+        value -> Nullable<Float8>,
+        message -> Text,
+        notified -> Bool,
+        notification_error -> Nullable<Text>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    metric_alerts (id) {
+        id -> Uuid,
+        organization_id -> Uuid,
+        owner_id -> Uuid,
+        metric_file_id -> Uuid,
+        name -> Text,
+        column_name -> Text,
+        time_column -> Nullable<Text>,
+        condition -> Text,
+        threshold -> Nullable<Float8>,
+        schedule -> Text,
+        timezone -> Text,
+        channel -> Text,
+        recipients -> Array<Text>,
+        target_url -> Nullable<Text>,
+        signing_secret -> Nullable<Text>,
+        notify_on_resolve -> Bool,
+        enabled -> Bool,
+        state -> Text,
+        last_value -> Nullable<Float8>,
+        last_message -> Nullable<Text>,
+        last_checked_at -> Nullable<Timestamptz>,
+        last_triggered_at -> Nullable<Timestamptz>,
+        next_run_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        deleted_at -> Nullable<Timestamptz>,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use super::sql_types::VerificationEnum;
@@ -567,6 +663,53 @@ diesel::table! {
         created_at -> Timestamptz,
         status -> Text,
         error_message -> Nullable<Text>,
+        dataset_column_id -> Nullable<Uuid>,
+        schedule -> Text,
+        next_run_at -> Nullable<Timestamptz>,
+        value_count -> Nullable<Int8>,
+        values_hash -> Nullable<Text>,
+        max_cardinality -> Nullable<Int8>,
+        claimed_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::AssetTypeEnum;
+
+    subscriptions (id) {
+        id -> Uuid,
+        organization_id -> Uuid,
+        owner_id -> Uuid,
+        asset_id -> Uuid,
+        asset_type -> AssetTypeEnum,
+        name -> Text,
+        schedule -> Text,
+        timezone -> Text,
+        channel -> Text,
+        format -> Text,
+        recipients -> Array<Text>,
+        target_url -> Nullable<Text>,
+        signing_secret -> Nullable<Text>,
+        enabled -> Bool,
+        next_run_at -> Nullable<Timestamptz>,
+        last_run_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        deleted_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    subscription_deliveries (id) {
+        id -> Uuid,
+        subscription_id -> Uuid,
+        status -> Text,
+        manual -> Bool,
+        row_count -> Nullable<Int8>,
+        error_message -> Nullable<Text>,
+        started_at -> Timestamptz,
+        finished_at -> Nullable<Timestamptz>,
     }
 }
 
@@ -712,16 +855,22 @@ diesel::table! {
 
 diesel::joinable!(api_keys -> organizations (organization_id));
 diesel::joinable!(api_keys -> users (owner_id));
+diesel::joinable!(catalog_drift -> data_sources (data_source_id));
+diesel::joinable!(catalog_drift -> datasets (dataset_id));
 diesel::joinable!(chats -> organizations (organization_id));
 diesel::joinable!(collections -> organizations (organization_id));
 diesel::joinable!(dashboard_versions -> dashboards (dashboard_id));
 diesel::joinable!(dashboards -> organizations (organization_id));
 diesel::joinable!(data_sources -> organizations (organization_id));
+diesel::joinable!(dataset_column_policies -> dataset_groups (dataset_group_id));
+diesel::joinable!(dataset_column_policies -> datasets (dataset_id));
+diesel::joinable!(dataset_column_policies -> permission_groups (permission_group_id));
 diesel::joinable!(dataset_groups -> organizations (organization_id));
 diesel::joinable!(dataset_groups_permissions -> dataset_groups (dataset_group_id));
 diesel::joinable!(dataset_groups_permissions -> organizations (organization_id));
 diesel::joinable!(dataset_permissions -> datasets (dataset_id));
 diesel::joinable!(dataset_permissions -> organizations (organization_id));
+diesel::joinable!(dataset_row_filters -> datasets (dataset_id));
 diesel::joinable!(datasets -> data_sources (data_source_id));
 diesel::joinable!(datasets -> organizations (organization_id));
 diesel::joinable!(datasets_to_dataset_groups -> dataset_groups (dataset_group_id));
@@ -732,6 +881,10 @@ diesel::joinable!(messages -> chats (chat_id));
 diesel::joinable!(messages -> users (created_by));
 diesel::joinable!(messages_deprecated -> datasets (dataset_id));
 diesel::joinable!(messages_to_files -> messages (message_id));
+diesel::joinable!(metric_alert_events -> metric_alerts (alert_id));
+diesel::joinable!(metric_alerts -> metric_files (metric_file_id));
+diesel::joinable!(metric_alerts -> organizations (organization_id));
+diesel::joinable!(metric_alerts -> users (owner_id));
 diesel::joinable!(metric_files -> data_sources (data_source_id));
 diesel::joinable!(metric_files_to_dashboard_files -> dashboard_files (dashboard_file_id));
 diesel::joinable!(metric_files_to_dashboard_files -> metric_files (metric_file_id));
@@ -742,6 +895,10 @@ diesel::joinable!(permission_groups -> organizations (organization_id));
 diesel::joinable!(permission_groups_to_users -> permission_groups (permission_group_id));
 diesel::joinable!(permission_groups_to_users -> users (user_id));
 diesel::joinable!(stored_values_sync_jobs -> data_sources (data_source_id));
+diesel::joinable!(stored_values_sync_jobs -> dataset_columns (dataset_column_id));
+diesel::joinable!(subscription_deliveries -> subscriptions (subscription_id));
+diesel::joinable!(subscriptions -> organizations (organization_id));
+diesel::joinable!(subscriptions -> users (owner_id));
 diesel::joinable!(teams -> organizations (organization_id));
 diesel::joinable!(teams -> users (created_by));
 diesel::joinable!(teams_to_users -> teams (team_id));
@@ -759,6 +916,7 @@ diesel::joinable!(users_to_organizations -> organizations (organization_id));
 diesel::allow_tables_to_appear_in_same_query!(
     api_keys,
     asset_permissions,
+    catalog_drift,
     chats,
     collections,
     collections_to_assets,
@@ -767,9 +925,11 @@ diesel::allow_tables_to_appear_in_same_query!(
     dashboards,
     data_sources,
     dataset_columns,
+    dataset_column_policies,
     dataset_groups,
     dataset_groups_permissions,
     dataset_permissions,
+    dataset_row_filters,
     datasets,
     datasets_to_dataset_groups,
     datasets_to_permission_groups,
@@ -777,6 +937,8 @@ diesel::allow_tables_to_appear_in_same_query!(
     messages,
     messages_deprecated,
     messages_to_files,
+    metric_alert_events,
+    metric_alerts,
     metric_files,
     metric_files_to_dashboard_files,
     metric_files_to_datasets,
@@ -787,6 +949,8 @@ diesel::allow_tables_to_appear_in_same_query!(
     permission_groups_to_users,
     sql_evaluations,
     stored_values_sync_jobs,
+    subscription_deliveries,
+    subscriptions,
     teams,
     teams_to_users,
     terms,
@@ -15,6 +15,7 @@ use lazy_static::lazy_static;
 
 lazy_static! {
     static ref DASHBOARD_NAME_DESC_RE: Regex = Regex::new(r#"^(\s*(?:name|description):\s*)(.*)$"#).unwrap();
+    static ref FILTER_ID_RE: Regex = Regex::new(r"^[A-Za-z_][A-Za-z0-9_]*$").unwrap();
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, FromSqlRow, AsExpression)]
@@ -29,6 +30,10 @@ pub struct DashboardYml {
     
     #[serde(alias = "rows")]
     pub rows: Vec<Row>,
+
+    /// Dashboard-wide filters applied to the data of the metrics they target
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub filters: Vec<DashboardFilter>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -55,6 +60,61 @@ pub struct RowItem {
     pub id: Uuid,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DashboardFilter {
+    /// Key the filter's value is sent under when fetching metric data
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(rename = "type")]
+    pub filter_type: DashboardFilterType,
+    /// Fixed choices for a `select` filter
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub options: Option<Vec<String>>,
+    /// Column whose stored values feed a `select` filter's picker
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub values_from: Option<FilterValuesSource>,
+    /// Value used when a request doesn't provide one. Without a default the filter is
+    /// inactive until a value is picked.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<Value>,
+    pub targets: Vec<DashboardFilterTarget>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DashboardFilterType {
+    /// `{ start, end }` or a relative range such as `last_30_days`
+    DateRange,
+    /// One or more values
+    Select,
+    /// `{ min, max }`, either bound optional
+    NumberRange,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FilterValuesSource {
+    pub database: String,
+    pub schema: String,
+    pub table: String,
+    pub column: String,
+}
+
+/// Where a filter applies within one metric: either a column of the metric's result, which
+/// the result is filtered on, or one of the metric's parameters, which receives the value.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DashboardFilterTarget {
+    #[serde(alias = "metric_id")]
+    pub metric_id: Uuid,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub column: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parameter: Option<String>,
+}
+
 impl DashboardYml {
     pub fn new(yml_content: String) -> Result<Self> {
         let processed_yml_content = yml_content
@@ -148,9 +208,94 @@ impl DashboardYml {
             }
         }
 
+        self.validate_filters()
+    }
+
+    fn validate_filters(&self) -> Result<()> {
+        let mut ids = std::collections::HashSet::new();
+
+        for filter in &self.filters {
+            if !FILTER_ID_RE.is_match(&filter.id) {
+                return Err(anyhow::anyhow!(
+                    "Filter id '{}' must start with a letter or underscore and contain only letters, digits and underscores",
+                    filter.id
+                ));
+            }
+
+            if !ids.insert(filter.id.as_str()) {
+                return Err(anyhow::anyhow!("Duplicate filter id '{}'", filter.id));
+            }
+
+            if filter.filter_type != DashboardFilterType::Select
+                && (filter.options.is_some() || filter.values_from.is_some())
+            {
+                return Err(anyhow::anyhow!(
+                    "Only select filters can have options or valuesFrom, filter '{}' can't",
+                    filter.id
+                ));
+            }
+
+            if filter.targets.is_empty() {
+                return Err(anyhow::anyhow!(
+                    "Filter '{}' must target at least one metric",
+                    filter.id
+                ));
+            }
+
+            for target in &filter.targets {
+                if !self.contains_metric(&target.metric_id) {
+                    return Err(anyhow::anyhow!(
+                        "Filter '{}' targets metric {} which is not on the dashboard",
+                        filter.id,
+                        target.metric_id
+                    ));
+                }
+
+                match (&target.column, &target.parameter) {
+                    (Some(_), None) => {}
+                    (None, Some(_)) if filter.filter_type == DashboardFilterType::NumberRange => {
+                        return Err(anyhow::anyhow!(
+                            "Number range filter '{}' can only target columns",
+                            filter.id
+                        ));
+                    }
+                    (None, Some(_)) => {}
+                    _ => {
+                        return Err(anyhow::anyhow!(
+                            "Each target of filter '{}' needs exactly one of column or parameter",
+                            filter.id
+                        ));
+                    }
+                }
+            }
+        }
+
         Ok(())
     }
 
+    pub fn contains_metric(&self, metric_id: &Uuid) -> bool {
+        self.rows
+            .iter()
+            .any(|row| row.items.iter().any(|item| &item.id == metric_id))
+    }
+
+    /// Drops filter targets whose metric is no longer on the dashboard, and filters left
+    /// without any target.
+    pub fn prune_filter_targets(&mut self) {
+        let metric_ids: std::collections::HashSet<Uuid> = self
+            .rows
+            .iter()
+            .flat_map(|row| row.items.iter().map(|item| item.id))
+            .collect();
+
+        for filter in &mut self.filters {
+            filter
+                .targets
+                .retain(|target| metric_ids.contains(&target.metric_id));
+        }
+        self.filters.retain(|filter| !filter.targets.is_empty());
+    }
+
     pub fn to_value(&self) -> Result<Value> {
         serde_json::to_value(self)
             .map_err(|e| anyhow::anyhow!("Failed to serialize dashboard yml: {}", e))
@@ -212,6 +357,7 @@ mod tests {
                     id: 1,
                 }
             ],
+            filters: vec![],
         };
         
         let json = serde_json::to_value(&dashboard).unwrap();
@@ -336,6 +482,7 @@ rows:
                     id: 1,
                 }
             ],
+            filters: vec![],
         };
         
         dashboard.add_row(
@@ -382,6 +529,7 @@ rows:
                     id: 3,
                 }
             ],
+            filters: vec![],
         };
         
         assert_eq!(dashboard.get_next_row_id(), 6);
@@ -411,4 +559,56 @@ rows:
         
         assert_eq!(dashboard.rows[0].id, 42);
     }
+
+    #[test]
+    fn test_dashboard_filters() {
+        let yaml = r#"
+name: Sales
+rows:
+  - id: 1
+    items:
+      - id: 00000000-0000-0000-0000-000000000001
+      - id: 00000000-0000-0000-0000-000000000002
+    columnSizes: [6, 6]
+filters:
+  - id: period
+    label: Period
+    type: date_range
+    default: last_30_days
+    targets:
+      - metricId: 00000000-0000-0000-0000-000000000001
+        column: order_date
+      - metricId: 00000000-0000-0000-0000-000000000002
+        parameter: period
+  - id: region
+    type: select
+    valuesFrom: { database: analytics, schema: sales, table: orders, column: region }
+    targets:
+      - metricId: 00000000-0000-0000-0000-000000000001
+        column: region
+"#;
+
+        let mut dashboard = DashboardYml::new(yaml.to_string()).unwrap();
+        assert_eq!(dashboard.filters.len(), 2);
+        assert_eq!(dashboard.filters[0].filter_type, DashboardFilterType::DateRange);
+        assert_eq!(dashboard.filters[1].values_from.as_ref().unwrap().column, "region");
+
+        // Targets must be metrics on the dashboard
+        let unknown_metric = yaml.replace(
+            "metricId: 00000000-0000-0000-0000-000000000002",
+            "metricId: 00000000-0000-0000-0000-000000000003",
+        );
+        assert!(DashboardYml::new(unknown_metric).is_err());
+
+        // A number range can't feed a parameter
+        let number_range = yaml.replace("type: date_range", "type: number_range").replace("    default: last_30_days\n", "");
+        assert!(DashboardYml::new(number_range).is_err());
+
+        // Removing a metric drops the targets that point at it
+        dashboard.rows[0].items.truncate(1);
+        dashboard.rows[0].column_sizes = vec![12];
+        dashboard.prune_filter_targets();
+        assert_eq!(dashboard.filters[0].targets.len(), 1);
+        assert!(dashboard.validate().is_ok());
+    }
 }
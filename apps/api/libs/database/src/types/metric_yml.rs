@@ -115,6 +115,10 @@ lazy_static! {
     static ref INDENT_RE: Regex = Regex::new(r#"^(\s*)\S"#).unwrap();
     // Regex to find the sql key to determine insertion point for timeFrame
     static ref SQL_KEY_RE: Regex = Regex::new(r#"^(\s*)sql:\s*.*$"#).unwrap();
+    static ref PARAMETER_NAME_RE: Regex = Regex::new(r"^[A-Za-z_][A-Za-z0-9_]*$").unwrap();
+    // `{{name}}` or `{{name.part}}` placeholders in metric SQL
+    static ref SQL_PARAMETER_RE: Regex =
+        Regex::new(r"\{\{\s*([A-Za-z_][A-Za-z0-9_]*)(?:\.([A-Za-z_][A-Za-z0-9_]*))?\s*\}\}").unwrap();
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, FromSqlRow, AsExpression)]
@@ -128,6 +132,42 @@ pub struct MetricYml {
     pub sql: String,
     #[serde(alias = "chart_config")]
     pub chart_config: ChartConfig,
+    /// How long query results for this metric may be served from cache, in seconds
+    #[serde(default, alias = "cache_ttl", skip_serializing_if = "Option::is_none")]
+    pub cache_ttl: Option<u64>,
+    /// Typed inputs referenced in `sql` as `{{name}}`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parameters: Vec<MetricParameter>,
+}
+
+/// A typed input to a metric's SQL. Date ranges are referenced as `{{name.start}}` and
+/// `{{name.end}}`, multi-selects as `IN ({{name}})`, everything else as `{{name}}`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricParameter {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub param_type: MetricParameterType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Allowed values. Required for `multi_select`; turns a `string` into an enum.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub options: Option<Vec<String>>,
+    /// Value used when a request doesn't provide one. A date range default is either
+    /// `{ start, end }` or a relative range such as `last_30_days`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MetricParameterType {
+    String,
+    Number,
+    Boolean,
+    Date,
+    DateRange,
+    MultiSelect,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -150,6 +190,21 @@ pub enum ChartConfig {
     Table(TableChartConfig),
 }
 
+impl ChartConfig {
+    /// Settings shared by every chart type, such as column label formats
+    pub fn base(&self) -> &BaseChartConfig {
+        match self {
+            ChartConfig::Bar(config) => &config.base,
+            ChartConfig::Line(config) => &config.base,
+            ChartConfig::Scatter(config) => &config.base,
+            ChartConfig::Pie(config) => &config.base,
+            ChartConfig::Combo(config) => &config.base,
+            ChartConfig::Metric(config) => &config.base,
+            ChartConfig::Table(config) => &config.base,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 #[serde(untagged)]
@@ -881,6 +936,56 @@ impl MetricYml {
     }
 
     pub fn validate(&self) -> Result<()> {
+        let mut names = std::collections::HashSet::new();
+
+        for parameter in &self.parameters {
+            if !PARAMETER_NAME_RE.is_match(&parameter.name) {
+                return Err(anyhow::anyhow!(
+                    "Parameter name '{}' must start with a letter or underscore and contain only letters, digits and underscores",
+                    parameter.name
+                ));
+            }
+            if !names.insert(parameter.name.as_str()) {
+                return Err(anyhow::anyhow!("Parameter '{}' is defined more than once", parameter.name));
+            }
+            if parameter.param_type == MetricParameterType::MultiSelect
+                && parameter.options.as_ref().is_none_or(|options| options.is_empty())
+            {
+                return Err(anyhow::anyhow!(
+                    "Multi-select parameter '{}' needs a list of options",
+                    parameter.name
+                ));
+            }
+        }
+
+        // Every placeholder has to refer to a declared parameter
+        for caps in SQL_PARAMETER_RE.captures_iter(&self.sql) {
+            let name = &caps[1];
+            let part = caps.get(2).map(|m| m.as_str());
+
+            let parameter = self
+                .parameters
+                .iter()
+                .find(|parameter| parameter.name == name)
+                .ok_or_else(|| anyhow::anyhow!("SQL references undefined parameter '{}'", name))?;
+
+            let is_range = parameter.param_type == MetricParameterType::DateRange;
+            match (is_range, part) {
+                (true, Some("start" | "end")) | (false, None) => {}
+                (true, _) => {
+                    return Err(anyhow::anyhow!(
+                        "Date range parameter '{}' must be referenced as {{{{{}.start}}}} or {{{{{}.end}}}}",
+                        name,
+                        name,
+                        name
+                    ))
+                }
+                (false, Some(part)) => {
+                    return Err(anyhow::anyhow!("Parameter '{}' has no '{}' part", name, part))
+                }
+            }
+        }
+
         Ok(())
     }
 }
@@ -1187,6 +1292,46 @@ chartConfig:
     }
 
     // ... existing tests ...
+    #[test]
+    fn test_metric_parameters() -> Result<()> {
+        let yml_content = r#"
+name: Revenue by Region
+sql: |
+  SELECT o.region, SUM(o.amount) AS revenue
+  FROM sales.orders o
+  WHERE o.region IN ({{regions}})
+    AND o.created_at BETWEEN {{period.start}} AND {{period.end}}
+  GROUP BY o.region
+timeFrame: Custom
+parameters:
+  - name: regions
+    type: multi_select
+    options: [west, east, north]
+    default: [west]
+  - name: period
+    type: date_range
+    default: last_30_days
+chartConfig:
+  selectedChartType: table
+  columnLabelFormats:
+    region: { columnType: string, style: string }
+"#;
+        let metric = MetricYml::new(yml_content.to_string())?;
+        assert_eq!(metric.parameters.len(), 2);
+        assert_eq!(metric.parameters[0].param_type, MetricParameterType::MultiSelect);
+        assert_eq!(metric.parameters[1].default, Some(json!("last_30_days")));
+
+        // Undefined parameter
+        let undefined = yml_content.replace("{{regions}}", "{{region}}");
+        assert!(MetricYml::new(undefined).is_err());
+
+        // Date ranges must be referenced by their start or end
+        let whole_range = yml_content.replace("{{period.start}}", "{{period}}");
+        assert!(MetricYml::new(whole_range).is_err());
+
+        Ok(())
+    }
+
     #[test]
     fn test_column_label_format_constructors() {
         let number_format = ColumnLabelFormat::new_number();
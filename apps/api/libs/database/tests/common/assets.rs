@@ -31,6 +31,8 @@ impl AssetTestHelpers {
             sql: "SELECT * FROM test".to_string(),
             time_frame: "last 30 days".to_string(),
             chart_config: create_default_chart_config(),
+            cache_ttl: None,
+            parameters: vec![],
             dataset_ids: Vec::new(),
         };
 
@@ -78,6 +80,7 @@ impl AssetTestHelpers {
             name: name.to_string(),
             description: Some(format!("Test dashboard description for {}", name)),
             rows: Vec::new(),
+            filters: vec![],
         };
 
         let dashboard_file = DashboardFile {
@@ -0,0 +1,258 @@
+//! Column masking policies assigned to permission groups and dataset groups.
+//!
+//! A policy hides, hashes, redacts or partially masks a single dataset column. It applies to a
+//! user when they belong to the policy's permission group (directly or through a team), or when
+//! they have been granted the policy's dataset group. The resulting masks are keyed by table
+//! name so they can be handed to `sql_analyzer::apply_column_masks`.
+
+use anyhow::{Context, Result};
+use database::{
+    enums::{ColumnMaskPolicy, IdentityType},
+    pool::get_pg_pool,
+    schema::{
+        dataset_column_policies, dataset_columns, dataset_groups_permissions, datasets,
+        permission_groups_to_identities, teams_to_users,
+    },
+};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use sql_analyzer::{ColumnMask, TableMask};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+use crate::row_level_security::is_exempt_from_data_policies;
+
+/// Returns the column masks that apply to `user_id` for queries against `data_source_id`,
+/// keyed by table (dataset) name.
+///
+/// Workspace and data admins are exempt. When several policies target the same column the
+/// most restrictive one wins.
+pub async fn get_column_masks(
+    user_id: &Uuid,
+    data_source_id: &Uuid,
+) -> Result<HashMap<String, TableMask>> {
+    let mut conn = get_pg_pool().get().await.context("DB Error")?;
+
+    let policies = dataset_column_policies::table
+        .inner_join(datasets::table.on(dataset_column_policies::dataset_id.eq(datasets::id)))
+        .filter(datasets::data_source_id.eq(data_source_id))
+        .filter(datasets::deleted_at.is_null())
+        .filter(dataset_column_policies::deleted_at.is_null())
+        .select((
+            datasets::id,
+            datasets::name,
+            datasets::organization_id,
+            dataset_column_policies::column_name,
+            dataset_column_policies::policy,
+            dataset_column_policies::visible_chars,
+            dataset_column_policies::permission_group_id,
+            dataset_column_policies::dataset_group_id,
+        ))
+        .load::<(
+            Uuid,
+            String,
+            Uuid,
+            String,
+            ColumnMaskPolicy,
+            Option<i32>,
+            Option<Uuid>,
+            Option<Uuid>,
+        )>(&mut conn)
+        .await
+        .context("Failed to load dataset column policies")?;
+
+    if policies.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    // All datasets on a data source belong to the same organization
+    let organization_id = policies[0].2;
+
+    if is_exempt_from_data_policies(user_id, &organization_id).await? {
+        return Ok(HashMap::new());
+    }
+
+    let permission_group_ids = get_user_permission_group_ids(user_id).await?;
+    let dataset_group_ids = get_user_dataset_group_ids(user_id, &permission_group_ids).await?;
+
+    // (dataset_id, dataset_name) -> column -> strictest policy
+    let mut applicable: HashMap<(Uuid, String), HashMap<String, (ColumnMaskPolicy, Option<i32>)>> =
+        HashMap::new();
+
+    for (
+        dataset_id,
+        dataset_name,
+        _,
+        column_name,
+        policy,
+        visible_chars,
+        permission_group_id,
+        dataset_group_id,
+    ) in policies
+    {
+        let applies = permission_group_id.is_some_and(|id| permission_group_ids.contains(&id))
+            || dataset_group_id.is_some_and(|id| dataset_group_ids.contains(&id));
+
+        if !applies {
+            continue;
+        }
+
+        applicable
+            .entry((dataset_id, dataset_name))
+            .or_default()
+            .entry(column_name.to_lowercase())
+            .and_modify(|existing| *existing = stricter(*existing, (policy, visible_chars)))
+            .or_insert((policy, visible_chars));
+    }
+
+    if applicable.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let dataset_ids: Vec<Uuid> = applicable.keys().map(|(id, _)| *id).collect();
+
+    let columns = dataset_columns::table
+        .filter(dataset_columns::dataset_id.eq_any(&dataset_ids))
+        .filter(dataset_columns::deleted_at.is_null())
+        .order(dataset_columns::created_at.asc())
+        .select((dataset_columns::dataset_id, dataset_columns::name))
+        .load::<(Uuid, String)>(&mut conn)
+        .await
+        .context("Failed to load dataset columns")?;
+
+    let mut columns_by_dataset: HashMap<Uuid, Vec<String>> = HashMap::new();
+    for (dataset_id, name) in columns {
+        columns_by_dataset.entry(dataset_id).or_default().push(name);
+    }
+
+    Ok(applicable
+        .into_iter()
+        .map(|((dataset_id, dataset_name), column_policies)| {
+            let masks = column_policies
+                .into_iter()
+                .map(|(column, (policy, visible_chars))| {
+                    (column, to_column_mask(policy, visible_chars))
+                })
+                .collect();
+
+            let table_mask = TableMask {
+                columns: columns_by_dataset.remove(&dataset_id).unwrap_or_default(),
+                masks,
+                row_filter: None,
+            };
+
+            (dataset_name, table_mask)
+        })
+        .collect())
+}
+
+fn stricter(
+    a: (ColumnMaskPolicy, Option<i32>),
+    b: (ColumnMaskPolicy, Option<i32>),
+) -> (ColumnMaskPolicy, Option<i32>) {
+    match a.0.cmp(&b.0) {
+        std::cmp::Ordering::Greater => a,
+        std::cmp::Ordering::Less => b,
+        // Two partial masks: reveal the fewest characters
+        std::cmp::Ordering::Equal => {
+            if a.1.unwrap_or(0) <= b.1.unwrap_or(0) {
+                a
+            } else {
+                b
+            }
+        }
+    }
+}
+
+fn to_column_mask(policy: ColumnMaskPolicy, visible_chars: Option<i32>) -> ColumnMask {
+    match policy {
+        ColumnMaskPolicy::Hide => ColumnMask::Hide,
+        ColumnMaskPolicy::Hash => ColumnMask::Hash,
+        ColumnMaskPolicy::Redact => ColumnMask::Redact,
+        ColumnMaskPolicy::Partial => ColumnMask::Partial {
+            visible_chars: visible_chars.unwrap_or(0).max(0) as usize,
+        },
+    }
+}
+
+async fn get_user_permission_group_ids(user_id: &Uuid) -> Result<HashSet<Uuid>> {
+    let mut conn = get_pg_pool().get().await.context("DB Error")?;
+
+    let direct = permission_groups_to_identities::table
+        .filter(permission_groups_to_identities::identity_id.eq(user_id))
+        .filter(permission_groups_to_identities::identity_type.eq(IdentityType::User))
+        .filter(permission_groups_to_identities::deleted_at.is_null())
+        .select(permission_groups_to_identities::permission_group_id)
+        .load::<Uuid>(&mut conn)
+        .await
+        .context("Failed to fetch user permission groups")?;
+
+    let via_teams = permission_groups_to_identities::table
+        .inner_join(
+            teams_to_users::table.on(permission_groups_to_identities::identity_id
+                .eq(teams_to_users::team_id)
+                .and(teams_to_users::user_id.eq(user_id))
+                .and(teams_to_users::deleted_at.is_null())),
+        )
+        .filter(permission_groups_to_identities::identity_type.eq(IdentityType::Team))
+        .filter(permission_groups_to_identities::deleted_at.is_null())
+        .select(permission_groups_to_identities::permission_group_id)
+        .load::<Uuid>(&mut conn)
+        .await
+        .context("Failed to fetch team permission groups")?;
+
+    Ok(direct.into_iter().chain(via_teams).collect())
+}
+
+async fn get_user_dataset_group_ids(
+    user_id: &Uuid,
+    permission_group_ids: &HashSet<Uuid>,
+) -> Result<HashSet<Uuid>> {
+    let mut conn = get_pg_pool().get().await.context("DB Error")?;
+    let permission_group_ids: Vec<Uuid> = permission_group_ids.iter().copied().collect();
+
+    let dataset_group_ids = dataset_groups_permissions::table
+        .filter(dataset_groups_permissions::deleted_at.is_null())
+        .filter(
+            dataset_groups_permissions::permission_type
+                .eq("user")
+                .and(dataset_groups_permissions::permission_id.eq(user_id))
+                .or(dataset_groups_permissions::permission_type
+                    .eq("permission_group")
+                    .and(dataset_groups_permissions::permission_id.eq_any(permission_group_ids))),
+        )
+        .select(dataset_groups_permissions::dataset_group_id)
+        .load::<Uuid>(&mut conn)
+        .await
+        .context("Failed to fetch user dataset groups")?;
+
+    Ok(dataset_group_ids.into_iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stricter_policy_wins() {
+        assert_eq!(
+            stricter((ColumnMaskPolicy::Hash, None), (ColumnMaskPolicy::Hide, None)).0,
+            ColumnMaskPolicy::Hide
+        );
+        assert_eq!(
+            stricter((ColumnMaskPolicy::Redact, None), (ColumnMaskPolicy::Partial, Some(4))).0,
+            ColumnMaskPolicy::Redact
+        );
+    }
+
+    #[test]
+    fn test_partial_policies_reveal_fewest_chars() {
+        assert_eq!(
+            stricter(
+                (ColumnMaskPolicy::Partial, Some(4)),
+                (ColumnMaskPolicy::Partial, Some(2))
+            ),
+            (ColumnMaskPolicy::Partial, Some(2))
+        );
+    }
+}
@@ -1,5 +1,11 @@
 //! Library for handling dataset security and permissions.
 
+pub mod column_masking;
+pub mod row_level_security;
+
+pub use column_masking::get_column_masks;
+pub use row_level_security::get_row_level_filters;
+
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use database::{
@@ -0,0 +1,292 @@
+//! Row-level security built from per-dataset filter templates and user attributes.
+//!
+//! Admins attach templates such as `region = {{user.region}}` to a dataset. At query time
+//! each placeholder is replaced with the querying user's attribute, rendered as a SQL
+//! literal escaped for the data source's dialect, and the resulting predicates are keyed
+//! by table name so they can be handed to `sql_analyzer::apply_row_level_filters`.
+
+use anyhow::{anyhow, Context, Result};
+use database::{
+    enums::{DataSourceType, UserOrganizationRole},
+    pool::get_pg_pool,
+    schema::{data_sources, dataset_row_filters, datasets, users, users_to_organizations},
+};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_json::Value;
+use sql_analyzer::literal;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+static TEMPLATE_PLACEHOLDER: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\{\{\s*user\.([A-Za-z0-9_]+)\s*\}\}").unwrap());
+
+/// Returns the rendered row-level filters that apply to `user_id` for queries against
+/// `data_source_id`, keyed by table (dataset) name.
+///
+/// Workspace and data admins are exempt and always get an empty map. Any template that
+/// references an attribute the user doesn't have is an error, so queries fail closed rather
+/// than running unfiltered.
+pub async fn get_row_level_filters(
+    user_id: &Uuid,
+    data_source_id: &Uuid,
+) -> Result<HashMap<String, String>> {
+    let mut conn = get_pg_pool().get().await.context("DB Error")?;
+
+    let templates = dataset_row_filters::table
+        .inner_join(datasets::table.on(dataset_row_filters::dataset_id.eq(datasets::id)))
+        .inner_join(data_sources::table.on(datasets::data_source_id.eq(data_sources::id)))
+        .filter(datasets::data_source_id.eq(data_source_id))
+        .filter(datasets::deleted_at.is_null())
+        .filter(dataset_row_filters::deleted_at.is_null())
+        .select((
+            datasets::name,
+            datasets::organization_id,
+            data_sources::type_,
+            dataset_row_filters::filter_template,
+        ))
+        .load::<(String, Uuid, DataSourceType, String)>(&mut conn)
+        .await
+        .context("Failed to load dataset row filters")?;
+
+    if templates.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    // All datasets on a data source belong to the same organization
+    let organization_id = templates[0].1;
+
+    if is_exempt_from_data_policies(user_id, &organization_id).await? {
+        return Ok(HashMap::new());
+    }
+
+    let attributes = users::table
+        .filter(users::id.eq(user_id))
+        .select(users::attributes)
+        .first::<Value>(&mut conn)
+        .await
+        .context("Failed to fetch user attributes")?;
+
+    let mut filters: HashMap<String, String> = HashMap::new();
+
+    for (dataset_name, _, data_source_type, template) in templates {
+        let rendered = render_filter_template(&template, &attributes, data_source_type)
+            .with_context(|| format!("Invalid row-level filter on dataset '{}'", dataset_name))?;
+
+        // Multiple templates on the same dataset must all hold
+        filters
+            .entry(dataset_name)
+            .and_modify(|existing| *existing = format!("({}) AND ({})", existing, rendered))
+            .or_insert(rendered);
+    }
+
+    Ok(filters)
+}
+
+/// Workspace and data admins manage data policies and are never restricted by them.
+pub(crate) async fn is_exempt_from_data_policies(
+    user_id: &Uuid,
+    organization_id: &Uuid,
+) -> Result<bool> {
+    let mut conn = get_pg_pool().get().await.context("DB Error")?;
+
+    let role = users_to_organizations::table
+        .filter(users_to_organizations::user_id.eq(user_id))
+        .filter(users_to_organizations::organization_id.eq(organization_id))
+        .filter(users_to_organizations::deleted_at.is_null())
+        .select(users_to_organizations::role)
+        .first::<UserOrganizationRole>(&mut conn)
+        .await
+        .optional()
+        .context("Failed to fetch user organization role")?;
+
+    Ok(matches!(
+        role,
+        Some(UserOrganizationRole::WorkspaceAdmin) | Some(UserOrganizationRole::DataAdmin)
+    ))
+}
+
+/// Replaces every `{{user.<attribute>}}` placeholder in `template` with the attribute value
+/// rendered as a SQL literal for `data_source_type`. Arrays render as a parenthesised list for
+/// use with `IN`.
+pub fn render_filter_template(
+    template: &str,
+    attributes: &Value,
+    data_source_type: DataSourceType,
+) -> Result<String> {
+    let mut rendered = String::with_capacity(template.len());
+    let mut last_end = 0;
+
+    for captures in TEMPLATE_PLACEHOLDER.captures_iter(template) {
+        let placeholder = captures.get(0).unwrap();
+        let attribute = &captures[1];
+
+        let value = attributes
+            .get(attribute)
+            .filter(|value| !value.is_null())
+            .ok_or_else(|| anyhow!("User is missing attribute '{}'", attribute))?;
+
+        rendered.push_str(&template[last_end..placeholder.start()]);
+        rendered.push_str(&to_sql_literal(value, attribute, data_source_type)?);
+        last_end = placeholder.end();
+    }
+
+    rendered.push_str(&template[last_end..]);
+
+    Ok(rendered)
+}
+
+/// Checks that a template is non-empty and that every `{{ ... }}` block is a well-formed
+/// `{{user.<attribute>}}` placeholder. Used when admins save templates.
+pub fn validate_filter_template(template: &str) -> Result<()> {
+    if template.trim().is_empty() {
+        return Err(anyhow!("Row-level filter template cannot be empty"));
+    }
+
+    let without_placeholders = TEMPLATE_PLACEHOLDER.replace_all(template, "");
+    if without_placeholders.contains("{{") || without_placeholders.contains("}}") {
+        return Err(anyhow!(
+            "Row-level filter placeholders must have the form {{{{user.<attribute>}}}}"
+        ));
+    }
+
+    Ok(())
+}
+
+fn to_sql_literal(
+    value: &Value,
+    attribute: &str,
+    data_source_type: DataSourceType,
+) -> Result<String> {
+    match value {
+        Value::String(_) | Value::Number(_) | Value::Bool(_) => {
+            Ok(literal(value, data_source_type.to_str())?)
+        }
+        Value::Array(items) if !items.is_empty() => {
+            let literals = items
+                .iter()
+                .map(|item| match item {
+                    Value::Array(_) | Value::Object(_) | Value::Null => Err(anyhow!(
+                        "Attribute '{}' must be a list of scalar values",
+                        attribute
+                    )),
+                    scalar => to_sql_literal(scalar, attribute, data_source_type),
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Ok(format!("({})", literals.join(", ")))
+        }
+        Value::Array(_) => Err(anyhow!("Attribute '{}' is an empty list", attribute)),
+        Value::Object(_) | Value::Null => Err(anyhow!(
+            "Attribute '{}' cannot be used in a row-level filter",
+            attribute
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_render_string_attribute() {
+        let attributes = json!({ "region": "west" });
+
+        let rendered = render_filter_template(
+            "region = {{user.region}}",
+            &attributes,
+            DataSourceType::Postgres,
+        )
+        .unwrap();
+
+        assert_eq!(rendered, "region = 'west'");
+    }
+
+    #[test]
+    fn test_render_escapes_quotes() {
+        let attributes = json!({ "region": "o'west' OR 1=1 --" });
+
+        let rendered = render_filter_template(
+            "region = {{ user.region }}",
+            &attributes,
+            DataSourceType::Postgres,
+        )
+        .unwrap();
+
+        assert_eq!(rendered, "region = 'o''west'' OR 1=1 --'");
+    }
+
+    #[test]
+    fn test_render_escapes_per_dialect() {
+        let attributes = json!({ "region": "west\\", "internal": true });
+        let template = "region = {{user.region}} OR is_internal = {{user.internal}}";
+
+        assert_eq!(
+            render_filter_template(template, &attributes, DataSourceType::MySql).unwrap(),
+            r"region = 'west\\' OR is_internal = TRUE"
+        );
+        assert_eq!(
+            render_filter_template(template, &attributes, DataSourceType::Snowflake).unwrap(),
+            r"region = 'west\\' OR is_internal = TRUE"
+        );
+        assert_eq!(
+            render_filter_template(template, &attributes, DataSourceType::SqlServer).unwrap(),
+            r"region = 'west\' OR is_internal = 1"
+        );
+    }
+
+    #[test]
+    fn test_render_multiple_and_list_attributes() {
+        let attributes = json!({ "regions": ["west", "east"], "tier": 2, "internal": false });
+
+        let rendered = render_filter_template(
+            "region IN {{user.regions}} AND tier <= {{user.tier}} AND is_internal = {{user.internal}}",
+            &attributes,
+            DataSourceType::Postgres,
+        )
+        .unwrap();
+
+        assert_eq!(
+            rendered,
+            "region IN ('west', 'east') AND tier <= 2 AND is_internal = FALSE"
+        );
+    }
+
+    #[test]
+    fn test_render_missing_attribute_fails_closed() {
+        let attributes = json!({ "user_email": "manager@example.com" });
+
+        let result = render_filter_template(
+            "region = {{user.region}}",
+            &attributes,
+            DataSourceType::Postgres,
+        );
+
+        assert!(
+            result.is_err(),
+            "Missing attributes must not render an empty filter"
+        );
+    }
+
+    #[test]
+    fn test_validate_filter_template() {
+        assert!(validate_filter_template("region = {{ user.region }}").is_ok());
+        assert!(validate_filter_template("region = {{user.region").is_err());
+        assert!(validate_filter_template("region = {{org.region}}").is_err());
+        assert!(validate_filter_template("   ").is_err());
+    }
+
+    #[test]
+    fn test_render_rejects_object_attribute() {
+        let attributes = json!({ "region": { "name": "west" } });
+
+        assert!(render_filter_template(
+            "region = {{user.region}}",
+            &attributes,
+            DataSourceType::Postgres
+        )
+        .is_err());
+    }
+}
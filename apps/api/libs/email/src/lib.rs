@@ -4,6 +4,7 @@
 
 pub use anyhow::{Result, Error};
 
+pub mod report;
 pub mod resend;
 // // pub mod models; // Consider moving structs like CollectionInvite etc. here if they grow complex
 // // pub mod utils;
@@ -11,6 +12,7 @@ pub mod resend;
 
 // Re-exports public API from the resend module
 pub use resend::{send_email, EmailType, CollectionInvite, DashboardInvite, ThreadInvite, InviteToBuster};
+pub use report::{send_report_email, ReportAttachment, ReportEmail, ReportTable};
 
 // // Example placeholder for where the resend logic might go
 // pub async fn resend_email(/* parameters */) -> Result<()> {
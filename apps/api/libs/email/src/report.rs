@@ -0,0 +1,242 @@
+//! Report emails for scheduled deliveries of metrics and dashboards.
+//!
+//! Reports go out through Resend, or through an SMTP server when `SMTP_HOST` is set.
+
+use anyhow::{anyhow, Context, Result};
+use html_escape::encode_text as escape_html;
+use lettre::message::{header::ContentType, Attachment, MultiPart, SinglePart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use resend_rs::types::{CreateAttachment, CreateEmailBaseOptions};
+use resend_rs::Resend;
+use std::env;
+
+const DEFAULT_FROM: &str = "Buster <buster@mail.buster.so>";
+
+const REPORT_TEMPLATE: &str = include_str!("report_template.html");
+
+/// A table of (already formatted) values shown in the body of a report email
+#[derive(Debug, Clone)]
+pub struct ReportTable {
+    pub title: String,
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+    /// Rows of the result left out of the table
+    pub omitted_rows: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReportAttachment {
+    pub file_name: String,
+    pub content_type: String,
+    pub content: Vec<u8>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReportEmail {
+    pub subject: String,
+    pub title: String,
+    pub tables: Vec<ReportTable>,
+    /// Where to view the asset in the app
+    pub link: Option<String>,
+    pub attachments: Vec<ReportAttachment>,
+}
+
+/// Sends a report to each address separately. Unlike invites this waits for the emails to
+/// be accepted, so failed deliveries can be recorded.
+pub async fn send_report_email(to_addresses: &[String], email: &ReportEmail) -> Result<()> {
+    if to_addresses.is_empty() {
+        return Err(anyhow!("Report email has no recipients"));
+    }
+
+    let html = render_report_html(email);
+    let from = env::var("EMAIL_FROM").unwrap_or_else(|_| DEFAULT_FROM.to_string());
+
+    let mut failed = Vec::new();
+    for to_address in to_addresses {
+        let result = match env::var("SMTP_HOST") {
+            Ok(host) => send_with_smtp(&host, &from, to_address, email, &html).await,
+            Err(_) => send_with_resend(&from, to_address, email, &html).await,
+        };
+        if let Err(e) = result {
+            tracing::error!(error = %e, email_recipient = %to_address, "Error sending report email");
+            failed.push(to_address.as_str());
+        }
+    }
+
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "Failed to send report email to {} of {} recipients: {}",
+            failed.len(),
+            to_addresses.len(),
+            failed.join(", ")
+        ))
+    }
+}
+
+async fn send_with_resend(
+    from: &str,
+    to_address: &str,
+    email: &ReportEmail,
+    html: &str,
+) -> Result<()> {
+    let api_key = env::var("RESEND_API_KEY").context("RESEND_API_KEY or SMTP_HOST must be set")?;
+
+    let mut options =
+        CreateEmailBaseOptions::new(from, vec![to_address.to_string()], &email.subject)
+            .with_html(html);
+    for attachment in &email.attachments {
+        options = options.with_attachment(
+            CreateAttachment::from_content(attachment.content.clone())
+                .with_filename(&attachment.file_name),
+        );
+    }
+
+    Resend::new(&api_key).emails.send(options).await?;
+    Ok(())
+}
+
+/// SMTP settings come from `SMTP_HOST`, `SMTP_PORT` (587 by default), `SMTP_USERNAME`,
+/// `SMTP_PASSWORD` and `SMTP_TLS`: `starttls` (the default), `tls` or `none`.
+async fn send_with_smtp(
+    host: &str,
+    from: &str,
+    to_address: &str,
+    email: &ReportEmail,
+    html: &str,
+) -> Result<()> {
+    let mut body = MultiPart::mixed().singlepart(SinglePart::html(html.to_string()));
+    for attachment in &email.attachments {
+        let content_type = ContentType::parse(&attachment.content_type)
+            .map_err(|e| anyhow!("Invalid attachment content type: {}", e))?;
+        body = body.singlepart(
+            Attachment::new(attachment.file_name.clone())
+                .body(attachment.content.clone(), content_type),
+        );
+    }
+
+    let message = Message::builder()
+        .from(from.parse().context("Invalid EMAIL_FROM address")?)
+        .to(to_address
+            .parse()
+            .with_context(|| format!("Invalid email address '{}'", to_address))?)
+        .subject(&email.subject)
+        .multipart(body)?;
+
+    let port = match env::var("SMTP_PORT") {
+        Ok(port) => port.parse::<u16>().context("Invalid SMTP_PORT")?,
+        Err(_) => 587,
+    };
+    let mut transport = match env::var("SMTP_TLS").as_deref() {
+        Ok("none") => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host),
+        Ok("tls") => AsyncSmtpTransport::<Tokio1Executor>::relay(host)?,
+        _ => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)?,
+    }
+    .port(port);
+    if let Ok(username) = env::var("SMTP_USERNAME") {
+        let password = env::var("SMTP_PASSWORD").unwrap_or_default();
+        transport = transport.credentials(Credentials::new(username, password));
+    }
+
+    transport.build().send(message).await?;
+    Ok(())
+}
+
+fn render_report_html(email: &ReportEmail) -> String {
+    let mut tables = String::new();
+    for table in &email.tables {
+        tables.push_str(&render_table_html(table));
+    }
+
+    let button = match &email.link {
+        Some(link) => format!(
+            r#"<p style="margin-top:24px"><a href="{}" style="background-color:#000000; border-radius:4px; color:#ffffff; display:inline-block; padding:8px 16px; text-decoration:none;">Open in Buster</a></p>"#,
+            html_escape::encode_double_quoted_attribute(link)
+        ),
+        None => String::new(),
+    };
+
+    REPORT_TEMPLATE
+        .replace("{{title}}", &escape_html(&email.title))
+        .replace("{{tables}}", &tables)
+        .replace("{{button}}", &button)
+}
+
+fn render_table_html(table: &ReportTable) -> String {
+    let cell = "padding:6px 10px; border-bottom:1px solid #e5e5e5; text-align:left;";
+    let mut html = format!(
+        r#"<h3 style="margin:24px 0 8px 0; font-size:15px;">{}</h3>"#,
+        escape_html(&table.title)
+    );
+
+    if table.columns.is_empty() {
+        html.push_str(r#"<p style="color:#737373">No data</p>"#);
+        return html;
+    }
+
+    html.push_str(r#"<table style="border-collapse:collapse; width:100%; font-size:13px;"><tr>"#);
+    for column in &table.columns {
+        html.push_str(&format!(
+            r#"<th style="{} background-color:#fafafa;">{}</th>"#,
+            cell,
+            escape_html(column)
+        ));
+    }
+    html.push_str("</tr>");
+    for row in &table.rows {
+        html.push_str("<tr>");
+        for value in row {
+            html.push_str(&format!(
+                r#"<td style="{}">{}</td>"#,
+                cell,
+                escape_html(value)
+            ));
+        }
+        html.push_str("</tr>");
+    }
+    html.push_str("</table>");
+
+    if table.omitted_rows > 0 {
+        html.push_str(&format!(
+            r#"<p style="color:#737373; margin-top:6px;">{} more {} not shown</p>"#,
+            table.omitted_rows,
+            if table.omitted_rows == 1 {
+                "row"
+            } else {
+                "rows"
+            }
+        ));
+    }
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_report_html_escapes_values() {
+        let email = ReportEmail {
+            subject: "Weekly revenue".to_string(),
+            title: "Revenue <weekly>".to_string(),
+            tables: vec![ReportTable {
+                title: "By region".to_string(),
+                columns: vec!["Region".to_string(), "Revenue".to_string()],
+                rows: vec![vec!["<script>".to_string(), "$1,200".to_string()]],
+                omitted_rows: 3,
+            }],
+            link: Some("https://app.buster.so/app/dashboards/1?a=\"b\"".to_string()),
+            attachments: vec![],
+        };
+
+        let html = render_report_html(&email);
+        assert!(html.contains("Revenue &lt;weekly&gt;"));
+        assert!(html.contains("&lt;script&gt;"));
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("$1,200"));
+        assert!(html.contains("3 more rows not shown"));
+        assert!(html.contains("?a=&quot;b&quot;"));
+    }
+}
@@ -0,0 +1,272 @@
+//! Checking alerts: running the metric as the alert's owner, evaluating the condition and
+//! notifying when the alert's state changes.
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use database::{
+    enums::AssetType,
+    models::{MetricAlert, MetricAlertEvent},
+    pool::get_pg_pool,
+    schema::{metric_alert_events, metric_alerts},
+};
+use diesel::{ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+use middleware::auth::find_user_by_id;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use super::evaluate::{column_values, evaluate, resolve_threshold, state_change, Evaluation};
+use super::helpers::goal_line_value;
+use super::notification::AlertNotification;
+use super::types::{AlertChannel, AlertCondition, AlertEventKind, AlertState};
+use crate::metrics::{
+    get_metric_data_handler, get_metric_data_handler::get_metric_with_data_access,
+    GetMetricDataRequest,
+};
+use crate::subscriptions::delivery::{asset_link, post_json};
+use crate::subscriptions::helpers::next_run_after;
+use crate::subscriptions::report::webhook_signature;
+
+/// Rows fetched per check; the latest values are read from these. A metric that returns
+/// more can't be evaluated, since its latest rows may be among those left out.
+const DATA_ROW_LIMIT: i64 = 5000;
+
+/// Starts a check of every enabled alert that's due.
+///
+/// Each alert is claimed by moving its `next_run_at` to the following run, so an alert is
+/// only checked once per run even with several servers checking for due alerts. Returns
+/// the number of checks started.
+pub async fn run_due_alerts() -> Result<usize> {
+    let mut conn = get_pg_pool()
+        .get()
+        .await
+        .context("Failed to get DB connection for scanning due alerts")?;
+
+    let now = Utc::now();
+    let due = metric_alerts::table
+        .filter(metric_alerts::enabled.eq(true))
+        .filter(metric_alerts::deleted_at.is_null())
+        .filter(metric_alerts::next_run_at.le(now))
+        .load::<MetricAlert>(&mut conn)
+        .await
+        .context("Failed to load due alerts")?;
+
+    let mut started = 0;
+    for alert in due {
+        let next_run_at = match next_run_after("alert", &alert.schedule, &alert.timezone, now) {
+            Ok(next_run_at) => Some(next_run_at),
+            Err(e) => {
+                warn!(alert_id = %alert.id, "Alert will not be rescheduled: {}", e);
+                None
+            }
+        };
+
+        let claimed = diesel::update(metric_alerts::table.find(alert.id))
+            .filter(metric_alerts::next_run_at.eq(alert.next_run_at))
+            .set(metric_alerts::next_run_at.eq(next_run_at))
+            .execute(&mut conn)
+            .await
+            .with_context(|| format!("Failed to claim alert {}", alert.id))?;
+        if claimed == 0 {
+            continue;
+        }
+
+        started += 1;
+        tokio::spawn(async move {
+            if let Err(e) = check_alert(&alert).await {
+                error!(alert_id = %alert.id, "Failed to record alert check: {}", e);
+            }
+        });
+    }
+
+    if started > 0 {
+        info!("Started {} due alert checks", started);
+    }
+    Ok(started)
+}
+
+/// Checks an alert, records its new state and notifies if the state changed.
+///
+/// A failed check puts the alert in the `error` state rather than returning an error;
+/// errors are only returned when the alert can't be updated.
+pub(crate) async fn check_alert(alert: &MetricAlert) -> Result<MetricAlert> {
+    let previous_state = AlertState::parse(&alert.state).unwrap_or(AlertState::Ok);
+    let checked_at = Utc::now();
+
+    let (state, value, message, metric_name, threshold) = match evaluate_alert(alert).await {
+        Ok((evaluation, metric_name, threshold)) => (
+            if evaluation.triggered {
+                AlertState::Triggered
+            } else {
+                AlertState::Ok
+            },
+            Some(evaluation.value),
+            evaluation.message,
+            Some(metric_name),
+            Some(threshold),
+        ),
+        Err(e) => {
+            warn!(alert_id = %alert.id, "Alert check failed: {:?}", e);
+            (
+                AlertState::Error,
+                None,
+                format!("{:#}", e),
+                None,
+                alert.threshold,
+            )
+        }
+    };
+
+    let event_kind = state_change(previous_state, state);
+    if let Some(kind) = event_kind {
+        let mut event = MetricAlertEvent {
+            id: Uuid::new_v4(),
+            alert_id: alert.id,
+            kind: kind.as_str().to_string(),
+            value,
+            message: message.clone(),
+            notified: false,
+            notification_error: None,
+            created_at: checked_at,
+        };
+
+        if kind != AlertEventKind::Resolved || alert.notify_on_resolve {
+            let notification = AlertNotification {
+                event_id: event.id,
+                kind,
+                alert_id: alert.id,
+                alert_name: alert.name.clone(),
+                metric_id: alert.metric_file_id,
+                metric_name,
+                column: alert.column_name.clone(),
+                condition: AlertCondition::parse(&alert.condition).unwrap_or(AlertCondition::Above),
+                threshold,
+                value,
+                message: message.clone(),
+                link: asset_link(AssetType::MetricFile, &alert.metric_file_id),
+                checked_at,
+            };
+            match notify(alert, &notification).await {
+                Ok(()) => event.notified = true,
+                Err(e) => {
+                    error!(alert_id = %alert.id, event_id = %event.id, "Alert notification failed: {:?}", e);
+                    event.notification_error = Some(format!("{:#}", e));
+                }
+            }
+        }
+
+        info!(alert_id = %alert.id, event = kind.as_str(), notified = event.notified, "Alert changed state");
+
+        let mut conn = get_pg_pool().get().await?;
+        diesel::insert_into(metric_alert_events::table)
+            .values(&event)
+            .execute(&mut conn)
+            .await
+            .context("Failed to record alert event")?;
+    }
+
+    let last_triggered_at: Option<DateTime<Utc>> = match event_kind {
+        Some(AlertEventKind::Triggered) => Some(checked_at),
+        _ => alert.last_triggered_at,
+    };
+
+    let mut conn = get_pg_pool().get().await?;
+    diesel::update(metric_alerts::table.find(alert.id))
+        .set((
+            metric_alerts::state.eq(state.as_str()),
+            metric_alerts::last_value.eq(value.or(alert.last_value)),
+            metric_alerts::last_message.eq(Some(&message)),
+            metric_alerts::last_checked_at.eq(Some(checked_at)),
+            metric_alerts::last_triggered_at.eq(last_triggered_at),
+        ))
+        .get_result::<MetricAlert>(&mut conn)
+        .await
+        .context("Failed to record alert check")
+}
+
+/// Runs the metric as the alert's owner, so alerts never act on data the owner can't see.
+/// Returns the evaluation with the metric's name and the threshold that was used.
+async fn evaluate_alert(alert: &MetricAlert) -> Result<(Evaluation, String, f64)> {
+    let owner = find_user_by_id(&alert.owner_id)
+        .await
+        .context("Failed to load the alert's owner")?
+        .ok_or_else(|| anyhow!("The alert's owner no longer exists"))?;
+    if !owner
+        .organizations
+        .iter()
+        .any(|org| org.id == alert.organization_id)
+    {
+        return Err(anyhow!(
+            "The alert's owner is no longer a member of the workspace"
+        ));
+    }
+
+    let condition = AlertCondition::parse(&alert.condition)
+        .ok_or_else(|| anyhow!("Unknown alert condition '{}'", alert.condition))?;
+
+    let metric = get_metric_with_data_access(&alert.metric_file_id, &owner, None, None).await?;
+    // The goal line is read on every check, so moving it on the chart moves the alert too
+    let threshold = resolve_threshold(condition, alert.threshold, goal_line_value(&metric))?;
+
+    let data = get_metric_data_handler(
+        GetMetricDataRequest {
+            metric_id: metric.id,
+            version_number: None,
+            limit: Some(DATA_ROW_LIMIT),
+            password: None,
+            force_refresh: true,
+            cursor: None,
+            parameters: Default::default(),
+            dashboard_id: None,
+            dashboard_filters: Default::default(),
+        },
+        owner,
+    )
+    .await
+    .with_context(|| format!("Failed to run metric '{}'", metric.name))?;
+
+    if data.has_more_records {
+        return Err(anyhow!(
+            "Metric '{}' returns more than {} rows, so its latest value can't be read. \
+             Aggregate or filter the metric to check it with an alert",
+            metric.name,
+            DATA_ROW_LIMIT
+        ));
+    }
+
+    let values = column_values(&data.data, &alert.column_name, alert.time_column.as_deref())?;
+    let evaluation = evaluate(condition, threshold, &alert.column_name, &values)?;
+    Ok((evaluation, metric.name, threshold))
+}
+
+async fn notify(alert: &MetricAlert, notification: &AlertNotification) -> Result<()> {
+    let channel = AlertChannel::parse(&alert.channel)
+        .ok_or_else(|| anyhow!("Unknown alert channel '{}'", alert.channel))?;
+
+    match channel {
+        AlertChannel::Email => {
+            email::send_report_email(&alert.recipients, &notification.email()).await
+        }
+        AlertChannel::Webhook => {
+            let target_url = alert
+                .target_url
+                .as_deref()
+                .ok_or_else(|| anyhow!("The alert has no target_url"))?;
+            let secret = alert
+                .signing_secret
+                .as_deref()
+                .ok_or_else(|| anyhow!("The alert has no signing secret"))?;
+            let body = serde_json::to_vec(&notification.webhook_payload())?;
+            let signature = webhook_signature(secret, Utc::now().timestamp(), &body);
+            post_json(
+                target_url,
+                body,
+                &[
+                    ("X-Buster-Signature", signature),
+                    ("X-Buster-Event", notification.event_id.to_string()),
+                ],
+            )
+            .await
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use anyhow::{anyhow, Result};
+use database::pool::get_pg_pool;
+use middleware::types::AuthenticatedUser;
+use uuid::Uuid;
+
+use super::check::check_alert;
+use super::helpers::{check_alert_write_access, find_alert};
+use super::types::AlertResponse;
+
+/// Handler for checking an alert right away. The schedule is unaffected. A state change
+/// is notified as it would be on schedule; a failed check puts the alert in the `error`
+/// state rather than returning an error.
+pub async fn check_alert_handler(
+    user: &AuthenticatedUser,
+    alert_id: &Uuid,
+) -> Result<AlertResponse> {
+    // Verify user has an organization
+    if user.organizations.is_empty() {
+        return Err(anyhow!("User is not a member of any organization"));
+    }
+
+    let user_org = &user.organizations[0];
+
+    let mut conn = get_pg_pool().get().await?;
+    let alert = find_alert(&mut conn, alert_id, &user_org.id).await?;
+    check_alert_write_access(user, &user_org.role, &alert)?;
+    drop(conn);
+
+    let alert = check_alert(&alert).await?;
+    Ok(AlertResponse::from(alert))
+}
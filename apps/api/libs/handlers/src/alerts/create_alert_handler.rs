@@ -0,0 +1,141 @@
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use database::{models::MetricAlert, pool::get_pg_pool, schema::metric_alerts};
+use diesel_async::RunQueryDsl;
+use middleware::types::AuthenticatedUser;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use super::evaluate::resolve_threshold;
+use super::helpers::{goal_line_value, normalize_alert_name, validate_alert_columns};
+use super::types::{AlertChannel, AlertCondition, AlertResponse, AlertState};
+use crate::metrics::get_metric_data_handler::get_metric_with_data_access;
+use crate::subscriptions::helpers::{
+    generate_signing_secret, normalize_recipients, validate_destination, validate_schedule,
+};
+
+/// Request for creating an alert on a metric
+#[derive(Debug, Deserialize)]
+pub struct CreateAlertRequest {
+    /// Defaults to the metric's name
+    pub name: Option<String>,
+    pub metric_id: Uuid,
+    /// The numeric result column to check
+    pub column: String,
+    /// Column to order the result by to find the latest value, e.g. a date
+    pub time_column: Option<String>,
+    pub condition: AlertCondition,
+    /// Optional for `above` and `below` when the metric has a goal line, and for `anomaly`
+    pub threshold: Option<f64>,
+    /// Cron expression for when to check, e.g. `0 * * * *` for hourly
+    pub schedule: String,
+    /// IANA time zone the schedule is in; UTC by default
+    pub timezone: Option<String>,
+    pub channel: AlertChannel,
+    #[serde(default)]
+    pub recipients: Vec<String>,
+    pub target_url: Option<String>,
+    /// Also notify when a triggered alert goes back to normal; true by default
+    pub notify_on_resolve: Option<bool>,
+}
+
+/// Handler for creating an alert owned by the user. The user must be able to see the
+/// metric, since checks run with their access.
+pub async fn create_alert_handler(
+    user: &AuthenticatedUser,
+    request: CreateAlertRequest,
+) -> Result<AlertResponse> {
+    // Verify user has an organization
+    if user.organizations.is_empty() {
+        return Err(anyhow!("User is not a member of any organization"));
+    }
+
+    let user_org = &user.organizations[0];
+
+    let timezone = request
+        .timezone
+        .map(|tz| tz.trim().to_string())
+        .unwrap_or_else(|| "UTC".to_string());
+    let now = Utc::now();
+    let next_run_at = validate_schedule("alert", &request.schedule, &timezone, now)?;
+
+    let recipients = normalize_recipients("alert", request.recipients)?;
+    let target_url = request.target_url.map(|url| url.trim().to_string());
+    validate_destination(
+        "alert",
+        request.channel.into(),
+        &recipients,
+        target_url.as_deref(),
+    )?;
+
+    let column = request.column.trim().to_string();
+    let time_column = request
+        .time_column
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty());
+    if column.is_empty() {
+        return Err(anyhow!("Invalid alert: column cannot be empty"));
+    }
+
+    let metric = get_metric_with_data_access(&request.metric_id, user, None, None).await?;
+    validate_alert_columns(&metric, &column, time_column.as_deref())?;
+    resolve_threshold(
+        request.condition,
+        request.threshold,
+        goal_line_value(&metric),
+    )?;
+
+    let name = match request.name {
+        Some(name) => normalize_alert_name(&name)?,
+        None => normalize_alert_name(&metric.name)?,
+    };
+
+    let signing_secret = (request.channel == AlertChannel::Webhook).then(generate_signing_secret);
+
+    let alert = MetricAlert {
+        id: Uuid::new_v4(),
+        organization_id: user_org.id,
+        owner_id: user.id,
+        metric_file_id: request.metric_id,
+        name,
+        column_name: column,
+        time_column,
+        condition: request.condition.as_str().to_string(),
+        threshold: request.threshold,
+        schedule: request.schedule.trim().to_string(),
+        timezone,
+        channel: request.channel.as_str().to_string(),
+        // Only email notifications use recipients, and only webhooks use a URL
+        recipients: match request.channel {
+            AlertChannel::Email => recipients,
+            AlertChannel::Webhook => Vec::new(),
+        },
+        target_url: match request.channel {
+            AlertChannel::Email => None,
+            AlertChannel::Webhook => target_url,
+        },
+        signing_secret: signing_secret.clone(),
+        notify_on_resolve: request.notify_on_resolve.unwrap_or(true),
+        enabled: true,
+        state: AlertState::Ok.as_str().to_string(),
+        last_value: None,
+        last_message: None,
+        last_checked_at: None,
+        last_triggered_at: None,
+        next_run_at: Some(next_run_at),
+        created_at: now,
+        updated_at: now,
+        deleted_at: None,
+    };
+
+    let mut conn = get_pg_pool().get().await?;
+    diesel::insert_into(metric_alerts::table)
+        .values(&alert)
+        .execute(&mut conn)
+        .await
+        .map_err(|e| anyhow!("Failed to create alert: {}", e))?;
+
+    let mut response = AlertResponse::from(alert);
+    response.signing_secret = signing_secret;
+    Ok(response)
+}
@@ -0,0 +1,38 @@
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use database::{pool::get_pg_pool, schema::metric_alerts};
+use diesel::ExpressionMethods;
+use diesel_async::RunQueryDsl;
+use middleware::types::AuthenticatedUser;
+use uuid::Uuid;
+
+use super::helpers::{check_alert_write_access, find_alert};
+
+/// Handler for deleting an alert. Its event history is kept.
+pub async fn delete_alert_handler(user: &AuthenticatedUser, alert_id: &Uuid) -> Result<()> {
+    // Verify user has an organization
+    if user.organizations.is_empty() {
+        return Err(anyhow!("User is not a member of any organization"));
+    }
+
+    let user_org = &user.organizations[0];
+
+    let mut conn = get_pg_pool().get().await?;
+
+    let alert = find_alert(&mut conn, alert_id, &user_org.id).await?;
+    check_alert_write_access(user, &user_org.role, &alert)?;
+
+    let now = Utc::now();
+    diesel::update(metric_alerts::table)
+        .filter(metric_alerts::id.eq(alert_id))
+        .set((
+            metric_alerts::deleted_at.eq(Some(now)),
+            metric_alerts::enabled.eq(false),
+            metric_alerts::updated_at.eq(now),
+        ))
+        .execute(&mut conn)
+        .await
+        .map_err(|e| anyhow!("Failed to delete alert: {}", e))?;
+
+    Ok(())
+}
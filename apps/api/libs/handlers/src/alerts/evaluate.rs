@@ -0,0 +1,385 @@
+//! Deciding whether an alert's condition holds for a metric's result.
+
+use std::cmp::Ordering;
+
+use anyhow::{anyhow, Result};
+use indexmap::IndexMap;
+use query_engine::data_types::DataType;
+
+use super::types::{AlertCondition, AlertEventKind, AlertState};
+use crate::metrics::export_formatting::{raw_value, ExportNumber};
+
+/// Standard deviations from the mean that count as an anomaly when no threshold is set
+pub(crate) const DEFAULT_ANOMALY_THRESHOLD: f64 = 3.0;
+
+/// Previous values the latest one is compared against for anomalies
+const ANOMALY_LOOKBACK: usize = 30;
+
+/// Fewer previous values than this don't say much about what's normal
+const MIN_ANOMALY_HISTORY: usize = 5;
+
+/// The result of checking an alert's condition against a metric's values
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Evaluation {
+    /// The latest value of the column
+    pub value: f64,
+    pub triggered: bool,
+    pub message: String,
+}
+
+/// The threshold a condition is checked against. `above` and `below` fall back to the
+/// metric's goal line, and `anomaly` to [`DEFAULT_ANOMALY_THRESHOLD`].
+pub(crate) fn resolve_threshold(
+    condition: AlertCondition,
+    threshold: Option<f64>,
+    goal_line: Option<f64>,
+) -> Result<f64> {
+    let threshold = match condition {
+        AlertCondition::Above | AlertCondition::Below => {
+            threshold.or(goal_line).ok_or_else(|| {
+                anyhow!("Invalid alert: a threshold is required when the metric has no goal line")
+            })?
+        }
+        AlertCondition::PercentChange => threshold.ok_or_else(|| {
+            anyhow!("Invalid alert: percent_change alerts need a threshold percentage")
+        })?,
+        AlertCondition::Anomaly => threshold.unwrap_or(DEFAULT_ANOMALY_THRESHOLD),
+    };
+
+    if !threshold.is_finite() {
+        return Err(anyhow!("Invalid alert: threshold must be a number"));
+    }
+    if matches!(
+        condition,
+        AlertCondition::PercentChange | AlertCondition::Anomaly
+    ) && threshold <= 0.0
+    {
+        return Err(anyhow!(
+            "Invalid alert: {} thresholds must be greater than zero",
+            condition.as_str()
+        ));
+    }
+    Ok(threshold)
+}
+
+/// The numeric values of `column`, oldest first. Rows are ordered by `time_column` when it's
+/// set and otherwise kept in the order the metric returned them; rows without a number in
+/// `column` are skipped.
+pub(crate) fn column_values(
+    data: &[IndexMap<String, DataType>],
+    column: &str,
+    time_column: Option<&str>,
+) -> Result<Vec<f64>> {
+    let Some(first) = data.first() else {
+        return Err(anyhow!("The metric returned no rows"));
+    };
+    let column = find_column(first, column)?;
+    let time_column = time_column
+        .map(|time_column| find_column(first, time_column))
+        .transpose()?;
+
+    let mut rows: Vec<&IndexMap<String, DataType>> = data.iter().collect();
+    if let Some(time_column) = time_column {
+        rows.sort_by(|a, b| {
+            compare_values(
+                a.get(time_column).unwrap_or(&DataType::Null),
+                b.get(time_column).unwrap_or(&DataType::Null),
+            )
+        });
+    }
+
+    let values: Vec<f64> = rows
+        .iter()
+        .filter_map(|row| row.get(column).and_then(ExportNumber::from_value))
+        .map(ExportNumber::to_f64)
+        .collect();
+    if values.is_empty() {
+        return Err(anyhow!("Column '{}' has no numeric values", column));
+    }
+    Ok(values)
+}
+
+/// The key for `column` in a result row, matching case-insensitively since databases
+/// differ in how they case unquoted names
+fn find_column<'a>(row: &'a IndexMap<String, DataType>, column: &str) -> Result<&'a str> {
+    row.keys()
+        .find(|key| key.as_str() == column)
+        .or_else(|| row.keys().find(|key| key.eq_ignore_ascii_case(column)))
+        .map(String::as_str)
+        .ok_or_else(|| anyhow!("Column '{}' is not in the metric's result", column))
+}
+
+/// Orders numbers numerically and everything else by its text, which sorts ISO dates and
+/// timestamps chronologically. Missing values sort first.
+fn compare_values(a: &DataType, b: &DataType) -> Ordering {
+    match (ExportNumber::from_value(a), ExportNumber::from_value(b)) {
+        (Some(a), Some(b)) => a.to_f64().total_cmp(&b.to_f64()),
+        _ => raw_value(a).cmp(&raw_value(b)),
+    }
+}
+
+/// Checks `condition` against the latest of `values`, which are ordered oldest first
+pub(crate) fn evaluate(
+    condition: AlertCondition,
+    threshold: f64,
+    column: &str,
+    values: &[f64],
+) -> Result<Evaluation> {
+    let Some((&value, previous)) = values.split_last() else {
+        return Err(anyhow!("Column '{}' has no numeric values", column));
+    };
+
+    let (triggered, message) = match condition {
+        AlertCondition::Above => (
+            value > threshold,
+            format!(
+                "{} is {}, {} {}",
+                column,
+                format_number(value),
+                if value > threshold {
+                    "above"
+                } else {
+                    "not above"
+                },
+                format_number(threshold)
+            ),
+        ),
+        AlertCondition::Below => (
+            value < threshold,
+            format!(
+                "{} is {}, {} {}",
+                column,
+                format_number(value),
+                if value < threshold {
+                    "below"
+                } else {
+                    "not below"
+                },
+                format_number(threshold)
+            ),
+        ),
+        AlertCondition::PercentChange => {
+            let Some(&last) = previous.last() else {
+                return Err(anyhow!(
+                    "percent_change alerts need at least two values of '{}'",
+                    column
+                ));
+            };
+            let change = if last == 0.0 {
+                if value == 0.0 {
+                    0.0
+                } else {
+                    f64::INFINITY.copysign(value)
+                }
+            } else {
+                (value - last) / last.abs() * 100.0
+            };
+            let message = if change.is_finite() {
+                format!(
+                    "{} changed {}{}% from {} to {}",
+                    column,
+                    if change > 0.0 { "+" } else { "" },
+                    format_number(change),
+                    format_number(last),
+                    format_number(value)
+                )
+            } else {
+                format!("{} changed from 0 to {}", column, format_number(value))
+            };
+            (change.abs() >= threshold, message)
+        }
+        AlertCondition::Anomaly => {
+            let history = &previous[previous.len().saturating_sub(ANOMALY_LOOKBACK)..];
+            if history.len() < MIN_ANOMALY_HISTORY {
+                return Err(anyhow!(
+                    "anomaly alerts need at least {} previous values of '{}', but the metric returned {}",
+                    MIN_ANOMALY_HISTORY,
+                    column,
+                    history.len()
+                ));
+            }
+            let mean = history.iter().sum::<f64>() / history.len() as f64;
+            let variance = history.iter().map(|v| (v - mean).powi(2)).sum::<f64>()
+                / (history.len() - 1) as f64;
+            let std_dev = variance.sqrt();
+            let z_score = if std_dev == 0.0 {
+                if value == mean {
+                    0.0
+                } else {
+                    f64::INFINITY
+                }
+            } else {
+                (value - mean).abs() / std_dev
+            };
+            let message = if z_score.is_finite() {
+                format!(
+                    "{} is {}, {} standard deviations from the recent average of {}",
+                    column,
+                    format_number(value),
+                    format_number(z_score),
+                    format_number(mean)
+                )
+            } else {
+                format!(
+                    "{} is {}, after {} values of {}",
+                    column,
+                    format_number(value),
+                    history.len(),
+                    format_number(mean)
+                )
+            };
+            (z_score >= threshold, message)
+        }
+    };
+
+    Ok(Evaluation {
+        value,
+        triggered,
+        message,
+    })
+}
+
+/// The event for an alert moving between states, if the move is worth a notification.
+/// Staying triggered doesn't notify again, and recovering from an error to `ok` is silent.
+pub(crate) fn state_change(previous: AlertState, next: AlertState) -> Option<AlertEventKind> {
+    match (previous, next) {
+        (AlertState::Ok | AlertState::Error, AlertState::Triggered) => {
+            Some(AlertEventKind::Triggered)
+        }
+        (AlertState::Triggered, AlertState::Ok) => Some(AlertEventKind::Resolved),
+        (AlertState::Ok | AlertState::Triggered, AlertState::Error) => Some(AlertEventKind::Error),
+        _ => None,
+    }
+}
+
+/// Rounds to two decimal places for messages
+pub(crate) fn format_number(value: f64) -> String {
+    let rounded = (value * 100.0).round() / 100.0;
+    // Avoid printing "-0"
+    if rounded == 0.0 {
+        "0".to_string()
+    } else {
+        rounded.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(day: &str, revenue: DataType) -> IndexMap<String, DataType> {
+        IndexMap::from([
+            ("day".to_string(), DataType::Text(Some(day.to_string()))),
+            ("revenue".to_string(), revenue),
+        ])
+    }
+
+    #[test]
+    fn test_column_values_orders_by_time_column() {
+        let data = vec![
+            row("2025-05-03", DataType::Int8(Some(30))),
+            row("2025-05-01", DataType::Float8(Some(10.5))),
+            row("2025-05-02", DataType::Null),
+        ];
+
+        assert_eq!(
+            column_values(&data, "REVENUE", Some("day")).unwrap(),
+            vec![10.5, 30.0]
+        );
+        assert_eq!(
+            column_values(&data, "revenue", None).unwrap(),
+            vec![30.0, 10.5]
+        );
+        assert!(column_values(&data, "profit", None).is_err());
+        assert!(column_values(&[], "revenue", None).is_err());
+    }
+
+    #[test]
+    fn test_evaluate_thresholds() {
+        let above = evaluate(AlertCondition::Above, 100.0, "revenue", &[90.0, 120.0]).unwrap();
+        assert!(above.triggered);
+        assert_eq!(above.value, 120.0);
+        assert_eq!(above.message, "revenue is 120, above 100");
+
+        let below = evaluate(AlertCondition::Below, 100.0, "revenue", &[120.0]).unwrap();
+        assert!(!below.triggered);
+        assert_eq!(below.message, "revenue is 120, not below 100");
+
+        let change = evaluate(
+            AlertCondition::PercentChange,
+            20.0,
+            "revenue",
+            &[200.0, 150.0],
+        )
+        .unwrap();
+        assert!(change.triggered);
+        assert_eq!(change.message, "revenue changed -25% from 200 to 150");
+        assert!(
+            !evaluate(
+                AlertCondition::PercentChange,
+                20.0,
+                "revenue",
+                &[200.0, 220.0]
+            )
+            .unwrap()
+            .triggered
+        );
+        assert!(evaluate(AlertCondition::PercentChange, 20.0, "revenue", &[200.0]).is_err());
+    }
+
+    #[test]
+    fn test_evaluate_anomaly() {
+        let history = [100.0, 102.0, 98.0, 101.0, 99.0];
+
+        let normal = [&history[..], &[101.0]].concat();
+        assert!(
+            !evaluate(AlertCondition::Anomaly, 3.0, "orders", &normal)
+                .unwrap()
+                .triggered
+        );
+
+        let spike = [&history[..], &[130.0]].concat();
+        let evaluation = evaluate(AlertCondition::Anomaly, 3.0, "orders", &spike).unwrap();
+        assert!(evaluation.triggered);
+        assert!(evaluation.message.contains("standard deviations"));
+
+        assert!(evaluate(AlertCondition::Anomaly, 3.0, "orders", &[1.0, 2.0, 3.0]).is_err());
+    }
+
+    #[test]
+    fn test_resolve_threshold_and_state_change() {
+        assert_eq!(
+            resolve_threshold(AlertCondition::Above, None, Some(500.0)).unwrap(),
+            500.0
+        );
+        assert_eq!(
+            resolve_threshold(AlertCondition::Above, Some(10.0), Some(500.0)).unwrap(),
+            10.0
+        );
+        assert!(resolve_threshold(AlertCondition::Below, None, None).is_err());
+        assert!(resolve_threshold(AlertCondition::PercentChange, None, None).is_err());
+        assert!(resolve_threshold(AlertCondition::Anomaly, Some(-1.0), None).is_err());
+        assert_eq!(
+            resolve_threshold(AlertCondition::Anomaly, None, None).unwrap(),
+            DEFAULT_ANOMALY_THRESHOLD
+        );
+
+        assert_eq!(
+            state_change(AlertState::Ok, AlertState::Triggered),
+            Some(AlertEventKind::Triggered)
+        );
+        assert_eq!(
+            state_change(AlertState::Triggered, AlertState::Triggered),
+            None
+        );
+        assert_eq!(
+            state_change(AlertState::Triggered, AlertState::Ok),
+            Some(AlertEventKind::Resolved)
+        );
+        assert_eq!(state_change(AlertState::Error, AlertState::Ok), None);
+        assert_eq!(
+            state_change(AlertState::Ok, AlertState::Error),
+            Some(AlertEventKind::Error)
+        );
+    }
+}
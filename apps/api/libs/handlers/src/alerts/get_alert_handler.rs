@@ -0,0 +1,39 @@
+use anyhow::{anyhow, Result};
+use database::{models::MetricAlertEvent, pool::get_pg_pool, schema::metric_alert_events};
+use diesel::{ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+use middleware::types::AuthenticatedUser;
+use uuid::Uuid;
+
+use super::helpers::{check_alert_write_access, find_alert};
+use super::types::{AlertEventResponse, AlertResponse};
+
+/// Events returned with an alert
+const RECENT_EVENTS: i64 = 20;
+
+/// Handler for getting an alert with its most recent state changes
+pub async fn get_alert_handler(user: &AuthenticatedUser, alert_id: &Uuid) -> Result<AlertResponse> {
+    // Verify user has an organization
+    if user.organizations.is_empty() {
+        return Err(anyhow!("User is not a member of any organization"));
+    }
+
+    let user_org = &user.organizations[0];
+
+    let mut conn = get_pg_pool().get().await?;
+
+    let alert = find_alert(&mut conn, alert_id, &user_org.id).await?;
+    check_alert_write_access(user, &user_org.role, &alert)?;
+
+    let events = metric_alert_events::table
+        .filter(metric_alert_events::alert_id.eq(alert.id))
+        .order(metric_alert_events::created_at.desc())
+        .limit(RECENT_EVENTS)
+        .load::<MetricAlertEvent>(&mut conn)
+        .await
+        .map_err(|e| anyhow!("Failed to load alert events: {}", e))?;
+
+    let mut response = AlertResponse::from(alert);
+    response.recent_events = Some(events.into_iter().map(AlertEventResponse::from).collect());
+    Ok(response)
+}
@@ -0,0 +1,104 @@
+use anyhow::{anyhow, Result};
+use database::{
+    enums::UserOrganizationRole, models::MetricAlert, schema::metric_alerts, types::SimpleType,
+};
+use diesel::{ExpressionMethods, QueryDsl};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use middleware::AuthenticatedUser;
+use uuid::Uuid;
+
+use crate::metrics::BusterMetric;
+
+const MAX_ALERT_NAME_LENGTH: usize = 255;
+
+/// Trims an alert name and rejects empty or oversized ones
+pub(crate) fn normalize_alert_name(name: &str) -> Result<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(anyhow!("Invalid alert: name cannot be empty"));
+    }
+    if name.chars().count() > MAX_ALERT_NAME_LENGTH {
+        return Err(anyhow!(
+            "Invalid alert: name cannot be longer than {} characters",
+            MAX_ALERT_NAME_LENGTH
+        ));
+    }
+    Ok(name.to_string())
+}
+
+/// The value of the first goal line shown on the metric's chart
+pub(crate) fn goal_line_value(metric: &BusterMetric) -> Option<f64> {
+    metric
+        .chart_config
+        .as_ref()?
+        .base()
+        .goal_lines
+        .as_ref()?
+        .iter()
+        .filter(|goal_line| goal_line.show != Some(false))
+        .find_map(|goal_line| goal_line.value)
+}
+
+/// Checks the columns an alert reads against the metric's last known result, when there
+/// is one. The value column must be numeric.
+pub(crate) fn validate_alert_columns(
+    metric: &BusterMetric,
+    column: &str,
+    time_column: Option<&str>,
+) -> Result<()> {
+    let Some(metadata) = &metric.data_metadata else {
+        return Ok(());
+    };
+    let find = |name: &str| {
+        metadata
+            .column_metadata
+            .iter()
+            .find(|c| c.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| {
+                anyhow!(
+                    "Invalid alert: column '{}' is not in the metric's result",
+                    name
+                )
+            })
+    };
+
+    if !matches!(find(column)?.simple_type, SimpleType::Number) {
+        return Err(anyhow!("Invalid alert: column '{}' is not numeric", column));
+    }
+    if let Some(time_column) = time_column {
+        find(time_column)?;
+    }
+    Ok(())
+}
+
+pub(crate) async fn find_alert(
+    conn: &mut AsyncPgConnection,
+    alert_id: &Uuid,
+    organization_id: &Uuid,
+) -> Result<MetricAlert> {
+    metric_alerts::table
+        .filter(metric_alerts::id.eq(alert_id))
+        .filter(metric_alerts::organization_id.eq(organization_id))
+        .filter(metric_alerts::deleted_at.is_null())
+        .first::<MetricAlert>(conn)
+        .await
+        .map_err(|e| match e {
+            diesel::result::Error::NotFound => anyhow!("Alert not found"),
+            e => anyhow!("Failed to load alert: {}", e),
+        })
+}
+
+/// Owners manage their own alerts; admins manage every alert in the workspace
+pub(crate) fn check_alert_write_access(
+    user: &AuthenticatedUser,
+    role: &UserOrganizationRole,
+    alert: &MetricAlert,
+) -> Result<()> {
+    match role {
+        UserOrganizationRole::WorkspaceAdmin | UserOrganizationRole::DataAdmin => Ok(()),
+        _ if alert.owner_id == user.id => Ok(()),
+        _ => Err(anyhow!(
+            "User does not have appropriate permissions to manage this alert"
+        )),
+    }
+}
@@ -0,0 +1,68 @@
+use anyhow::{anyhow, Result};
+use database::{
+    enums::UserOrganizationRole, models::MetricAlert, pool::get_pg_pool, schema::metric_alerts,
+};
+use diesel::{ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+use middleware::types::AuthenticatedUser;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use super::types::{AlertResponse, AlertState};
+
+/// Request for listing alerts
+#[derive(Debug, Deserialize)]
+pub struct ListAlertsRequest {
+    /// Only alerts on this metric
+    pub metric_id: Option<Uuid>,
+    /// Only alerts in this state
+    pub state: Option<AlertState>,
+    pub page: Option<i64>,
+    pub page_size: Option<i64>,
+}
+
+/// Handler for listing alerts, newest first. Admins see every alert in the workspace; other
+/// users see their own.
+pub async fn list_alerts_handler(
+    user: &AuthenticatedUser,
+    request: ListAlertsRequest,
+) -> Result<Vec<AlertResponse>> {
+    // Verify user has an organization
+    if user.organizations.is_empty() {
+        return Err(anyhow!("User is not a member of any organization"));
+    }
+
+    let user_org = &user.organizations[0];
+
+    let page = request.page.unwrap_or(0).max(0);
+    let page_size = request.page_size.unwrap_or(25).clamp(1, 100);
+
+    let mut conn = get_pg_pool().get().await?;
+
+    let mut query = metric_alerts::table
+        .filter(metric_alerts::organization_id.eq(user_org.id))
+        .filter(metric_alerts::deleted_at.is_null())
+        .into_boxed();
+    if !matches!(
+        user_org.role,
+        UserOrganizationRole::WorkspaceAdmin | UserOrganizationRole::DataAdmin
+    ) {
+        query = query.filter(metric_alerts::owner_id.eq(user.id));
+    }
+    if let Some(metric_id) = request.metric_id {
+        query = query.filter(metric_alerts::metric_file_id.eq(metric_id));
+    }
+    if let Some(state) = request.state {
+        query = query.filter(metric_alerts::state.eq(state.as_str()));
+    }
+
+    let rows = query
+        .order(metric_alerts::created_at.desc())
+        .limit(page_size)
+        .offset(page * page_size)
+        .load::<MetricAlert>(&mut conn)
+        .await
+        .map_err(|e| anyhow!("Failed to load alerts: {}", e))?;
+
+    Ok(rows.into_iter().map(AlertResponse::from).collect())
+}
@@ -0,0 +1,22 @@
+mod check;
+mod check_alert_handler;
+mod create_alert_handler;
+mod delete_alert_handler;
+mod evaluate;
+mod get_alert_handler;
+mod helpers;
+mod list_alerts_handler;
+mod notification;
+pub mod types;
+mod update_alert_handler;
+
+pub use check::run_due_alerts;
+pub use check_alert_handler::check_alert_handler;
+pub use create_alert_handler::{create_alert_handler, CreateAlertRequest};
+pub use delete_alert_handler::delete_alert_handler;
+pub use get_alert_handler::get_alert_handler;
+pub use list_alerts_handler::{list_alerts_handler, ListAlertsRequest};
+pub use types::{
+    AlertChannel, AlertCondition, AlertEventKind, AlertEventResponse, AlertResponse, AlertState,
+};
+pub use update_alert_handler::{update_alert_handler, UpdateAlertRequest};
@@ -0,0 +1,128 @@
+//! What an alert sends when its state changes, and how it's laid out for each channel.
+
+use chrono::{DateTime, Utc};
+use email::{ReportEmail, ReportTable};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use super::evaluate::format_number;
+use super::types::{AlertCondition, AlertEventKind};
+
+#[derive(Debug, Clone)]
+pub(crate) struct AlertNotification {
+    pub event_id: Uuid,
+    pub kind: AlertEventKind,
+    pub alert_id: Uuid,
+    pub alert_name: String,
+    pub metric_id: Uuid,
+    /// Unknown when the metric couldn't be loaded
+    pub metric_name: Option<String>,
+    pub column: String,
+    pub condition: AlertCondition,
+    pub threshold: Option<f64>,
+    pub value: Option<f64>,
+    pub message: String,
+    pub link: Option<String>,
+    pub checked_at: DateTime<Utc>,
+}
+
+impl AlertNotification {
+    fn headline(&self) -> String {
+        match self.kind {
+            AlertEventKind::Triggered => format!("Alert triggered: {}", self.alert_name),
+            AlertEventKind::Resolved => format!("Alert resolved: {}", self.alert_name),
+            AlertEventKind::Error => format!("Alert failed: {}", self.alert_name),
+        }
+    }
+
+    pub fn email(&self) -> ReportEmail {
+        let mut rows = Vec::new();
+        if let Some(metric_name) = &self.metric_name {
+            rows.push(vec!["Metric".to_string(), metric_name.clone()]);
+        }
+        rows.push(vec!["Column".to_string(), self.column.clone()]);
+        rows.push(vec![
+            "Condition".to_string(),
+            self.condition.as_str().replace('_', " "),
+        ]);
+        if let Some(threshold) = self.threshold {
+            rows.push(vec!["Threshold".to_string(), format_number(threshold)]);
+        }
+        if let Some(value) = self.value {
+            rows.push(vec!["Latest value".to_string(), format_number(value)]);
+        }
+        rows.push(vec![
+            "Checked at".to_string(),
+            self.checked_at.format("%Y-%m-%d %H:%M UTC").to_string(),
+        ]);
+
+        ReportEmail {
+            subject: self.headline(),
+            title: self.message.clone(),
+            tables: vec![ReportTable {
+                title: self.headline(),
+                columns: vec!["Detail".to_string(), "Value".to_string()],
+                rows,
+                omitted_rows: 0,
+            }],
+            link: self.link.clone(),
+            attachments: Vec::new(),
+        }
+    }
+
+    /// The JSON body POSTed to webhooks
+    pub fn webhook_payload(&self) -> Value {
+        json!({
+            "event_id": self.event_id,
+            "event": self.kind.as_str(),
+            "alert_id": self.alert_id,
+            "alert_name": self.alert_name,
+            "metric_id": self.metric_id,
+            "metric_name": self.metric_name,
+            "column": self.column,
+            "condition": self.condition.as_str(),
+            "threshold": self.threshold,
+            "value": self.value,
+            "message": self.message,
+            "link": self.link,
+            "checked_at": self.checked_at,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_alert_notification_layouts() {
+        let notification = AlertNotification {
+            event_id: Uuid::nil(),
+            kind: AlertEventKind::Triggered,
+            alert_id: Uuid::nil(),
+            alert_name: "Revenue drop".to_string(),
+            metric_id: Uuid::nil(),
+            metric_name: Some("Daily revenue".to_string()),
+            column: "revenue".to_string(),
+            condition: AlertCondition::Below,
+            threshold: Some(1000.0),
+            value: Some(812.456),
+            message: "revenue is 812.46, below 1000".to_string(),
+            link: None,
+            checked_at: Utc.with_ymd_and_hms(2025, 5, 14, 9, 0, 0).unwrap(),
+        };
+
+        let email = notification.email();
+        assert_eq!(email.subject, "Alert triggered: Revenue drop");
+        assert_eq!(email.title, "revenue is 812.46, below 1000");
+        assert!(email.tables[0]
+            .rows
+            .contains(&vec!["Latest value".to_string(), "812.46".to_string()]));
+
+        let payload = notification.webhook_payload();
+        assert_eq!(payload["event"], "triggered");
+        assert_eq!(payload["condition"], "below");
+        assert_eq!(payload["value"], 812.456);
+    }
+}
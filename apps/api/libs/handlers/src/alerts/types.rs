@@ -0,0 +1,206 @@
+use chrono::{DateTime, Utc};
+use database::models::{MetricAlert, MetricAlertEvent};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::subscriptions::SubscriptionChannel;
+
+/// What an alert checks the latest value of its column for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertCondition {
+    /// The value is greater than the threshold
+    Above,
+    /// The value is less than the threshold
+    Below,
+    /// The value changed by at least `threshold` percent from the previous row
+    PercentChange,
+    /// The value is at least `threshold` standard deviations from the previous rows' mean
+    Anomaly,
+}
+
+impl AlertCondition {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertCondition::Above => "above",
+            AlertCondition::Below => "below",
+            AlertCondition::PercentChange => "percent_change",
+            AlertCondition::Anomaly => "anomaly",
+        }
+    }
+
+    pub fn parse(condition: &str) -> Option<Self> {
+        [Self::Above, Self::Below, Self::PercentChange, Self::Anomaly]
+            .into_iter()
+            .find(|c| c.as_str() == condition)
+    }
+}
+
+/// Where an alert's notifications are sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertChannel {
+    /// To `recipients`, through Resend or SMTP
+    Email,
+    /// A JSON payload POSTed to `target_url`, signed with the alert's secret
+    Webhook,
+}
+
+impl AlertChannel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertChannel::Email => "email",
+            AlertChannel::Webhook => "webhook",
+        }
+    }
+
+    pub fn parse(channel: &str) -> Option<Self> {
+        [Self::Email, Self::Webhook]
+            .into_iter()
+            .find(|c| c.as_str() == channel)
+    }
+}
+
+impl From<AlertChannel> for SubscriptionChannel {
+    fn from(channel: AlertChannel) -> Self {
+        match channel {
+            AlertChannel::Email => SubscriptionChannel::Email,
+            AlertChannel::Webhook => SubscriptionChannel::Webhook,
+        }
+    }
+}
+
+/// The outcome of an alert's last check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertState {
+    Ok,
+    Triggered,
+    /// The metric couldn't be run or its value couldn't be read
+    Error,
+}
+
+impl AlertState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertState::Ok => "ok",
+            AlertState::Triggered => "triggered",
+            AlertState::Error => "error",
+        }
+    }
+
+    pub fn parse(state: &str) -> Option<Self> {
+        [Self::Ok, Self::Triggered, Self::Error]
+            .into_iter()
+            .find(|s| s.as_str() == state)
+    }
+}
+
+/// A change in an alert's state, which is recorded and notified
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertEventKind {
+    Triggered,
+    Resolved,
+    Error,
+}
+
+impl AlertEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertEventKind::Triggered => "triggered",
+            AlertEventKind::Resolved => "resolved",
+            AlertEventKind::Error => "error",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AlertResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub metric_id: Uuid,
+    pub owner_id: Uuid,
+    pub column: String,
+    pub time_column: Option<String>,
+    pub condition: String,
+    pub threshold: Option<f64>,
+    pub schedule: String,
+    pub timezone: String,
+    pub channel: String,
+    pub recipients: Vec<String>,
+    pub target_url: Option<String>,
+    pub notify_on_resolve: bool,
+    pub enabled: bool,
+    pub state: String,
+    pub last_value: Option<f64>,
+    pub last_message: Option<String>,
+    pub last_checked_at: Option<DateTime<Utc>>,
+    pub last_triggered_at: Option<DateTime<Utc>>,
+    pub next_run_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// Only returned when the alert is created; webhook payloads are signed with it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signing_secret: Option<String>,
+    /// Most recent events first; only returned for a single alert
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recent_events: Option<Vec<AlertEventResponse>>,
+}
+
+impl From<MetricAlert> for AlertResponse {
+    fn from(alert: MetricAlert) -> Self {
+        Self {
+            id: alert.id,
+            name: alert.name,
+            metric_id: alert.metric_file_id,
+            owner_id: alert.owner_id,
+            column: alert.column_name,
+            time_column: alert.time_column,
+            condition: alert.condition,
+            threshold: alert.threshold,
+            schedule: alert.schedule,
+            timezone: alert.timezone,
+            channel: alert.channel,
+            recipients: alert.recipients,
+            target_url: alert.target_url,
+            notify_on_resolve: alert.notify_on_resolve,
+            enabled: alert.enabled,
+            state: alert.state,
+            last_value: alert.last_value,
+            last_message: alert.last_message,
+            last_checked_at: alert.last_checked_at,
+            last_triggered_at: alert.last_triggered_at,
+            next_run_at: alert.next_run_at,
+            created_at: alert.created_at,
+            updated_at: alert.updated_at,
+            signing_secret: None,
+            recent_events: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AlertEventResponse {
+    pub id: Uuid,
+    pub kind: String,
+    pub value: Option<f64>,
+    pub message: String,
+    pub notified: bool,
+    pub notification_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<MetricAlertEvent> for AlertEventResponse {
+    fn from(event: MetricAlertEvent) -> Self {
+        Self {
+            id: event.id,
+            kind: event.kind,
+            value: event.value,
+            message: event.message,
+            notified: event.notified,
+            notification_error: event.notification_error,
+            created_at: event.created_at,
+        }
+    }
+}
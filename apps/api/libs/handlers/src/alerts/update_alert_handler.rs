@@ -0,0 +1,198 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use database::{models::MetricAlert, pool::get_pg_pool, schema::metric_alerts};
+use diesel::{AsChangeset, ExpressionMethods};
+use diesel_async::RunQueryDsl;
+use middleware::types::AuthenticatedUser;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use super::evaluate::resolve_threshold;
+use super::helpers::{
+    check_alert_write_access, find_alert, goal_line_value, normalize_alert_name,
+    validate_alert_columns,
+};
+use super::types::{AlertChannel, AlertCondition, AlertResponse, AlertState};
+use crate::metrics::get_metric_data_handler::get_metric_with_data_access;
+use crate::subscriptions::helpers::{
+    generate_signing_secret, normalize_recipients, validate_destination, validate_schedule,
+};
+
+/// Request for updating an alert. Omitted fields are left unchanged.
+#[derive(Debug, Deserialize)]
+pub struct UpdateAlertRequest {
+    pub name: Option<String>,
+    pub column: Option<String>,
+    pub time_column: Option<String>,
+    pub condition: Option<AlertCondition>,
+    pub threshold: Option<f64>,
+    /// Remove the threshold, so `above` and `below` follow the metric's goal line and
+    /// `anomaly` uses the default
+    #[serde(default)]
+    pub clear_threshold: bool,
+    pub schedule: Option<String>,
+    pub timezone: Option<String>,
+    pub channel: Option<AlertChannel>,
+    pub recipients: Option<Vec<String>>,
+    pub target_url: Option<String>,
+    pub notify_on_resolve: Option<bool>,
+    pub enabled: Option<bool>,
+    /// Replace the key webhook payloads are signed with; the new key is returned
+    #[serde(default)]
+    pub rotate_signing_secret: bool,
+}
+
+#[derive(AsChangeset)]
+#[diesel(table_name = metric_alerts)]
+#[diesel(treat_none_as_null = true)]
+struct AlertChangeset {
+    name: String,
+    column_name: String,
+    time_column: Option<String>,
+    condition: String,
+    threshold: Option<f64>,
+    schedule: String,
+    timezone: String,
+    channel: String,
+    recipients: Vec<String>,
+    target_url: Option<String>,
+    signing_secret: Option<String>,
+    notify_on_resolve: bool,
+    enabled: bool,
+    state: String,
+    next_run_at: Option<DateTime<Utc>>,
+    updated_at: DateTime<Utc>,
+}
+
+/// Handler for updating an alert. Changing what's checked resets the alert to `ok`, so the
+/// next check notifies if the new condition holds. The next run is worked out again from
+/// the schedule.
+pub async fn update_alert_handler(
+    user: &AuthenticatedUser,
+    alert_id: &Uuid,
+    request: UpdateAlertRequest,
+) -> Result<AlertResponse> {
+    // Verify user has an organization
+    if user.organizations.is_empty() {
+        return Err(anyhow!("User is not a member of any organization"));
+    }
+
+    let user_org = &user.organizations[0];
+
+    let mut conn = get_pg_pool().get().await?;
+
+    let alert = find_alert(&mut conn, alert_id, &user_org.id).await?;
+    check_alert_write_access(user, &user_org.role, &alert)?;
+
+    let name = match request.name {
+        Some(name) => normalize_alert_name(&name)?,
+        None => alert.name.clone(),
+    };
+    let schedule = request
+        .schedule
+        .map(|schedule| schedule.trim().to_string())
+        .unwrap_or_else(|| alert.schedule.clone());
+    let timezone = request
+        .timezone
+        .map(|tz| tz.trim().to_string())
+        .unwrap_or_else(|| alert.timezone.clone());
+    let next_run_at = validate_schedule("alert", &schedule, &timezone, Utc::now())?;
+
+    let channel = match request.channel {
+        Some(channel) => channel,
+        None => AlertChannel::parse(&alert.channel)
+            .ok_or_else(|| anyhow!("Unknown alert channel '{}'", alert.channel))?,
+    };
+    let recipients = match request.recipients {
+        Some(recipients) => normalize_recipients("alert", recipients)?,
+        None => alert.recipients.clone(),
+    };
+    let target_url = request
+        .target_url
+        .map(|url| url.trim().to_string())
+        .or_else(|| alert.target_url.clone());
+    validate_destination("alert", channel.into(), &recipients, target_url.as_deref())?;
+
+    let column = request
+        .column
+        .map(|column| column.trim().to_string())
+        .unwrap_or_else(|| alert.column_name.clone());
+    if column.is_empty() {
+        return Err(anyhow!("Invalid alert: column cannot be empty"));
+    }
+    let time_column = match request.time_column {
+        Some(time_column) => Some(time_column.trim().to_string()).filter(|c| !c.is_empty()),
+        None => alert.time_column.clone(),
+    };
+    let condition = match request.condition {
+        Some(condition) => condition,
+        None => AlertCondition::parse(&alert.condition)
+            .ok_or_else(|| anyhow!("Unknown alert condition '{}'", alert.condition))?,
+    };
+    let threshold = match (request.threshold, request.clear_threshold) {
+        (_, true) => None,
+        (Some(threshold), false) => Some(threshold),
+        (None, false) => alert.threshold,
+    };
+
+    let check_changed = column != alert.column_name
+        || time_column != alert.time_column
+        || condition.as_str() != alert.condition
+        || threshold != alert.threshold;
+    if check_changed {
+        let metric = get_metric_with_data_access(&alert.metric_file_id, user, None, None).await?;
+        validate_alert_columns(&metric, &column, time_column.as_deref())?;
+        resolve_threshold(condition, threshold, goal_line_value(&metric))?;
+    }
+
+    // Webhooks keep their key unless it's rotated; email doesn't sign anything
+    let new_signing_secret = (channel == AlertChannel::Webhook
+        && (request.rotate_signing_secret || alert.signing_secret.is_none()))
+    .then(generate_signing_secret);
+    let signing_secret = match channel {
+        AlertChannel::Webhook => new_signing_secret
+            .clone()
+            .or_else(|| alert.signing_secret.clone()),
+        AlertChannel::Email => None,
+    };
+
+    let changeset = AlertChangeset {
+        name,
+        column_name: column,
+        time_column,
+        condition: condition.as_str().to_string(),
+        threshold,
+        schedule,
+        timezone,
+        channel: channel.as_str().to_string(),
+        recipients: match channel {
+            AlertChannel::Email => recipients,
+            AlertChannel::Webhook => Vec::new(),
+        },
+        target_url: match channel {
+            AlertChannel::Email => None,
+            AlertChannel::Webhook => target_url,
+        },
+        signing_secret,
+        notify_on_resolve: request.notify_on_resolve.unwrap_or(alert.notify_on_resolve),
+        enabled: request.enabled.unwrap_or(alert.enabled),
+        state: if check_changed {
+            AlertState::Ok.as_str().to_string()
+        } else {
+            alert.state.clone()
+        },
+        next_run_at: Some(next_run_at),
+        updated_at: Utc::now(),
+    };
+
+    let alert = diesel::update(metric_alerts::table)
+        .filter(metric_alerts::id.eq(alert_id))
+        .set(changeset)
+        .get_result::<MetricAlert>(&mut conn)
+        .await
+        .map_err(|e| anyhow!("Failed to update alert: {}", e))?;
+
+    let mut response = AlertResponse::from(alert);
+    response.signing_secret = new_signing_secret;
+    Ok(response)
+}
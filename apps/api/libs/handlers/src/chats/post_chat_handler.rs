@@ -13,6 +13,7 @@ use agents::{
              create_dashboards::CreateDashboardFilesOutput,
             create_metrics::{CreateMetricFilesOutput}, // Alias to avoid name clash
             search_data_catalog::SearchDataCatalogOutput,
+            search_terms::SearchTermsOutput,
         },
         // Remove the old import
         // planning_tools::CreatePlanOutput,
@@ -1553,6 +1554,7 @@ fn transform_tool_message(
 
         // Existing tool result processing - pass duration
         "search_data_catalog" => tool_data_catalog_search(id.clone(), content, delta_duration)?,
+        "search_terms" => tool_search_terms(id.clone(), content, delta_duration)?,
         "create_metrics" => tool_create_metrics(id.clone(), content, delta_duration)?,
         "update_metrics" => tool_modify_metrics(id.clone(), content, delta_duration)?,
         "create_dashboards" => tool_create_dashboards(id.clone(), content, delta_duration)?,
@@ -2067,6 +2069,54 @@ fn tool_data_catalog_search(id: String, content: String, delta_duration: Duratio
     Ok(vec![buster_thought])
 }
 
+fn tool_search_terms(id: String, content: String, delta_duration: Duration) -> Result<Vec<BusterReasoningMessage>> {
+    let search_terms_result = match serde_json::from_str::<SearchTermsOutput>(&content) {
+        Ok(result) => result,
+        Err(e) => {
+            tracing::error!("Failed to parse SearchTermsOutput: {}. Content: {}", e, content);
+            return Ok(vec![BusterReasoningMessage::Pill(BusterReasoningPill {
+                id,
+                thought_type: "pills".to_string(),
+                title: "Error Searching Glossary".to_string(),
+                secondary_title: format!("Error: {}", e),
+                pill_containers: None,
+                status: "failed".to_string(),
+            })]);
+        }
+    };
+
+    // The same term can answer several queries; show it once
+    let mut term_pills: Vec<BusterThoughtPill> = Vec::new();
+    for term in search_terms_result.results.into_iter().flat_map(|r| r.terms) {
+        let term_id = term.id.to_string();
+        if !term_pills.iter().any(|pill| pill.id == term_id) {
+            term_pills.push(BusterThoughtPill {
+                id: term_id,
+                text: term.name,
+                thought_file_type: "term".to_string(),
+            });
+        }
+    }
+
+    let title = if term_pills.is_empty() {
+        "No glossary terms found".to_string()
+    } else {
+        format!("{} glossary terms found", term_pills.len())
+    };
+
+    Ok(vec![BusterReasoningMessage::Pill(BusterReasoningPill {
+        id,
+        thought_type: "pills".to_string(),
+        title,
+        secondary_title: format!("{} seconds", delta_duration.as_secs()),
+        pill_containers: Some(vec![BusterThoughtPillContainer {
+            title: "Glossary terms".to_string(),
+            pills: term_pills,
+        }]),
+        status: "completed".to_string(),
+    })])
+}
+
 fn proccess_data_catalog_search_results(
     results: SearchDataCatalogOutput,
 ) -> Result<Vec<BusterThoughtPillContainer>> {
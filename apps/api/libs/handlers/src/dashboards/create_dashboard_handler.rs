@@ -1,12 +1,13 @@
 use anyhow::Result;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use database::pool::get_pg_pool;
 use database::schema::dashboard_files;
 use database::types::dashboard_yml::DashboardYml;
 use database::types::VersionHistory;
-use diesel::{insert_into, ExpressionMethods};
-use diesel_async::RunQueryDsl;
+use diesel::{insert_into, ExpressionMethods, OptionalExtension, QueryDsl};
+use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection, RunQueryDsl};
 use middleware::AuthenticatedUser;
+use serde::Deserialize;
 use serde_json::Value;
 use uuid::Uuid;
 
@@ -18,7 +19,24 @@ use database::enums::{AssetPermissionRole, AssetType, IdentityType, Verification
 use database::schema::asset_permissions;
 use std::collections::HashMap;
 
-pub async fn create_dashboard_handler(user: &AuthenticatedUser) -> Result<BusterDashboardResponse> {
+/// Id, name, file name, creator, creation and update time of a created dashboard
+type DashboardFileRow = (Uuid, String, String, Uuid, DateTime<Utc>, DateTime<Utc>);
+
+#[derive(Debug, Default, Deserialize)]
+pub struct CreateDashboardRequest {
+    /// Id for the new dashboard, so a dashboard file can keep the id it was written with.
+    /// A random id is used when omitted.
+    pub id: Option<Uuid>,
+}
+
+/// Creates an empty dashboard owned by the user.
+///
+/// Creating a dashboard with the id of one the user owns but deleted restores it, emptied,
+/// so files pushed again after a delete work.
+pub async fn create_dashboard_handler(
+    user: &AuthenticatedUser,
+    request: CreateDashboardRequest,
+) -> Result<BusterDashboardResponse> {
     let mut conn = get_pg_pool().get().await?;
 
     // Create a default dashboard YAML
@@ -26,6 +44,7 @@ pub async fn create_dashboard_handler(user: &AuthenticatedUser) -> Result<Buster
         name: "Untitled Dashboard".to_string(),
         description: None,
         rows: vec![],
+        filters: vec![],
     };
 
     // Convert to YAML string for the file field
@@ -34,8 +53,34 @@ pub async fn create_dashboard_handler(user: &AuthenticatedUser) -> Result<Buster
     // Convert to JSON Value for the content field
     let content_value: Value = serde_json::to_value(&dashboard_yml)?;
 
-    // Generate a unique ID and filename
-    let dashboard_id = Uuid::new_v4();
+    let dashboard_id = request.id.unwrap_or_else(Uuid::new_v4);
+    let existing = dashboard_files::table
+        .filter(dashboard_files::id.eq(dashboard_id))
+        .select(dashboard_files::deleted_at)
+        .first::<Option<DateTime<Utc>>>(&mut conn)
+        .await
+        .optional()?;
+    let restoring = match existing {
+        None | Some(None) => false,
+        Some(Some(_)) => asset_permissions::table
+            .filter(asset_permissions::asset_id.eq(dashboard_id))
+            .filter(asset_permissions::asset_type.eq(AssetType::DashboardFile))
+            .filter(asset_permissions::identity_id.eq(user.id))
+            .filter(asset_permissions::identity_type.eq(IdentityType::User))
+            .filter(asset_permissions::role.eq(AssetPermissionRole::Owner))
+            .filter(asset_permissions::deleted_at.is_null())
+            .select(asset_permissions::asset_id)
+            .first::<Uuid>(&mut conn)
+            .await
+            .optional()?
+            .is_some(),
+    };
+    if existing.is_some() && !restoring {
+        return Err(anyhow::anyhow!(
+            "Invalid dashboard: a dashboard with id {} already exists",
+            dashboard_id
+        ));
+    }
 
     // Get user's organization ID
     let organization_id = match get_user_organization_id(&user.id).await? {
@@ -49,57 +94,87 @@ pub async fn create_dashboard_handler(user: &AuthenticatedUser) -> Result<Buster
     // Create version history with initial version 1
     let version_history = VersionHistory::new(1, dashboard_yml);
 
-    // Insert the dashboard file
-    let dashboard_file = insert_into(dashboard_files::table)
-        .values((
-            dashboard_files::id.eq(dashboard_id),
-            dashboard_files::name.eq("Untitled Dashboard"),
-            dashboard_files::file_name.eq("Untitled Dashboard"),
-            dashboard_files::content.eq(&content_value),
-            dashboard_files::organization_id.eq(organization_id),
-            dashboard_files::created_by.eq(user.id),
-            dashboard_files::created_at.eq(now),
-            dashboard_files::updated_at.eq(now),
-            dashboard_files::publicly_accessible.eq(false),
-            dashboard_files::version_history.eq(serde_json::to_value(version_history)?),
-        ))
-        .returning((
-            dashboard_files::id,
-            dashboard_files::name,
-            dashboard_files::file_name,
-            dashboard_files::created_by,
-            dashboard_files::created_at,
-            dashboard_files::updated_at,
-        ))
-        .get_result::<(
-            Uuid,
-            String,
-            String,
-            Uuid,
-            chrono::DateTime<chrono::Utc>,
-            chrono::DateTime<chrono::Utc>,
-        )>(&mut conn)
-        .await?;
-
-    // Insert user permission for the dashboard
-    insert_into(asset_permissions::table)
-        .values((
-            asset_permissions::identity_id.eq(user.id),
-            asset_permissions::identity_type.eq(IdentityType::User),
-            asset_permissions::asset_id.eq(dashboard_id),
-            asset_permissions::asset_type.eq(AssetType::DashboardFile),
-            asset_permissions::role.eq(AssetPermissionRole::Owner),
-            asset_permissions::created_at.eq(now),
-            asset_permissions::updated_at.eq(now),
-            asset_permissions::created_by.eq(user.id),
-            asset_permissions::updated_by.eq(user.id),
-        ))
-        .execute(&mut conn)
+    let version_history = serde_json::to_value(version_history)?;
+
+    // The dashboard and its owner are written together, so a failure can't leave a dashboard
+    // nobody can open
+    let dashboard_file = conn
+        .transaction::<_, anyhow::Error, _>(|conn| {
+            async move {
+                if restoring {
+                    // The owner's permission survived the delete
+                    return Ok(diesel::update(dashboard_files::table.find(dashboard_id))
+                        .set((
+                            dashboard_files::name.eq("Untitled Dashboard"),
+                            dashboard_files::file_name.eq("Untitled Dashboard"),
+                            dashboard_files::content.eq(&content_value),
+                            dashboard_files::updated_at.eq(now),
+                            dashboard_files::deleted_at.eq(None::<DateTime<Utc>>),
+                            dashboard_files::version_history.eq(&version_history),
+                        ))
+                        .returning((
+                            dashboard_files::id,
+                            dashboard_files::name,
+                            dashboard_files::file_name,
+                            dashboard_files::created_by,
+                            dashboard_files::created_at,
+                            dashboard_files::updated_at,
+                        ))
+                        .get_result::<DashboardFileRow>(conn)
+                        .await?);
+                }
+
+                let dashboard_file = insert_into(dashboard_files::table)
+                    .values((
+                        dashboard_files::id.eq(dashboard_id),
+                        dashboard_files::name.eq("Untitled Dashboard"),
+                        dashboard_files::file_name.eq("Untitled Dashboard"),
+                        dashboard_files::content.eq(&content_value),
+                        dashboard_files::organization_id.eq(organization_id),
+                        dashboard_files::created_by.eq(user.id),
+                        dashboard_files::created_at.eq(now),
+                        dashboard_files::updated_at.eq(now),
+                        dashboard_files::publicly_accessible.eq(false),
+                        dashboard_files::version_history.eq(&version_history),
+                    ))
+                    .returning((
+                        dashboard_files::id,
+                        dashboard_files::name,
+                        dashboard_files::file_name,
+                        dashboard_files::created_by,
+                        dashboard_files::created_at,
+                        dashboard_files::updated_at,
+                    ))
+                    .get_result::<DashboardFileRow>(conn)
+                    .await?;
+
+                insert_into(asset_permissions::table)
+                    .values((
+                        asset_permissions::identity_id.eq(user.id),
+                        asset_permissions::identity_type.eq(IdentityType::User),
+                        asset_permissions::asset_id.eq(dashboard_id),
+                        asset_permissions::asset_type.eq(AssetType::DashboardFile),
+                        asset_permissions::role.eq(AssetPermissionRole::Owner),
+                        asset_permissions::created_at.eq(now),
+                        asset_permissions::updated_at.eq(now),
+                        asset_permissions::created_by.eq(user.id),
+                        asset_permissions::updated_by.eq(user.id),
+                    ))
+                    .execute(conn)
+                    .await?;
+
+                Ok(dashboard_file)
+            }
+            .scope_boxed()
+        })
         .await?;
 
     // Construct the dashboard
     let dashboard = BusterDashboard {
-        config: DashboardConfig { rows: vec![] },
+        config: DashboardConfig {
+            rows: vec![],
+            filters: None,
+        },
         created_at: dashboard_file.4,
         created_by: dashboard_file.3,
         description: None,
@@ -0,0 +1,250 @@
+use anyhow::{anyhow, Context, Result};
+use database::{
+    enums::DataSourceType, pool::get_pg_pool, schema::datasets, types::DashboardFilterType,
+};
+use dataset_security::{get_column_masks, get_row_level_filters, has_all_datasets_access};
+use diesel::{ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+use middleware::AuthenticatedUser;
+use query_engine::{
+    arrow_conversion::as_string, data_source_query_routes::query_engine::query_engine_for_user,
+    pagination::get_data_source_type,
+};
+use semantic_layer::dialect::{literal, quote_identifier, uses_top};
+use serde::Serialize;
+use serde_json::Value;
+use stored_values::search::{list_column_values, SearchTarget};
+use uuid::Uuid;
+
+use super::get_dashboard_handler;
+
+const DEFAULT_FILTER_VALUES_LIMIT: i64 = 100;
+const MAX_FILTER_VALUES_LIMIT: i64 = 1000;
+
+#[derive(Debug, Serialize)]
+pub struct DashboardFilterValuesResponse {
+    pub filter_id: String,
+    pub values: Vec<String>,
+}
+
+/// Lists the values a dashboard's `select` filter can be set to, either its fixed options or
+/// the values of the column it draws from that the user is allowed to see.
+pub async fn get_dashboard_filter_values_handler(
+    dashboard_id: &Uuid,
+    filter_id: &str,
+    search: Option<String>,
+    limit: Option<i64>,
+    user: &AuthenticatedUser,
+    password: Option<String>,
+) -> Result<DashboardFilterValuesResponse> {
+    // Goes through the regular dashboard access checks
+    let dashboard = get_dashboard_handler(dashboard_id, user, None, password).await?;
+
+    let filter = dashboard
+        .dashboard
+        .config
+        .filters
+        .unwrap_or_default()
+        .into_iter()
+        .find(|filter| filter.id == filter_id)
+        .ok_or_else(|| anyhow!("Filter '{}' not found on dashboard", filter_id))?;
+
+    if filter.filter_type != DashboardFilterType::Select {
+        return Err(anyhow!("Filter '{}' is not a select filter", filter_id));
+    }
+
+    let limit = limit
+        .unwrap_or(DEFAULT_FILTER_VALUES_LIMIT)
+        .clamp(1, MAX_FILTER_VALUES_LIMIT);
+    let search = search.filter(|s| !s.trim().is_empty());
+
+    let values = if let Some(options) = filter.options {
+        let needle = search.as_deref().map(str::to_lowercase);
+        options
+            .into_iter()
+            .filter(|option| {
+                needle
+                    .as_ref()
+                    .is_none_or(|needle| option.to_lowercase().contains(needle))
+            })
+            .take(limit as usize)
+            .collect()
+    } else if let Some(source) = filter.values_from {
+        // Stored values live per data source; the filter's first loadable metric decides which
+        let data_source_id = filter
+            .targets
+            .iter()
+            .find_map(|target| dashboard.metrics.get(&target.metric_id))
+            .map(|metric| metric.data_source_id)
+            .ok_or_else(|| anyhow!("None of the metrics of filter '{}' could be loaded", filter_id))?;
+
+        let target = SearchTarget {
+            database_name: source.database,
+            schema_name: source.schema,
+            table_name: source.table,
+            column_name: source.column,
+        };
+
+        let values =
+            column_values_for_user(&user.id, data_source_id, &target, search.as_deref(), limit).await;
+        match values {
+            Ok(values) => values,
+            Err(e) => {
+                tracing::error!(
+                    "Failed to list values for filter '{}' on dashboard {}: {}",
+                    filter_id,
+                    dashboard_id,
+                    e
+                );
+                return Err(e);
+            }
+        }
+    } else {
+        Vec::new()
+    };
+
+    Ok(DashboardFilterValuesResponse {
+        filter_id: filter.id,
+        values,
+    })
+}
+
+/// Values of the target column the user may see. The column's table must be a dataset the user
+/// has permission on. A masked column has none, and a user with a row-level filter on the table
+/// gets values from the warehouse through their policies, since the stored values cover every
+/// row.
+async fn column_values_for_user(
+    user_id: &Uuid,
+    data_source_id: Uuid,
+    target: &SearchTarget,
+    search: Option<&str>,
+    limit: i64,
+) -> Result<Vec<String>> {
+    let dataset_id = dataset_for_target(&data_source_id, target).await?;
+    if !has_all_datasets_access(user_id, &[dataset_id]).await? {
+        return Err(anyhow!(
+            "You don't have permission to see the values of {}.{}",
+            target.table_name,
+            target.column_name
+        ));
+    }
+
+    let column_masks = get_column_masks(user_id, &data_source_id).await?;
+    let is_masked = column_masks.iter().any(|(table, table_mask)| {
+        table.eq_ignore_ascii_case(&target.table_name)
+            && table_mask.masks.contains_key(&target.column_name.to_lowercase())
+    });
+    if is_masked {
+        return Ok(Vec::new());
+    }
+
+    let row_filters = get_row_level_filters(user_id, &data_source_id).await?;
+    if !row_filters
+        .keys()
+        .any(|table| table.eq_ignore_ascii_case(&target.table_name))
+    {
+        return list_column_values(data_source_id, target, search, limit).await;
+    }
+
+    let data_source_type = get_data_source_type(&data_source_id).await?;
+    let sql = distinct_values_sql(target, search, limit, data_source_type)?;
+    let result = query_engine_for_user(user_id, &data_source_id, &sql, Some(limit)).await?;
+
+    Ok(result
+        .data
+        .into_iter()
+        .filter_map(|row| row.into_values().next())
+        .filter_map(|value| as_string(&value))
+        .collect())
+}
+
+/// The dataset on the data source that the target's table belongs to
+async fn dataset_for_target(data_source_id: &Uuid, target: &SearchTarget) -> Result<Uuid> {
+    let mut conn = get_pg_pool().get().await.context("DB Error")?;
+
+    let candidates = datasets::table
+        .filter(datasets::data_source_id.eq(data_source_id))
+        .filter(datasets::deleted_at.is_null())
+        .select((datasets::id, datasets::name, datasets::schema))
+        .load::<(Uuid, String, String)>(&mut conn)
+        .await
+        .context("Failed to load datasets")?;
+
+    candidates
+        .into_iter()
+        .find(|(_, name, schema)| {
+            name.eq_ignore_ascii_case(&target.table_name)
+                && (target.schema_name.is_empty() || schema.eq_ignore_ascii_case(&target.schema_name))
+        })
+        .map(|(dataset_id, _, _)| dataset_id)
+        .ok_or_else(|| {
+            anyhow!(
+                "Filter values come from {}, which is not a dataset on this data source",
+                target.table_name
+            )
+        })
+}
+
+fn distinct_values_sql(
+    target: &SearchTarget,
+    search: Option<&str>,
+    limit: i64,
+    data_source_type: DataSourceType,
+) -> Result<String> {
+    let column = quote_identifier(&target.column_name, data_source_type);
+    let table = [&target.database_name, &target.schema_name, &target.table_name]
+        .into_iter()
+        .filter(|part| !part.is_empty())
+        .map(|part| quote_identifier(part, data_source_type))
+        .collect::<Vec<_>>()
+        .join(".");
+
+    let mut condition = format!("{} IS NOT NULL", column);
+    if let Some(search) = search {
+        let pattern = Value::String(format!("%{}%", search.to_lowercase()));
+        condition.push_str(&format!(
+            " AND LOWER({}) LIKE {}",
+            column,
+            literal(&pattern, data_source_type)?
+        ));
+    }
+
+    Ok(if uses_top(data_source_type) {
+        format!(
+            "SELECT DISTINCT TOP {} {} FROM {} WHERE {} ORDER BY {}",
+            limit, column, table, condition, column
+        )
+    } else {
+        format!(
+            "SELECT DISTINCT {} FROM {} WHERE {} ORDER BY {} LIMIT {}",
+            column, table, condition, column, limit
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distinct_values_sql() {
+        let target = SearchTarget {
+            database_name: "analytics".to_string(),
+            schema_name: "sales".to_string(),
+            table_name: "customers".to_string(),
+            column_name: "Region Name".to_string(),
+        };
+
+        assert_eq!(
+            distinct_values_sql(&target, Some("West's"), 50, DataSourceType::Postgres).unwrap(),
+            "SELECT DISTINCT \"Region Name\" FROM analytics.sales.customers \
+             WHERE \"Region Name\" IS NOT NULL AND LOWER(\"Region Name\") LIKE '%west''s%' \
+             ORDER BY \"Region Name\" LIMIT 50"
+        );
+        assert_eq!(
+            distinct_values_sql(&target, None, 10, DataSourceType::SqlServer).unwrap(),
+            "SELECT DISTINCT TOP 10 [Region Name] FROM analytics.sales.customers \
+             WHERE [Region Name] IS NOT NULL ORDER BY [Region Name]"
+        );
+    }
+}
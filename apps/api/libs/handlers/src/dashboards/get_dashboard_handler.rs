@@ -22,7 +22,7 @@ use database::pool::get_pg_pool;
 use database::schema::{
     asset_permissions, collections, collections_to_assets, dashboard_files, metric_files, users,
 };
-use database::types::{MetricYml, VersionHistory};
+use database::types::{DashboardFilter, MetricYml, VersionHistory};
 use sharing::{check_permission_access, compute_effective_permission};
 
 use super::{
@@ -541,5 +541,13 @@ fn parse_dashboard_config(content: &Value) -> Result<DashboardConfig> {
         })
         .collect::<Result<Vec<_>>>()?;
 
-    Ok(DashboardConfig { rows })
+    let filters = match content.get("filters") {
+        Some(filters) => Some(
+            serde_json::from_value::<Vec<DashboardFilter>>(filters.clone())
+                .map_err(|e| anyhow!("Invalid filters in dashboard content: {}", e))?,
+        ),
+        None => None,
+    };
+
+    Ok(DashboardConfig { rows, filters })
 }
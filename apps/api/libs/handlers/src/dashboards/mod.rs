@@ -1,5 +1,6 @@
 mod create_dashboard_handler;
 mod delete_dashboard_handler;
+mod get_dashboard_filter_values_handler;
 mod get_dashboard_handler;
 mod list_dashboard_handler;
 mod update_dashboard_handler;
@@ -8,6 +9,7 @@ pub mod sharing;
 
 pub use create_dashboard_handler::*;
 pub use delete_dashboard_handler::*;
+pub use get_dashboard_filter_values_handler::*;
 pub use get_dashboard_handler::*;
 pub use list_dashboard_handler::*;
 pub use update_dashboard_handler::*;
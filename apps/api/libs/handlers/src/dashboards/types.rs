@@ -2,6 +2,7 @@ use std::collections::HashMap;
 
 use chrono::{DateTime, Utc};
 use database::enums::{AssetPermissionRole, Verification, WorkspaceSharing};
+use database::types::DashboardFilter;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -86,6 +87,9 @@ pub struct DashboardCollection {
 #[serde(rename_all = "camelCase")]
 pub struct DashboardConfig {
     pub rows: Vec<DashboardRow>,
+    /// Dashboard-wide filters. Left out of an update, the existing filters are kept.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filters: Option<Vec<DashboardFilter>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
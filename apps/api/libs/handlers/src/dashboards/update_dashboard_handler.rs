@@ -106,6 +106,7 @@ pub async fn update_dashboard_handler(
         name: "New Dashboard".to_string(),
         description: None,
         rows: Vec::new(),
+        filters: vec![],
     };
 
     let mut current_version_history: VersionHistory = dashboard_files::table
@@ -186,6 +187,12 @@ pub async fn update_dashboard_handler(
                 }
 
                 dashboard_yml.rows = new_rows;
+                if let Some(filters) = config.filters {
+                    dashboard_yml.filters = filters;
+                }
+                // Filters can't point at metrics that were just removed
+                dashboard_yml.prune_filter_targets();
+                dashboard_yml.validate()?;
                 has_changes = true;
             }
         }
@@ -422,6 +429,7 @@ mod tests {
                 name: "Empty Dashboard".to_string(),
                 description: None,
                 rows: Vec::new(),
+                filters: vec![],
             }),
         );
 
@@ -435,6 +443,7 @@ mod tests {
                 column_sizes: vec![12],
                 id: 1,
             }],
+            filters: vec![],
         };
 
         // Version 2 content
@@ -458,6 +467,7 @@ mod tests {
                     id: 2,
                 },
             ],
+            filters: vec![],
         };
 
         // Add versions to history
@@ -539,6 +549,7 @@ mod tests {
                     id: 2,
                 },
             ],
+            filters: vec![],
         };
 
         // Extract metric IDs
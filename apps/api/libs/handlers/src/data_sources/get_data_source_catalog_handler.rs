@@ -0,0 +1,44 @@
+use anyhow::{anyhow, Result};
+use database::{
+    enums::UserOrganizationRole, models::DataSource, pool::get_pg_pool, schema::data_sources,
+};
+use diesel::{ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+use middleware::types::AuthenticatedUser;
+use query_engine::introspection::{introspect_data_source, Catalog};
+use uuid::Uuid;
+
+/// Reads the warehouse catalog of a data source, optionally limited to `schemas`
+pub async fn get_data_source_catalog_handler(
+    user: &AuthenticatedUser,
+    data_source_id: &Uuid,
+    schemas: &[String],
+) -> Result<Catalog> {
+    // Verify user has an organization
+    if user.organizations.is_empty() {
+        return Err(anyhow!("User is not a member of any organization"));
+    }
+
+    let user_org = &user.organizations[0];
+
+    // The catalog lists every table the credentials can see, so it is limited to admins
+    if user_org.role != UserOrganizationRole::WorkspaceAdmin
+        && user_org.role != UserOrganizationRole::DataAdmin
+    {
+        return Err(anyhow!(
+            "User does not have appropriate permissions to read the data source catalog"
+        ));
+    }
+
+    let mut conn = get_pg_pool().get().await?;
+
+    data_sources::table
+        .filter(data_sources::id.eq(data_source_id))
+        .filter(data_sources::organization_id.eq(user_org.id))
+        .filter(data_sources::deleted_at.is_null())
+        .first::<DataSource>(&mut conn)
+        .await
+        .map_err(|e| anyhow!("Data source not found: {}", e))?;
+
+    introspect_data_source(data_source_id, schemas).await
+}
@@ -1,7 +1,9 @@
 mod create_data_source_handler;
 mod delete_data_source_handler;
+mod get_data_source_catalog_handler;
 mod get_data_source_handler;
 mod list_data_sources_handler;
+mod sync_data_source_catalog_handler;
 mod update_data_source_handler;
 
 // Explicitly re-export the specific items from each module
@@ -9,6 +11,7 @@ pub use create_data_source_handler::{
     create_data_source_handler, CreateDataSourceRequest, CreateDataSourceResponse,
 };
 pub use delete_data_source_handler::delete_data_source_handler;
+pub use get_data_source_catalog_handler::get_data_source_catalog_handler;
 pub use get_data_source_handler::{
     get_data_source_handler, CreatedByResponse, DataSourceResponse, DatasetResponse,
     GetDataSourceRequest,
@@ -16,6 +19,9 @@ pub use get_data_source_handler::{
 pub use list_data_sources_handler::{
     list_data_sources_handler, DataSourceListItem, ListDataSourcesRequest,
 };
+pub use sync_data_source_catalog_handler::{
+    sync_data_source_catalog_handler, SyncDataSourceCatalogRequest, SyncDataSourceCatalogResponse,
+};
 pub use update_data_source_handler::{
     update_data_source_handler, CreatedBy, DataSourceResponse as UpdateDataSourceResponse,
     UpdateDataSourceRequest,
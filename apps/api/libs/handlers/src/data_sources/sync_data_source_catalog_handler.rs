@@ -0,0 +1,625 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use database::{
+    enums::{CatalogDriftKind, DatasetType, UserOrganizationRole},
+    models::{CatalogDrift, DataSource, Dataset, DatasetColumn},
+    pool::get_pg_pool,
+    schema::{catalog_drift, data_sources, dataset_columns, datasets},
+};
+use diesel::{ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+use middleware::types::AuthenticatedUser;
+use query_engine::introspection::{
+    introspect_data_source, same_column_type, Catalog, CatalogTable, CatalogTableKind,
+    CATALOG_ROW_LIMIT,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct SyncDataSourceCatalogRequest {
+    /// Schemas to sync; every schema of the default database when empty
+    #[serde(default)]
+    pub schemas: Vec<String>,
+    /// Create datasets for tables and views that don't have one yet
+    #[serde(default)]
+    pub import_new_tables: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SyncDataSourceCatalogResponse {
+    pub tables_scanned: usize,
+    /// Drift still open for the synced schemas
+    pub drift: Vec<CatalogDrift>,
+    /// Datasets whose columns were recorded for the first time
+    pub baselined_datasets: Vec<Uuid>,
+    pub imported_datasets: Vec<Uuid>,
+    /// New tables that weren't imported because a dataset already uses their name
+    pub skipped_tables: Vec<String>,
+}
+
+/// A dataset with the columns recorded for it
+#[derive(Debug, Clone)]
+struct DatasetSnapshot {
+    id: Uuid,
+    schema: String,
+    table: String,
+    database: Option<String>,
+    columns: Vec<ColumnSnapshot>,
+}
+
+#[derive(Debug, Clone)]
+struct ColumnSnapshot {
+    name: String,
+    type_: String,
+    nullable: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct DriftEntry {
+    kind: CatalogDriftKind,
+    dataset_id: Option<Uuid>,
+    schema_name: String,
+    table_name: String,
+    column_name: Option<String>,
+    expected: Option<String>,
+    actual: Option<String>,
+}
+
+impl DriftEntry {
+    fn matches(&self, drift: &CatalogDrift) -> bool {
+        self.kind == drift.kind
+            && self.dataset_id == drift.dataset_id
+            && self.schema_name == drift.schema_name
+            && self.table_name == drift.table_name
+            && self.column_name == drift.column_name
+            && self.expected == drift.expected
+            && self.actual == drift.actual
+    }
+}
+
+/// Introspects a data source and compares its catalog with the data source's datasets.
+/// Differences are kept as open drift until a later sync no longer finds them. Datasets
+/// without recorded columns get them from the catalog, and new tables can be imported as
+/// datasets so a data source is usable without deploying models first.
+pub async fn sync_data_source_catalog_handler(
+    user: &AuthenticatedUser,
+    data_source_id: &Uuid,
+    request: SyncDataSourceCatalogRequest,
+) -> Result<SyncDataSourceCatalogResponse> {
+    // Verify user has an organization
+    if user.organizations.is_empty() {
+        return Err(anyhow!("User is not a member of any organization"));
+    }
+
+    let user_org = &user.organizations[0];
+
+    if user_org.role != UserOrganizationRole::WorkspaceAdmin
+        && user_org.role != UserOrganizationRole::DataAdmin
+    {
+        return Err(anyhow!(
+            "User does not have appropriate permissions to sync the data source catalog"
+        ));
+    }
+
+    let mut conn = get_pg_pool().get().await?;
+
+    let data_source = data_sources::table
+        .filter(data_sources::id.eq(data_source_id))
+        .filter(data_sources::organization_id.eq(user_org.id))
+        .filter(data_sources::deleted_at.is_null())
+        .first::<DataSource>(&mut conn)
+        .await
+        .map_err(|e| anyhow!("Data source not found: {}", e))?;
+
+    let catalog = introspect_data_source(&data_source.id, &request.schemas).await?;
+
+    // Missing tables in a partial catalog would all look dropped
+    if catalog.truncated {
+        return Err(anyhow!(
+            "The catalog has more than {} columns; limit the sync to fewer schemas",
+            CATALOG_ROW_LIMIT
+        ));
+    }
+
+    let active_datasets = datasets::table
+        .filter(datasets::data_source_id.eq(data_source.id))
+        .filter(datasets::deleted_at.is_null())
+        .load::<Dataset>(&mut conn)
+        .await
+        .map_err(|e| anyhow!("Failed to load datasets: {}", e))?;
+
+    let dataset_ids: Vec<Uuid> = active_datasets.iter().map(|d| d.id).collect();
+    let mut columns_by_dataset: HashMap<Uuid, Vec<ColumnSnapshot>> = HashMap::new();
+    for column in dataset_columns::table
+        .filter(dataset_columns::dataset_id.eq_any(&dataset_ids))
+        .filter(dataset_columns::deleted_at.is_null())
+        .load::<DatasetColumn>(&mut conn)
+        .await
+        .map_err(|e| anyhow!("Failed to load dataset columns: {}", e))?
+    {
+        columns_by_dataset
+            .entry(column.dataset_id)
+            .or_default()
+            .push(ColumnSnapshot {
+                name: column.name,
+                type_: column.type_,
+                nullable: column.nullable,
+            });
+    }
+
+    let snapshots: Vec<DatasetSnapshot> = active_datasets
+        .iter()
+        .map(|dataset| DatasetSnapshot {
+            id: dataset.id,
+            schema: dataset.schema.clone(),
+            table: dataset.database_name.clone(),
+            database: dataset.database_identifier.clone(),
+            columns: columns_by_dataset.remove(&dataset.id).unwrap_or_default(),
+        })
+        .collect();
+
+    let mut drift = diff_catalog(&catalog, &snapshots);
+    let now = Utc::now();
+
+    // Datasets that match a table but have no columns yet get the catalog's columns
+    let mut baselined_datasets = Vec::new();
+    let mut new_columns = Vec::new();
+    for snapshot in snapshots.iter().filter(|s| s.columns.is_empty()) {
+        if let Some(table) = matching_table(&catalog, snapshot) {
+            baselined_datasets.push(snapshot.id);
+            new_columns.extend(dataset_columns_for(snapshot.id, table, now));
+        }
+    }
+
+    let mut imported_datasets = Vec::new();
+    let mut skipped_tables = Vec::new();
+    if request.import_new_tables {
+        // Dataset names are unique per data source, deleted datasets included
+        let taken_names: HashSet<String> = datasets::table
+            .filter(datasets::data_source_id.eq(data_source.id))
+            .select(datasets::database_name)
+            .load::<String>(&mut conn)
+            .await
+            .map_err(|e| anyhow!("Failed to load dataset names: {}", e))?
+            .into_iter()
+            .map(|name| name.to_lowercase())
+            .collect();
+
+        let mut new_datasets = Vec::new();
+        for entry in drift
+            .iter()
+            .filter(|d| d.kind == CatalogDriftKind::NewTable)
+        {
+            let Some(table) = catalog.find_table(&entry.schema_name, &entry.table_name) else {
+                continue;
+            };
+
+            let name = table.name.to_lowercase();
+            if taken_names.contains(&name)
+                || new_datasets
+                    .iter()
+                    .any(|d: &Dataset| d.database_name.to_lowercase() == name)
+            {
+                skipped_tables.push(format!("{}.{}", table.schema, table.name));
+                continue;
+            }
+
+            let dataset = Dataset {
+                id: Uuid::new_v4(),
+                name: table.name.clone(),
+                database_name: table.name.clone(),
+                when_to_use: table.comment.clone(),
+                when_not_to_use: None,
+                type_: match table.kind {
+                    CatalogTableKind::Table => DatasetType::Table,
+                    CatalogTableKind::View => DatasetType::View,
+                },
+                definition: String::new(),
+                schema: table.schema.clone(),
+                enabled: true,
+                imported: true,
+                data_source_id: data_source.id,
+                organization_id: data_source.organization_id,
+                created_by: user.id,
+                updated_by: user.id,
+                created_at: now,
+                updated_at: now,
+                deleted_at: None,
+                model: None,
+                yml_file: None,
+                database_identifier: Some(table.database.clone()),
+            };
+            new_columns.extend(dataset_columns_for(dataset.id, table, now));
+            new_datasets.push(dataset);
+        }
+
+        if !new_datasets.is_empty() {
+            if let Err(e) = diesel::insert_into(datasets::table)
+                .values(&new_datasets)
+                .execute(&mut conn)
+                .await
+            {
+                tracing::error!(
+                    "Failed to import new tables for data source {}: {}",
+                    data_source.id,
+                    e
+                );
+                return Err(anyhow!("Failed to import new tables: {}", e));
+            }
+
+            imported_datasets = new_datasets.iter().map(|d| d.id).collect();
+
+            // Imported tables are no longer drift
+            drift.retain(|entry| {
+                entry.kind != CatalogDriftKind::NewTable
+                    || !new_datasets.iter().any(|d| {
+                        d.schema == entry.schema_name && d.database_name == entry.table_name
+                    })
+            });
+        }
+    }
+
+    if !new_columns.is_empty() {
+        diesel::insert_into(dataset_columns::table)
+            .values(&new_columns)
+            .on_conflict_do_nothing()
+            .execute(&mut conn)
+            .await
+            .map_err(|e| anyhow!("Failed to record dataset columns: {}", e))?;
+    }
+
+    // Open drift within the synced scope is resolved unless it was found again
+    let open_drift = catalog_drift::table
+        .filter(catalog_drift::data_source_id.eq(data_source.id))
+        .filter(catalog_drift::resolved_at.is_null())
+        .load::<CatalogDrift>(&mut conn)
+        .await
+        .map_err(|e| anyhow!("Failed to load catalog drift: {}", e))?;
+
+    let in_scope = |schema: &str| {
+        catalog
+            .schemas
+            .as_ref()
+            .is_none_or(|schemas| schemas.iter().any(|s| s.eq_ignore_ascii_case(schema)))
+    };
+
+    let resolved_ids: Vec<Uuid> = open_drift
+        .iter()
+        .filter(|open| in_scope(&open.schema_name) && !drift.iter().any(|d| d.matches(open)))
+        .map(|open| open.id)
+        .collect();
+
+    if !resolved_ids.is_empty() {
+        diesel::update(catalog_drift::table)
+            .filter(catalog_drift::id.eq_any(&resolved_ids))
+            .set(catalog_drift::resolved_at.eq(Some(now)))
+            .execute(&mut conn)
+            .await
+            .map_err(|e| anyhow!("Failed to resolve catalog drift: {}", e))?;
+    }
+
+    let new_drift: Vec<CatalogDrift> = drift
+        .iter()
+        .filter(|entry| !open_drift.iter().any(|open| entry.matches(open)))
+        .map(|entry| CatalogDrift {
+            id: Uuid::new_v4(),
+            data_source_id: data_source.id,
+            dataset_id: entry.dataset_id,
+            kind: entry.kind,
+            schema_name: entry.schema_name.clone(),
+            table_name: entry.table_name.clone(),
+            column_name: entry.column_name.clone(),
+            expected: entry.expected.clone(),
+            actual: entry.actual.clone(),
+            detected_at: now,
+            resolved_at: None,
+        })
+        .collect();
+
+    if !new_drift.is_empty() {
+        diesel::insert_into(catalog_drift::table)
+            .values(&new_drift)
+            .execute(&mut conn)
+            .await
+            .map_err(|e| anyhow!("Failed to record catalog drift: {}", e))?;
+    }
+
+    let current_drift = open_drift
+        .into_iter()
+        .filter(|open| in_scope(&open.schema_name) && drift.iter().any(|d| d.matches(open)))
+        .chain(new_drift)
+        .collect();
+
+    Ok(SyncDataSourceCatalogResponse {
+        tables_scanned: catalog.tables.len(),
+        drift: current_drift,
+        baselined_datasets,
+        imported_datasets,
+        skipped_tables,
+    })
+}
+
+/// The catalog table a dataset reads from, if the catalog covers the dataset's schema
+fn matching_table<'a>(catalog: &'a Catalog, dataset: &DatasetSnapshot) -> Option<&'a CatalogTable> {
+    catalog
+        .find_table(&dataset.schema, &dataset.table)
+        .filter(|table| {
+            dataset
+                .database
+                .as_deref()
+                .is_none_or(|database| database.eq_ignore_ascii_case(&table.database))
+        })
+}
+
+/// Whether the catalog can tell if the dataset's table still exists
+fn covers_dataset(catalog: &Catalog, dataset: &DatasetSnapshot) -> bool {
+    let same_database = dataset.database.as_deref().is_none_or(|database| {
+        database.eq_ignore_ascii_case(&catalog.database)
+            || catalog
+                .tables
+                .iter()
+                .any(|table| table.database.eq_ignore_ascii_case(database))
+    });
+
+    same_database && catalog.covers_schema(&dataset.schema)
+}
+
+fn diff_catalog(catalog: &Catalog, datasets: &[DatasetSnapshot]) -> Vec<DriftEntry> {
+    let mut drift = Vec::new();
+
+    for dataset in datasets.iter().filter(|d| covers_dataset(catalog, d)) {
+        let entry = |kind, column_name: Option<&str>, expected, actual| DriftEntry {
+            kind,
+            dataset_id: Some(dataset.id),
+            schema_name: dataset.schema.clone(),
+            table_name: dataset.table.clone(),
+            column_name: column_name.map(str::to_string),
+            expected,
+            actual,
+        };
+
+        let Some(table) = matching_table(catalog, dataset) else {
+            drift.push(entry(CatalogDriftKind::DroppedTable, None, None, None));
+            continue;
+        };
+
+        // Columns are compared once they have been recorded
+        if dataset.columns.is_empty() {
+            continue;
+        }
+
+        for column in &dataset.columns {
+            let Some(actual) = table
+                .columns
+                .iter()
+                .find(|c| c.name.eq_ignore_ascii_case(&column.name))
+            else {
+                drift.push(entry(
+                    CatalogDriftKind::DroppedColumn,
+                    Some(&column.name),
+                    Some(column.type_.clone()),
+                    None,
+                ));
+                continue;
+            };
+
+            if !column.type_.is_empty() && !same_column_type(&column.type_, &actual.data_type) {
+                drift.push(entry(
+                    CatalogDriftKind::TypeChanged,
+                    Some(&column.name),
+                    Some(column.type_.clone()),
+                    Some(actual.data_type.clone()),
+                ));
+            }
+
+            if column.nullable != actual.nullable {
+                drift.push(entry(
+                    CatalogDriftKind::NullabilityChanged,
+                    Some(&column.name),
+                    Some(nullability(column.nullable)),
+                    Some(nullability(actual.nullable)),
+                ));
+            }
+        }
+
+        for actual in table.columns.iter().filter(|c| {
+            !dataset
+                .columns
+                .iter()
+                .any(|column| column.name.eq_ignore_ascii_case(&c.name))
+        }) {
+            drift.push(entry(
+                CatalogDriftKind::NewColumn,
+                Some(&actual.name),
+                None,
+                Some(actual.data_type.clone()),
+            ));
+        }
+    }
+
+    for table in &catalog.tables {
+        let has_dataset = datasets.iter().any(|dataset| {
+            dataset.schema.eq_ignore_ascii_case(&table.schema)
+                && dataset.table.eq_ignore_ascii_case(&table.name)
+        });
+
+        if !has_dataset {
+            drift.push(DriftEntry {
+                kind: CatalogDriftKind::NewTable,
+                dataset_id: None,
+                schema_name: table.schema.clone(),
+                table_name: table.name.clone(),
+                column_name: None,
+                expected: None,
+                actual: Some(
+                    match table.kind {
+                        CatalogTableKind::Table => "table",
+                        CatalogTableKind::View => "view",
+                    }
+                    .to_string(),
+                ),
+            });
+        }
+    }
+
+    drift
+}
+
+fn nullability(nullable: bool) -> String {
+    let nullability = if nullable { "nullable" } else { "not null" };
+    nullability.to_string()
+}
+
+fn dataset_columns_for(
+    dataset_id: Uuid,
+    table: &CatalogTable,
+    now: chrono::DateTime<Utc>,
+) -> Vec<DatasetColumn> {
+    table
+        .columns
+        .iter()
+        .map(|column| DatasetColumn {
+            id: Uuid::new_v4(),
+            dataset_id,
+            name: column.name.clone(),
+            type_: column.data_type.clone(),
+            description: column.comment.clone(),
+            nullable: column.nullable,
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+            stored_values: None,
+            stored_values_status: None,
+            stored_values_error: None,
+            stored_values_count: None,
+            stored_values_last_synced: None,
+            semantic_type: None,
+            dim_type: None,
+            expr: None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use query_engine::introspection::CatalogColumn;
+
+    fn column(name: &str, data_type: &str, nullable: bool) -> CatalogColumn {
+        CatalogColumn {
+            name: name.to_string(),
+            data_type: data_type.to_string(),
+            nullable,
+            comment: None,
+        }
+    }
+
+    fn catalog(schemas: Option<Vec<String>>) -> Catalog {
+        Catalog {
+            database: "analytics".to_string(),
+            schemas,
+            databases: vec!["analytics".to_string()],
+            tables: vec![
+                CatalogTable {
+                    database: "analytics".to_string(),
+                    schema: "sales".to_string(),
+                    name: "orders".to_string(),
+                    kind: CatalogTableKind::Table,
+                    comment: None,
+                    columns: vec![
+                        column("id", "integer", false),
+                        column("amount", "numeric", true),
+                        column("region", "character varying", false),
+                    ],
+                },
+                CatalogTable {
+                    database: "analytics".to_string(),
+                    schema: "sales".to_string(),
+                    name: "returns".to_string(),
+                    kind: CatalogTableKind::View,
+                    comment: None,
+                    columns: vec![column("id", "integer", false)],
+                },
+            ],
+            truncated: false,
+        }
+    }
+
+    fn dataset(schema: &str, table: &str, columns: Vec<(&str, &str, bool)>) -> DatasetSnapshot {
+        DatasetSnapshot {
+            id: Uuid::new_v4(),
+            schema: schema.to_string(),
+            table: table.to_string(),
+            database: None,
+            columns: columns
+                .into_iter()
+                .map(|(name, type_, nullable)| ColumnSnapshot {
+                    name: name.to_string(),
+                    type_: type_.to_string(),
+                    nullable,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_diff_catalog_flags_drift() {
+        let orders = dataset(
+            "sales",
+            "ORDERS",
+            vec![
+                ("id", "int4", false),
+                ("amount", "text", true),
+                ("region", "varchar", true),
+                ("discount", "numeric", true),
+            ],
+        );
+        let customers = dataset("sales", "customers", vec![]);
+
+        let drift = diff_catalog(&catalog(None), &[orders.clone(), customers.clone()]);
+        let kinds: Vec<(CatalogDriftKind, &str, Option<&str>)> = drift
+            .iter()
+            .map(|d| (d.kind, d.table_name.as_str(), d.column_name.as_deref()))
+            .collect();
+
+        assert_eq!(
+            kinds,
+            vec![
+                (CatalogDriftKind::TypeChanged, "ORDERS", Some("amount")),
+                (
+                    CatalogDriftKind::NullabilityChanged,
+                    "ORDERS",
+                    Some("region")
+                ),
+                (CatalogDriftKind::DroppedColumn, "ORDERS", Some("discount")),
+                (CatalogDriftKind::DroppedTable, "customers", None),
+                (CatalogDriftKind::NewTable, "returns", None),
+            ]
+        );
+        assert_eq!(drift[0].expected.as_deref(), Some("text"));
+        assert_eq!(drift[0].actual.as_deref(), Some("numeric"));
+        assert_eq!(drift[4].actual.as_deref(), Some("view"));
+    }
+
+    #[test]
+    fn test_diff_catalog_skips_uncovered_datasets() {
+        // A schema the sync was limited away from isn't reported as dropped
+        let finance = dataset("finance", "ledger", vec![]);
+        let drift = diff_catalog(&catalog(Some(vec!["sales".to_string()])), &[finance]);
+        assert!(drift.iter().all(|d| d.kind == CatalogDriftKind::NewTable));
+
+        // Nor is a dataset from another database
+        let mut other_database = dataset("sales", "orders", vec![]);
+        other_database.database = Some("warehouse".to_string());
+        let drift = diff_catalog(&catalog(None), &[other_database]);
+        assert!(drift.iter().all(|d| d.kind == CatalogDriftKind::NewTable));
+
+        // Datasets without recorded columns only get table-level drift
+        let drift = diff_catalog(&catalog(None), &[dataset("sales", "orders", vec![])]);
+        assert_eq!(drift.len(), 1);
+        assert_eq!(drift[0].table_name, "returns");
+    }
+}
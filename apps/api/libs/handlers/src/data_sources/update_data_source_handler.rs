@@ -236,6 +236,48 @@ pub async fn update_data_source_handler(
 
                 Credential::MySql(updated)
             }
+            Credential::Mariadb(creds) => {
+                let mut updated = creds.clone();
+
+                if let Some(host) = new_credentials.get("host").and_then(|v| v.as_str()) {
+                    updated.host = host.to_string();
+                }
+                if let Some(port) = new_credentials
+                    .get("port")
+                    .and_then(|v| v.as_u64())
+                    .map(|v| v as u16)
+                {
+                    updated.port = port;
+                }
+                if let Some(username) = new_credentials.get("username").and_then(|v| v.as_str()) {
+                    updated.username = username.to_string();
+                }
+                if let Some(password) = new_credentials.get("password").and_then(|v| v.as_str()) {
+                    updated.password = password.to_string();
+                }
+                if let Some(default_database) = new_credentials
+                    .get("default_database")
+                    .and_then(|v| v.as_str())
+                {
+                    updated.default_database = default_database.to_string();
+                }
+                if let Some(jump_host) = new_credentials.get("jump_host").and_then(|v| v.as_str()) {
+                    updated.jump_host = Some(jump_host.to_string());
+                }
+                if let Some(ssh_username) =
+                    new_credentials.get("ssh_username").and_then(|v| v.as_str())
+                {
+                    updated.ssh_username = Some(ssh_username.to_string());
+                }
+                if let Some(ssh_private_key) = new_credentials
+                    .get("ssh_private_key")
+                    .and_then(|v| v.as_str())
+                {
+                    updated.ssh_private_key = Some(ssh_private_key.to_string());
+                }
+
+                Credential::Mariadb(updated)
+            }
             Credential::Bigquery(creds) => {
                 let mut updated = creds.clone();
 
@@ -404,6 +446,60 @@ pub async fn update_data_source_handler(
 
                 Credential::Snowflake(updated)
             }
+            Credential::DuckDb(creds) => {
+                let mut updated = creds.clone();
+
+                if let Some(database_path) =
+                    new_credentials.get("database_path").and_then(|v| v.as_str())
+                {
+                    updated.database_path = Some(database_path.to_string());
+                }
+                if let Some(file_search_path) = new_credentials
+                    .get("file_search_path")
+                    .and_then(|v| v.as_str())
+                {
+                    updated.file_search_path = Some(file_search_path.to_string());
+                }
+                if let Some(default_schema) = new_credentials
+                    .get("default_schema")
+                    .and_then(|v| v.as_str())
+                {
+                    updated.default_schema = Some(default_schema.to_string());
+                }
+
+                Credential::DuckDb(updated)
+            }
+            Credential::ClickHouse(creds) => {
+                let mut updated = creds.clone();
+
+                if let Some(host) = new_credentials.get("host").and_then(|v| v.as_str()) {
+                    updated.host = host.to_string();
+                }
+                if let Some(port) = new_credentials
+                    .get("port")
+                    .and_then(|v| v.as_u64())
+                    .map(|v| v as u16)
+                {
+                    updated.port = port;
+                }
+                if let Some(username) = new_credentials.get("username").and_then(|v| v.as_str()) {
+                    updated.username = username.to_string();
+                }
+                if let Some(password) = new_credentials.get("password").and_then(|v| v.as_str()) {
+                    updated.password = password.to_string();
+                }
+                if let Some(default_database) = new_credentials
+                    .get("default_database")
+                    .and_then(|v| v.as_str())
+                {
+                    updated.default_database = default_database.to_string();
+                }
+                if let Some(use_tls) = new_credentials.get("use_tls").and_then(|v| v.as_bool()) {
+                    updated.use_tls = Some(use_tls);
+                }
+
+                Credential::ClickHouse(updated)
+            }
         };
 
         // Update the secret
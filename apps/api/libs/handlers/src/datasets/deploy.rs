@@ -13,6 +13,7 @@ use uuid::Uuid;
 
 // Types from this crate's parent (handlers) -> Corrected to super
 use super::types::{DeployDatasetsRequest, ValidationError, ValidationResult}; // Added DeployDatasetsRequest
+use super::validation::{lint_deploy_requests, validate_deploy_requests};
 
 // Corrected to use the `database` crate directly as per Cargo.toml
 use database::{
@@ -127,6 +128,12 @@ pub async fn deploy_datasets_handler_core(
             }
         };
 
+        // Check the models against the warehouse and each other before anything is upserted
+        let mut model_errors = validate_deploy_requests(&data_source, &group).await;
+        for (name, errors) in lint_deploy_requests(&data_source, &group).await {
+            model_errors.entry(name).or_default().extend(errors);
+        }
+
         let mut datasets_to_upsert_map: HashMap<(String, Uuid), database::models::Dataset> =
             HashMap::new(); // Incorrect path
 
@@ -152,6 +159,13 @@ pub async fn deploy_datasets_handler_core(
                 results.push(validation);
                 continue; // Skip this request
             }
+            if let Some(errors) = model_errors.get(&req.name) {
+                for error in errors {
+                    validation.add_error(error.clone());
+                }
+                results.push(validation);
+                continue; // Broken models are not deployed
+            }
             validation.success = true; // Assume success initially, will be overridden by upsert errors
             results.push(validation);
 
@@ -1,2 +1,3 @@
 pub mod deploy;
-pub mod types; 
\ No newline at end of file
+pub mod types;
+pub mod validation;
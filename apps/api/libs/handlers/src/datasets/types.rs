@@ -1,5 +1,6 @@
 // This file will contain types related to dataset deployment, like ValidationResult and ValidationError. 
 
+use semantic_layer::Diagnostic;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -54,21 +55,55 @@ impl ValidationError {
         }
     }
 
-    // Add other factory methods if they were used (e.g., table_not_found, column_not_found)
-    // fn table_not_found(table_name: &str) -> Self {
-    //     Self {
-    //         code: "TABLE_NOT_FOUND".to_string(),
-    //         message: format!("Table '{}' not found in data source.", table_name),
-    //         location: None,
-    //     }
-    // }
-    // fn column_not_found(column_name: &str) -> Self {
-    //     Self {
-    //         code: "COLUMN_NOT_FOUND".to_string(),
-    //         message: format!("Column '{}' not found in table.", column_name),
-    //         location: Some(format!("column: {}", column_name)),
-    //     }
-    // }
+    pub fn table_not_found(schema: &str, table_name: &str) -> Self {
+        Self {
+            code: "TABLE_NOT_FOUND".to_string(),
+            message: format!(
+                "Table or view '{}' not found in schema '{}'.",
+                table_name, schema
+            ),
+            location: None,
+        }
+    }
+
+    pub fn column_not_found(column_name: &str, table_name: &str) -> Self {
+        Self {
+            code: "COLUMN_NOT_FOUND".to_string(),
+            message: format!(
+                "Column '{}' not found in table '{}'.",
+                column_name, table_name
+            ),
+            location: Some(format!("column: {}", column_name)),
+        }
+    }
+
+    pub fn invalid_expression(column_name: &str, message: String) -> Self {
+        Self {
+            code: "INVALID_EXPRESSION".to_string(),
+            message,
+            location: Some(format!("column: {}", column_name)),
+        }
+    }
+
+    pub fn invalid_relationship(relationship_name: &str, message: String) -> Self {
+        Self {
+            code: "INVALID_RELATIONSHIP".to_string(),
+            message,
+            location: Some(format!("relationship: {}", relationship_name)),
+        }
+    }
+
+    /// An error the semantic model linter found, e.g. `unknown-column` as `UNKNOWN_COLUMN`
+    pub fn lint(diagnostic: &Diagnostic) -> Self {
+        Self {
+            code: diagnostic.code.to_uppercase().replace('-', "_"),
+            message: diagnostic.message.clone(),
+            location: Some(format!(
+                "line {}, column {}",
+                diagnostic.span.line, diagnostic.span.column
+            )),
+        }
+    }
 }
 // --- End Local Struct Definitions --- 
 
@@ -0,0 +1,512 @@
+//! Checks dataset models against the live warehouse before they are deployed: the table or
+//! view must exist, every column and expression must compile against it, and relationship
+//! join keys must exist on both sides with types that can be joined. The models are also
+//! linted together with the ones already deployed to the data source.
+
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use database::{models::DataSource, pool::get_pg_pool, schema::datasets};
+use diesel::{ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+use query_engine::data_source_query_routes::query_engine::query_engine;
+use query_engine::introspection::{
+    introspect_data_source, join_compatible_types, qualified_table_name, Catalog, CatalogTable,
+};
+use semantic_layer::{lint_files, models::Model as SemanticModel, SourceFile};
+use tracing::{info, warn};
+
+use super::types::{DeployDatasetsRequest, ValidationError};
+
+/// A join declared by a model, from `entity_relationships` or the model's YAML relationships
+#[derive(Debug, Clone, PartialEq)]
+struct JoinKey {
+    name: String,
+    source_col: String,
+    ref_model: String,
+    ref_col: String,
+}
+
+/// Validates the models of one data source, returning the errors found per model name.
+/// Data sources whose catalog can't be introspected are not validated.
+pub async fn validate_deploy_requests(
+    data_source: &DataSource,
+    requests: &[&DeployDatasetsRequest],
+) -> HashMap<String, Vec<ValidationError>> {
+    let mut errors: HashMap<String, Vec<ValidationError>> = HashMap::new();
+
+    let joins: HashMap<&str, Vec<JoinKey>> = requests
+        .iter()
+        .map(|req| (req.name.as_str(), join_keys(req)))
+        .collect();
+
+    // Schemas of referenced models that are already deployed rather than in this batch
+    let referenced_schemas = match existing_model_schemas(data_source, requests, &joins).await {
+        Ok(schemas) => schemas,
+        Err(e) => {
+            warn!(
+                "Failed to look up referenced models for data source '{}': {}",
+                data_source.name, e
+            );
+            HashMap::new()
+        }
+    };
+
+    let mut schemas: Vec<String> = requests
+        .iter()
+        .map(|req| req.schema.clone())
+        .chain(referenced_schemas.values().cloned())
+        .collect();
+    schemas.sort_by_key(|schema| schema.to_lowercase());
+    schemas.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+
+    let catalog = match introspect_data_source(&data_source.id, &schemas).await {
+        Ok(catalog) if !catalog.truncated => catalog,
+        Ok(_) => {
+            warn!(
+                "Catalog of data source '{}' is too large to validate against; skipping validation",
+                data_source.name
+            );
+            return errors;
+        }
+        Err(e) if e.to_string().contains("not supported") => {
+            info!(
+                "Skipping warehouse validation for data source '{}': {}",
+                data_source.name, e
+            );
+            return errors;
+        }
+        Err(e) => {
+            for req in requests {
+                errors.entry(req.name.clone()).or_default().push(
+                    ValidationError::data_source_error(format!(
+                        "Could not validate the model against the warehouse: {}",
+                        e
+                    )),
+                );
+            }
+            return errors;
+        }
+    };
+
+    for req in requests {
+        let model_errors = validate_model(
+            data_source,
+            &catalog,
+            req,
+            requests,
+            &referenced_schemas,
+            &joins[req.name.as_str()],
+        )
+        .await;
+
+        if !model_errors.is_empty() {
+            errors.insert(req.name.clone(), model_errors);
+        }
+    }
+
+    errors
+}
+
+/// Lints the models of one data source, returning the errors found per model name.
+///
+/// Models already deployed to the data source are linted alongside the batch so
+/// relationships to them resolve, but only the batch's diagnostics are reported.
+/// Warnings are logged and don't block the deploy.
+pub async fn lint_deploy_requests(
+    data_source: &DataSource,
+    requests: &[&DeployDatasetsRequest],
+) -> HashMap<String, Vec<ValidationError>> {
+    let mut errors: HashMap<String, Vec<ValidationError>> = HashMap::new();
+
+    let mut files: Vec<SourceFile> = requests
+        .iter()
+        .filter_map(|req| {
+            req.yml_file.as_ref().map(|content| SourceFile {
+                path: req.name.clone(),
+                content: content.clone(),
+            })
+        })
+        .collect();
+    if files.is_empty() {
+        return errors;
+    }
+    let batch_size = files.len();
+
+    match deployed_model_files(data_source, requests).await {
+        Ok(deployed) => files.extend(deployed),
+        Err(e) => warn!(
+            "Failed to load deployed models of data source '{}' for linting: {}",
+            data_source.name, e
+        ),
+    }
+
+    for diagnostic in lint_files(&files) {
+        let Some(file) = files[..batch_size].iter().find(|f| f.path == diagnostic.file) else {
+            continue;
+        };
+        if diagnostic.is_error() {
+            errors
+                .entry(file.path.clone())
+                .or_default()
+                .push(ValidationError::lint(&diagnostic));
+        } else {
+            warn!("Model '{}': {}", file.path, diagnostic);
+        }
+    }
+
+    errors
+}
+
+async fn validate_model(
+    data_source: &DataSource,
+    catalog: &Catalog,
+    req: &DeployDatasetsRequest,
+    requests: &[&DeployDatasetsRequest],
+    referenced_schemas: &HashMap<String, String>,
+    joins: &[JoinKey],
+) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+
+    let Some(table) = catalog.find_table(&req.schema, &req.name) else {
+        errors.push(ValidationError::table_not_found(&req.schema, &req.name));
+        return errors;
+    };
+
+    // Plain column references are checked against the catalog, anything else is compiled
+    let mut expressions = Vec::new();
+    for column in &req.columns {
+        let expr = column
+            .expr
+            .as_deref()
+            .map(str::trim)
+            .filter(|expr| !expr.is_empty())
+            .unwrap_or(&column.name);
+
+        match plain_column(expr) {
+            Some(name) => {
+                if find_column(table, name).is_none() {
+                    errors.push(ValidationError::column_not_found(name, &req.name));
+                }
+            }
+            None => expressions.push((column.name.as_str(), expr)),
+        }
+    }
+
+    let table_ref = qualified_table_name(&data_source.type_, table);
+    for (column_name, expr, message) in
+        compile_expressions(data_source, &table_ref, &expressions).await
+    {
+        errors.push(ValidationError::invalid_expression(
+            column_name,
+            format!(
+                "Expression '{}' for column '{}' does not compile: {}",
+                expr, column_name, message
+            ),
+        ));
+    }
+
+    for join in joins {
+        if let Err(message) = check_join(catalog, table, join, requests, referenced_schemas) {
+            errors.push(ValidationError::invalid_relationship(&join.name, message));
+        }
+    }
+
+    errors
+}
+
+fn check_join(
+    catalog: &Catalog,
+    table: &CatalogTable,
+    join: &JoinKey,
+    requests: &[&DeployDatasetsRequest],
+    referenced_schemas: &HashMap<String, String>,
+) -> Result<(), String> {
+    let source = find_column(table, &join.source_col).ok_or_else(|| {
+        format!(
+            "Join key '{}' not found in table '{}'.",
+            join.source_col, table.name
+        )
+    })?;
+
+    let ref_schema = requests
+        .iter()
+        .find(|req| req.name == join.ref_model)
+        .map(|req| req.schema.clone())
+        .or_else(|| referenced_schemas.get(&join.ref_model).cloned())
+        .ok_or_else(|| {
+            format!(
+                "Relationship references unknown model '{}'.",
+                join.ref_model
+            )
+        })?;
+
+    let ref_table = catalog
+        .find_table(&ref_schema, &join.ref_model)
+        .ok_or_else(|| {
+            format!(
+                "Referenced table '{}' not found in schema '{}'.",
+                join.ref_model, ref_schema
+            )
+        })?;
+
+    let target = find_column(ref_table, &join.ref_col).ok_or_else(|| {
+        format!(
+            "Join key '{}' not found in referenced table '{}'.",
+            join.ref_col, ref_table.name
+        )
+    })?;
+
+    if !join_compatible_types(&source.data_type, &target.data_type) {
+        return Err(format!(
+            "Join keys '{}' ({}) and '{}.{}' ({}) have incompatible types.",
+            join.source_col, source.data_type, join.ref_model, join.ref_col, target.data_type
+        ));
+    }
+
+    Ok(())
+}
+
+/// Compiles the expressions without reading any rows, returning the ones that fail with the
+/// warehouse's error
+async fn compile_expressions<'a>(
+    data_source: &DataSource,
+    table_ref: &str,
+    expressions: &[(&'a str, &'a str)],
+) -> Vec<(&'a str, &'a str, String)> {
+    if expressions.is_empty() {
+        return Vec::new();
+    }
+
+    let exprs: Vec<&str> = expressions.iter().map(|(_, expr)| *expr).collect();
+    let Err(e) = query_engine(&data_source.id, &probe_sql(table_ref, &exprs), Some(1)).await else {
+        return Vec::new();
+    };
+
+    if expressions.len() == 1 {
+        let (column_name, expr) = expressions[0];
+        return vec![(column_name, expr, e.to_string())];
+    }
+
+    // Mixing aggregates with plain expressions can fail on its own, so each is retried alone
+    let mut failures = Vec::new();
+    for (column_name, expr) in expressions {
+        if let Err(e) =
+            query_engine(&data_source.id, &probe_sql(table_ref, &[*expr]), Some(1)).await
+        {
+            failures.push((*column_name, *expr, e.to_string()));
+        }
+    }
+    failures
+}
+
+/// A query that compiles `exprs` against the table but returns no rows, on every dialect
+fn probe_sql(table_ref: &str, exprs: &[&str]) -> String {
+    let columns: Vec<String> = exprs
+        .iter()
+        .enumerate()
+        .map(|(i, expr)| format!("({}) AS buster_probe_{}", expr, i))
+        .collect();
+
+    format!(
+        "SELECT {} FROM {} WHERE 1 = 0",
+        columns.join(", "),
+        table_ref
+    )
+}
+
+/// The column name if `expr` is just a (possibly quoted) column reference
+fn plain_column(expr: &str) -> Option<&str> {
+    let name = ['"', '`']
+        .iter()
+        .find_map(|quote| {
+            expr.strip_prefix(*quote)
+                .and_then(|rest| rest.strip_suffix(*quote))
+        })
+        .unwrap_or(expr);
+
+    let valid = !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name.chars().all(|c| c.is_alphanumeric() || c == '_');
+
+    valid.then_some(name)
+}
+
+fn find_column<'a>(
+    table: &'a CatalogTable,
+    name: &str,
+) -> Option<&'a query_engine::introspection::CatalogColumn> {
+    table
+        .columns
+        .iter()
+        .find(|column| column.name.eq_ignore_ascii_case(name))
+}
+
+fn join_keys(req: &DeployDatasetsRequest) -> Vec<JoinKey> {
+    // Entity relationships join on a key of the same name in both models
+    let mut joins: Vec<JoinKey> = req
+        .entity_relationships
+        .iter()
+        .flatten()
+        .map(|entity| JoinKey {
+            name: entity.name.clone(),
+            source_col: entity.expr.clone(),
+            ref_model: entity.name.clone(),
+            ref_col: entity.expr.clone(),
+        })
+        .collect();
+
+    if let Some(model) = req
+        .yml_file
+        .as_deref()
+        .and_then(|yml| serde_yaml::from_str::<SemanticModel>(yml).ok())
+    {
+        joins.extend(model.relationships.into_iter().map(|relationship| JoinKey {
+            name: relationship.name.clone(),
+            source_col: relationship.source_col,
+            ref_model: relationship.name,
+            ref_col: relationship.ref_col,
+        }));
+    }
+
+    joins
+}
+
+/// Schemas of the already deployed models that the batch references, by model name
+async fn existing_model_schemas(
+    data_source: &DataSource,
+    requests: &[&DeployDatasetsRequest],
+    joins: &HashMap<&str, Vec<JoinKey>>,
+) -> Result<HashMap<String, String>> {
+    let names: Vec<&str> = joins
+        .values()
+        .flatten()
+        .map(|join| join.ref_model.as_str())
+        .filter(|name| !requests.iter().any(|req| req.name == *name))
+        .collect();
+
+    if names.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let mut conn = get_pg_pool()
+        .get()
+        .await
+        .map_err(|e| anyhow!("Failed to get database connection: {}", e))?;
+
+    let schemas = datasets::table
+        .filter(datasets::data_source_id.eq(data_source.id))
+        .filter(datasets::database_name.eq_any(&names))
+        .filter(datasets::deleted_at.is_null())
+        .select((datasets::database_name, datasets::schema))
+        .load::<(String, String)>(&mut conn)
+        .await?;
+
+    Ok(schemas.into_iter().collect())
+}
+
+/// YAML of the models deployed to the data source that aren't part of this batch
+async fn deployed_model_files(
+    data_source: &DataSource,
+    requests: &[&DeployDatasetsRequest],
+) -> Result<Vec<SourceFile>> {
+    let names: Vec<&str> = requests.iter().map(|req| req.name.as_str()).collect();
+
+    let mut conn = get_pg_pool()
+        .get()
+        .await
+        .map_err(|e| anyhow!("Failed to get database connection: {}", e))?;
+
+    let deployed = datasets::table
+        .filter(datasets::data_source_id.eq(data_source.id))
+        .filter(datasets::database_name.ne_all(&names))
+        .filter(datasets::deleted_at.is_null())
+        .filter(datasets::yml_file.is_not_null())
+        .select((datasets::database_name, datasets::yml_file))
+        .load::<(String, Option<String>)>(&mut conn)
+        .await?;
+
+    Ok(deployed
+        .into_iter()
+        .map(|(name, yml_file)| SourceFile {
+            // Distinct from the batch's paths, which are bare model names
+            path: format!("deployed/{}", name),
+            content: yml_file.unwrap_or_default(),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datasets::types::DeployDatasetsEntityRelationshipsRequest;
+
+    #[test]
+    fn test_plain_column() {
+        assert_eq!(plain_column("amount"), Some("amount"));
+        assert_eq!(plain_column("\"Order Id\""), None);
+        assert_eq!(plain_column("`order_id`"), Some("order_id"));
+        assert_eq!(plain_column("amount * 100"), None);
+        assert_eq!(plain_column("SUM(amount)"), None);
+        assert_eq!(plain_column("1st"), None);
+    }
+
+    #[test]
+    fn test_probe_sql() {
+        assert_eq!(
+            probe_sql("\"sales\".\"orders\"", &["amount * 100", "SUM(amount)"]),
+            "SELECT (amount * 100) AS buster_probe_0, (SUM(amount)) AS buster_probe_1 FROM \"sales\".\"orders\" WHERE 1 = 0"
+        );
+    }
+
+    #[test]
+    fn test_join_keys() {
+        let req = DeployDatasetsRequest {
+            id: None,
+            data_source_name: "warehouse".to_string(),
+            env: "dev".to_string(),
+            type_: "view".to_string(),
+            name: "orders".to_string(),
+            model: Some("orders".to_string()),
+            schema: "sales".to_string(),
+            database: None,
+            description: String::new(),
+            sql_definition: None,
+            entity_relationships: Some(vec![DeployDatasetsEntityRelationshipsRequest {
+                name: "regions".to_string(),
+                expr: "region_id".to_string(),
+                type_: "foreign".to_string(),
+            }]),
+            columns: vec![],
+            yml_file: Some(
+                r#"
+name: orders
+relationships:
+  - name: customers
+    source_col: customer_id
+    ref_col: id
+"#
+                .to_string(),
+            ),
+            database_identifier: None,
+        };
+
+        assert_eq!(
+            join_keys(&req),
+            vec![
+                JoinKey {
+                    name: "regions".to_string(),
+                    source_col: "region_id".to_string(),
+                    ref_model: "regions".to_string(),
+                    ref_col: "region_id".to_string(),
+                },
+                JoinKey {
+                    name: "customers".to_string(),
+                    source_col: "customer_id".to_string(),
+                    ref_model: "customers".to_string(),
+                    ref_col: "id".to_string(),
+                },
+            ]
+        );
+    }
+}
@@ -1,3 +1,4 @@
+pub mod alerts;
 pub mod chats;
 pub mod collections;
 pub mod dashboards;
@@ -9,6 +10,9 @@ pub mod messages;
 pub mod metrics;
 pub mod organizations;
 pub mod search;
+pub mod semantic;
+pub mod subscriptions;
+pub mod terms;
 pub mod users;
 pub mod utils;
 
@@ -0,0 +1,247 @@
+use agents::tools::categories::file_tools::{
+    common::process_metric_file, metric_evaluation::spawn_metric_evaluation,
+};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use database::{
+    enums::{AssetPermissionRole, AssetType, DataSourceType, IdentityType, Verification},
+    models::{AssetPermission, MetricFile, MetricFileToDataset},
+    pool::get_pg_pool,
+    schema::{asset_permissions, data_sources, metric_files, metric_files_to_datasets},
+    types::{DataMetadata, MetricYml, VersionHistory},
+};
+use diesel::{insert_into, AsChangeset, ExpressionMethods, OptionalExtension, QueryDsl};
+use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection, RunQueryDsl};
+use middleware::AuthenticatedUser;
+use query_engine::query_cache::{invalidate_query_cache_scope, metric_cache_scope};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::metrics::get_metric_handler::get_metric_handler;
+use crate::metrics::types::BusterMetric;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateMetricRequest {
+    /// Id for the new metric. Lets files that reference the metric, like dashboards, be
+    /// written before it exists. A random id is used when omitted.
+    pub id: Option<Uuid>,
+    /// The metric's YAML definition
+    pub file: String,
+    /// Defaults to the workspace's data source when it only has one
+    pub data_source_id: Option<Uuid>,
+}
+
+/// Handler to create a metric from its YAML definition
+///
+/// The SQL is validated against the data source the same way metrics created in chats are,
+/// and the user becomes the metric's owner. Creating a metric with the id of one the user owns
+/// but deleted restores it with the new definition, so files pushed again after a delete work.
+pub async fn create_metric_handler(
+    user: &AuthenticatedUser,
+    request: CreateMetricRequest,
+) -> Result<BusterMetric> {
+    // Verify user has an organization
+    if user.organizations.is_empty() {
+        return Err(anyhow!("User is not a member of any organization"));
+    }
+
+    let organization_id = user.organizations[0].id;
+
+    let mut conn = get_pg_pool()
+        .get()
+        .await
+        .map_err(|e| anyhow!("Failed to get database connection: {}", e))?;
+
+    let metric_id = request.id.unwrap_or_else(Uuid::new_v4);
+    let existing = metric_files::table
+        .filter(metric_files::id.eq(metric_id))
+        .select(metric_files::deleted_at)
+        .first::<Option<DateTime<Utc>>>(&mut conn)
+        .await
+        .optional()?;
+    let restoring = match existing {
+        None | Some(None) => false,
+        Some(Some(_)) => asset_permissions::table
+            .filter(asset_permissions::asset_id.eq(metric_id))
+            .filter(asset_permissions::asset_type.eq(AssetType::MetricFile))
+            .filter(asset_permissions::identity_id.eq(user.id))
+            .filter(asset_permissions::identity_type.eq(IdentityType::User))
+            .filter(asset_permissions::role.eq(AssetPermissionRole::Owner))
+            .filter(asset_permissions::deleted_at.is_null())
+            .select(asset_permissions::asset_id)
+            .first::<Uuid>(&mut conn)
+            .await
+            .optional()?
+            .is_some(),
+    };
+    if existing.is_some() && !restoring {
+        return Err(anyhow!(
+            "Invalid metric: a metric with id {} already exists",
+            metric_id
+        ));
+    }
+
+    let mut data_source_query = data_sources::table
+        .filter(data_sources::organization_id.eq(organization_id))
+        .filter(data_sources::deleted_at.is_null())
+        .select((data_sources::id, data_sources::type_))
+        .into_boxed();
+    if let Some(data_source_id) = request.data_source_id {
+        data_source_query = data_source_query.filter(data_sources::id.eq(data_source_id));
+    }
+    let candidates = data_source_query
+        .limit(2)
+        .load::<(Uuid, DataSourceType)>(&mut conn)
+        .await?;
+    let (data_source_id, data_source_type) = match candidates.as_slice() {
+        [data_source] => *data_source,
+        [] if request.data_source_id.is_some() => {
+            return Err(anyhow!("Data source not found"));
+        }
+        [] => return Err(anyhow!("Invalid metric: the workspace has no data sources")),
+        _ => {
+            return Err(anyhow!(
+                "Invalid metric: data_source_id is required when the workspace has more than one data source"
+            ))
+        }
+    };
+    let data_source_dialect = data_source_type.to_string();
+
+    let (mut metric_file, metric_yml, _, _, dataset_ids) = process_metric_file(
+        metric_id.to_string(),
+        String::new(),
+        request.file,
+        data_source_id,
+        data_source_dialect.clone(),
+        &user.id,
+    )
+    .await
+    .map_err(|e| anyhow!("Invalid metric: {}", e))?;
+    metric_file.id = metric_id;
+    metric_file.file_name = metric_yml.name.clone();
+
+    let now = Utc::now();
+    let dataset_links: Vec<MetricFileToDataset> = dataset_ids
+        .into_iter()
+        .map(|dataset_id| MetricFileToDataset {
+            metric_file_id: metric_id,
+            dataset_id,
+            metric_version_number: 1,
+            created_at: now,
+        })
+        .collect();
+
+    // The metric, its owner and its datasets are written together, so a failure can't
+    // leave a metric nobody can open
+    conn.transaction::<_, anyhow::Error, _>(|conn| {
+        async move {
+            if restoring {
+                // The owner's permission survived the delete; the old dataset links didn't
+                // come from this definition
+                diesel::update(metric_files::table.find(metric_id))
+                    .set(RestoredMetricFile::from(metric_file))
+                    .execute(conn)
+                    .await
+                    .map_err(|e| anyhow!("Failed to restore metric: {}", e))?;
+
+                diesel::delete(
+                    metric_files_to_datasets::table
+                        .filter(metric_files_to_datasets::metric_file_id.eq(metric_id)),
+                )
+                .execute(conn)
+                .await
+                .map_err(|e| anyhow!("Failed to unlink metric datasets: {}", e))?;
+            } else {
+                insert_into(metric_files::table)
+                    .values(&metric_file)
+                    .execute(conn)
+                    .await
+                    .map_err(|e| anyhow!("Failed to create metric: {}", e))?;
+
+                insert_into(asset_permissions::table)
+                    .values(&AssetPermission {
+                        identity_id: user.id,
+                        identity_type: IdentityType::User,
+                        asset_id: metric_id,
+                        asset_type: AssetType::MetricFile,
+                        role: AssetPermissionRole::Owner,
+                        created_at: now,
+                        updated_at: now,
+                        deleted_at: None,
+                        created_by: user.id,
+                        updated_by: user.id,
+                    })
+                    .execute(conn)
+                    .await
+                    .map_err(|e| anyhow!("Failed to set metric permissions: {}", e))?;
+            }
+
+            if !dataset_links.is_empty() {
+                insert_into(metric_files_to_datasets::table)
+                    .values(&dataset_links)
+                    .on_conflict_do_nothing()
+                    .execute(conn)
+                    .await
+                    .map_err(|e| anyhow!("Failed to link metric datasets: {}", e))?;
+            }
+
+            Ok(())
+        }
+        .scope_boxed()
+    })
+    .await?;
+
+    // Cached results of a restored metric were computed from its previous SQL
+    if restoring {
+        if let Err(e) = invalidate_query_cache_scope(&metric_cache_scope(&metric_id)).await {
+            tracing::warn!(
+                "Failed to invalidate query cache for metric {}: {}",
+                metric_id,
+                e
+            );
+        }
+    }
+
+    spawn_metric_evaluation(metric_id, metric_yml, data_source_dialect, user.id);
+
+    get_metric_handler(&metric_id, user, None, None).await
+}
+
+/// The columns a restored metric takes from its new definition. Everything about the deleted
+/// metric's previous content is replaced, including its evaluation and version history.
+#[derive(AsChangeset)]
+#[diesel(table_name = metric_files, treat_none_as_null = true)]
+struct RestoredMetricFile {
+    name: String,
+    file_name: String,
+    content: MetricYml,
+    verification: Verification,
+    evaluation_obj: Option<Value>,
+    evaluation_summary: Option<String>,
+    evaluation_score: Option<f64>,
+    updated_at: DateTime<Utc>,
+    deleted_at: Option<DateTime<Utc>>,
+    version_history: VersionHistory,
+    data_metadata: Option<DataMetadata>,
+    data_source_id: Uuid,
+}
+
+impl From<MetricFile> for RestoredMetricFile {
+    fn from(metric_file: MetricFile) -> Self {
+        RestoredMetricFile {
+            name: metric_file.name,
+            file_name: metric_file.file_name,
+            content: metric_file.content,
+            verification: metric_file.verification,
+            evaluation_obj: metric_file.evaluation_obj,
+            evaluation_summary: metric_file.evaluation_summary,
+            evaluation_score: metric_file.evaluation_score,
+            updated_at: metric_file.updated_at,
+            deleted_at: None,
+            version_history: metric_file.version_history,
+            data_metadata: metric_file.data_metadata,
+            data_source_id: metric_file.data_source_id,
+        }
+    }
+}
@@ -0,0 +1,496 @@
+//! Formatting of metric values for file exports, following the metric's column label formats.
+//!
+//! Integers and decimals are formatted from their exact digits so exports never lose precision
+//! the way a round trip through `f64` does. Floats are formatted as floats.
+
+use std::fmt::Write;
+
+use database::types::ColumnLabelFormat;
+use query_engine::arrow_conversion::as_string;
+use query_engine::data_types::DataType;
+
+/// A numeric cell value, kept exact for integers and decimals
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportNumber {
+    Exact { value: i128, scale: u32 },
+    Float(f64),
+}
+
+impl ExportNumber {
+    pub fn from_value(value: &DataType) -> Option<Self> {
+        match value {
+            DataType::Int2(Some(v)) => Some(Self::exact(*v as i128)),
+            DataType::Int4(Some(v)) => Some(Self::exact(*v as i128)),
+            DataType::Int8(Some(v)) => Some(Self::exact(*v as i128)),
+            DataType::Oid(Some(v)) => Some(Self::exact(*v as i128)),
+            DataType::Decimal(Some(d)) => Some(Self::Exact {
+                value: d.mantissa(),
+                scale: d.scale(),
+            }),
+            DataType::Float4(Some(v)) => Some(Self::Float(*v as f64)),
+            DataType::Float8(Some(v)) => Some(Self::Float(*v)),
+            _ => None,
+        }
+    }
+
+    fn exact(value: i128) -> Self {
+        Self::Exact { value, scale: 0 }
+    }
+
+    pub fn to_f64(self) -> f64 {
+        match self {
+            Self::Exact { value, scale } => value as f64 / 10f64.powi(scale as i32),
+            Self::Float(v) => v,
+        }
+    }
+
+    /// Whether the value survives conversion to `f64`, which is how spreadsheets store numbers
+    pub fn fits_f64(self) -> bool {
+        match self {
+            Self::Exact { value, .. } => value.unsigned_abs() < 10u128.pow(15),
+            Self::Float(_) => true,
+        }
+    }
+
+    fn multiply(self, multiplier: f64) -> Self {
+        if multiplier == 1.0 {
+            return self;
+        }
+
+        match self {
+            Self::Exact { value, scale } if multiplier.fract() == 0.0 && multiplier.abs() < 1e15 => {
+                match value.checked_mul(multiplier as i128) {
+                    Some(value) => Self::Exact { value, scale },
+                    None => Self::Float(self.to_f64() * multiplier),
+                }
+            }
+            _ => Self::Float(self.to_f64() * multiplier),
+        }
+    }
+
+    /// Splits the value into sign, integer digits and fraction digits, rounding half away from
+    /// zero to at most `max_fraction` digits. Without a maximum, exact values keep their own
+    /// scale and floats are rounded to two digits.
+    fn to_fixed(self, min_fraction: u32, max_fraction: Option<u32>) -> (bool, String, String) {
+        let (negative, int_part, mut fraction) = match self {
+            Self::Exact { value, scale } => {
+                let max_fraction = max_fraction.unwrap_or(scale).max(min_fraction);
+                let (mut value, mut scale) = (value, scale);
+
+                if scale > max_fraction {
+                    let divisor = 10i128.pow(scale - max_fraction);
+                    let remainder = (value % divisor).unsigned_abs();
+                    value /= divisor;
+                    if remainder * 2 >= divisor.unsigned_abs() {
+                        value += if self.is_negative() { -1 } else { 1 };
+                    }
+                    scale = max_fraction;
+                }
+
+                let digits = format!("{:0>width$}", value.unsigned_abs(), width = scale as usize + 1);
+                let (int_part, fraction) = digits.split_at(digits.len() - scale as usize);
+                (value < 0, int_part.to_string(), fraction.to_string())
+            }
+            Self::Float(v) if !v.is_finite() => return (false, v.to_string(), String::new()),
+            Self::Float(v) => {
+                let max_fraction = max_fraction.unwrap_or(2).max(min_fraction);
+                let digits = format!("{:.*}", max_fraction as usize, v.abs());
+                let (int_part, fraction) = digits.split_once('.').unwrap_or((digits.as_str(), ""));
+                // Values that round to zero shouldn't print as "-0"
+                let is_zero = int_part.chars().chain(fraction.chars()).all(|c| c == '0');
+                (v < 0.0 && !is_zero, int_part.to_string(), fraction.to_string())
+            }
+        };
+
+        while fraction.len() > min_fraction as usize && fraction.ends_with('0') {
+            fraction.pop();
+        }
+        while fraction.len() < min_fraction as usize {
+            fraction.push('0');
+        }
+
+        (negative, int_part, fraction)
+    }
+
+    fn is_negative(self) -> bool {
+        match self {
+            Self::Exact { value, .. } => value < 0,
+            Self::Float(v) => v < 0.0,
+        }
+    }
+}
+
+/// Header for a column: its display name when one is set.
+pub fn column_header<'a>(column: &'a str, format: Option<&'a ColumnLabelFormat>) -> &'a str {
+    format
+        .and_then(|f| f.display_name.as_deref())
+        .filter(|name| !name.trim().is_empty())
+        .unwrap_or(column)
+}
+
+/// Formats a value as text the way the UI would display it under `format`.
+pub fn format_value(value: &DataType, format: &ColumnLabelFormat) -> String {
+    if is_missing(value) {
+        return match &format.replace_missing_data_with {
+            Some(serde_json::Value::Number(n)) => n
+                .as_f64()
+                .map(|v| format_number(ExportNumber::Float(v), format))
+                .unwrap_or_default(),
+            Some(serde_json::Value::String(s)) => s.clone(),
+            _ => String::new(),
+        };
+    }
+
+    if format.column_type == "number" {
+        if let Some(number) = ExportNumber::from_value(value) {
+            return format_number(number, format);
+        }
+    }
+
+    if let Some(date_format) = format.date_format.as_deref().filter(|f| *f != "auto") {
+        let datetime = match value {
+            DataType::Date(Some(d)) => d.and_hms_opt(0, 0, 0),
+            DataType::Timestamp(Some(ts)) => Some(*ts),
+            DataType::Timestamptz(Some(ts)) => Some(ts.naive_utc()),
+            _ => None,
+        };
+
+        if let Some(datetime) = datetime {
+            let mut formatted = String::new();
+            if write!(formatted, "{}", datetime.format(&dayjs_to_chrono(date_format))).is_ok() {
+                return formatted;
+            }
+        }
+    }
+
+    raw_value(value)
+}
+
+/// Plain text form of a value. Decimals keep every digit; everything else matches the JSON
+/// data response.
+pub fn raw_value(value: &DataType) -> String {
+    match value {
+        DataType::Decimal(Some(d)) => {
+            let number = ExportNumber::from_value(value).unwrap();
+            let (negative, int_part, fraction) = number.to_fixed(d.scale(), None);
+            let mut raw = String::new();
+            if negative {
+                raw.push('-');
+            }
+            raw.push_str(&int_part);
+            if !fraction.is_empty() {
+                raw.push('.');
+                raw.push_str(&fraction);
+            }
+            raw
+        }
+        other => as_string(other).unwrap_or_default(),
+    }
+}
+
+fn is_missing(value: &DataType) -> bool {
+    matches!(value, DataType::Null) || as_string(value).is_none()
+}
+
+fn format_number(number: ExportNumber, format: &ColumnLabelFormat) -> String {
+    let number = number.multiply(format.multiplier.unwrap_or(1.0));
+    let min_fraction = format.minimum_fraction_digits.unwrap_or(0).clamp(0, 20) as u32;
+    let max_fraction = format
+        .maximum_fraction_digits
+        .map(|digits| digits.clamp(0, 20) as u32);
+
+    let (number, unit) = if format.compact_numbers == Some(true) {
+        compact(number)
+    } else {
+        (number, "")
+    };
+
+    let (negative, int_part, fraction) = number.to_fixed(min_fraction, max_fraction);
+    let int_part = match format.number_separator_style.as_deref() {
+        Some(separator) if !separator.is_empty() => group_digits(&int_part, separator),
+        _ => int_part,
+    };
+
+    let mut formatted = String::new();
+    if negative {
+        formatted.push('-');
+    }
+    if let Some(prefix) = &format.prefix {
+        formatted.push_str(prefix);
+    }
+    if format.style == "currency" {
+        formatted.push_str(&currency_symbol(format.currency.as_deref().unwrap_or("USD")));
+    }
+    formatted.push_str(&int_part);
+    if !fraction.is_empty() {
+        formatted.push('.');
+        formatted.push_str(&fraction);
+    }
+    formatted.push_str(unit);
+    if format.style == "percent" {
+        formatted.push('%');
+    }
+    if let Some(suffix) = &format.suffix {
+        formatted.push_str(suffix);
+    }
+
+    formatted
+}
+
+fn compact(number: ExportNumber) -> (ExportNumber, &'static str) {
+    let value = number.to_f64();
+
+    for (threshold, unit) in [(1e12, "T"), (1e9, "B"), (1e6, "M"), (1e3, "K")] {
+        if value.abs() >= threshold {
+            return (ExportNumber::Float(value / threshold), unit);
+        }
+    }
+
+    (number, "")
+}
+
+fn group_digits(digits: &str, separator: &str) -> String {
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push_str(separator);
+        }
+        grouped.push(c);
+    }
+
+    grouped
+}
+
+fn currency_symbol(currency: &str) -> String {
+    match currency.to_uppercase().as_str() {
+        "USD" | "CAD" | "AUD" => "$".to_string(),
+        "EUR" => "€".to_string(),
+        "GBP" => "£".to_string(),
+        "JPY" | "CNY" => "¥".to_string(),
+        "INR" => "₹".to_string(),
+        "KRW" => "₩".to_string(),
+        other => format!("{} ", other),
+    }
+}
+
+/// Excel number format equivalent to a column label format. The multiplier is applied to
+/// the value itself, and compact numbers have no Excel equivalent, so neither appears here.
+pub fn excel_number_format(format: &ColumnLabelFormat) -> String {
+    let min_fraction = format.minimum_fraction_digits.unwrap_or(0).clamp(0, 20) as usize;
+    let max_fraction = format
+        .maximum_fraction_digits
+        .map(|digits| digits.clamp(0, 20) as usize)
+        .unwrap_or(10)
+        .max(min_fraction);
+
+    let mut excel_format = String::new();
+    if let Some(prefix) = &format.prefix {
+        excel_format.push_str(&excel_literal(prefix));
+    }
+    if format.style == "currency" {
+        excel_format.push_str(&excel_literal(&currency_symbol(
+            format.currency.as_deref().unwrap_or("USD"),
+        )));
+    }
+
+    let grouped = format
+        .number_separator_style
+        .as_deref()
+        .is_some_and(|separator| !separator.is_empty());
+    excel_format.push_str(if grouped { "#,##0" } else { "0" });
+
+    if max_fraction > 0 {
+        excel_format.push('.');
+        excel_format.push_str(&"0".repeat(min_fraction));
+        excel_format.push_str(&"#".repeat(max_fraction - min_fraction));
+    }
+
+    if format.style == "percent" {
+        excel_format.push_str(&excel_literal("%"));
+    }
+    if let Some(suffix) = &format.suffix {
+        excel_format.push_str(&excel_literal(suffix));
+    }
+
+    excel_format
+}
+
+/// Excel date format equivalent to a dayjs format string, e.g. `MMM D, YYYY` -> `mmm d, yyyy`.
+pub fn excel_date_format(date_format: &str) -> String {
+    const TOKENS: &[(&str, &str)] = &[
+        ("YYYY", "yyyy"),
+        ("YY", "yy"),
+        ("MMMM", "mmmm"),
+        ("MMM", "mmm"),
+        ("MM", "mm"),
+        ("M", "m"),
+        ("DD", "dd"),
+        ("D", "d"),
+        ("dddd", "dddd"),
+        ("ddd", "ddd"),
+        ("HH", "hh"),
+        ("H", "h"),
+        ("hh", "hh"),
+        ("h", "h"),
+        ("mm", "mm"),
+        ("m", "m"),
+        ("ss", "ss"),
+        ("s", "s"),
+        ("A", "AM/PM"),
+        ("a", "am/pm"),
+    ];
+
+    convert_dayjs(date_format, TOKENS, |literal, out| {
+        if literal.chars().all(|c| !c.is_alphanumeric() && c != '"') {
+            out.push_str(literal);
+        } else {
+            out.push_str(&excel_literal(literal));
+        }
+    })
+}
+
+/// chrono format equivalent to a dayjs format string, e.g. `MMM D, YYYY` -> `%b %-d, %Y`.
+pub fn dayjs_to_chrono(date_format: &str) -> String {
+    const TOKENS: &[(&str, &str)] = &[
+        ("YYYY", "%Y"),
+        ("YY", "%y"),
+        ("MMMM", "%B"),
+        ("MMM", "%b"),
+        ("MM", "%m"),
+        ("M", "%-m"),
+        ("DD", "%d"),
+        ("D", "%-d"),
+        ("dddd", "%A"),
+        ("ddd", "%a"),
+        ("HH", "%H"),
+        ("H", "%-H"),
+        ("hh", "%I"),
+        ("h", "%-I"),
+        ("mm", "%M"),
+        ("m", "%-M"),
+        ("ss", "%S"),
+        ("s", "%-S"),
+        ("A", "%p"),
+        ("a", "%P"),
+    ];
+
+    convert_dayjs(date_format, TOKENS, |literal, out| {
+        out.push_str(&literal.replace('%', "%%"));
+    })
+}
+
+/// Rewrites dayjs tokens (longest match first), passing everything else, including
+/// `[escaped]` text, through `literal`. Localized presets such as `LL` are expanded first.
+fn convert_dayjs(
+    date_format: &str,
+    tokens: &[(&str, &str)],
+    literal: impl Fn(&str, &mut String),
+) -> String {
+    let date_format = match date_format {
+        "LT" => "h:mm A",
+        "LTS" => "h:mm:ss A",
+        "L" => "MM/DD/YYYY",
+        "LL" => "MMMM D, YYYY",
+        "LLL" => "MMMM D, YYYY h:mm A",
+        "LLLL" => "dddd, MMMM D, YYYY h:mm A",
+        other => other,
+    };
+
+    let mut converted = String::new();
+    let mut rest = date_format;
+
+    'outer: while !rest.is_empty() {
+        if let Some(escaped) = rest.strip_prefix('[') {
+            let end = escaped.find(']').unwrap_or(escaped.len());
+            literal(&escaped[..end], &mut converted);
+            rest = escaped.get(end + 1..).unwrap_or("");
+            continue;
+        }
+
+        for (token, replacement) in tokens {
+            if let Some(after) = rest.strip_prefix(token) {
+                converted.push_str(replacement);
+                rest = after;
+                continue 'outer;
+            }
+        }
+
+        let c = rest.chars().next().unwrap();
+        literal(&rest[..c.len_utf8()], &mut converted);
+        rest = &rest[c.len_utf8()..];
+    }
+
+    converted
+}
+
+fn excel_literal(text: &str) -> String {
+    format!("\"{}\"", text.replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use tiberius::numeric::Decimal;
+
+    fn currency_format() -> ColumnLabelFormat {
+        let mut format = ColumnLabelFormat::new_number();
+        format.style = "currency".to_string();
+        format.currency = Some("USD".to_string());
+        format
+    }
+
+    #[test]
+    fn test_decimal_formatting_is_exact() {
+        let value = DataType::Decimal(Some(Decimal::new(-123456789012345678, 4)));
+        let mut format = currency_format();
+        format.minimum_fraction_digits = Some(2);
+        format.maximum_fraction_digits = None;
+
+        assert_eq!(format_value(&value, &format), "-$12,345,678,901,234.5678");
+        assert_eq!(raw_value(&value), "-12345678901234.5678");
+
+        format.maximum_fraction_digits = Some(2);
+        assert_eq!(format_value(&value, &format), "-$12,345,678,901,234.57");
+    }
+
+    #[test]
+    fn test_number_formatting_options() {
+        let mut format = ColumnLabelFormat::new_number();
+        format.style = "percent".to_string();
+        format.multiplier = Some(100.0);
+        format.maximum_fraction_digits = Some(1);
+        assert_eq!(format_value(&DataType::Float8(Some(0.1234)), &format), "12.3%");
+
+        let mut format = ColumnLabelFormat::new_number();
+        format.compact_numbers = Some(true);
+        format.prefix = Some("~".to_string());
+        format.suffix = Some(" users".to_string());
+        assert_eq!(format_value(&DataType::Int8(Some(2_500_000)), &format), "~2.5M users");
+
+        let format = ColumnLabelFormat::new_number();
+        assert_eq!(format_value(&DataType::Float8(Some(-0.001)), &format), "0");
+        assert_eq!(format_value(&DataType::Null, &format), "0");
+    }
+
+    #[test]
+    fn test_date_formatting() {
+        let mut format = ColumnLabelFormat::new_date();
+        format.date_format = Some("MMM D, YYYY [at] h:mm A".to_string());
+        let value = DataType::Timestamp(NaiveDate::from_ymd_opt(2024, 3, 5).unwrap().and_hms_opt(14, 7, 0));
+
+        assert_eq!(format_value(&value, &format), "Mar 5, 2024 at 2:07 PM");
+        assert_eq!(excel_date_format("MMM D, YYYY [at] h:mm A"), "mmm d, yyyy \"at\" h:mm AM/PM");
+        assert_eq!(dayjs_to_chrono("LL"), "%B %-d, %Y");
+    }
+
+    #[test]
+    fn test_excel_number_format() {
+        let mut format = currency_format();
+        format.minimum_fraction_digits = Some(2);
+        format.maximum_fraction_digits = Some(4);
+
+        assert_eq!(excel_number_format(&format), "\"$\"#,##0.00##");
+    }
+}
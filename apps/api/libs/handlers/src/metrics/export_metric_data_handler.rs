@@ -0,0 +1,442 @@
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, Result};
+use arrow::datatypes::SchemaRef;
+use database::types::{ColumnLabelFormat, MetricYml};
+use indexmap::IndexMap;
+use middleware::AuthenticatedUser;
+use parquet::arrow::ArrowWriter;
+use query_engine::arrow_conversion::{rows_to_record_batch, schema_from_rows};
+use query_engine::data_types::DataType;
+use query_engine::metric_parameters::{bind_metric_sql_for_data_source, ParameterOverrides};
+use query_engine::pagination::{query_page_for_user, PageCursor, MAX_PAGE_SIZE};
+use rust_xlsxwriter::{Format, Workbook, Worksheet};
+use serde::Deserialize;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+use super::export_formatting::{
+    column_header, excel_date_format, excel_number_format, format_value, raw_value, ExportNumber,
+};
+use super::get_metric_data_handler::get_metric_with_data_access;
+
+/// Excel's hard limit on rows per worksheet, including the header row
+const XLSX_MAX_ROWS: u32 = 1_048_576;
+const XLSX_MIN_COLUMN_WIDTH: usize = 10;
+const XLSX_MAX_COLUMN_WIDTH: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MetricExportFormat {
+    Csv,
+    Xlsx,
+    Parquet,
+}
+
+impl MetricExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            MetricExportFormat::Csv => "text/csv",
+            MetricExportFormat::Xlsx => {
+                "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"
+            }
+            MetricExportFormat::Parquet => "application/vnd.apache.parquet",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            MetricExportFormat::Csv => "csv",
+            MetricExportFormat::Xlsx => "xlsx",
+            MetricExportFormat::Parquet => "parquet",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportMetricDataRequest {
+    pub metric_id: Uuid,
+    pub version_number: Option<i32>,
+    pub password: Option<String>,
+    pub format: MetricExportFormat,
+    /// Format values using the metric's column label formats. Parquet output is always raw.
+    pub apply_formatting: bool,
+    /// Values for the metric's parameters, overriding their defaults
+    #[serde(default)]
+    pub parameters: ParameterOverrides,
+}
+
+pub struct MetricExport {
+    pub file_name: String,
+    pub format: MetricExportFormat,
+    /// File contents, in order. CSV and Parquet arrive a page at a time; XLSX arrives in one
+    /// piece once the workbook is complete.
+    pub chunks: mpsc::Receiver<Result<Vec<u8>>>,
+}
+
+/// Exports the full result of a metric's SQL as CSV, XLSX or Parquet
+pub async fn export_metric_data_handler(
+    request: ExportMetricDataRequest,
+    user: AuthenticatedUser,
+) -> Result<MetricExport> {
+    tracing::info!(
+        "Exporting metric data for metric_id: {}, user_id: {}, format: {:?}",
+        request.metric_id,
+        user.id,
+        request.format
+    );
+
+    let metric = get_metric_with_data_access(
+        &request.metric_id,
+        &user,
+        request.version_number,
+        request.password.clone(),
+    )
+    .await?;
+
+    let metric_yml: MetricYml = serde_yaml::from_str(&metric.file)
+        .map_err(|e| anyhow!("Failed to parse metric definition: {}", e))?;
+
+    // Label format keys are stored lowercased
+    let formats: IndexMap<String, ColumnLabelFormat> = if request.apply_formatting {
+        metric_yml.chart_config.base().column_label_formats.clone()
+    } else {
+        IndexMap::new()
+    };
+
+    let file_name = format!("{}.{}", export_file_stem(&metric.name), request.format.extension());
+    let data_source_id = metric.data_source_id;
+    let sql = bind_metric_sql_for_data_source(&metric_yml, &request.parameters, &data_source_id).await?;
+    let format = request.format;
+    let metric_id = request.metric_id;
+
+    let (tx, rx) = mpsc::channel(2);
+
+    tokio::spawn(async move {
+        let mut exporter = Exporter::new(format, formats);
+
+        if let Err(e) = export_pages(&tx, &mut exporter, &user.id, &data_source_id, &sql).await {
+            tracing::error!("Error exporting data for metric {}: {}", metric_id, e);
+            let _ = tx.send(Err(e)).await;
+        }
+    });
+
+    Ok(MetricExport {
+        file_name,
+        format,
+        chunks: rx,
+    })
+}
+
+async fn export_pages(
+    tx: &mpsc::Sender<Result<Vec<u8>>>,
+    exporter: &mut Exporter,
+    user_id: &Uuid,
+    data_source_id: &Uuid,
+    sql: &str,
+) -> Result<()> {
+    let mut cursor: Option<PageCursor> = None;
+
+    loop {
+        let page = query_page_for_user(user_id, data_source_id, sql, MAX_PAGE_SIZE, cursor, true).await?;
+        let chunk = exporter.write_page(&page.result.data)?;
+
+        if !chunk.is_empty() && tx.send(Ok(chunk)).await.is_err() {
+            // Client went away, stop querying
+            return Ok(());
+        }
+
+        match page.next_cursor {
+            Some(next) => cursor = Some(next),
+            None => break,
+        }
+    }
+
+    let trailer = exporter.finish()?;
+    if !trailer.is_empty() {
+        let _ = tx.send(Ok(trailer)).await;
+    }
+
+    Ok(())
+}
+
+/// In-memory sink that writers append to and the exporter drains after every page
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl SharedBuffer {
+    fn take(&self) -> Vec<u8> {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Incremental writer for one export. Column order and, for Parquet, the schema are fixed by
+/// the first page.
+struct Exporter {
+    formats: IndexMap<String, ColumnLabelFormat>,
+    columns: Option<Vec<String>>,
+    buffer: SharedBuffer,
+    target: ExportTarget,
+}
+
+enum ExportTarget {
+    Csv(csv::Writer<SharedBuffer>),
+    Xlsx { workbook: Workbook, next_row: u32 },
+    Parquet(Option<(SchemaRef, ArrowWriter<SharedBuffer>)>),
+}
+
+impl Exporter {
+    fn new(format: MetricExportFormat, formats: IndexMap<String, ColumnLabelFormat>) -> Self {
+        let buffer = SharedBuffer::default();
+
+        let target = match format {
+            MetricExportFormat::Csv => ExportTarget::Csv(csv::Writer::from_writer(buffer.clone())),
+            MetricExportFormat::Xlsx => {
+                // Rows are flushed to a temp file as they're written, so a large export
+                // doesn't hold every cell in memory
+                let mut workbook = Workbook::new();
+                workbook.add_worksheet_with_constant_memory();
+                ExportTarget::Xlsx {
+                    workbook,
+                    next_row: 0,
+                }
+            }
+            MetricExportFormat::Parquet => ExportTarget::Parquet(None),
+        };
+
+        Self {
+            formats,
+            columns: None,
+            buffer,
+            target,
+        }
+    }
+
+    /// Writes one page of rows and returns the bytes ready to send
+    fn write_page(&mut self, rows: &[IndexMap<String, DataType>]) -> Result<Vec<u8>> {
+        let first_page = self.columns.is_none();
+        if first_page {
+            self.columns = Some(
+                rows.first()
+                    .map(|row| row.keys().cloned().collect())
+                    .unwrap_or_default(),
+            );
+        }
+        let columns = self.columns.clone().unwrap_or_default();
+
+        match &mut self.target {
+            ExportTarget::Csv(writer) => {
+                if first_page {
+                    let headers: Vec<&str> = columns
+                        .iter()
+                        .map(|column| column_header(column, self.formats.get(&column.to_lowercase())))
+                        .collect();
+                    writer.write_record(&headers)?;
+                }
+
+                for row in rows {
+                    let record: Vec<String> = columns
+                        .iter()
+                        .map(|column| {
+                            let value = row.get(column).unwrap_or(&DataType::Null);
+                            match self.formats.get(&column.to_lowercase()) {
+                                Some(format) => format_value(value, format),
+                                None => raw_value(value),
+                            }
+                        })
+                        .collect();
+                    writer.write_record(&record)?;
+                }
+
+                writer.flush()?;
+                Ok(self.buffer.take())
+            }
+            ExportTarget::Xlsx { workbook, next_row } => {
+                if *next_row as usize + rows.len() + usize::from(first_page) > XLSX_MAX_ROWS as usize {
+                    return Err(anyhow!(
+                        "Result has more rows than an Excel worksheet can hold ({}); export as CSV or Parquet instead",
+                        XLSX_MAX_ROWS - 1
+                    ));
+                }
+
+                let worksheet = workbook.worksheet_from_index(0)?;
+                let formats = &self.formats;
+                let format_for = |column: &str| formats.get(&column.to_lowercase());
+
+                if first_page {
+                    let bold = Format::new().set_bold();
+                    for (col, column) in columns.iter().enumerate() {
+                        let header = column_header(column, format_for(column));
+                        // Autofit only sees the last row in constant memory mode, so size
+                        // columns by their header instead
+                        let width = (header.chars().count() + 2).clamp(XLSX_MIN_COLUMN_WIDTH, XLSX_MAX_COLUMN_WIDTH);
+                        worksheet.set_column_width(col as u16, width as f64)?;
+                        worksheet.write_string_with_format(0, col as u16, header, &bold)?;
+                    }
+                    *next_row = 1;
+                }
+
+                let cell_formats: Vec<(Format, Format)> = columns
+                    .iter()
+                    .map(|column| xlsx_cell_formats(format_for(column)))
+                    .collect();
+
+                for row in rows {
+                    for (col, column) in columns.iter().enumerate() {
+                        let value = row.get(column).unwrap_or(&DataType::Null);
+                        write_xlsx_cell(
+                            worksheet,
+                            *next_row,
+                            col as u16,
+                            value,
+                            format_for(column),
+                            &cell_formats[col],
+                        )?;
+                    }
+                    *next_row += 1;
+                }
+
+                // The workbook is only serialized once it is complete
+                Ok(Vec::new())
+            }
+            ExportTarget::Parquet(writer) => {
+                if writer.is_none() {
+                    let schema = schema_from_rows(rows);
+                    let arrow_writer = ArrowWriter::try_new(self.buffer.clone(), schema.clone(), None)?;
+                    *writer = Some((schema, arrow_writer));
+                }
+                let (schema, writer) = writer.as_mut().unwrap();
+
+                if !rows.is_empty() {
+                    let batch = rows_to_record_batch(schema, rows)?;
+                    writer.write(&batch)?;
+                    // Close the row group so its bytes can be sent right away
+                    writer.flush()?;
+                }
+
+                Ok(self.buffer.take())
+            }
+        }
+    }
+
+    /// Completes the file and returns any remaining bytes
+    fn finish(&mut self) -> Result<Vec<u8>> {
+        match &mut self.target {
+            ExportTarget::Csv(writer) => {
+                writer.flush()?;
+                Ok(self.buffer.take())
+            }
+            ExportTarget::Xlsx { workbook, .. } => Ok(workbook.save_to_buffer()?),
+            ExportTarget::Parquet(writer) => {
+                // An empty result still produces a valid (empty) file
+                let (_, writer) = match writer.take() {
+                    Some(writer) => writer,
+                    None => {
+                        let schema = schema_from_rows(&[]);
+                        let arrow_writer = ArrowWriter::try_new(self.buffer.clone(), schema.clone(), None)?;
+                        (schema, arrow_writer)
+                    }
+                };
+                writer.close()?;
+                Ok(self.buffer.take())
+            }
+        }
+    }
+}
+
+/// Number and date cell formats for one XLSX column
+fn xlsx_cell_formats(format: Option<&ColumnLabelFormat>) -> (Format, Format) {
+    let number_format = match format {
+        Some(format) if format.column_type == "number" => {
+            Format::new().set_num_format(excel_number_format(format))
+        }
+        _ => Format::new(),
+    };
+
+    let date_format = match format.and_then(|f| f.date_format.as_deref()) {
+        Some(date_format) if date_format != "auto" => {
+            Format::new().set_num_format(excel_date_format(date_format))
+        }
+        _ => Format::new().set_num_format("yyyy-mm-dd hh:mm:ss"),
+    };
+
+    (number_format, date_format)
+}
+
+fn write_xlsx_cell(
+    worksheet: &mut Worksheet,
+    row: u32,
+    col: u16,
+    value: &DataType,
+    format: Option<&ColumnLabelFormat>,
+    (number_format, date_format): &(Format, Format),
+) -> Result<()> {
+    if let Some(number) = ExportNumber::from_value(value) {
+        let is_number_column = format.is_none_or(|f| f.column_type == "number");
+
+        // Decimals with more digits than a spreadsheet number can hold are written as text
+        if is_number_column && number.fits_f64() {
+            let multiplier = format.and_then(|f| f.multiplier).unwrap_or(1.0);
+            worksheet.write_number_with_format(row, col, number.to_f64() * multiplier, number_format)?;
+            return Ok(());
+        }
+    }
+
+    match value {
+        DataType::Date(Some(date)) => {
+            let date_format = if format.is_some_and(|f| f.date_format.as_deref().is_some_and(|d| d != "auto")) {
+                date_format.clone()
+            } else {
+                Format::new().set_num_format("yyyy-mm-dd")
+            };
+            worksheet.write_datetime_with_format(row, col, date, &date_format)?;
+        }
+        DataType::Timestamp(Some(ts)) => {
+            worksheet.write_datetime_with_format(row, col, ts, date_format)?;
+        }
+        DataType::Timestamptz(Some(ts)) => {
+            worksheet.write_datetime_with_format(row, col, ts.naive_utc(), date_format)?;
+        }
+        DataType::Bool(Some(b)) => {
+            worksheet.write_boolean(row, col, *b)?;
+        }
+        other => {
+            let text = match format {
+                Some(format) => format_value(other, format),
+                None => raw_value(other),
+            };
+            if !text.is_empty() {
+                worksheet.write_string(row, col, text)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// File name for an export, derived from the metric name
+fn export_file_stem(name: &str) -> String {
+    let stem: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    let stem = stem.trim_matches('_');
+
+    if stem.is_empty() {
+        "metric".to_string()
+    } else {
+        stem.to_string()
+    }
+}
@@ -2,7 +2,7 @@ use anyhow::{anyhow, Result};
 use database::{
     pool::get_pg_pool,
     schema::metric_files,
-    types::{data_metadata::DataMetadata, MetricYml},
+    types::{data_metadata::DataMetadata, DashboardYml, MetricYml},
 };
 use diesel::{ExpressionMethods, QueryDsl};
 use diesel_async::RunQueryDsl;
@@ -13,7 +13,13 @@ use sharing::asset_access_checks::check_metric_collection_access;
 use uuid::Uuid;
 
 use query_engine::data_types::DataType;
+use query_engine::dashboard_filters::{filtered_metric_sql_for_data_source, DashboardFilterValues};
+use query_engine::metric_parameters::{bind_metric_sql_for_data_source, ParameterOverrides};
+use query_engine::pagination::{cached_first_page_for_user, query_page_for_user, PageCursor};
+use query_engine::query_cache::{metric_cache_scope, QueryCacheOptions, DEFAULT_QUERY_CACHE_TTL};
+use std::time::Duration;
 
+use crate::dashboards::get_dashboard_handler;
 use crate::metrics::{get_metric_for_dashboard_handler, get_metric_handler, BusterMetric};
 
 /// Request structure for the get_metric_data handler
@@ -23,6 +29,18 @@ pub struct GetMetricDataRequest {
     pub version_number: Option<i32>,
     pub limit: Option<i64>,
     pub password: Option<String>,
+    /// Bypass the query result cache and re-run the SQL against the data source
+    pub force_refresh: bool,
+    /// Cursor from a previous response's `next_cursor`; fetches the following page
+    pub cursor: Option<String>,
+    /// Values for the metric's parameters, overriding their defaults
+    #[serde(default)]
+    pub parameters: ParameterOverrides,
+    /// Dashboard the metric is shown on; its filters are applied to the data
+    pub dashboard_id: Option<Uuid>,
+    /// Values picked for the dashboard's filters, overriding their defaults
+    #[serde(default)]
+    pub dashboard_filters: DashboardFilterValues,
 }
 
 /// Structure for the metric data response
@@ -32,6 +50,11 @@ pub struct MetricDataResponse {
     pub data: Vec<IndexMap<String, DataType>>,
     pub data_metadata: DataMetadata,
     pub has_more_records: bool,
+    /// Pass back as `cursor` to fetch the next page; `None` on the last page
+    pub next_cursor: Option<String>,
+    /// Dashboard filters the data was fetched without, and why
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
 }
 
 /// Handler to retrieve both the metric definition and its associated data
@@ -45,12 +68,210 @@ pub async fn get_metric_data_handler(
         user.id
     );
 
-    // --- Step 1: Try retrieving metric with standard permission checks ---
-    let metric_result = get_metric_handler(
+    let metric = get_metric_with_data_access(
         &request.metric_id,
         &user,
         request.version_number,
-        request.password.clone(), // Clone password for potential reuse/logging
+        request.password.clone(),
+    )
+    .await?;
+
+    tracing::debug!("Parsing metric definition from YAML to get SQL.");
+    // Parse the metric definition from YAML to get SQL
+    let metric_yml: MetricYml = match serde_yaml::from_str(&metric.file) {
+        Ok(yml) => yml,
+        Err(parse_err) => {
+            tracing::error!("Failed to parse metric YAML: {}", parse_err);
+            return Err(anyhow!("Failed to parse metric definition: {}", parse_err));
+        }
+    };
+    let cache_ttl = metric_yml
+        .cache_ttl
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_QUERY_CACHE_TTL);
+
+    // --- USE DIRECT DATA SOURCE ID ---
+    let data_source_id = metric.data_source_id; // Already a Uuid
+    tracing::debug!(metric_id = %request.metric_id, data_source_id = %data_source_id, "Using direct data source ID from metric");
+
+    // Bind parameter values and apply dashboard filters before the SQL is secured, paginated
+    // or used as a cache key
+    let sql_result = match request.dashboard_id {
+        Some(dashboard_id) => {
+            let dashboard =
+                get_dashboard_content(&dashboard_id, &user, request.password.clone()).await?;
+            filtered_metric_sql_for_data_source(
+                &request.metric_id,
+                &metric_yml,
+                &request.parameters,
+                &dashboard,
+                &request.dashboard_filters,
+                &data_source_id,
+            )
+            .await
+            .map(|filtered| (filtered.sql, filtered.warnings))
+        }
+        None => bind_metric_sql_for_data_source(&metric_yml, &request.parameters, &data_source_id)
+            .await
+            .map(|sql| (sql, Vec::new())),
+    };
+    let (sql, warnings) = match sql_result {
+        Ok(prepared) => prepared,
+        Err(e) => {
+            tracing::error!("Failed to prepare SQL for metric {}: {}", request.metric_id, e);
+            return Err(e);
+        }
+    };
+    for warning in &warnings {
+        tracing::warn!(metric_id = %request.metric_id, "{}", warning);
+    }
+
+    tracing::info!(
+        "Querying data for metric {}. Data source: {}, Limit: {:?}", // Removed dataset name as we don't fetch it anymore
+        request.metric_id,
+        data_source_id, // Use the direct ID
+        request.limit
+    );
+
+    let display_limit = request.limit.unwrap_or(5000).min(5000);
+
+    // Try to get cached metadata first
+    let mut conn_meta = get_pg_pool().get().await?;
+    let cached_metadata = metric_files::table
+        .filter(metric_files::id.eq(request.metric_id))
+        .select(metric_files::data_metadata)
+        .first::<Option<DataMetadata>>(&mut conn_meta)
+        .await
+        .map_err(|e| anyhow!("Error retrieving cached metadata: {}", e))?;
+    tracing::debug!("Cached metadata found: {}", cached_metadata.is_some());
+
+    // Later pages seek past the cursor directly; only the first page is cached
+    if let Some(cursor) = &request.cursor {
+        let cursor = PageCursor::decode(cursor)?;
+        let page = query_page_for_user(&user.id, &data_source_id, &sql, display_limit, Some(cursor), false)
+            .await
+            .map_err(|e| anyhow!("Error executing metric query: {}", e))?;
+
+        let mut data_metadata = cached_metadata.unwrap_or(page.result.metadata);
+        data_metadata.row_count = page.result.data.len() as i64;
+
+        return Ok(MetricDataResponse {
+            metric_id: request.metric_id,
+            data: page.result.data,
+            data_metadata,
+            has_more_records: page.has_more_records,
+            next_cursor: page.next_cursor.map(|cursor| cursor.encode()),
+            warnings,
+        });
+    }
+
+    // Execute the first page of the metric query as written; ordered queries get a cursor
+    let cache_options = QueryCacheOptions {
+        scope: Some(metric_cache_scope(&request.metric_id)),
+        ttl: cache_ttl,
+        force_refresh: request.force_refresh,
+    };
+
+    let page = match cached_first_page_for_user(
+        &user.id,
+        &data_source_id, // Use the direct ID
+        &sql,
+        display_limit,
+        cache_options,
+    )
+    .await
+    {
+        Ok(page) => {
+            tracing::info!(
+                "Successfully executed metric query. Rows returned: {}",
+                page.result.data.len()
+            );
+            page
+        }
+        Err(e) => {
+            tracing::error!(
+                "Error executing metric query for metric {}: {}",
+                request.metric_id,
+                e
+            );
+            return Err(anyhow!("Error executing metric query: {}", e));
+        }
+    };
+
+    let has_more_records = page.has_more_records;
+    let data = page.result.data;
+    let query_result_metadata = page.result.metadata;
+
+    // Determine which metadata to use
+    let final_metadata = if let Some(metadata) = cached_metadata {
+        tracing::debug!(
+            "Using cached metadata. Cached rows: {}, Query rows: {}",
+            metadata.row_count,
+            data.len()
+        );
+        // Use cached metadata but update row count if it differs significantly or if cached count is 0
+        // (We update if different because the cache might be stale regarding row count)
+        if metadata.row_count != data.len() as i64 {
+            tracing::debug!("Row count changed. Updating metadata row count.");
+            let mut updated_metadata = metadata.clone();
+            updated_metadata.row_count = data.len() as i64;
+            // Potentially update updated_at? For now, just row count.
+            updated_metadata
+        } else {
+            metadata
+        }
+    } else {
+        tracing::debug!("No cached metadata found. Using metadata from query result.");
+        // No cached metadata, use the one from query_result
+        let mut metadata = query_result_metadata;
+        // Update row count to match the actual data we're returning
+        metadata.row_count = data.len() as i64;
+        metadata
+    };
+
+    // Construct and return the response
+    tracing::info!(
+        "Successfully retrieved data for metric {}. Returning response with has_more_records: {}",
+        request.metric_id,
+        has_more_records
+    );
+    Ok(MetricDataResponse {
+        metric_id: request.metric_id,
+        data,
+        data_metadata: final_metadata,
+        has_more_records,
+        next_cursor: page.next_cursor.map(|cursor| cursor.encode()),
+        warnings,
+    })
+}
+
+/// Loads a dashboard's content to apply its filters, after the same access checks as viewing
+/// the dashboard
+async fn get_dashboard_content(
+    dashboard_id: &Uuid,
+    user: &AuthenticatedUser,
+    password: Option<String>,
+) -> Result<DashboardYml> {
+    let dashboard = get_dashboard_handler(dashboard_id, user, None, password).await?;
+
+    serde_yaml::from_str(&dashboard.dashboard.file)
+        .map_err(|e| anyhow!("Failed to parse dashboard {}: {}", dashboard_id, e))
+}
+
+/// Fetches a metric for data access. Falls back to dashboard, chat and collection access when
+/// the user can't see the metric directly.
+pub(crate) async fn get_metric_with_data_access(
+    metric_id: &Uuid,
+    user: &AuthenticatedUser,
+    version_number: Option<i32>,
+    password: Option<String>,
+) -> Result<BusterMetric> {
+    // --- Step 1: Try retrieving metric with standard permission checks ---
+    let metric_result = get_metric_handler(
+        metric_id,
+        user,
+        version_number,
+        password.clone(), // Clone password for potential reuse/logging
     )
     .await;
 
@@ -73,7 +294,7 @@ pub async fn get_metric_data_handler(
                 );
 
                 // Check if user has access to ANY dashboard containing this metric (including public dashboards)
-                let has_dashboard_access = sharing::check_metric_dashboard_access(&request.metric_id, &user.id, &user.organizations)
+                let has_dashboard_access = sharing::check_metric_dashboard_access(metric_id, &user.id, &user.organizations)
                     .await
                     .unwrap_or(false);
 
@@ -81,10 +302,10 @@ pub async fn get_metric_data_handler(
                     // User has access to a dashboard containing this metric
                     tracing::info!("Found associated dashboard with user access. Fetching metric with dashboard context.");
                     match get_metric_for_dashboard_handler(
-                        &request.metric_id,
-                        &user,
-                        request.version_number,
-                        request.password.clone(),
+                        metric_id,
+                        user,
+                        version_number,
+                        password.clone(),
                     )
                     .await
                     {
@@ -103,7 +324,7 @@ pub async fn get_metric_data_handler(
                 } else {
                     // No dashboard access, check if user has access via a chat
                     tracing::info!("No dashboard association found. Checking chat access.");
-                    let has_chat_access = sharing::check_metric_chat_access(&request.metric_id, &user.id, &user.organizations)
+                    let has_chat_access = sharing::check_metric_chat_access(metric_id, &user.id, &user.organizations)
                         .await
                         .unwrap_or(false);
 
@@ -111,10 +332,10 @@ pub async fn get_metric_data_handler(
                         // User has access to a chat containing this metric
                         tracing::info!("Found associated chat with user access. Fetching metric with chat context.");
                         match get_metric_for_dashboard_handler(
-                            &request.metric_id,
-                            &user,
-                            request.version_number,
-                            request.password.clone(),
+                            metric_id,
+                            user,
+                            version_number,
+                            password.clone(),
                         )
                         .await
                         {
@@ -133,7 +354,7 @@ pub async fn get_metric_data_handler(
                     } else {
                         // No chat access, check if user has access via a collection
                         tracing::info!("No chat association found. Checking collection access.");
-                        let has_collection_access = check_metric_collection_access(&request.metric_id, &user.id, &user.organizations)
+                        let has_collection_access = check_metric_collection_access(metric_id, &user.id, &user.organizations)
                             .await
                             .unwrap_or(false);
 
@@ -141,10 +362,10 @@ pub async fn get_metric_data_handler(
                             // User has access to a collection containing this metric
                             tracing::info!("Found associated collection with user access. Fetching metric with collection context.");
                             match get_metric_for_dashboard_handler(
-                                &request.metric_id,
-                                &user,
-                                request.version_number,
-                                request.password.clone(),
+                                metric_id,
+                                user,
+                                version_number,
+                                password.clone(),
                             )
                             .await
                             {
@@ -175,114 +396,5 @@ pub async fn get_metric_data_handler(
         }
     };
 
-    // --- Step 5: Proceed with data fetching using the obtained metric definition ---
-    tracing::debug!("Parsing metric definition from YAML to get SQL.");
-    // Parse the metric definition from YAML to get SQL
-    let metric_yml: MetricYml = match serde_yaml::from_str(&metric.file) {
-        Ok(yml) => yml,
-        Err(parse_err) => {
-            tracing::error!("Failed to parse metric YAML: {}", parse_err);
-            return Err(anyhow!("Failed to parse metric definition: {}", parse_err));
-        }
-    };
-    let sql = metric_yml.sql;
-
-    // --- USE DIRECT DATA SOURCE ID ---
-    let data_source_id = metric.data_source_id; // Already a Uuid
-    tracing::debug!(metric_id = %request.metric_id, data_source_id = %data_source_id, "Using direct data source ID from metric");
-
-    tracing::info!(
-        "Querying data for metric {}. Data source: {}, Limit: {:?}", // Removed dataset name as we don't fetch it anymore
-        request.metric_id,
-        data_source_id, // Use the direct ID
-        request.limit
-    );
-
-    // Determine the actual query limit - we query for one extra record to check if there are more
-    let display_limit = request.limit.unwrap_or(5000).min(5000);
-    let query_limit = display_limit + 1;
-
-    // Try to get cached metadata first
-    let mut conn_meta = get_pg_pool().get().await?;
-    let cached_metadata = metric_files::table
-        .filter(metric_files::id.eq(request.metric_id))
-        .select(metric_files::data_metadata)
-        .first::<Option<DataMetadata>>(&mut conn_meta)
-        .await
-        .map_err(|e| anyhow!("Error retrieving cached metadata: {}", e))?;
-    tracing::debug!("Cached metadata found: {}", cached_metadata.is_some());
-
-    // Execute the query to get the metric data
-    let query_result = match query_engine::data_source_query_routes::query_engine::query_engine(
-        &data_source_id, // Use the direct ID
-        &sql,
-        Some(query_limit),
-    )
-    .await
-    {
-        Ok(result) => {
-            tracing::info!(
-                "Successfully executed metric query. Rows returned: {}",
-                result.data.len()
-            );
-            result
-        }
-        Err(e) => {
-            tracing::error!(
-                "Error executing metric query for metric {}: {}",
-                request.metric_id,
-                e
-            );
-            return Err(anyhow!("Error executing metric query: {}", e));
-        }
-    };
-
-    // Check if we have more records than the display limit
-    let has_more_records = query_result.data.len() > display_limit as usize;
-    
-    // Truncate to display limit if we got more
-    let mut data = query_result.data;
-    if has_more_records {
-        data.truncate(display_limit as usize);
-    }
-
-    // Determine which metadata to use
-    let final_metadata = if let Some(metadata) = cached_metadata {
-        tracing::debug!(
-            "Using cached metadata. Cached rows: {}, Query rows: {}",
-            metadata.row_count,
-            data.len()
-        );
-        // Use cached metadata but update row count if it differs significantly or if cached count is 0
-        // (We update if different because the cache might be stale regarding row count)
-        if metadata.row_count != data.len() as i64 {
-            tracing::debug!("Row count changed. Updating metadata row count.");
-            let mut updated_metadata = metadata.clone();
-            updated_metadata.row_count = data.len() as i64;
-            // Potentially update updated_at? For now, just row count.
-            updated_metadata
-        } else {
-            metadata
-        }
-    } else {
-        tracing::debug!("No cached metadata found. Using metadata from query result.");
-        // No cached metadata, use the one from query_result
-        let mut metadata = query_result.metadata.clone();
-        // Update row count to match the actual data we're returning
-        metadata.row_count = data.len() as i64;
-        metadata
-    };
-
-    // Construct and return the response
-    tracing::info!(
-        "Successfully retrieved data for metric {}. Returning response with has_more_records: {}",
-        request.metric_id,
-        has_more_records
-    );
-    Ok(MetricDataResponse {
-        metric_id: request.metric_id,
-        data,
-        data_metadata: final_metadata,
-        has_more_records,
-    })
+    Ok(metric)
 }
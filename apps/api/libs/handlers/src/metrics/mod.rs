@@ -1,21 +1,32 @@
 pub mod bulk_update_metrics_handler;
 pub mod color_palette_helpers;
+pub mod create_metric_handler;
 pub mod delete_metric_handler;
+pub mod export_formatting;
+pub mod export_metric_data_handler;
 pub mod get_metric_data_handler;
 pub mod get_metric_handler;
 pub mod list_metrics_handler;
 pub mod sharing;
+pub mod stream_metric_data_handler;
 pub mod types;
 pub mod update_metric_handler;
 pub mod get_metric_for_dashboard_handler;
 
 // Re-export specific items from handlers
 pub use bulk_update_metrics_handler::*;
+pub use create_metric_handler::*;
 pub use delete_metric_handler::*;
+pub use export_metric_data_handler::{
+    export_metric_data_handler, ExportMetricDataRequest, MetricExport, MetricExportFormat,
+};
 pub use get_metric_handler::*;
 pub use list_metrics_handler::*;
 pub use update_metric_handler::*;
 pub use get_metric_for_dashboard_handler::get_metric_for_dashboard_handler;
+pub use stream_metric_data_handler::{
+    stream_metric_data_handler, MetricDataStreamFormat, StreamMetricDataRequest,
+};
 
 // For get_metric_data_handler, only export the handler functions and request types
 // but not the types that conflict with types.rs
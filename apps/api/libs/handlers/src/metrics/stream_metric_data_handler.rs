@@ -0,0 +1,161 @@
+use anyhow::{anyhow, Result};
+use arrow::datatypes::SchemaRef;
+use arrow::ipc::writer::StreamWriter;
+use database::types::MetricYml;
+use middleware::AuthenticatedUser;
+use query_engine::arrow_conversion::{rows_to_record_batch, schema_from_rows};
+use query_engine::metric_parameters::{bind_metric_sql_for_data_source, ParameterOverrides};
+use query_engine::pagination::{query_page_for_user, PageCursor, MAX_PAGE_SIZE};
+use serde::Deserialize;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+use super::get_metric_data_handler::get_metric_with_data_access;
+
+/// Wire format of a streamed metric result
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MetricDataStreamFormat {
+    /// One JSON object per row, newline separated
+    #[default]
+    Ndjson,
+    /// Arrow IPC stream format
+    Arrow,
+}
+
+impl MetricDataStreamFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            MetricDataStreamFormat::Ndjson => "application/x-ndjson",
+            MetricDataStreamFormat::Arrow => "application/vnd.apache.arrow.stream",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StreamMetricDataRequest {
+    pub metric_id: Uuid,
+    pub version_number: Option<i32>,
+    pub password: Option<String>,
+    pub format: MetricDataStreamFormat,
+    /// Rows fetched from the data source per round trip
+    pub page_size: Option<i64>,
+    /// Values for the metric's parameters, overriding their defaults
+    #[serde(default)]
+    pub parameters: ParameterOverrides,
+}
+
+/// Streams the full result of a metric's SQL, one page at a time, without the 5000-row cap
+/// of the regular data endpoint. Access is checked before the stream starts; errors that
+/// happen mid-stream are sent down the channel and end it.
+pub async fn stream_metric_data_handler(
+    request: StreamMetricDataRequest,
+    user: AuthenticatedUser,
+) -> Result<mpsc::Receiver<Result<Vec<u8>>>> {
+    let metric = get_metric_with_data_access(
+        &request.metric_id,
+        &user,
+        request.version_number,
+        request.password.clone(),
+    )
+    .await?;
+
+    let metric_yml: MetricYml = serde_yaml::from_str(&metric.file)
+        .map_err(|e| anyhow!("Failed to parse metric definition: {}", e))?;
+
+    let data_source_id = metric.data_source_id;
+    let sql = bind_metric_sql_for_data_source(&metric_yml, &request.parameters, &data_source_id).await?;
+    let page_size = request.page_size.unwrap_or(MAX_PAGE_SIZE);
+    let format = request.format;
+
+    // A small buffer keeps at most a couple of pages in memory when the client reads slowly
+    let (tx, rx) = mpsc::channel(2);
+
+    tokio::spawn(async move {
+        if let Err(e) = stream_pages(
+            &tx,
+            &user.id,
+            &data_source_id,
+            &sql,
+            page_size,
+            format,
+        )
+        .await
+        {
+            tracing::error!(
+                "Error streaming data for metric {}: {}",
+                request.metric_id,
+                e
+            );
+            let _ = tx.send(Err(e)).await;
+        }
+    });
+
+    Ok(rx)
+}
+
+async fn stream_pages(
+    tx: &mpsc::Sender<Result<Vec<u8>>>,
+    user_id: &Uuid,
+    data_source_id: &Uuid,
+    sql: &str,
+    page_size: i64,
+    format: MetricDataStreamFormat,
+) -> Result<()> {
+    let mut cursor: Option<PageCursor> = None;
+    let mut arrow_writer: Option<(SchemaRef, StreamWriter<Vec<u8>>)> = None;
+    // The Arrow schema can't change once written, so read every column the way its later
+    // pages will come back: decimals and wide integers exactly, as text
+    let exact_decimals = format == MetricDataStreamFormat::Arrow;
+
+    loop {
+        let page =
+            query_page_for_user(user_id, data_source_id, sql, page_size, cursor, exact_decimals).await?;
+
+        let chunk = match format {
+            MetricDataStreamFormat::Ndjson => {
+                let mut chunk = Vec::new();
+                for row in &page.result.data {
+                    serde_json::to_writer(&mut chunk, row)?;
+                    chunk.push(b'\n');
+                }
+                chunk
+            }
+            MetricDataStreamFormat::Arrow => {
+                // The schema is fixed by the first page and written once at the start. A later
+                // value of another type fails the stream rather than being sent as null.
+                if arrow_writer.is_none() {
+                    let schema = schema_from_rows(&page.result.data);
+                    let writer = StreamWriter::try_new(Vec::new(), &schema)?;
+                    arrow_writer = Some((schema, writer));
+                }
+                let (schema, writer) = arrow_writer.as_mut().unwrap();
+
+                if !page.result.data.is_empty() {
+                    let batch = rows_to_record_batch(schema, &page.result.data)?;
+                    writer.write(&batch)?;
+                }
+
+                std::mem::take(writer.get_mut())
+            }
+        };
+
+        if !chunk.is_empty() && tx.send(Ok(chunk)).await.is_err() {
+            // Client went away, stop querying
+            return Ok(());
+        }
+
+        match page.next_cursor {
+            Some(next) => cursor = Some(next),
+            None => break,
+        }
+    }
+
+    if let Some((_, mut writer)) = arrow_writer {
+        writer.finish()?;
+        let trailer = std::mem::take(writer.get_mut());
+        let _ = tx.send(Ok(trailer)).await;
+    }
+
+    Ok(())
+}
@@ -1,3 +1,4 @@
+use agents::tools::categories::file_tools::metric_evaluation::spawn_metric_evaluation;
 use anyhow::{anyhow, bail, Result};
 use chrono::{DateTime, Utc};
 use database::{
@@ -16,7 +17,9 @@ use diesel::{insert_into, AsChangeset, ExpressionMethods, QueryDsl};
 use diesel_async::RunQueryDsl;
 use indexmap;
 use middleware::AuthenticatedUser;
-use query_engine::data_source_query_routes::query_engine::query_engine;
+use query_engine::data_source_query_routes::query_engine::query_engine_for_user;
+use query_engine::metric_parameters::{bind_metric_sql, ParameterOverrides};
+use query_engine::query_cache::{invalidate_query_cache_scope, metric_cache_scope};
 use serde_json::Value;
 use sharing::check_permission_access;
 use sql_analyzer::{analyze_query, types::TableKind};
@@ -193,6 +196,8 @@ pub async fn update_metric_handler(
     let requires_revalidation =
         request.sql.is_some() || request.file.is_some() || request.restore_to_version.is_some();
 
+    let mut evaluation_dialect: Option<String> = None;
+
     if requires_revalidation {
         let data_source_dialect = match data_sources::table
             .filter(data_sources::id.eq(data_source_id.unwrap()))
@@ -203,9 +208,13 @@ pub async fn update_metric_handler(
             Ok(dialect) => dialect.to_string(),
             Err(e) => return Err(anyhow!("Failed to fetch data source dialect: {}", e)),
         };
+        evaluation_dialect = Some(data_source_dialect.clone());
+
+        // Validate against the SQL as it runs with the parameters' default values
+        let bound_sql = bind_metric_sql(&final_content, &ParameterOverrides::new(), &data_source_dialect)?;
 
         // 1. Analyze SQL to get table names
-        let analysis_result = analyze_query(final_content.sql.clone(), &data_source_dialect).await?;
+        let analysis_result = analyze_query(bound_sql.clone(), &data_source_dialect).await?;
         let table_names: Vec<String> = analysis_result
             .tables
             .into_iter()
@@ -242,7 +251,7 @@ pub async fn update_metric_handler(
             }
 
             // 4. Execute Query for Metadata (using the same data_source_id)
-            match query_engine(&ds_id, &final_content.sql, Some(100)).await {
+            match query_engine_for_user(&user.id, &ds_id, &bound_sql, Some(100)).await {
                 Ok(query_result) => {
                     data_metadata = Some(query_result.metadata.clone());
                     // Update column formats based on new metadata
@@ -349,6 +358,16 @@ pub async fn update_metric_handler(
         .await
         .map_err(|e| anyhow!("Failed to update metric file record: {}", e))?;
 
+    // Cached results were computed from the previous SQL
+    if let Err(e) = invalidate_query_cache_scope(&metric_cache_scope(metric_id)).await {
+        tracing::warn!("Failed to invalidate query cache for metric {}: {}", metric_id, e);
+    }
+
+    // The SQL changed, so the previous evaluation no longer applies
+    if let Some(dialect) = evaluation_dialect {
+        spawn_metric_evaluation(*metric_id, final_content.clone(), dialect, user.id);
+    }
+
     // --- Update Dataset Associations for the NEW/UPDATED version ---
     let now = Utc::now();
     let new_associations: Vec<MetricFileToDataset> = validated_dataset_ids
@@ -0,0 +1,5 @@
+mod semantic_query_handler;
+pub mod types;
+
+pub use semantic_query_handler::semantic_query_handler;
+pub use types::{SemanticQueryRequest, SemanticQueryResponse};
@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Context, Result};
+use database::{enums::DataSourceType, pool::get_pg_pool, schema::{data_sources, datasets}};
+use dataset_security::has_all_datasets_access;
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl};
+use diesel_async::RunQueryDsl;
+use middleware::types::AuthenticatedUser;
+use query_engine::data_source_query_routes::query_engine::query_engine_for_user;
+use semantic_layer::{compile_query, models::Model as SemanticModel, SemanticQuery, TimeDimension};
+use tracing::warn;
+use uuid::Uuid;
+
+use super::types::{SemanticQueryRequest, SemanticQueryResponse};
+
+/// Handler for running a semantic query against the models deployed to a data source.
+///
+/// The request is compiled to SQL through the semantic layer, the user must have access to
+/// every dataset the SQL reads, and the SQL runs with the user's row-level filters and
+/// column masks like any other query.
+pub async fn semantic_query_handler(
+    user: &AuthenticatedUser,
+    request: SemanticQueryRequest,
+) -> Result<SemanticQueryResponse> {
+    if user.organizations.is_empty() {
+        return Err(anyhow!("User is not a member of any organization"));
+    }
+
+    let mut conn = get_pg_pool().get().await?;
+
+    let (organization_id, data_source_type) = data_sources::table
+        .filter(data_sources::id.eq(request.data_source_id))
+        .filter(data_sources::deleted_at.is_null())
+        .select((data_sources::organization_id, data_sources::type_))
+        .first::<(Uuid, DataSourceType)>(&mut conn)
+        .await
+        .optional()
+        .context("Failed to load data source")?
+        .ok_or_else(|| anyhow!("Data source not found"))?;
+
+    if !user.organizations.iter().any(|org| org.id == organization_id) {
+        return Err(anyhow!("Data source not found"));
+    }
+
+    let deployed = datasets::table
+        .filter(datasets::data_source_id.eq(request.data_source_id))
+        .filter(datasets::deleted_at.is_null())
+        .filter(datasets::yml_file.is_not_null())
+        .select((datasets::id, datasets::yml_file))
+        .load::<(Uuid, Option<String>)>(&mut conn)
+        .await
+        .context("Failed to load datasets")?;
+    drop(conn);
+
+    let mut models = Vec::new();
+    let mut dataset_ids: HashMap<String, Uuid> = HashMap::new();
+    for (dataset_id, yml_file) in deployed {
+        match serde_yaml::from_str::<SemanticModel>(yml_file.as_deref().unwrap_or_default()) {
+            Ok(model) => {
+                dataset_ids.insert(model.name.to_lowercase(), dataset_id);
+                models.push(model);
+            }
+            Err(e) => warn!(%dataset_id, "Skipping dataset with unparseable model: {}", e),
+        }
+    }
+
+    let query = semantic_query(&request)?;
+    let compiled = compile_query(&models, &query, data_source_type)
+        .map_err(|e| anyhow!("Invalid semantic query: {}", e))?;
+
+    let used_datasets: Vec<Uuid> = compiled
+        .models
+        .iter()
+        .filter_map(|name| dataset_ids.get(&name.to_lowercase()).copied())
+        .collect();
+    if !has_all_datasets_access(&user.id, &used_datasets).await? {
+        return Err(anyhow!(
+            "You don't have permission to query the datasets: {}",
+            compiled.models.join(", ")
+        ));
+    }
+
+    let limit = request.limit.map(|limit| i64::try_from(limit).unwrap_or(i64::MAX));
+    let result = query_engine_for_user(&user.id, &request.data_source_id, &compiled.sql, limit)
+        .await
+        .context("Failed to run semantic query")?;
+
+    Ok(SemanticQueryResponse {
+        sql: compiled.sql,
+        columns: compiled.columns,
+        data: result.data,
+        data_metadata: result.metadata,
+    })
+}
+
+fn semantic_query(request: &SemanticQueryRequest) -> Result<SemanticQuery> {
+    let time_dimension = match (&request.time_dimension, request.time_grain) {
+        (Some(dimension), Some(grain)) => Some(TimeDimension {
+            dimension: dimension.clone(),
+            grain,
+        }),
+        (None, None) => None,
+        (Some(_), None) => {
+            return Err(anyhow!("Invalid semantic query: time_dimension needs a time_grain"))
+        }
+        (None, Some(_)) => {
+            return Err(anyhow!("Invalid semantic query: time_grain needs a time_dimension"))
+        }
+    };
+
+    Ok(SemanticQuery {
+        metrics: request.metrics.clone(),
+        dimensions: request.dimensions.clone(),
+        filters: request.filters.clone(),
+        time_dimension,
+        order_by: request.order_by.clone(),
+        limit: request.limit,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use semantic_layer::TimeGrain;
+
+    #[test]
+    fn test_semantic_query_requires_time_dimension_and_grain_together() {
+        let request: SemanticQueryRequest = serde_json::from_value(serde_json::json!({
+            "data_source_id": Uuid::new_v4(),
+            "metrics": ["revenue"],
+            "time_dimension": "orders.created_at",
+            "time_grain": "month",
+            "limit": 12
+        }))
+        .unwrap();
+
+        let query = semantic_query(&request).unwrap();
+        assert_eq!(
+            query.time_dimension,
+            Some(TimeDimension {
+                dimension: "orders.created_at".to_string(),
+                grain: TimeGrain::Month,
+            })
+        );
+        assert_eq!(query.limit, Some(12));
+
+        let request = SemanticQueryRequest {
+            time_dimension: None,
+            ..request
+        };
+        assert!(semantic_query(&request)
+            .unwrap_err()
+            .to_string()
+            .contains("time_grain needs a time_dimension"));
+    }
+}
@@ -0,0 +1,33 @@
+use database::types::DataMetadata;
+use indexmap::IndexMap;
+use query_engine::data_types::DataType;
+use semantic_layer::{FilterRequest, MetricRequest, OrderBy, TimeGrain};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Request for querying governed metrics through the semantic layer
+#[derive(Debug, Clone, Deserialize)]
+pub struct SemanticQueryRequest {
+    pub data_source_id: Uuid,
+    #[serde(default)]
+    pub metrics: Vec<MetricRequest>,
+    #[serde(default)]
+    pub dimensions: Vec<String>,
+    #[serde(default)]
+    pub filters: Vec<FilterRequest>,
+    /// Date or timestamp dimension bucketed by `time_grain`
+    pub time_dimension: Option<String>,
+    pub time_grain: Option<TimeGrain>,
+    #[serde(default)]
+    pub order_by: Vec<OrderBy>,
+    pub limit: Option<u64>,
+}
+
+/// Rows of a semantic query, with the SQL it compiled to
+#[derive(Debug, Serialize)]
+pub struct SemanticQueryResponse {
+    pub sql: String,
+    pub columns: Vec<String>,
+    pub data: Vec<IndexMap<String, DataType>>,
+    pub data_metadata: DataMetadata,
+}
@@ -0,0 +1,115 @@
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use database::{enums::AssetType, models::Subscription, pool::get_pg_pool, schema::subscriptions};
+use diesel_async::RunQueryDsl;
+use middleware::types::AuthenticatedUser;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use super::helpers::{
+    check_asset_access, generate_signing_secret, normalize_recipients, normalize_subscription_name,
+    validate_destination, validate_schedule,
+};
+use super::types::{SubscriptionChannel, SubscriptionFormat, SubscriptionResponse};
+
+/// Request for subscribing to a metric or dashboard
+#[derive(Debug, Deserialize)]
+pub struct CreateSubscriptionRequest {
+    /// Defaults to the asset's name
+    pub name: Option<String>,
+    pub asset_id: Uuid,
+    /// `metric_file` or `dashboard_file`
+    pub asset_type: AssetType,
+    /// Cron expression, e.g. `0 9 * * MON` for Mondays at 09:00
+    pub schedule: String,
+    /// IANA time zone the schedule is in; UTC by default
+    pub timezone: Option<String>,
+    pub channel: SubscriptionChannel,
+    pub format: Option<SubscriptionFormat>,
+    #[serde(default)]
+    pub recipients: Vec<String>,
+    pub target_url: Option<String>,
+}
+
+/// Handler for creating a subscription owned by the user. The user must be able to see the
+/// asset, since deliveries run with their access.
+pub async fn create_subscription_handler(
+    user: &AuthenticatedUser,
+    request: CreateSubscriptionRequest,
+) -> Result<SubscriptionResponse> {
+    // Verify user has an organization
+    if user.organizations.is_empty() {
+        return Err(anyhow!("User is not a member of any organization"));
+    }
+
+    let user_org = &user.organizations[0];
+
+    let timezone = request
+        .timezone
+        .map(|tz| tz.trim().to_string())
+        .unwrap_or_else(|| "UTC".to_string());
+    let now = Utc::now();
+    let next_run_at = validate_schedule("subscription", &request.schedule, &timezone, now)?;
+
+    let recipients = normalize_recipients("subscription", request.recipients)?;
+    let target_url = request.target_url.map(|url| url.trim().to_string());
+    validate_destination(
+        "subscription",
+        request.channel,
+        &recipients,
+        target_url.as_deref(),
+    )?;
+
+    let asset_name = check_asset_access(user, request.asset_type, &request.asset_id).await?;
+    let name = match request.name {
+        Some(name) => normalize_subscription_name(&name)?,
+        None => normalize_subscription_name(&asset_name)?,
+    };
+
+    let signing_secret =
+        (request.channel == SubscriptionChannel::Webhook).then(generate_signing_secret);
+
+    let subscription = Subscription {
+        id: Uuid::new_v4(),
+        organization_id: user_org.id,
+        owner_id: user.id,
+        asset_id: request.asset_id,
+        asset_type: request.asset_type,
+        name,
+        schedule: request.schedule.trim().to_string(),
+        timezone,
+        channel: request.channel.as_str().to_string(),
+        format: request
+            .format
+            .unwrap_or(SubscriptionFormat::Summary)
+            .as_str()
+            .to_string(),
+        // Only email deliveries use recipients, and only Slack and webhooks use a URL
+        recipients: match request.channel {
+            SubscriptionChannel::Email => recipients,
+            _ => Vec::new(),
+        },
+        target_url: match request.channel {
+            SubscriptionChannel::Email => None,
+            _ => target_url,
+        },
+        signing_secret: signing_secret.clone(),
+        enabled: true,
+        next_run_at: Some(next_run_at),
+        last_run_at: None,
+        created_at: now,
+        updated_at: now,
+        deleted_at: None,
+    };
+
+    let mut conn = get_pg_pool().get().await?;
+    diesel::insert_into(subscriptions::table)
+        .values(&subscription)
+        .execute(&mut conn)
+        .await
+        .map_err(|e| anyhow!("Failed to create subscription: {}", e))?;
+
+    let mut response = SubscriptionResponse::from(subscription);
+    response.signing_secret = signing_secret;
+    Ok(response)
+}
@@ -0,0 +1,41 @@
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use database::{pool::get_pg_pool, schema::subscriptions};
+use diesel::ExpressionMethods;
+use diesel_async::RunQueryDsl;
+use middleware::types::AuthenticatedUser;
+use uuid::Uuid;
+
+use super::helpers::{check_subscription_write_access, find_subscription};
+
+/// Handler for deleting a subscription. Its delivery history is kept.
+pub async fn delete_subscription_handler(
+    user: &AuthenticatedUser,
+    subscription_id: &Uuid,
+) -> Result<()> {
+    // Verify user has an organization
+    if user.organizations.is_empty() {
+        return Err(anyhow!("User is not a member of any organization"));
+    }
+
+    let user_org = &user.organizations[0];
+
+    let mut conn = get_pg_pool().get().await?;
+
+    let subscription = find_subscription(&mut conn, subscription_id, &user_org.id).await?;
+    check_subscription_write_access(user, &user_org.role, &subscription)?;
+
+    let now = Utc::now();
+    diesel::update(subscriptions::table)
+        .filter(subscriptions::id.eq(subscription_id))
+        .set((
+            subscriptions::deleted_at.eq(Some(now)),
+            subscriptions::enabled.eq(false),
+            subscriptions::updated_at.eq(now),
+        ))
+        .execute(&mut conn)
+        .await
+        .map_err(|e| anyhow!("Failed to delete subscription: {}", e))?;
+
+    Ok(())
+}
@@ -0,0 +1,410 @@
+//! Running subscriptions: building a delivery's report as the subscription's owner and
+//! sending it to the subscription's channel.
+
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use chrono::Utc;
+use database::{
+    enums::AssetType,
+    models::{Subscription, SubscriptionDelivery},
+    pool::get_pg_pool,
+    schema::{subscription_deliveries, subscriptions},
+};
+use diesel::{ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+use email::ReportAttachment;
+use indexmap::IndexMap;
+use middleware::{auth::find_user_by_id, AuthenticatedUser};
+use reqwest::Url;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use super::helpers::{is_private_host, next_run_after, PublicAddressResolver};
+use super::report::{webhook_signature, Report, ReportSection};
+use super::types::{SubscriptionChannel, SubscriptionFormat};
+use crate::dashboards::get_dashboard_handler;
+use crate::metrics::{
+    export_metric_data_handler, get_metric_data_handler,
+    get_metric_data_handler::get_metric_with_data_access, BusterMetric, ExportMetricDataRequest,
+    GetMetricDataRequest, MetricExportFormat,
+};
+
+/// Rows fetched per metric to count and summarize its result
+const DATA_ROW_LIMIT: i64 = 5000;
+
+/// Email providers reject larger attachments
+const MAX_CSV_BYTES: usize = 10 * 1024 * 1024;
+
+const HTTP_TIMEOUT_SECS: u64 = 30;
+
+/// Starts every enabled subscription whose next run is due.
+///
+/// Each subscription is claimed by moving its `next_run_at` to the following run, so a
+/// delivery is only sent once even with several servers checking for due subscriptions.
+/// Returns the number of deliveries started.
+pub async fn run_due_subscriptions() -> Result<usize> {
+    let mut conn = get_pg_pool()
+        .get()
+        .await
+        .context("Failed to get DB connection for scanning due subscriptions")?;
+
+    let now = Utc::now();
+    let due = subscriptions::table
+        .filter(subscriptions::enabled.eq(true))
+        .filter(subscriptions::deleted_at.is_null())
+        .filter(subscriptions::next_run_at.le(now))
+        .load::<Subscription>(&mut conn)
+        .await
+        .context("Failed to load due subscriptions")?;
+
+    let mut started = 0;
+    for subscription in due {
+        let next_run_at = match next_run_after(
+            "subscription",
+            &subscription.schedule,
+            &subscription.timezone,
+            now,
+        ) {
+            Ok(next_run_at) => Some(next_run_at),
+            Err(e) => {
+                warn!(subscription_id = %subscription.id, "Subscription will not be rescheduled: {}", e);
+                None
+            }
+        };
+
+        let claimed = diesel::update(subscriptions::table.find(subscription.id))
+            .filter(subscriptions::next_run_at.eq(subscription.next_run_at))
+            .set((
+                subscriptions::next_run_at.eq(next_run_at),
+                subscriptions::last_run_at.eq(Some(now)),
+            ))
+            .execute(&mut conn)
+            .await
+            .with_context(|| format!("Failed to claim subscription {}", subscription.id))?;
+        if claimed == 0 {
+            continue;
+        }
+
+        started += 1;
+        tokio::spawn(async move {
+            if let Err(e) = deliver_subscription(&subscription, false).await {
+                error!(subscription_id = %subscription.id, "Failed to record subscription delivery: {}", e);
+            }
+        });
+    }
+
+    if started > 0 {
+        info!("Started {} due subscription deliveries", started);
+    }
+    Ok(started)
+}
+
+/// Builds and sends one delivery, recording it in the subscription's history.
+///
+/// A failed delivery is recorded with its error rather than returned; errors are only
+/// returned when the history can't be written.
+pub(crate) async fn deliver_subscription(
+    subscription: &Subscription,
+    manual: bool,
+) -> Result<SubscriptionDelivery> {
+    let mut delivery = SubscriptionDelivery {
+        id: Uuid::new_v4(),
+        subscription_id: subscription.id,
+        status: "in_progress".to_string(),
+        manual,
+        row_count: None,
+        error_message: None,
+        started_at: Utc::now(),
+        finished_at: None,
+    };
+
+    let mut conn = get_pg_pool().get().await?;
+    diesel::insert_into(subscription_deliveries::table)
+        .values(&delivery)
+        .execute(&mut conn)
+        .await
+        .context("Failed to record subscription delivery")?;
+    // Building a report can take a while; don't hold on to the connection
+    drop(conn);
+
+    let result = async {
+        let report = build_report(subscription).await?;
+        send_report(subscription, &report, &delivery.id).await?;
+        Ok::<_, anyhow::Error>(report.row_count())
+    }
+    .await;
+
+    match result {
+        Ok(row_count) => {
+            info!(subscription_id = %subscription.id, delivery_id = %delivery.id, row_count, "Delivered subscription");
+            delivery.status = "success".to_string();
+            delivery.row_count = Some(row_count as i64);
+        }
+        Err(e) => {
+            error!(subscription_id = %subscription.id, delivery_id = %delivery.id, "Subscription delivery failed: {:?}", e);
+            delivery.status = "error".to_string();
+            delivery.error_message = Some(format!("{:#}", e));
+        }
+    }
+    delivery.finished_at = Some(Utc::now());
+
+    let mut conn = get_pg_pool().get().await?;
+    diesel::update(subscription_deliveries::table.find(delivery.id))
+        .set((
+            subscription_deliveries::status.eq(&delivery.status),
+            subscription_deliveries::row_count.eq(delivery.row_count),
+            subscription_deliveries::error_message.eq(&delivery.error_message),
+            subscription_deliveries::finished_at.eq(delivery.finished_at),
+        ))
+        .execute(&mut conn)
+        .await
+        .context("Failed to record subscription delivery result")?;
+
+    Ok(delivery)
+}
+
+/// Runs the asset's SQL as the subscription's owner, so deliveries never contain data the
+/// owner can't see
+async fn build_report(subscription: &Subscription) -> Result<Report> {
+    let owner = find_user_by_id(&subscription.owner_id)
+        .await
+        .context("Failed to load the subscription's owner")?
+        .ok_or_else(|| anyhow!("The subscription's owner no longer exists"))?;
+    if !owner
+        .organizations
+        .iter()
+        .any(|org| org.id == subscription.organization_id)
+    {
+        return Err(anyhow!(
+            "The subscription's owner is no longer a member of the workspace"
+        ));
+    }
+
+    let format = SubscriptionFormat::parse(&subscription.format)
+        .ok_or_else(|| anyhow!("Unknown subscription format '{}'", subscription.format))?;
+
+    let (title, sections) = match subscription.asset_type {
+        AssetType::MetricFile => {
+            let metric =
+                get_metric_with_data_access(&subscription.asset_id, &owner, None, None).await?;
+            let section = metric_section(&owner, &metric, None, format).await?;
+            (metric.name, vec![section])
+        }
+        AssetType::DashboardFile => {
+            let dashboard =
+                get_dashboard_handler(&subscription.asset_id, &owner, None, None).await?;
+            let mut sections = Vec::new();
+            for item in dashboard
+                .dashboard
+                .config
+                .rows
+                .iter()
+                .flat_map(|row| &row.items)
+            {
+                let Some(metric) = Uuid::parse_str(&item.id)
+                    .ok()
+                    .and_then(|id| dashboard.metrics.get(&id))
+                else {
+                    continue;
+                };
+                sections.push(
+                    metric_section(&owner, metric, Some(subscription.asset_id), format).await?,
+                );
+            }
+            (dashboard.dashboard.name, sections)
+        }
+        asset_type => {
+            return Err(anyhow!(
+                "Subscriptions can't deliver {} assets",
+                asset_type.to_string()
+            ))
+        }
+    };
+
+    Ok(Report {
+        subscription_id: subscription.id,
+        subscription_name: subscription.name.clone(),
+        title,
+        asset_id: subscription.asset_id,
+        asset_type: subscription.asset_type.to_string(),
+        link: asset_link(subscription.asset_type, &subscription.asset_id),
+        generated_at: Utc::now(),
+        sections,
+    })
+}
+
+/// Fetches a metric's data, fresh from the data source. Metrics on a dashboard get the
+/// dashboard's default filter values.
+async fn metric_section(
+    owner: &AuthenticatedUser,
+    metric: &BusterMetric,
+    dashboard_id: Option<Uuid>,
+    format: SubscriptionFormat,
+) -> Result<ReportSection> {
+    let data = get_metric_data_handler(
+        GetMetricDataRequest {
+            metric_id: metric.id,
+            version_number: None,
+            limit: Some(DATA_ROW_LIMIT),
+            password: None,
+            force_refresh: true,
+            cursor: None,
+            parameters: Default::default(),
+            dashboard_id,
+            dashboard_filters: Default::default(),
+        },
+        owner.clone(),
+    )
+    .await
+    .with_context(|| format!("Failed to run metric '{}'", metric.name))?;
+
+    let formats = metric
+        .chart_config
+        .as_ref()
+        .map(|config| config.base().column_label_formats.clone())
+        .unwrap_or_else(IndexMap::new);
+    let mut section = ReportSection::new(
+        metric.id,
+        metric.name.clone(),
+        &data.data,
+        &formats,
+        data.has_more_records,
+    );
+
+    if format == SubscriptionFormat::Csv {
+        section.csv = Some(metric_csv(owner, metric).await?);
+    }
+    Ok(section)
+}
+
+/// The metric's full result as a formatted CSV file
+async fn metric_csv(owner: &AuthenticatedUser, metric: &BusterMetric) -> Result<ReportAttachment> {
+    let mut export = export_metric_data_handler(
+        ExportMetricDataRequest {
+            metric_id: metric.id,
+            version_number: None,
+            password: None,
+            format: MetricExportFormat::Csv,
+            apply_formatting: true,
+            parameters: Default::default(),
+        },
+        owner.clone(),
+    )
+    .await?;
+
+    let mut content = Vec::new();
+    while let Some(chunk) = export.chunks.recv().await {
+        content.extend(chunk?);
+        if content.len() > MAX_CSV_BYTES {
+            return Err(anyhow!(
+                "The CSV for metric '{}' is larger than {} MB; use the summary format instead",
+                metric.name,
+                MAX_CSV_BYTES / (1024 * 1024)
+            ));
+        }
+    }
+
+    Ok(ReportAttachment {
+        file_name: export.file_name,
+        content_type: MetricExportFormat::Csv.content_type().to_string(),
+        content,
+    })
+}
+
+pub(crate) fn asset_link(asset_type: AssetType, asset_id: &Uuid) -> Option<String> {
+    let buster_url = env::var("BUSTER_URL").ok()?;
+    let path = match asset_type {
+        AssetType::MetricFile => "metrics",
+        AssetType::DashboardFile => "dashboards",
+        _ => return None,
+    };
+    Some(format!(
+        "{}/app/{}/{}",
+        buster_url.trim_end_matches('/'),
+        path,
+        asset_id
+    ))
+}
+
+async fn send_report(
+    subscription: &Subscription,
+    report: &Report,
+    delivery_id: &Uuid,
+) -> Result<()> {
+    let channel = SubscriptionChannel::parse(&subscription.channel)
+        .ok_or_else(|| anyhow!("Unknown subscription channel '{}'", subscription.channel))?;
+    let target_url = || {
+        subscription
+            .target_url
+            .as_deref()
+            .ok_or_else(|| anyhow!("The subscription has no target_url"))
+    };
+
+    match channel {
+        SubscriptionChannel::Email => {
+            email::send_report_email(&subscription.recipients, &report.email()).await
+        }
+        SubscriptionChannel::Slack => {
+            let body = serde_json::to_vec(&report.slack_payload())?;
+            post_json(target_url()?, body, &[]).await
+        }
+        SubscriptionChannel::Webhook => {
+            let secret = subscription
+                .signing_secret
+                .as_deref()
+                .ok_or_else(|| anyhow!("The subscription has no signing secret"))?;
+            let body = serde_json::to_vec(&report.webhook_payload())?;
+            let signature = webhook_signature(secret, Utc::now().timestamp(), &body);
+            post_json(
+                target_url()?,
+                body,
+                &[
+                    ("X-Buster-Signature", signature),
+                    ("X-Buster-Delivery", delivery_id.to_string()),
+                ],
+            )
+            .await
+        }
+    }
+}
+
+pub(crate) async fn post_json(url: &str, body: Vec<u8>, headers: &[(&str, String)]) -> Result<()> {
+    // Hosts are resolved by the resolver, which refuses private addresses; literal
+    // addresses skip resolution and are checked here
+    if is_private_host(&Url::parse(url)?) {
+        return Err(anyhow!("{} is a private address", url));
+    }
+
+    // Redirects aren't followed, so a webhook can't bounce deliveries to another host.
+    // Proxies are bypassed so every connection goes to an address the resolver checked.
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(HTTP_TIMEOUT_SECS))
+        .redirect(reqwest::redirect::Policy::none())
+        .dns_resolver(Arc::new(PublicAddressResolver))
+        .no_proxy()
+        .build()?;
+
+    let mut request = client
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(body);
+    for (name, value) in headers {
+        request = request.header(*name, value);
+    }
+
+    let response = request.send().await?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(anyhow!(
+            "{} responded with {}: {}",
+            url,
+            status,
+            body.chars().take(500).collect::<String>()
+        ));
+    }
+    Ok(())
+}
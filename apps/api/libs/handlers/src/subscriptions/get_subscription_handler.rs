@@ -0,0 +1,47 @@
+use anyhow::{anyhow, Result};
+use database::{models::SubscriptionDelivery, pool::get_pg_pool, schema::subscription_deliveries};
+use diesel::{ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+use middleware::types::AuthenticatedUser;
+use uuid::Uuid;
+
+use super::helpers::{check_subscription_write_access, find_subscription};
+use super::types::{SubscriptionDeliveryResponse, SubscriptionResponse};
+
+/// Deliveries returned with a subscription
+const RECENT_DELIVERIES: i64 = 20;
+
+/// Handler for getting a subscription with its most recent deliveries
+pub async fn get_subscription_handler(
+    user: &AuthenticatedUser,
+    subscription_id: &Uuid,
+) -> Result<SubscriptionResponse> {
+    // Verify user has an organization
+    if user.organizations.is_empty() {
+        return Err(anyhow!("User is not a member of any organization"));
+    }
+
+    let user_org = &user.organizations[0];
+
+    let mut conn = get_pg_pool().get().await?;
+
+    let subscription = find_subscription(&mut conn, subscription_id, &user_org.id).await?;
+    check_subscription_write_access(user, &user_org.role, &subscription)?;
+
+    let deliveries = subscription_deliveries::table
+        .filter(subscription_deliveries::subscription_id.eq(subscription.id))
+        .order(subscription_deliveries::started_at.desc())
+        .limit(RECENT_DELIVERIES)
+        .load::<SubscriptionDelivery>(&mut conn)
+        .await
+        .map_err(|e| anyhow!("Failed to load subscription deliveries: {}", e))?;
+
+    let mut response = SubscriptionResponse::from(subscription);
+    response.recent_deliveries = Some(
+        deliveries
+            .into_iter()
+            .map(SubscriptionDeliveryResponse::from)
+            .collect(),
+    );
+    Ok(response)
+}
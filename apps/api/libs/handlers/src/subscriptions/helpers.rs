@@ -0,0 +1,519 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use database::{
+    enums::{AssetType, UserOrganizationRole},
+    models::Subscription,
+    schema::subscriptions,
+};
+use diesel::{ExpressionMethods, QueryDsl};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use middleware::AuthenticatedUser;
+use rand::{distributions::Alphanumeric, Rng};
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::Url;
+use uuid::Uuid;
+
+use super::types::SubscriptionChannel;
+use crate::dashboards::get_dashboard_handler;
+use crate::metrics::get_metric_data_handler::get_metric_with_data_access;
+
+const MAX_SUBSCRIPTION_NAME_LENGTH: usize = 255;
+const MAX_RECIPIENTS: usize = 50;
+
+/// Deliveries can't be scheduled closer together than this
+const MIN_SCHEDULE_INTERVAL_MINUTES: i64 = 15;
+
+/// Trims a subscription name and rejects empty or oversized ones
+pub(crate) fn normalize_subscription_name(name: &str) -> Result<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(anyhow!("Invalid subscription: name cannot be empty"));
+    }
+    if name.chars().count() > MAX_SUBSCRIPTION_NAME_LENGTH {
+        return Err(anyhow!(
+            "Invalid subscription: name cannot be longer than {} characters",
+            MAX_SUBSCRIPTION_NAME_LENGTH
+        ));
+    }
+    Ok(name.to_string())
+}
+
+/// Parses a cron expression. Standard five-field expressions like `0 9 * * MON` run at the
+/// top of the minute; six- and seven-field ones start with seconds.
+fn parse_schedule(subject: &str, schedule: &str) -> Result<cron::Schedule> {
+    let schedule = schedule.trim();
+    let expression = match schedule.split_whitespace().count() {
+        5 => format!("0 {}", schedule),
+        _ => schedule.to_string(),
+    };
+    cron::Schedule::from_str(&expression).map_err(|e| {
+        anyhow!(
+            "Invalid {}: schedule '{}' is not valid cron: {}",
+            subject,
+            schedule,
+            e
+        )
+    })
+}
+
+fn parse_timezone(subject: &str, timezone: &str) -> Result<Tz> {
+    timezone.trim().parse::<Tz>().map_err(|_| {
+        anyhow!(
+            "Invalid {}: '{}' is not an IANA time zone",
+            subject,
+            timezone
+        )
+    })
+}
+
+/// The first run of `schedule`, read in `timezone`, after `after`.
+///
+/// Subscriptions and alerts share this and the checks below; errors call the
+/// `subject` (`subscription` or `alert`) invalid.
+pub(crate) fn next_run_after(
+    subject: &str,
+    schedule: &str,
+    timezone: &str,
+    after: DateTime<Utc>,
+) -> Result<DateTime<Utc>> {
+    let tz = parse_timezone(subject, timezone)?;
+    parse_schedule(subject, schedule)?
+        .after(&after.with_timezone(&tz))
+        .next()
+        .map(|run| run.with_timezone(&Utc))
+        .ok_or_else(|| {
+            anyhow!(
+                "Invalid {}: schedule '{}' has no upcoming runs",
+                subject,
+                schedule
+            )
+        })
+}
+
+/// Checks a schedule and time zone and returns the next run after `now`
+pub(crate) fn validate_schedule(
+    subject: &str,
+    schedule: &str,
+    timezone: &str,
+    now: DateTime<Utc>,
+) -> Result<DateTime<Utc>> {
+    let next_run = next_run_after(subject, schedule, timezone, now)?;
+    let following_run = next_run_after(subject, schedule, timezone, next_run)?;
+    if following_run - next_run < chrono::Duration::minutes(MIN_SCHEDULE_INTERVAL_MINUTES) {
+        return Err(anyhow!(
+            "Invalid {}: schedules can't run more often than every {} minutes",
+            subject,
+            MIN_SCHEDULE_INTERVAL_MINUTES
+        ));
+    }
+    Ok(next_run)
+}
+
+/// Trims and deduplicates email recipients, rejecting anything that isn't an address
+pub(crate) fn normalize_recipients(subject: &str, recipients: Vec<String>) -> Result<Vec<String>> {
+    let mut normalized: Vec<String> = Vec::new();
+    for recipient in recipients {
+        let recipient = recipient.trim().to_string();
+        let valid = recipient
+            .split_once('@')
+            .is_some_and(|(local, domain)| !local.is_empty() && domain.contains('.'))
+            && !recipient.contains(char::is_whitespace);
+        if !valid {
+            return Err(anyhow!(
+                "Invalid {}: '{}' is not an email address",
+                subject,
+                recipient
+            ));
+        }
+        if !normalized
+            .iter()
+            .any(|r| r.eq_ignore_ascii_case(&recipient))
+        {
+            normalized.push(recipient);
+        }
+    }
+
+    if normalized.len() > MAX_RECIPIENTS {
+        return Err(anyhow!(
+            "Invalid {}: at most {} recipients are allowed",
+            subject,
+            MAX_RECIPIENTS
+        ));
+    }
+    Ok(normalized)
+}
+
+/// Checks a channel's destination. Email needs recipients; Slack and webhooks need an HTTPS
+/// URL, which for Slack must be an incoming webhook and for webhooks can't be a private address.
+pub(crate) fn validate_destination(
+    subject: &str,
+    channel: SubscriptionChannel,
+    recipients: &[String],
+    target_url: Option<&str>,
+) -> Result<()> {
+    match channel {
+        SubscriptionChannel::Email => {
+            if recipients.is_empty() {
+                return Err(anyhow!(
+                    "Invalid {}: email deliveries need at least one recipient",
+                    subject
+                ));
+            }
+        }
+        SubscriptionChannel::Slack | SubscriptionChannel::Webhook => {
+            let url = target_url.ok_or_else(|| {
+                anyhow!(
+                    "Invalid {}: {} deliveries need a target_url",
+                    subject,
+                    channel.as_str()
+                )
+            })?;
+            let url = Url::parse(url)
+                .map_err(|_| anyhow!("Invalid {}: '{}' is not a URL", subject, url))?;
+            if url.scheme() != "https" {
+                return Err(anyhow!("Invalid {}: target_url must use https", subject));
+            }
+
+            match channel {
+                SubscriptionChannel::Slack if url.host_str() != Some("hooks.slack.com") => {
+                    return Err(anyhow!(
+                        "Invalid {}: Slack deliveries need an incoming webhook URL on hooks.slack.com",
+                    subject
+                    ));
+                }
+                SubscriptionChannel::Webhook if is_private_host(&url) => {
+                    return Err(anyhow!(
+                        "Invalid {}: webhooks can't be sent to private addresses",
+                        subject
+                    ));
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn is_private_host(url: &Url) -> bool {
+    let Some(host) = url.host_str() else {
+        return true;
+    };
+    if host.eq_ignore_ascii_case("localhost")
+        || host.ends_with(".localhost")
+        || host.ends_with(".internal")
+    {
+        return true;
+    }
+
+    match host
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse::<IpAddr>()
+    {
+        Ok(ip) => is_private_ip(ip),
+        Err(_) => false,
+    }
+}
+
+fn is_private_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [first, second, ..] = ip.octets();
+            ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                // "This network" (0.0.0.0/8)
+                || first == 0
+                // Carrier-grade NAT (100.64.0.0/10)
+                || (first == 100 && (second & 0xc0) == 64)
+                // Benchmarking (198.18.0.0/15)
+                || (first == 198 && (second & 0xfe) == 18)
+        }
+        IpAddr::V6(ip) => match embedded_ipv4(&ip) {
+            Some(ip) => is_private_ip(IpAddr::V4(ip)),
+            None => {
+                let segments = ip.segments();
+                ip.is_loopback()
+                    || ip.is_unspecified()
+                    // Unique local (fc00::/7) and link-local (fe80::/10) addresses
+                    || (segments[0] & 0xfe00) == 0xfc00
+                    || (segments[0] & 0xffc0) == 0xfe80
+                    // Local-use NAT64 (64:ff9b:1::/48) translates to private networks
+                    || segments[..3] == [0x64, 0xff9b, 0x1]
+            }
+        },
+    }
+}
+
+/// The IPv4 address an IPv6 address reaches: IPv4-mapped (::ffff:0:0/96), NAT64
+/// (64:ff9b::/96) and 6to4 (2002::/16) addresses all lead to one
+fn embedded_ipv4(ip: &Ipv6Addr) -> Option<Ipv4Addr> {
+    let segments = ip.segments();
+    let from_segments =
+        |high: u16, low: u16| Ipv4Addr::from((u32::from(high) << 16) | u32::from(low));
+
+    if let Some(ip) = ip.to_ipv4_mapped() {
+        Some(ip)
+    } else if segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0] {
+        Some(from_segments(segments[6], segments[7]))
+    } else if segments[0] == 0x2002 {
+        Some(from_segments(segments[1], segments[2]))
+    } else {
+        None
+    }
+}
+
+/// Resolves hosts for outgoing deliveries and refuses any that resolve to a private address.
+/// Target URLs are checked when saved, but a host's DNS records can change afterwards.
+pub(crate) struct PublicAddressResolver;
+
+impl Resolve for PublicAddressResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> =
+                tokio::net::lookup_host((name.as_str(), 0)).await?.collect();
+            let addrs = public_addresses(name.as_str(), addrs)?;
+            Ok::<Addrs, Box<dyn std::error::Error + Send + Sync>>(Box::new(addrs.into_iter()))
+        })
+    }
+}
+
+/// Fails when any of the addresses is private, so a host can't mix a public record in
+fn public_addresses(host: &str, addrs: Vec<SocketAddr>) -> std::io::Result<Vec<SocketAddr>> {
+    if addrs.iter().any(|addr| is_private_ip(addr.ip())) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::PermissionDenied,
+            format!("{} resolves to a private address", host),
+        ));
+    }
+    Ok(addrs)
+}
+
+/// A random key for signing webhook payloads
+pub(crate) fn generate_signing_secret() -> String {
+    let secret: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(40)
+        .map(char::from)
+        .collect();
+    format!("whsec_{}", secret)
+}
+
+/// Checks the user can see the asset and its data, and returns its name
+pub(crate) async fn check_asset_access(
+    user: &AuthenticatedUser,
+    asset_type: AssetType,
+    asset_id: &Uuid,
+) -> Result<String> {
+    match asset_type {
+        AssetType::MetricFile => {
+            let metric = get_metric_with_data_access(asset_id, user, None, None).await?;
+            Ok(metric.name)
+        }
+        AssetType::DashboardFile => {
+            let dashboard = get_dashboard_handler(asset_id, user, None, None).await?;
+            Ok(dashboard.dashboard.name)
+        }
+        _ => Err(anyhow!(
+            "Invalid subscription: only metrics and dashboards can be subscribed to"
+        )),
+    }
+}
+
+pub(crate) async fn find_subscription(
+    conn: &mut AsyncPgConnection,
+    subscription_id: &Uuid,
+    organization_id: &Uuid,
+) -> Result<Subscription> {
+    subscriptions::table
+        .filter(subscriptions::id.eq(subscription_id))
+        .filter(subscriptions::organization_id.eq(organization_id))
+        .filter(subscriptions::deleted_at.is_null())
+        .first::<Subscription>(conn)
+        .await
+        .map_err(|e| match e {
+            diesel::result::Error::NotFound => anyhow!("Subscription not found"),
+            e => anyhow!("Failed to load subscription: {}", e),
+        })
+}
+
+/// Owners manage their own subscriptions; admins manage every subscription in the workspace
+pub(crate) fn check_subscription_write_access(
+    user: &AuthenticatedUser,
+    role: &UserOrganizationRole,
+    subscription: &Subscription,
+) -> Result<()> {
+    match role {
+        UserOrganizationRole::WorkspaceAdmin | UserOrganizationRole::DataAdmin => Ok(()),
+        _ if subscription.owner_id == user.id => Ok(()),
+        _ => Err(anyhow!(
+            "User does not have appropriate permissions to manage this subscription"
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_next_run_after_uses_timezone() {
+        // Mondays at 09:00 in New York, which is on daylight saving time in May
+        let after = Utc.with_ymd_and_hms(2025, 5, 13, 12, 0, 0).unwrap();
+        assert_eq!(
+            next_run_after("subscription", "0 9 * * MON", "America/New_York", after).unwrap(),
+            Utc.with_ymd_and_hms(2025, 5, 19, 13, 0, 0).unwrap()
+        );
+        assert_eq!(
+            next_run_after("subscription", "0 0 9 * * MON", "UTC", after).unwrap(),
+            Utc.with_ymd_and_hms(2025, 5, 19, 9, 0, 0).unwrap()
+        );
+        assert!(next_run_after("subscription", "0 9 * * MON", "Mars/Olympus", after).is_err());
+        assert!(next_run_after("subscription", "every monday", "UTC", after).is_err());
+    }
+
+    #[test]
+    fn test_validate_schedule_rejects_frequent_schedules() {
+        let now = Utc.with_ymd_and_hms(2025, 5, 13, 12, 0, 0).unwrap();
+        assert!(validate_schedule("subscription", "*/5 * * * *", "UTC", now).is_err());
+        assert!(validate_schedule("subscription", "*/30 * * * *", "UTC", now).is_ok());
+    }
+
+    #[test]
+    fn test_validate_destination() {
+        let recipients = normalize_recipients(
+            "subscription",
+            vec![
+                " exec@example.com ".to_string(),
+                "EXEC@example.com".to_string(),
+            ],
+        )
+        .unwrap();
+        assert_eq!(recipients, vec!["exec@example.com".to_string()]);
+        assert!(normalize_recipients("subscription", vec!["not an email".to_string()]).is_err());
+
+        assert!(validate_destination(
+            "subscription",
+            SubscriptionChannel::Email,
+            &recipients,
+            None
+        )
+        .is_ok());
+        assert!(
+            validate_destination("subscription", SubscriptionChannel::Email, &[], None).is_err()
+        );
+        assert!(validate_destination(
+            "subscription",
+            SubscriptionChannel::Slack,
+            &[],
+            Some("https://hooks.slack.com/services/T0/B0/x")
+        )
+        .is_ok());
+        assert!(validate_destination(
+            "subscription",
+            SubscriptionChannel::Slack,
+            &[],
+            Some("https://example.com/hook")
+        )
+        .is_err());
+        assert!(validate_destination(
+            "subscription",
+            SubscriptionChannel::Webhook,
+            &[],
+            Some("https://example.com/hook")
+        )
+        .is_ok());
+        assert!(validate_destination(
+            "subscription",
+            SubscriptionChannel::Webhook,
+            &[],
+            Some("http://example.com/hook")
+        )
+        .is_err());
+        assert!(validate_destination(
+            "subscription",
+            SubscriptionChannel::Webhook,
+            &[],
+            Some("https://10.0.0.5/hook")
+        )
+        .is_err());
+        assert!(validate_destination(
+            "subscription",
+            SubscriptionChannel::Webhook,
+            &[],
+            Some("https://[::1]/hook")
+        )
+        .is_err());
+        assert!(
+            validate_destination("subscription", SubscriptionChannel::Webhook, &[], None).is_err()
+        );
+        assert_eq!(
+            validate_destination(
+                "alert",
+                SubscriptionChannel::Webhook,
+                &[],
+                Some("http://example.com/hook")
+            )
+            .unwrap_err()
+            .to_string(),
+            "Invalid alert: target_url must use https"
+        );
+    }
+
+    #[test]
+    fn test_public_addresses_refuses_private_ones() {
+        let public: SocketAddr = "93.184.216.34:0".parse().unwrap();
+        assert_eq!(
+            public_addresses("example.com", vec![public]).unwrap(),
+            vec![public]
+        );
+
+        // Neighbours of the private ranges and public IPv4 behind NAT64 and 6to4 are allowed
+        for other in [
+            "100.128.0.1:0",
+            "198.20.0.1:0",
+            "[64:ff9b::5db8:d822]:0",
+            "[2002:5db8:d822::1]:0",
+        ] {
+            let other: SocketAddr = other.parse().unwrap();
+            assert!(public_addresses("example.com", vec![other]).is_ok(), "{}", other);
+        }
+
+        for private in [
+            "127.0.0.1:0",
+            "10.1.2.3:0",
+            "169.254.169.254:0",
+            "[::1]:0",
+            "[fd00::1]:0",
+            "[::ffff:192.168.1.1]:0",
+            "0.1.2.3:0",
+            "100.64.0.1:0",
+            "100.127.255.254:0",
+            "198.18.0.1:0",
+            "198.19.255.255:0",
+            "[64:ff9b::10.0.0.1]:0",
+            "[64:ff9b::a9fe:a9fe]:0",
+            "[64:ff9b:1::1]:0",
+            "[2002:c0a8:0101::1]:0",
+            "[2002:7f00:1::]:0",
+        ] {
+            let private: SocketAddr = private.parse().unwrap();
+            assert!(public_addresses("example.com", vec![public, private]).is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_public_address_resolver_refuses_localhost() {
+        let resolved = PublicAddressResolver
+            .resolve(Name::from_str("localhost").unwrap())
+            .await;
+        assert!(resolved.is_err());
+    }
+}
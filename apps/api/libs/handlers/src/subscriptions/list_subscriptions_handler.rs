@@ -0,0 +1,63 @@
+use anyhow::{anyhow, Result};
+use database::{
+    enums::UserOrganizationRole, models::Subscription, pool::get_pg_pool, schema::subscriptions,
+};
+use diesel::{ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+use middleware::types::AuthenticatedUser;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use super::types::SubscriptionResponse;
+
+/// Request for listing subscriptions
+#[derive(Debug, Deserialize)]
+pub struct ListSubscriptionsRequest {
+    /// Only subscriptions to this metric or dashboard
+    pub asset_id: Option<Uuid>,
+    pub page: Option<i64>,
+    pub page_size: Option<i64>,
+}
+
+/// Handler for listing subscriptions, newest first. Admins see every subscription in the
+/// workspace; other users see their own.
+pub async fn list_subscriptions_handler(
+    user: &AuthenticatedUser,
+    request: ListSubscriptionsRequest,
+) -> Result<Vec<SubscriptionResponse>> {
+    // Verify user has an organization
+    if user.organizations.is_empty() {
+        return Err(anyhow!("User is not a member of any organization"));
+    }
+
+    let user_org = &user.organizations[0];
+
+    let page = request.page.unwrap_or(0).max(0);
+    let page_size = request.page_size.unwrap_or(25).clamp(1, 100);
+
+    let mut conn = get_pg_pool().get().await?;
+
+    let mut query = subscriptions::table
+        .filter(subscriptions::organization_id.eq(user_org.id))
+        .filter(subscriptions::deleted_at.is_null())
+        .into_boxed();
+    if !matches!(
+        user_org.role,
+        UserOrganizationRole::WorkspaceAdmin | UserOrganizationRole::DataAdmin
+    ) {
+        query = query.filter(subscriptions::owner_id.eq(user.id));
+    }
+    if let Some(asset_id) = request.asset_id {
+        query = query.filter(subscriptions::asset_id.eq(asset_id));
+    }
+
+    let rows = query
+        .order(subscriptions::created_at.desc())
+        .limit(page_size)
+        .offset(page * page_size)
+        .load::<Subscription>(&mut conn)
+        .await
+        .map_err(|e| anyhow!("Failed to load subscriptions: {}", e))?;
+
+    Ok(rows.into_iter().map(SubscriptionResponse::from).collect())
+}
@@ -0,0 +1,21 @@
+mod create_subscription_handler;
+mod delete_subscription_handler;
+pub(crate) mod delivery;
+mod get_subscription_handler;
+pub(crate) mod helpers;
+mod list_subscriptions_handler;
+pub(crate) mod report;
+mod send_subscription_handler;
+pub mod types;
+mod update_subscription_handler;
+
+pub use create_subscription_handler::{create_subscription_handler, CreateSubscriptionRequest};
+pub use delete_subscription_handler::delete_subscription_handler;
+pub use delivery::run_due_subscriptions;
+pub use get_subscription_handler::get_subscription_handler;
+pub use list_subscriptions_handler::{list_subscriptions_handler, ListSubscriptionsRequest};
+pub use send_subscription_handler::send_subscription_handler;
+pub use types::{
+    SubscriptionChannel, SubscriptionDeliveryResponse, SubscriptionFormat, SubscriptionResponse,
+};
+pub use update_subscription_handler::{update_subscription_handler, UpdateSubscriptionRequest};
@@ -0,0 +1,346 @@
+//! The content of a delivery, and how it's laid out for each channel.
+
+use chrono::{DateTime, Utc};
+use database::types::ColumnLabelFormat;
+use email::{ReportAttachment, ReportEmail, ReportTable};
+use hmac::{Hmac, Mac};
+use indexmap::IndexMap;
+use query_engine::data_types::DataType;
+use serde_json::{json, Value};
+use sha2::Sha256;
+use uuid::Uuid;
+
+use crate::metrics::export_formatting::{column_header, format_value, raw_value};
+
+/// Rows of each metric shown in emails, Slack messages and webhook payloads
+pub(crate) const SUMMARY_ROWS: usize = 20;
+
+/// Slack rejects section text longer than 3000 characters
+const SLACK_SECTION_LIMIT: usize = 3000;
+
+/// The results of one metric in a delivery
+#[derive(Debug, Clone)]
+pub(crate) struct ReportSection {
+    pub metric_id: Uuid,
+    pub name: String,
+    pub columns: Vec<String>,
+    /// The first [`SUMMARY_ROWS`] rows, formatted like the UI shows them
+    pub rows: Vec<Vec<String>>,
+    pub row_count: usize,
+    /// Whether the result had more rows than were fetched
+    pub has_more_rows: bool,
+    /// The metric's full result, for the `csv` format
+    pub csv: Option<ReportAttachment>,
+}
+
+impl ReportSection {
+    pub fn new(
+        metric_id: Uuid,
+        name: String,
+        data: &[IndexMap<String, DataType>],
+        formats: &IndexMap<String, ColumnLabelFormat>,
+        has_more_rows: bool,
+    ) -> Self {
+        let keys: Vec<&String> = data
+            .first()
+            .map(|row| row.keys().collect())
+            .unwrap_or_default();
+        let columns = keys
+            .iter()
+            .map(|key| column_header(key, formats.get(&key.to_lowercase())).to_string())
+            .collect();
+        let rows = data
+            .iter()
+            .take(SUMMARY_ROWS)
+            .map(|row| {
+                keys.iter()
+                    .map(|key| {
+                        let value = row.get(*key).unwrap_or(&DataType::Null);
+                        match formats.get(&key.to_lowercase()) {
+                            Some(format) => format_value(value, format),
+                            None => raw_value(value),
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Self {
+            metric_id,
+            name,
+            columns,
+            rows,
+            row_count: data.len(),
+            has_more_rows,
+            csv: None,
+        }
+    }
+
+    fn omitted_rows(&self) -> usize {
+        self.row_count.saturating_sub(self.rows.len())
+    }
+
+    fn row_count_label(&self) -> String {
+        format!(
+            "{}{}",
+            self.row_count,
+            if self.has_more_rows { "+" } else { "" }
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Report {
+    pub subscription_id: Uuid,
+    pub subscription_name: String,
+    /// The metric's or dashboard's name
+    pub title: String,
+    pub asset_id: Uuid,
+    pub asset_type: &'static str,
+    pub link: Option<String>,
+    pub generated_at: DateTime<Utc>,
+    pub sections: Vec<ReportSection>,
+}
+
+impl Report {
+    pub fn row_count(&self) -> usize {
+        self.sections.iter().map(|section| section.row_count).sum()
+    }
+
+    pub fn email(&self) -> ReportEmail {
+        ReportEmail {
+            subject: format!("{}: {}", self.subscription_name, self.title),
+            title: self.title.clone(),
+            tables: self
+                .sections
+                .iter()
+                .map(|section| ReportTable {
+                    title: section.name.clone(),
+                    columns: section.columns.clone(),
+                    rows: section.rows.clone(),
+                    omitted_rows: section.omitted_rows(),
+                })
+                .collect(),
+            link: self.link.clone(),
+            attachments: self
+                .sections
+                .iter()
+                .filter_map(|section| section.csv.clone())
+                .collect(),
+        }
+    }
+
+    /// A Slack incoming webhook message, with each metric's first rows as a text table
+    pub fn slack_payload(&self) -> Value {
+        let mut blocks = vec![json!({
+            "type": "header",
+            "text": { "type": "plain_text", "text": truncate(&self.title, 150) },
+        })];
+
+        for section in &self.sections {
+            let mut text = format!("*{}*\n", escape_slack(&section.name));
+            if section.columns.is_empty() {
+                text.push_str("_No data_");
+            } else {
+                let table = text_table(&section.columns, &section.rows);
+                let room = SLACK_SECTION_LIMIT.saturating_sub(text.len() + 8);
+                text.push_str(&format!("```{}```", truncate(&table, room)));
+                if section.omitted_rows() > 0 || section.has_more_rows {
+                    text.push_str(&format!(
+                        "\nShowing {} of {} rows",
+                        section.rows.len(),
+                        section.row_count_label()
+                    ));
+                }
+            }
+            blocks.push(json!({
+                "type": "section",
+                "text": { "type": "mrkdwn", "text": text },
+            }));
+        }
+
+        if let Some(link) = &self.link {
+            blocks.push(json!({
+                "type": "section",
+                "text": { "type": "mrkdwn", "text": format!("<{}|Open in Buster>", link) },
+            }));
+        }
+
+        json!({
+            "text": format!("{}: {}", self.subscription_name, self.title),
+            "blocks": blocks,
+        })
+    }
+
+    /// The JSON body POSTed to webhooks
+    pub fn webhook_payload(&self) -> Value {
+        let metrics: Vec<Value> = self
+            .sections
+            .iter()
+            .map(|section| {
+                json!({
+                    "id": section.metric_id,
+                    "name": section.name,
+                    "columns": section.columns,
+                    "rows": section.rows,
+                    "row_count": section.row_count,
+                    "has_more_rows": section.has_more_rows,
+                    "csv": section
+                        .csv
+                        .as_ref()
+                        .map(|csv| String::from_utf8_lossy(&csv.content).into_owned()),
+                })
+            })
+            .collect();
+
+        json!({
+            "subscription_id": self.subscription_id,
+            "subscription_name": self.subscription_name,
+            "asset_id": self.asset_id,
+            "asset_type": self.asset_type,
+            "title": self.title,
+            "link": self.link,
+            "generated_at": self.generated_at,
+            "metrics": metrics,
+        })
+    }
+}
+
+/// The `X-Buster-Signature` header for a webhook body: an HMAC-SHA256 of `{timestamp}.{body}`,
+/// so receivers can check the payload came from Buster and reject replays.
+pub(crate) fn webhook_signature(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    let digest: String = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    format!("t={},v1={}", timestamp, digest)
+}
+
+/// Columns padded to line up in a monospaced block
+fn text_table(columns: &[String], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = columns.iter().map(|c| c.chars().count()).collect();
+    for row in rows {
+        for (width, value) in widths.iter_mut().zip(row) {
+            *width = (*width).max(value.chars().count());
+        }
+    }
+
+    let line = |values: &[String]| {
+        values
+            .iter()
+            .zip(&widths)
+            .map(|(value, width)| format!("{:<width$}", value, width = *width))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    let mut table = vec![line(columns)];
+    table.push(
+        widths
+            .iter()
+            .map(|width| "-".repeat(*width))
+            .collect::<Vec<_>>()
+            .join("  "),
+    );
+    table.extend(rows.iter().map(|row| line(row)));
+    table.join("\n")
+}
+
+fn escape_slack(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(max_chars.saturating_sub(1)).collect();
+    truncated.push('…');
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn section() -> ReportSection {
+        let data: Vec<IndexMap<String, DataType>> = (1..=25)
+            .map(|i| {
+                IndexMap::from([
+                    (
+                        "region".to_string(),
+                        DataType::Text(Some(format!("r{}", i))),
+                    ),
+                    ("revenue".to_string(), DataType::Int8(Some(i * 100))),
+                ])
+            })
+            .collect();
+        ReportSection::new(
+            Uuid::new_v4(),
+            "Revenue by region".to_string(),
+            &data,
+            &IndexMap::new(),
+            false,
+        )
+    }
+
+    fn report() -> Report {
+        Report {
+            subscription_id: Uuid::new_v4(),
+            subscription_name: "Monday revenue".to_string(),
+            title: "Revenue".to_string(),
+            asset_id: Uuid::new_v4(),
+            asset_type: "dashboard_file",
+            link: Some("https://app.buster.so/app/dashboards/1".to_string()),
+            generated_at: Utc::now(),
+            sections: vec![section()],
+        }
+    }
+
+    #[test]
+    fn test_section_keeps_summary_rows() {
+        let section = section();
+        assert_eq!(section.columns, vec!["region", "revenue"]);
+        assert_eq!(section.rows.len(), SUMMARY_ROWS);
+        assert_eq!(section.rows[0], vec!["r1", "100"]);
+        assert_eq!(section.row_count, 25);
+        assert_eq!(section.omitted_rows(), 5);
+
+        let email = report().email();
+        assert_eq!(email.subject, "Monday revenue: Revenue");
+        assert_eq!(email.tables[0].omitted_rows, 5);
+        assert!(email.attachments.is_empty());
+    }
+
+    #[test]
+    fn test_slack_payload() {
+        let payload = report().slack_payload();
+        let blocks = payload["blocks"].as_array().unwrap();
+        assert_eq!(blocks.len(), 3);
+        let text = blocks[1]["text"]["text"].as_str().unwrap();
+        assert!(text
+            .starts_with("*Revenue by region*\n```region  revenue\n------  -------\nr1      100"));
+        assert!(text.ends_with("Showing 20 of 25 rows"));
+        assert!(text.len() <= SLACK_SECTION_LIMIT);
+    }
+
+    #[test]
+    fn test_webhook_signature() {
+        // Checked against `printf '1700000000.{"a":1}' | openssl dgst -sha256 -hmac whsec_test`
+        assert_eq!(
+            webhook_signature("whsec_test", 1_700_000_000, br#"{"a":1}"#),
+            "t=1700000000,v1=38877139021993b830af32feea6e18a8da83eb2f6e49ee50bd9e4cf4ca4d3789"
+        );
+    }
+}
@@ -0,0 +1,30 @@
+use anyhow::{anyhow, Result};
+use database::pool::get_pg_pool;
+use middleware::types::AuthenticatedUser;
+use uuid::Uuid;
+
+use super::delivery::deliver_subscription;
+use super::helpers::{check_subscription_write_access, find_subscription};
+use super::types::SubscriptionDeliveryResponse;
+
+/// Handler for sending a subscription right away, e.g. to check it's set up right. The
+/// schedule is unaffected. A failed delivery is returned with its error, not as an error.
+pub async fn send_subscription_handler(
+    user: &AuthenticatedUser,
+    subscription_id: &Uuid,
+) -> Result<SubscriptionDeliveryResponse> {
+    // Verify user has an organization
+    if user.organizations.is_empty() {
+        return Err(anyhow!("User is not a member of any organization"));
+    }
+
+    let user_org = &user.organizations[0];
+
+    let mut conn = get_pg_pool().get().await?;
+    let subscription = find_subscription(&mut conn, subscription_id, &user_org.id).await?;
+    check_subscription_write_access(user, &user_org.role, &subscription)?;
+    drop(conn);
+
+    let delivery = deliver_subscription(&subscription, true).await?;
+    Ok(SubscriptionDeliveryResponse::from(delivery))
+}
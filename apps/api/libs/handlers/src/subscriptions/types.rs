@@ -0,0 +1,135 @@
+use chrono::{DateTime, Utc};
+use database::enums::AssetType;
+use database::models::{Subscription, SubscriptionDelivery};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Where a subscription's deliveries are sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubscriptionChannel {
+    /// To `recipients`, through Resend or SMTP
+    Email,
+    /// To a Slack incoming webhook at `target_url`
+    Slack,
+    /// A JSON payload POSTed to `target_url`, signed with the subscription's secret
+    Webhook,
+}
+
+impl SubscriptionChannel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SubscriptionChannel::Email => "email",
+            SubscriptionChannel::Slack => "slack",
+            SubscriptionChannel::Webhook => "webhook",
+        }
+    }
+
+    pub fn parse(channel: &str) -> Option<Self> {
+        [Self::Email, Self::Slack, Self::Webhook]
+            .into_iter()
+            .find(|c| c.as_str() == channel)
+    }
+}
+
+/// What a delivery contains. Slack messages always show the summary, since incoming
+/// webhooks can't upload files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubscriptionFormat {
+    /// The first rows of each metric as a table
+    Summary,
+    /// The summary, plus each metric's full result as a CSV file
+    Csv,
+}
+
+impl SubscriptionFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SubscriptionFormat::Summary => "summary",
+            SubscriptionFormat::Csv => "csv",
+        }
+    }
+
+    pub fn parse(format: &str) -> Option<Self> {
+        [Self::Summary, Self::Csv]
+            .into_iter()
+            .find(|f| f.as_str() == format)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SubscriptionResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub asset_id: Uuid,
+    pub asset_type: AssetType,
+    pub owner_id: Uuid,
+    pub schedule: String,
+    pub timezone: String,
+    pub channel: String,
+    pub format: String,
+    pub recipients: Vec<String>,
+    pub target_url: Option<String>,
+    pub enabled: bool,
+    pub next_run_at: Option<DateTime<Utc>>,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// Only returned when the subscription is created; webhook payloads are signed with it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signing_secret: Option<String>,
+    /// Most recent deliveries first; only returned for a single subscription
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recent_deliveries: Option<Vec<SubscriptionDeliveryResponse>>,
+}
+
+impl From<Subscription> for SubscriptionResponse {
+    fn from(subscription: Subscription) -> Self {
+        Self {
+            id: subscription.id,
+            name: subscription.name,
+            asset_id: subscription.asset_id,
+            asset_type: subscription.asset_type,
+            owner_id: subscription.owner_id,
+            schedule: subscription.schedule,
+            timezone: subscription.timezone,
+            channel: subscription.channel,
+            format: subscription.format,
+            recipients: subscription.recipients,
+            target_url: subscription.target_url,
+            enabled: subscription.enabled,
+            next_run_at: subscription.next_run_at,
+            last_run_at: subscription.last_run_at,
+            created_at: subscription.created_at,
+            updated_at: subscription.updated_at,
+            signing_secret: None,
+            recent_deliveries: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SubscriptionDeliveryResponse {
+    pub id: Uuid,
+    pub status: String,
+    pub manual: bool,
+    pub row_count: Option<i64>,
+    pub error_message: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+impl From<SubscriptionDelivery> for SubscriptionDeliveryResponse {
+    fn from(delivery: SubscriptionDelivery) -> Self {
+        Self {
+            id: delivery.id,
+            status: delivery.status,
+            manual: delivery.manual,
+            row_count: delivery.row_count,
+            error_message: delivery.error_message,
+            started_at: delivery.started_at,
+            finished_at: delivery.finished_at,
+        }
+    }
+}
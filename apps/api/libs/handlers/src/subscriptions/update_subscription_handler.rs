@@ -0,0 +1,142 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use database::{models::Subscription, pool::get_pg_pool, schema::subscriptions};
+use diesel::{AsChangeset, ExpressionMethods};
+use diesel_async::RunQueryDsl;
+use middleware::types::AuthenticatedUser;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use super::helpers::{
+    check_subscription_write_access, find_subscription, generate_signing_secret,
+    normalize_recipients, normalize_subscription_name, validate_destination, validate_schedule,
+};
+use super::types::{SubscriptionChannel, SubscriptionFormat, SubscriptionResponse};
+
+/// Request for updating a subscription. Omitted fields are left unchanged.
+#[derive(Debug, Deserialize)]
+pub struct UpdateSubscriptionRequest {
+    pub name: Option<String>,
+    pub schedule: Option<String>,
+    pub timezone: Option<String>,
+    pub channel: Option<SubscriptionChannel>,
+    pub format: Option<SubscriptionFormat>,
+    pub recipients: Option<Vec<String>>,
+    pub target_url: Option<String>,
+    pub enabled: Option<bool>,
+    /// Replace the key webhook payloads are signed with; the new key is returned
+    #[serde(default)]
+    pub rotate_signing_secret: bool,
+}
+
+#[derive(AsChangeset)]
+#[diesel(table_name = subscriptions)]
+#[diesel(treat_none_as_null = true)]
+struct SubscriptionChangeset {
+    name: String,
+    schedule: String,
+    timezone: String,
+    channel: String,
+    format: String,
+    recipients: Vec<String>,
+    target_url: Option<String>,
+    signing_secret: Option<String>,
+    enabled: bool,
+    next_run_at: Option<DateTime<Utc>>,
+    updated_at: DateTime<Utc>,
+}
+
+/// Handler for updating a subscription. The next run is worked out again from the schedule,
+/// so re-enabling a subscription doesn't send the deliveries it missed.
+pub async fn update_subscription_handler(
+    user: &AuthenticatedUser,
+    subscription_id: &Uuid,
+    request: UpdateSubscriptionRequest,
+) -> Result<SubscriptionResponse> {
+    // Verify user has an organization
+    if user.organizations.is_empty() {
+        return Err(anyhow!("User is not a member of any organization"));
+    }
+
+    let user_org = &user.organizations[0];
+
+    let mut conn = get_pg_pool().get().await?;
+
+    let subscription = find_subscription(&mut conn, subscription_id, &user_org.id).await?;
+    check_subscription_write_access(user, &user_org.role, &subscription)?;
+
+    let name = match request.name {
+        Some(name) => normalize_subscription_name(&name)?,
+        None => subscription.name.clone(),
+    };
+    let schedule = request
+        .schedule
+        .map(|schedule| schedule.trim().to_string())
+        .unwrap_or_else(|| subscription.schedule.clone());
+    let timezone = request
+        .timezone
+        .map(|tz| tz.trim().to_string())
+        .unwrap_or_else(|| subscription.timezone.clone());
+    let enabled = request.enabled.unwrap_or(subscription.enabled);
+    let next_run_at = validate_schedule("subscription", &schedule, &timezone, Utc::now())?;
+
+    let channel = match request.channel {
+        Some(channel) => channel,
+        None => SubscriptionChannel::parse(&subscription.channel)
+            .ok_or_else(|| anyhow!("Unknown subscription channel '{}'", subscription.channel))?,
+    };
+    let recipients = match request.recipients {
+        Some(recipients) => normalize_recipients("subscription", recipients)?,
+        None => subscription.recipients.clone(),
+    };
+    let target_url = request
+        .target_url
+        .map(|url| url.trim().to_string())
+        .or_else(|| subscription.target_url.clone());
+    validate_destination("subscription", channel, &recipients, target_url.as_deref())?;
+
+    // Webhooks keep their key unless it's rotated; other channels don't sign anything
+    let new_signing_secret = (channel == SubscriptionChannel::Webhook
+        && (request.rotate_signing_secret || subscription.signing_secret.is_none()))
+    .then(generate_signing_secret);
+    let signing_secret = match channel {
+        SubscriptionChannel::Webhook => new_signing_secret
+            .clone()
+            .or_else(|| subscription.signing_secret.clone()),
+        _ => None,
+    };
+
+    let changeset = SubscriptionChangeset {
+        name,
+        schedule,
+        timezone,
+        channel: channel.as_str().to_string(),
+        format: request
+            .format
+            .map(|format| format.as_str().to_string())
+            .unwrap_or_else(|| subscription.format.clone()),
+        recipients: match channel {
+            SubscriptionChannel::Email => recipients,
+            _ => Vec::new(),
+        },
+        target_url: match channel {
+            SubscriptionChannel::Email => None,
+            _ => target_url,
+        },
+        signing_secret,
+        enabled,
+        next_run_at: Some(next_run_at),
+        updated_at: Utc::now(),
+    };
+
+    let subscription = diesel::update(subscriptions::table)
+        .filter(subscriptions::id.eq(subscription_id))
+        .set(changeset)
+        .get_result::<Subscription>(&mut conn)
+        .await
+        .map_err(|e| anyhow!("Failed to update subscription: {}", e))?;
+
+    let mut response = SubscriptionResponse::from(subscription);
+    response.signing_secret = new_signing_secret;
+    Ok(response)
+}
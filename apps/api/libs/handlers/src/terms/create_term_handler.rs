@@ -0,0 +1,74 @@
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use database::{models::Term, pool::get_pg_pool, schema::terms};
+use diesel_async::RunQueryDsl;
+use middleware::types::AuthenticatedUser;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use super::helpers::{
+    check_term_write_access, check_unique_term_name, load_term_response, normalize_optional_text,
+    normalize_term_name, set_term_datasets, sync_term_search,
+};
+use super::types::TermResponse;
+
+/// Request for creating a glossary term
+#[derive(Debug, Deserialize)]
+pub struct CreateTermRequest {
+    pub name: String,
+    pub definition: Option<String>,
+    pub sql_snippet: Option<String>,
+    #[serde(default)]
+    pub dataset_ids: Vec<Uuid>,
+}
+
+/// Handler for creating a glossary term and linking it to datasets
+pub async fn create_term_handler(
+    user: &AuthenticatedUser,
+    request: CreateTermRequest,
+) -> Result<TermResponse> {
+    // Verify user has an organization
+    if user.organizations.is_empty() {
+        return Err(anyhow!("User is not a member of any organization"));
+    }
+
+    let user_org = &user.organizations[0];
+    check_term_write_access(&user_org.role)?;
+
+    let name = normalize_term_name(&request.name)?;
+
+    let mut conn = get_pg_pool().get().await?;
+
+    check_unique_term_name(&mut conn, &name, &user_org.id, None).await?;
+
+    let now = Utc::now();
+    let term = Term {
+        id: Uuid::new_v4(),
+        name,
+        definition: normalize_optional_text(request.definition),
+        sql_snippet: normalize_optional_text(request.sql_snippet),
+        organization_id: user_org.id,
+        created_by: user.id,
+        updated_by: user.id,
+        created_at: now,
+        updated_at: now,
+        deleted_at: None,
+    };
+
+    diesel::insert_into(terms::table)
+        .values(&term)
+        .execute(&mut conn)
+        .await
+        .map_err(|e| anyhow!("Failed to create term: {}", e))?;
+
+    if !request.dataset_ids.is_empty() {
+        set_term_datasets(&mut conn, &term.id, &user_org.id, &request.dataset_ids).await?;
+    }
+
+    // The term is saved either way; a failed index only affects search
+    if let Err(e) = sync_term_search(&term).await {
+        tracing::error!("Failed to index term {} for search: {}", term.id, e);
+    }
+
+    load_term_response(&mut conn, term).await
+}
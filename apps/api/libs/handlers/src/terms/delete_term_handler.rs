@@ -0,0 +1,52 @@
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use database::{
+    helpers::terms::delete_term_search,
+    pool::get_pg_pool,
+    schema::{terms, terms_to_datasets},
+};
+use diesel::{ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+use middleware::types::AuthenticatedUser;
+use uuid::Uuid;
+
+use super::helpers::{check_term_write_access, find_term};
+
+/// Handler for soft deleting a glossary term and its dataset links
+pub async fn delete_term_handler(user: &AuthenticatedUser, term_id: &Uuid) -> Result<()> {
+    // Verify user has an organization
+    if user.organizations.is_empty() {
+        return Err(anyhow!("User is not a member of any organization"));
+    }
+
+    let user_org = &user.organizations[0];
+    check_term_write_access(&user_org.role)?;
+
+    let mut conn = get_pg_pool().get().await?;
+
+    find_term(&mut conn, term_id, &user_org.id).await?;
+
+    let now = Utc::now();
+
+    diesel::update(terms::table)
+        .filter(terms::id.eq(term_id))
+        .set((
+            terms::deleted_at.eq(now),
+            terms::updated_at.eq(now),
+            terms::updated_by.eq(user.id),
+        ))
+        .execute(&mut conn)
+        .await
+        .map_err(|e| anyhow!("Failed to delete term: {}", e))?;
+
+    diesel::update(terms_to_datasets::table)
+        .filter(terms_to_datasets::term_id.eq(term_id))
+        .filter(terms_to_datasets::deleted_at.is_null())
+        .set(terms_to_datasets::deleted_at.eq(now))
+        .execute(&mut conn)
+        .await?;
+
+    delete_term_search(term_id).await?;
+
+    Ok(())
+}
@@ -0,0 +1,23 @@
+use anyhow::{anyhow, Result};
+use database::pool::get_pg_pool;
+use middleware::types::AuthenticatedUser;
+use uuid::Uuid;
+
+use super::helpers::{find_term, load_term_response};
+use super::types::TermResponse;
+
+/// Handler for reading a single glossary term
+pub async fn get_term_handler(user: &AuthenticatedUser, term_id: &Uuid) -> Result<TermResponse> {
+    // Verify user has an organization
+    if user.organizations.is_empty() {
+        return Err(anyhow!("User is not a member of any organization"));
+    }
+
+    let user_org = &user.organizations[0];
+
+    let mut conn = get_pg_pool().get().await?;
+
+    let term = find_term(&mut conn, term_id, &user_org.id).await?;
+
+    load_term_response(&mut conn, term).await
+}
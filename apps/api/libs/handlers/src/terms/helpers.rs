@@ -0,0 +1,283 @@
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use database::{
+    enums::UserOrganizationRole,
+    helpers::terms::{
+        create_term_search_embedding_index, reset_term_search_embeddings,
+        set_term_search_embedding, term_search_content, term_search_rows_without_embedding,
+        upsert_term_search,
+    },
+    models::{Term, TermToDataset},
+    schema::{datasets, terms, terms_to_datasets},
+};
+use diesel::{upsert::excluded, ExpressionMethods, JoinOnDsl, QueryDsl};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use stored_values::get_embedder;
+use uuid::Uuid;
+
+use super::types::{TermDataset, TermResponse};
+
+const MAX_TERM_NAME_LENGTH: usize = 255;
+
+/// Terms embedded per round trip when filling missing embeddings
+const TERM_REEMBED_BATCH_SIZE: i64 = 100;
+
+/// Only admins curate the glossary; every member can read it
+pub(crate) fn check_term_write_access(role: &UserOrganizationRole) -> Result<()> {
+    match role {
+        UserOrganizationRole::WorkspaceAdmin | UserOrganizationRole::DataAdmin => Ok(()),
+        _ => Err(anyhow!(
+            "User does not have appropriate permissions to manage terms"
+        )),
+    }
+}
+
+/// Trims a term name and rejects empty or oversized ones
+pub(crate) fn normalize_term_name(name: &str) -> Result<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(anyhow!("Invalid term: name cannot be empty"));
+    }
+    if name.chars().count() > MAX_TERM_NAME_LENGTH {
+        return Err(anyhow!(
+            "Invalid term: name cannot be longer than {} characters",
+            MAX_TERM_NAME_LENGTH
+        ));
+    }
+    Ok(name.to_string())
+}
+
+/// Blank definitions and snippets are stored as NULL
+pub(crate) fn normalize_optional_text(value: Option<String>) -> Option<String> {
+    value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+pub(crate) async fn find_term(
+    conn: &mut AsyncPgConnection,
+    term_id: &Uuid,
+    organization_id: &Uuid,
+) -> Result<Term> {
+    terms::table
+        .filter(terms::id.eq(term_id))
+        .filter(terms::organization_id.eq(organization_id))
+        .filter(terms::deleted_at.is_null())
+        .first::<Term>(conn)
+        .await
+        .map_err(|e| match e {
+            diesel::result::Error::NotFound => anyhow!("Term not found"),
+            e => anyhow!("Failed to load term: {}", e),
+        })
+}
+
+/// Errors if another live term in the organization already uses `name`
+pub(crate) async fn check_unique_term_name(
+    conn: &mut AsyncPgConnection,
+    name: &str,
+    organization_id: &Uuid,
+    exclude_id: Option<&Uuid>,
+) -> Result<()> {
+    let existing = terms::table
+        .filter(terms::organization_id.eq(organization_id))
+        .filter(terms::deleted_at.is_null())
+        .select((terms::id, terms::name))
+        .load::<(Uuid, String)>(conn)
+        .await?;
+
+    let duplicate = existing.iter().any(|(id, existing_name)| {
+        Some(id) != exclude_id && existing_name.eq_ignore_ascii_case(name)
+    });
+
+    if duplicate {
+        return Err(anyhow!("A term named '{}' already exists", name));
+    }
+    Ok(())
+}
+
+/// Replaces the dataset links of a term with `dataset_ids`
+pub(crate) async fn set_term_datasets(
+    conn: &mut AsyncPgConnection,
+    term_id: &Uuid,
+    organization_id: &Uuid,
+    dataset_ids: &[Uuid],
+) -> Result<()> {
+    let mut requested = dataset_ids.to_vec();
+    requested.sort();
+    requested.dedup();
+
+    let found: Vec<Uuid> = datasets::table
+        .filter(datasets::id.eq_any(&requested))
+        .filter(datasets::organization_id.eq(organization_id))
+        .filter(datasets::deleted_at.is_null())
+        .select(datasets::id)
+        .load(conn)
+        .await?;
+
+    if found.len() != requested.len() {
+        return Err(anyhow!("Dataset not found for term link"));
+    }
+
+    let now = Utc::now();
+
+    diesel::update(terms_to_datasets::table)
+        .filter(terms_to_datasets::term_id.eq(term_id))
+        .filter(terms_to_datasets::dataset_id.ne_all(&found))
+        .filter(terms_to_datasets::deleted_at.is_null())
+        .set(terms_to_datasets::deleted_at.eq(now))
+        .execute(conn)
+        .await?;
+
+    if found.is_empty() {
+        return Ok(());
+    }
+
+    let links: Vec<TermToDataset> = found
+        .into_iter()
+        .map(|dataset_id| TermToDataset {
+            term_id: *term_id,
+            dataset_id,
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+        })
+        .collect();
+
+    diesel::insert_into(terms_to_datasets::table)
+        .values(&links)
+        .on_conflict((terms_to_datasets::term_id, terms_to_datasets::dataset_id))
+        .do_update()
+        .set((
+            terms_to_datasets::deleted_at.eq(excluded(terms_to_datasets::deleted_at)),
+            terms_to_datasets::updated_at.eq(excluded(terms_to_datasets::updated_at)),
+        ))
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+pub(crate) async fn load_term_response(
+    conn: &mut AsyncPgConnection,
+    term: Term,
+) -> Result<TermResponse> {
+    let datasets = terms_to_datasets::table
+        .inner_join(datasets::table.on(datasets::id.eq(terms_to_datasets::dataset_id)))
+        .filter(terms_to_datasets::term_id.eq(term.id))
+        .filter(terms_to_datasets::deleted_at.is_null())
+        .filter(datasets::deleted_at.is_null())
+        .select((datasets::id, datasets::name))
+        .order(datasets::name.asc())
+        .load::<(Uuid, String)>(conn)
+        .await?
+        .into_iter()
+        .map(|(id, name)| TermDataset { id, name })
+        .collect();
+
+    Ok(TermResponse {
+        id: term.id,
+        name: term.name,
+        definition: term.definition,
+        sql_snippet: term.sql_snippet,
+        datasets,
+        created_by: term.created_by,
+        updated_by: term.updated_by,
+        created_at: term.created_at,
+        updated_at: term.updated_at,
+    })
+}
+
+/// Embeds text for glossary search, returning `None` if the embedding service fails
+pub(crate) async fn embed_text(text: &str) -> Option<Vec<f32>> {
+    let embedding = match get_embedder() {
+        Ok(embedder) => embedder.embed(&[text.to_string()]).await,
+        Err(e) => Err(e),
+    };
+
+    match embedding {
+        Ok(embeddings) => embeddings.into_iter().next(),
+        Err(e) => {
+            tracing::error!("Failed to generate term embedding: {}", e);
+            None
+        }
+    }
+}
+
+/// Refreshes the search row of a term after it was created or changed
+pub(crate) async fn sync_term_search(term: &Term) -> Result<()> {
+    let embedding = embed_text(&term_search_content(term)).await;
+    upsert_term_search(term, embedding.as_deref()).await
+}
+
+/// Makes term embeddings match the configured embedder.
+///
+/// If they came from a different model, the embedding column is resized and cleared, then
+/// every term is embedded again. Terms without an embedding are filled in either way.
+/// Run at startup so changing `EMBEDDING_PROVIDER`, `EMBEDDING_MODEL` or
+/// `EMBEDDING_DIMENSIONS` migrates existing terms.
+pub async fn ensure_term_search_embeddings() -> Result<()> {
+    let embedder = get_embedder()?;
+
+    if reset_term_search_embeddings(&embedder.model_id(), embedder.dimensions()).await? {
+        tracing::info!(
+            embedding_model = %embedder.model_id(),
+            "Embedding model changed, re-embedding terms"
+        );
+    }
+
+    loop {
+        let rows = term_search_rows_without_embedding(TERM_REEMBED_BATCH_SIZE).await?;
+        if rows.is_empty() {
+            break;
+        }
+
+        let (term_ids, contents): (Vec<Uuid>, Vec<String>) = rows.into_iter().unzip();
+        let embeddings = embedder.embed(&contents).await?;
+        if embeddings.len() != term_ids.len() {
+            return Err(anyhow!(
+                "Expected {} embeddings, received {}",
+                term_ids.len(),
+                embeddings.len()
+            ));
+        }
+
+        for (term_id, embedding) in term_ids.iter().zip(embeddings) {
+            set_term_search_embedding(term_id, &embedding).await?;
+        }
+    }
+
+    create_term_search_embedding_index().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_term_write_access() {
+        assert!(check_term_write_access(&UserOrganizationRole::WorkspaceAdmin).is_ok());
+        assert!(check_term_write_access(&UserOrganizationRole::DataAdmin).is_ok());
+        assert!(check_term_write_access(&UserOrganizationRole::Querier).is_err());
+        assert!(check_term_write_access(&UserOrganizationRole::Viewer).is_err());
+    }
+
+    #[test]
+    fn test_normalize_term_name() {
+        assert_eq!(
+            normalize_term_name("  Net revenue ").unwrap(),
+            "Net revenue"
+        );
+        assert!(normalize_term_name("   ").is_err());
+        assert!(normalize_term_name(&"a".repeat(MAX_TERM_NAME_LENGTH + 1)).is_err());
+    }
+
+    #[test]
+    fn test_normalize_optional_text() {
+        assert_eq!(normalize_optional_text(None), None);
+        assert_eq!(normalize_optional_text(Some("  ".to_string())), None);
+        assert_eq!(
+            normalize_optional_text(Some(" sum(amount) ".to_string())),
+            Some("sum(amount)".to_string())
+        );
+    }
+}
@@ -0,0 +1,51 @@
+use anyhow::{anyhow, Result};
+use database::{models::Term, pool::get_pg_pool, schema::terms};
+use diesel::{ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+use middleware::types::AuthenticatedUser;
+use serde::Deserialize;
+
+use super::helpers::load_term_response;
+use super::types::TermResponse;
+
+/// Request for listing glossary terms
+#[derive(Debug, Deserialize)]
+pub struct ListTermsRequest {
+    pub page: Option<i64>,
+    pub page_size: Option<i64>,
+}
+
+/// Handler for listing the glossary terms of the user's organization, sorted by name
+pub async fn list_terms_handler(
+    user: &AuthenticatedUser,
+    request: ListTermsRequest,
+) -> Result<Vec<TermResponse>> {
+    // Verify user has an organization
+    if user.organizations.is_empty() {
+        return Err(anyhow!("User is not a member of any organization"));
+    }
+
+    let user_org = &user.organizations[0];
+
+    let page = request.page.unwrap_or(0).max(0);
+    let page_size = request.page_size.unwrap_or(25).clamp(1, 100);
+
+    let mut conn = get_pg_pool().get().await?;
+
+    let term_rows = terms::table
+        .filter(terms::organization_id.eq(user_org.id))
+        .filter(terms::deleted_at.is_null())
+        .order(terms::name.asc())
+        .limit(page_size)
+        .offset(page * page_size)
+        .load::<Term>(&mut conn)
+        .await
+        .map_err(|e| anyhow!("Failed to load terms: {}", e))?;
+
+    let mut responses = Vec::with_capacity(term_rows.len());
+    for term in term_rows {
+        responses.push(load_term_response(&mut conn, term).await?);
+    }
+
+    Ok(responses)
+}
@@ -0,0 +1,17 @@
+mod create_term_handler;
+mod delete_term_handler;
+mod get_term_handler;
+mod helpers;
+mod list_terms_handler;
+mod search_terms_handler;
+pub mod types;
+mod update_term_handler;
+
+pub use create_term_handler::{create_term_handler, CreateTermRequest};
+pub use delete_term_handler::delete_term_handler;
+pub use get_term_handler::get_term_handler;
+pub use helpers::ensure_term_search_embeddings;
+pub use list_terms_handler::{list_terms_handler, ListTermsRequest};
+pub use search_terms_handler::{search_terms_handler, SearchTermsRequest};
+pub use types::{TermDataset, TermResponse, TermSearchItem};
+pub use update_term_handler::{update_term_handler, UpdateTermRequest};
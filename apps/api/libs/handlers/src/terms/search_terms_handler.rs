@@ -0,0 +1,50 @@
+use anyhow::{anyhow, Result};
+use database::helpers::terms::search_terms;
+use middleware::types::AuthenticatedUser;
+use serde::Deserialize;
+
+use super::helpers::embed_text;
+use super::types::TermSearchItem;
+
+/// Request for searching the glossary
+#[derive(Debug, Deserialize)]
+pub struct SearchTermsRequest {
+    pub query: String,
+    pub limit: Option<i64>,
+}
+
+/// Handler for hybrid (full-text and embedding) search over the glossary
+pub async fn search_terms_handler(
+    user: &AuthenticatedUser,
+    request: SearchTermsRequest,
+) -> Result<Vec<TermSearchItem>> {
+    // Verify user has an organization
+    if user.organizations.is_empty() {
+        return Err(anyhow!("User is not a member of any organization"));
+    }
+
+    let user_org = &user.organizations[0];
+    let limit = request.limit.unwrap_or(10).clamp(1, 50);
+
+    let query = request.query.trim();
+    if query.is_empty() {
+        return Ok(vec![]);
+    }
+
+    // Full-text search still works if the embedding cannot be generated
+    let embedding = embed_text(query).await;
+
+    let results = search_terms(&user_org.id, query, embedding.as_deref(), limit).await?;
+
+    Ok(results
+        .into_iter()
+        .map(|result| TermSearchItem {
+            id: result.id,
+            name: result.name,
+            definition: result.definition,
+            sql_snippet: result.sql_snippet,
+            datasets: result.dataset_names,
+            score: result.score,
+        })
+        .collect())
+}
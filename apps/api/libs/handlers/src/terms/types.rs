@@ -0,0 +1,35 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// A dataset a term is linked to
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TermDataset {
+    pub id: Uuid,
+    pub name: String,
+}
+
+/// A glossary term with the datasets it applies to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TermResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub definition: Option<String>,
+    pub sql_snippet: Option<String>,
+    pub datasets: Vec<TermDataset>,
+    pub created_by: Uuid,
+    pub updated_by: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A term returned by glossary search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TermSearchItem {
+    pub id: Uuid,
+    pub name: String,
+    pub definition: Option<String>,
+    pub sql_snippet: Option<String>,
+    pub datasets: Vec<String>,
+    pub score: f64,
+}
@@ -0,0 +1,91 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use database::{models::Term, pool::get_pg_pool, schema::terms};
+use diesel::{AsChangeset, ExpressionMethods};
+use diesel_async::RunQueryDsl;
+use middleware::types::AuthenticatedUser;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use super::helpers::{
+    check_term_write_access, check_unique_term_name, find_term, load_term_response,
+    normalize_optional_text, normalize_term_name, set_term_datasets, sync_term_search,
+};
+use super::types::TermResponse;
+
+/// Request for updating a glossary term.
+///
+/// Omitted fields are left unchanged; an empty `definition` or `sql_snippet`
+/// clears it, and `dataset_ids` replaces the linked datasets.
+#[derive(Debug, Deserialize)]
+pub struct UpdateTermRequest {
+    pub name: Option<String>,
+    pub definition: Option<String>,
+    pub sql_snippet: Option<String>,
+    pub dataset_ids: Option<Vec<Uuid>>,
+}
+
+#[derive(AsChangeset)]
+#[diesel(table_name = terms)]
+struct TermChangeset {
+    name: Option<String>,
+    definition: Option<Option<String>>,
+    sql_snippet: Option<Option<String>>,
+    updated_by: Uuid,
+    updated_at: DateTime<Utc>,
+}
+
+/// Handler for updating a glossary term
+pub async fn update_term_handler(
+    user: &AuthenticatedUser,
+    term_id: &Uuid,
+    request: UpdateTermRequest,
+) -> Result<TermResponse> {
+    // Verify user has an organization
+    if user.organizations.is_empty() {
+        return Err(anyhow!("User is not a member of any organization"));
+    }
+
+    let user_org = &user.organizations[0];
+    check_term_write_access(&user_org.role)?;
+
+    let mut conn = get_pg_pool().get().await?;
+
+    find_term(&mut conn, term_id, &user_org.id).await?;
+
+    let name = match request.name {
+        Some(name) => {
+            let name = normalize_term_name(&name)?;
+            check_unique_term_name(&mut conn, &name, &user_org.id, Some(term_id)).await?;
+            Some(name)
+        }
+        None => None,
+    };
+
+    let changeset = TermChangeset {
+        name,
+        definition: request.definition.map(|d| normalize_optional_text(Some(d))),
+        sql_snippet: request
+            .sql_snippet
+            .map(|s| normalize_optional_text(Some(s))),
+        updated_by: user.id,
+        updated_at: Utc::now(),
+    };
+
+    let term = diesel::update(terms::table)
+        .filter(terms::id.eq(term_id))
+        .set(changeset)
+        .get_result::<Term>(&mut conn)
+        .await
+        .map_err(|e| anyhow!("Failed to update term: {}", e))?;
+
+    if let Some(dataset_ids) = &request.dataset_ids {
+        set_term_datasets(&mut conn, &term.id, &user_org.id, dataset_ids).await?;
+    }
+
+    if let Err(e) = sync_term_search(&term).await {
+        tracing::error!("Failed to index term {} for search: {}", term.id, e);
+    }
+
+    load_term_response(&mut conn, term).await
+}
@@ -55,6 +55,8 @@ async fn create_test_metric(
             bar_show_total_at_top: None,
             line_group_type: None,
         }),
+        cache_ttl: None,
+        parameters: vec![],
         dataset_ids: Vec::new(),
     };
     
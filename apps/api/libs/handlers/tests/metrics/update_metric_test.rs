@@ -142,6 +142,8 @@ async fn test_update_metric_status() -> Result<()> {
         sql: "SELECT * FROM test".to_string(),
         time_frame: "last 30 days".to_string(),
         chart_config: create_default_chart_config(),
+        cache_ttl: None,
+        parameters: vec![],
         dataset_ids: vec![],
     };
     
@@ -344,6 +346,8 @@ async fn test_update_metric_status_unauthorized() -> Result<()> {
         sql: "SELECT * FROM test".to_string(),
         time_frame: "last 30 days".to_string(),
         chart_config: create_default_chart_config(),
+        cache_ttl: None,
+        parameters: vec![],
         dataset_ids: vec![],
     };
     
@@ -511,6 +515,8 @@ async fn test_update_metric_status_null_value() -> Result<()> {
         sql: "SELECT * FROM test".to_string(),
         time_frame: "last 30 days".to_string(),
         chart_config: create_default_chart_config(),
+        cache_ttl: None,
+        parameters: vec![],
         dataset_ids: vec![],
     };
     
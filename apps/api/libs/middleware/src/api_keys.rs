@@ -0,0 +1,333 @@
+//! API key scopes, hashing, and the checks applied to requests made with a key.
+
+use std::net::IpAddr;
+
+use axum::http::Method;
+use chrono::{DateTime, Utc};
+use rand::{distributions::Alphanumeric, Rng};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+/// Keys minted since scopes were introduced start with this; older keys are JWTs
+pub const API_KEY_PREFIX: &str = "bst_";
+
+/// How much of a key is stored in the clear, so users can tell their keys apart
+const STORED_PREFIX_LEN: usize = 12;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiKeyScope {
+    /// Read anything the owner can see, including searches
+    Read,
+    /// Create and change chats, metrics, dashboards, collections and the like
+    Write,
+    /// Run SQL and semantic queries against data sources
+    RunSql,
+    /// Deploy datasets and manage data sources
+    DeployDatasets,
+    /// Manage users, permissions, access policies and API keys
+    ManageUsers,
+}
+
+impl ApiKeyScope {
+    pub const ALL: [ApiKeyScope; 5] = [
+        ApiKeyScope::Read,
+        ApiKeyScope::Write,
+        ApiKeyScope::RunSql,
+        ApiKeyScope::DeployDatasets,
+        ApiKeyScope::ManageUsers,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiKeyScope::Read => "read",
+            ApiKeyScope::Write => "write",
+            ApiKeyScope::RunSql => "run_sql",
+            ApiKeyScope::DeployDatasets => "deploy_datasets",
+            ApiKeyScope::ManageUsers => "manage_users",
+        }
+    }
+
+    pub fn parse(scope: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|s| s.as_str() == scope)
+    }
+}
+
+/// The API key a request was authenticated with. Added to the request's extensions
+/// alongside the [`crate::AuthenticatedUser`] of its owner.
+#[derive(Debug, Clone)]
+pub struct ApiKeyAuth {
+    pub id: Uuid,
+    pub scopes: Vec<ApiKeyScope>,
+    pub allowed_ips: Vec<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl ApiKeyAuth {
+    pub fn allows(&self, scope: ApiKeyScope) -> bool {
+        self.scopes.contains(&scope)
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| expires_at <= Utc::now())
+    }
+
+    /// Whether the key can be used from `ip`. Keys without an allow-list can be used from
+    /// anywhere; keys with one can't be used when the client's address is unknown.
+    pub fn allows_ip(&self, ip: Option<IpAddr>) -> bool {
+        if self.allowed_ips.is_empty() {
+            return true;
+        }
+        ip.is_some_and(|ip| {
+            self.allowed_ips
+                .iter()
+                .any(|allowed| ip_in_range(ip, allowed))
+        })
+    }
+
+    /// Whether a key created with this key may be used from `range`, which must lie inside
+    /// one of this key's allow-list entries when it has any
+    pub fn allows_ip_range(&self, range: &str) -> bool {
+        self.allowed_ips.is_empty()
+            || self
+                .allowed_ips
+                .iter()
+                .any(|allowed| range_in_range(range, allowed))
+    }
+}
+
+/// A new random key. Only its hash and prefix are stored.
+pub fn generate_api_key() -> String {
+    let secret: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(40)
+        .map(char::from)
+        .collect();
+    format!("{}{}", API_KEY_PREFIX, secret)
+}
+
+/// Keys are long random strings, so a fast hash is enough to keep them from being read back
+pub fn hash_api_key(key: &str) -> String {
+    format!("{:x}", Sha256::digest(key.as_bytes()))
+}
+
+pub fn api_key_prefix(key: &str) -> String {
+    key.chars().take(STORED_PREFIX_LEN).collect()
+}
+
+/// Parses an allow-list entry, an IP address or a CIDR range like `10.0.0.0/8`
+pub fn parse_ip_range(range: &str) -> Option<(IpAddr, u8)> {
+    let (address, prefix_len) = match range.trim().split_once('/') {
+        Some((address, prefix_len)) => (address, Some(prefix_len)),
+        None => (range.trim(), None),
+    };
+    let address: IpAddr = address.parse().ok()?;
+    let max_len = if address.is_ipv4() { 32 } else { 128 };
+    let prefix_len = match prefix_len {
+        Some(len) => len.parse::<u8>().ok().filter(|len| *len <= max_len)?,
+        None => max_len,
+    };
+    Some((address, prefix_len))
+}
+
+/// Whether `ip` is in an allow-list entry (see [`parse_ip_range`])
+pub fn ip_in_range(ip: IpAddr, range: &str) -> bool {
+    let Some((network, prefix_len)) = parse_ip_range(range) else {
+        return false;
+    };
+    let ip = match (ip, network) {
+        // IPv4 clients can show up as IPv4-mapped IPv6 addresses on dual-stack sockets
+        (IpAddr::V6(v6), IpAddr::V4(_)) => match v6.to_ipv4_mapped() {
+            Some(v4) => IpAddr::V4(v4),
+            None => return false,
+        },
+        _ => ip,
+    };
+
+    match (ip, network) {
+        (IpAddr::V4(ip), IpAddr::V4(network)) => {
+            let mask = u32::MAX.checked_shl(32 - prefix_len as u32).unwrap_or(0);
+            u32::from(ip) & mask == u32::from(network) & mask
+        }
+        (IpAddr::V6(ip), IpAddr::V6(network)) => {
+            let mask = u128::MAX.checked_shl(128 - prefix_len as u32).unwrap_or(0);
+            u128::from(ip) & mask == u128::from(network) & mask
+        }
+        _ => false,
+    }
+}
+
+/// Whether every address in allow-list entry `range` is also in `outer`
+pub fn range_in_range(range: &str, outer: &str) -> bool {
+    let (Some((network, prefix_len)), Some((outer_network, outer_prefix_len))) =
+        (parse_ip_range(range), parse_ip_range(outer))
+    else {
+        return false;
+    };
+
+    network.is_ipv4() == outer_network.is_ipv4()
+        && prefix_len >= outer_prefix_len
+        && ip_in_range(network, outer)
+}
+
+/// The scope a request needs, from its method and its path under `/api/v1`
+pub fn required_scope(method: &Method, path: &str) -> ApiKeyScope {
+    let path = path.strip_prefix("/api/v1").unwrap_or(path);
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    let reads = matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS);
+
+    match segments.first().copied().unwrap_or_default() {
+        // Chats stream over websockets, and sending a prompt creates messages
+        "ws" => ApiKeyScope::Write,
+        "sql" | "semantic" => ApiKeyScope::RunSql,
+        // Searches are POSTs, but don't change anything
+        "search" | "helpers" => ApiKeyScope::Read,
+        // Keys, users and permissions can't even be listed without the admin scope
+        "api_keys" | "organizations" | "permission_groups" | "dataset_groups" => {
+            ApiKeyScope::ManageUsers
+        }
+        "users" if segments.get(1) != Some(&"favorites") => ApiKeyScope::ManageUsers,
+        "datasets"
+            if segments.iter().any(|s| {
+                matches!(
+                    *s,
+                    "row_filters"
+                        | "column_policies"
+                        | "permission_groups"
+                        | "dataset_groups"
+                        | "users"
+                )
+            }) =>
+        {
+            ApiKeyScope::ManageUsers
+        }
+        _ if reads => ApiKeyScope::Read,
+        "datasets" | "data_sources" => ApiKeyScope::DeployDatasets,
+        _ => ApiKeyScope::Write,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_required_scope() {
+        let cases = [
+            (Method::GET, "/api/v1/dashboards/1", ApiKeyScope::Read),
+            (Method::POST, "/api/v1/search", ApiKeyScope::Read),
+            (Method::DELETE, "/api/v1/dashboards", ApiKeyScope::Write),
+            (Method::PUT, "/api/v1/users/favorites", ApiKeyScope::Write),
+            (Method::GET, "/api/v1/ws", ApiKeyScope::Write),
+            (Method::POST, "/api/v1/sql/run", ApiKeyScope::RunSql),
+            (Method::POST, "/api/v1/semantic/query", ApiKeyScope::RunSql),
+            (
+                Method::POST,
+                "/api/v1/datasets/deploy",
+                ApiKeyScope::DeployDatasets,
+            ),
+            (
+                Method::DELETE,
+                "/api/v1/data_sources/1",
+                ApiKeyScope::DeployDatasets,
+            ),
+            (
+                Method::PUT,
+                "/api/v1/datasets/1/row_filters",
+                ApiKeyScope::ManageUsers,
+            ),
+            (
+                Method::POST,
+                "/api/v1/users/invite",
+                ApiKeyScope::ManageUsers,
+            ),
+            (Method::POST, "/api/v1/api_keys", ApiKeyScope::ManageUsers),
+            (Method::GET, "/api/v1/api_keys", ApiKeyScope::ManageUsers),
+            (Method::GET, "/api/v1/users", ApiKeyScope::ManageUsers),
+            (
+                Method::GET,
+                "/api/v1/organizations/1",
+                ApiKeyScope::ManageUsers,
+            ),
+            (
+                Method::GET,
+                "/api/v1/permission_groups",
+                ApiKeyScope::ManageUsers,
+            ),
+            (
+                Method::GET,
+                "/api/v1/dataset_groups",
+                ApiKeyScope::ManageUsers,
+            ),
+            (
+                Method::GET,
+                "/api/v1/datasets/1/row_filters",
+                ApiKeyScope::ManageUsers,
+            ),
+            (Method::GET, "/api/v1/users/favorites", ApiKeyScope::Read),
+            (Method::GET, "/api/v1/datasets", ApiKeyScope::Read),
+        ];
+        for (method, path, scope) in cases {
+            assert_eq!(required_scope(&method, path), scope, "{} {}", method, path);
+        }
+    }
+
+    #[test]
+    fn test_allows_ip() {
+        let mut key = ApiKeyAuth {
+            id: Uuid::new_v4(),
+            scopes: vec![ApiKeyScope::Read],
+            allowed_ips: vec![],
+            expires_at: None,
+        };
+        assert!(key.allows_ip(None));
+
+        key.allowed_ips = vec!["10.1.0.0/16".to_string(), "2001:db8::1".to_string()];
+        assert!(key.allows_ip("10.1.200.3".parse().ok()));
+        assert!(key.allows_ip("::ffff:10.1.0.9".parse().ok()));
+        assert!(key.allows_ip("2001:db8::1".parse().ok()));
+        assert!(!key.allows_ip("10.2.0.1".parse().ok()));
+        assert!(!key.allows_ip("2001:db8::2".parse().ok()));
+        assert!(!key.allows_ip(None));
+
+        assert_eq!(
+            parse_ip_range("0.0.0.0/0"),
+            Some(("0.0.0.0".parse().unwrap(), 0))
+        );
+        assert_eq!(parse_ip_range("10.0.0.0/33"), None);
+        assert_eq!(parse_ip_range("example.com"), None);
+    }
+
+    #[test]
+    fn test_allows_ip_range() {
+        let mut key = ApiKeyAuth {
+            id: Uuid::new_v4(),
+            scopes: vec![ApiKeyScope::Read],
+            allowed_ips: vec![],
+            expires_at: None,
+        };
+        assert!(key.allows_ip_range("0.0.0.0/0"));
+
+        key.allowed_ips = vec!["10.1.0.0/16".to_string(), "2001:db8::/32".to_string()];
+        assert!(key.allows_ip_range("10.1.0.0/16"));
+        assert!(key.allows_ip_range("10.1.4.0/24"));
+        assert!(key.allows_ip_range("10.1.4.2"));
+        assert!(key.allows_ip_range("2001:db8:1::/48"));
+        assert!(!key.allows_ip_range("10.0.0.0/8"));
+        assert!(!key.allows_ip_range("10.2.0.1"));
+        assert!(!key.allows_ip_range("::ffff:10.1.0.9"));
+        assert!(!key.allows_ip_range("0.0.0.0/0"));
+    }
+
+    #[test]
+    fn test_generated_keys_are_hashed_and_prefixed() {
+        let key = generate_api_key();
+        assert!(key.starts_with(API_KEY_PREFIX));
+        assert_eq!(key.len(), 44);
+        assert_eq!(api_key_prefix(&key), key[..12]);
+        assert_eq!(hash_api_key(&key).len(), 64);
+        assert_ne!(hash_api_key(&key), hash_api_key(&generate_api_key()));
+    }
+}
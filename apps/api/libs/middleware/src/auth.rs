@@ -1,25 +1,48 @@
 use anyhow::{anyhow, Result};
-use axum::{extract::Request, http::StatusCode, middleware::Next, response::Response};
+use axum::{
+    extract::{ConnectInfo, OriginalUri, Request},
+    http::StatusCode,
+    middleware::Next,
+    response::Response,
+};
+use chrono::{DateTime, Duration, Utc};
 use database::{
     models::User,
     pool::get_pg_pool,
     schema::{api_keys, teams_to_users, users, users_to_organizations},
 };
-use diesel::{ExpressionMethods, JoinOnDsl, QueryDsl};
+use diesel::{BoolExpressionMethods, ExpressionMethods, OptionalExtension, QueryDsl};
 use diesel_async::RunQueryDsl;
 use futures::try_join;
 use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
 use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, env};
+use std::{
+    collections::HashMap,
+    env,
+    net::{IpAddr, SocketAddr},
+};
 use uuid::Uuid;
 
+use crate::api_keys::{
+    hash_api_key, ip_in_range, required_scope, ApiKeyAuth, ApiKeyScope, API_KEY_PREFIX,
+};
 use crate::types::{AuthenticatedUser, OrganizationMembership, TeamMembership};
 
 lazy_static! {
     static ref JWT_SECRET: String = env::var("JWT_SECRET").expect("JWT_SECRET is not set");
     static ref WEBHOOK_TOKEN: String =
         env::var("BUSTER_WH_TOKEN").expect("BUSTER_WH_TOKEN is not set");
+    /// Proxies (IPs or CIDR ranges) whose `X-Forwarded-For` entries are believed
+    static ref TRUSTED_PROXIES: Vec<String> = env::var("TRUSTED_PROXIES")
+        .map(|value| {
+            value
+                .split(',')
+                .map(|range| range.trim().to_string())
+                .filter(|range| !range.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -84,8 +107,8 @@ pub async fn auth(mut req: Request, next: Next) -> Result<Response, StatusCode>
         }
     };
 
-    let user = match authorize_current_user(&token).await {
-        Ok(Some(user)) => user,
+    let (user, api_key) = match authorize_current_user(&token).await {
+        Ok(Some(authorized)) => authorized,
         Ok(None) => return Err(StatusCode::UNAUTHORIZED),
         Err(e) => {
             tracing::error!("Authorization error: {}", e);
@@ -93,6 +116,37 @@ pub async fn auth(mut req: Request, next: Next) -> Result<Response, StatusCode>
         }
     };
 
+    if let Some(api_key) = api_key {
+        let path = req.extensions().get::<OriginalUri>().map_or_else(
+            || req.uri().path().to_string(),
+            |uri| uri.path().to_string(),
+        );
+        let scope = required_scope(req.method(), &path);
+        if !api_key.allows(scope) {
+            tracing::warn!(
+                api_key_id = %api_key.id,
+                "API key without the '{}' scope used for {} {}",
+                scope.as_str(),
+                req.method(),
+                path
+            );
+            return Err(StatusCode::FORBIDDEN);
+        }
+
+        let client_ip = client_ip(&req);
+        if !api_key.allows_ip(client_ip) {
+            tracing::warn!(
+                api_key_id = %api_key.id,
+                "API key used from an address outside its allow-list: {:?}",
+                client_ip
+            );
+            return Err(StatusCode::FORBIDDEN);
+        }
+
+        record_api_key_use(api_key.id);
+        req.extensions_mut().insert(api_key);
+    }
+
     // --- Payment Required Check START ---
     if env::var("ENVIRONMENT").unwrap_or_default() == "production" {
         if let Some(org_membership) = user.organizations.get(0) {
@@ -147,7 +201,13 @@ pub async fn auth(mut req: Request, next: Next) -> Result<Response, StatusCode>
     Ok(next.run(req).await)
 }
 
-async fn authorize_current_user(token: &str) -> Result<Option<AuthenticatedUser>> {
+async fn authorize_current_user(
+    token: &str,
+) -> Result<Option<(AuthenticatedUser, Option<ApiKeyAuth>)>> {
+    if token.starts_with(API_KEY_PREFIX) {
+        return authorize_api_key(token).await;
+    }
+
     let mut validation = Validation::new(Algorithm::HS256);
     validation.set_audience(&["authenticated", "api"]);
 
@@ -162,12 +222,12 @@ async fn authorize_current_user(token: &str) -> Result<Option<AuthenticatedUser>
         }
     };
 
-    let user = match token_data.aud.contains("api") {
-        true => find_user_by_api_key(token).await,
-        false => find_user_by_id(&Uuid::parse_str(&token_data.sub).unwrap()).await,
-    };
+    // API keys minted before scopes were introduced are JWTs
+    if token_data.aud.contains("api") {
+        return authorize_api_key(token).await;
+    }
 
-    let user = match user {
+    let user = match find_user_by_id(&Uuid::parse_str(&token_data.sub)?).await {
         Ok(user) => user,
         Err(e) => {
             tracing::error!("Error while querying user: {}", e);
@@ -175,10 +235,121 @@ async fn authorize_current_user(token: &str) -> Result<Option<AuthenticatedUser>
         }
     };
 
-    Ok(user)
+    Ok(user.map(|user| (user, None)))
+}
+
+/// Finds the owner of an API key, if the key exists and hasn't expired
+async fn authorize_api_key(token: &str) -> Result<Option<(AuthenticatedUser, Option<ApiKeyAuth>)>> {
+    let mut conn = get_pg_pool().get().await?;
+    let found = api_keys::table
+        .filter(api_keys::key_hash.eq(hash_api_key(token)))
+        .filter(api_keys::deleted_at.is_null())
+        .select((
+            api_keys::id,
+            api_keys::owner_id,
+            api_keys::scopes,
+            api_keys::allowed_ips,
+            api_keys::expires_at,
+        ))
+        .first::<(Uuid, Uuid, Vec<String>, Vec<String>, Option<DateTime<Utc>>)>(&mut conn)
+        .await
+        .optional()
+        .map_err(|e| anyhow!("Error querying API key: {}", e))?;
+    drop(conn);
+
+    let Some((id, owner_id, scopes, allowed_ips, expires_at)) = found else {
+        return Ok(None);
+    };
+    let api_key = ApiKeyAuth {
+        id,
+        scopes: scopes
+            .iter()
+            .filter_map(|scope| ApiKeyScope::parse(scope))
+            .collect(),
+        allowed_ips,
+        expires_at,
+    };
+    if api_key.is_expired() {
+        tracing::warn!(api_key_id = %api_key.id, "Expired API key used");
+        return Ok(None);
+    }
+
+    let user = find_user_by_id(&owner_id)
+        .await
+        .map_err(|e| anyhow!("Error while querying user: {}", e))?;
+    Ok(user.map(|user| (user, Some(api_key))))
+}
+
+/// The client's address, as seen by the API or reported by a trusted proxy
+fn client_ip(req: &Request) -> Option<IpAddr> {
+    let peer = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+    let forwarded_for = req
+        .headers()
+        .get("x-forwarded-for")
+        .and_then(|value| value.to_str().ok());
+
+    resolve_client_ip(peer, forwarded_for, &TRUSTED_PROXIES)
 }
 
-async fn find_user_by_id(id: &Uuid) -> Result<Option<AuthenticatedUser>> {
+/// The peer address, unless the peer is a trusted proxy. Then `X-Forwarded-For` is walked
+/// from the right past the trusted hops, and the first untrusted address is the client.
+/// Anyone can send the header, so entries added before the trusted hops are ignored.
+fn resolve_client_ip(
+    peer: Option<IpAddr>,
+    forwarded_for: Option<&str>,
+    trusted_proxies: &[String],
+) -> Option<IpAddr> {
+    let is_trusted = |ip: IpAddr| trusted_proxies.iter().any(|range| ip_in_range(ip, range));
+
+    let mut client = peer?;
+    if !is_trusted(client) {
+        return Some(client);
+    }
+    for hop in forwarded_for.unwrap_or_default().rsplit(',') {
+        // A malformed entry can't be attributed to anyone; stop at the last trusted hop
+        let Ok(ip) = hop.trim().parse::<IpAddr>() else {
+            break;
+        };
+        client = ip;
+        if !is_trusted(ip) {
+            break;
+        }
+    }
+    Some(client)
+}
+
+/// Records when a key was last used, at most once a minute per key so busy keys
+/// don't write on every request
+fn record_api_key_use(api_key_id: Uuid) {
+    tokio::spawn(async move {
+        let result = async {
+            let mut conn = get_pg_pool().get().await?;
+            let now = Utc::now();
+            diesel::update(api_keys::table)
+                .filter(api_keys::id.eq(api_key_id))
+                .filter(
+                    api_keys::last_used_at
+                        .is_null()
+                        .or(api_keys::last_used_at.lt(now - Duration::minutes(1))),
+                )
+                .set(api_keys::last_used_at.eq(now))
+                .execute(&mut conn)
+                .await?;
+            Ok::<_, anyhow::Error>(())
+        }
+        .await;
+
+        if let Err(e) = result {
+            tracing::warn!(%api_key_id, "Failed to record API key use: {}", e);
+        }
+    });
+}
+
+/// Loads a user with their organization and team memberships
+pub async fn find_user_by_id(id: &Uuid) -> Result<Option<AuthenticatedUser>> {
     let pg_pool = get_pg_pool();
     let id = *id; // Clone the UUID for move into tasks
 
@@ -262,91 +433,51 @@ async fn find_user_by_id(id: &Uuid) -> Result<Option<AuthenticatedUser>> {
     }))
 }
 
-async fn find_user_by_api_key(token: &str) -> Result<Option<AuthenticatedUser>> {
-    let pg_pool = get_pg_pool();
-    let token = token.to_string(); // Clone the token for move into tasks
-
-    // Get user info in a separate task
-    let user_task = tokio::spawn(async move {
-        let mut conn = pg_pool.get().await?;
-        users::table
-            .inner_join(api_keys::table.on(users::id.eq(api_keys::owner_id)))
-            .filter(api_keys::key.eq(token))
-            .filter(api_keys::deleted_at.is_null())
-            .select(users::all_columns)
-            .first::<User>(&mut conn)
-            .await
-            .map_err(|e| anyhow!("Error querying user: {}", e))
-    });
-
-    // Get user first since we need the ID for the other queries
-    let user = user_task
-        .await
-        .map_err(|e| anyhow!("User task failed: {}", e))??;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let user_id = user.id;
-    let pg_pool = get_pg_pool();
-
-    // Spawn tasks for organizations and teams queries
-    let orgs_task = tokio::spawn(async move {
-        let mut conn = pg_pool.get().await?;
-        users_to_organizations::table
-            .filter(users_to_organizations::user_id.eq(user_id))
-            .filter(users_to_organizations::deleted_at.is_null())
-            .select((
-                users_to_organizations::organization_id,
-                users_to_organizations::role,
-            ))
-            .load::<(Uuid, database::enums::UserOrganizationRole)>(&mut conn)
-            .await
-            .map_err(|e| anyhow!("Error querying organizations: {}", e))
-    });
-
-    let teams_task = tokio::spawn(async move {
-        let mut conn = pg_pool.get().await?;
-        teams_to_users::table
-            .filter(teams_to_users::user_id.eq(user_id))
-            .filter(teams_to_users::deleted_at.is_null())
-            .select((teams_to_users::team_id, teams_to_users::role))
-            .load::<(Uuid, database::enums::TeamToUserRole)>(&mut conn)
-            .await
-            .map_err(|e| anyhow!("Error querying teams: {}", e))
-    });
-
-    // Wait for organization and team tasks to complete
-    let (organizations, teams) = try_join!(
-        async {
-            orgs_task
-                .await
-                .map_err(|e| anyhow!("Organizations task failed: {}", e))?
-        },
-        async {
-            teams_task
-                .await
-                .map_err(|e| anyhow!("Teams task failed: {}", e))?
-        }
-    )?;
-
-    let organizations = organizations
-        .into_iter()
-        .map(|(id, role)| OrganizationMembership { id, role })
-        .collect();
-
-    let teams = teams
-        .into_iter()
-        .map(|(id, role)| TeamMembership { id, role })
-        .collect();
+    fn ip(address: &str) -> Option<IpAddr> {
+        address.parse().ok()
+    }
 
-    Ok(Some(AuthenticatedUser {
-        id: user.id,
-        email: user.email,
-        name: user.name,
-        config: user.config,
-        created_at: user.created_at,
-        updated_at: user.updated_at,
-        attributes: user.attributes,
-        avatar_url: user.avatar_url,
-        organizations,
-        teams,
-    }))
+    #[test]
+    fn test_resolve_client_ip() {
+        let trusted = vec!["10.0.0.0/8".to_string()];
+
+        // A direct client can't pick its address by sending the header
+        assert_eq!(
+            resolve_client_ip(ip("203.0.113.7"), Some("10.1.1.1"), &trusted),
+            ip("203.0.113.7")
+        );
+        assert_eq!(
+            resolve_client_ip(ip("203.0.113.7"), Some("198.51.100.1"), &[]),
+            ip("203.0.113.7")
+        );
+
+        // Behind trusted proxies, the first untrusted hop from the right is the client, even
+        // if the client prepended its own entries
+        assert_eq!(
+            resolve_client_ip(
+                ip("10.0.0.2"),
+                Some("192.0.2.1, 198.51.100.4, 10.0.0.5"),
+                &trusted
+            ),
+            ip("198.51.100.4")
+        );
+
+        // A trusted proxy that sent no usable header is the best address there is
+        assert_eq!(
+            resolve_client_ip(ip("10.0.0.2"), None, &trusted),
+            ip("10.0.0.2")
+        );
+        assert_eq!(
+            resolve_client_ip(ip("10.0.0.2"), Some("198.51.100.4, not-an-ip"), &trusted),
+            ip("10.0.0.2")
+        );
+        assert_eq!(
+            resolve_client_ip(None, Some("198.51.100.4"), &trusted),
+            None
+        );
+    }
 }
@@ -0,0 +1,299 @@
+//! Conversion of query rows into Arrow record batches for streaming and file exports.
+
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use arrow::array::{
+    ArrayRef, BooleanBuilder, Date32Builder, Float64Builder, Int64Builder, StringBuilder,
+    TimestampMicrosecondBuilder,
+};
+use arrow::datatypes::{DataType as ArrowDataType, Field, Schema, SchemaRef, TimeUnit};
+use arrow::record_batch::RecordBatch;
+use chrono::NaiveDate;
+use indexmap::IndexMap;
+
+use crate::data_types::DataType;
+
+/// Builds an Arrow schema from the first non-null value of each column. Columns that are
+/// entirely null (or hold types Arrow has no direct equivalent for) become strings.
+/// Integers and floats use their widest Arrow type so later rows still fit. Decimal columns
+/// are written as their exact text: a later page can bring a larger scale, or a value too
+/// wide for any Arrow decimal, and the schema can't change once written.
+pub fn schema_from_rows(rows: &[IndexMap<String, DataType>]) -> SchemaRef {
+    let column_names: Vec<&String> = rows.first().map(|row| row.keys().collect()).unwrap_or_default();
+
+    let fields: Vec<Field> = column_names
+        .into_iter()
+        .map(|name| {
+            let arrow_type = rows
+                .iter()
+                .filter_map(|row| row.get(name))
+                .find_map(arrow_type_for)
+                .unwrap_or(ArrowDataType::Utf8);
+
+            Field::new(name, arrow_type, true)
+        })
+        .collect();
+
+    Arc::new(Schema::new(fields))
+}
+
+/// Converts rows into a record batch matching `schema`. A value that doesn't fit its column's
+/// type fails the batch: the schema is usually built from an earlier page, and writing the
+/// value as null would silently lose data.
+pub fn rows_to_record_batch(
+    schema: &SchemaRef,
+    rows: &[IndexMap<String, DataType>],
+) -> Result<RecordBatch> {
+    let columns: Vec<ArrayRef> = schema
+        .fields()
+        .iter()
+        .map(|field| build_column(field, rows))
+        .collect::<Result<_>>()?;
+
+    RecordBatch::try_new(schema.clone(), columns)
+        .map_err(|e| anyhow!("Failed to build record batch: {}", e))
+}
+
+fn arrow_type_for(value: &DataType) -> Option<ArrowDataType> {
+    match value {
+        DataType::Bool(Some(_)) => Some(ArrowDataType::Boolean),
+        DataType::Int2(Some(_))
+        | DataType::Int4(Some(_))
+        | DataType::Int8(Some(_))
+        | DataType::Oid(Some(_)) => Some(ArrowDataType::Int64),
+        DataType::Float4(Some(_)) | DataType::Float8(Some(_)) => Some(ArrowDataType::Float64),
+        DataType::Date(Some(_)) => Some(ArrowDataType::Date32),
+        DataType::Timestamp(Some(_)) => {
+            Some(ArrowDataType::Timestamp(TimeUnit::Microsecond, None))
+        }
+        DataType::Timestamptz(Some(_)) => Some(ArrowDataType::Timestamp(
+            TimeUnit::Microsecond,
+            Some("UTC".into()),
+        )),
+        DataType::Char(Some(_))
+        | DataType::Text(Some(_))
+        | DataType::Decimal(Some(_))
+        | DataType::Uuid(Some(_))
+        | DataType::Time(Some(_))
+        | DataType::Json(Some(_))
+        | DataType::Bytea(Some(_))
+        | DataType::Unknown(Some(_)) => Some(ArrowDataType::Utf8),
+        _ => None,
+    }
+}
+
+fn build_column(field: &Field, rows: &[IndexMap<String, DataType>]) -> Result<ArrayRef> {
+    let values = rows.iter().map(|row| row.get(field.name()));
+
+    Ok(match field.data_type() {
+        ArrowDataType::Boolean => {
+            let mut builder = BooleanBuilder::with_capacity(rows.len());
+            for value in values {
+                let converted = match value {
+                    Some(DataType::Bool(v)) => *v,
+                    _ => None,
+                };
+                builder.append_option(fit(field, value, converted)?);
+            }
+            Arc::new(builder.finish())
+        }
+        ArrowDataType::Int64 => {
+            let mut builder = Int64Builder::with_capacity(rows.len());
+            for value in values {
+                builder.append_option(fit(field, value, value.and_then(as_i64))?);
+            }
+            Arc::new(builder.finish())
+        }
+        ArrowDataType::Float64 => {
+            let mut builder = Float64Builder::with_capacity(rows.len());
+            for value in values {
+                builder.append_option(fit(field, value, value.and_then(as_f64))?);
+            }
+            Arc::new(builder.finish())
+        }
+        ArrowDataType::Date32 => {
+            let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).unwrap();
+            let mut builder = Date32Builder::with_capacity(rows.len());
+            for value in values {
+                let converted = match value {
+                    Some(DataType::Date(Some(date))) => {
+                        Some(date.signed_duration_since(epoch).num_days() as i32)
+                    }
+                    _ => None,
+                };
+                builder.append_option(fit(field, value, converted)?);
+            }
+            Arc::new(builder.finish())
+        }
+        ArrowDataType::Timestamp(_, timezone) => {
+            let mut builder = TimestampMicrosecondBuilder::with_capacity(rows.len());
+            for value in values {
+                let converted = match value {
+                    Some(DataType::Timestamp(Some(ts))) => Some(ts.and_utc().timestamp_micros()),
+                    Some(DataType::Timestamptz(Some(ts))) => Some(ts.timestamp_micros()),
+                    _ => None,
+                };
+                builder.append_option(fit(field, value, converted)?);
+            }
+            match timezone {
+                Some(tz) => Arc::new(builder.finish().with_timezone(tz.clone())),
+                None => Arc::new(builder.finish()),
+            }
+        }
+        _ => {
+            let mut builder = StringBuilder::with_capacity(rows.len(), rows.len() * 16);
+            for value in values {
+                builder.append_option(value.and_then(as_string));
+            }
+            Arc::new(builder.finish())
+        }
+    })
+}
+
+/// Passes a converted value through, failing when a non-null value had no representation
+fn fit<T>(field: &Field, value: Option<&DataType>, converted: Option<T>) -> Result<Option<T>> {
+    match (converted, value) {
+        (Some(converted), _) => Ok(Some(converted)),
+        (None, Some(value)) if as_string(value).is_some() => Err(anyhow!(
+            "Column '{}' has a value that doesn't fit its {} type: {:?}",
+            field.name(),
+            field.data_type(),
+            value
+        )),
+        (None, _) => Ok(None),
+    }
+}
+
+fn as_i64(value: &DataType) -> Option<i64> {
+    match value {
+        DataType::Int2(v) => v.map(i64::from),
+        DataType::Int4(v) => v.map(i64::from),
+        DataType::Int8(v) => *v,
+        DataType::Oid(v) => v.map(i64::from),
+        _ => None,
+    }
+}
+
+fn as_f64(value: &DataType) -> Option<f64> {
+    match value {
+        DataType::Float4(v) => v.map(f64::from),
+        DataType::Float8(v) => *v,
+        DataType::Decimal(v) => v.and_then(|d| d.to_string().parse::<f64>().ok()),
+        other => as_i64(other).map(|v| v as f64),
+    }
+}
+
+/// String form of a value as it appears in JSON responses.
+pub fn as_string(value: &DataType) -> Option<String> {
+    match value {
+        DataType::Null => None,
+        DataType::Char(v) | DataType::Text(v) | DataType::Unknown(v) => v.clone(),
+        DataType::Json(v) => v.as_ref().map(|json| json.to_string()),
+        other => match serde_json::to_value(other).ok()? {
+            serde_json::Value::Null => None,
+            serde_json::Value::String(s) => Some(s),
+            json => Some(json.to_string()),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{Array, Float64Array, Int64Array, StringArray};
+    use tiberius::numeric::Decimal;
+
+    #[test]
+    fn test_rows_to_record_batch() {
+        let rows: Vec<IndexMap<String, DataType>> = vec![
+            IndexMap::from([
+                ("id".to_string(), DataType::Int8(Some(1))),
+                ("amount".to_string(), DataType::Float8(None)),
+                ("region".to_string(), DataType::Text(Some("west".to_string()))),
+            ]),
+            IndexMap::from([
+                ("id".to_string(), DataType::Int8(Some(2))),
+                ("amount".to_string(), DataType::Float8(Some(10.5))),
+                ("region".to_string(), DataType::Null),
+            ]),
+        ];
+
+        let schema = schema_from_rows(&rows);
+        assert_eq!(schema.field(1).data_type(), &ArrowDataType::Float64);
+
+        let batch = rows_to_record_batch(&schema, &rows).unwrap();
+        assert_eq!(batch.num_rows(), 2);
+
+        let ids = batch.column(0).as_any().downcast_ref::<Int64Array>().unwrap();
+        assert_eq!(ids.value(1), 2);
+
+        let amounts = batch.column(1).as_any().downcast_ref::<Float64Array>().unwrap();
+        assert!(amounts.is_null(0));
+        assert_eq!(amounts.value(1), 10.5);
+
+        let regions = batch.column(2).as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!(regions.value(0), "west");
+        assert!(regions.is_null(1));
+    }
+
+    #[test]
+    fn test_decimal_columns_keep_exact_values() {
+        let first_page: Vec<IndexMap<String, DataType>> = vec![
+            IndexMap::from([(
+                "balance".to_string(),
+                DataType::Decimal(Some(Decimal::new(123456789012345678, 2))),
+            )]),
+            IndexMap::from([(
+                "balance".to_string(),
+                DataType::Decimal(Some(Decimal::new(15, 1))),
+            )]),
+        ];
+
+        let schema = schema_from_rows(&first_page);
+        assert_eq!(schema.field(0).data_type(), &ArrowDataType::Utf8);
+
+        let batch = rows_to_record_batch(&schema, &first_page).unwrap();
+        let balances = batch.column(0).as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!(balances.value(0), "1234567890123456.78");
+        assert_eq!(balances.value(1), "1.5");
+
+        // Later pages with a larger scale, or decimals too wide for a Decimal, still fit
+        let wide = "123456789012345678901234567890.12";
+        let later_page: Vec<IndexMap<String, DataType>> = vec![
+            IndexMap::from([("balance".to_string(), DataType::Decimal(None))]),
+            IndexMap::from([(
+                "balance".to_string(),
+                DataType::Decimal(Some(Decimal::new(1234, 3))),
+            )]),
+            IndexMap::from([("balance".to_string(), DataType::exact_decimal(wide.to_string()))]),
+        ];
+        let batch = rows_to_record_batch(&schema, &later_page).unwrap();
+        let balances = batch.column(0).as_any().downcast_ref::<StringArray>().unwrap();
+        assert!(balances.is_null(0));
+        assert_eq!(balances.value(1), "1.234");
+        assert_eq!(balances.value(2), wide);
+    }
+
+    #[test]
+    fn test_values_that_outgrow_the_schema_fail() {
+        let first_page: Vec<IndexMap<String, DataType>> =
+            vec![IndexMap::from([("active".to_string(), DataType::Bool(Some(true)))])];
+        let schema = schema_from_rows(&first_page);
+
+        // A later value of another type can't be written without losing it
+        let later_page: Vec<IndexMap<String, DataType>> =
+            vec![IndexMap::from([("active".to_string(), DataType::Text(Some("yes".to_string())))])];
+        assert!(rows_to_record_batch(&schema, &later_page).is_err());
+
+        // Integers are widened, so a small first page doesn't cap later values
+        let small: Vec<IndexMap<String, DataType>> =
+            vec![IndexMap::from([("id".to_string(), DataType::Int2(Some(1)))])];
+        let schema = schema_from_rows(&small);
+        let large: Vec<IndexMap<String, DataType>> =
+            vec![IndexMap::from([("id".to_string(), DataType::Int8(Some(i64::MAX)))])];
+        let batch = rows_to_record_batch(&schema, &large).unwrap();
+        let ids = batch.column(0).as_any().downcast_ref::<Int64Array>().unwrap();
+        assert_eq!(ids.value(0), i64::MAX);
+    }
+}
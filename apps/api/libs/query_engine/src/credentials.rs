@@ -11,11 +11,14 @@ use database::{enums::DataSourceType, vault::read_secret};
 pub enum Credential {
     Postgres(PostgresCredentials),
     MySql(MySqlCredentials),
+    Mariadb(MariadbCredentials),
     Bigquery(BigqueryCredentials),
     SqlServer(SqlServerCredentials),
     Redshift(RedshiftCredentials),
     Databricks(DatabricksCredentials),
     Snowflake(SnowflakeCredentials),
+    DuckDb(DuckDbCredentials),
+    ClickHouse(ClickHouseCredentials),
 }
 
 /// Custom deserializer that handles both string and JSON object formats for credentials
@@ -154,16 +157,49 @@ pub struct SqlServerCredentials {
 
 // can get rid of schemas and
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DuckDbCredentials {
+    /// Path to a `.duckdb` database file. When omitted an in-memory database is used,
+    /// which is enough for querying Parquet/CSV files with `read_parquet`/`read_csv_auto`.
+    #[serde(alias = "path")]
+    pub database_path: Option<String>,
+    /// Directory that relative file paths in queries (e.g. `read_parquet('orders.parquet')`)
+    /// are resolved against.
+    pub file_search_path: Option<String>,
+    pub default_schema: Option<String>,
+}
+
+/// Connects over the HTTP interface. The user's settings profile should have `readonly=2`
+/// (or `readonly=0`), which lets each query set its own row limit and output format while
+/// staying read-only. A `readonly=1` profile can't change any setting, so its queries run with
+/// the server's defaults: rows past the limit are only dropped once downloaded, and decimals
+/// arrive as floats.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ClickHouseCredentials {
+    pub host: String,
+    /// HTTP(S) interface port, usually 8443 with TLS or 8123 without.
+    pub port: u16,
+    pub username: String,
+    pub password: String,
+    #[serde(alias = "database")]
+    pub default_database: String,
+    /// Defaults to true; only disable for local or private-network servers.
+    pub use_tls: Option<bool>,
+}
+
 impl Credential {
     pub fn get_type_string(&self) -> String {
         match self {
             Credential::Postgres(_) => "postgres".to_string(),
             Credential::MySql(_) => "mysql".to_string(),
+            Credential::Mariadb(_) => "mariadb".to_string(),
             Credential::Bigquery(_) => "bigquery".to_string(),
             Credential::SqlServer(_) => "sqlserver".to_string(),
             Credential::Redshift(_) => "redshift".to_string(),
             Credential::Databricks(_) => "databricks".to_string(),
             Credential::Snowflake(_) => "snowflake".to_string(),
+            Credential::DuckDb(_) => "duckdb".to_string(),
+            Credential::ClickHouse(_) => "clickhouse".to_string(),
         }
     }
 
@@ -171,11 +207,14 @@ impl Credential {
         match self {
             Credential::Postgres(_) => DataSourceType::Postgres,
             Credential::MySql(_) => DataSourceType::MySql,
+            Credential::Mariadb(_) => DataSourceType::Mariadb,
             Credential::Bigquery(_) => DataSourceType::BigQuery,
             Credential::SqlServer(_) => DataSourceType::SqlServer,
             Credential::Redshift(_) => DataSourceType::Redshift,
             Credential::Databricks(_) => DataSourceType::Databricks,
             Credential::Snowflake(_) => DataSourceType::Snowflake,
+            Credential::DuckDb(_) => DataSourceType::DuckDb,
+            Credential::ClickHouse(_) => DataSourceType::ClickHouse,
         }
     }
 }
@@ -226,14 +265,14 @@ pub async fn get_data_source_credentials(
             }
             Err(e) => return Err(anyhow!("Error deserializing MySQL secret: {:?}", e)),
         },
-        DataSourceType::Mariadb => match serde_json::from_str::<MySqlCredentials>(&secret_string) {
+        DataSourceType::Mariadb => match serde_json::from_str::<MariadbCredentials>(&secret_string) {
             Ok(mut credential) => {
                 if redact_secret {
                     credential.password = "[REDACTED]".to_string();
                     credential.ssh_private_key =
                         credential.ssh_private_key.map(|_| "[REDACTED]".to_string());
                 }
-                Credential::MySql(credential)
+                Credential::Mariadb(credential)
             }
             Err(e) => return Err(anyhow!("Error deserializing MariaDB secret: {:?}", e)),
         },
@@ -298,6 +337,23 @@ pub async fn get_data_source_credentials(
                 Err(e) => return Err(anyhow!("Error deserializing Supabase secret: {:?}", e)),
             }
         }
+        DataSourceType::DuckDb => {
+            match serde_json::from_str::<DuckDbCredentials>(&secret_string) {
+                Ok(credential) => Credential::DuckDb(credential),
+                Err(e) => return Err(anyhow!("Error deserializing DuckDB secret: {:?}", e)),
+            }
+        }
+        DataSourceType::ClickHouse => {
+            match serde_json::from_str::<ClickHouseCredentials>(&secret_string) {
+                Ok(mut credential) => {
+                    if redact_secret {
+                        credential.password = "[REDACTED]".to_string();
+                    }
+                    Credential::ClickHouse(credential)
+                }
+                Err(e) => return Err(anyhow!("Error deserializing ClickHouse secret: {:?}", e)),
+            }
+        }
     };
     Ok(credential)
 }
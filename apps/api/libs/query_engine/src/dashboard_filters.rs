@@ -0,0 +1,553 @@
+//! Dashboard-level filters applied to the SQL of a dashboard's metrics.
+//!
+//! A filter targets each metric either through a column of the metric's result, which the
+//! result is then filtered on, or through one of the metric's parameters, which receives the
+//! filter's value.
+
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use chrono::{Duration, NaiveDate, Utc};
+use database::types::{DashboardFilter, DashboardFilterType, DashboardYml, MetricYml};
+use serde_json::{json, Value};
+use sql_analyzer::{apply_result_filters, ParameterType, ResultFilter, ResultFilterCondition};
+use uuid::Uuid;
+
+use crate::metric_parameters::{bind_metric_sql, date_range, scalar_text, ParameterOverrides};
+use crate::pagination::get_data_source_type;
+
+/// Filter values picked on a dashboard, keyed by filter id
+pub type DashboardFilterValues = HashMap<String, Value>;
+
+/// What a dashboard's filters amount to for one of its metrics
+#[derive(Debug, Default, PartialEq)]
+pub struct MetricFilters {
+    /// Values for the metric's parameters
+    pub parameters: ParameterOverrides,
+    /// Conditions on the metric's result columns
+    pub result_filters: Vec<ResultFilter>,
+}
+
+/// A metric's SQL with a dashboard's filters applied
+#[derive(Debug, PartialEq)]
+pub struct FilteredMetricSql {
+    pub sql: String,
+    /// Dashboard filters the metric runs without, and why, to show alongside its data
+    pub warnings: Vec<String>,
+}
+
+/// Returns the metric's SQL with its parameters bound and the dashboard's filters applied.
+/// Explicit `overrides` take precedence over filters that feed the same parameter.
+pub fn filtered_metric_sql(
+    metric_id: &Uuid,
+    metric_yml: &MetricYml,
+    overrides: &ParameterOverrides,
+    dashboard: &DashboardYml,
+    values: &DashboardFilterValues,
+    data_source_dialect: &str,
+) -> Result<FilteredMetricSql> {
+    let filters = resolve_dashboard_filters(
+        dashboard,
+        metric_id,
+        metric_yml,
+        values,
+        Utc::now().date_naive(),
+    )?;
+
+    let mut parameters = filters.parameters;
+    parameters.extend(overrides.iter().map(|(name, value)| (name.clone(), value.clone())));
+
+    let sql = bind_metric_sql(metric_yml, &parameters, data_source_dialect)?;
+
+    let filtered = apply_result_filters(&sql, data_source_dialect, &filters.result_filters)
+        .map_err(|e| anyhow!("Failed to apply dashboard filters: {}", e))?;
+
+    let warnings = filtered
+        .skipped
+        .iter()
+        .map(|skipped| {
+            let filter_id = dashboard
+                .filters
+                .iter()
+                .find(|filter| {
+                    filter.targets.iter().any(|target| {
+                        &target.metric_id == metric_id
+                            && target.column.as_deref() == Some(skipped.column.as_str())
+                    })
+                })
+                .map_or(skipped.column.as_str(), |filter| filter.id.as_str());
+            format!(
+                "Dashboard filter '{}' wasn't applied to this metric: {}",
+                filter_id, skipped.reason
+            )
+        })
+        .collect();
+
+    Ok(FilteredMetricSql {
+        sql: filtered.sql,
+        warnings,
+    })
+}
+
+/// Same as `filtered_metric_sql`, looking up the dialect from the data source.
+pub async fn filtered_metric_sql_for_data_source(
+    metric_id: &Uuid,
+    metric_yml: &MetricYml,
+    overrides: &ParameterOverrides,
+    dashboard: &DashboardYml,
+    values: &DashboardFilterValues,
+    data_source_id: &Uuid,
+) -> Result<FilteredMetricSql> {
+    let data_source_type = get_data_source_type(data_source_id).await?;
+
+    filtered_metric_sql(
+        metric_id,
+        metric_yml,
+        overrides,
+        dashboard,
+        values,
+        data_source_type.to_str(),
+    )
+}
+
+/// Works out the parameter values and result filters the dashboard's filters produce for
+/// `metric_id`, using `values` where given and the filter defaults otherwise. Filters with
+/// neither are inactive. Select values are compared as the type `metric_yml` gives their
+/// column.
+pub fn resolve_dashboard_filters(
+    dashboard: &DashboardYml,
+    metric_id: &Uuid,
+    metric_yml: &MetricYml,
+    values: &DashboardFilterValues,
+    today: NaiveDate,
+) -> Result<MetricFilters> {
+    if !dashboard.contains_metric(metric_id) {
+        return Err(anyhow!("Metric {} is not on this dashboard", metric_id));
+    }
+
+    if let Some(unknown) = values
+        .keys()
+        .find(|id| !dashboard.filters.iter().any(|f| &f.id == *id))
+    {
+        return Err(anyhow!("Unknown dashboard filter '{}'", unknown));
+    }
+
+    let mut resolved = MetricFilters::default();
+
+    for filter in &dashboard.filters {
+        let Some(value) = values
+            .get(&filter.id)
+            .or(filter.default.as_ref())
+            .filter(|value| !value.is_null())
+        else {
+            continue;
+        };
+
+        for target in filter.targets.iter().filter(|t| &t.metric_id == metric_id) {
+            match (&target.column, &target.parameter) {
+                (Some(column), _) => {
+                    resolved.result_filters.push(ResultFilter {
+                        column: column.clone(),
+                        condition: column_condition(
+                            filter,
+                            value,
+                            column_value_type(metric_yml, column),
+                            today,
+                        )?,
+                    });
+                }
+                (None, Some(parameter)) => {
+                    resolved
+                        .parameters
+                        .insert(parameter.clone(), parameter_value(filter, value, today)?);
+                }
+                (None, None) => {}
+            }
+        }
+    }
+
+    Ok(resolved)
+}
+
+fn invalid_value(filter: &DashboardFilter, value: &Value) -> anyhow::Error {
+    anyhow!("Invalid value for dashboard filter '{}': {}", filter.id, value)
+}
+
+/// The type of a metric's result column from its label format, text when it has none
+fn column_value_type(metric_yml: &MetricYml, column: &str) -> ParameterType {
+    let formats = &metric_yml.chart_config.base().column_label_formats;
+    let format = formats
+        .get(&column.to_lowercase())
+        .or_else(|| formats.get(column));
+
+    match format.map(|format| format.column_type.as_str()) {
+        Some("number") => ParameterType::Number,
+        Some("date") => ParameterType::Date,
+        _ => ParameterType::String,
+    }
+}
+
+fn column_condition(
+    filter: &DashboardFilter,
+    value: &Value,
+    column_type: ParameterType,
+    today: NaiveDate,
+) -> Result<ResultFilterCondition> {
+    match filter.filter_type {
+        DashboardFilterType::DateRange => {
+            let (start, end) =
+                date_range(value, today).ok_or_else(|| invalid_value(filter, value))?;
+
+            // A date-only end covers the whole day, timestamps included
+            let (end, end_inclusive) = match NaiveDate::parse_from_str(&end, "%Y-%m-%d") {
+                Ok(date) => ((date + Duration::days(1)).to_string(), false),
+                Err(_) => (end, true),
+            };
+
+            Ok(ResultFilterCondition::Range {
+                value_type: ParameterType::Date,
+                start: Some(start),
+                end: Some(end),
+                end_inclusive,
+            })
+        }
+        DashboardFilterType::Select => Ok(ResultFilterCondition::In {
+            value_type: column_type,
+            values: selected_values(filter, value)?,
+        }),
+        DashboardFilterType::NumberRange => {
+            let (min, max) = match value {
+                Value::Object(range) => (range.get("min"), range.get("max")),
+                Value::Array(range) if range.len() == 2 => (range.first(), range.get(1)),
+                _ => return Err(invalid_value(filter, value)),
+            };
+            let bound = |bound: Option<&Value>| match bound {
+                None | Some(Value::Null) => Ok(None),
+                Some(bound) => scalar_text(bound)
+                    .map(Some)
+                    .ok_or_else(|| invalid_value(filter, value)),
+            };
+
+            Ok(ResultFilterCondition::Range {
+                value_type: ParameterType::Number,
+                start: bound(min)?,
+                end: bound(max)?,
+                end_inclusive: true,
+            })
+        }
+    }
+}
+
+fn parameter_value(filter: &DashboardFilter, value: &Value, today: NaiveDate) -> Result<Value> {
+    match filter.filter_type {
+        DashboardFilterType::DateRange => {
+            let (start, end) =
+                date_range(value, today).ok_or_else(|| invalid_value(filter, value))?;
+            Ok(json!({ "start": start, "end": end }))
+        }
+        DashboardFilterType::Select => {
+            let selected = selected_values(filter, value)?;
+            // A single pick also fits a plain string parameter
+            Ok(match selected.as_slice() {
+                [single] if !value.is_array() => json!(single),
+                _ => json!(selected),
+            })
+        }
+        DashboardFilterType::NumberRange => Err(anyhow!(
+            "Number range filter '{}' can only target columns",
+            filter.id
+        )),
+    }
+}
+
+fn selected_values(filter: &DashboardFilter, value: &Value) -> Result<Vec<String>> {
+    let selected = match value {
+        Value::Array(items) => items
+            .iter()
+            .map(scalar_text)
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| invalid_value(filter, value))?,
+        other => vec![scalar_text(other).ok_or_else(|| invalid_value(filter, value))?],
+    };
+
+    if selected.is_empty() {
+        return Err(anyhow!(
+            "Dashboard filter '{}' needs at least one value",
+            filter.id
+        ));
+    }
+
+    if let Some(options) = &filter.options {
+        if let Some(not_allowed) = selected.iter().find(|v| !options.contains(v)) {
+            return Err(anyhow!(
+                "'{}' is not an allowed value for dashboard filter '{}'",
+                not_allowed,
+                filter.id
+            ));
+        }
+    }
+
+    Ok(selected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dashboard() -> DashboardYml {
+        DashboardYml::new(
+            r#"
+name: Sales
+rows:
+  - id: 1
+    items:
+      - id: 00000000-0000-0000-0000-000000000001
+      - id: 00000000-0000-0000-0000-000000000002
+    columnSizes: [6, 6]
+filters:
+  - id: period
+    type: date_range
+    default: { start: "2024-01-01", end: "2024-01-31" }
+    targets:
+      - metricId: 00000000-0000-0000-0000-000000000001
+        column: order_date
+      - metricId: 00000000-0000-0000-0000-000000000002
+        parameter: period
+  - id: region
+    type: select
+    options: [west, east]
+    targets:
+      - metricId: 00000000-0000-0000-0000-000000000001
+        column: region
+  - id: amount
+    type: number_range
+    targets:
+      - metricId: 00000000-0000-0000-0000-000000000001
+        column: amount
+  - id: store
+    type: select
+    targets:
+      - metricId: 00000000-0000-0000-0000-000000000001
+        column: store_id
+"#
+            .to_string(),
+        )
+        .unwrap()
+    }
+
+    fn metric(n: u128) -> Uuid {
+        Uuid::from_u128(n)
+    }
+
+    fn orders_metric() -> MetricYml {
+        serde_yaml::from_str(
+            r#"
+name: Orders
+timeFrame: Custom
+sql: SELECT o.order_date, o.region, o.store_id, o.amount FROM sales.orders o
+chartConfig:
+  selectedChartType: table
+  columnLabelFormats:
+    order_date: { columnType: date, style: date }
+    region: { columnType: string, style: string }
+    store_id: { columnType: number, style: number }
+    amount: { columnType: number, style: currency }
+"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_resolve_column_and_parameter_targets() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 15).unwrap();
+        let values = DashboardFilterValues::from([
+            ("region".to_string(), json!(["west"])),
+            ("amount".to_string(), json!({ "min": 10 })),
+        ]);
+
+        let first =
+            resolve_dashboard_filters(&dashboard(), &metric(1), &orders_metric(), &values, today)
+                .unwrap();
+        assert!(first.parameters.is_empty());
+        assert_eq!(
+            first.result_filters,
+            vec![
+                ResultFilter {
+                    column: "order_date".to_string(),
+                    condition: ResultFilterCondition::Range {
+                        value_type: ParameterType::Date,
+                        start: Some("2024-01-01".to_string()),
+                        end: Some("2024-02-01".to_string()),
+                        end_inclusive: false,
+                    },
+                },
+                ResultFilter {
+                    column: "region".to_string(),
+                    condition: ResultFilterCondition::In {
+                        value_type: ParameterType::String,
+                        values: vec!["west".to_string()],
+                    },
+                },
+                ResultFilter {
+                    column: "amount".to_string(),
+                    condition: ResultFilterCondition::Range {
+                        value_type: ParameterType::Number,
+                        start: Some("10".to_string()),
+                        end: None,
+                        end_inclusive: true,
+                    },
+                },
+            ]
+        );
+
+        let values = DashboardFilterValues::from([("period".to_string(), json!("last_7_days"))]);
+        let second =
+            resolve_dashboard_filters(&dashboard(), &metric(2), &orders_metric(), &values, today)
+                .unwrap();
+        assert!(second.result_filters.is_empty());
+        assert_eq!(
+            second.parameters,
+            ParameterOverrides::from([(
+                "period".to_string(),
+                json!({ "start": "2024-03-08", "end": "2024-03-15" })
+            )])
+        );
+    }
+
+    #[test]
+    fn test_resolve_rejects_bad_values() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 15).unwrap();
+        let dashboard = dashboard();
+
+        let metric_yml = orders_metric();
+
+        // Not on the dashboard
+        assert!(resolve_dashboard_filters(
+            &dashboard,
+            &metric(3),
+            &metric_yml,
+            &DashboardFilterValues::new(),
+            today
+        )
+        .is_err());
+
+        for (id, value) in [
+            ("region", json!(["south"])),
+            ("period", json!(42)),
+            ("amount", json!("lots")),
+            ("unknown", json!("x")),
+        ] {
+            let values = DashboardFilterValues::from([(id.to_string(), value)]);
+            assert!(
+                resolve_dashboard_filters(&dashboard, &metric(1), &metric_yml, &values, today)
+                    .is_err()
+            );
+        }
+    }
+
+    #[test]
+    fn test_select_values_take_their_column_type() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 15).unwrap();
+        let values = DashboardFilterValues::from([("store".to_string(), json!([3, "7"]))]);
+
+        let resolved =
+            resolve_dashboard_filters(&dashboard(), &metric(1), &orders_metric(), &values, today)
+                .unwrap();
+        assert!(resolved.result_filters.contains(&ResultFilter {
+            column: "store_id".to_string(),
+            condition: ResultFilterCondition::In {
+                value_type: ParameterType::Number,
+                values: vec!["3".to_string(), "7".to_string()],
+            },
+        }));
+
+        let filtered = filtered_metric_sql(
+            &metric(1),
+            &orders_metric(),
+            &ParameterOverrides::new(),
+            &dashboard(),
+            &values,
+            "postgres",
+        )
+        .unwrap();
+        assert!(filtered.sql.ends_with("store_id IN (3, 7)"), "{}", filtered.sql);
+
+        // Numeric columns only take numbers
+        let values = DashboardFilterValues::from([("store".to_string(), json!("west"))]);
+        assert!(filtered_metric_sql(
+            &metric(1),
+            &orders_metric(),
+            &ParameterOverrides::new(),
+            &dashboard(),
+            &values,
+            "postgres",
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_filtered_metric_sql() {
+        let metric_yml: MetricYml = serde_yaml::from_str(
+            r#"
+name: Orders
+timeFrame: Custom
+sql: SELECT o.order_date, o.region, o.amount FROM sales.orders o
+chartConfig:
+  selectedChartType: table
+  columnLabelFormats: {}
+"#,
+        )
+        .unwrap();
+
+        let values = DashboardFilterValues::from([("region".to_string(), json!("east"))]);
+        let filtered = filtered_metric_sql(
+            &metric(1),
+            &metric_yml,
+            &ParameterOverrides::new(),
+            &dashboard(),
+            &values,
+            "postgres",
+        )
+        .unwrap();
+
+        assert_eq!(
+            filtered.sql,
+            "SELECT * FROM (SELECT o.order_date, o.region, o.amount FROM sales.orders AS o) AS buster_filtered WHERE order_date >= '2024-01-01' AND order_date < '2024-02-01' AND region IN ('east')"
+        );
+        assert!(filtered.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_filtered_metric_sql_warns_about_skipped_filters() {
+        let metric_yml: MetricYml = serde_yaml::from_str(
+            r#"
+name: Top regions
+timeFrame: Custom
+sql: SELECT o.region, SUM(o.amount) AS amount FROM sales.orders o GROUP BY o.region ORDER BY amount DESC LIMIT 5
+chartConfig:
+  selectedChartType: table
+  columnLabelFormats: {}
+"#,
+        )
+        .unwrap();
+
+        // The region filter moves in front of the limit; the metric has no order_date to filter
+        let values = DashboardFilterValues::from([("region".to_string(), json!("east"))]);
+        let filtered = filtered_metric_sql(
+            &metric(1),
+            &metric_yml,
+            &ParameterOverrides::new(),
+            &dashboard(),
+            &values,
+            "postgres",
+        )
+        .unwrap();
+
+        assert_eq!(
+            filtered.sql,
+            "SELECT o.region, SUM(o.amount) AS amount FROM sales.orders AS o WHERE o.region IN ('east') GROUP BY o.region ORDER BY amount DESC LIMIT 5"
+        );
+        assert_eq!(filtered.warnings.len(), 1);
+        assert!(filtered.warnings[0].starts_with("Dashboard filter 'period' wasn't applied"));
+    }
+}
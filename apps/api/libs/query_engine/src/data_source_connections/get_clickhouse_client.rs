@@ -0,0 +1,161 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::Duration;
+
+use crate::credentials::ClickHouseCredentials;
+
+pub async fn get_clickhouse_client(credentials: &ClickHouseCredentials) -> Result<ClickHouse> {
+    let clickhouse_client = ClickHouse::new(credentials);
+
+    Ok(clickhouse_client)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ClickHouseColumn {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub type_name: String,
+}
+
+/// Response body of the HTTP interface when using `FORMAT JSONCompact`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ClickHouseQueryResponse {
+    pub meta: Vec<ClickHouseColumn>,
+    pub data: Vec<Vec<Value>>,
+    pub rows: Option<u64>,
+}
+
+#[derive(Clone)]
+pub struct ClickHouse {
+    pub base_url: String,
+    pub username: String,
+    pub password: String,
+    pub database: String,
+}
+
+impl ClickHouse {
+    pub fn new(credentials: &ClickHouseCredentials) -> Self {
+        let scheme = if credentials.use_tls.unwrap_or(true) {
+            "https"
+        } else {
+            "http"
+        };
+
+        ClickHouse {
+            base_url: format!(
+                "{scheme}://{host}:{port}/",
+                host = credentials.host,
+                port = credentials.port
+            ),
+            username: credentials.username.clone(),
+            password: credentials.password.clone(),
+            database: credentials.default_database.clone(),
+        }
+    }
+
+    /// Runs a statement over the HTTP interface. When `max_rows` is set the server stops
+    /// reading once it has produced that many rows instead of erroring.
+    ///
+    /// Users whose profile has `readonly=1` can't change settings, so when the server refuses
+    /// them the statement is run again with none (see `ClickHouseCredentials`).
+    pub async fn query(
+        &self,
+        statement: String,
+        max_rows: Option<usize>,
+    ) -> Result<ClickHouseQueryResponse> {
+        let mut settings = vec![
+            ("readonly", "2".to_string()),
+            ("output_format_json_quote_64bit_integers", "0".to_string()),
+            // Quoted decimals keep their exact digits instead of passing through a float
+            ("output_format_json_quote_decimals", "1".to_string()),
+        ];
+
+        if let Some(max_rows) = max_rows {
+            settings.push(("max_result_rows", max_rows.to_string()));
+            settings.push(("result_overflow_mode", "break".to_string()));
+        }
+
+        match self.send(&statement, settings).await {
+            Err(e) if is_readonly_error(&e) => {
+                tracing::warn!(
+                    "ClickHouse user {} can't change settings (readonly=1), running the query with the server's defaults",
+                    self.username
+                );
+                self.send(&statement, Vec::new()).await
+            }
+            result => result,
+        }
+    }
+
+    async fn send(
+        &self,
+        statement: &str,
+        settings: Vec<(&str, String)>,
+    ) -> Result<ClickHouseQueryResponse> {
+        let client = reqwest::Client::new();
+
+        // The database and default format are request parameters, not settings, so readonly
+        // profiles accept them
+        let mut params = vec![
+            ("database", self.database.clone()),
+            ("default_format", "JSONCompact".to_string()),
+        ];
+        params.extend(settings);
+
+        let response = match client
+            .post(&self.base_url)
+            .query(&params)
+            .header("X-ClickHouse-User", &self.username)
+            .header("X-ClickHouse-Key", &self.password)
+            .timeout(Duration::from_secs(300))
+            .body(statement.to_string())
+            .send()
+            .await
+        {
+            Ok(res) => res,
+            Err(e) => return Err(anyhow!(e.to_string())),
+        };
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(anyhow!("ClickHouse returned {}: {}", status, body.trim()));
+        }
+
+        let response: ClickHouseQueryResponse = match response.json().await {
+            Ok(res) => res,
+            Err(e) => return Err(anyhow!(e.to_string())),
+        };
+
+        Ok(response)
+    }
+}
+
+/// Whether the server refused to change a setting because the user's profile is read-only
+/// (error 164, `READONLY`)
+fn is_readonly_error(error: &anyhow::Error) -> bool {
+    let message = error.to_string();
+
+    message.contains("Code: 164") && message.contains("Cannot modify")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_readonly_errors() {
+        let refused = anyhow!(
+            "ClickHouse returned 500 Internal Server Error: Code: 164. DB::Exception: Cannot modify 'readonly' setting in readonly mode. (READONLY)"
+        );
+        assert!(is_readonly_error(&refused));
+
+        // A write refused in readonly mode fails the same way with or without settings
+        let write = anyhow!(
+            "ClickHouse returned 500 Internal Server Error: Code: 164. DB::Exception: analyst: Cannot execute query in readonly mode. (READONLY)"
+        );
+        assert!(!is_readonly_error(&write));
+        assert!(!is_readonly_error(&anyhow!("ClickHouse returned 404 Not Found: Code: 60. Table doesn't exist")));
+    }
+}
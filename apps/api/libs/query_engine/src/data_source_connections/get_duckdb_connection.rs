@@ -0,0 +1,70 @@
+use anyhow::{anyhow, Result};
+use duckdb::{AccessMode, Config, Connection};
+
+use crate::credentials::DuckDbCredentials;
+
+pub async fn get_duckdb_connection(credentials: &DuckDbCredentials) -> Result<Connection> {
+    // File databases are opened read-only so queries can never modify the source data
+    // and several readers can share the same file.
+    let connection = match &credentials.database_path {
+        Some(database_path) => {
+            let config = Config::default()
+                .access_mode(AccessMode::ReadOnly)
+                .map_err(|e| anyhow!("Invalid DuckDB configuration: {}", e))?;
+
+            match Connection::open_with_flags(database_path, config) {
+                Ok(connection) => connection,
+                Err(e) => {
+                    tracing::error!("There was an issue while opening the DuckDB database: {}", e);
+                    return Err(anyhow!(e));
+                }
+            }
+        }
+        None => match Connection::open_in_memory() {
+            Ok(connection) => connection,
+            Err(e) => {
+                tracing::error!("There was an issue while opening an in-memory DuckDB database: {}", e);
+                return Err(anyhow!(e));
+            }
+        },
+    };
+
+    // Queries may only read files under the configured file_search_path. Without one they
+    // can't touch the filesystem or network at all.
+    let allowed_directories = match &credentials.file_search_path {
+        Some(file_search_path) => {
+            connection
+                .execute_batch(&format!(
+                    "SET file_search_path = '{}';",
+                    file_search_path.replace('\'', "''")
+                ))
+                .map_err(|e| anyhow!("Error setting DuckDB file_search_path: {}", e))?;
+
+            let root = if file_search_path.ends_with('/') {
+                file_search_path.clone()
+            } else {
+                format!("{}/", file_search_path)
+            };
+            format!("['{}']", root.replace('\'', "''"))
+        }
+        None => "[]".to_string(),
+    };
+
+    if let Some(default_schema) = &credentials.default_schema {
+        connection
+            .execute_batch(&format!("SET schema = '{}';", default_schema.replace('\'', "''")))
+            .map_err(|e| anyhow!("Error setting DuckDB default schema: {}", e))?;
+    }
+
+    // Locking the configuration last stops user SQL from undoing any of this with SET
+    connection
+        .execute_batch(&format!(
+            "SET allowed_directories = {};
+             SET enable_external_access = false;
+             SET lock_configuration = true;",
+            allowed_directories
+        ))
+        .map_err(|e| anyhow!("Error restricting DuckDB file access: {}", e))?;
+
+    Ok(connection)
+}
@@ -1,5 +1,8 @@
 pub mod get_bigquery_client;
+pub mod get_clickhouse_client;
 pub mod get_databricks_client;
+pub mod get_duckdb_connection;
+pub mod get_mariadb_connection;
 pub mod get_mysql_connection;
 pub mod get_postgres_connection;
 pub mod get_redshift_connection;
@@ -1,9 +1,12 @@
 use crate::credentials::Credential;
 use crate::data_source_connections::{
-    get_bigquery_client::get_bigquery_client, get_databricks_client::get_databricks_client,
-    get_mysql_connection::get_mysql_connection, get_postgres_connection::get_postgres_connection,
+    get_bigquery_client::get_bigquery_client, get_clickhouse_client::get_clickhouse_client,
+    get_databricks_client::get_databricks_client,
+    get_duckdb_connection::get_duckdb_connection, get_mariadb_connection::get_mariadb_connection,
+    get_mysql_connection::get_mysql_connection,
+    get_postgres_connection::get_postgres_connection,
     get_redshift_connection::get_redshift_connection, get_snowflake_client::get_snowflake_client,
-    get_sql_server_connection::get_sql_server_connection,
+    get_sql_server_connection::get_sql_server_connection, ssh_tunneling::kill_ssh_tunnel,
 };
 use anyhow::{anyhow, Result};
 
@@ -38,6 +41,25 @@ pub async fn test_data_source_connection(credential: &Credential) -> Result<()>
 
             Ok(())
         }
+        Credential::Mariadb(credential) => {
+            let (pool, ssh_tunnel, temp_files) = match get_mariadb_connection(credential).await {
+                Ok(connection) => connection,
+                Err(e) => return Err(anyhow!("Error getting mariadb client: {:?}", e)),
+            };
+
+            let test_result = sqlx::query("SELECT 1").execute(&pool).await;
+
+            if let (Some(mut ssh_tunnel), Some(temp_files)) = (ssh_tunnel, temp_files) {
+                let _ = kill_ssh_tunnel(&mut ssh_tunnel, temp_files).await;
+            };
+
+            match test_result {
+                Ok(_) => (),
+                Err(e) => return Err(anyhow!("Error executing test query: {:?}", e)),
+            }
+
+            Ok(())
+        }
         Credential::Postgres(credential) => {
             match get_postgres_connection(credential).await {
                 Ok(client) => client,
@@ -69,6 +91,32 @@ pub async fn test_data_source_connection(credential: &Credential) -> Result<()>
                 Err(e) => return Err(anyhow!("Error getting sqlserver client: {:?}", e)),
             };
 
+            Ok(())
+        }
+        Credential::DuckDb(credential) => {
+            let connection = match get_duckdb_connection(credential).await {
+                Ok(connection) => connection,
+                Err(e) => return Err(anyhow!("Error getting duckdb connection: {:?}", e)),
+            };
+
+            match connection.execute_batch("SELECT 1") {
+                Ok(_) => (),
+                Err(e) => return Err(anyhow!("Error executing test query: {:?}", e)),
+            }
+
+            Ok(())
+        }
+        Credential::ClickHouse(credential) => {
+            let client = match get_clickhouse_client(credential).await {
+                Ok(client) => client,
+                Err(e) => return Err(anyhow!("Error getting clickhouse client: {:?}", e)),
+            };
+
+            match client.query("SELECT 1".to_string(), None).await {
+                Ok(_) => (),
+                Err(e) => return Err(anyhow!("Error executing test query: {:?}", e)),
+            }
+
             Ok(())
         }
     }
@@ -0,0 +1,246 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use indexmap::IndexMap;
+
+use anyhow::{anyhow, Error};
+use serde_json::Value;
+
+use crate::{
+    data_source_connections::get_clickhouse_client::ClickHouse, data_types::DataType,
+};
+
+pub async fn clickhouse_query(
+    clickhouse_client: ClickHouse,
+    query: String,
+    limit: Option<i64>,
+    exact_decimals: bool,
+) -> Result<Vec<IndexMap<std::string::String, DataType>>, Error> {
+    // Get the limit value, defaulting to 5000 if not specified
+    let default_limit = 5000;
+    let limit_value = limit.unwrap_or(default_limit) as usize;
+
+    // Let the server stop early instead of appending a LIMIT to the query
+    let results = match clickhouse_client.query(query, Some(limit_value)).await {
+        Ok(results) => results,
+        Err(e) => {
+            tracing::error!("Error executing ClickHouse query: {}", e);
+            return Err(anyhow!(e.to_string()));
+        }
+    };
+
+    // Create vector with estimated capacity
+    let mut result: Vec<IndexMap<String, DataType>> =
+        Vec::with_capacity(results.data.len().min(limit_value));
+
+    let columns = results.meta;
+
+    for row in results.data {
+        // result_overflow_mode=break stops at block boundaries, so trim here as well
+        if result.len() >= limit_value {
+            break;
+        }
+
+        let mut row_map: IndexMap<String, DataType> = IndexMap::with_capacity(columns.len());
+
+        for (i, column) in columns.iter().enumerate() {
+            let value = row.get(i).unwrap_or(&Value::Null);
+            row_map.insert(
+                column.name.clone(),
+                parse_clickhouse_value(&column.type_name, value, exact_decimals),
+            );
+        }
+
+        result.push(row_map);
+    }
+
+    Ok(result)
+}
+
+/// Strips `Nullable(...)` and `LowCardinality(...)` wrappers, which don't change how
+/// values are encoded in JSON output.
+fn unwrap_type_modifiers(type_name: &str) -> &str {
+    let mut current = type_name.trim();
+
+    loop {
+        let inner = ["Nullable(", "LowCardinality("].iter().find_map(|prefix| {
+            current
+                .strip_prefix(prefix)
+                .and_then(|rest| rest.strip_suffix(')'))
+        });
+
+        match inner {
+            Some(inner) => current = inner.trim(),
+            None => return current,
+        }
+    }
+}
+
+/// Returns the type name without its arguments, e.g. `DateTime64(3, 'UTC')` -> `DateTime64`.
+fn base_type_name(type_name: &str) -> &str {
+    match type_name.find('(') {
+        Some(idx) => &type_name[..idx],
+        None => type_name,
+    }
+}
+
+fn value_as_string(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::String(s) => Some(s.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+fn value_as_i64(value: &Value) -> Option<i64> {
+    match value {
+        Value::Number(n) => n.as_i64(),
+        Value::String(s) => s.parse::<i64>().ok(),
+        _ => None,
+    }
+}
+
+fn value_as_f64(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.parse::<f64>().ok(),
+        _ => None,
+    }
+}
+
+fn parse_datetime(value: &Value) -> Option<NaiveDateTime> {
+    let s = value.as_str()?;
+    NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.f")
+        .or_else(|_| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S"))
+        .ok()
+}
+
+pub fn parse_clickhouse_value(type_name: &str, value: &Value, exact_decimals: bool) -> DataType {
+    let type_name = unwrap_type_modifiers(type_name);
+
+    match base_type_name(type_name) {
+        "Bool" | "Boolean" => DataType::Bool(match value {
+            Value::Bool(b) => Some(*b),
+            Value::Number(n) => n.as_u64().map(|n| n != 0),
+            _ => None,
+        }),
+        "Int8" | "UInt8" | "Int16" => {
+            DataType::Int2(value_as_i64(value).and_then(|v| i16::try_from(v).ok()))
+        }
+        "UInt16" | "Int32" => DataType::Int4(value_as_i64(value).and_then(|v| i32::try_from(v).ok())),
+        "UInt32" | "Int64" => DataType::Int8(value_as_i64(value)),
+        // 64-bit unsigned and wider integers may not fit in an i64
+        "UInt64" | "Int128" | "UInt128" | "Int256" | "UInt256" => {
+            DataType::wide_integer(value_as_string(value), exact_decimals)
+        }
+        "Float32" => DataType::Float4(value_as_f64(value).map(|v| v as f32)),
+        "Float64" => DataType::Float8(value_as_f64(value)),
+        "Decimal" | "Decimal32" | "Decimal64" | "Decimal128" | "Decimal256" => {
+            DataType::decimal(value_as_string(value), exact_decimals)
+        }
+        "String" | "FixedString" | "Enum8" | "Enum16" | "IPv4" | "IPv6" => {
+            DataType::Text(value_as_string(value))
+        }
+        "UUID" => DataType::Uuid(value.as_str().and_then(|s| s.parse::<uuid::Uuid>().ok())),
+        "Date" | "Date32" => DataType::Date(
+            value
+                .as_str()
+                .and_then(|s| NaiveDate::parse_from_str(s, "%Y-%m-%d").ok()),
+        ),
+        "DateTime" | "DateTime64" => DataType::Timestamp(parse_datetime(value)),
+        "Array" | "Map" | "Tuple" | "Nested" | "JSON" | "Object" => match value {
+            Value::Null => DataType::Json(None),
+            other => DataType::Json(Some(other.clone())),
+        },
+        "Nothing" => DataType::Null,
+        _ => DataType::Unknown(value_as_string(value)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_unwraps_low_cardinality_and_nullable() {
+        assert_eq!(unwrap_type_modifiers("LowCardinality(Nullable(String))"), "String");
+        assert_eq!(unwrap_type_modifiers("Nullable(DateTime64(3, 'UTC'))"), "DateTime64(3, 'UTC')");
+        assert_eq!(unwrap_type_modifiers("Array(Nullable(Int32))"), "Array(Nullable(Int32))");
+    }
+
+    #[test]
+    fn test_parse_clickhouse_scalar_types() {
+        assert_eq!(
+            parse_clickhouse_value("LowCardinality(String)", &json!("west"), false),
+            DataType::Text(Some("west".to_string()))
+        );
+        assert_eq!(
+            parse_clickhouse_value("Nullable(Int64)", &Value::Null, false),
+            DataType::Int8(None)
+        );
+        assert_eq!(
+            parse_clickhouse_value("UInt64", &json!(42), false),
+            DataType::Int8(Some(42))
+        );
+        assert_eq!(
+            parse_clickhouse_value("UInt64", &json!("18446744073709551615"), false),
+            DataType::Decimal(Some("18446744073709551615".parse().unwrap()))
+        );
+        assert_eq!(
+            parse_clickhouse_value("UInt64", &json!("42"), true),
+            DataType::Decimal(Some("42".parse().unwrap()))
+        );
+        let wide = "57896044618658097711785492504343953926634992332820282019728792003956564819967";
+        assert_eq!(
+            parse_clickhouse_value("Int256", &json!(wide), false),
+            DataType::Text(Some(wide.to_string()))
+        );
+        assert_eq!(
+            parse_clickhouse_value("Decimal(18, 4)", &json!("12345678901234.5678"), true),
+            DataType::Decimal(Some("12345678901234.5678".parse().unwrap()))
+        );
+        assert_eq!(
+            parse_clickhouse_value("Nullable(Decimal(18, 4))", &Value::Null, true),
+            DataType::Decimal(None)
+        );
+        for wide in ["123456789012345678901234567890.12", "12345678901234567890123456.123456"] {
+            assert_eq!(
+                parse_clickhouse_value("Decimal(76, 6)", &json!(wide), true),
+                DataType::Text(Some(wide.to_string()))
+            );
+        }
+        // The data endpoints get decimals as floats
+        assert_eq!(
+            parse_clickhouse_value("Decimal(18, 4)", &json!("20.25"), false),
+            DataType::Float8(Some(20.25))
+        );
+        assert_eq!(
+            parse_clickhouse_value("Date32", &json!("2024-02-29"), false),
+            DataType::Date(NaiveDate::from_ymd_opt(2024, 2, 29))
+        );
+    }
+
+    #[test]
+    fn test_parse_clickhouse_datetime64() {
+        let expected = NaiveDate::from_ymd_opt(2024, 5, 1)
+            .and_then(|d| d.and_hms_milli_opt(12, 30, 15, 250));
+
+        assert_eq!(
+            parse_clickhouse_value("DateTime64(3, 'UTC')", &json!("2024-05-01 12:30:15.250"), false),
+            DataType::Timestamp(expected)
+        );
+        assert_eq!(
+            parse_clickhouse_value("DateTime('Europe/Berlin')", &json!("2024-05-01 12:30:15"), false),
+            DataType::Timestamp(
+                NaiveDate::from_ymd_opt(2024, 5, 1).and_then(|d| d.and_hms_opt(12, 30, 15))
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_clickhouse_array() {
+        assert_eq!(
+            parse_clickhouse_value("Array(LowCardinality(String))", &json!(["a", "b"]), false),
+            DataType::Json(Some(json!(["a", "b"])))
+        );
+    }
+}
@@ -11,6 +11,7 @@ pub async fn databricks_query(
     databricks_client: Databricks,
     query: String,
     limit: Option<i64>,
+    exact_decimals: bool,
 ) -> Result<Vec<IndexMap<std::string::String, DataType>>, Error> {
     // Get the limit value, defaulting to 5000 if not specified
     let default_limit = 5000;
@@ -54,7 +55,10 @@ pub async fn databricks_query(
                 "BIGINT" => DataType::Int8(row[i].parse::<i64>().ok()),
                 "BOOL" => DataType::Bool(row[i].parse::<bool>().ok()),
                 "DATE" => DataType::Date(row[i].parse::<chrono::NaiveDate>().ok()),
-                "DECIMAL" => DataType::Float8(row[i].parse::<f64>().ok()),
+                "DECIMAL" => DataType::decimal(
+                    row[i].parse::<f64>().is_ok().then(|| row[i].clone()),
+                    exact_decimals,
+                ),
                 "DOUBLE" => DataType::Float8(row[i].parse::<f64>().ok()),
                 "FLOAT" => DataType::Float8(row[i].parse::<f64>().ok()),
                 "INT" => DataType::Int4(row[i].parse::<i32>().ok()),
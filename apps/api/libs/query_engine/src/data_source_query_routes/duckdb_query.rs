@@ -0,0 +1,292 @@
+use chrono::{DateTime, NaiveDate, NaiveTime};
+use indexmap::IndexMap;
+
+use anyhow::{anyhow, Error};
+use duckdb::types::{TimeUnit, Value};
+use duckdb::Connection;
+use serde_json::json;
+
+use crate::data_types::DataType;
+
+pub async fn duckdb_query(
+    connection: Connection,
+    query: String,
+    limit: Option<i64>,
+    exact_decimals: bool,
+) -> Result<Vec<IndexMap<std::string::String, DataType>>, Error> {
+    // Get the limit value, defaulting to 5000 if not specified
+    let default_limit = 5000;
+    let limit_value = limit.unwrap_or(default_limit) as usize;
+
+    // DuckDB runs in-process and its API is blocking, so keep it off the async runtime
+    match tokio::task::spawn_blocking(move || run_query(&connection, &query, limit_value, exact_decimals)).await {
+        Ok(result) => result,
+        Err(e) => {
+            tracing::error!("DuckDB query task failed: {}", e);
+            Err(anyhow!(e))
+        }
+    }
+}
+
+fn run_query(
+    connection: &Connection,
+    query: &str,
+    limit_value: usize,
+    exact_decimals: bool,
+) -> Result<Vec<IndexMap<String, DataType>>, Error> {
+    let mut stmt = connection.prepare(query)?;
+    let mut rows = stmt.query([])?;
+
+    // Column names are only available once the statement has been executed
+    let column_names = rows
+        .as_ref()
+        .map(|stmt| stmt.column_names())
+        .unwrap_or_default();
+
+    // Pre-allocate result vector with estimated capacity to reduce allocations
+    let mut result: Vec<IndexMap<String, DataType>> = Vec::with_capacity(limit_value.min(5000));
+
+    while let Some(row) = rows.next()? {
+        let mut row_map: IndexMap<String, DataType> = IndexMap::with_capacity(column_names.len());
+
+        for (i, column_name) in column_names.iter().enumerate() {
+            let value: Value = row.get(i)?;
+            row_map.insert(column_name.clone(), convert_value(value, exact_decimals));
+        }
+
+        result.push(row_map);
+
+        // Stop processing if we've reached the limit
+        if result.len() >= limit_value {
+            break;
+        }
+    }
+
+    Ok(result)
+}
+
+pub fn convert_value(value: Value, exact_decimals: bool) -> DataType {
+    match value {
+        Value::Null => DataType::Null,
+        Value::Boolean(v) => DataType::Bool(Some(v)),
+        Value::TinyInt(v) => DataType::Int2(Some(v as i16)),
+        Value::SmallInt(v) => DataType::Int2(Some(v)),
+        Value::Int(v) => DataType::Int4(Some(v)),
+        Value::BigInt(v) => DataType::Int8(Some(v)),
+        Value::UTinyInt(v) => DataType::Int2(Some(v as i16)),
+        Value::USmallInt(v) => DataType::Int4(Some(v as i32)),
+        Value::UInt(v) => DataType::Int8(Some(v as i64)),
+        Value::UBigInt(v) => DataType::wide_integer(Some(v.to_string()), exact_decimals),
+        Value::HugeInt(v) => DataType::wide_integer(Some(v.to_string()), exact_decimals),
+        Value::Float(v) => DataType::Float4(Some(v)),
+        Value::Double(v) => DataType::Float8(Some(v)),
+        Value::Decimal(v) => DataType::decimal(Some(v.to_string()), exact_decimals),
+        Value::Text(v) | Value::Enum(v) => DataType::Text(Some(v)),
+        Value::Blob(v) => DataType::Bytea(Some(v)),
+        Value::Date32(days) => DataType::Date(
+            NaiveDate::from_ymd_opt(1970, 1, 1)
+                .and_then(|epoch| epoch.checked_add_signed(chrono::Duration::days(days as i64))),
+        ),
+        Value::Timestamp(unit, v) => DataType::Timestamp(
+            DateTime::from_timestamp_micros(to_micros(unit, v)).map(|ts| ts.naive_utc()),
+        ),
+        Value::Time64(unit, v) => {
+            let micros = to_micros(unit, v);
+            DataType::Time(NaiveTime::from_num_seconds_from_midnight_opt(
+                (micros / 1_000_000) as u32,
+                ((micros % 1_000_000) * 1_000) as u32,
+            ))
+        }
+        other => DataType::Json(Some(value_to_json(other, exact_decimals))),
+    }
+}
+
+fn to_micros(unit: TimeUnit, value: i64) -> i64 {
+    match unit {
+        TimeUnit::Second => value * 1_000_000,
+        TimeUnit::Millisecond => value * 1_000,
+        TimeUnit::Microsecond => value,
+        TimeUnit::Nanosecond => value / 1_000,
+    }
+}
+
+/// Converts nested DuckDB values (lists, structs, maps, intervals) into JSON.
+fn value_to_json(value: Value, exact_decimals: bool) -> serde_json::Value {
+    match value {
+        Value::List(items) | Value::Array(items) => {
+            serde_json::Value::Array(
+                items
+                    .into_iter()
+                    .map(|item| value_to_json(item, exact_decimals))
+                    .collect(),
+            )
+        }
+        Value::Struct(fields) => serde_json::Value::Object(
+            fields
+                .iter()
+                .map(|(key, value)| (key.clone(), value_to_json(value.clone(), exact_decimals)))
+                .collect(),
+        ),
+        Value::Map(entries) => serde_json::Value::Object(
+            entries
+                .iter()
+                .map(|(key, value)| {
+                    (json_key(key.clone()), value_to_json(value.clone(), exact_decimals))
+                })
+                .collect(),
+        ),
+        Value::Union(inner) => value_to_json(*inner, exact_decimals),
+        Value::Interval { months, days, nanos } => json!({
+            "months": months,
+            "days": days,
+            "nanos": nanos,
+        }),
+        other => serde_json::to_value(convert_value(other, exact_decimals))
+            .unwrap_or(serde_json::Value::Null),
+    }
+}
+
+fn json_key(value: Value) -> String {
+    // Keys are text either way, so decimal keys keep their exact digits
+    match value_to_json(value, true) {
+        serde_json::Value::String(s) => s,
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::credentials::DuckDbCredentials;
+    use crate::data_source_connections::get_duckdb_connection::get_duckdb_connection;
+    use std::io::Write;
+
+    #[tokio::test]
+    async fn test_duckdb_query_in_memory() {
+        let connection = Connection::open_in_memory().expect("Failed to open DuckDB");
+
+        let results = duckdb_query(
+            connection,
+            "SELECT range AS num, 'row_' || range AS label, DATE '2024-01-01' + range::INT AS day FROM range(100)".to_string(),
+            Some(10),
+            false,
+        )
+        .await
+        .expect("Query should succeed");
+
+        assert_eq!(results.len(), 10, "Should return exactly 10 rows with limit 10");
+        assert_eq!(results[0].get("num"), Some(&DataType::Int8(Some(0))));
+        assert_eq!(results[1].get("label"), Some(&DataType::Text(Some("row_1".to_string()))));
+        assert_eq!(
+            results[2].get("day"),
+            Some(&DataType::Date(NaiveDate::from_ymd_opt(2024, 1, 3)))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_duckdb_query_reads_csv_file() {
+        let mut csv_file = tempfile::Builder::new()
+            .suffix(".csv")
+            .tempfile()
+            .expect("Failed to create temp file");
+        writeln!(csv_file, "region,revenue\nwest,10.5\neast,20.25").unwrap();
+
+        let connection = Connection::open_in_memory().expect("Failed to open DuckDB");
+        let query = format!(
+            "SELECT region, revenue FROM read_csv_auto('{}') ORDER BY region",
+            csv_file.path().display()
+        );
+
+        let results = duckdb_query(connection, query, None, false)
+            .await
+            .expect("Query should succeed");
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].get("region"), Some(&DataType::Text(Some("east".to_string()))));
+        assert_eq!(results[0].get("revenue"), Some(&DataType::Float8(Some(20.25))));
+    }
+
+    #[tokio::test]
+    async fn test_duckdb_query_keeps_decimals_exact() {
+        let connection = Connection::open_in_memory().expect("Failed to open DuckDB");
+
+        let results = duckdb_query(
+            connection,
+            "SELECT 12345678901234.5678::DECIMAL(18, 4) AS amount".to_string(),
+            None,
+            true,
+        )
+        .await
+        .expect("Query should succeed");
+
+        assert_eq!(
+            results[0].get("amount"),
+            Some(&DataType::Decimal(Some("12345678901234.5678".parse().unwrap())))
+        );
+
+        let connection = Connection::open_in_memory().expect("Failed to open DuckDB");
+        let results = duckdb_query(connection, "SELECT 20.25::DECIMAL(18, 4) AS amount".to_string(), None, false)
+            .await
+            .expect("Query should succeed");
+
+        assert_eq!(results[0].get("amount"), Some(&DataType::Float8(Some(20.25))));
+    }
+
+    #[tokio::test]
+    async fn test_duckdb_connection_only_reads_file_search_path() {
+        let root = tempfile::tempdir().expect("Failed to create temp dir");
+        std::fs::write(root.path().join("orders.csv"), "id\n1\n2\n").unwrap();
+        let outside = tempfile::Builder::new()
+            .suffix(".csv")
+            .tempfile()
+            .expect("Failed to create temp file");
+        std::fs::write(outside.path(), "id\n3\n").unwrap();
+
+        let credentials = DuckDbCredentials {
+            database_path: None,
+            file_search_path: Some(root.path().display().to_string()),
+            default_schema: None,
+        };
+
+        let connection = get_duckdb_connection(&credentials).await.unwrap();
+        let results = duckdb_query(connection, "SELECT id FROM read_csv_auto('orders.csv')".to_string(), None, false)
+            .await
+            .expect("Files under the search path should be readable");
+        assert_eq!(results.len(), 2);
+
+        let connection = get_duckdb_connection(&credentials).await.unwrap();
+        let query = format!("SELECT id FROM read_csv_auto('{}')", outside.path().display());
+        assert!(duckdb_query(connection, query, None, false).await.is_err());
+
+        let connection = get_duckdb_connection(&credentials).await.unwrap();
+        let query = "SET enable_external_access = true".to_string();
+        assert!(duckdb_query(connection, query, None, false).await.is_err());
+    }
+
+    #[test]
+    fn test_wide_integers_stay_exact() {
+        assert_eq!(convert_value(Value::UBigInt(42), false), DataType::Int8(Some(42)));
+        assert_eq!(
+            convert_value(Value::UBigInt(u64::MAX), false),
+            DataType::Decimal(Some("18446744073709551615".parse().unwrap()))
+        );
+        assert_eq!(
+            convert_value(Value::HugeInt(i128::MIN), false),
+            DataType::Text(Some(i128::MIN.to_string()))
+        );
+        assert_eq!(
+            convert_value(Value::HugeInt(42), true),
+            DataType::Decimal(Some("42".parse().unwrap()))
+        );
+    }
+
+    #[test]
+    fn test_nested_values_convert_to_json() {
+        let value = Value::List(vec![Value::Int(1), Value::Null, Value::Text("a".to_string())]);
+
+        match convert_value(value, false) {
+            DataType::Json(Some(json)) => assert_eq!(json, json!([1, null, "a"])),
+            other => panic!("Expected Json, got {:?}", other),
+        }
+    }
+}
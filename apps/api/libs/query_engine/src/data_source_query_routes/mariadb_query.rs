@@ -0,0 +1,170 @@
+use chrono::Utc;
+use indexmap::IndexMap;
+
+use anyhow::Error;
+use futures::TryStreamExt;
+use sqlx::{Column, MySql, Pool, Row};
+
+use crate::data_types::DataType;
+
+pub async fn mariadb_query(
+    pool: Pool<MySql>,
+    query: String,
+    limit: Option<i64>,
+    exact_decimals: bool,
+) -> Result<Vec<IndexMap<std::string::String, DataType>>, Error> {
+    // Get the limit value, defaulting to 5000 if not specified
+    let default_limit = 5000;
+    let limit_value = limit.unwrap_or(default_limit) as usize;
+
+    // Create query stream without appending LIMIT
+    let mut stream = sqlx::query(&query).fetch(&pool);
+
+    // Pre-allocate result vector with estimated capacity to reduce allocations
+    let mut result: Vec<IndexMap<String, DataType>> = Vec::with_capacity(limit_value);
+
+    // Process all rows without spawning tasks per row
+    while let Some(row) = stream.try_next().await? {
+        let mut row_map: IndexMap<String, DataType> = IndexMap::with_capacity(row.len());
+
+        for (i, column) in row.columns().iter().enumerate() {
+            let column_name = column.name();
+            let type_info = column.type_info().clone().to_string();
+
+            let column_value = match type_info.as_str() {
+                "BOOL" | "BOOLEAN" => DataType::Bool(row.try_get::<bool, _>(i).ok()),
+                "BIT" => DataType::Bytea(row.try_get::<Vec<u8>, _>(i).ok()),
+                "TINYINT" => DataType::Int2(row.try_get::<i8, _>(i).ok().map(i16::from)),
+                "TINYINT UNSIGNED" => DataType::Int2(row.try_get::<u8, _>(i).ok().map(i16::from)),
+                "SMALLINT" => DataType::Int2(row.try_get::<i16, _>(i).ok()),
+                "SMALLINT UNSIGNED" | "YEAR" => {
+                    DataType::Int4(row.try_get::<u16, _>(i).ok().map(i32::from))
+                }
+                "MEDIUMINT" | "INT" | "INTEGER" => DataType::Int4(row.try_get::<i32, _>(i).ok()),
+                "MEDIUMINT UNSIGNED" | "INT UNSIGNED" => {
+                    DataType::Int8(row.try_get::<u32, _>(i).ok().map(i64::from))
+                }
+                "BIGINT" => DataType::Int8(row.try_get::<i64, _>(i).ok()),
+                "BIGINT UNSIGNED" => DataType::wide_integer(
+                    row.try_get::<u64, _>(i).ok().map(|v| v.to_string()),
+                    exact_decimals,
+                ),
+                "FLOAT" => DataType::Float4(row.try_get::<f32, _>(i).ok()),
+                "DOUBLE" => DataType::Float8(row.try_get::<f64, _>(i).ok()),
+                "DECIMAL" | "DEC" => DataType::decimal(
+                    row.try_get::<sqlx::types::BigDecimal, _>(i).ok().map(|v| v.to_string()),
+                    exact_decimals,
+                ),
+                name if is_mariadb_text_type(name) => {
+                    parse_mariadb_text(name, row.try_get::<String, _>(i).ok())
+                }
+                "BINARY" | "VARBINARY" | "TINYBLOB" | "BLOB" | "MEDIUMBLOB" | "LONGBLOB" => {
+                    DataType::Bytea(row.try_get::<Vec<u8>, _>(i).ok())
+                }
+                "TIMESTAMP" => {
+                    DataType::Timestamptz(row.try_get::<chrono::DateTime<Utc>, _>(i).ok())
+                }
+                "DATETIME" => DataType::Timestamp(row.try_get::<chrono::NaiveDateTime, _>(i).ok()),
+                "DATE" => DataType::Date(row.try_get::<chrono::NaiveDate, _>(i).ok()),
+                "TIME" => DataType::Time(row.try_get::<chrono::NaiveTime, _>(i).ok()),
+                "JSON" => DataType::Json(row.try_get::<serde_json::Value, _>(i).ok()),
+                _ => DataType::Unknown(row.try_get::<String, _>(i).ok()),
+            };
+
+            row_map.insert(column_name.to_string(), column_value);
+        }
+
+        result.push(row_map);
+
+        // Stop processing if we've reached the limit
+        if result.len() >= limit_value {
+            break;
+        }
+    }
+
+    Ok(result)
+}
+
+/// Whether sqlx reports the column as one MariaDB sends as text. Character columns are typed by
+/// their column type, never by what a value looks like, so every row of a column gets the same
+/// type.
+///
+/// sqlx names columns after their wire type, so MariaDB's `UUID` and `INET4`/`INET6` columns
+/// arrive as `CHAR` and stay text, and `JSON` columns arrive as `LONGTEXT`.
+pub fn is_mariadb_text_type(type_name: &str) -> bool {
+    matches!(
+        type_name,
+        "CHAR" | "VARCHAR" | "TINYTEXT" | "TEXT" | "MEDIUMTEXT" | "LONGTEXT" | "ENUM" | "SET"
+    )
+}
+
+/// Converts a value of a text column. MariaDB's `JSON` type is a `LONGTEXT` with a
+/// `JSON_VALID` check, and sqlx can't tell the two apart, so `LONGTEXT` objects and arrays
+/// are read as JSON. Everything else stays text.
+pub fn parse_mariadb_text(type_name: &str, value: Option<String>) -> DataType {
+    let Some(text) = value else {
+        return DataType::Text(None);
+    };
+
+    let looks_like_json = matches!(text.trim_start().chars().next(), Some('{') | Some('['));
+    if type_name == "LONGTEXT" && looks_like_json {
+        if let Ok(json @ (serde_json::Value::Object(_) | serde_json::Value::Array(_))) =
+            serde_json::from_str::<serde_json::Value>(&text)
+        {
+            return DataType::Json(Some(json));
+        }
+    }
+
+    DataType::Text(Some(text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_longtext_json_values() {
+        let object = r#"{"plan": "pro", "seats": [1, 2]}"#.to_string();
+        assert_eq!(
+            parse_mariadb_text("LONGTEXT", Some(object.clone())),
+            DataType::Json(Some(serde_json::json!({"plan": "pro", "seats": [1, 2]})))
+        );
+        assert_eq!(
+            parse_mariadb_text("LONGTEXT", Some(" [1, 2]".to_string())),
+            DataType::Json(Some(serde_json::json!([1, 2])))
+        );
+
+        // Only LONGTEXT can be a JSON column, and other values stay text
+        assert_eq!(parse_mariadb_text("VARCHAR", Some(object.clone())), DataType::Text(Some(object)));
+        for text in ["plain notes", "[not json", "42", r#""quoted""#] {
+            assert_eq!(
+                parse_mariadb_text("LONGTEXT", Some(text.to_string())),
+                DataType::Text(Some(text.to_string()))
+            );
+        }
+        assert_eq!(parse_mariadb_text("LONGTEXT", None), DataType::Text(None));
+    }
+
+    #[test]
+    fn test_bigint_unsigned_stays_exact() {
+        assert_eq!(DataType::wide_integer(Some(42.to_string()), false), DataType::Int8(Some(42)));
+        assert_eq!(
+            DataType::wide_integer(Some(u64::MAX.to_string()), false),
+            DataType::Decimal(Some("18446744073709551615".parse().unwrap()))
+        );
+        assert_eq!(DataType::wide_integer(None, false), DataType::Int8(None));
+    }
+
+    #[test]
+    fn test_mariadb_text_types() {
+        // The names sqlx reports for UUID, INET6 and JSON columns
+        for type_name in ["CHAR", "VARCHAR", "LONGTEXT"] {
+            assert!(is_mariadb_text_type(type_name), "{} should be text", type_name);
+        }
+
+        // MariaDB never reports these names, and binary columns are bytes
+        for type_name in ["UUID", "INET6", "BINARY", "VARBINARY", "BLOB"] {
+            assert!(!is_mariadb_text_type(type_name), "{} should not be text", type_name);
+        }
+    }
+}
@@ -1,5 +1,8 @@
 pub mod bigquery_query;
+pub mod clickhouse_query;
 pub mod databricks_query;
+pub mod duckdb_query;
+pub mod mariadb_query;
 pub mod mysql_query;
 pub mod postgres_query;
 pub mod query_engine;
@@ -11,6 +11,7 @@ pub async fn mysql_query(
     pool: Pool<MySql>,
     query: String,
     limit: Option<i64>,
+    exact_decimals: bool,
 ) -> Result<Vec<IndexMap<std::string::String, DataType>>, Error> {
     // Get the limit value, defaulting to 5000 if not specified
     let default_limit = 5000;
@@ -40,7 +41,10 @@ pub async fn mysql_query(
                 "TEXT" | "VARCHAR" => DataType::Text(row.try_get::<String, _>(i).ok()),
                 "FLOAT" => DataType::Float4(row.try_get::<f32, _>(i).ok()),
                 "DOUBLE" => DataType::Float8(row.try_get::<f64, _>(i).ok()),
-                "DECIMAL" | "DEC" => DataType::Float8(row.try_get::<f64, _>(i).ok()),
+                "DECIMAL" | "DEC" => DataType::decimal(
+                    row.try_get::<sqlx::types::BigDecimal, _>(i).ok().map(|v| v.to_string()),
+                    exact_decimals,
+                ),
                 "UUID" => DataType::Uuid(row.try_get::<uuid::Uuid, _>(i).ok()),
                 "TIMESTAMP" | "DATETIME" => DataType::Timestamp(row.try_get::<chrono::NaiveDateTime, _>(i).ok()),
                 "DATE" => DataType::Date(row.try_get::<chrono::NaiveDate, _>(i).ok()),
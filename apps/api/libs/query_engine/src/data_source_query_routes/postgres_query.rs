@@ -57,6 +57,7 @@ pub async fn postgres_query(
     pg_pool: Pool<Postgres>,
     query: String,
     limit: Option<i64>,
+    exact_decimals: bool,
 ) -> Result<Vec<IndexMap<std::string::String, DataType>>, Error> {
     // Parse the query and quote identifiers
     let dialect = PostgreSqlDialect {};
@@ -96,11 +97,10 @@ pub async fn postgres_query(
                 "TEXT" | "VARCHAR" | "USER-DEFINED" => DataType::Text(row.try_get::<String, _>(i).ok()),
                 "FLOAT4" => DataType::Float4(row.try_get::<f32, _>(i).ok()),
                 "FLOAT8" => DataType::Float8(row.try_get::<f64, _>(i).ok()),
-                "NUMERIC" => {
-                    DataType::Float8(row.try_get(i).ok().and_then(
-                        |v: sqlx::types::BigDecimal| v.to_string().parse::<f64>().ok(),
-                    ))
-                }
+                "NUMERIC" => DataType::decimal(
+                    row.try_get::<sqlx::types::BigDecimal, _>(i).ok().map(|v| v.to_string()),
+                    exact_decimals,
+                ),
                 "UUID" => DataType::Uuid(row.try_get::<uuid::Uuid, _>(i).ok()),
                 "TIMESTAMP" => {
                     DataType::Timestamp(row.try_get::<chrono::NaiveDateTime, _>(i).ok())
@@ -1,5 +1,5 @@
 use indexmap::IndexMap;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use anyhow::{anyhow, Result};
 use uuid::Uuid;
@@ -7,28 +7,28 @@ use uuid::Uuid;
 use crate::{
     credentials::Credential,
     data_source_connections::{
-        get_bigquery_client::get_bigquery_client, get_databricks_client::get_databricks_client,
-        get_mysql_connection::get_mysql_connection,
+        get_bigquery_client::get_bigquery_client, get_clickhouse_client::get_clickhouse_client,
+        get_databricks_client::get_databricks_client,
+        get_duckdb_connection::get_duckdb_connection,
+        get_mariadb_connection::get_mariadb_connection, get_mysql_connection::get_mysql_connection,
         get_postgres_connection::get_postgres_connection,
         get_redshift_connection::get_redshift_connection,
         get_snowflake_client::get_snowflake_client,
         get_sql_server_connection::get_sql_server_connection, ssh_tunneling::kill_ssh_tunnel,
     },
     data_types::DataType,
+    pagination::get_data_source_type,
 };
 
 use database::types::data_metadata::{ColumnMetaData, ColumnType, DataMetadata, SimpleType};
+use dataset_security::{get_column_masks, get_row_level_filters};
+use sql_analyzer::{apply_column_masks, apply_row_level_filters, TableMask};
 use database::vault::read_secret;
-use database::{
-    enums::DataSourceType,
-    pool::get_pg_pool,
-    schema::data_sources,
-};
-use diesel::prelude::*;
-use diesel_async::RunQueryDsl;
 
 use super::{
-    bigquery_query::bigquery_query, databricks_query::databricks_query, mysql_query::mysql_query,
+    bigquery_query::bigquery_query, clickhouse_query::clickhouse_query,
+    databricks_query::databricks_query,
+    duckdb_query::duckdb_query, mariadb_query::mariadb_query, mysql_query::mysql_query,
     postgres_query::postgres_query, redshift_query::redshift_query,
     security_utils::query_safety_filter_with_dialect, snowflake_query::{snowflake_query, ProcessingResult},
     sql_server_query::sql_server_query,
@@ -45,19 +45,32 @@ pub async fn query_engine(
     data_source_id: &Uuid,
     sql: &str,
     limit: Option<i64>,
+) -> Result<QueryResult> {
+    run_query_engine(data_source_id, sql, limit, false).await
+}
+
+/// Like [`query_engine`], but decimals come back exactly instead of as floats: as a
+/// `Decimal` when they fit one and as text when they're wider. Integer columns that can
+/// outgrow an `i64` come back as exact decimals throughout, so each column keeps one type
+/// across pages. Meant for exports, where the data endpoints' float values would lose
+/// precision.
+pub async fn exact_query_engine(
+    data_source_id: &Uuid,
+    sql: &str,
+    limit: Option<i64>,
+) -> Result<QueryResult> {
+    run_query_engine(data_source_id, sql, limit, true).await
+}
+
+async fn run_query_engine(
+    data_source_id: &Uuid,
+    sql: &str,
+    limit: Option<i64>,
+    exact_decimals: bool,
 ) -> Result<QueryResult> {
     let corrected_sql = sql.to_owned();
 
-    // Fetch the data source type from the database
-    let mut conn = get_pg_pool().get().await
-        .map_err(|e| anyhow!("Failed to get database connection: {}", e))?;
-    
-    let data_source_type = data_sources::table
-        .filter(data_sources::id.eq(data_source_id))
-        .select(data_sources::type_)
-        .first::<DataSourceType>(&mut conn)
-        .await
-        .map_err(|e| anyhow!("Failed to fetch data source type: {}", e))?;
+    let data_source_type = get_data_source_type(data_source_id).await?;
     
     let data_source_dialect = data_source_type.to_str();
 
@@ -68,7 +81,7 @@ pub async fn query_engine(
         return Err(anyhow!(warning)) 
     };
 
-    let results = match route_to_query(data_source_id, &secure_sql, limit).await {
+    let results = match route_to_query(data_source_id, &secure_sql, limit, exact_decimals).await {
         Ok(results) => results,
         Err(e) => {
             tracing::error!(
@@ -89,6 +102,96 @@ pub async fn query_engine(
     })
 }
 
+/// Runs a query on behalf of a user, enforcing the row-level security filters and column
+/// masking policies defined on the datasets it reads. Use this instead of [`query_engine`]
+/// for any user-initiated query.
+pub async fn query_engine_for_user(
+    user_id: &Uuid,
+    data_source_id: &Uuid,
+    sql: &str,
+    limit: Option<i64>,
+) -> Result<QueryResult> {
+    let secured_sql = secure_sql_for_user(user_id, data_source_id, sql).await?;
+
+    query_engine(data_source_id, &secured_sql, limit).await
+}
+
+/// Returns `sql` rewritten with the row-level filters and column masks that apply to the user.
+///
+/// Row filters always read the unmasked table: a table that is both filtered and masked gets
+/// its filter inside the masked projection, and only the other filtered tables are rewritten
+/// into filtered derived tables.
+pub async fn secure_sql_for_user(user_id: &Uuid, data_source_id: &Uuid, sql: &str) -> Result<String> {
+    let mut table_filters = get_row_level_filters(user_id, data_source_id)
+        .await
+        .map_err(|e| anyhow!("Failed to resolve row-level security filters: {}", e))?;
+    let mut table_masks = get_column_masks(user_id, data_source_id)
+        .await
+        .map_err(|e| anyhow!("Failed to resolve column masking policies: {}", e))?;
+    table_masks.retain(|_, table_mask| !table_mask.masks.is_empty());
+
+    for (table, table_mask) in table_masks.iter_mut() {
+        table_mask.row_filter = table_filters.remove(table);
+    }
+
+    let filtered_sql =
+        apply_user_row_level_filters(user_id, data_source_id, sql, table_filters).await?;
+    apply_user_column_masks(user_id, data_source_id, &filtered_sql, table_masks).await
+}
+
+/// Rewrites `sql` so every table in `table_filters` is read through a filtered derived table.
+/// Returns the query unchanged when there are no filters, and fails when there are but the
+/// query can't be parsed or no tables can be found in it.
+async fn apply_user_row_level_filters(
+    user_id: &Uuid,
+    data_source_id: &Uuid,
+    sql: &str,
+    table_filters: HashMap<String, String>,
+) -> Result<String> {
+    if table_filters.is_empty() {
+        return Ok(sql.to_string());
+    }
+
+    tracing::debug!(
+        "Applying row-level security filters for user {} on tables {:?}",
+        user_id,
+        table_filters.keys().collect::<Vec<_>>()
+    );
+
+    let data_source_type = get_data_source_type(data_source_id).await?;
+
+    apply_row_level_filters(sql.to_string(), data_source_type.to_str(), table_filters)
+        .await
+        .map_err(|e| anyhow!("Failed to apply row-level security filters: {}", e))
+}
+
+/// Rewrites `sql` so every table in `table_masks` is read through a projection that hides,
+/// hashes, redacts or partially masks its columns. Returns the query unchanged when there
+/// are no masks.
+async fn apply_user_column_masks(
+    user_id: &Uuid,
+    data_source_id: &Uuid,
+    sql: &str,
+    table_masks: HashMap<String, TableMask>,
+) -> Result<String> {
+    if table_masks.is_empty() {
+        return Ok(sql.to_string());
+    }
+
+    tracing::debug!(
+        "Applying column masks for user {} on tables {:?}",
+        user_id,
+        table_masks.keys().collect::<Vec<_>>()
+    );
+
+    let data_source_type = get_data_source_type(data_source_id).await?;
+    let hash_salt = std::env::var("COLUMN_MASK_HASH_SALT").ok().filter(|salt| !salt.is_empty());
+
+    apply_column_masks(sql.to_string(), data_source_type.to_str(), table_masks, hash_salt)
+        .await
+        .map_err(|e| anyhow!("Failed to apply column masking policies: {}", e))
+}
+
 // Consolidated metadata calculation function
 fn compute_data_metadata(data: &[IndexMap<String, DataType>]) -> DataMetadata {
     if data.is_empty() {
@@ -292,6 +395,7 @@ mod tests {
             pool.clone(),
             "SELECT generate_series(1, 100) AS num".to_string(),
             Some(10),
+            false,
         )
         .await
         .expect("Query should succeed");
@@ -303,6 +407,7 @@ mod tests {
             pool.clone(),
             "SELECT generate_series(1, 6000) AS num".to_string(),
             None,
+            false,
         )
         .await
         .expect("Query should succeed");
@@ -314,6 +419,7 @@ mod tests {
             pool,
             "SELECT generate_series(1, 6000) AS num".to_string(),
             Some(6000),
+            false,
         )
         .await
         .expect("Query should succeed");
@@ -345,6 +451,7 @@ mod tests {
             pool.clone(),
             "SELECT * FROM (SELECT 1 AS num UNION SELECT 2 UNION SELECT 3 UNION SELECT 4 UNION SELECT 5 UNION SELECT 6 UNION SELECT 7 UNION SELECT 8 UNION SELECT 9 UNION SELECT 10) AS t".to_string(),
             Some(5),
+            false,
         )
         .await
         .expect("Query should succeed");
@@ -387,6 +494,7 @@ async fn route_to_query(
     data_source_id: &Uuid,
     sql: &str,
     limit: Option<i64>,
+    exact_decimals: bool,
 ) -> Result<Vec<IndexMap<String, DataType>>> {
     let credentials_string = match read_secret(data_source_id).await {
         Ok(credentials) => credentials,
@@ -410,7 +518,7 @@ async fn route_to_query(
                 }
             };
 
-            let results = match postgres_query(pg_pool, sql.to_owned(), limit).await {
+            let results = match postgres_query(pg_pool, sql.to_owned(), limit, exact_decimals).await {
                 Ok(results) => results,
                 Err(e) => {
                     return Err(anyhow!(e));
@@ -428,7 +536,7 @@ async fn route_to_query(
 
             
 
-            match redshift_query(redshift_client, sql.to_owned(), limit).await {
+            match redshift_query(redshift_client, sql.to_owned(), limit, exact_decimals).await {
                 Ok(results) => results,
                 Err(e) => {
                     tracing::error!("There was an issue while fetching the tables: {}", e);
@@ -447,7 +555,7 @@ async fn route_to_query(
                 }
             };
 
-            let results = match mysql_query(mysql_pool, sql.to_owned(), limit).await {
+            let results = match mysql_query(mysql_pool, sql.to_owned(), limit, exact_decimals).await {
                 Ok(results) => results,
                 Err(e) => {
                     tracing::error!("There was an issue while fetching the tables: {}", e);
@@ -461,6 +569,35 @@ async fn route_to_query(
 
             results
         }
+        Credential::Mariadb(credentials) => {
+            let (mariadb_pool, ssh_tunnel, temp_files) = match get_mariadb_connection(&credentials)
+                .await
+            {
+                Ok(mariadb_pool) => mariadb_pool,
+                Err(e) => {
+                    tracing::error!("There was an issue while establishing a connection to the parent data source: {}", e);
+                    return Err(anyhow!(e));
+                }
+            };
+
+            let results = match mariadb_query(mariadb_pool, sql.to_owned(), limit, exact_decimals).await {
+                Ok(results) => results,
+                Err(e) => {
+                    tracing::error!("There was an issue while fetching the tables: {}", e);
+                    // Tear down the tunnel before bailing so the ssh process doesn't leak
+                    if let (Some(mut ssh_tunnel), Some(temp_files)) = (ssh_tunnel, temp_files) {
+                        let _ = kill_ssh_tunnel(&mut ssh_tunnel, temp_files).await;
+                    };
+                    return Err(anyhow!(e));
+                }
+            };
+
+            if let (Some(mut ssh_tunnel), Some(temp_files)) = (ssh_tunnel, temp_files) {
+                let _ = kill_ssh_tunnel(&mut ssh_tunnel, temp_files).await;
+            };
+
+            results
+        }
         Credential::Bigquery(credentials) => {
             let (bq_client, project_id) = match get_bigquery_client(&credentials).await {
                 Ok((bq_client, project_id)) => (bq_client, project_id),
@@ -518,7 +655,7 @@ async fn route_to_query(
 
             
 
-            match databricks_query(databricks_client, sql.to_owned(), limit).await {
+            match databricks_query(databricks_client, sql.to_owned(), limit, exact_decimals).await {
                 Ok(results) => results,
                 Err(e) => {
                     tracing::error!("There was an issue while fetching the tables: {}", e);
@@ -554,6 +691,40 @@ async fn route_to_query(
                 }
             }
         }
+        Credential::DuckDb(credentials) => {
+            let duckdb_connection = match get_duckdb_connection(&credentials).await {
+                Ok(duckdb_connection) => duckdb_connection,
+                Err(e) => {
+                    tracing::error!("There was an issue while establishing a connection to the parent data source: {}", e);
+                    return Err(anyhow!(e));
+                }
+            };
+
+            match duckdb_query(duckdb_connection, sql.to_owned(), limit, exact_decimals).await {
+                Ok(results) => results,
+                Err(e) => {
+                    tracing::error!("There was an issue while fetching the tables: {}", e);
+                    return Err(anyhow!(e));
+                }
+            }
+        }
+        Credential::ClickHouse(credentials) => {
+            let clickhouse_client = match get_clickhouse_client(&credentials).await {
+                Ok(clickhouse_client) => clickhouse_client,
+                Err(e) => {
+                    tracing::error!("There was an issue while establishing a connection to the parent data source: {}", e);
+                    return Err(anyhow!(e));
+                }
+            };
+
+            match clickhouse_query(clickhouse_client, sql.to_owned(), limit, exact_decimals).await {
+                Ok(results) => results,
+                Err(e) => {
+                    tracing::error!("There was an issue while fetching the tables: {}", e);
+                    return Err(anyhow!(e));
+                }
+            }
+        }
     };
 
     Ok(results)
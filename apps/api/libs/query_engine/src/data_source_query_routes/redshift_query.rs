@@ -4,7 +4,6 @@ use indexmap::IndexMap;
 
 use anyhow::{Error, Result};
 use sqlx::{types::BigDecimal, Column, Pool, Postgres, Row};
-use num_traits::cast::ToPrimitive;
 
 use crate::data_types::DataType;
 
@@ -12,6 +11,7 @@ pub async fn redshift_query(
     pg_pool: Pool<Postgres>,
     query: String,
     limit: Option<i64>,
+    exact_decimals: bool,
 ) -> Result<Vec<IndexMap<std::string::String, DataType>>, Error> {
     // Get the limit value, defaulting to 5000 if not specified
     let default_limit = 5000;
@@ -41,12 +41,12 @@ pub async fn redshift_query(
                 "TEXT" | "VARCHAR" | "CHARACTER VARYING" => DataType::Text(row.try_get::<Option<String>, _>(i).unwrap_or(None)),
                 "FLOAT4" => DataType::Float4(row.try_get::<Option<f32>, _>(i).unwrap_or(None)),
                 "FLOAT8" => DataType::Float8(row.try_get::<Option<f64>, _>(i).unwrap_or(None)),
-                "NUMERIC" => {
-                    match row.try_get::<Option<BigDecimal>, _>(i).unwrap_or(None) {
-                        Some(value) => DataType::Float8(value.to_f64()),
-                        None => DataType::Float8(None),
-                    }
-                }
+                "NUMERIC" => DataType::decimal(
+                    row.try_get::<Option<BigDecimal>, _>(i)
+                        .unwrap_or(None)
+                        .map(|value| value.to_string()),
+                    exact_decimals,
+                ),
                 "UUID" => DataType::Uuid(row.try_get::<Option<uuid::Uuid>, _>(i).unwrap_or(None)),
                 "TIMESTAMP" => DataType::Timestamp(row.try_get::<Option<chrono::NaiveDateTime>, _>(i).unwrap_or(None)),
                 "DATE" => DataType::Date(row.try_get::<Option<chrono::NaiveDate>, _>(i).unwrap_or(None)),
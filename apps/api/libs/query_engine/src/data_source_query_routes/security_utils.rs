@@ -1,7 +1,7 @@
 use sqlparser::dialect::{
     GenericDialect, SnowflakeDialect, PostgreSqlDialect, MySqlDialect, 
     BigQueryDialect, MsSqlDialect, DatabricksDialect, SQLiteDialect,
-    AnsiDialect, Dialect
+    DuckDbDialect, ClickHouseDialect, AnsiDialect, Dialect
 };
 use sqlparser::parser::Parser;
 use sqlparser::ast::{Statement, SetExpr, Query};
@@ -16,6 +16,8 @@ fn get_dialect(data_source_type: &str) -> Box<dyn Dialect> {
         "snowflake" => Box::new(SnowflakeDialect {}),
         "sqlserver" | "mssql" => Box::new(MsSqlDialect {}),
         "sqlite" => Box::new(SQLiteDialect {}),
+        "duckdb" => Box::new(DuckDbDialect {}),
+        "clickhouse" => Box::new(ClickHouseDialect {}),
         "ansi" => Box::new(AnsiDialect {}),
         _ => Box::new(GenericDialect {}),
     }
@@ -239,6 +241,21 @@ mod tests {
         assert!(result.is_none(), "Safe UNION query was rejected: {:?}", result);
     }
 
+    #[tokio::test]
+    async fn test_clickhouse_query_with_dialect_parameter() {
+        let query = "SELECT toStartOfMonth(event_time) AS month, count() AS events
+                    FROM analytics.events
+                    WHERE event_type IN ('click', 'view')
+                    GROUP BY month
+                    ORDER BY month";
+
+        let result = query_safety_filter_with_dialect(query.to_string(), "clickhouse").await;
+        assert!(result.is_none(), "ClickHouse query should be accepted with ClickHouse dialect: {:?}", result);
+
+        let result = query_safety_filter_with_dialect("DROP TABLE analytics.events".to_string(), "clickhouse").await;
+        assert!(result.is_some(), "DROP should be rejected with ClickHouse dialect");
+    }
+
     #[tokio::test]
     async fn test_snowflake_complex_case_expression() {
         // This is the exact query that fails in production
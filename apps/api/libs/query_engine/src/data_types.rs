@@ -81,6 +81,40 @@ impl fmt::Display for DataType {
 
 impl DataType {
 
+    /// A decimal parsed from its text, kept as that text when it doesn't fit a `Decimal`
+    /// exactly (more than 28 digits would be rounded or rejected)
+    pub fn exact_decimal(text: String) -> DataType {
+        match text.parse::<Decimal>() {
+            Ok(decimal) if decimal.to_string() == text => DataType::Decimal(Some(decimal)),
+            _ => DataType::Text(Some(text)),
+        }
+    }
+
+    /// A decimal column's value: exact (see `exact_decimal`) when `exact`, otherwise the
+    /// nearest float, which is what the data endpoints return
+    pub fn decimal(text: Option<String>, exact: bool) -> DataType {
+        match (text, exact) {
+            (Some(text), true) => DataType::exact_decimal(text),
+            (None, true) => DataType::Decimal(None),
+            (text, false) => DataType::Float8(text.and_then(|text| text.parse::<f64>().ok())),
+        }
+    }
+
+    /// A value of an integer type that can outgrow an `i64`. Values that fit are `Int8` and
+    /// wider ones keep their digits as an exact decimal. With `exact`, every value is an exact
+    /// decimal, so the column keeps one type however wide its later values get.
+    pub fn wide_integer(text: Option<String>, exact: bool) -> DataType {
+        match (text, exact) {
+            (Some(text), true) => DataType::exact_decimal(text),
+            (None, true) => DataType::Decimal(None),
+            (Some(text), false) => match text.parse::<i64>() {
+                Ok(v) => DataType::Int8(Some(v)),
+                Err(_) => DataType::exact_decimal(text),
+            },
+            (None, false) => DataType::Int8(None),
+        }
+    }
+
     pub fn simple_type(&self) -> Option<String> {
         match self {
             DataType::Bool(_) => Some("boolean".to_string()),
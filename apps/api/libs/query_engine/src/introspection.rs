@@ -0,0 +1,679 @@
+//! Reads the catalog of a data source's warehouse: the databases it can see and the tables,
+//! views and columns of its default database, with their types, nullability and comments.
+//!
+//! Every dialect is read through its `information_schema` (or the closest equivalent) using
+//! plain SELECTs, so the catalog queries go through `query_engine` like any other query.
+
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, Result};
+use database::{enums::DataSourceType, vault::read_secret};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::credentials::Credential;
+use crate::data_source_query_routes::query_engine::query_engine;
+use crate::data_types::DataType;
+
+/// Most catalog rows (one per column) read in a single introspection
+pub const CATALOG_ROW_LIMIT: i64 = 250_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CatalogTableKind {
+    Table,
+    View,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CatalogColumn {
+    pub name: String,
+    pub data_type: String,
+    pub nullable: bool,
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CatalogTable {
+    pub database: String,
+    pub schema: String,
+    pub name: String,
+    pub kind: CatalogTableKind,
+    pub comment: Option<String>,
+    pub columns: Vec<CatalogColumn>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Catalog {
+    /// The database whose tables were read
+    pub database: String,
+    /// Schemas the introspection was limited to, or `None` for every schema
+    pub schemas: Option<Vec<String>>,
+    /// Every database the data source's credentials can see
+    pub databases: Vec<String>,
+    pub tables: Vec<CatalogTable>,
+    /// Set when the catalog hit `CATALOG_ROW_LIMIT` and is missing tables
+    pub truncated: bool,
+}
+
+impl Catalog {
+    /// Whether the catalog is complete for `schema`, i.e. a table missing from it is gone
+    pub fn covers_schema(&self, schema: &str) -> bool {
+        match &self.schemas {
+            Some(schemas) => schemas.iter().any(|s| s.eq_ignore_ascii_case(schema)),
+            None => self
+                .tables
+                .iter()
+                .any(|table| table.schema.eq_ignore_ascii_case(schema)),
+        }
+    }
+
+    pub fn find_table(&self, schema: &str, name: &str) -> Option<&CatalogTable> {
+        self.tables.iter().find(|table| {
+            table.schema.eq_ignore_ascii_case(schema) && table.name.eq_ignore_ascii_case(name)
+        })
+    }
+}
+
+/// Introspects the default database of a data source, optionally limited to `schemas`.
+/// Without `schemas`, BigQuery reads its default dataset and MySQL/MariaDB their default
+/// database; the other dialects read every schema.
+pub async fn introspect_data_source(data_source_id: &Uuid, schemas: &[String]) -> Result<Catalog> {
+    let credentials_string = read_secret(data_source_id)
+        .await
+        .map_err(|e| anyhow!("Failed to read data source credentials: {}", e))?;
+    let credential: Credential = serde_json::from_str(&credentials_string)
+        .map_err(|e| anyhow!("Failed to parse data source credentials: {}", e))?;
+
+    let databases = match databases_sql(&credential)? {
+        Some(sql) => {
+            let result = query_engine(data_source_id, &sql, Some(CATALOG_ROW_LIMIT)).await?;
+            let mut databases: Vec<String> = result
+                .data
+                .iter()
+                .filter_map(|row| text_value(row, "database_name"))
+                .collect();
+            databases.sort();
+            databases.dedup();
+            databases
+        }
+        None => vec![default_database(&credential)?.to_string()],
+    };
+
+    let sql = columns_sql(&credential, schemas)?;
+    let result = match query_engine(data_source_id, &sql, Some(CATALOG_ROW_LIMIT)).await {
+        Ok(result) => result,
+        Err(e) => {
+            tracing::error!(
+                "Failed to read the catalog of data source {}: {}",
+                data_source_id,
+                e
+            );
+            return Err(anyhow!("Failed to read the warehouse catalog: {}", e));
+        }
+    };
+
+    let truncated = result.data.len() as i64 >= CATALOG_ROW_LIMIT;
+    if truncated {
+        tracing::warn!(
+            "Catalog of data source {} hit the {} row limit and is incomplete",
+            data_source_id,
+            CATALOG_ROW_LIMIT
+        );
+    }
+
+    Ok(Catalog {
+        database: default_database(&credential)?.to_string(),
+        schemas: (!schemas.is_empty()).then(|| schemas.to_vec()),
+        databases,
+        tables: catalog_tables(&result.data),
+        truncated,
+    })
+}
+
+/// Whether two column types are the same once sizes and dialect spellings are set aside,
+/// e.g. `character varying(255)` and `VARCHAR`.
+pub fn same_column_type(a: &str, b: &str) -> bool {
+    canonical_type(a) == canonical_type(b)
+}
+
+/// Whether columns of these types can be joined on without an explicit cast
+pub fn join_compatible_types(a: &str, b: &str) -> bool {
+    let family = |data_type: &str| match canonical_type(data_type).as_str() {
+        "integer" | "bigint" | "smallint" | "numeric" | "double" | "real" => "number".to_string(),
+        "timestamp" | "timestamptz" | "date" => "time".to_string(),
+        other => other.to_string(),
+    };
+
+    family(a) == family(b)
+}
+
+/// The table's quoted name, qualified enough to query it on the data source
+pub fn qualified_table_name(data_source_type: &DataSourceType, table: &CatalogTable) -> String {
+    let quote = match data_source_type {
+        DataSourceType::BigQuery
+        | DataSourceType::Databricks
+        | DataSourceType::MySql
+        | DataSourceType::Mariadb => '`',
+        _ => '"',
+    };
+    let quoted = |name: &str| {
+        let escaped = name.replace(quote, &format!("{quote}{quote}"));
+        format!("{quote}{escaped}{quote}")
+    };
+
+    match data_source_type {
+        // Tables outside the default project/database are only reachable fully qualified
+        DataSourceType::BigQuery | DataSourceType::Snowflake | DataSourceType::Databricks => {
+            format!(
+                "{}.{}.{}",
+                quoted(&table.database),
+                quoted(&table.schema),
+                quoted(&table.name)
+            )
+        }
+        _ => format!("{}.{}", quoted(&table.schema), quoted(&table.name)),
+    }
+}
+
+fn canonical_type(data_type: &str) -> String {
+    let lowered = data_type.trim().to_lowercase();
+    let base = match lowered.find('(') {
+        Some(paren) => {
+            let rest = lowered[paren..]
+                .find(')')
+                .map(|end| &lowered[paren + end + 1..]);
+            format!("{}{}", &lowered[..paren], rest.unwrap_or_default())
+        }
+        None => lowered,
+    };
+    let base = base.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    let canonical = match base.as_str() {
+        "int" | "int4" | "integer" | "int32" => "integer",
+        "int8" | "bigint" | "int64" => "bigint",
+        "int2" | "smallint" | "int16" => "smallint",
+        "varchar" | "character varying" | "text" | "string" | "nvarchar" | "char" | "character"
+        | "nchar" | "bpchar" | "ntext" => "text",
+        "float8" | "double" | "double precision" | "float64" | "float" => "double",
+        "float4" | "real" | "float32" => "real",
+        "numeric" | "decimal" | "number" | "bignumeric" => "numeric",
+        "bool" | "boolean" | "bit" => "boolean",
+        "timestamp"
+        | "timestamp without time zone"
+        | "datetime"
+        | "datetime2"
+        | "timestamp_ntz" => "timestamp",
+        "timestamptz"
+        | "timestamp with time zone"
+        | "timestamp_tz"
+        | "timestamp_ltz"
+        | "datetimeoffset" => "timestamptz",
+        "time" | "time without time zone" => "time",
+        "json" | "jsonb" | "variant" => "json",
+        other => other,
+    };
+
+    canonical.to_string()
+}
+
+fn default_database(credential: &Credential) -> Result<&str> {
+    match credential {
+        Credential::Postgres(c) => Ok(&c.default_database),
+        Credential::Redshift(c) => Ok(&c.default_database),
+        Credential::MySql(c) => Ok(&c.default_database),
+        Credential::Mariadb(c) => Ok(&c.default_database),
+        Credential::SqlServer(c) => Ok(&c.default_database),
+        Credential::Snowflake(c) => Ok(&c.default_database),
+        Credential::Bigquery(c) => Ok(&c.default_project_id),
+        Credential::Databricks(c) => Ok(&c.default_catalog),
+        other => Err(anyhow!(
+            "Catalog introspection is not supported for {} data sources",
+            other.get_type_string()
+        )),
+    }
+}
+
+/// SQL listing the databases the credentials can see, or `None` where the SQL interface
+/// can't (BigQuery projects).
+pub fn databases_sql(credential: &Credential) -> Result<Option<String>> {
+    let sql = match credential {
+        Credential::Postgres(_) => "SELECT datname AS database_name FROM pg_catalog.pg_database WHERE NOT datistemplate AND datallowconn".to_string(),
+        Credential::Redshift(_) => {
+            "SELECT database_name FROM svv_redshift_databases".to_string()
+        }
+        Credential::MySql(_) | Credential::Mariadb(_) => format!(
+            "SELECT schema_name AS database_name FROM information_schema.schemata WHERE schema_name NOT IN ({})",
+            MYSQL_SYSTEM_SCHEMAS
+        ),
+        Credential::SqlServer(_) => {
+            "SELECT name AS database_name FROM sys.databases WHERE database_id > 4".to_string()
+        }
+        Credential::Snowflake(c) => format!(
+            "SELECT database_name FROM {}.information_schema.databases",
+            quote_identifier(&c.default_database, '"')?
+        ),
+        Credential::Databricks(c) => format!(
+            "SELECT catalog_name AS database_name FROM {}.information_schema.catalogs",
+            quote_identifier(&c.default_catalog, '`')?
+        ),
+        Credential::Bigquery(_) => return Ok(None),
+        other => {
+            return Err(anyhow!(
+                "Catalog introspection is not supported for {} data sources",
+                other.get_type_string()
+            ))
+        }
+    };
+
+    Ok(Some(sql))
+}
+
+const MYSQL_SYSTEM_SCHEMAS: &str = "'mysql', 'information_schema', 'performance_schema', 'sys'";
+
+/// SQL reading one row per column of every table and view in scope. All dialects return the
+/// same columns: database_name, schema_name, table_name, table_type, table_comment,
+/// column_name, data_type, is_nullable, ordinal_position and column_comment.
+pub fn columns_sql(credential: &Credential, schemas: &[String]) -> Result<String> {
+    let schema_filter = |column: &str| -> Result<String> {
+        if schemas.is_empty() {
+            return Ok(String::new());
+        }
+        let names = schemas
+            .iter()
+            .map(|schema| schema_literal(schema))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(format!(" AND LOWER({}) IN ({})", column, names.join(", ")))
+    };
+
+    let sql = match credential {
+        Credential::Postgres(_) => format!(
+            "SELECT c.table_catalog AS database_name, c.table_schema AS schema_name, c.table_name AS table_name, \
+             t.table_type AS table_type, obj_description(pc.oid, 'pg_class') AS table_comment, \
+             c.column_name AS column_name, c.data_type AS data_type, c.is_nullable AS is_nullable, \
+             c.ordinal_position AS ordinal_position, col_description(pc.oid, c.ordinal_position) AS column_comment \
+             FROM information_schema.columns c \
+             JOIN information_schema.tables t ON t.table_schema = c.table_schema AND t.table_name = c.table_name \
+             JOIN pg_catalog.pg_namespace pn ON pn.nspname = c.table_schema \
+             JOIN pg_catalog.pg_class pc ON pc.relnamespace = pn.oid AND pc.relname = c.table_name \
+             WHERE c.table_schema NOT IN ('pg_catalog', 'information_schema') \
+             AND c.table_schema NOT LIKE 'pg_toast%'{}",
+            schema_filter("c.table_schema")?
+        ),
+        Credential::Redshift(_) => format!(
+            "SELECT c.table_catalog AS database_name, c.table_schema AS schema_name, c.table_name AS table_name, \
+             t.table_type AS table_type, t.remarks AS table_comment, \
+             c.column_name AS column_name, c.data_type AS data_type, c.is_nullable AS is_nullable, \
+             c.ordinal_position AS ordinal_position, c.remarks AS column_comment \
+             FROM svv_columns c \
+             JOIN svv_tables t ON t.table_schema = c.table_schema AND t.table_name = c.table_name \
+             WHERE c.table_schema NOT IN ('pg_catalog', 'information_schema', 'pg_internal'){}",
+            schema_filter("c.table_schema")?
+        ),
+        Credential::MySql(_) | Credential::Mariadb(_) => {
+            // A MySQL database is what other dialects call a schema
+            let scope = if schemas.is_empty() {
+                format!(" AND LOWER(c.table_schema) = {}", schema_literal(default_database(credential)?)?)
+            } else {
+                schema_filter("c.table_schema")?
+            };
+            format!(
+                "SELECT c.table_schema AS database_name, c.table_schema AS schema_name, c.table_name AS table_name, \
+                 t.table_type AS table_type, t.table_comment AS table_comment, \
+                 c.column_name AS column_name, c.column_type AS data_type, c.is_nullable AS is_nullable, \
+                 c.ordinal_position AS ordinal_position, c.column_comment AS column_comment \
+                 FROM information_schema.columns c \
+                 JOIN information_schema.tables t ON t.table_schema = c.table_schema AND t.table_name = c.table_name \
+                 WHERE c.table_schema NOT IN ({}){}",
+                MYSQL_SYSTEM_SCHEMAS, scope
+            )
+        }
+        Credential::SqlServer(_) => {
+            let object_id = "OBJECT_ID(QUOTENAME(c.TABLE_SCHEMA) + '.' + QUOTENAME(c.TABLE_NAME))";
+            format!(
+                "SELECT c.TABLE_CATALOG AS database_name, c.TABLE_SCHEMA AS schema_name, c.TABLE_NAME AS table_name, \
+                 t.TABLE_TYPE AS table_type, CAST(tp.value AS NVARCHAR(4000)) AS table_comment, \
+                 c.COLUMN_NAME AS column_name, c.DATA_TYPE AS data_type, c.IS_NULLABLE AS is_nullable, \
+                 c.ORDINAL_POSITION AS ordinal_position, CAST(cp.value AS NVARCHAR(4000)) AS column_comment \
+                 FROM INFORMATION_SCHEMA.COLUMNS c \
+                 JOIN INFORMATION_SCHEMA.TABLES t ON t.TABLE_SCHEMA = c.TABLE_SCHEMA AND t.TABLE_NAME = c.TABLE_NAME \
+                 LEFT JOIN sys.extended_properties tp ON tp.major_id = {object_id} AND tp.minor_id = 0 AND tp.name = 'MS_Description' \
+                 LEFT JOIN sys.extended_properties cp ON cp.major_id = {object_id} \
+                 AND cp.minor_id = COLUMNPROPERTY({object_id}, c.COLUMN_NAME, 'ColumnId') AND cp.name = 'MS_Description' \
+                 WHERE c.TABLE_SCHEMA NOT IN ('sys', 'INFORMATION_SCHEMA'){}",
+                schema_filter("c.TABLE_SCHEMA")?
+            )
+        }
+        Credential::Snowflake(c) => {
+            let database = quote_identifier(&c.default_database, '"')?;
+            format!(
+                "SELECT c.table_catalog AS database_name, c.table_schema AS schema_name, c.table_name AS table_name, \
+                 t.table_type AS table_type, t.comment AS table_comment, \
+                 c.column_name AS column_name, c.data_type AS data_type, c.is_nullable AS is_nullable, \
+                 c.ordinal_position AS ordinal_position, c.comment AS column_comment \
+                 FROM {database}.information_schema.columns c \
+                 JOIN {database}.information_schema.tables t ON t.table_schema = c.table_schema AND t.table_name = c.table_name \
+                 WHERE c.table_schema <> 'INFORMATION_SCHEMA'{}",
+                schema_filter("c.table_schema")?
+            )
+        }
+        Credential::Bigquery(c) => {
+            // INFORMATION_SCHEMA views are per dataset, so each dataset gets its own SELECT
+            let datasets = if schemas.is_empty() {
+                vec![c.default_dataset_id.clone()]
+            } else {
+                schemas.to_vec()
+            };
+            let project = quote_identifier(&c.default_project_id, '`')?;
+            let selects = datasets
+                .iter()
+                .map(|dataset| {
+                    let prefix = format!("{}.{}.INFORMATION_SCHEMA", project, quote_identifier(dataset, '`')?);
+                    Ok(format!(
+                        "SELECT c.table_catalog AS database_name, c.table_schema AS schema_name, c.table_name AS table_name, \
+                         t.table_type AS table_type, JSON_VALUE(o.option_value, '$') AS table_comment, \
+                         c.column_name AS column_name, c.data_type AS data_type, c.is_nullable AS is_nullable, \
+                         c.ordinal_position AS ordinal_position, p.description AS column_comment \
+                         FROM {prefix}.COLUMNS c \
+                         JOIN {prefix}.TABLES t ON t.table_name = c.table_name \
+                         LEFT JOIN {prefix}.TABLE_OPTIONS o ON o.table_name = c.table_name AND o.option_name = 'description' \
+                         LEFT JOIN {prefix}.COLUMN_FIELD_PATHS p ON p.table_name = c.table_name \
+                         AND p.column_name = c.column_name AND p.field_path = c.column_name"
+                    ))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            selects.join(" UNION ALL ")
+        }
+        Credential::Databricks(c) => {
+            let catalog = quote_identifier(&c.default_catalog, '`')?;
+            format!(
+                "SELECT c.table_catalog AS database_name, c.table_schema AS schema_name, c.table_name AS table_name, \
+                 t.table_type AS table_type, t.comment AS table_comment, \
+                 c.column_name AS column_name, c.data_type AS data_type, c.is_nullable AS is_nullable, \
+                 c.ordinal_position AS ordinal_position, c.comment AS column_comment \
+                 FROM {catalog}.information_schema.columns c \
+                 JOIN {catalog}.information_schema.tables t ON t.table_schema = c.table_schema AND t.table_name = c.table_name \
+                 WHERE c.table_schema <> 'information_schema'{}",
+                schema_filter("c.table_schema")?
+            )
+        }
+        other => {
+            return Err(anyhow!(
+                "Catalog introspection is not supported for {} data sources",
+                other.get_type_string()
+            ))
+        }
+    };
+
+    Ok(sql)
+}
+
+/// Names that end up inside catalog SQL are limited to characters that never need escaping
+fn check_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '$' | '-' | ' '));
+    if valid {
+        Ok(())
+    } else {
+        Err(anyhow!("Invalid schema or database name '{}'", name))
+    }
+}
+
+fn schema_literal(schema: &str) -> Result<String> {
+    check_name(schema)?;
+    Ok(format!("'{}'", schema.to_lowercase()))
+}
+
+fn quote_identifier(name: &str, quote: char) -> Result<String> {
+    check_name(name)?;
+    Ok(format!("{quote}{name}{quote}"))
+}
+
+/// Groups catalog rows (one per column) into tables, ordered by schema, table and position
+pub fn catalog_tables(rows: &[IndexMap<String, DataType>]) -> Vec<CatalogTable> {
+    // Columns are added in position order, so each table's columns come out sorted
+    let mut rows: Vec<_> = rows.iter().collect();
+    rows.sort_by_key(|row| int_value(row, "ordinal_position").unwrap_or(i64::MAX));
+
+    let mut tables: BTreeMap<(String, String, String), CatalogTable> = BTreeMap::new();
+
+    for row in rows {
+        let (Some(schema), Some(table_name), Some(column_name)) = (
+            text_value(row, "schema_name"),
+            text_value(row, "table_name"),
+            text_value(row, "column_name"),
+        ) else {
+            continue;
+        };
+        let database = text_value(row, "database_name").unwrap_or_default();
+
+        let table = tables
+            .entry((database.clone(), schema.clone(), table_name.clone()))
+            .or_insert_with(|| {
+                let kind = match text_value(row, "table_type") {
+                    Some(table_type) if table_type.to_uppercase().contains("VIEW") => {
+                        CatalogTableKind::View
+                    }
+                    _ => CatalogTableKind::Table,
+                };
+                CatalogTable {
+                    database,
+                    schema,
+                    name: table_name,
+                    kind,
+                    comment: text_value(row, "table_comment"),
+                    columns: Vec::new(),
+                }
+            });
+
+        if table.comment.is_none() {
+            table.comment = text_value(row, "table_comment");
+        }
+
+        let nullable = match value(row, "is_nullable") {
+            Some(DataType::Bool(Some(nullable))) => *nullable,
+            _ => text_value(row, "is_nullable")
+                .map(|n| !n.eq_ignore_ascii_case("no"))
+                .unwrap_or(true),
+        };
+
+        table.columns.push(CatalogColumn {
+            name: column_name,
+            data_type: text_value(row, "data_type").unwrap_or_default(),
+            nullable,
+            comment: text_value(row, "column_comment"),
+        });
+    }
+
+    tables.into_values().collect()
+}
+
+// Some warehouses upper-case result column names, so lookups ignore case
+fn value<'a>(row: &'a IndexMap<String, DataType>, key: &str) -> Option<&'a DataType> {
+    row.iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(key))
+        .map(|(_, value)| value)
+}
+
+fn text_value(row: &IndexMap<String, DataType>, key: &str) -> Option<String> {
+    let text = match value(row, key)? {
+        DataType::Text(Some(s)) | DataType::Char(Some(s)) | DataType::Unknown(Some(s)) => s.clone(),
+        DataType::Json(Some(serde_json::Value::String(s))) => s.clone(),
+        DataType::Null => return None,
+        other => other.to_string(),
+    };
+    let text = text.trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+fn int_value(row: &IndexMap<String, DataType>, key: &str) -> Option<i64> {
+    match value(row, key)? {
+        DataType::Int2(Some(n)) => Some(*n as i64),
+        DataType::Int4(Some(n)) => Some(*n as i64),
+        DataType::Int8(Some(n)) => Some(*n),
+        DataType::Float4(Some(n)) => Some(*n as i64),
+        DataType::Float8(Some(n)) => Some(*n as i64),
+        DataType::Decimal(Some(n)) => n.to_string().split('.').next()?.parse().ok(),
+        _ => text_value(row, key)?.parse().ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::credentials::{BigqueryCredentials, PostgresCredentials};
+
+    fn postgres() -> Credential {
+        Credential::Postgres(PostgresCredentials {
+            host: "localhost".to_string(),
+            port: 5432,
+            username: "buster".to_string(),
+            password: "secret".to_string(),
+            jump_host: None,
+            ssh_username: None,
+            ssh_private_key: None,
+            default_database: "analytics".to_string(),
+            default_schema: None,
+        })
+    }
+
+    fn row(values: &[(&str, DataType)]) -> IndexMap<String, DataType> {
+        values
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.clone()))
+            .collect()
+    }
+
+    fn text(s: &str) -> DataType {
+        DataType::Text(Some(s.to_string()))
+    }
+
+    #[test]
+    fn test_catalog_tables_groups_rows() {
+        let rows = vec![
+            row(&[
+                ("DATABASE_NAME", text("analytics")),
+                ("SCHEMA_NAME", text("sales")),
+                ("TABLE_NAME", text("orders")),
+                ("TABLE_TYPE", text("BASE TABLE")),
+                ("TABLE_COMMENT", text("")),
+                ("COLUMN_NAME", text("amount")),
+                ("DATA_TYPE", text("numeric")),
+                ("IS_NULLABLE", text("YES")),
+                ("ORDINAL_POSITION", DataType::Int8(Some(2))),
+                ("COLUMN_COMMENT", DataType::Null),
+            ]),
+            row(&[
+                ("DATABASE_NAME", text("analytics")),
+                ("SCHEMA_NAME", text("sales")),
+                ("TABLE_NAME", text("orders")),
+                ("TABLE_TYPE", text("BASE TABLE")),
+                ("TABLE_COMMENT", text("")),
+                ("COLUMN_NAME", text("id")),
+                ("DATA_TYPE", text("integer")),
+                ("IS_NULLABLE", text("NO")),
+                ("ORDINAL_POSITION", text("1")),
+                ("COLUMN_COMMENT", text("Order id")),
+            ]),
+            row(&[
+                ("database_name", text("analytics")),
+                ("schema_name", text("sales")),
+                ("table_name", text("daily_orders")),
+                ("table_type", text("VIEW")),
+                ("table_comment", text("Orders per day")),
+                ("column_name", text("day")),
+                ("data_type", text("date")),
+                ("is_nullable", DataType::Bool(Some(false))),
+                ("ordinal_position", DataType::Int4(Some(1))),
+                ("column_comment", DataType::Null),
+            ]),
+        ];
+
+        let tables = catalog_tables(&rows);
+        assert_eq!(tables.len(), 2);
+
+        assert_eq!(tables[0].name, "daily_orders");
+        assert_eq!(tables[0].kind, CatalogTableKind::View);
+        assert_eq!(tables[0].comment.as_deref(), Some("Orders per day"));
+        assert!(!tables[0].columns[0].nullable);
+
+        assert_eq!(tables[1].name, "orders");
+        assert_eq!(tables[1].kind, CatalogTableKind::Table);
+        assert_eq!(tables[1].comment, None);
+        assert_eq!(
+            tables[1].columns,
+            vec![
+                CatalogColumn {
+                    name: "id".to_string(),
+                    data_type: "integer".to_string(),
+                    nullable: false,
+                    comment: Some("Order id".to_string()),
+                },
+                CatalogColumn {
+                    name: "amount".to_string(),
+                    data_type: "numeric".to_string(),
+                    nullable: true,
+                    comment: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_columns_sql_filters_and_rejects_bad_names() {
+        let sql = columns_sql(&postgres(), &["Sales".to_string(), "finance".to_string()]).unwrap();
+        assert!(sql.ends_with("AND LOWER(c.table_schema) IN ('sales', 'finance')"));
+
+        assert!(columns_sql(&postgres(), &["sales' OR '1'='1".to_string()]).is_err());
+
+        let bigquery = Credential::Bigquery(BigqueryCredentials {
+            credentials_json: serde_json::json!({}),
+            default_project_id: "my-project".to_string(),
+            default_dataset_id: "sales".to_string(),
+        });
+        let sql = columns_sql(&bigquery, &[]).unwrap();
+        assert!(sql.contains("FROM `my-project`.`sales`.INFORMATION_SCHEMA.COLUMNS c"));
+        assert!(!sql.contains("UNION ALL"));
+        assert_eq!(databases_sql(&bigquery).unwrap(), None);
+    }
+
+    #[test]
+    fn test_qualified_table_name() {
+        let table = CatalogTable {
+            database: "my-project".to_string(),
+            schema: "sales".to_string(),
+            name: "Order \"Lines\"".to_string(),
+            kind: CatalogTableKind::Table,
+            comment: None,
+            columns: vec![],
+        };
+
+        assert_eq!(
+            qualified_table_name(&DataSourceType::Postgres, &table),
+            "\"sales\".\"Order \"\"Lines\"\"\""
+        );
+        assert_eq!(
+            qualified_table_name(&DataSourceType::BigQuery, &table),
+            "`my-project`.`sales`.`Order \"Lines\"`"
+        );
+    }
+
+    #[test]
+    fn test_same_column_type() {
+        assert!(same_column_type("character varying(255)", "VARCHAR"));
+        assert!(same_column_type(
+            "timestamp without time zone",
+            "TIMESTAMP_NTZ"
+        ));
+        assert!(same_column_type("NUMBER(38,0)", "numeric"));
+        assert!(!same_column_type("integer", "bigint"));
+        assert!(!same_column_type("text", "date"));
+
+        assert!(join_compatible_types("integer", "NUMBER(38,0)"));
+        assert!(join_compatible_types("varchar(36)", "text"));
+        assert!(!join_compatible_types("uuid", "bigint"));
+    }
+}
@@ -3,3 +3,9 @@ pub mod data_source_connections;
 pub mod data_types;
 pub mod credentials;
 pub mod data_source_helpers;
+pub mod query_cache;
+pub mod pagination;
+pub mod arrow_conversion;
+pub mod metric_parameters;
+pub mod dashboard_filters;
+pub mod introspection;
@@ -0,0 +1,335 @@
+//! Values for metric parameters.
+//!
+//! A metric declares typed parameters in its YAML and references them in SQL as `{{name}}`.
+//! Requests may override the declared defaults; the resolved values are then bound into the
+//! SQL by `sql_analyzer::bind_parameters` before any security rewrites run.
+
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use chrono::{Datelike, Duration, Months, NaiveDate, Utc};
+use database::types::{MetricParameter, MetricParameterType, MetricYml};
+use serde_json::Value;
+use sql_analyzer::{bind_parameters, ParameterType, ParameterValue};
+use uuid::Uuid;
+
+use crate::pagination::get_data_source_type;
+
+/// Parameter values supplied with a request, keyed by parameter name
+pub type ParameterOverrides = HashMap<String, Value>;
+
+/// Returns the metric's SQL with every parameter bound, using `overrides` where given and
+/// the declared defaults otherwise.
+pub fn bind_metric_sql(
+    metric_yml: &MetricYml,
+    overrides: &ParameterOverrides,
+    data_source_dialect: &str,
+) -> Result<String> {
+    if metric_yml.parameters.is_empty() && overrides.is_empty() {
+        return Ok(metric_yml.sql.clone());
+    }
+
+    let values = resolve_parameter_values(&metric_yml.parameters, overrides, Utc::now().date_naive())?;
+
+    bind_parameters(&metric_yml.sql, data_source_dialect, &values)
+        .map_err(|e| anyhow!("Failed to bind metric parameters: {}", e))
+}
+
+/// Same as `bind_metric_sql`, looking up the dialect from the data source.
+pub async fn bind_metric_sql_for_data_source(
+    metric_yml: &MetricYml,
+    overrides: &ParameterOverrides,
+    data_source_id: &Uuid,
+) -> Result<String> {
+    if metric_yml.parameters.is_empty() && overrides.is_empty() {
+        return Ok(metric_yml.sql.clone());
+    }
+
+    let data_source_type = get_data_source_type(data_source_id).await?;
+    bind_metric_sql(metric_yml, overrides, data_source_type.to_str())
+}
+
+/// Resolves a value for every declared parameter. Date ranges produce `name.start` and
+/// `name.end` entries. Relative ranges are computed from `today`.
+pub fn resolve_parameter_values(
+    parameters: &[MetricParameter],
+    overrides: &ParameterOverrides,
+    today: NaiveDate,
+) -> Result<HashMap<String, ParameterValue>> {
+    if let Some(unknown) = overrides
+        .keys()
+        .find(|name| !parameters.iter().any(|p| &p.name == *name))
+    {
+        return Err(anyhow!("Unknown parameter '{}'", unknown));
+    }
+
+    let mut values = HashMap::new();
+
+    for parameter in parameters {
+        let value = overrides
+            .get(&parameter.name)
+            .or(parameter.default.as_ref())
+            .filter(|value| !value.is_null())
+            .ok_or_else(|| {
+                anyhow!(
+                    "Parameter '{}' has no default and no value was provided",
+                    parameter.name
+                )
+            })?;
+
+        let invalid = || anyhow!("Invalid value for parameter '{}': {}", parameter.name, value);
+
+        match parameter.param_type {
+            MetricParameterType::String => {
+                let text = scalar_text(value).ok_or_else(invalid)?;
+                check_options(parameter, &text)?;
+                values.insert(
+                    parameter.name.clone(),
+                    ParameterValue::Single(ParameterType::String, text),
+                );
+            }
+            MetricParameterType::Number => {
+                let text = scalar_text(value).ok_or_else(invalid)?;
+                values.insert(
+                    parameter.name.clone(),
+                    ParameterValue::Single(ParameterType::Number, text),
+                );
+            }
+            MetricParameterType::Boolean => {
+                let text = scalar_text(value).ok_or_else(invalid)?;
+                values.insert(
+                    parameter.name.clone(),
+                    ParameterValue::Single(ParameterType::Boolean, text),
+                );
+            }
+            MetricParameterType::Date => {
+                let text = match value {
+                    Value::String(s) => relative_date(s, today)
+                        .map(|date| date.to_string())
+                        .unwrap_or_else(|| s.clone()),
+                    _ => return Err(invalid()),
+                };
+                values.insert(
+                    parameter.name.clone(),
+                    ParameterValue::Single(ParameterType::Date, text),
+                );
+            }
+            MetricParameterType::DateRange => {
+                let (start, end) = date_range(value, today).ok_or_else(invalid)?;
+                values.insert(
+                    format!("{}.start", parameter.name),
+                    ParameterValue::Single(ParameterType::Date, start),
+                );
+                values.insert(
+                    format!("{}.end", parameter.name),
+                    ParameterValue::Single(ParameterType::Date, end),
+                );
+            }
+            MetricParameterType::MultiSelect => {
+                let items = match value {
+                    Value::Array(items) => items
+                        .iter()
+                        .map(scalar_text)
+                        .collect::<Option<Vec<_>>>()
+                        .ok_or_else(invalid)?,
+                    other => vec![scalar_text(other).ok_or_else(invalid)?],
+                };
+                if items.is_empty() {
+                    return Err(anyhow!(
+                        "Parameter '{}' needs at least one value",
+                        parameter.name
+                    ));
+                }
+                for item in &items {
+                    check_options(parameter, item)?;
+                }
+                values.insert(
+                    parameter.name.clone(),
+                    ParameterValue::List(ParameterType::String, items),
+                );
+            }
+        }
+    }
+
+    Ok(values)
+}
+
+pub(crate) fn scalar_text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+fn check_options(parameter: &MetricParameter, value: &str) -> Result<()> {
+    match &parameter.options {
+        Some(options) if !options.iter().any(|option| option == value) => Err(anyhow!(
+            "'{}' is not an allowed value for parameter '{}'",
+            value,
+            parameter.name
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// `today` and `yesterday`
+fn relative_date(value: &str, today: NaiveDate) -> Option<NaiveDate> {
+    match value {
+        "today" => Some(today),
+        "yesterday" => today.checked_sub_signed(Duration::days(1)),
+        _ => None,
+    }
+}
+
+/// A range given as `{ "start", "end" }`, `[start, end]`, or a relative range name such as
+/// `last_30_days`, `last_3_months`, `month_to_date` or `year_to_date`
+pub(crate) fn date_range(value: &Value, today: NaiveDate) -> Option<(String, String)> {
+    let bound = |value: &Value| match value {
+        Value::String(s) => Some(
+            relative_date(s, today)
+                .map(|date| date.to_string())
+                .unwrap_or_else(|| s.clone()),
+        ),
+        _ => None,
+    };
+
+    match value {
+        Value::Object(range) => Some((bound(range.get("start")?)?, bound(range.get("end")?)?)),
+        Value::Array(range) if range.len() == 2 => Some((bound(&range[0])?, bound(&range[1])?)),
+        Value::String(name) => {
+            let start = match name.as_str() {
+                "today" => today,
+                "month_to_date" => today.with_day(1)?,
+                "year_to_date" => NaiveDate::from_ymd_opt(today.year(), 1, 1)?,
+                other => {
+                    let rest = other.strip_prefix("last_")?;
+                    let (count, unit) = rest.split_once('_')?;
+                    let count: u32 = count.parse().ok()?;
+                    match unit {
+                        "days" | "day" => today.checked_sub_signed(Duration::days(count as i64))?,
+                        "weeks" | "week" => {
+                            today.checked_sub_signed(Duration::weeks(count as i64))?
+                        }
+                        "months" | "month" => today.checked_sub_months(Months::new(count))?,
+                        "years" | "year" => today.checked_sub_months(Months::new(count * 12))?,
+                        _ => return None,
+                    }
+                }
+            };
+            Some((start.to_string(), today.to_string()))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn parameter(
+        name: &str,
+        param_type: MetricParameterType,
+        options: Option<Vec<&str>>,
+        default: Option<Value>,
+    ) -> MetricParameter {
+        MetricParameter {
+            name: name.to_string(),
+            param_type,
+            label: None,
+            options: options.map(|o| o.into_iter().map(String::from).collect()),
+            default,
+        }
+    }
+
+    #[test]
+    fn test_resolve_defaults_and_overrides() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 15).unwrap();
+        let parameters = vec![
+            parameter(
+                "regions",
+                MetricParameterType::MultiSelect,
+                Some(vec!["west", "east"]),
+                Some(json!(["west"])),
+            ),
+            parameter("period", MetricParameterType::DateRange, None, Some(json!("last_30_days"))),
+            parameter("min_amount", MetricParameterType::Number, None, Some(json!(100))),
+        ];
+
+        let overrides = ParameterOverrides::from([("regions".to_string(), json!(["west", "east"]))]);
+        let values = resolve_parameter_values(&parameters, &overrides, today).unwrap();
+
+        assert_eq!(
+            values["regions"],
+            ParameterValue::List(ParameterType::String, vec!["west".to_string(), "east".to_string()])
+        );
+        assert_eq!(
+            values["period.start"],
+            ParameterValue::Single(ParameterType::Date, "2024-02-14".to_string())
+        );
+        assert_eq!(
+            values["period.end"],
+            ParameterValue::Single(ParameterType::Date, "2024-03-15".to_string())
+        );
+        assert_eq!(
+            values["min_amount"],
+            ParameterValue::Single(ParameterType::Number, "100".to_string())
+        );
+    }
+
+    #[test]
+    fn test_resolve_rejects_bad_values() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 15).unwrap();
+        let parameters = vec![parameter(
+            "region",
+            MetricParameterType::String,
+            Some(vec!["west", "east"]),
+            None,
+        )];
+
+        // No default and no override
+        assert!(resolve_parameter_values(&parameters, &ParameterOverrides::new(), today).is_err());
+
+        // Not one of the options
+        let overrides = ParameterOverrides::from([("region".to_string(), json!("south"))]);
+        assert!(resolve_parameter_values(&parameters, &overrides, today).is_err());
+
+        // Unknown parameter
+        let overrides = ParameterOverrides::from([
+            ("region".to_string(), json!("west")),
+            ("country".to_string(), json!("us")),
+        ]);
+        assert!(resolve_parameter_values(&parameters, &overrides, today).is_err());
+    }
+
+    #[test]
+    fn test_bind_metric_sql() {
+        let metric_yml: MetricYml = serde_yaml::from_str(
+            r#"
+name: Orders
+timeFrame: Custom
+sql: SELECT o.id FROM sales.orders o WHERE o.region IN ({{regions}}) AND o.created_at >= {{period.start}}
+parameters:
+  - name: regions
+    type: multi_select
+    options: [west, east]
+    default: [west]
+  - name: period
+    type: date_range
+    default: { start: "2024-01-01", end: "2024-01-31" }
+chartConfig:
+  selectedChartType: table
+  columnLabelFormats: {}
+"#,
+        )
+        .unwrap();
+
+        let sql = bind_metric_sql(&metric_yml, &ParameterOverrides::new(), "postgres").unwrap();
+        assert_eq!(
+            sql,
+            "SELECT o.id FROM sales.orders AS o WHERE o.region IN ('west') AND o.created_at >= '2024-01-01'"
+        );
+    }
+}
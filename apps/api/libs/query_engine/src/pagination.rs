@@ -0,0 +1,666 @@
+//! Keyset pagination for user queries.
+//!
+//! Pages follow the query's own `ORDER BY`, or every result column when it has none. Each
+//! page after the first seeks past the sort key of the last row already returned, so fetching
+//! a page costs the same wherever it is in the result and only one page of rows is ever held
+//! in memory. Rows that tie on the whole sort key are told apart by how many of them were
+//! already returned; order by a unique key to page exactly through ties.
+
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use database::{enums::DataSourceType, pool::get_pg_pool, schema::data_sources};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use sql_analyzer::analysis::get_dialect;
+use sql_analyzer::literal;
+use sqlparser::ast::{Expr, Ident, OrderBy, OrderByExpr, Query, SelectItem, SetExpr, Statement, Value};
+use sqlparser::parser::Parser;
+use uuid::Uuid;
+
+use crate::data_source_query_routes::query_engine::{
+    exact_query_engine, query_engine, secure_sql_for_user, QueryResult,
+};
+use crate::data_types::DataType;
+use crate::query_cache::{cached_secured_query, QueryCacheOptions};
+
+/// Largest page a caller may request.
+pub const MAX_PAGE_SIZE: i64 = 5000;
+
+/// Opaque position in a paginated result, handed to clients as a URL-safe string.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PageCursor {
+    /// Sort key of the last row returned
+    pub after: Vec<serde_json::Value>,
+    /// How many rows with exactly that sort key were returned
+    pub skip: i64,
+}
+
+impl PageCursor {
+    pub fn encode(&self) -> String {
+        // Serializing JSON values and an integer can't fail
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    pub fn decode(cursor: &str) -> Result<Self> {
+        let bytes = URL_SAFE_NO_PAD
+            .decode(cursor)
+            .map_err(|_| anyhow!("Invalid cursor"))?;
+        let cursor: PageCursor =
+            serde_json::from_slice(&bytes).map_err(|_| anyhow!("Invalid cursor"))?;
+
+        if cursor.after.is_empty() || cursor.skip < 1 {
+            return Err(anyhow!("Invalid cursor"));
+        }
+
+        Ok(cursor)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct QueryPage {
+    pub result: QueryResult,
+    /// Whether rows follow this page, even when they can't be reached with a cursor
+    pub has_more_records: bool,
+    /// Cursor for the following page, or `None` when this was the last one
+    pub next_cursor: Option<PageCursor>,
+}
+
+/// Runs one page of `sql` for a user, applying the same security rewrites as
+/// `query_engine_for_user`. With `exact_decimals`, decimals come back as by
+/// `exact_query_engine` rather than as floats.
+pub async fn query_page_for_user(
+    user_id: &Uuid,
+    data_source_id: &Uuid,
+    sql: &str,
+    page_size: i64,
+    cursor: Option<PageCursor>,
+    exact_decimals: bool,
+) -> Result<QueryPage> {
+    let page_size = page_size.clamp(1, MAX_PAGE_SIZE);
+
+    let secured_sql = secure_sql_for_user(user_id, data_source_id, sql).await?;
+    let data_source_type = get_data_source_type(data_source_id).await?;
+
+    // Ask for one extra row to find out whether another page exists
+    let (paginated_sql, keys) =
+        paginate(&secured_sql, &data_source_type, page_size + 1, cursor.as_ref())?;
+    let mut result = match exact_decimals {
+        true => exact_query_engine(data_source_id, &paginated_sql, Some(page_size + 1)).await?,
+        false => query_engine(data_source_id, &paginated_sql, Some(page_size + 1)).await?,
+    };
+
+    let has_more_records = result.data.len() as i64 > page_size;
+    if has_more_records {
+        result.data.truncate(page_size as usize);
+    }
+    result.metadata.row_count = result.data.len() as i64;
+
+    let next_cursor = match has_more_records {
+        true => Some(
+            next_cursor(&keys, &result.data, cursor.as_ref())
+                .ok_or_else(|| anyhow!("The query's results are missing the columns it's paged by"))?,
+        ),
+        false => None,
+    };
+
+    Ok(QueryPage {
+        result,
+        has_more_records,
+        next_cursor,
+    })
+}
+
+/// Runs the first page of `sql` for a user through the query cache.
+///
+/// The query runs as written, so it's only sorted when it asks to be. When it has an
+/// `ORDER BY` on its result columns, the page carries a cursor that `query_page_for_user`
+/// continues from in the same order; other queries get no cursor.
+pub async fn cached_first_page_for_user(
+    user_id: &Uuid,
+    data_source_id: &Uuid,
+    sql: &str,
+    page_size: i64,
+    options: QueryCacheOptions,
+) -> Result<QueryPage> {
+    let page_size = page_size.clamp(1, MAX_PAGE_SIZE);
+
+    let secured_sql = secure_sql_for_user(user_id, data_source_id, sql).await?;
+    let data_source_type = get_data_source_type(data_source_id).await?;
+
+    let mut result = cached_secured_query(
+        data_source_id,
+        data_source_type.to_str(),
+        &secured_sql,
+        Some(page_size + 1),
+        options,
+    )
+    .await?;
+
+    let has_more_records = result.data.len() as i64 > page_size;
+    if has_more_records {
+        result.data.truncate(page_size as usize);
+    }
+    result.metadata.row_count = result.data.len() as i64;
+
+    let next_cursor = match has_more_records {
+        true => match ordered_sort_keys(&secured_sql, &data_source_type) {
+            Ok(keys) => next_cursor(&keys, &result.data, None),
+            Err(e) => {
+                tracing::debug!("Returning first page without a cursor: {}", e);
+                None
+            }
+        },
+        false => None,
+    };
+
+    Ok(QueryPage {
+        result,
+        has_more_records,
+        next_cursor,
+    })
+}
+
+/// Rewrites `sql` so the warehouse only returns `limit` rows following `cursor`.
+///
+/// The query is wrapped and the outer query orders by its sort keys, seeking past the
+/// cursor's key and skipping the rows tied with it that were already returned. The query's
+/// own `ORDER BY` moves outside unless it also limits the rows; an unordered query that limits
+/// its rows is ordered by every result column first, so the limit picks the same rows for
+/// every page.
+pub fn paginate_sql(
+    sql: &str,
+    data_source_type: &DataSourceType,
+    limit: i64,
+    cursor: Option<&PageCursor>,
+) -> Result<String> {
+    paginate(sql, data_source_type, limit, cursor).map(|(sql, _)| sql)
+}
+
+fn paginate(
+    sql: &str,
+    data_source_type: &DataSourceType,
+    limit: i64,
+    cursor: Option<&PageCursor>,
+) -> Result<(String, Vec<SortKey>)> {
+    let mut query = parse_single_query(sql.trim(), data_source_type)?;
+    let keys = sort_keys(&query, data_source_type)?;
+
+    if !limits_rows(&query) {
+        query.order_by = None;
+    } else if query.order_by.is_none() {
+        order_by_positions(&mut query, keys.len());
+    }
+
+    let seek = match cursor {
+        Some(cursor) => format!(
+            " WHERE {}",
+            seek_predicate(&keys, &cursor.after, data_source_type)?
+        ),
+        None => String::new(),
+    };
+    let order = keys.iter().map(SortKey::to_string).collect::<Vec<_>>().join(", ");
+    let skip = cursor.map_or(0, |cursor| cursor.skip);
+    let page = match data_source_type {
+        DataSourceType::SqlServer => format!("OFFSET {} ROWS FETCH NEXT {} ROWS ONLY", skip, limit),
+        _ => format!("LIMIT {} OFFSET {}", limit, skip),
+    };
+
+    Ok((
+        format!(
+            "SELECT * FROM ({}) AS paginated_query{} ORDER BY {} {}",
+            query, seek, order, page
+        ),
+        keys,
+    ))
+}
+
+fn parse_single_query(sql: &str, data_source_type: &DataSourceType) -> Result<Query> {
+    let mut statements = Parser::parse_sql(get_dialect(data_source_type.to_str()), sql)
+        .map_err(|e| anyhow!("Failed to parse query for pagination: {}", e))?;
+
+    match (statements.pop(), statements.is_empty()) {
+        (Some(Statement::Query(query)), true) => Ok(*query),
+        _ => Err(anyhow!("Only a single SELECT query can be paginated")),
+    }
+}
+
+/// Whether the query already caps or skips rows, with LIMIT, OFFSET, FETCH or TOP
+fn limits_rows(query: &Query) -> bool {
+    let has_top = matches!(query.body.as_ref(), SetExpr::Select(select) if select.top.is_some());
+
+    query.limit.is_some() || query.offset.is_some() || query.fetch.is_some() || has_top
+}
+
+/// A result column pages are ordered by
+#[derive(Debug, Clone)]
+struct SortKey {
+    column: Ident,
+    descending: bool,
+    /// NULLS FIRST/LAST as written in the query, if it was
+    nulls: Option<bool>,
+    /// Where NULL sorts, from `nulls` or the warehouse's default
+    nulls_first: bool,
+}
+
+impl SortKey {
+    fn new(column: Ident, term: Option<&OrderByExpr>, data_source_type: &DataSourceType) -> Self {
+        let descending = term.and_then(|term| term.asc) == Some(false);
+        let nulls = term.and_then(|term| term.nulls_first);
+        let nulls_first = nulls.unwrap_or(match data_source_type {
+            // NULL sorts above every value
+            DataSourceType::Postgres
+            | DataSourceType::Supabase
+            | DataSourceType::Redshift
+            | DataSourceType::Snowflake => descending,
+            // NULL sorts last in either direction
+            DataSourceType::DuckDb | DataSourceType::ClickHouse => false,
+            // NULL sorts below every value
+            _ => !descending,
+        });
+
+        SortKey {
+            column,
+            descending,
+            nulls,
+            nulls_first,
+        }
+    }
+
+    /// Conditions for a row sorting after `value` on this key, and for it tying with `value`
+    fn compare(&self, value: &serde_json::Value, dialect: &str) -> Result<(Option<String>, String)> {
+        let column = &self.column;
+
+        if value.is_null() {
+            let after = self.nulls_first.then(|| format!("{} IS NOT NULL", column));
+            return Ok((after, format!("{} IS NULL", column)));
+        }
+
+        let value = literal(value, dialect)?;
+        let op = if self.descending { "<" } else { ">" };
+        let after = match self.nulls_first {
+            true => format!("{} {} {}", column, op, value),
+            false => format!("({} {} {} OR {} IS NULL)", column, op, value, column),
+        };
+
+        Ok((Some(after), format!("{} = {}", column, value)))
+    }
+}
+
+impl std::fmt::Display for SortKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.column)?;
+        if self.descending {
+            write!(f, " DESC")?;
+        }
+        match self.nulls {
+            Some(true) => write!(f, " NULLS FIRST"),
+            Some(false) => write!(f, " NULLS LAST"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The query's `ORDER BY` as sort keys, or every result column when it has none
+fn sort_keys(query: &Query, data_source_type: &DataSourceType) -> Result<Vec<SortKey>> {
+    let columns = result_columns(query);
+
+    let Some(order_by) = &query.order_by else {
+        return columns
+            .ok_or_else(|| anyhow!("List the selected columns instead of * to page through this query"))?
+            .into_iter()
+            .map(|column| {
+                column
+                    .map(|column| SortKey::new(column, None, data_source_type))
+                    .ok_or_else(|| anyhow!("Name every selected expression to page through this query"))
+            })
+            .collect();
+    };
+
+    order_by
+        .exprs
+        .iter()
+        .map(|term| {
+            let column = order_by_column(&term.expr, columns.as_deref())
+                .ok_or_else(|| anyhow!("Order by selected columns to page through this query"))?;
+            Ok(SortKey::new(column, Some(term), data_source_type))
+        })
+        .collect()
+}
+
+/// Sort keys of a query that orders its own rows
+fn ordered_sort_keys(sql: &str, data_source_type: &DataSourceType) -> Result<Vec<SortKey>> {
+    let query = parse_single_query(sql.trim(), data_source_type)?;
+    if query.order_by.is_none() {
+        return Err(anyhow!("Add an ORDER BY to page through this query"));
+    }
+
+    sort_keys(&query, data_source_type)
+}
+
+/// The result column an `ORDER BY` term names, by position or by name. Names are taken on
+/// trust when the result columns can't be known from the SQL (`SELECT *`).
+fn order_by_column(expr: &Expr, columns: Option<&[Option<Ident>]>) -> Option<Ident> {
+    let name = match expr {
+        Expr::Value(Value::Number(position, _)) => {
+            let index = position.parse::<usize>().ok()?.checked_sub(1)?;
+            return columns?.get(index)?.clone();
+        }
+        Expr::Identifier(ident) => ident,
+        Expr::CompoundIdentifier(idents) => idents.last()?,
+        _ => return None,
+    };
+
+    match columns {
+        Some(columns) => columns
+            .iter()
+            .flatten()
+            .find(|column| column.value.eq_ignore_ascii_case(&name.value))
+            .cloned(),
+        None => Some(name.clone()),
+    }
+}
+
+/// Rows sorting after `after`, or tying with it on every key
+fn seek_predicate(
+    keys: &[SortKey],
+    after: &[serde_json::Value],
+    data_source_type: &DataSourceType,
+) -> Result<String> {
+    if keys.len() != after.len() {
+        return Err(anyhow!("Invalid cursor"));
+    }
+
+    let all = |conditions: Vec<String>| match conditions.len() {
+        1 => conditions.join(""),
+        _ => format!("({})", conditions.join(" AND ")),
+    };
+
+    let mut branches = Vec::new();
+    let mut ties = Vec::new();
+    for (key, value) in keys.iter().zip(after) {
+        let (sorts_after, tie) = key.compare(value, data_source_type.to_str())?;
+        if let Some(sorts_after) = sorts_after {
+            branches.push(all(ties.iter().cloned().chain([sorts_after]).collect()));
+        }
+        ties.push(tie);
+    }
+    branches.push(all(ties));
+
+    Ok(branches.join(" OR "))
+}
+
+/// Cursor following the last of `rows`, counting the rows tied with it, including those
+/// returned before `previous` when the whole page ties with it
+fn next_cursor(
+    keys: &[SortKey],
+    rows: &[IndexMap<String, DataType>],
+    previous: Option<&PageCursor>,
+) -> Option<PageCursor> {
+    let key_of = |row: &IndexMap<String, DataType>| {
+        keys.iter()
+            .map(|key| {
+                row.iter()
+                    .find(|(name, _)| name.eq_ignore_ascii_case(&key.column.value))
+                    .map(|(_, value)| serde_json::to_value(value).unwrap_or_default())
+            })
+            .collect::<Option<Vec<_>>>()
+    };
+
+    let after = key_of(rows.last()?)?;
+    let mut skip = 0;
+    for row in rows.iter().rev() {
+        if key_of(row)? != after {
+            break;
+        }
+        skip += 1;
+    }
+    if let Some(previous) = previous.filter(|previous| skip == rows.len() as i64 && previous.after == after) {
+        skip += previous.skip;
+    }
+
+    Some(PageCursor { after, skip })
+}
+
+/// Orders the query by every result column, which makes the order total: rows that tie on all
+/// of them are identical
+fn order_by_positions(query: &mut Query, columns: usize) {
+    let terms = (1..=columns).map(|position| OrderByExpr {
+        expr: Expr::Value(Value::Number(position.to_string(), false)),
+        asc: None,
+        nulls_first: None,
+        with_fill: None,
+    });
+
+    query.order_by = Some(OrderBy {
+        exprs: terms.collect(),
+        interpolate: None,
+    });
+}
+
+/// Result columns of the query, `None` for unnamed expressions, or `None` altogether when they
+/// can't be known from the SQL alone
+fn result_columns(query: &Query) -> Option<Vec<Option<Ident>>> {
+    let mut body = query.body.as_ref();
+    // Set operations take their column names from the first branch
+    while let SetExpr::SetOperation { left, .. } = body {
+        body = left.as_ref();
+    }
+    let SetExpr::Select(select) = body else {
+        return None;
+    };
+
+    select
+        .projection
+        .iter()
+        .map(|item| match item {
+            SelectItem::ExprWithAlias { alias, .. } => Some(Some(alias.clone())),
+            SelectItem::UnnamedExpr(Expr::Identifier(ident)) => Some(Some(ident.clone())),
+            SelectItem::UnnamedExpr(Expr::CompoundIdentifier(idents)) => Some(idents.last().cloned()),
+            SelectItem::UnnamedExpr(_) => Some(None),
+            SelectItem::Wildcard(_) | SelectItem::QualifiedWildcard(..) => None,
+        })
+        .collect()
+}
+
+pub async fn get_data_source_type(data_source_id: &Uuid) -> Result<DataSourceType> {
+    let mut conn = get_pg_pool()
+        .get()
+        .await
+        .map_err(|e| anyhow!("Failed to get database connection: {}", e))?;
+
+    data_sources::table
+        .filter(data_sources::id.eq(data_source_id))
+        .select(data_sources::type_)
+        .first::<DataSourceType>(&mut conn)
+        .await
+        .map_err(|e| anyhow!("Failed to fetch data source type: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn cursor(after: Vec<serde_json::Value>, skip: i64) -> PageCursor {
+        PageCursor { after, skip }
+    }
+
+    fn keys(sql: &str) -> Vec<SortKey> {
+        let query = parse_single_query(sql, &DataSourceType::Postgres).unwrap();
+        sort_keys(&query, &DataSourceType::Postgres).unwrap()
+    }
+
+    fn row(id: i32, name: &str) -> IndexMap<String, DataType> {
+        IndexMap::from([
+            ("id".to_string(), DataType::Int4(Some(id))),
+            ("name".to_string(), DataType::Text(Some(name.to_string()))),
+        ])
+    }
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let page_cursor = cursor(vec![json!(42), json!("2024-01-01"), json!(null)], 3);
+
+        assert_eq!(PageCursor::decode(&page_cursor.encode()).unwrap(), page_cursor);
+        assert!(PageCursor::decode("not a cursor").is_err());
+        assert!(PageCursor::decode(&cursor(vec![json!(1)], 0).encode()).is_err());
+        assert!(PageCursor::decode(&cursor(vec![], 1).encode()).is_err());
+    }
+
+    #[test]
+    fn test_paginate_sql() {
+        // The query's ORDER BY moves outside the wrapped query
+        assert_eq!(
+            paginate_sql("SELECT id, amount FROM orders ORDER BY id;\n", &DataSourceType::Postgres, 101, None)
+                .unwrap(),
+            "SELECT * FROM (SELECT id, amount FROM orders) AS paginated_query ORDER BY id LIMIT 101 OFFSET 0"
+        );
+        assert_eq!(
+            paginate_sql("SELECT id, amount AS total FROM orders ORDER BY 2 DESC", &DataSourceType::Postgres, 10, None)
+                .unwrap(),
+            "SELECT * FROM (SELECT id, amount AS total FROM orders) AS paginated_query \
+             ORDER BY total DESC LIMIT 10 OFFSET 0"
+        );
+        assert_eq!(
+            paginate_sql("SELECT * FROM orders ORDER BY orders.id", &DataSourceType::Postgres, 10, None).unwrap(),
+            "SELECT * FROM (SELECT * FROM orders) AS paginated_query ORDER BY id LIMIT 10 OFFSET 0"
+        );
+
+        // Unordered queries are ordered by every column
+        assert_eq!(
+            paginate_sql("SELECT id, amount FROM orders", &DataSourceType::Postgres, 10, None).unwrap(),
+            "SELECT * FROM (SELECT id, amount FROM orders) AS paginated_query ORDER BY id, amount LIMIT 10 OFFSET 0"
+        );
+        assert_eq!(
+            paginate_sql("SELECT id FROM orders UNION SELECT id FROM refunds", &DataSourceType::Postgres, 10, None)
+                .unwrap(),
+            "SELECT * FROM (SELECT id FROM orders UNION SELECT id FROM refunds) AS paginated_query \
+             ORDER BY id LIMIT 10 OFFSET 0"
+        );
+
+        // Limited queries keep their ORDER BY, and are ordered by every column when they have none
+        assert_eq!(
+            paginate_sql(
+                "SELECT orders.id, orders.amount AS total FROM orders ORDER BY total DESC, orders.id LIMIT 500",
+                &DataSourceType::Postgres,
+                101,
+                None
+            )
+            .unwrap(),
+            "SELECT * FROM (SELECT orders.id, orders.amount AS total FROM orders \
+             ORDER BY total DESC, orders.id LIMIT 500) \
+             AS paginated_query ORDER BY total DESC, id LIMIT 101 OFFSET 0"
+        );
+        assert_eq!(
+            paginate_sql("SELECT id FROM orders LIMIT 500", &DataSourceType::Postgres, 10, None).unwrap(),
+            "SELECT * FROM (SELECT id FROM orders ORDER BY 1 LIMIT 500) \
+             AS paginated_query ORDER BY id LIMIT 10 OFFSET 0"
+        );
+
+        // Queries that can't be ordered by named result columns can't be paged
+        for sql in [
+            "SELECT * FROM orders",
+            "SELECT count(*) FROM orders",
+            "SELECT id, amount FROM orders ORDER BY amount * 2 DESC",
+            "SELECT 1; SELECT 2",
+        ] {
+            assert!(paginate_sql(sql, &DataSourceType::Postgres, 10, None).is_err(), "{}", sql);
+        }
+    }
+
+    #[test]
+    fn test_paginate_sql_seeks_past_cursor() {
+        // Postgres sorts NULL last ascending, so NULLs follow every value
+        assert_eq!(
+            paginate_sql(
+                "SELECT id, name FROM customers ORDER BY name",
+                &DataSourceType::Postgres,
+                10,
+                Some(&cursor(vec![json!("O'Brien")], 2))
+            )
+            .unwrap(),
+            "SELECT * FROM (SELECT id, name FROM customers) AS paginated_query \
+             WHERE (name > 'O''Brien' OR name IS NULL) OR name = 'O''Brien' \
+             ORDER BY name LIMIT 10 OFFSET 2"
+        );
+
+        // SQL Server sorts NULL first ascending and last descending
+        assert_eq!(
+            paginate_sql(
+                "SELECT id, amount FROM orders ORDER BY amount DESC, id",
+                &DataSourceType::SqlServer,
+                10,
+                Some(&cursor(vec![json!(100), json!(7)], 1))
+            )
+            .unwrap(),
+            "SELECT * FROM (SELECT id, amount FROM orders) AS paginated_query \
+             WHERE (amount < 100 OR amount IS NULL) OR (amount = 100 AND id > 7) OR (amount = 100 AND id = 7) \
+             ORDER BY amount DESC, id OFFSET 1 ROWS FETCH NEXT 10 ROWS ONLY"
+        );
+
+        // A NULL key is followed only by values when NULLs sort first
+        assert_eq!(
+            paginate_sql(
+                "SELECT name FROM customers ORDER BY name NULLS FIRST",
+                &DataSourceType::Postgres,
+                10,
+                Some(&cursor(vec![json!(null)], 1))
+            )
+            .unwrap(),
+            "SELECT * FROM (SELECT name FROM customers) AS paginated_query \
+             WHERE name IS NOT NULL OR name IS NULL ORDER BY name NULLS FIRST LIMIT 10 OFFSET 1"
+        );
+        assert_eq!(
+            paginate_sql(
+                "SELECT name FROM customers ORDER BY name",
+                &DataSourceType::Postgres,
+                10,
+                Some(&cursor(vec![json!(null)], 1))
+            )
+            .unwrap(),
+            "SELECT * FROM (SELECT name FROM customers) AS paginated_query \
+             WHERE name IS NULL ORDER BY name LIMIT 10 OFFSET 1"
+        );
+
+        // The cursor must carry a value for every sort key
+        assert!(paginate_sql(
+            "SELECT id, amount FROM orders ORDER BY amount DESC, id",
+            &DataSourceType::Postgres,
+            10,
+            Some(&cursor(vec![json!(100)], 1))
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_next_cursor_counts_ties() {
+        let keys = keys("SELECT id, name FROM customers ORDER BY id");
+
+        // Trailing rows that tie with the last one are counted
+        let rows = [row(1, "a"), row(2, "b"), row(2, "c")];
+        assert_eq!(next_cursor(&keys, &rows, None), Some(cursor(vec![json!(2)], 2)));
+
+        // A page that ties with the previous cursor throughout adds to its count
+        let rows = [row(2, "d"), row(2, "e")];
+        assert_eq!(
+            next_cursor(&keys, &rows, Some(&cursor(vec![json!(2)], 2))),
+            Some(cursor(vec![json!(2)], 4))
+        );
+        assert_eq!(
+            next_cursor(&keys, &rows, Some(&cursor(vec![json!(1)], 1))),
+            Some(cursor(vec![json!(2)], 2))
+        );
+
+        // Rows without the sort key column can't be continued from
+        let keys = self::keys("SELECT id, email FROM customers ORDER BY email");
+        assert_eq!(next_cursor(&keys, &rows, None), None);
+    }
+
+    #[test]
+    fn test_only_ordered_queries_continue_their_first_page() {
+        assert!(ordered_sort_keys("SELECT id FROM orders ORDER BY id", &DataSourceType::Postgres).is_ok());
+        assert!(ordered_sort_keys("SELECT id FROM orders", &DataSourceType::Postgres).is_err());
+    }
+}
@@ -0,0 +1,441 @@
+//! Result cache for user queries.
+//!
+//! Results are stored in Redis when the pool is available and fall back to a bounded
+//! in-process cache otherwise. Keys are derived from the canonical form of the *secured* SQL
+//! (after row-level filters and column masks are applied), so two users only share a cache
+//! entry when they would have run exactly the same query.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use database::{pool::try_get_redis_pool, types::data_metadata::DataMetadata};
+use indexmap::IndexMap;
+use once_cell::sync::Lazy;
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use sql_analyzer::analysis::get_dialect;
+use sqlparser::parser::Parser;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tiberius::numeric::Decimal;
+use uuid::Uuid;
+
+use crate::{
+    data_source_query_routes::query_engine::{query_engine, secure_sql_for_user, QueryResult},
+    data_types::DataType,
+    pagination::get_data_source_type,
+};
+
+const CACHE_KEY_PREFIX: &str = "query_cache";
+const DEFAULT_SCOPE: &str = "adhoc";
+const MAX_LOCAL_ENTRIES: usize = 1000;
+
+/// TTL used when the caller doesn't specify one.
+pub const DEFAULT_QUERY_CACHE_TTL: Duration = Duration::from_secs(300);
+
+static LOCAL_CACHE: Lazy<Mutex<HashMap<String, (Instant, QueryResult)>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone)]
+pub struct QueryCacheOptions {
+    /// Groups entries so they can be invalidated together, e.g. `metric:<id>`
+    pub scope: Option<String>,
+    pub ttl: Duration,
+    /// Skip the lookup and overwrite any cached entry with a fresh result
+    pub force_refresh: bool,
+}
+
+impl Default for QueryCacheOptions {
+    fn default() -> Self {
+        QueryCacheOptions {
+            scope: None,
+            ttl: DEFAULT_QUERY_CACHE_TTL,
+            force_refresh: false,
+        }
+    }
+}
+
+/// Returns the cache scope used for all queries run for a metric.
+pub fn metric_cache_scope(metric_id: &Uuid) -> String {
+    format!("metric:{}", metric_id)
+}
+
+/// Cached variant of `query_engine_for_user`. The user's security rewrites are applied
+/// before the key is computed, so cached results never cross security boundaries.
+pub async fn cached_query_engine_for_user(
+    user_id: &Uuid,
+    data_source_id: &Uuid,
+    sql: &str,
+    limit: Option<i64>,
+    options: QueryCacheOptions,
+) -> Result<QueryResult> {
+    let secured_sql = secure_sql_for_user(user_id, data_source_id, sql).await?;
+    let data_source_type = get_data_source_type(data_source_id).await?;
+
+    cached_secured_query(data_source_id, data_source_type.to_str(), &secured_sql, limit, options)
+        .await
+}
+
+/// Runs SQL that already carries the user's security rewrites through the cache.
+pub(crate) async fn cached_secured_query(
+    data_source_id: &Uuid,
+    data_source_dialect: &str,
+    secured_sql: &str,
+    limit: Option<i64>,
+    options: QueryCacheOptions,
+) -> Result<QueryResult> {
+    let key = query_cache_key(
+        options.scope.as_deref(),
+        data_source_id,
+        data_source_dialect,
+        secured_sql,
+        limit,
+    );
+
+    if !options.force_refresh {
+        if let Some(result) = get_cached_result(&key).await {
+            tracing::debug!("Query cache hit for key {}", key);
+            return Ok(result);
+        }
+    }
+
+    let result = query_engine(data_source_id, secured_sql, limit).await?;
+
+    if !options.ttl.is_zero() {
+        set_cached_result(&key, &result, options.ttl).await;
+    }
+
+    Ok(result)
+}
+
+/// Drops every cached result in `scope`, e.g. after a metric's SQL changed.
+pub async fn invalidate_query_cache_scope(scope: &str) -> Result<()> {
+    let prefix = format!("{}:{}:", CACHE_KEY_PREFIX, scope);
+
+    if let Ok(mut local) = LOCAL_CACHE.lock() {
+        local.retain(|key, _| !key.starts_with(&prefix));
+    }
+
+    let Some(pool) = try_get_redis_pool() else {
+        return Ok(());
+    };
+
+    let mut conn = pool
+        .get()
+        .await
+        .map_err(|e| anyhow!("Error getting redis connection: {}", e))?;
+
+    let keys: Vec<String> = {
+        let mut iter = conn
+            .scan_match::<_, String>(format!("{}*", prefix))
+            .await
+            .map_err(|e| anyhow!("Error scanning query cache keys: {}", e))?;
+        let mut keys = Vec::new();
+        while let Some(key) = iter.next_item().await {
+            keys.push(key);
+        }
+        keys
+    };
+
+    if !keys.is_empty() {
+        conn.del::<_, ()>(keys)
+            .await
+            .map_err(|e| anyhow!("Error deleting query cache keys: {}", e))?;
+    }
+
+    Ok(())
+}
+
+/// Builds the cache key from the canonical SQL text, parsed with the data source's dialect.
+/// Statements that fail to parse are keyed by their exact text, so formatting differences
+/// miss the cache rather than risk two queries sharing an entry.
+pub fn query_cache_key(
+    scope: Option<&str>,
+    data_source_id: &Uuid,
+    data_source_dialect: &str,
+    sql: &str,
+    limit: Option<i64>,
+) -> String {
+    let canonical_sql = match Parser::parse_sql(get_dialect(data_source_dialect), sql) {
+        Ok(statements) => statements
+            .iter()
+            .map(|statement| statement.to_string())
+            .collect::<Vec<_>>()
+            .join("; "),
+        Err(_) => sql.to_string(),
+    };
+
+    let mut hasher = Sha256::new();
+    hasher.update(data_source_id.as_bytes());
+    hasher.update(limit.unwrap_or(-1).to_le_bytes());
+    hasher.update(canonical_sql.as_bytes());
+
+    format!(
+        "{}:{}:{:x}",
+        CACHE_KEY_PREFIX,
+        scope.unwrap_or(DEFAULT_SCOPE),
+        hasher.finalize()
+    )
+}
+
+async fn get_cached_result(key: &str) -> Option<QueryResult> {
+    if let Some(pool) = try_get_redis_pool() {
+        match pool.get().await {
+            Ok(mut conn) => match conn.get::<_, Option<Vec<u8>>>(key).await {
+                Ok(Some(bytes)) => match serde_json::from_slice::<CachedQueryResult>(&bytes) {
+                    Ok(cached) => return Some(cached.into()),
+                    Err(e) => tracing::warn!("Discarding unreadable query cache entry: {}", e),
+                },
+                Ok(None) => return None,
+                Err(e) => tracing::warn!("Error reading query cache from redis: {}", e),
+            },
+            Err(e) => tracing::warn!("Error getting redis connection for query cache: {}", e),
+        }
+    }
+
+    let mut local = LOCAL_CACHE.lock().ok()?;
+    match local.get(key) {
+        Some((expires_at, result)) if *expires_at > Instant::now() => Some(result.clone()),
+        Some(_) => {
+            local.remove(key);
+            None
+        }
+        None => None,
+    }
+}
+
+async fn set_cached_result(key: &str, result: &QueryResult, ttl: Duration) {
+    if let Some(pool) = try_get_redis_pool() {
+        let bytes = match serde_json::to_vec(&CachedQueryResult::from(result)) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                tracing::warn!("Error serializing query cache entry: {}", e);
+                return;
+            }
+        };
+
+        match pool.get().await {
+            Ok(mut conn) => match conn.set_ex::<_, _, ()>(key, bytes, ttl.as_secs().max(1)).await {
+                Ok(()) => return,
+                Err(e) => tracing::warn!("Error writing query cache to redis: {}", e),
+            },
+            Err(e) => tracing::warn!("Error getting redis connection for query cache: {}", e),
+        }
+    }
+
+    let Ok(mut local) = LOCAL_CACHE.lock() else {
+        return;
+    };
+
+    let now = Instant::now();
+    local.retain(|_, (expires_at, _)| *expires_at > now);
+
+    if local.len() >= MAX_LOCAL_ENTRIES {
+        // Evict whatever would have expired first
+        if let Some(oldest) = local
+            .iter()
+            .min_by_key(|(_, (expires_at, _))| *expires_at)
+            .map(|(key, _)| key.clone())
+        {
+            local.remove(&oldest);
+        }
+    }
+
+    local.insert(key.to_string(), (now + ttl, result.clone()));
+}
+
+/// `DataType` serializes untagged, which loses the variant on the way back in (a float can
+/// come back as an `Int2`, a date as `Text`). The cache stores an explicitly tagged copy.
+#[derive(Serialize, Deserialize)]
+struct CachedQueryResult {
+    data: Vec<IndexMap<String, CachedValue>>,
+    metadata: DataMetadata,
+}
+
+#[derive(Serialize, Deserialize)]
+enum CachedValue {
+    Bool(Option<bool>),
+    Bytea(Option<Vec<u8>>),
+    Char(Option<String>),
+    Int8(Option<i64>),
+    Int4(Option<i32>),
+    Int2(Option<i16>),
+    Text(Option<String>),
+    Oid(Option<u32>),
+    Float4(Option<f32>),
+    Float8(Option<f64>),
+    Decimal(Option<Decimal>),
+    Uuid(Option<Uuid>),
+    Timestamp(Option<NaiveDateTime>),
+    Timestamptz(Option<DateTime<Utc>>),
+    Date(Option<NaiveDate>),
+    Time(Option<NaiveTime>),
+    Json(Option<Value>),
+    Unknown(Option<String>),
+    Null,
+}
+
+impl From<&DataType> for CachedValue {
+    fn from(value: &DataType) -> Self {
+        match value.clone() {
+            DataType::Bool(v) => CachedValue::Bool(v),
+            DataType::Bytea(v) => CachedValue::Bytea(v),
+            DataType::Char(v) => CachedValue::Char(v),
+            DataType::Int8(v) => CachedValue::Int8(v),
+            DataType::Int4(v) => CachedValue::Int4(v),
+            DataType::Int2(v) => CachedValue::Int2(v),
+            DataType::Text(v) => CachedValue::Text(v),
+            DataType::Oid(v) => CachedValue::Oid(v),
+            DataType::Float4(v) => CachedValue::Float4(v),
+            DataType::Float8(v) => CachedValue::Float8(v),
+            DataType::Decimal(v) => CachedValue::Decimal(v),
+            DataType::Uuid(v) => CachedValue::Uuid(v),
+            DataType::Timestamp(v) => CachedValue::Timestamp(v),
+            DataType::Timestamptz(v) => CachedValue::Timestamptz(v),
+            DataType::Date(v) => CachedValue::Date(v),
+            DataType::Time(v) => CachedValue::Time(v),
+            DataType::Json(v) => CachedValue::Json(v),
+            DataType::Unknown(v) => CachedValue::Unknown(v),
+            DataType::Null => CachedValue::Null,
+        }
+    }
+}
+
+impl From<CachedValue> for DataType {
+    fn from(value: CachedValue) -> Self {
+        match value {
+            CachedValue::Bool(v) => DataType::Bool(v),
+            CachedValue::Bytea(v) => DataType::Bytea(v),
+            CachedValue::Char(v) => DataType::Char(v),
+            CachedValue::Int8(v) => DataType::Int8(v),
+            CachedValue::Int4(v) => DataType::Int4(v),
+            CachedValue::Int2(v) => DataType::Int2(v),
+            CachedValue::Text(v) => DataType::Text(v),
+            CachedValue::Oid(v) => DataType::Oid(v),
+            CachedValue::Float4(v) => DataType::Float4(v),
+            CachedValue::Float8(v) => DataType::Float8(v),
+            CachedValue::Decimal(v) => DataType::Decimal(v),
+            CachedValue::Uuid(v) => DataType::Uuid(v),
+            CachedValue::Timestamp(v) => DataType::Timestamp(v),
+            CachedValue::Timestamptz(v) => DataType::Timestamptz(v),
+            CachedValue::Date(v) => DataType::Date(v),
+            CachedValue::Time(v) => DataType::Time(v),
+            CachedValue::Json(v) => DataType::Json(v),
+            CachedValue::Unknown(v) => DataType::Unknown(v),
+            CachedValue::Null => DataType::Null,
+        }
+    }
+}
+
+impl From<&QueryResult> for CachedQueryResult {
+    fn from(result: &QueryResult) -> Self {
+        CachedQueryResult {
+            data: result
+                .data
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .map(|(column, value)| (column.clone(), CachedValue::from(value)))
+                        .collect()
+                })
+                .collect(),
+            metadata: result.metadata.clone(),
+        }
+    }
+}
+
+impl From<CachedQueryResult> for QueryResult {
+    fn from(cached: CachedQueryResult) -> Self {
+        QueryResult {
+            data: cached
+                .data
+                .into_iter()
+                .map(|row| {
+                    row.into_iter()
+                        .map(|(column, value)| (column, DataType::from(value)))
+                        .collect()
+                })
+                .collect(),
+            metadata: cached.metadata,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_key_ignores_formatting() {
+        let data_source_id = Uuid::new_v4();
+
+        let a = query_cache_key(None, &data_source_id, "postgres", "select id from orders where id = 1", None);
+        let b = query_cache_key(
+            None,
+            &data_source_id,
+            "postgres",
+            "SELECT  id\n  FROM orders\nWHERE id = 1",
+            None,
+        );
+
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_cache_key_keeps_string_literals_apart() {
+        let data_source_id = Uuid::new_v4();
+        let key = |sql: &str| query_cache_key(None, &data_source_id, "mysql", sql, None);
+
+        // Backslash escapes only parse with the MySQL dialect
+        assert_ne!(
+            key(r"SELECT id FROM customers WHERE name = 'a  b' AND note = 'it\'s'"),
+            key(r"SELECT id FROM customers WHERE name = 'a b' AND note = 'it\'s'")
+        );
+
+        // Unparseable SQL is keyed by its exact text
+        assert_ne!(
+            key("SELECT id FROM customers WHERE name = 'a  b' QUALIFY ??"),
+            key("SELECT id FROM customers WHERE name = 'a b' QUALIFY ??")
+        );
+    }
+
+    #[test]
+    fn test_cache_key_varies_by_source_limit_and_scope() {
+        let data_source_id = Uuid::new_v4();
+        let sql = "SELECT id FROM orders";
+
+        let base = query_cache_key(None, &data_source_id, "postgres", sql, Some(10));
+
+        assert_ne!(base, query_cache_key(None, &Uuid::new_v4(), "postgres", sql, Some(10)));
+        assert_ne!(base, query_cache_key(None, &data_source_id, "postgres", sql, Some(11)));
+        assert_ne!(base, query_cache_key(Some("metric:1"), &data_source_id, "postgres", sql, Some(10)));
+        assert!(query_cache_key(Some("metric:1"), &data_source_id, "postgres", sql, None)
+            .starts_with("query_cache:metric:1:"));
+    }
+
+    #[test]
+    fn test_cached_value_round_trip_keeps_types() {
+        let mut row = IndexMap::new();
+        row.insert("amount".to_string(), DataType::Float8(Some(0.1)));
+        row.insert("day".to_string(), DataType::Date(NaiveDate::from_ymd_opt(2024, 1, 2)));
+        row.insert("count".to_string(), DataType::Int8(Some(7)));
+
+        let result = QueryResult {
+            data: vec![row.clone()],
+            metadata: DataMetadata {
+                column_count: 3,
+                row_count: 1,
+                column_metadata: vec![],
+            },
+        };
+
+        let bytes = serde_json::to_vec(&CachedQueryResult::from(&result)).unwrap();
+        let restored: QueryResult = serde_json::from_slice::<CachedQueryResult>(&bytes)
+            .unwrap()
+            .into();
+
+        assert_eq!(restored.data[0], row);
+    }
+}
@@ -0,0 +1,783 @@
+//! Compiles [`SemanticQuery`]s into SQL for a data source.
+//!
+//! Metrics, dimensions and filters are resolved against the models, the models they touch
+//! are joined along the shortest path of declared relationships, and the result is
+//! rendered in the data source's dialect.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::{anyhow, bail, Result};
+use database::enums::DataSourceType;
+use serde_json::Value;
+
+use crate::dialect::{literal, quote_identifier, truncate_to_grain, uses_top};
+use crate::expr::{tokenize, Token};
+use crate::joins::{join_edges, JoinEdge, JoinTree};
+use crate::models::{Argument, Model};
+use crate::query::{CompiledQuery, FilterOperator, FilterRequest, SemanticQuery};
+
+/// Compiles a semantic query against `models` into SQL for `data_source_type`.
+///
+/// Errors describe what in the request can't be resolved, so they can be shown to
+/// whoever wrote it.
+pub fn compile_query(
+    models: &[Model],
+    query: &SemanticQuery,
+    data_source_type: DataSourceType,
+) -> Result<CompiledQuery> {
+    Compiler {
+        models,
+        data_source_type,
+    }
+    .compile(query)
+}
+
+struct Compiler<'a> {
+    models: &'a [Model],
+    data_source_type: DataSourceType,
+}
+
+/// An output column: a dimension or a metric
+struct SelectItem {
+    model: usize,
+    name: String,
+    expr: String,
+}
+
+impl<'a> Compiler<'a> {
+    fn compile(&self, query: &SemanticQuery) -> Result<CompiledQuery> {
+        if query.metrics.is_empty() && query.dimensions.is_empty() && query.time_dimension.is_none() {
+            bail!("A semantic query needs at least one metric or dimension");
+        }
+
+        let mut used_models = BTreeSet::new();
+        let mut dimensions = Vec::new();
+        let mut metrics = Vec::new();
+
+        if let Some(time_dimension) = &query.time_dimension {
+            let (model, column) = self.resolve_column(&time_dimension.dimension, false)?;
+            used_models.insert(model);
+            dimensions.push(SelectItem {
+                model,
+                name: column.to_string(),
+                expr: truncate_to_grain(
+                    &self.column_expr(model, column),
+                    time_dimension.grain,
+                    self.data_source_type,
+                ),
+            });
+        }
+
+        for reference in &query.dimensions {
+            let (model, column) = self.resolve_column(reference, false)?;
+            used_models.insert(model);
+            dimensions.push(SelectItem {
+                model,
+                name: column.to_string(),
+                expr: self.column_expr(model, column),
+            });
+        }
+
+        for request in &query.metrics {
+            let (model, metric) = self.resolve(request.name(), "metric", |m, name| {
+                m.metrics.iter().find(|metric| metric.name.eq_ignore_ascii_case(name))
+            })?;
+            used_models.insert(model);
+            let no_args = BTreeMap::new();
+            let expr = self.rewrite_expr(
+                &metric.expr,
+                model,
+                &format!("Metric '{}'", metric.name),
+                &metric.args,
+                request.args().unwrap_or(&no_args),
+                &mut used_models,
+            )?;
+            metrics.push(SelectItem {
+                model,
+                name: metric.name.clone(),
+                expr,
+            });
+        }
+
+        let mut conditions = Vec::new();
+        for filter in &query.filters {
+            conditions.push(self.filter_condition(filter, &mut used_models)?);
+        }
+
+        let items: Vec<&SelectItem> = dimensions.iter().chain(metrics.iter()).collect();
+        let mut aliases = BTreeSet::new();
+        for item in &items {
+            if !aliases.insert(item.name.to_lowercase()) {
+                bail!(
+                    "More than one requested field is named '{}'; output columns must have unique names",
+                    item.name
+                );
+            }
+        }
+
+        // Metrics are aggregated over the rows of their model, so the first one decides the base
+        let base = metrics.first().or(dimensions.first()).map(|item| item.model).unwrap();
+        let joins = self.plan_joins(base, &used_models)?;
+        self.check_fan_out(&joins, &metrics)?;
+
+        let order_by = query
+            .order_by
+            .iter()
+            .map(|order| {
+                let item = self.find_output(&items, &order.field)?;
+                Ok(format!(
+                    "{}{}",
+                    quote_identifier(&item.name, self.data_source_type),
+                    if order.descending { " DESC" } else { " ASC" }
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut sql = String::from("SELECT ");
+        if let (Some(limit), true) = (query.limit, uses_top(self.data_source_type)) {
+            sql.push_str(&format!("TOP {} ", limit));
+        }
+        sql.push_str(
+            &items
+                .iter()
+                .map(|item| {
+                    format!(
+                        "{} AS {}",
+                        item.expr,
+                        quote_identifier(&item.name, self.data_source_type)
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(", "),
+        );
+        sql.push_str(&format!("\nFROM {}", self.table_expr(base)));
+
+        for edge in &joins {
+            sql.push_str(&format!(
+                "\n{} {} ON {} = {}",
+                self.join_keyword(edge)?,
+                self.table_expr(edge.to),
+                self.column_expr(edge.from, &edge.from_col),
+                self.column_expr(edge.to, &edge.to_col)
+            ));
+        }
+
+        if !conditions.is_empty() {
+            sql.push_str(&format!("\nWHERE {}", conditions.join(" AND ")));
+        }
+        if !dimensions.is_empty() {
+            sql.push_str(&format!(
+                "\nGROUP BY {}",
+                dimensions
+                    .iter()
+                    .map(|item| item.expr.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        if !order_by.is_empty() {
+            sql.push_str(&format!("\nORDER BY {}", order_by.join(", ")));
+        }
+        if let (Some(limit), false) = (query.limit, uses_top(self.data_source_type)) {
+            sql.push_str(&format!("\nLIMIT {}", limit));
+        }
+
+        Ok(CompiledQuery {
+            sql,
+            columns: items.iter().map(|item| item.name.clone()).collect(),
+            models: std::iter::once(base)
+                .chain(joins.iter().map(|edge| edge.to))
+                .map(|model| self.models[model].name.clone())
+                .collect(),
+        })
+    }
+
+    fn find_model(&self, name: &str) -> Option<usize> {
+        crate::joins::find_model(self.models, name)
+    }
+
+    /// Resolves `model.name` or a bare `name` that only one model defines
+    fn resolve<T>(
+        &self,
+        reference: &str,
+        kind: &str,
+        find: impl Fn(&'a Model, &str) -> Option<&'a T>,
+    ) -> Result<(usize, &'a T)> {
+        let models: &'a [Model] = self.models;
+
+        if let Some((model_name, name)) = reference.split_once('.') {
+            let model = self
+                .find_model(model_name)
+                .ok_or_else(|| anyhow!("Unknown model '{}' in '{}'", model_name, reference))?;
+            let found = find(&models[model], name)
+                .ok_or_else(|| anyhow!("Model '{}' has no {} '{}'", models[model].name, kind, name))?;
+            return Ok((model, found));
+        }
+
+        let mut matches = models
+            .iter()
+            .enumerate()
+            .filter_map(|(i, model)| find(model, reference).map(|found| (i, found)));
+        match (matches.next(), matches.next()) {
+            (Some(found), None) => Ok(found),
+            (None, _) => Err(anyhow!("Unknown {} '{}'", kind, reference)),
+            (Some((first, _)), Some((second, _))) => Err(anyhow!(
+                "{} '{}' is defined by more than one model (e.g. '{}' and '{}'); reference it as model.{}",
+                capitalize(kind),
+                reference,
+                self.models[first].name,
+                self.models[second].name,
+                reference
+            )),
+        }
+    }
+
+    /// Resolves a dimension, or a dimension or measure if `allow_measures` is set
+    fn resolve_column(&self, reference: &str, allow_measures: bool) -> Result<(usize, &'a str)> {
+        let kind = if allow_measures { "dimension or measure" } else { "dimension" };
+        self.resolve(reference, kind, |model, name| {
+            find_column(model, name, allow_measures)
+        })
+        .map(|(model, column)| (model, column.as_str()))
+    }
+
+    fn model_alias(&self, model: usize) -> String {
+        quote_identifier(&self.models[model].name, self.data_source_type)
+    }
+
+    fn column_expr(&self, model: usize, column: &str) -> String {
+        format!(
+            "{}.{}",
+            self.model_alias(model),
+            quote_identifier(column, self.data_source_type)
+        )
+    }
+
+    fn table_expr(&self, model: usize) -> String {
+        let m = &self.models[model];
+        let table = [m.database.as_deref(), m.schema.as_deref(), Some(m.name.as_str())]
+            .into_iter()
+            .flatten()
+            .map(|part| quote_identifier(part, self.data_source_type))
+            .collect::<Vec<_>>()
+            .join(".");
+        let alias = self.model_alias(model);
+        if table == alias {
+            table
+        } else {
+            format!("{} AS {}", table, alias)
+        }
+    }
+
+    fn filter_condition(
+        &self,
+        filter: &FilterRequest,
+        used_models: &mut BTreeSet<usize>,
+    ) -> Result<String> {
+        match filter {
+            FilterRequest::Named { filter, args } => {
+                let (model, definition) = self.resolve(filter, "filter", |m, name| {
+                    m.filters.iter().find(|f| f.name.eq_ignore_ascii_case(name))
+                })?;
+                used_models.insert(model);
+                let expr = self.rewrite_expr(
+                    &definition.expr,
+                    model,
+                    &format!("Filter '{}'", definition.name),
+                    &definition.args,
+                    args,
+                    used_models,
+                )?;
+                Ok(format!("({})", expr))
+            }
+            FilterRequest::Condition {
+                field,
+                operator,
+                values,
+            } => {
+                let (model, column) = self.resolve_column(field, true)?;
+                used_models.insert(model);
+                let column = self.column_expr(model, column);
+                let literals = values
+                    .iter()
+                    .map(|value| literal(value, self.data_source_type))
+                    .collect::<Result<Vec<_>>>()?;
+
+                let expected = match operator {
+                    FilterOperator::IsNull | FilterOperator::IsNotNull => Some(0),
+                    FilterOperator::In | FilterOperator::NotIn => None,
+                    FilterOperator::Between => Some(2),
+                    _ => Some(1),
+                };
+                match expected {
+                    Some(count) if literals.len() != count => bail!(
+                        "Filter on '{}' with operator {:?} takes {} value(s), got {}",
+                        field,
+                        operator,
+                        count,
+                        literals.len()
+                    ),
+                    None if literals.is_empty() => {
+                        bail!("Filter on '{}' with operator {:?} needs at least one value", field, operator)
+                    }
+                    _ => {}
+                }
+                if expected == Some(1) && values[0].is_null() {
+                    bail!("Filter on '{}' compares with null; use is_null or is_not_null", field);
+                }
+
+                Ok(match operator {
+                    FilterOperator::Eq => format!("{} = {}", column, literals[0]),
+                    FilterOperator::Neq => format!("{} <> {}", column, literals[0]),
+                    FilterOperator::Gt => format!("{} > {}", column, literals[0]),
+                    FilterOperator::Gte => format!("{} >= {}", column, literals[0]),
+                    FilterOperator::Lt => format!("{} < {}", column, literals[0]),
+                    FilterOperator::Lte => format!("{} <= {}", column, literals[0]),
+                    FilterOperator::In => format!("{} IN ({})", column, literals.join(", ")),
+                    FilterOperator::NotIn => format!("{} NOT IN ({})", column, literals.join(", ")),
+                    FilterOperator::Between => {
+                        format!("{} BETWEEN {} AND {}", column, literals[0], literals[1])
+                    }
+                    FilterOperator::IsNull => format!("{} IS NULL", column),
+                    FilterOperator::IsNotNull => format!("{} IS NOT NULL", column),
+                })
+            }
+        }
+    }
+
+    /// Rewrites a metric or filter expression so it can run against the joined models:
+    /// bare columns of `owner` and `model.column` references are qualified with the model
+    /// aliases, and `{arg}` placeholders are replaced with the request's values.
+    fn rewrite_expr(
+        &self,
+        expr: &str,
+        owner: usize,
+        context: &str,
+        declared_args: &[Argument],
+        args: &BTreeMap<String, Value>,
+        used_models: &mut BTreeSet<usize>,
+    ) -> Result<String> {
+        if let Some(unknown) = args
+            .keys()
+            .find(|name| !declared_args.iter().any(|arg| &arg.name == *name))
+        {
+            bail!("{} has no argument '{}'", context, unknown);
+        }
+
+        let tokens = tokenize(expr).map_err(|e| anyhow!("{} has an {}", context, e))?;
+        let mut out = String::with_capacity(expr.len());
+
+        for token in tokens {
+            match token {
+                Token::Placeholder(name) => {
+                    out.push_str(&self.argument_literal(&name, context, declared_args, args)?)
+                }
+                Token::Ident {
+                    qualifier: Some(qualifier),
+                    name: column,
+                    ..
+                } => match self.find_model(&qualifier) {
+                    Some(model) => {
+                        let name = find_column(&self.models[model], &column, true).ok_or_else(|| {
+                            anyhow!(
+                                "{} references '{}.{}', but model '{}' has no such dimension or measure",
+                                context,
+                                qualifier,
+                                column,
+                                self.models[model].name
+                            )
+                        })?;
+                        used_models.insert(model);
+                        out.push_str(&self.column_expr(model, name));
+                    }
+                    None => out.push_str(&format!("{}.{}", qualifier, column)),
+                },
+                Token::Ident {
+                    qualifier: None,
+                    name: word,
+                    is_function,
+                } => match find_column(&self.models[owner], &word, true) {
+                    Some(name) if !is_function => out.push_str(&self.column_expr(owner, name)),
+                    _ => out.push_str(&word),
+                },
+                Token::Text(text) => out.push_str(&text),
+            }
+        }
+
+        Ok(out)
+    }
+
+    fn argument_literal(
+        &self,
+        name: &str,
+        context: &str,
+        declared_args: &[Argument],
+        args: &BTreeMap<String, Value>,
+    ) -> Result<String> {
+        let declared = declared_args
+            .iter()
+            .find(|arg| arg.name == name)
+            .ok_or_else(|| anyhow!("{} uses '{{{}}}', which isn't one of its arguments", context, name))?;
+        let value = args
+            .get(name)
+            .ok_or_else(|| anyhow!("{} requires a value for argument '{}'", context, name))?;
+
+        let numeric = matches!(
+            declared.type_.to_lowercase().as_str(),
+            "integer" | "int" | "bigint" | "number" | "numeric" | "decimal" | "float" | "double"
+        );
+        if numeric && !value.is_number() {
+            bail!(
+                "Argument '{}' of {} must be a number, got {}",
+                name,
+                context.to_lowercase(),
+                value
+            );
+        }
+
+        literal(value, self.data_source_type)
+    }
+
+    /// Finds the joins needed to reach every used model from `base`, in join order
+    fn plan_joins(&self, base: usize, used_models: &BTreeSet<usize>) -> Result<Vec<JoinEdge>> {
+        if used_models.iter().all(|&model| model == base) {
+            return Ok(Vec::new());
+        }
+
+        let edges = join_edges(self.models);
+        let tree = JoinTree::new(&edges, self.models.len(), base);
+
+        let mut needed = vec![false; self.models.len()];
+        for &model in used_models {
+            if !tree.reaches(model) {
+                bail!(
+                    "Model '{}' has no relationship path to model '{}'",
+                    self.models[base].name,
+                    self.models[model].name
+                );
+            }
+            let mut current = model;
+            while let Some(index) = tree.parent[current] {
+                if needed[current] {
+                    break;
+                }
+                needed[current] = true;
+                current = edges[index].from;
+            }
+        }
+
+        Ok(tree
+            .order
+            .iter()
+            .filter(|&&model| needed[model])
+            .filter_map(|&model| tree.parent[model])
+            .map(|index| edges[index].clone())
+            .collect())
+    }
+
+    /// Rejects joins that would repeat rows of a model whose metrics are being aggregated
+    fn check_fan_out(&self, joins: &[JoinEdge], metrics: &[SelectItem]) -> Result<()> {
+        for (position, edge) in joins.iter().enumerate() {
+            if !edge.fans_out {
+                continue;
+            }
+
+            // Models joined through this edge, whose rows aren't repeated by it
+            let mut downstream = BTreeSet::from([edge.to]);
+            for later in &joins[position + 1..] {
+                if downstream.contains(&later.from) {
+                    downstream.insert(later.to);
+                }
+            }
+
+            if let Some(metric) = metrics.iter().find(|m| !downstream.contains(&m.model)) {
+                bail!(
+                    "Metric '{}' would be inflated by joining model '{}' through relationship '{}', \
+                     which matches several rows per row of '{}'; query it separately",
+                    metric.name,
+                    self.models[edge.to].name,
+                    edge.relationship,
+                    self.models[edge.from].name
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn join_keyword(&self, edge: &JoinEdge) -> Result<&'static str> {
+        let join_type = edge.join_type.as_deref().map(|t| t.trim().to_uppercase());
+        match join_type.as_deref() {
+            None | Some("LEFT") => Ok("LEFT JOIN"),
+            Some("INNER") => Ok("INNER JOIN"),
+            Some("RIGHT") => Ok("RIGHT JOIN"),
+            Some("FULL")
+                if matches!(
+                    self.data_source_type,
+                    DataSourceType::MySql | DataSourceType::Mariadb
+                ) =>
+            {
+                bail!(
+                    "Relationship '{}' uses a FULL join, which {} doesn't support",
+                    edge.relationship,
+                    self.data_source_type.to_str()
+                )
+            }
+            Some("FULL") => Ok("FULL JOIN"),
+            Some(other) => bail!(
+                "Relationship '{}' has unsupported join type '{}'; use LEFT, INNER, RIGHT or FULL",
+                edge.relationship,
+                other
+            ),
+        }
+    }
+
+    /// Finds the requested metric or dimension an `order_by` entry refers to
+    fn find_output<'i>(&self, items: &[&'i SelectItem], field: &str) -> Result<&'i SelectItem> {
+        let found = match field.split_once('.') {
+            Some((model_name, name)) => {
+                let model = self.find_model(model_name);
+                items.iter().find(|item| {
+                    Some(item.model) == model && item.name.eq_ignore_ascii_case(name)
+                })
+            }
+            None => items.iter().find(|item| item.name.eq_ignore_ascii_case(field)),
+        };
+        found.copied().ok_or_else(|| {
+            anyhow!(
+                "Can only order by requested metrics and dimensions; '{}' isn't one of them",
+                field
+            )
+        })
+    }
+}
+
+pub(crate) fn find_column<'m>(model: &'m Model, name: &str, allow_measures: bool) -> Option<&'m String> {
+    model
+        .dimensions
+        .iter()
+        .map(|dimension| &dimension.name)
+        .chain(
+            model
+                .measures
+                .iter()
+                .filter(|_| allow_measures)
+                .map(|measure| &measure.name),
+        )
+        .find(|column| column.eq_ignore_ascii_case(name))
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::{MetricRequest, OrderBy, TimeDimension, TimeGrain};
+    use serde_json::json;
+
+    fn models() -> Vec<Model> {
+        serde_yaml::from_str(
+            r#"
+- name: orders
+  database: analytics
+  schema: sales
+  dimensions:
+    - name: id
+    - name: customer_id
+    - name: status
+    - name: created_at
+  measures:
+    - name: amount
+  metrics:
+    - name: revenue
+      expr: SUM(amount)
+    - name: large_order_count
+      expr: COUNT(CASE WHEN amount > {min_amount} THEN id END)
+      args:
+        - name: min_amount
+          type: integer
+  filters:
+    - name: completed
+      expr: status = 'completed'
+  relationships:
+    - name: customers
+      source_col: customer_id
+      ref_col: id
+      cardinality: many-to-one
+- name: customers
+  schema: sales
+  dimensions:
+    - name: id
+    - name: region
+  metrics:
+    - name: customer_count
+      expr: COUNT(DISTINCT id)
+  filters:
+    - name: in_region
+      expr: customers.region = {region}
+      args:
+        - name: region
+          type: string
+- name: line_items
+  schema: sales
+  dimensions:
+    - name: order_id
+    - name: sku
+  metrics:
+    - name: item_count
+      expr: COUNT(*)
+  relationships:
+    - name: orders
+      source_col: order_id
+      ref_col: id
+      cardinality: many-to-one
+"#,
+        )
+        .unwrap()
+    }
+
+    fn metric(name: &str) -> MetricRequest {
+        MetricRequest::Name(name.to_string())
+    }
+
+    #[test]
+    fn test_compile_metric_by_related_dimension() {
+        let query = SemanticQuery {
+            metrics: vec![metric("revenue")],
+            dimensions: vec!["region".to_string()],
+            filters: vec![
+                FilterRequest::Named {
+                    filter: "completed".to_string(),
+                    args: BTreeMap::new(),
+                },
+                FilterRequest::Named {
+                    filter: "in_region".to_string(),
+                    args: BTreeMap::from([("region".to_string(), json!("O'Hare"))]),
+                },
+            ],
+            order_by: vec![OrderBy {
+                field: "revenue".to_string(),
+                descending: true,
+            }],
+            limit: Some(10),
+            ..Default::default()
+        };
+
+        let compiled = compile_query(&models(), &query, DataSourceType::Postgres).unwrap();
+        assert_eq!(
+            compiled.sql,
+            "SELECT customers.region AS region, SUM(orders.amount) AS revenue\n\
+             FROM analytics.sales.orders AS orders\n\
+             LEFT JOIN sales.customers AS customers ON orders.customer_id = customers.id\n\
+             WHERE (orders.status = 'completed') AND (customers.region = 'O''Hare')\n\
+             GROUP BY customers.region\n\
+             ORDER BY revenue DESC\n\
+             LIMIT 10"
+        );
+        assert_eq!(compiled.columns, vec!["region", "revenue"]);
+        assert_eq!(compiled.models, vec!["orders", "customers"]);
+    }
+
+    #[test]
+    fn test_compile_time_grain_and_conditions_for_sql_server() {
+        let query = SemanticQuery {
+            metrics: vec![MetricRequest::WithArgs {
+                name: "large_order_count".to_string(),
+                args: BTreeMap::from([("min_amount".to_string(), json!(100))]),
+            }],
+            time_dimension: Some(TimeDimension {
+                dimension: "created_at".to_string(),
+                grain: TimeGrain::Month,
+            }),
+            filters: vec![FilterRequest::Condition {
+                field: "orders.status".to_string(),
+                operator: FilterOperator::In,
+                values: vec![json!("completed"), json!("shipped")],
+            }],
+            limit: Some(5),
+            ..Default::default()
+        };
+
+        let compiled = compile_query(&models(), &query, DataSourceType::SqlServer).unwrap();
+        assert_eq!(
+            compiled.sql,
+            "SELECT TOP 5 DATEADD(month, DATEDIFF(month, 0, orders.created_at), 0) AS created_at, \
+             COUNT(CASE WHEN orders.amount > 100 THEN orders.id END) AS large_order_count\n\
+             FROM analytics.sales.orders AS orders\n\
+             WHERE orders.status IN ('completed', 'shipped')\n\
+             GROUP BY DATEADD(month, DATEDIFF(month, 0, orders.created_at), 0)"
+        );
+    }
+
+    #[test]
+    fn test_compile_rejects_fan_out() {
+        // Each order has many line items, so joining them would count revenue repeatedly
+        let query = SemanticQuery {
+            metrics: vec![metric("revenue")],
+            dimensions: vec!["sku".to_string()],
+            ..Default::default()
+        };
+        let err = compile_query(&models(), &query, DataSourceType::Postgres).unwrap_err();
+        assert!(err.to_string().contains("would be inflated"), "{}", err);
+
+        // The same holds walking a many-to-one relationship backwards
+        let query = SemanticQuery {
+            metrics: vec![metric("customer_count")],
+            dimensions: vec!["status".to_string()],
+            ..Default::default()
+        };
+        let err = compile_query(&models(), &query, DataSourceType::Postgres).unwrap_err();
+        assert!(err.to_string().contains("relationship 'orders.customers'"), "{}", err);
+
+        // Metrics of the many side can be broken down by dimensions any number of joins away
+        let query = SemanticQuery {
+            metrics: vec![metric("item_count")],
+            dimensions: vec!["region".to_string()],
+            ..Default::default()
+        };
+        let compiled = compile_query(&models(), &query, DataSourceType::Postgres).unwrap();
+        assert_eq!(
+            compiled.sql,
+            "SELECT customers.region AS region, COUNT(*) AS item_count\n\
+             FROM sales.line_items AS line_items\n\
+             LEFT JOIN analytics.sales.orders AS orders ON line_items.order_id = orders.id\n\
+             LEFT JOIN sales.customers AS customers ON orders.customer_id = customers.id\n\
+             GROUP BY customers.region"
+        );
+    }
+
+    #[test]
+    fn test_compile_reports_unresolvable_requests() {
+        let compile = |query: SemanticQuery| {
+            compile_query(&models(), &query, DataSourceType::Postgres)
+                .unwrap_err()
+                .to_string()
+        };
+
+        assert!(compile(SemanticQuery::default()).contains("at least one metric or dimension"));
+        assert!(compile(SemanticQuery {
+            dimensions: vec!["id".to_string()],
+            ..Default::default()
+        })
+        .contains("reference it as model.id"));
+        assert!(compile(SemanticQuery {
+            metrics: vec![metric("large_order_count")],
+            ..Default::default()
+        })
+        .contains("requires a value for argument 'min_amount'"));
+        assert!(compile(SemanticQuery {
+            metrics: vec![metric("revenue")],
+            order_by: vec![OrderBy {
+                field: "status".to_string(),
+                descending: false,
+            }],
+            ..Default::default()
+        })
+        .contains("Can only order by"));
+    }
+}
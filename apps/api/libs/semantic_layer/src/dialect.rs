@@ -0,0 +1,137 @@
+//! Dialect-specific SQL fragments for the warehouses a data source can point at.
+
+use anyhow::{anyhow, Result};
+use database::enums::DataSourceType;
+use serde_json::Value;
+
+use crate::query::TimeGrain;
+
+/// Quotes an identifier unless it's a plain name of letters, digits and underscores.
+///
+/// Plain names are left unquoted so warehouses that fold case (Snowflake, Postgres)
+/// resolve them the same way they resolve the hand-written SQL in the models.
+pub fn quote_identifier(name: &str, data_source_type: DataSourceType) -> String {
+    if is_plain_identifier(name) {
+        return name.to_string();
+    }
+
+    let (open, close) = match data_source_type {
+        DataSourceType::BigQuery
+        | DataSourceType::Databricks
+        | DataSourceType::MySql
+        | DataSourceType::Mariadb
+        | DataSourceType::ClickHouse => ('`', '`'),
+        DataSourceType::SqlServer => ('[', ']'),
+        _ => ('"', '"'),
+    };
+    let escaped = name.replace(close, &format!("{close}{close}"));
+    format!("{open}{escaped}{close}")
+}
+
+pub fn is_plain_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Truncates a date or timestamp expression to the start of its time grain.
+/// Weeks start on Monday everywhere.
+pub fn truncate_to_grain(expr: &str, grain: TimeGrain, data_source_type: DataSourceType) -> String {
+    match data_source_type {
+        DataSourceType::BigQuery => {
+            let part = match grain {
+                TimeGrain::Week => "ISOWEEK",
+                TimeGrain::Day => "DAY",
+                TimeGrain::Month => "MONTH",
+                TimeGrain::Quarter => "QUARTER",
+                TimeGrain::Year => "YEAR",
+            };
+            format!("DATE_TRUNC({expr}, {part})")
+        }
+        DataSourceType::MySql | DataSourceType::Mariadb => match grain {
+            TimeGrain::Day => format!("DATE({expr})"),
+            TimeGrain::Week => format!("DATE_SUB(DATE({expr}), INTERVAL WEEKDAY({expr}) DAY)"),
+            TimeGrain::Month => {
+                format!("DATE_SUB(DATE({expr}), INTERVAL (DAYOFMONTH({expr}) - 1) DAY)")
+            }
+            TimeGrain::Quarter => {
+                format!("MAKEDATE(YEAR({expr}), 1) + INTERVAL (QUARTER({expr}) - 1) QUARTER")
+            }
+            TimeGrain::Year => format!("MAKEDATE(YEAR({expr}), 1)"),
+        },
+        // DATETRUNC needs SQL Server 2022, so count whole units from day 0 instead
+        DataSourceType::SqlServer => match grain {
+            // DATEDIFF counts weeks from Sundays; step back to Monday whatever DATEFIRST is
+            TimeGrain::Week => format!(
+                "DATEADD(day, -((DATEPART(weekday, {expr}) + @@DATEFIRST + 5) % 7), CAST({expr} AS date))"
+            ),
+            _ => {
+                let part = grain.as_str();
+                format!("DATEADD({part}, DATEDIFF({part}, 0, {expr}), 0)")
+            }
+        },
+        DataSourceType::ClickHouse => {
+            let function = match grain {
+                TimeGrain::Day => "toStartOfDay",
+                TimeGrain::Week => "toMonday",
+                TimeGrain::Month => "toStartOfMonth",
+                TimeGrain::Quarter => "toStartOfQuarter",
+                TimeGrain::Year => "toStartOfYear",
+            };
+            format!("{function}({expr})")
+        }
+        DataSourceType::Postgres
+        | DataSourceType::Redshift
+        | DataSourceType::Supabase
+        | DataSourceType::Snowflake
+        | DataSourceType::Databricks
+        | DataSourceType::DuckDb => format!("DATE_TRUNC('{}', {expr})", grain.as_str()),
+    }
+}
+
+/// Renders a JSON value from a request as a SQL literal
+pub fn literal(value: &Value, data_source_type: DataSourceType) -> Result<String> {
+    sql_analyzer::literal(value, data_source_type.to_str()).map_err(|e| anyhow!("{}", e))
+}
+
+/// Quotes a string as a SQL literal, escaping it the way the warehouse expects
+pub fn string_literal(value: &str, data_source_type: DataSourceType) -> String {
+    sql_analyzer::string_literal(value, data_source_type.to_str())
+}
+
+/// Whether the dialect limits rows with `SELECT TOP n` rather than a trailing `LIMIT n`
+pub fn uses_top(data_source_type: DataSourceType) -> bool {
+    matches!(data_source_type, DataSourceType::SqlServer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_string_literal_escapes_per_dialect() {
+        let value = json!(r"\' OR 1=1 --");
+        let cases = [
+            (DataSourceType::BigQuery, r"'\\\' OR 1=1 --'"),
+            (DataSourceType::Databricks, r"'\\\' OR 1=1 --'"),
+            (DataSourceType::MySql, r"'\\\' OR 1=1 --'"),
+            (DataSourceType::Mariadb, r"'\\\' OR 1=1 --'"),
+            (DataSourceType::ClickHouse, r"'\\\' OR 1=1 --'"),
+            (DataSourceType::Snowflake, r"'\\'' OR 1=1 --'"),
+            (DataSourceType::Redshift, r"'\\'' OR 1=1 --'"),
+            (DataSourceType::Postgres, r"'\'' OR 1=1 --'"),
+            (DataSourceType::Supabase, r"'\'' OR 1=1 --'"),
+            (DataSourceType::DuckDb, r"'\'' OR 1=1 --'"),
+            (DataSourceType::SqlServer, r"'\'' OR 1=1 --'"),
+        ];
+        for (data_source_type, expected) in cases {
+            assert_eq!(
+                literal(&value, data_source_type).unwrap(),
+                expected,
+                "{:?}",
+                data_source_type
+            );
+        }
+    }
+}
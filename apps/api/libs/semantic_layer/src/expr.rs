@@ -0,0 +1,127 @@
+//! Tokenizer for the SQL snippets in metric and filter `expr`s.
+//!
+//! It only understands as much SQL as the semantic layer needs: column references,
+//! `{arg}` placeholders, and string literals and quoted identifiers to skip over.
+
+use anyhow::{anyhow, Result};
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Token {
+    /// Anything passed through as written: literals, numbers, operators, whitespace
+    Text(String),
+    /// An `{arg}` placeholder
+    Placeholder(String),
+    /// A bare `name` or a `qualifier.name`
+    Ident {
+        qualifier: Option<String>,
+        name: String,
+        /// Followed by `(`, so a function rather than a column
+        is_function: bool,
+    },
+}
+
+pub(crate) fn tokenize(expr: &str) -> Result<Vec<Token>> {
+    let chars: Vec<char> = expr.chars().collect();
+    let mut tokens = Vec::new();
+    let mut text = String::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if c == '\'' {
+            let end = string_literal_end(&chars, i)
+                .ok_or_else(|| anyhow!("unterminated string literal"))?;
+            text.extend(&chars[i..=end]);
+            i = end + 1;
+        } else if c == '"' || c == '`' {
+            let end = chars[i + 1..]
+                .iter()
+                .position(|&ch| ch == c)
+                .map(|offset| i + 1 + offset)
+                .unwrap_or(chars.len() - 1);
+            text.extend(&chars[i..=end]);
+            i = end + 1;
+        } else if c == '{' {
+            let Some(offset) = chars[i..].iter().position(|&ch| ch == '}') else {
+                text.push(c);
+                i += 1;
+                continue;
+            };
+            flush(&mut tokens, &mut text);
+            let name: String = chars[i + 1..i + offset].iter().collect();
+            tokens.push(Token::Placeholder(name.trim().to_string()));
+            i += offset + 1;
+        } else if c.is_ascii_digit() {
+            let end = scan(&chars, i, |ch| {
+                ch.is_ascii_alphanumeric() || ch == '.' || ch == '_'
+            });
+            text.extend(&chars[i..end]);
+            i = end;
+        } else if is_ident_start(c) {
+            flush(&mut tokens, &mut text);
+            let mut end = scan(&chars, i, is_ident_char);
+            let mut qualifier = None;
+            let mut name: String = chars[i..end].iter().collect();
+
+            if end + 1 < chars.len() && chars[end] == '.' && is_ident_start(chars[end + 1]) {
+                let name_end = scan(&chars, end + 1, is_ident_char);
+                qualifier = Some(name);
+                name = chars[end + 1..name_end].iter().collect();
+                end = name_end;
+            }
+
+            let is_function = chars[end..]
+                .iter()
+                .find(|ch| !ch.is_whitespace())
+                .is_some_and(|&ch| ch == '(');
+            tokens.push(Token::Ident {
+                qualifier,
+                name,
+                is_function,
+            });
+            i = end;
+        } else {
+            text.push(c);
+            i += 1;
+        }
+    }
+
+    flush(&mut tokens, &mut text);
+    Ok(tokens)
+}
+
+fn flush(tokens: &mut Vec<Token>, text: &mut String) {
+    if !text.is_empty() {
+        tokens.push(Token::Text(std::mem::take(text)));
+    }
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_'
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Index of the quote closing the string literal opened at `start`
+fn string_literal_end(chars: &[char], start: usize) -> Option<usize> {
+    let mut i = start + 1;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 2,
+            '\'' if chars.get(i + 1) == Some(&'\'') => i += 2,
+            '\'' => return Some(i),
+            _ => i += 1,
+        }
+    }
+    None
+}
+
+fn scan(chars: &[char], start: usize, matches: impl Fn(char) -> bool) -> usize {
+    chars[start..]
+        .iter()
+        .position(|&ch| !matches(ch))
+        .map_or(chars.len(), |offset| start + offset)
+}
@@ -0,0 +1,125 @@
+//! The join graph the models' relationships declare.
+
+use std::collections::VecDeque;
+
+use crate::models::Model;
+
+/// A join from one model to a related one, from either side's declared relationship
+#[derive(Debug, Clone)]
+pub(crate) struct JoinEdge {
+    pub from: usize,
+    pub to: usize,
+    pub from_col: String,
+    pub to_col: String,
+    pub join_type: Option<String>,
+    pub relationship: String,
+    /// Whether a row of `from` can match several rows of `to`
+    pub fans_out: bool,
+}
+
+pub(crate) fn find_model(models: &[Model], name: &str) -> Option<usize> {
+    models
+        .iter()
+        .position(|model| model.name.eq_ignore_ascii_case(name))
+}
+
+/// Joins are taken from both models' relationships; a model's own declarations come first
+pub(crate) fn join_edges(models: &[Model]) -> Vec<JoinEdge> {
+    let mut forward = Vec::new();
+    let mut reverse = Vec::new();
+
+    for (from, model) in models.iter().enumerate() {
+        for relationship in &model.relationships {
+            let Some(to) = find_model(models, &relationship.name) else {
+                continue;
+            };
+            if to == from {
+                continue;
+            }
+            let cardinality = relationship
+                .cardinality
+                .as_deref()
+                .unwrap_or_default()
+                .to_lowercase()
+                .replace('_', "-");
+
+            forward.push(JoinEdge {
+                from,
+                to,
+                from_col: relationship.source_col.clone(),
+                to_col: relationship.ref_col.clone(),
+                join_type: relationship.type_.clone(),
+                relationship: format!("{}.{}", model.name, relationship.name),
+                fans_out: matches!(cardinality.as_str(), "one-to-many" | "many-to-many"),
+            });
+            reverse.push(JoinEdge {
+                from: to,
+                to: from,
+                from_col: relationship.ref_col.clone(),
+                to_col: relationship.source_col.clone(),
+                join_type: None,
+                relationship: format!("{}.{}", model.name, relationship.name),
+                fans_out: matches!(cardinality.as_str(), "many-to-one" | "many-to-many"),
+            });
+        }
+    }
+
+    forward.extend(reverse);
+    forward
+}
+
+/// The shortest join paths from a base model to every model it can reach
+pub(crate) struct JoinTree {
+    /// The edge each model is reached through
+    pub parent: Vec<Option<usize>>,
+    /// Reachable models, base first, in breadth-first order
+    pub order: Vec<usize>,
+    visited: Vec<bool>,
+}
+
+impl JoinTree {
+    pub fn new(edges: &[JoinEdge], model_count: usize, base: usize) -> Self {
+        // Breadth-first, so every model is reached through the fewest joins
+        let mut parent = vec![None; model_count];
+        let mut visited = vec![false; model_count];
+        let mut order = Vec::new();
+        let mut queue = VecDeque::from([base]);
+        visited[base] = true;
+
+        while let Some(model) = queue.pop_front() {
+            order.push(model);
+            for (index, edge) in edges.iter().enumerate() {
+                if edge.from == model && !visited[edge.to] {
+                    visited[edge.to] = true;
+                    parent[edge.to] = Some(index);
+                    queue.push_back(edge.to);
+                }
+            }
+        }
+
+        Self {
+            parent,
+            order,
+            visited,
+        }
+    }
+
+    pub fn reaches(&self, model: usize) -> bool {
+        self.visited[model]
+    }
+
+    /// Indexes of the edges leading from the base to `model`, in join order
+    pub fn path(&self, edges: &[JoinEdge], model: usize) -> Option<Vec<usize>> {
+        if !self.reaches(model) {
+            return None;
+        }
+        let mut path = Vec::new();
+        let mut current = model;
+        while let Some(index) = self.parent[current] {
+            path.push(index);
+            current = edges[index].from;
+        }
+        path.reverse();
+        Some(path)
+    }
+}
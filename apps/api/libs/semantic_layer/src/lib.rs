@@ -1,2 +1,14 @@
+pub mod compiler;
+pub mod dialect;
+mod expr;
+mod joins;
+pub mod lint;
 pub mod models;
-// Placeholder for semantic_layer library code
+pub mod query;
+
+pub use compiler::compile_query;
+pub use lint::{lint_files, Diagnostic, Severity, SourceFile};
+pub use query::{
+    CompiledQuery, FilterOperator, FilterRequest, MetricRequest, OrderBy, SemanticQuery,
+    TimeDimension, TimeGrain,
+};
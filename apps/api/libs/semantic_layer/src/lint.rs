@@ -0,0 +1,1113 @@
+//! Lints semantic model files for mistakes that otherwise only show up once the models
+//! are queried: broken relationships, ambiguous or fanning-out joins, and expressions
+//! that reference fields or arguments that don't exist.
+//!
+//! Every diagnostic points at the file and lines it's about.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use serde::Serialize;
+use yaml_rust2::parser::{Event, MarkedEventReceiver, Parser};
+use yaml_rust2::scanner::Marker;
+
+use crate::compiler::find_column;
+use crate::expr::{tokenize, Token};
+use crate::joins::{find_model, join_edges, JoinEdge, JoinTree};
+use crate::models::{Argument, Model};
+
+/// A semantic model file to lint
+#[derive(Debug, Clone)]
+pub struct SourceFile {
+    pub path: String,
+    pub content: String,
+}
+
+/// Lines a diagnostic covers. Lines and columns are 1-based.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Span {
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Diagnostic {
+    pub file: String,
+    pub span: Span,
+    pub severity: Severity,
+    /// Stable identifier of the check, e.g. `unknown-column`
+    pub code: &'static str,
+    /// The model the diagnostic is about, if the file parsed
+    pub model: Option<String>,
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}: {}[{}]: {}",
+            self.file, self.span.line, self.span.column, self.severity, self.code, self.message
+        )
+    }
+}
+
+/// Lints a set of model files together, so relationships between them can be checked.
+///
+/// Diagnostics are ordered by file, then by line.
+pub fn lint_files(files: &[SourceFile]) -> Vec<Diagnostic> {
+    let mut linter = Linter {
+        files,
+        spans: Vec::new(),
+        models: Vec::new(),
+        origins: Vec::new(),
+        edges: Vec::new(),
+        diagnostics: Vec::new(),
+    };
+
+    for (index, file) in files.iter().enumerate() {
+        linter.spans.push(SpanIndex::parse(&file.content));
+        match serde_yaml::from_str::<Model>(&file.content) {
+            Ok(model) => {
+                linter.models.push(model);
+                linter.origins.push(index);
+            }
+            Err(e) => {
+                let (line, column) = e.location().map_or((1, 1), |l| (l.line(), l.column()));
+                linter.diagnostics.push(Diagnostic {
+                    file: file.path.clone(),
+                    span: Span {
+                        line,
+                        column,
+                        end_line: line,
+                    },
+                    severity: Severity::Error,
+                    code: "parse-error",
+                    model: None,
+                    message: format!("Invalid semantic model: {}", e),
+                });
+            }
+        }
+    }
+
+    linter.edges = join_edges(&linter.models);
+    linter.check_duplicate_models();
+    for model in 0..linter.models.len() {
+        linter.check_duplicate_fields(model);
+        linter.check_relationships(model);
+        linter.check_expressions(model);
+    }
+    linter.check_join_paths();
+
+    let order: HashMap<&str, usize> = files
+        .iter()
+        .enumerate()
+        .map(|(index, file)| (file.path.as_str(), index))
+        .collect();
+    let mut diagnostics = linter.diagnostics;
+    diagnostics.sort_by_key(|d| (order[d.file.as_str()], d.span.line, d.span.column));
+    diagnostics
+}
+
+/// Bare words SQL expressions use that aren't column references
+const SQL_KEYWORDS: &[&str] = &[
+    "all",
+    "and",
+    "any",
+    "as",
+    "asc",
+    "at",
+    "between",
+    "bigint",
+    "bool",
+    "boolean",
+    "by",
+    "case",
+    "char",
+    "current",
+    "current_date",
+    "current_time",
+    "current_timestamp",
+    "date",
+    "day",
+    "decimal",
+    "desc",
+    "distinct",
+    "double",
+    "else",
+    "end",
+    "epoch",
+    "escape",
+    "exists",
+    "false",
+    "first",
+    "float",
+    "following",
+    "from",
+    "hour",
+    "ilike",
+    "in",
+    "int",
+    "integer",
+    "interval",
+    "is",
+    "last",
+    "like",
+    "minute",
+    "month",
+    "not",
+    "null",
+    "nulls",
+    "numeric",
+    "on",
+    "or",
+    "over",
+    "partition",
+    "preceding",
+    "precision",
+    "quarter",
+    "range",
+    "real",
+    "row",
+    "rows",
+    "second",
+    "similar",
+    "smallint",
+    "some",
+    "string",
+    "text",
+    "then",
+    "time",
+    "timestamp",
+    "timestamptz",
+    "to",
+    "true",
+    "unbounded",
+    "varchar",
+    "week",
+    "when",
+    "where",
+    "within",
+    "year",
+    "zone",
+];
+
+struct Linter<'a> {
+    files: &'a [SourceFile],
+    spans: Vec<SpanIndex>,
+    models: Vec<Model>,
+    /// Index of the file each model was read from
+    origins: Vec<usize>,
+    edges: Vec<JoinEdge>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// Which kind of expression is being checked, for messages
+#[derive(Clone, Copy, PartialEq)]
+enum ExprKind {
+    Metric,
+    Filter,
+}
+
+impl ExprKind {
+    fn label(self) -> &'static str {
+        match self {
+            ExprKind::Metric => "Metric",
+            ExprKind::Filter => "Filter",
+        }
+    }
+}
+
+impl<'a> Linter<'a> {
+    fn report(
+        &mut self,
+        model: usize,
+        path: &str,
+        severity: Severity,
+        code: &'static str,
+        message: String,
+    ) {
+        let file = self.origins[model];
+        self.diagnostics.push(Diagnostic {
+            file: self.files[file].path.clone(),
+            span: self.spans[file].find(path),
+            severity,
+            code,
+            model: Some(self.models[model].name.clone()),
+            message,
+        });
+    }
+
+    fn check_duplicate_models(&mut self) {
+        let mut seen: HashMap<String, usize> = HashMap::new();
+        for model in 0..self.models.len() {
+            let name = self.models[model].name.to_lowercase();
+            match seen.get(&name) {
+                Some(&first) => {
+                    let message = format!(
+                        "Model '{}' is already defined in {}",
+                        self.models[model].name, self.files[self.origins[first]].path
+                    );
+                    self.report(model, "name", Severity::Error, "duplicate-name", message);
+                }
+                None => {
+                    seen.insert(name, model);
+                }
+            }
+        }
+    }
+
+    fn check_duplicate_fields(&mut self, model: usize) {
+        let m = &self.models[model];
+        let mut duplicates = Vec::new();
+
+        let columns = m
+            .dimensions
+            .iter()
+            .enumerate()
+            .map(|(i, d)| (format!("dimensions.{i}.name"), d.name.as_str()))
+            .chain(
+                m.measures
+                    .iter()
+                    .enumerate()
+                    .map(|(i, measure)| (format!("measures.{i}.name"), measure.name.as_str())),
+            );
+        duplicates.extend(find_duplicates(columns, "dimension or measure"));
+        duplicates.extend(find_duplicates(
+            m.metrics
+                .iter()
+                .enumerate()
+                .map(|(i, metric)| (format!("metrics.{i}.name"), metric.name.as_str())),
+            "metric",
+        ));
+        duplicates.extend(find_duplicates(
+            m.filters
+                .iter()
+                .enumerate()
+                .map(|(i, filter)| (format!("filters.{i}.name"), filter.name.as_str())),
+            "filter",
+        ));
+        for (i, metric) in m.metrics.iter().enumerate() {
+            duplicates.extend(find_duplicates(
+                declared_args(&metric.args, &format!("metrics.{i}")),
+                &format!("argument of metric '{}'", metric.name),
+            ));
+        }
+        for (i, filter) in m.filters.iter().enumerate() {
+            duplicates.extend(find_duplicates(
+                declared_args(&filter.args, &format!("filters.{i}")),
+                &format!("argument of filter '{}'", filter.name),
+            ));
+        }
+
+        let mut relationships = HashSet::new();
+        for (i, relationship) in m.relationships.iter().enumerate() {
+            let key = (
+                relationship.name.to_lowercase(),
+                relationship.source_col.to_lowercase(),
+                relationship.ref_col.to_lowercase(),
+            );
+            if !relationships.insert(key) {
+                duplicates.push((
+                    format!("relationships.{i}.name"),
+                    format!(
+                        "Relationship to '{}' on {} = {} is declared more than once",
+                        relationship.name, relationship.source_col, relationship.ref_col
+                    ),
+                ));
+            }
+        }
+
+        for (path, message) in duplicates {
+            self.report(model, &path, Severity::Error, "duplicate-name", message);
+        }
+    }
+
+    fn check_relationships(&mut self, model: usize) {
+        let mut problems = Vec::new();
+        let m = &self.models[model];
+
+        for (i, relationship) in m.relationships.iter().enumerate() {
+            let path = format!("relationships.{i}");
+
+            if find_column(m, &relationship.source_col, true).is_none() {
+                problems.push((
+                    format!("{path}.source_col"),
+                    Severity::Error,
+                    "unknown-column",
+                    format!(
+                        "Relationship '{}' joins on '{}', which isn't a dimension or measure of model '{}'",
+                        relationship.name, relationship.source_col, m.name
+                    ),
+                ));
+            }
+
+            match find_model(&self.models, &relationship.name) {
+                None => problems.push((
+                    format!("{path}.name"),
+                    Severity::Error,
+                    "unknown-model",
+                    format!(
+                        "Relationship '{}' points at a model that isn't defined",
+                        relationship.name
+                    ),
+                )),
+                Some(target) => {
+                    let target = &self.models[target];
+                    if find_column(target, &relationship.ref_col, true).is_none() {
+                        problems.push((
+                            format!("{path}.ref_col"),
+                            Severity::Error,
+                            "unknown-column",
+                            format!(
+                                "Relationship '{}' joins on '{}', which isn't a dimension or measure of model '{}'",
+                                relationship.name, relationship.ref_col, target.name
+                            ),
+                        ));
+                    }
+                }
+            }
+
+            if let Some(join_type) = &relationship.type_ {
+                if !matches!(
+                    join_type.trim().to_uppercase().as_str(),
+                    "LEFT" | "INNER" | "RIGHT" | "FULL"
+                ) {
+                    problems.push((
+                        format!("{path}.type"),
+                        Severity::Error,
+                        "invalid-join-type",
+                        format!(
+                            "Relationship '{}' has join type '{}'; use LEFT, INNER, RIGHT or FULL",
+                            relationship.name, join_type
+                        ),
+                    ));
+                }
+            }
+
+            if let Some(cardinality) = &relationship.cardinality {
+                if !matches!(
+                    cardinality.to_lowercase().replace('_', "-").as_str(),
+                    "one-to-one" | "one-to-many" | "many-to-one" | "many-to-many"
+                ) {
+                    problems.push((
+                        format!("{path}.cardinality"),
+                        Severity::Warning,
+                        "unknown-cardinality",
+                        format!(
+                            "Relationship '{}' has cardinality '{}', so joins through it can't be \
+                             checked for fan-out; use one-to-one, one-to-many, many-to-one or many-to-many",
+                            relationship.name, cardinality
+                        ),
+                    ));
+                }
+            }
+        }
+
+        for (path, severity, code, message) in problems {
+            self.report(model, &path, severity, code, message);
+        }
+    }
+
+    fn check_expressions(&mut self, model: usize) {
+        let mut problems = Vec::new();
+        let m = &self.models[model];
+
+        for (i, metric) in m.metrics.iter().enumerate() {
+            problems.extend(self.expr_problems(
+                model,
+                ExprKind::Metric,
+                &metric.name,
+                &metric.expr,
+                &metric.args,
+                &format!("metrics.{i}"),
+            ));
+        }
+        for (i, filter) in m.filters.iter().enumerate() {
+            problems.extend(self.expr_problems(
+                model,
+                ExprKind::Filter,
+                &filter.name,
+                &filter.expr,
+                &filter.args,
+                &format!("filters.{i}"),
+            ));
+        }
+
+        for (path, severity, code, message) in problems {
+            self.report(model, &path, severity, code, message);
+        }
+    }
+
+    /// Checks the columns, joins and arguments a metric or filter expression uses
+    fn expr_problems(
+        &self,
+        model: usize,
+        kind: ExprKind,
+        name: &str,
+        expr: &str,
+        args: &[Argument],
+        path: &str,
+    ) -> Vec<(String, Severity, &'static str, String)> {
+        let m = &self.models[model];
+        let label = format!("{} '{}'", kind.label(), name);
+        let expr_path = format!("{path}.expr");
+        let mut problems = Vec::new();
+
+        let tokens = match tokenize(expr) {
+            Ok(tokens) => tokens,
+            Err(e) => {
+                problems.push((
+                    expr_path,
+                    Severity::Error,
+                    "invalid-expr",
+                    format!("{} has an {}", label, e),
+                ));
+                return problems;
+            }
+        };
+
+        let mut used_args = HashSet::new();
+        let mut joined = HashSet::new();
+        let tree = JoinTree::new(&self.edges, self.models.len(), model);
+
+        for token in tokens {
+            match token {
+                Token::Text(_) => {}
+                Token::Placeholder(arg) => {
+                    if !args.iter().any(|declared| declared.name == arg) {
+                        problems.push((
+                            expr_path.clone(),
+                            Severity::Error,
+                            "undeclared-arg",
+                            format!(
+                                "{} uses '{{{}}}', but declares no argument '{}'",
+                                label, arg, arg
+                            ),
+                        ));
+                    }
+                    used_args.insert(arg);
+                }
+                Token::Ident {
+                    qualifier: Some(qualifier),
+                    name: column,
+                    is_function,
+                } => {
+                    let Some(target) = find_model(&self.models, &qualifier) else {
+                        // Schema-qualified functions are left alone
+                        if !is_function {
+                            problems.push((
+                                expr_path.clone(),
+                                Severity::Error,
+                                "unknown-model",
+                                format!(
+                                    "{} references '{}.{}', but there is no model '{}'",
+                                    label, qualifier, column, qualifier
+                                ),
+                            ));
+                        }
+                        continue;
+                    };
+                    let t = &self.models[target];
+
+                    if find_column(t, &column, true).is_none() {
+                        problems.push((
+                            expr_path.clone(),
+                            Severity::Error,
+                            if kind == ExprKind::Metric { "undefined-measure" } else { "unknown-column" },
+                            format!(
+                                "{} references '{}.{}', but model '{}' has no such dimension or measure",
+                                label, qualifier, column, t.name
+                            ),
+                        ));
+                    }
+                    if target == model || !joined.insert(target) {
+                        continue;
+                    }
+
+                    match tree.path(&self.edges, target) {
+                        None => problems.push((
+                            expr_path.clone(),
+                            Severity::Error,
+                            "no-join-path",
+                            format!(
+                                "{} references model '{}', but no relationships connect it to model '{}'",
+                                label, t.name, m.name
+                            ),
+                        )),
+                        Some(path) => {
+                            if let Some(edge) = path.iter().map(|&i| &self.edges[i]).find(|e| e.fans_out) {
+                                let consequence = match kind {
+                                    ExprKind::Metric => "its aggregate would count rows of that model more than once".to_string(),
+                                    ExprKind::Filter => format!("metrics of model '{}' can't be queried with it", m.name),
+                                };
+                                problems.push((
+                                    expr_path.clone(),
+                                    Severity::Warning,
+                                    "fan-out",
+                                    format!(
+                                        "{} reaches model '{}' through relationship '{}', which matches several \
+                                         rows of '{}' per row of '{}', so {}",
+                                        label,
+                                        t.name,
+                                        edge.relationship,
+                                        self.models[edge.to].name,
+                                        self.models[edge.from].name,
+                                        consequence
+                                    ),
+                                ));
+                            }
+                        }
+                    }
+                }
+                Token::Ident {
+                    qualifier: None,
+                    name: word,
+                    is_function,
+                } => {
+                    if is_function
+                        || SQL_KEYWORDS.contains(&word.to_lowercase().as_str())
+                        || find_column(m, &word, true).is_some()
+                    {
+                        continue;
+                    }
+                    problems.push(match kind {
+                        ExprKind::Metric => (
+                            expr_path.clone(),
+                            Severity::Error,
+                            "undefined-measure",
+                            format!(
+                                "{} references '{}', which isn't a measure or dimension of model '{}'",
+                                label, word, m.name
+                            ),
+                        ),
+                        ExprKind::Filter => (
+                            expr_path.clone(),
+                            Severity::Error,
+                            "unknown-column",
+                            format!(
+                                "{} references '{}', which isn't a dimension or measure of model '{}'",
+                                label, word, m.name
+                            ),
+                        ),
+                    });
+                }
+            }
+        }
+
+        for (i, arg) in args.iter().enumerate() {
+            if !used_args.contains(&arg.name) {
+                problems.push((
+                    format!("{path}.args.{i}"),
+                    Severity::Warning,
+                    "unused-arg",
+                    format!(
+                        "{} declares argument '{}' but its expr doesn't use it",
+                        label, arg.name
+                    ),
+                ));
+            }
+        }
+
+        problems
+    }
+
+    /// Warns about pairs of models that more than one shortest chain of relationships connects,
+    /// since the compiler joins them through whichever one was declared first
+    fn check_join_paths(&mut self) {
+        // Both models declaring the same relationship is one join, not two
+        let mut seen = HashSet::new();
+        let edges: Vec<&JoinEdge> = self
+            .edges
+            .iter()
+            .filter(|e| {
+                seen.insert((
+                    e.from,
+                    e.to,
+                    e.from_col.to_lowercase(),
+                    e.to_col.to_lowercase(),
+                ))
+            })
+            .collect();
+
+        let count = self.models.len();
+        let mut ambiguous = Vec::new();
+        for source in 0..count {
+            let mut distance: Vec<Option<usize>> = vec![None; count];
+            let mut paths = vec![0usize; count];
+            // Whether shortest paths from separate predecessors meet at the model, rather
+            // than the model just inheriting an ambiguity from further back
+            let mut merges = vec![false; count];
+            let mut queue = std::collections::VecDeque::from([source]);
+            distance[source] = Some(0);
+            paths[source] = 1;
+
+            while let Some(current) = queue.pop_front() {
+                let next = distance[current].unwrap() + 1;
+                for edge in edges.iter().filter(|e| e.from == current) {
+                    match distance[edge.to] {
+                        None => {
+                            distance[edge.to] = Some(next);
+                            paths[edge.to] = paths[current];
+                            queue.push_back(edge.to);
+                        }
+                        Some(d) if d == next => {
+                            paths[edge.to] = paths[edge.to].saturating_add(paths[current]);
+                            merges[edge.to] = true;
+                        }
+                        Some(_) => {}
+                    }
+                }
+            }
+
+            for target in source + 1..count {
+                if !merges[target] {
+                    continue;
+                }
+                let message = format!(
+                    "Models '{}' and '{}' are connected by {} different chains of {} relationship(s), \
+                     so queries using both may not join them the way you expect",
+                    self.models[source].name,
+                    self.models[target].name,
+                    paths[target],
+                    distance[target].unwrap()
+                );
+                ambiguous.push((source, message));
+            }
+        }
+
+        for (model, message) in ambiguous {
+            self.report(
+                model,
+                "relationships",
+                Severity::Warning,
+                "ambiguous-join",
+                message,
+            );
+        }
+    }
+}
+
+fn declared_args<'m>(args: &'m [Argument], path: &str) -> impl Iterator<Item = (String, &'m str)> {
+    let path = path.to_string();
+    args.iter()
+        .enumerate()
+        .map(move |(i, arg)| (format!("{path}.args.{i}.name"), arg.name.as_str()))
+}
+
+/// Paths and messages for names that appear more than once, ignoring case
+fn find_duplicates<'m>(
+    names: impl Iterator<Item = (String, &'m str)>,
+    kind: &str,
+) -> Vec<(String, String)> {
+    let mut seen = HashSet::new();
+    names
+        .filter(|(_, name)| !seen.insert(name.to_lowercase()))
+        .map(|(path, name)| {
+            (
+                path,
+                format!("There is more than one {} named '{}'", kind, name),
+            )
+        })
+        .collect()
+}
+
+/// Where each node of a YAML document sits, keyed by its dotted path
+/// (e.g. `relationships.0.source_col`; the document itself is the empty path)
+struct SpanIndex {
+    spans: HashMap<String, Span>,
+}
+
+impl SpanIndex {
+    fn parse(content: &str) -> Self {
+        let mut builder = SpanBuilder::default();
+        // Files that don't parse are reported from serde_yaml's error; keep what was found
+        let _ = Parser::new_from_str(content).load(&mut builder, false);
+        Self {
+            spans: builder.spans,
+        }
+    }
+
+    /// The span of `path`, or of its closest ancestor that was found
+    fn find(&self, path: &str) -> Span {
+        let mut path = path;
+        loop {
+            if let Some(span) = self.spans.get(path) {
+                return *span;
+            }
+            match path.rsplit_once('.') {
+                Some((parent, _)) => path = parent,
+                None if !path.is_empty() => path = "",
+                None => {
+                    return Span {
+                        line: 1,
+                        column: 1,
+                        end_line: 1,
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A mapping or sequence being read
+struct Frame {
+    path: String,
+    is_mapping: bool,
+    /// The key whose value comes next, with where it was written
+    key: Option<(String, Marker)>,
+    next_index: usize,
+    start: Option<Marker>,
+    end_line: usize,
+}
+
+#[derive(Default)]
+struct SpanBuilder {
+    stack: Vec<Frame>,
+    spans: HashMap<String, Span>,
+}
+
+impl SpanBuilder {
+    /// The path of the node starting now, and where its span starts if that's not the
+    /// node itself. Returns `None` for mapping keys.
+    fn next_node(&mut self, event: &Event, mark: Marker) -> Option<(String, Option<Marker>)> {
+        let Some(parent) = self.stack.last_mut() else {
+            return Some((String::new(), None));
+        };
+
+        if !parent.is_mapping {
+            let index = parent.next_index;
+            parent.next_index += 1;
+            return Some((child_path(&parent.path, &index.to_string()), None));
+        }
+
+        match parent.key.take() {
+            // Values span from their key, which is what a reader would look for
+            Some((key, key_mark)) => Some((child_path(&parent.path, &key), Some(key_mark))),
+            None => {
+                let key = match event {
+                    Event::Scalar(key, ..) => key.clone(),
+                    _ => String::new(),
+                };
+                parent.key = Some((key, mark));
+                None
+            }
+        }
+    }
+}
+
+impl MarkedEventReceiver for SpanBuilder {
+    fn on_event(&mut self, event: Event, mark: Marker) {
+        match event {
+            Event::Scalar(..)
+            | Event::Alias(..)
+            | Event::MappingStart(..)
+            | Event::SequenceStart(..) => {
+                for frame in &mut self.stack {
+                    frame.start.get_or_insert(mark);
+                    frame.end_line = frame.end_line.max(mark.line());
+                }
+
+                let Some((path, start)) = self.next_node(&event, mark) else {
+                    return;
+                };
+                match event {
+                    Event::MappingStart(..) | Event::SequenceStart(..) => self.stack.push(Frame {
+                        path,
+                        is_mapping: matches!(event, Event::MappingStart(..)),
+                        key: None,
+                        next_index: 0,
+                        start,
+                        end_line: start.map_or(0, |start| start.line()),
+                    }),
+                    _ => {
+                        let start = start.unwrap_or(mark);
+                        self.spans.insert(
+                            path,
+                            Span {
+                                line: start.line(),
+                                column: start.col() + 1,
+                                end_line: mark.line().max(start.line()),
+                            },
+                        );
+                    }
+                }
+            }
+            Event::MappingEnd | Event::SequenceEnd => {
+                let Some(frame) = self.stack.pop() else {
+                    return;
+                };
+                // End events are marked at the token after the node, so they don't extend it
+                let start = frame.start.unwrap_or(mark);
+                let end_line = frame.end_line.max(start.line());
+                self.spans.insert(
+                    frame.path,
+                    Span {
+                        line: start.line(),
+                        column: start.col() + 1,
+                        end_line,
+                    },
+                );
+                if let Some(parent) = self.stack.last_mut() {
+                    parent.end_line = parent.end_line.max(end_line);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn child_path(parent: &str, key: &str) -> String {
+    if parent.is_empty() {
+        key.to_string()
+    } else {
+        format!("{parent}.{key}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(path: &str, content: &str) -> SourceFile {
+        SourceFile {
+            path: path.to_string(),
+            content: content.to_string(),
+        }
+    }
+
+    fn codes(diagnostics: &[Diagnostic]) -> Vec<(&str, usize, &'static str)> {
+        diagnostics
+            .iter()
+            .map(|d| (d.file.as_str(), d.span.line, d.code))
+            .collect()
+    }
+
+    const ORDERS: &str = r#"name: orders
+dimensions:
+  - name: id
+  - name: customer_id
+  - name: status
+measures:
+  - name: amount
+metrics:
+  - name: revenue
+    expr: SUM(amount)
+  - name: average_item_price
+    expr: SUM(line_items.price) / COUNT(DISTINCT id)
+filters:
+  - name: large
+    expr: amount > {min_amount}
+relationships:
+  - name: customers
+    source_col: customer_id
+    ref_col: id
+    cardinality: many-to-one
+  - name: line_items
+    source_col: id
+    ref_col: order_id
+    cardinality: one-to-many
+"#;
+
+    const LINE_ITEMS: &str = r#"name: line_items
+dimensions:
+  - name: order_id
+  - name: sku
+measures:
+  - name: price
+"#;
+
+    const CUSTOMERS: &str = r#"name: customers
+dimensions:
+  - name: id
+  - name: region
+"#;
+
+    #[test]
+    fn test_lint_clean_models() {
+        let orders = ORDERS
+            .replace("    expr: SUM(line_items.price) / COUNT(DISTINCT id)\n", "    expr: COUNT(DISTINCT id)\n")
+            .replace(
+                "    expr: amount > {min_amount}\n",
+                "    expr: amount > {min_amount}\n    args:\n      - name: min_amount\n        type: number\n",
+            );
+        let diagnostics = lint_files(&[
+            file("orders.yml", &orders),
+            file("line_items.yml", LINE_ITEMS),
+            file("customers.yml", CUSTOMERS),
+        ]);
+        assert_eq!(diagnostics, vec![]);
+    }
+
+    #[test]
+    fn test_lint_reports_expression_problems_with_lines() {
+        let diagnostics = lint_files(&[
+            file("orders.yml", ORDERS),
+            file("line_items.yml", LINE_ITEMS),
+            file("customers.yml", CUSTOMERS),
+        ]);
+        assert_eq!(
+            codes(&diagnostics),
+            vec![
+                ("orders.yml", 12, "fan-out"),
+                ("orders.yml", 15, "undeclared-arg")
+            ]
+        );
+        assert!(diagnostics[0]
+            .message
+            .contains("through relationship 'orders.line_items'"));
+        assert_eq!(
+            diagnostics[1].to_string(),
+            "orders.yml:15:5: error[undeclared-arg]: Filter 'large' uses '{min_amount}', \
+             but declares no argument 'min_amount'"
+        );
+
+        let orders = ORDERS
+            .replace("SUM(amount)", "SUM(amount * discount)")
+            .replace(
+            "    expr: amount > {min_amount}\n",
+            "    expr: amount > 100\n    args:\n      - name: min_amount\n        type: number\n",
+        );
+        let diagnostics = lint_files(&[
+            file("orders.yml", &orders),
+            file("line_items.yml", LINE_ITEMS),
+            file("customers.yml", CUSTOMERS),
+        ]);
+        assert_eq!(
+            codes(&diagnostics),
+            vec![
+                ("orders.yml", 10, "undefined-measure"),
+                ("orders.yml", 12, "fan-out"),
+                ("orders.yml", 17, "unused-arg"),
+            ]
+        );
+        assert!(diagnostics[0].message.contains("'discount'"));
+    }
+
+    #[test]
+    fn test_lint_reports_broken_relationships_and_duplicates() {
+        let orders = ORDERS
+            .replace("ref_col: order_id", "ref_col: order")
+            .replace("  - name: status\n", "  - name: status\n  - name: Status\n");
+        let shipments = r#"name: shipments
+dimensions:
+  - name: id
+relationships:
+  - name: warehouses
+    source_col: warehouse_id
+    ref_col: id
+"#;
+        let diagnostics = lint_files(&[
+            file("orders.yml", &orders),
+            file("line_items.yml", LINE_ITEMS),
+            file("customers.yml", CUSTOMERS),
+            file("shipments.yml", shipments),
+            file("more/customers.yml", CUSTOMERS),
+            file("broken.yml", "name: broken\ndimensions: [\n"),
+        ]);
+
+        assert_eq!(
+            codes(&diagnostics),
+            vec![
+                ("orders.yml", 6, "duplicate-name"),
+                ("orders.yml", 13, "fan-out"),
+                ("orders.yml", 16, "undeclared-arg"),
+                ("orders.yml", 24, "unknown-column"),
+                ("shipments.yml", 5, "unknown-model"),
+                ("shipments.yml", 6, "unknown-column"),
+                ("more/customers.yml", 1, "duplicate-name"),
+                ("broken.yml", 3, "parse-error"),
+            ]
+        );
+        assert!(diagnostics[6]
+            .message
+            .contains("already defined in customers.yml"));
+        assert!(diagnostics[7].model.is_none());
+    }
+
+    #[test]
+    fn test_lint_reports_ambiguous_join_paths() {
+        // Orders reach customers both directly and through their stores
+        let orders = r#"name: orders
+dimensions:
+  - name: customer_id
+  - name: store_id
+relationships:
+  - name: customers
+    source_col: customer_id
+    ref_col: id
+  - name: stores
+    source_col: store_id
+    ref_col: id
+"#;
+        let stores = r#"name: stores
+dimensions:
+  - name: id
+  - name: owner_id
+relationships:
+  - name: owners
+    source_col: owner_id
+    ref_col: id
+"#;
+        let owners = r#"name: owners
+dimensions:
+  - name: id
+relationships:
+  - name: customers
+    source_col: id
+    ref_col: id
+"#;
+        let customers = CUSTOMERS.to_string()
+            + "relationships:\n  - name: orders\n    source_col: id\n    ref_col: customer_id\n";
+
+        let files = [
+            file("orders.yml", orders),
+            file("customers.yml", &customers),
+            file("stores.yml", stores),
+            file("owners.yml", owners),
+        ];
+        // Declaring the same join from both sides isn't ambiguous
+        assert!(lint_files(&files[..2])
+            .iter()
+            .all(|d| d.code != "ambiguous-join"));
+
+        let diagnostics = lint_files(&files);
+        assert_eq!(
+            codes(&diagnostics),
+            vec![
+                ("orders.yml", 5, "ambiguous-join"),
+                ("customers.yml", 5, "ambiguous-join")
+            ]
+        );
+        assert!(diagnostics[0].message.contains(
+            "Models 'orders' and 'owners' are connected by 2 different chains of 2 relationship(s)"
+        ));
+        assert_eq!(diagnostics[0].span.end_line, 11);
+        assert!(diagnostics[1]
+            .message
+            .contains("Models 'customers' and 'stores'"));
+    }
+}
@@ -0,0 +1,132 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A request against the semantic layer: which metrics to compute, broken down by which
+/// dimensions, and how to filter, order and limit the result.
+///
+/// Fields are referenced as `model.field`, or by bare name when only one model has it.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct SemanticQuery {
+    #[serde(default)]
+    pub metrics: Vec<MetricRequest>,
+    #[serde(default)]
+    pub dimensions: Vec<String>,
+    #[serde(default)]
+    pub filters: Vec<FilterRequest>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_dimension: Option<TimeDimension>,
+    #[serde(default)]
+    pub order_by: Vec<OrderBy>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u64>,
+}
+
+/// A metric to compute, with values for its arguments if it takes any
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(untagged)]
+pub enum MetricRequest {
+    Name(String),
+    WithArgs {
+        name: String,
+        #[serde(default)]
+        args: BTreeMap<String, Value>,
+    },
+}
+
+impl MetricRequest {
+    pub fn name(&self) -> &str {
+        match self {
+            MetricRequest::Name(name) => name,
+            MetricRequest::WithArgs { name, .. } => name,
+        }
+    }
+
+    pub fn args(&self) -> Option<&BTreeMap<String, Value>> {
+        match self {
+            MetricRequest::Name(_) => None,
+            MetricRequest::WithArgs { args, .. } => Some(args),
+        }
+    }
+}
+
+/// Either one of the models' named filters or a condition on a dimension or measure
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(untagged)]
+pub enum FilterRequest {
+    Named {
+        filter: String,
+        #[serde(default)]
+        args: BTreeMap<String, Value>,
+    },
+    Condition {
+        field: String,
+        operator: FilterOperator,
+        #[serde(default)]
+        values: Vec<Value>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterOperator {
+    Eq,
+    Neq,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    In,
+    NotIn,
+    Between,
+    IsNull,
+    IsNotNull,
+}
+
+/// A dimension to bucket by a time grain, e.g. orders per month
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct TimeDimension {
+    pub dimension: String,
+    pub grain: TimeGrain,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeGrain {
+    Day,
+    Week,
+    Month,
+    Quarter,
+    Year,
+}
+
+impl TimeGrain {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TimeGrain::Day => "day",
+            TimeGrain::Week => "week",
+            TimeGrain::Month => "month",
+            TimeGrain::Quarter => "quarter",
+            TimeGrain::Year => "year",
+        }
+    }
+}
+
+/// Orders by a requested metric or dimension
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct OrderBy {
+    pub field: String,
+    #[serde(default)]
+    pub descending: bool,
+}
+
+/// SQL compiled from a [`SemanticQuery`]
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct CompiledQuery {
+    pub sql: String,
+    /// Output column names, in order
+    pub columns: Vec<String>,
+    /// Names of the models the SQL reads from
+    pub models: Vec<String>,
+}
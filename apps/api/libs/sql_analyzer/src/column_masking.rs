@@ -0,0 +1,46 @@
+use std::collections::HashMap;
+use crate::{errors::SqlAnalyzerError, types::TableMask, utils::column_masking};
+
+/// Applies column masking policies to a SQL query by replacing each masked table reference
+/// with a derived table whose projection hides, hashes, redacts or partially masks columns.
+/// The masking expressions are written for `data_source_dialect`.
+///
+/// # Examples
+/// ```no_run
+/// use sql_analyzer::{apply_column_masks, ColumnMask, TableMask};
+/// use std::collections::HashMap;
+///
+/// #[tokio::main]
+/// async fn main() -> anyhow::Result<()> {
+///     let sql = "SELECT c.name, c.email FROM customers c";
+///     let mut masks = HashMap::new();
+///     masks.insert(
+///         "customers".to_string(),
+///         TableMask {
+///             columns: vec!["id".to_string(), "name".to_string(), "email".to_string()],
+///             masks: HashMap::from([("email".to_string(), ColumnMask::Hash)]),
+///             row_filter: None,
+///         },
+///     );
+///
+///     let masked_sql =
+///         apply_column_masks(sql.to_string(), "postgres", masks, Some("salt".to_string())).await?;
+///     println!("Masked SQL: {}", masked_sql);
+///     Ok(())
+/// }
+/// ```
+pub async fn apply_column_masks(
+    sql: String,
+    data_source_dialect: &str,
+    table_masks: HashMap<String, TableMask>,
+    hash_salt: Option<String>,
+) -> Result<String, SqlAnalyzerError> {
+    let data_source_dialect = data_source_dialect.to_string();
+    let result = tokio::task::spawn_blocking(move || {
+        column_masking::apply_column_masks(&sql, &data_source_dialect, table_masks, hash_salt.as_deref())
+    })
+    .await
+    .map_err(|e| SqlAnalyzerError::Internal(anyhow::anyhow!("Task join error: {}", e)))??;
+
+    Ok(result)
+}
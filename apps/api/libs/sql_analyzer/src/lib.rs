@@ -13,14 +13,22 @@ pub mod utils;
 pub mod analysis;
 pub mod semantic;
 pub mod row_filtering;
+pub mod column_masking;
 
 pub use errors::SqlAnalyzerError;
 pub use types::{
     QuerySummary, TableInfo, JoinInfo, CteSummary, 
     SemanticLayer, ValidationMode, Metric, Filter, 
-    Parameter, ParameterType, Relationship
+    Parameter, ParameterType, ParameterValue, Relationship, ColumnMask, TableMask,
+    ResultFilter, ResultFilterCondition, FilteredQuery, SkippedResultFilter,
+    QueryIssue, QueryIssueKind, IssueSeverity
 };
 
 pub use analysis::analyze_query;
 pub use semantic::{validate_semantic_query, substitute_semantic_query, validate_and_substitute_semantic_query};
-pub use row_filtering::apply_row_level_filters;
\ No newline at end of file
+pub use row_filtering::apply_row_level_filters;
+pub use column_masking::apply_column_masks;
+pub use utils::parameter_binding::{bind_parameters, find_parameter_placeholders};
+pub use utils::result_filtering::apply_result_filters;
+pub use utils::query_checks::check_query;
+pub use utils::literals::{literal, string_literal};
\ No newline at end of file
@@ -2,12 +2,13 @@ use anyhow::Result;
 use std::collections::HashMap;
 use crate::{
     errors::SqlAnalyzerError,
-    utils::semantic, // Assuming the rewrite logic is also in utils::semantic based on original lib.rs
+    utils::row_filtering,
 };
 
-/// Applies row-level filters to a SQL query by replacing table references with filtered CTEs.
+/// Applies row-level filters to a SQL query by replacing each filtered table reference with
+/// a derived table that only returns the rows its filter allows. The query is parsed with
+/// `data_source_dialect`.
 ///
-/// (Original documentation and examples included here)
 /// # Examples
 /// ```no_run
 /// use sql_analyzer::apply_row_level_filters;
@@ -20,20 +21,19 @@ use crate::{
 ///     filters.insert("users".to_string(), "tenant_id = 123".to_string());
 ///     filters.insert("orders".to_string(), "created_at > '2023-01-01'".to_string());
 ///
-///     let filtered_sql = apply_row_level_filters(sql.to_string(), filters).await?;
+///     let filtered_sql = apply_row_level_filters(sql.to_string(), "postgres", filters).await?;
 ///     println!("Filtered SQL: {}", filtered_sql);
 ///     Ok(())
 /// }
 /// ```
 pub async fn apply_row_level_filters(
     sql: String,
+    data_source_dialect: &str,
     table_filters: HashMap<String, String>,
 ) -> Result<String, SqlAnalyzerError> {
+    let data_source_dialect = data_source_dialect.to_string();
     let result = tokio::task::spawn_blocking(move || {
-        // Assuming the actual implementation function is called apply_row_level_filters 
-        // within the utils::semantic module, based on the original lib.rs structure.
-        // If it's named differently or located elsewhere (e.g., utils::rewriting), adjust this call.
-        semantic::apply_row_level_filters(&sql, table_filters)
+        row_filtering::apply_row_level_filters(&sql, &data_source_dialect, table_filters)
     })
     .await
     .map_err(|e| SqlAnalyzerError::Internal(anyhow::anyhow!("Task join error: {}", e)))??;
@@ -87,6 +87,84 @@ pub enum ParameterType {
     Boolean,
 }
 
+/// A value bound to a `{{name}}` placeholder by `bind_parameters`. Values are given as text
+/// and checked against the parameter type when they are bound.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub enum ParameterValue {
+    Single(ParameterType, String),
+    /// Several values, expanded into an `IN (...)` list
+    List(ParameterType, Vec<String>),
+}
+
+/// A condition on one column of a query's result, applied by `apply_result_filters`.
+/// Values are given as text and checked against the type like parameter values.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct ResultFilter {
+    pub column: String,
+    pub condition: ResultFilterCondition,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub enum ResultFilterCondition {
+    /// `column >= start` and `column <= end` (`< end` unless `end_inclusive`); a missing
+    /// bound is left open
+    Range {
+        value_type: ParameterType,
+        start: Option<String>,
+        end: Option<String>,
+        end_inclusive: bool,
+    },
+    /// `column IN (values)`
+    In {
+        value_type: ParameterType,
+        values: Vec<String>,
+    },
+}
+
+/// A query with result filters applied by `apply_result_filters`
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct FilteredQuery {
+    pub sql: String,
+    /// Filters that couldn't be applied to the query, which runs without them
+    pub skipped: Vec<SkippedResultFilter>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct SkippedResultFilter {
+    pub column: String,
+    pub reason: String,
+}
+
+/// A pattern found by `check_query` that often makes a query's numbers wrong
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct QueryIssue {
+    pub kind: QueryIssueKind,
+    pub severity: IssueSeverity,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryIssueKind {
+    /// A selected column is neither aggregated nor grouped
+    MissingGroupBy,
+    /// SUM/COUNT/AVG over a join that may repeat rows
+    FanOutJoin,
+    /// A join without a condition
+    CrossJoin,
+    /// A whole table is returned without a filter, aggregation or limit
+    UnboundedScan,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueSeverity {
+    /// The query is almost certainly wrong or rejected by the warehouse
+    Error,
+    /// The query may be right but needs a second look
+    Warning,
+}
+
 /// A metric definition in the semantic layer
 #[derive(Serialize, Debug, Clone)]
 pub struct Metric {
@@ -209,3 +287,27 @@ impl SemanticLayer {
             .unwrap_or(false)
     }
 }
+
+/// How a column is transformed before a query can read it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ColumnMask {
+    /// The column is removed from the table entirely
+    Hide,
+    /// The value is replaced by a salted SHA-256 hash, so equal values still match
+    Hash,
+    /// The value is replaced by a constant placeholder
+    Redact,
+    /// Only the last `visible_chars` characters are kept, e.g. `****1234`
+    Partial { visible_chars: usize },
+}
+
+/// Column masks for a single table, together with the table's full column list so that
+/// unmasked columns can be passed through unchanged
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableMask {
+    pub columns: Vec<String>,
+    pub masks: HashMap<String, ColumnMask>,
+    /// Row-level filter on the table. It's applied to the unmasked columns, so a filter on a
+    /// masked column still compares against the real values.
+    pub row_filter: Option<String>,
+}
@@ -0,0 +1,267 @@
+use crate::analysis::get_dialect;
+use crate::errors::SqlAnalyzerError;
+use crate::types::{ColumnMask, TableMask};
+use crate::utils::result_filtering::identifier_quote;
+use crate::utils::row_filtering::is_single_filtered_select;
+use sqlparser::ast::{
+    Ident, Query, Statement, TableAlias, TableFactor, Visit, VisitMut, Visitor, VisitorMut,
+};
+use sqlparser::parser::Parser;
+use std::collections::{HashMap, HashSet};
+use std::ops::ControlFlow;
+
+/// Rewrites every reference to a masked table into a derived table that projects the masked
+/// columns. Hidden columns are left out of the projection entirely, so any query that
+/// references them fails instead of leaking the value.
+///
+/// Hashed columns are salted with `hash_salt`, which must be set when any column is hashed.
+pub fn apply_column_masks(
+    sql: &str,
+    data_source_dialect: &str,
+    table_masks: HashMap<String, TableMask>,
+    hash_salt: Option<&str>,
+) -> Result<String, SqlAnalyzerError> {
+    if table_masks.values().all(|mask| mask.masks.is_empty()) {
+        return Ok(sql.to_string());
+    }
+
+    let mut statements = Parser::parse_sql(get_dialect(data_source_dialect), sql)?;
+
+    // Table names are matched case-insensitively, like unquoted identifiers in most warehouses
+    let table_masks: HashMap<String, TableMask> = table_masks
+        .into_iter()
+        .filter(|(_, mask)| !mask.masks.is_empty())
+        .map(|(name, mask)| (name.to_lowercase(), mask))
+        .collect();
+
+    let mut cte_collector = CteNameCollector::default();
+    let _ = Visit::visit(&statements, &mut cte_collector);
+
+    let mut rewriter = MaskRewriter {
+        data_source_dialect,
+        hash_salt,
+        table_masks: &table_masks,
+        cte_names: cte_collector.names,
+        error: None,
+    };
+    let _ = VisitMut::visit(&mut statements, &mut rewriter);
+
+    if let Some(error) = rewriter.error {
+        return Err(error);
+    }
+
+    Ok(statements
+        .iter()
+        .map(|statement| statement.to_string())
+        .collect::<Vec<_>>()
+        .join("; "))
+}
+
+/// Collects the lowercased names of every CTE defined anywhere in a statement
+#[derive(Default)]
+pub(crate) struct CteNameCollector {
+    pub(crate) names: HashSet<String>,
+}
+
+impl Visitor for CteNameCollector {
+    type Break = ();
+
+    fn pre_visit_query(&mut self, query: &Query) -> ControlFlow<Self::Break> {
+        if let Some(with) = &query.with {
+            for cte in &with.cte_tables {
+                self.names.insert(cte.alias.name.value.to_lowercase());
+            }
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+struct MaskRewriter<'a> {
+    data_source_dialect: &'a str,
+    hash_salt: Option<&'a str>,
+    table_masks: &'a HashMap<String, TableMask>,
+    cte_names: HashSet<String>,
+    error: Option<SqlAnalyzerError>,
+}
+
+impl VisitorMut for MaskRewriter<'_> {
+    type Break = ();
+
+    // Rewriting after the factor's children have been visited keeps the visitor from
+    // descending into the derived table we just created and masking it a second time.
+    fn post_visit_table_factor(&mut self, table_factor: &mut TableFactor) -> ControlFlow<Self::Break> {
+        let TableFactor::Table { name, alias, args, .. } = table_factor else {
+            return ControlFlow::Continue(());
+        };
+
+        let Some(table_name) = name.0.last().map(|ident| ident.value.to_lowercase()) else {
+            return ControlFlow::Continue(());
+        };
+
+        if name.0.len() == 1 && self.cte_names.contains(&table_name) {
+            return ControlFlow::Continue(());
+        }
+
+        let Some(table_mask) = self.table_masks.get(&table_name) else {
+            return ControlFlow::Continue(());
+        };
+
+        if args.is_some() {
+            self.error = Some(SqlAnalyzerError::UnsupportedStatement(format!(
+                "Column masks can't be applied to table function '{}'",
+                name
+            )));
+            return ControlFlow::Break(());
+        }
+
+        let subquery = match build_masked_subquery(
+            &name.to_string(),
+            table_mask,
+            self.data_source_dialect,
+            self.hash_salt,
+        ) {
+            Ok(subquery) => subquery,
+            Err(e) => {
+                self.error = Some(e);
+                return ControlFlow::Break(());
+            }
+        };
+
+        // Keep the original alias (or the bare table name) so outer column references still resolve
+        let alias = alias.clone().unwrap_or_else(|| TableAlias {
+            name: name.0.last().cloned().unwrap_or_else(|| Ident::new(table_name.clone())),
+            columns: vec![],
+        });
+
+        *table_factor = TableFactor::Derived {
+            lateral: false,
+            subquery: Box::new(subquery),
+            alias: Some(alias),
+        };
+
+        ControlFlow::Continue(())
+    }
+}
+
+fn build_masked_subquery(
+    full_name: &str,
+    table_mask: &TableMask,
+    data_source_dialect: &str,
+    hash_salt: Option<&str>,
+) -> Result<Query, SqlAnalyzerError> {
+    let masks: HashMap<String, &ColumnMask> = table_mask
+        .masks
+        .iter()
+        .map(|(column, mask)| (column.to_lowercase(), mask))
+        .collect();
+
+    let quote = identifier_quote(data_source_dialect);
+    let mut projection = Vec::new();
+    for column in &table_mask.columns {
+        let ident = quote_identifier(column, quote);
+        match masks.get(&column.to_lowercase()) {
+            None => projection.push(ident),
+            Some(ColumnMask::Hide) => {}
+            Some(mask) => {
+                let expr = mask_expression(mask, &ident, data_source_dialect, hash_salt)?;
+                projection.push(format!("{} AS {}", expr, ident));
+            }
+        }
+    }
+
+    if projection.is_empty() {
+        return Err(SqlAnalyzerError::Internal(anyhow::anyhow!(
+            "All columns of table '{}' are hidden",
+            full_name
+        )));
+    }
+
+    // The row filter reads the table itself, before any column is masked
+    let sql = match &table_mask.row_filter {
+        Some(filter) => format!("SELECT {} FROM {} WHERE ({})", projection.join(", "), full_name, filter),
+        None => format!("SELECT {} FROM {}", projection.join(", "), full_name),
+    };
+
+    if let [Statement::Query(query)] = Parser::parse_sql(get_dialect(data_source_dialect), &sql)?.as_slice() {
+        if table_mask.row_filter.is_none() || is_single_filtered_select(query) {
+            return Ok(query.as_ref().clone());
+        }
+    }
+
+    Err(SqlAnalyzerError::Internal(anyhow::anyhow!(
+        "Failed to build masked projection for '{}'",
+        full_name
+    )))
+}
+
+fn mask_expression(
+    mask: &ColumnMask,
+    ident: &str,
+    data_source_dialect: &str,
+    hash_salt: Option<&str>,
+) -> Result<String, SqlAnalyzerError> {
+    let dialect = data_source_dialect.to_lowercase();
+    let text = cast_to_text(ident, &dialect);
+
+    Ok(match mask {
+        ColumnMask::Hide => "NULL".to_string(),
+        ColumnMask::Hash => {
+            let salt = hash_salt.ok_or_else(|| {
+                SqlAnalyzerError::Internal(anyhow::anyhow!("A hash salt is required to hash masked columns"))
+            })?;
+            // Quotes and backslashes escape differently per dialect, so keep them out entirely
+            if salt.contains(['\\', '\'']) {
+                return Err(SqlAnalyzerError::Internal(anyhow::anyhow!(
+                    "The hash salt can't contain quotes or backslashes"
+                )));
+            }
+            let salted = format!("CONCAT('{}', {})", salt, text);
+            // Every variant produces the lowercase hex SHA-256 digest as a string
+            match dialect.as_str() {
+                "postgres" | "supabase" => {
+                    format!("ENCODE(SHA256(CONVERT_TO({}, 'UTF8')), 'hex')", salted)
+                }
+                "bigquery" => format!("TO_HEX(SHA256({}))", salted),
+                "sqlserver" => format!(
+                    "LOWER(CONVERT(VARCHAR(64), HASHBYTES('SHA2_256', {}), 2))",
+                    salted
+                ),
+                "duckdb" => format!("SHA256({})", salted),
+                "clickhouse" => format!("lower(hex(SHA256({})))", salted),
+                _ => format!("SHA2({}, 256)", salted),
+            }
+        }
+        ColumnMask::Redact => "'REDACTED'".to_string(),
+        ColumnMask::Partial { visible_chars } => {
+            format!("CONCAT('****', RIGHT({}, {}))", text, visible_chars)
+        }
+    })
+}
+
+/// Casts a column to the dialect's string type; not every warehouse has VARCHAR
+fn cast_to_text(ident: &str, dialect: &str) -> String {
+    match dialect {
+        "bigquery" | "databricks" => format!("CAST({} AS STRING)", ident),
+        "mysql" | "mariadb" => format!("CAST({} AS CHAR)", ident),
+        "sqlserver" => format!("CAST({} AS NVARCHAR(MAX))", ident),
+        "clickhouse" => format!("toString({})", ident),
+        "postgres" | "supabase" => format!("CAST({} AS TEXT)", ident),
+        _ => format!("CAST({} AS VARCHAR)", ident),
+    }
+}
+
+fn quote_identifier(column: &str, quote: char) -> String {
+    let is_simple = column
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_lowercase() || c == '_')
+        && column
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+
+    if is_simple {
+        column.to_string()
+    } else {
+        Ident::with_quote(quote, column).to_string()
+    }
+}
@@ -0,0 +1,39 @@
+use crate::errors::SqlAnalyzerError;
+use serde_json::Value;
+
+/// Renders a JSON value as a SQL literal for `data_source_dialect`
+pub fn literal(value: &Value, data_source_dialect: &str) -> Result<String, SqlAnalyzerError> {
+    let dialect = data_source_dialect.to_lowercase();
+
+    match value {
+        Value::Null => Ok("NULL".to_string()),
+        Value::Bool(b) => Ok(match (dialect.as_str(), b) {
+            // SQL Server has no boolean literals
+            ("sqlserver", true) => "1".to_string(),
+            ("sqlserver", false) => "0".to_string(),
+            (_, true) => "TRUE".to_string(),
+            (_, false) => "FALSE".to_string(),
+        }),
+        Value::Number(n) => Ok(n.to_string()),
+        Value::String(s) => Ok(string_literal(s, &dialect)),
+        Value::Array(_) | Value::Object(_) => Err(SqlAnalyzerError::InvalidParameter(format!(
+            "Only strings, numbers, booleans and null can be used as values, got {}",
+            value
+        ))),
+    }
+}
+
+/// Quotes a string as a SQL literal, escaping it the way the warehouse expects
+pub fn string_literal(value: &str, data_source_dialect: &str) -> String {
+    match data_source_dialect.to_lowercase().as_str() {
+        // These treat backslashes in string literals as escapes
+        "bigquery" | "databricks" | "mysql" | "mariadb" | "clickhouse" => {
+            format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+        }
+        // Also backslash escapes, but quotes are doubled like standard SQL
+        "snowflake" | "redshift" => {
+            format!("'{}'", value.replace('\\', "\\\\").replace('\'', "''"))
+        }
+        _ => format!("'{}'", value.replace('\'', "''")),
+    }
+}
@@ -11,6 +11,12 @@ use std::ops::ControlFlow;
 use anyhow::Result;
 
 pub mod semantic;
+pub mod column_masking;
+pub mod row_filtering;
+pub mod parameter_binding;
+pub mod result_filtering;
+pub mod query_checks;
+pub mod literals;
 
 pub(crate) fn analyze_sql(sql: &str) -> Result<QuerySummary, SqlAnalyzerError> {
     let ast = Parser::parse_sql(&GenericDialect, sql)?;
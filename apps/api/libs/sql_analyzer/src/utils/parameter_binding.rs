@@ -0,0 +1,238 @@
+use crate::analysis::get_dialect;
+use crate::errors::SqlAnalyzerError;
+use crate::types::{ParameterType, ParameterValue};
+use lazy_static::lazy_static;
+use regex::Regex;
+use sqlparser::ast::{Expr, Value, VisitMut, VisitorMut};
+use sqlparser::parser::Parser;
+use std::collections::{HashMap, HashSet};
+use std::ops::ControlFlow;
+
+lazy_static! {
+    static ref PLACEHOLDER_RE: Regex =
+        Regex::new(r"\{\{\s*([A-Za-z_][A-Za-z0-9_]*(?:\.[A-Za-z_][A-Za-z0-9_]*)?)\s*\}\}").unwrap();
+    static ref NUMBER_RE: Regex = Regex::new(r"^-?\d+(\.\d+)?([eE][+-]?\d+)?$").unwrap();
+    static ref DATE_RE: Regex =
+        Regex::new(r"^\d{4}-\d{2}-\d{2}([ T]\d{2}:\d{2}(:\d{2}(\.\d+)?)?(Z|[+-]\d{2}:?\d{2})?)?$").unwrap();
+}
+
+/// Prefix of the placeholders `{{name}}` is swapped for before parsing. `:name` parses as a
+/// placeholder value in every dialect we support.
+const BOUND_PREFIX: &str = ":__buster_param_";
+
+/// Names of every `{{name}}` placeholder in `sql`, in order of first use.
+pub fn find_parameter_placeholders(sql: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+
+    PLACEHOLDER_RE
+        .captures_iter(sql)
+        .map(|caps| caps[1].to_string())
+        .filter(|name| seen.insert(name.clone()))
+        .collect()
+}
+
+/// Replaces `{{name}}` placeholders with typed SQL literals. The query is parsed in the data
+/// source's dialect and each value becomes a single literal node in the AST, so a value can
+/// never change the shape of the query the way splicing it into the text could.
+/// Placeholders must appear where a value is expected; list values are only allowed inside
+/// an `IN (...)` list.
+pub fn bind_parameters(
+    sql: &str,
+    data_source_dialect: &str,
+    values: &HashMap<String, ParameterValue>,
+) -> Result<String, SqlAnalyzerError> {
+    let names = find_parameter_placeholders(sql);
+    if names.is_empty() {
+        return Ok(sql.to_string());
+    }
+
+    for name in &names {
+        if !values.contains_key(name) {
+            return Err(SqlAnalyzerError::MissingParameter(name.clone()));
+        }
+    }
+
+    let indexed_sql = PLACEHOLDER_RE.replace_all(sql, |caps: &regex::Captures| {
+        let index = names.iter().position(|name| name == &caps[1]).unwrap();
+        format!("{}{}", BOUND_PREFIX, index)
+    });
+
+    let dialect = get_dialect(data_source_dialect);
+    let mut statements = Parser::parse_sql(dialect, &indexed_sql)?;
+
+    let mut binder = ParameterBinder {
+        names: &names,
+        values,
+        boolean_as_bit: data_source_dialect.eq_ignore_ascii_case("sqlserver"),
+        bound: HashSet::new(),
+        error: None,
+    };
+    let _ = VisitMut::visit(&mut statements, &mut binder);
+
+    if let Some(error) = binder.error {
+        return Err(error);
+    }
+
+    // A placeholder inside a string literal or identifier never reaches the AST as a value
+    if let Some(name) = names.iter().find(|name| !binder.bound.contains(*name)) {
+        return Err(SqlAnalyzerError::InvalidParameter(format!(
+            "Parameter '{}' must be used where a value is expected, not inside a string or identifier",
+            name
+        )));
+    }
+
+    Ok(statements
+        .iter()
+        .map(|statement| statement.to_string())
+        .collect::<Vec<_>>()
+        .join("; "))
+}
+
+/// Turns a textual value into a SQL literal of the given type, rejecting values that don't
+/// fit it. SQL Server has no boolean literals, so `boolean_as_bit` renders them as 1 and 0.
+pub(crate) fn parameter_literal(
+    name: &str,
+    param_type: &ParameterType,
+    raw: &str,
+    boolean_as_bit: bool,
+) -> Result<Expr, SqlAnalyzerError> {
+    let value = match param_type {
+        ParameterType::Number => {
+            let raw = raw.trim();
+            if !NUMBER_RE.is_match(raw) {
+                return Err(SqlAnalyzerError::InvalidParameter(format!(
+                    "Parameter '{}' expects a number but got '{}'",
+                    name, raw
+                )));
+            }
+            Value::Number(raw.to_string().parse().unwrap(), false)
+        }
+        ParameterType::Boolean => {
+            let value = match raw.trim().to_lowercase().as_str() {
+                "true" | "1" => true,
+                "false" | "0" => false,
+                _ => {
+                    return Err(SqlAnalyzerError::InvalidParameter(format!(
+                        "Parameter '{}' expects a boolean but got '{}'",
+                        name, raw
+                    )))
+                }
+            };
+            if boolean_as_bit {
+                Value::Number((if value { "1" } else { "0" }).parse().unwrap(), false)
+            } else {
+                Value::Boolean(value)
+            }
+        }
+        ParameterType::Date => {
+            let raw = raw.trim();
+            if !DATE_RE.is_match(raw) {
+                return Err(SqlAnalyzerError::InvalidParameter(format!(
+                    "Parameter '{}' expects a date (YYYY-MM-DD) but got '{}'",
+                    name, raw
+                )));
+            }
+            Value::SingleQuotedString(raw.to_string())
+        }
+        ParameterType::String => {
+            // The printer doubles single quotes, but leaves a quote alone when it follows a
+            // backslash, and half of our dialects treat backslashes as escapes. Rejecting
+            // them keeps every value inside its literal whatever the dialect.
+            if raw.contains('\\') {
+                return Err(SqlAnalyzerError::InvalidParameter(format!(
+                    "Parameter '{}' can't contain backslashes",
+                    name
+                )));
+            }
+            Value::SingleQuotedString(raw.to_string())
+        }
+    };
+
+    Ok(Expr::Value(value))
+}
+
+struct ParameterBinder<'a> {
+    names: &'a [String],
+    values: &'a HashMap<String, ParameterValue>,
+    boolean_as_bit: bool,
+    bound: HashSet<String>,
+    error: Option<SqlAnalyzerError>,
+}
+
+impl ParameterBinder<'_> {
+    fn placeholder_name(&self, expr: &Expr) -> Option<&str> {
+        match expr {
+            Expr::Value(Value::Placeholder(placeholder)) => placeholder
+                .strip_prefix(BOUND_PREFIX)
+                .and_then(|index| index.parse::<usize>().ok())
+                .and_then(|index| self.names.get(index))
+                .map(|name| name.as_str()),
+            _ => None,
+        }
+    }
+
+    fn bind(&mut self, expr: &mut Expr) -> Result<(), SqlAnalyzerError> {
+        if let Expr::InList { list, .. } = expr {
+            let mut expanded = Vec::with_capacity(list.len());
+
+            for item in list.drain(..) {
+                let Some(name) = self.placeholder_name(&item).map(str::to_string) else {
+                    expanded.push(item);
+                    continue;
+                };
+
+                match &self.values[&name] {
+                    ParameterValue::List(param_type, items) => {
+                        if items.is_empty() {
+                            return Err(SqlAnalyzerError::InvalidParameter(format!(
+                                "Parameter '{}' needs at least one value",
+                                name
+                            )));
+                        }
+                        for raw in items {
+                            expanded.push(parameter_literal(&name, param_type, raw, self.boolean_as_bit)?);
+                        }
+                    }
+                    ParameterValue::Single(param_type, raw) => {
+                        expanded.push(parameter_literal(&name, param_type, raw, self.boolean_as_bit)?);
+                    }
+                }
+                self.bound.insert(name);
+            }
+
+            *list = expanded;
+            return Ok(());
+        }
+
+        if let Some(name) = self.placeholder_name(expr).map(str::to_string) {
+            match &self.values[&name] {
+                ParameterValue::Single(param_type, raw) => {
+                    *expr = parameter_literal(&name, param_type, raw, self.boolean_as_bit)?;
+                }
+                ParameterValue::List(..) => {
+                    return Err(SqlAnalyzerError::InvalidParameter(format!(
+                        "Parameter '{}' holds several values and can only be used as `IN ({{{{{}}}}})`",
+                        name, name
+                    )));
+                }
+            }
+            self.bound.insert(name);
+        }
+
+        Ok(())
+    }
+}
+
+impl VisitorMut for ParameterBinder<'_> {
+    type Break = ();
+
+    fn pre_visit_expr(&mut self, expr: &mut Expr) -> ControlFlow<Self::Break> {
+        match self.bind(expr) {
+            Ok(()) => ControlFlow::Continue(()),
+            Err(e) => {
+                self.error = Some(e);
+                ControlFlow::Break(())
+            }
+        }
+    }
+}
@@ -0,0 +1,469 @@
+use crate::analysis::get_dialect;
+use crate::errors::SqlAnalyzerError;
+use crate::types::{IssueSeverity, QueryIssue, QueryIssueKind};
+use sqlparser::ast::{
+    DuplicateTreatment, Expr, FunctionArguments, GroupByExpr, JoinConstraint, JoinOperator, Query,
+    Select, SelectItem, SetExpr, Statement, TableFactor, TableWithJoins, Value, Visit, Visitor,
+};
+use sqlparser::parser::Parser;
+use std::collections::HashSet;
+use std::ops::ControlFlow;
+
+/// Functions that collapse rows when used without `OVER`
+const AGGREGATE_FUNCTIONS: &[&str] = &[
+    "any_value",
+    "approx_count_distinct",
+    "array_agg",
+    "avg",
+    "bit_and",
+    "bit_or",
+    "bool_and",
+    "bool_or",
+    "count",
+    "count_if",
+    "countif",
+    "group_concat",
+    "listagg",
+    "max",
+    "median",
+    "min",
+    "percentile_cont",
+    "percentile_disc",
+    "stddev",
+    "stddev_pop",
+    "stddev_samp",
+    "string_agg",
+    "sum",
+    "var_pop",
+    "var_samp",
+    "variance",
+];
+
+/// Aggregates whose result grows when a join repeats rows
+const FAN_OUT_SENSITIVE_FUNCTIONS: &[&str] = &["avg", "count", "count_if", "countif", "sum"];
+
+/// Bare words some dialects accept as function arguments that are not columns, e.g. `DATEADD(day, ...)`
+const DATE_PARTS: &[&str] = &[
+    "year",
+    "quarter",
+    "month",
+    "week",
+    "day",
+    "hour",
+    "minute",
+    "second",
+    "millisecond",
+    "microsecond",
+    "dayofweek",
+    "dayofyear",
+];
+
+/// Statically checks a read-only query for patterns that commonly produce wrong numbers:
+/// columns missing from `GROUP BY`, aggregates over joins that can repeat rows, joins
+/// without a condition, and unfiltered scans that return a whole table.
+///
+/// The checks are heuristics; issues describe what to review rather than proven bugs.
+pub fn check_query(
+    sql: &str,
+    data_source_dialect: &str,
+) -> Result<Vec<QueryIssue>, SqlAnalyzerError> {
+    let dialect = get_dialect(data_source_dialect);
+    let statements = Parser::parse_sql(dialect, sql)?;
+
+    let mut issues = Vec::new();
+    for statement in &statements {
+        match statement {
+            Statement::Query(query) => {
+                check_unbounded_scan(query, &mut issues);
+                check_query_body(query, &mut issues);
+            }
+            other => {
+                return Err(SqlAnalyzerError::UnsupportedStatement(format!(
+                    "Only SELECT queries can be checked. Found: {}",
+                    other
+                )))
+            }
+        }
+    }
+
+    Ok(issues)
+}
+
+fn check_query_body(query: &Query, issues: &mut Vec<QueryIssue>) {
+    if let Some(with) = &query.with {
+        for cte in &with.cte_tables {
+            check_query_body(&cte.query, issues);
+        }
+    }
+    check_set_expr(&query.body, issues);
+}
+
+fn check_set_expr(set_expr: &SetExpr, issues: &mut Vec<QueryIssue>) {
+    match set_expr {
+        SetExpr::Select(select) => check_select(select, issues),
+        SetExpr::Query(query) => check_query_body(query, issues),
+        SetExpr::SetOperation { left, right, .. } => {
+            check_set_expr(left, issues);
+            check_set_expr(right, issues);
+        }
+        _ => {}
+    }
+}
+
+fn check_select(select: &Select, issues: &mut Vec<QueryIssue>) {
+    for table in &select.from {
+        check_table_factor(&table.relation, issues);
+        for join in &table.joins {
+            check_table_factor(&join.relation, issues);
+        }
+    }
+
+    let aggregates = select_aggregates(select);
+
+    check_group_by(select, !aggregates.is_empty(), issues);
+    check_joins(select, &aggregates, issues);
+}
+
+fn check_table_factor(factor: &TableFactor, issues: &mut Vec<QueryIssue>) {
+    match factor {
+        TableFactor::Derived { subquery, .. } => check_query_body(subquery, issues),
+        TableFactor::NestedJoin {
+            table_with_joins, ..
+        } => {
+            check_table_factor(&table_with_joins.relation, issues);
+            for join in &table_with_joins.joins {
+                check_table_factor(&join.relation, issues);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Lower-cased final part of a function name, e.g. `sum` for `pg_catalog.SUM`
+fn function_name(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Function(function) => function
+            .name
+            .0
+            .last()
+            .map(|ident| ident.value.to_lowercase()),
+        _ => None,
+    }
+}
+
+fn is_aggregate_call(expr: &Expr) -> bool {
+    match expr {
+        Expr::Function(function) if function.over.is_none() => function_name(expr)
+            .map(|name| AGGREGATE_FUNCTIONS.contains(&name.as_str()))
+            .unwrap_or(false),
+        _ => false,
+    }
+}
+
+/// Subtrees whose column references do not have to be grouped
+fn is_opaque(expr: &Expr) -> bool {
+    is_aggregate_call(expr)
+        || matches!(expr, Expr::Function(function) if function.over.is_some())
+        || matches!(
+            expr,
+            Expr::Subquery(_) | Expr::Exists { .. } | Expr::InSubquery { .. }
+        )
+}
+
+/// An aggregate call found in a select list
+struct AggregateCall {
+    name: String,
+    distinct: bool,
+}
+
+/// Collects the aggregate calls and the ungrouped column references of an expression
+#[derive(Default)]
+struct ExprScan {
+    opaque_depth: usize,
+    aggregates: Vec<AggregateCall>,
+    columns: Vec<String>,
+}
+
+impl Visitor for ExprScan {
+    type Break = ();
+
+    fn pre_visit_expr(&mut self, expr: &Expr) -> ControlFlow<Self::Break> {
+        if self.opaque_depth == 0 && is_aggregate_call(expr) {
+            if let Expr::Function(function) = expr {
+                let distinct = matches!(
+                    &function.args,
+                    FunctionArguments::List(list)
+                        if list.duplicate_treatment == Some(DuplicateTreatment::Distinct)
+                );
+                self.aggregates.push(AggregateCall {
+                    name: function_name(expr).unwrap_or_default(),
+                    distinct,
+                });
+            }
+        }
+        if is_opaque(expr) {
+            self.opaque_depth += 1;
+        } else if self.opaque_depth == 0 {
+            match expr {
+                Expr::Identifier(ident) => {
+                    let name = ident.value.to_lowercase();
+                    if ident.quote_style.is_some() || !DATE_PARTS.contains(&name.as_str()) {
+                        self.columns.push(name);
+                    }
+                }
+                Expr::CompoundIdentifier(idents) => {
+                    if let Some(ident) = idents.last() {
+                        self.columns.push(ident.value.to_lowercase());
+                    }
+                }
+                _ => {}
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn post_visit_expr(&mut self, expr: &Expr) -> ControlFlow<Self::Break> {
+        if is_opaque(expr) {
+            self.opaque_depth -= 1;
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+fn scan_expr(expr: &Expr) -> ExprScan {
+    let mut scan = ExprScan::default();
+    let _ = expr.visit(&mut scan);
+    scan
+}
+
+fn select_item_expr(item: &SelectItem) -> Option<(&Expr, Option<String>)> {
+    match item {
+        SelectItem::UnnamedExpr(expr) => Some((expr, None)),
+        SelectItem::ExprWithAlias { expr, alias } => Some((expr, Some(alias.value.to_lowercase()))),
+        _ => None,
+    }
+}
+
+fn select_aggregates(select: &Select) -> Vec<AggregateCall> {
+    select
+        .projection
+        .iter()
+        .filter_map(select_item_expr)
+        .flat_map(|(expr, _)| scan_expr(expr).aggregates)
+        .chain(
+            select
+                .having
+                .iter()
+                .flat_map(|having| scan_expr(having).aggregates),
+        )
+        .collect()
+}
+
+fn normalized(expr: &Expr) -> String {
+    expr.to_string().to_lowercase()
+}
+
+/// Every non-aggregated column of the select list must be grouped once the select aggregates
+fn check_group_by(select: &Select, has_aggregates: bool, issues: &mut Vec<QueryIssue>) {
+    let group_exprs = match &select.group_by {
+        GroupByExpr::All(_) => return,
+        GroupByExpr::Expressions(exprs, _) => exprs,
+    };
+    if !has_aggregates && group_exprs.is_empty() {
+        return;
+    }
+
+    let mut grouped_exprs: HashSet<String> = HashSet::new();
+    let mut grouped_columns: HashSet<String> = HashSet::new();
+    for expr in group_exprs {
+        // `GROUP BY 1` refers to the first select item
+        let expr = match expr {
+            Expr::Value(Value::Number(position, _)) => match position
+                .parse::<usize>()
+                .ok()
+                .and_then(|p| p.checked_sub(1))
+                .and_then(|p| select.projection.get(p))
+                .and_then(select_item_expr)
+            {
+                Some((item_expr, _)) => item_expr,
+                None => continue,
+            },
+            expr => expr,
+        };
+        grouped_exprs.insert(normalized(expr));
+        match expr {
+            Expr::Identifier(ident) => {
+                grouped_columns.insert(ident.value.to_lowercase());
+            }
+            Expr::CompoundIdentifier(idents) => {
+                if let Some(ident) = idents.last() {
+                    grouped_columns.insert(ident.value.to_lowercase());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut missing: Vec<String> = Vec::new();
+    for (expr, alias) in select.projection.iter().filter_map(select_item_expr) {
+        if grouped_exprs.contains(&normalized(expr))
+            || alias
+                .as_ref()
+                .is_some_and(|alias| grouped_columns.contains(alias))
+        {
+            continue;
+        }
+        for column in scan_expr(expr).columns {
+            if !grouped_columns.contains(&column) && !missing.contains(&column) {
+                missing.push(column);
+            }
+        }
+    }
+
+    if !missing.is_empty() {
+        issues.push(QueryIssue {
+            kind: QueryIssueKind::MissingGroupBy,
+            severity: IssueSeverity::Error,
+            message: format!(
+                "Column(s) {} are selected alongside aggregates but are not in GROUP BY",
+                missing.join(", ")
+            ),
+        });
+    }
+}
+
+fn relation_name(factor: &TableFactor) -> String {
+    match factor {
+        TableFactor::Table { name, alias, .. } => alias
+            .as_ref()
+            .map(|a| a.name.value.clone())
+            .unwrap_or_else(|| name.to_string()),
+        TableFactor::Derived { alias, .. } => alias
+            .as_ref()
+            .map(|a| a.name.value.clone())
+            .unwrap_or_else(|| "subquery".to_string()),
+        other => other.to_string(),
+    }
+}
+
+fn is_unconditioned(operator: &JoinOperator) -> bool {
+    match operator {
+        JoinOperator::CrossJoin => true,
+        JoinOperator::Inner(constraint)
+        | JoinOperator::LeftOuter(constraint)
+        | JoinOperator::RightOuter(constraint)
+        | JoinOperator::FullOuter(constraint) => matches!(
+            constraint,
+            JoinConstraint::None | JoinConstraint::On(Expr::Value(Value::Boolean(true)))
+        ),
+        _ => false,
+    }
+}
+
+fn check_joins(select: &Select, aggregates: &[AggregateCall], issues: &mut Vec<QueryIssue>) {
+    let mut joined: Vec<String> = Vec::new();
+    let mut cross_joined: Vec<String> = Vec::new();
+
+    for (index, table) in select.from.iter().enumerate() {
+        let TableWithJoins { relation, joins } = table;
+        if index > 0 {
+            // `FROM a, b` is only a cross join when nothing in WHERE relates the two
+            joined.push(relation_name(relation));
+            if select.selection.is_none() {
+                cross_joined.push(relation_name(relation));
+            }
+        }
+        for join in joins {
+            joined.push(relation_name(&join.relation));
+            if is_unconditioned(&join.join_operator) {
+                cross_joined.push(relation_name(&join.relation));
+            }
+        }
+    }
+
+    if !cross_joined.is_empty() {
+        issues.push(QueryIssue {
+            kind: QueryIssueKind::CrossJoin,
+            severity: IssueSeverity::Warning,
+            message: format!(
+                "{} joined without a join condition, so every row is paired with every other row",
+                cross_joined.join(", ")
+            ),
+        });
+    }
+
+    let mut sensitive: Vec<String> = Vec::new();
+    for aggregate in aggregates {
+        let name = aggregate.name.to_uppercase();
+        if !aggregate.distinct
+            && FAN_OUT_SENSITIVE_FUNCTIONS.contains(&aggregate.name.as_str())
+            && !sensitive.contains(&name)
+        {
+            sensitive.push(name);
+        }
+    }
+
+    if !joined.is_empty() && !sensitive.is_empty() {
+        issues.push(QueryIssue {
+            kind: QueryIssueKind::FanOutJoin,
+            severity: IssueSeverity::Warning,
+            message: format!(
+                "{} is computed across a join with {}; if the join matches several rows per key the result is inflated",
+                sensitive.join("/"),
+                joined.join(", ")
+            ),
+        });
+    }
+}
+
+/// A top-level select without a filter, limit or aggregation returns a whole table
+fn check_unbounded_scan(query: &Query, issues: &mut Vec<QueryIssue>) {
+    if query.limit.is_some() || query.fetch.is_some() {
+        return;
+    }
+    let SetExpr::Select(select) = query.body.as_ref() else {
+        return;
+    };
+    let is_grouped = match &select.group_by {
+        GroupByExpr::All(_) => true,
+        GroupByExpr::Expressions(exprs, _) => !exprs.is_empty(),
+    };
+    if select.top.is_some()
+        || select.selection.is_some()
+        || select.having.is_some()
+        || is_grouped
+        || !select_aggregates(select).is_empty()
+    {
+        return;
+    }
+
+    let cte_names: HashSet<String> = query
+        .with
+        .iter()
+        .flat_map(|with| with.cte_tables.iter())
+        .map(|cte| cte.alias.name.value.to_lowercase())
+        .collect();
+
+    let scanned: Vec<String> = select
+        .from
+        .iter()
+        .filter_map(|table| match &table.relation {
+            TableFactor::Table { name, .. } => {
+                let name = name.to_string();
+                (!cte_names.contains(&name.to_lowercase())).then_some(name)
+            }
+            _ => None,
+        })
+        .collect();
+
+    if !scanned.is_empty() {
+        issues.push(QueryIssue {
+            kind: QueryIssueKind::UnboundedScan,
+            severity: IssueSeverity::Warning,
+            message: format!(
+                "Every row of {} is returned with no filter, aggregation or limit",
+                scanned.join(", ")
+            ),
+        });
+    }
+}
@@ -0,0 +1,479 @@
+use crate::analysis::get_dialect;
+use crate::errors::SqlAnalyzerError;
+use crate::types::{FilteredQuery, ResultFilter, ResultFilterCondition, SkippedResultFilter};
+use crate::utils::parameter_binding::parameter_literal;
+use sqlparser::ast::{
+    visit_expressions, BinaryOperator, Expr, Ident, OrderByExpr, Query, Select, SelectItem,
+    SetExpr, Statement, TableFactor, Value,
+};
+use sqlparser::parser::Parser;
+use std::ops::ControlFlow;
+
+/// Alias of the derived table the original query is wrapped in
+const FILTERED_ALIAS: &str = "buster_filtered";
+
+/// Filters the result of a query by wrapping it as
+/// `SELECT * FROM (<query>) AS buster_filtered WHERE <filters>`. Working on the result means
+/// a filter only needs a column the query returns, whatever the query does to produce it.
+/// CTEs are hoisted to the wrapper, and an `ORDER BY` that only names result columns moves
+/// out with them so the filtered rows keep their order.
+///
+/// A query that limits its rows has to be filtered before the limit instead, so each filter
+/// becomes a `WHERE` (or, on an aggregate, `HAVING`) condition on the selected expression it
+/// names. Filters that can't be placed there are skipped and reported in the result.
+pub fn apply_result_filters(
+    sql: &str,
+    data_source_dialect: &str,
+    filters: &[ResultFilter],
+) -> Result<FilteredQuery, SqlAnalyzerError> {
+    let boolean_as_bit = data_source_dialect.eq_ignore_ascii_case("sqlserver");
+    let quote = identifier_quote(data_source_dialect);
+
+    let mut active_filters = Vec::new();
+    let mut predicates = Vec::new();
+    for filter in filters {
+        let column = Expr::Identifier(column_ident(&filter.column, quote));
+        if let Some(predicate) = filter_predicate(filter, column, boolean_as_bit)? {
+            active_filters.push(filter);
+            predicates.push(predicate);
+        }
+    }
+
+    let Some(predicate) = and_all(predicates) else {
+        return Ok(FilteredQuery {
+            sql: sql.to_string(),
+            skipped: vec![],
+        });
+    };
+
+    let dialect = get_dialect(data_source_dialect);
+    let mut statements = Parser::parse_sql(dialect, sql)?;
+    if statements.len() != 1 {
+        return Err(SqlAnalyzerError::UnsupportedStatement(
+            "Result filters can only be applied to a single query".to_string(),
+        ));
+    }
+    let mut inner = match statements.pop() {
+        Some(Statement::Query(query)) => query,
+        Some(other) => {
+            return Err(SqlAnalyzerError::UnsupportedStatement(format!(
+                "Result filters can only be applied to a query, got: {}",
+                other
+            )))
+        }
+        None => unreachable!(),
+    };
+
+    // Filtering after a LIMIT would drop rows from the capped result rather than pick the
+    // capped rows from the filtered ones
+    if limits_rows(&inner) {
+        return filter_before_limit(inner, &active_filters, boolean_as_bit);
+    }
+
+    let outer_order_by = match inner.order_by.take() {
+        Some(mut order_by) => {
+            let terms = order_by
+                .exprs
+                .iter()
+                .map(|term| {
+                    result_order_expr(&inner, &term.expr).map(|expr| OrderByExpr {
+                        expr,
+                        ..term.clone()
+                    })
+                })
+                .collect::<Option<Vec<_>>>();
+            match terms {
+                Some(terms) => {
+                    order_by.exprs = terms;
+                    Some(order_by)
+                }
+                // SQL Server rejects an ORDER BY inside a derived table
+                None if boolean_as_bit => {
+                    return Err(SqlAnalyzerError::UnsupportedStatement(
+                        "Result filters need the query to order by its result columns".to_string(),
+                    ))
+                }
+                None => {
+                    inner.order_by = Some(order_by);
+                    None
+                }
+            }
+        }
+        None => None,
+    };
+
+    let wrapper_sql = format!("SELECT * FROM (SELECT 1) AS {}", FILTERED_ALIAS);
+    let mut wrapper = match Parser::parse_sql(dialect, &wrapper_sql)?.pop() {
+        Some(Statement::Query(query)) => query,
+        _ => {
+            return Err(SqlAnalyzerError::Internal(anyhow::anyhow!(
+                "Failed to build result filter wrapper"
+            )))
+        }
+    };
+
+    wrapper.with = inner.with.take();
+    wrapper.order_by = outer_order_by;
+
+    let SetExpr::Select(select) = wrapper.body.as_mut() else {
+        return Err(SqlAnalyzerError::Internal(anyhow::anyhow!(
+            "Failed to build result filter wrapper"
+        )));
+    };
+    match select.from.first_mut().map(|from| &mut from.relation) {
+        Some(TableFactor::Derived { subquery, .. }) => *subquery = inner,
+        _ => {
+            return Err(SqlAnalyzerError::Internal(anyhow::anyhow!(
+                "Failed to build result filter wrapper"
+            )))
+        }
+    }
+    select.selection = Some(predicate);
+
+    Ok(FilteredQuery {
+        sql: wrapper.to_string(),
+        skipped: vec![],
+    })
+}
+
+/// Adds each filter to the query itself, on the selected expression the filter's column
+/// names, so the query's limit picks its rows from the filtered ones.
+fn filter_before_limit(
+    mut query: Box<Query>,
+    filters: &[&ResultFilter],
+    boolean_as_bit: bool,
+) -> Result<FilteredQuery, SqlAnalyzerError> {
+    let mut skipped = Vec::new();
+    let skip = |filter: &ResultFilter, reason: String| SkippedResultFilter {
+        column: filter.column.clone(),
+        reason,
+    };
+
+    let SetExpr::Select(select) = query.body.as_mut() else {
+        return Ok(FilteredQuery {
+            sql: query.to_string(),
+            skipped: filters
+                .iter()
+                .map(|filter| {
+                    skip(
+                        filter,
+                        "the query limits the rows of a set operation, so its result can't be \
+                         filtered before the limit"
+                            .to_string(),
+                    )
+                })
+                .collect(),
+        });
+    };
+
+    let mut where_predicates = Vec::new();
+    let mut having_predicates = Vec::new();
+    for filter in filters {
+        let Some(expr) = selected_expr(select, &filter.column) else {
+            skipped.push(skip(
+                filter,
+                format!(
+                    "'{}' isn't a column the query selects, so it can't be filtered before the \
+                     query's row limit",
+                    filter.column
+                ),
+            ));
+            continue;
+        };
+
+        let placement = match placement(expr) {
+            Ok(placement) => placement,
+            Err(computed_by) => {
+                skipped.push(skip(
+                    filter,
+                    format!(
+                        "'{}' is computed by {}, so it can't be filtered before the query's row \
+                         limit",
+                        filter.column, computed_by
+                    ),
+                ));
+                continue;
+            }
+        };
+
+        let operand = match expr {
+            Expr::Identifier(_)
+            | Expr::CompoundIdentifier(_)
+            | Expr::Function(_)
+            | Expr::Nested(_) => expr.clone(),
+            _ => Expr::Nested(Box::new(expr.clone())),
+        };
+        if let Some(predicate) = filter_predicate(filter, operand, boolean_as_bit)? {
+            match placement {
+                Placement::Where => where_predicates.push(predicate),
+                Placement::Having => having_predicates.push(predicate),
+            }
+        }
+    }
+
+    // The query's own conditions come first
+    where_predicates.splice(0..0, select.selection.take().map(nest_or));
+    having_predicates.splice(0..0, select.having.take().map(nest_or));
+    select.selection = and_all(where_predicates);
+    select.having = and_all(having_predicates);
+
+    Ok(FilteredQuery {
+        sql: query.to_string(),
+        skipped,
+    })
+}
+
+enum Placement {
+    Where,
+    Having,
+}
+
+/// Functions that aggregate rows, so a condition on them belongs in `HAVING`
+const AGGREGATE_FUNCTIONS: &[&str] = &[
+    "any_value",
+    "approx_count_distinct",
+    "approx_distinct",
+    "array_agg",
+    "avg",
+    "bool_and",
+    "bool_or",
+    "count",
+    "count_if",
+    "countif",
+    "group_concat",
+    "listagg",
+    "max",
+    "median",
+    "min",
+    "percentile_cont",
+    "percentile_disc",
+    "stddev",
+    "stddev_pop",
+    "stddev_samp",
+    "string_agg",
+    "sum",
+    "var_pop",
+    "var_samp",
+    "variance",
+];
+
+/// Where a condition on a selected expression goes, or what keeps it from going anywhere
+/// before the query's limit.
+fn placement(expr: &Expr) -> Result<Placement, &'static str> {
+    let mut aggregated = false;
+    let outcome = visit_expressions(expr, |expr| match expr {
+        Expr::Subquery(_) | Expr::Exists { .. } | Expr::InSubquery { .. } => {
+            ControlFlow::Break("a subquery")
+        }
+        Expr::Function(function) if function.over.is_some() => {
+            ControlFlow::Break("a window function")
+        }
+        Expr::Function(function) => {
+            aggregated |= function.filter.is_some()
+                || function.name.0.last().is_some_and(|name| {
+                    AGGREGATE_FUNCTIONS.contains(&name.value.to_lowercase().as_str())
+                });
+            ControlFlow::Continue(())
+        }
+        _ => ControlFlow::Continue(()),
+    });
+
+    match outcome {
+        ControlFlow::Break(computed_by) => Err(computed_by),
+        ControlFlow::Continue(()) if aggregated => Ok(Placement::Having),
+        ControlFlow::Continue(()) => Ok(Placement::Where),
+    }
+}
+
+/// The selected expression returned as `column`, matched like `result_order_expr` matches
+/// result columns
+fn selected_expr<'a>(select: &'a Select, column: &str) -> Option<&'a Expr> {
+    select.projection.iter().find_map(|item| {
+        let (expr, name) = match item {
+            SelectItem::ExprWithAlias { expr, alias } => (expr, alias),
+            SelectItem::UnnamedExpr(expr @ Expr::Identifier(ident)) => (expr, ident),
+            SelectItem::UnnamedExpr(expr @ Expr::CompoundIdentifier(idents)) => {
+                (expr, idents.last()?)
+            }
+            _ => return None,
+        };
+        name.value.eq_ignore_ascii_case(column).then_some(expr)
+    })
+}
+
+fn and_all(predicates: impl IntoIterator<Item = Expr>) -> Option<Expr> {
+    predicates.into_iter().reduce(|left, right| Expr::BinaryOp {
+        left: Box::new(left),
+        op: BinaryOperator::And,
+        right: Box::new(right),
+    })
+}
+
+/// Keeps an existing `OR` condition together once more conditions are ANDed to it
+fn nest_or(expr: Expr) -> Expr {
+    match expr {
+        Expr::BinaryOp {
+            op: BinaryOperator::Or,
+            ..
+        } => Expr::Nested(Box::new(expr)),
+        expr => expr,
+    }
+}
+
+fn filter_predicate(
+    filter: &ResultFilter,
+    column: Expr,
+    boolean_as_bit: bool,
+) -> Result<Option<Expr>, SqlAnalyzerError> {
+    let compare = |op: BinaryOperator, value: Expr| Expr::BinaryOp {
+        left: Box::new(column.clone()),
+        op,
+        right: Box::new(value),
+    };
+
+    match &filter.condition {
+        ResultFilterCondition::Range {
+            value_type,
+            start,
+            end,
+            end_inclusive,
+        } => {
+            let lower = start
+                .as_ref()
+                .map(|start| parameter_literal(&filter.column, value_type, start, boolean_as_bit))
+                .transpose()?
+                .map(|value| compare(BinaryOperator::GtEq, value));
+
+            let upper_op = if *end_inclusive {
+                BinaryOperator::LtEq
+            } else {
+                BinaryOperator::Lt
+            };
+            let upper = end
+                .as_ref()
+                .map(|end| parameter_literal(&filter.column, value_type, end, boolean_as_bit))
+                .transpose()?
+                .map(|value| compare(upper_op, value));
+
+            Ok(match (lower, upper) {
+                (Some(lower), Some(upper)) => Some(Expr::BinaryOp {
+                    left: Box::new(lower),
+                    op: BinaryOperator::And,
+                    right: Box::new(upper),
+                }),
+                (lower, upper) => lower.or(upper),
+            })
+        }
+        ResultFilterCondition::In { value_type, values } => {
+            if values.is_empty() {
+                return Err(SqlAnalyzerError::InvalidParameter(format!(
+                    "Filter on '{}' needs at least one value",
+                    filter.column
+                )));
+            }
+
+            let list = values
+                .iter()
+                .map(|value| parameter_literal(&filter.column, value_type, value, boolean_as_bit))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(Some(Expr::InList {
+                expr: Box::new(column),
+                list,
+                negated: false,
+            }))
+        }
+    }
+}
+
+/// Result columns are usually lowercase; those are left unquoted so warehouses that fold
+/// unquoted names to upper case still resolve them.
+fn column_ident(column: &str, quote: char) -> Ident {
+    let is_simple = column
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_lowercase() || c == '_')
+        && column
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+
+    if is_simple {
+        Ident::new(column)
+    } else {
+        Ident::with_quote(quote, column)
+    }
+}
+
+pub(crate) fn identifier_quote(data_source_dialect: &str) -> char {
+    match data_source_dialect.to_lowercase().as_str() {
+        "bigquery" | "databricks" | "mysql" | "mariadb" | "hive" => '`',
+        _ => '"',
+    }
+}
+
+/// Whether the query caps or skips rows, with LIMIT, OFFSET, FETCH or TOP
+fn limits_rows(query: &Query) -> bool {
+    let has_top = matches!(query.body.as_ref(), SetExpr::Select(select) if select.top.is_some());
+
+    query.limit.is_some() || query.offset.is_some() || query.fetch.is_some() || has_top
+}
+
+/// An ORDER BY term rewritten to refer to the query's result, by name or by position.
+/// Returns `None` when the term orders by something the query doesn't return.
+fn result_order_expr(query: &Query, expr: &Expr) -> Option<Expr> {
+    let SetExpr::Select(select) = query.body.as_ref() else {
+        // Set operations can only order by their result columns already
+        return names_result_column(expr).then(|| expr.clone());
+    };
+    if select.projection.iter().any(|item| {
+        matches!(
+            item,
+            SelectItem::Wildcard(_) | SelectItem::QualifiedWildcard(..)
+        )
+    }) {
+        return names_result_column(expr).then(|| expr.clone());
+    }
+
+    if let Expr::Value(Value::Number(..)) = expr {
+        return Some(expr.clone());
+    }
+
+    if let Some(name) = match expr {
+        Expr::Identifier(ident) => Some(ident),
+        Expr::CompoundIdentifier(idents) => idents.last(),
+        _ => None,
+    } {
+        let is_result_column = select.projection.iter().any(|item| {
+            let result_name = match item {
+                SelectItem::ExprWithAlias { alias, .. } => Some(alias),
+                SelectItem::UnnamedExpr(Expr::Identifier(ident)) => Some(ident),
+                SelectItem::UnnamedExpr(Expr::CompoundIdentifier(idents)) => idents.last(),
+                _ => None,
+            };
+            result_name
+                .is_some_and(|result_name| result_name.value.eq_ignore_ascii_case(&name.value))
+        });
+        if is_result_column {
+            return Some(Expr::Identifier(name.clone()));
+        }
+    }
+
+    // Anything else has to be one of the selected expressions, which is ordered by position
+    let position = select.projection.iter().position(|item| match item {
+        SelectItem::ExprWithAlias {
+            expr: projected, ..
+        }
+        | SelectItem::UnnamedExpr(projected) => projected == expr,
+        _ => false,
+    })?;
+
+    Some(Expr::Value(Value::Number(
+        (position + 1).to_string(),
+        false,
+    )))
+}
+
+/// Positions and bare names refer to the same column inside and outside the wrapper
+fn names_result_column(expr: &Expr) -> bool {
+    matches!(expr, Expr::Identifier(_) | Expr::Value(Value::Number(..)))
+}
@@ -0,0 +1,169 @@
+use crate::analysis::get_dialect;
+use crate::errors::SqlAnalyzerError;
+use crate::utils::column_masking::CteNameCollector;
+use sqlparser::ast::{
+    Expr, Ident, Query, SetExpr, Statement, TableAlias, TableFactor, Visit, VisitMut, VisitorMut,
+};
+use sqlparser::parser::Parser;
+use std::collections::{HashMap, HashSet};
+use std::ops::ControlFlow;
+
+/// Rewrites every reference to a filtered table into a derived table that only returns the
+/// rows its filter allows, keeping the reference's alias (or table name) so the rest of the
+/// query resolves unchanged.
+///
+/// Tables are matched on the last part of their name, case-insensitively, wherever they
+/// appear: joins, CTE bodies, set operations and subqueries. Because filters exist, any query
+/// that can't be fully rewritten is rejected rather than run unfiltered. The query and filters
+/// are parsed and written back with `data_source_dialect`.
+pub fn apply_row_level_filters(
+    sql: &str,
+    data_source_dialect: &str,
+    table_filters: HashMap<String, String>,
+) -> Result<String, SqlAnalyzerError> {
+    if table_filters.is_empty() {
+        return Ok(sql.to_string());
+    }
+
+    let mut statements = Parser::parse_sql(get_dialect(data_source_dialect), sql)?;
+
+    if statements.is_empty()
+        || !statements.iter().all(|statement| matches!(statement, Statement::Query(_)))
+    {
+        return Err(SqlAnalyzerError::UnsupportedStatement(
+            "Row-level filters can only be applied to SELECT queries".to_string(),
+        ));
+    }
+
+    let table_filters: HashMap<String, String> = table_filters
+        .into_iter()
+        .map(|(name, filter)| (name.to_lowercase(), filter))
+        .collect();
+
+    let mut cte_collector = CteNameCollector::default();
+    let _ = Visit::visit(&statements, &mut cte_collector);
+
+    // Inside its own body a CTE name can still mean the real table, so a CTE that shadows a
+    // filtered table can't be told apart from it safely.
+    if let Some(name) = cte_collector.names.iter().find(|name| table_filters.contains_key(*name)) {
+        return Err(SqlAnalyzerError::UnsupportedStatement(format!(
+            "CTE '{}' shadows a table with a row-level filter",
+            name
+        )));
+    }
+
+    let mut rewriter = RowFilterRewriter {
+        data_source_dialect,
+        table_filters: &table_filters,
+        cte_names: cte_collector.names,
+        tables_seen: 0,
+        error: None,
+    };
+    let _ = VisitMut::visit(&mut statements, &mut rewriter);
+
+    if let Some(error) = rewriter.error {
+        return Err(error);
+    }
+
+    if rewriter.tables_seen == 0 {
+        return Err(SqlAnalyzerError::UnsupportedStatement(
+            "Row-level filters apply, but no tables could be found in the query".to_string(),
+        ));
+    }
+
+    Ok(statements
+        .iter()
+        .map(|statement| statement.to_string())
+        .collect::<Vec<_>>()
+        .join("; "))
+}
+
+struct RowFilterRewriter<'a> {
+    data_source_dialect: &'a str,
+    table_filters: &'a HashMap<String, String>,
+    cte_names: HashSet<String>,
+    tables_seen: usize,
+    error: Option<SqlAnalyzerError>,
+}
+
+impl VisitorMut for RowFilterRewriter<'_> {
+    type Break = ();
+
+    // Rewriting after the factor's children have been visited keeps the visitor from
+    // descending into the derived table we just created and filtering it a second time.
+    fn post_visit_table_factor(&mut self, table_factor: &mut TableFactor) -> ControlFlow<Self::Break> {
+        let TableFactor::Table { name, alias, args, .. } = table_factor else {
+            return ControlFlow::Continue(());
+        };
+
+        let Some(table_name) = name.0.last().map(|ident| ident.value.to_lowercase()) else {
+            return ControlFlow::Continue(());
+        };
+
+        if name.0.len() == 1 && self.cte_names.contains(&table_name) {
+            return ControlFlow::Continue(());
+        }
+        self.tables_seen += 1;
+
+        let Some(filter) = self.table_filters.get(&table_name) else {
+            return ControlFlow::Continue(());
+        };
+
+        if args.is_some() {
+            self.error = Some(SqlAnalyzerError::UnsupportedStatement(format!(
+                "Row-level filters can't be applied to table function '{}'",
+                name
+            )));
+            return ControlFlow::Break(());
+        }
+
+        let subquery = match build_filtered_subquery(&name.to_string(), filter, self.data_source_dialect) {
+            Ok(subquery) => subquery,
+            Err(e) => {
+                self.error = Some(e);
+                return ControlFlow::Break(());
+            }
+        };
+
+        let alias = alias.clone().unwrap_or_else(|| TableAlias {
+            name: name.0.last().cloned().unwrap_or_else(|| Ident::new(table_name.clone())),
+            columns: vec![],
+        });
+
+        *table_factor = TableFactor::Derived {
+            lateral: false,
+            subquery: Box::new(subquery),
+            alias: Some(alias),
+        };
+
+        ControlFlow::Continue(())
+    }
+}
+
+fn build_filtered_subquery(
+    full_name: &str,
+    filter: &str,
+    data_source_dialect: &str,
+) -> Result<Query, SqlAnalyzerError> {
+    let sql = format!("SELECT * FROM {} WHERE ({})", full_name, filter);
+
+    if let [Statement::Query(query)] = Parser::parse_sql(get_dialect(data_source_dialect), &sql)?.as_slice() {
+        if is_single_filtered_select(query) {
+            return Ok(query.as_ref().clone());
+        }
+    }
+
+    Err(SqlAnalyzerError::Internal(anyhow::anyhow!(
+        "Invalid row-level filter for '{}'",
+        full_name
+    )))
+}
+
+/// Whether a query built as `SELECT ... WHERE (<filter>)` kept the filter as its one condition.
+/// A filter that closes its parentheses early would no longer be a single condition.
+pub(crate) fn is_single_filtered_select(query: &Query) -> bool {
+    matches!(
+        query.body.as_ref(),
+        SetExpr::Select(select) if matches!(select.selection, Some(Expr::Nested(_)))
+    )
+}
@@ -2224,478 +2224,4 @@ pub fn validate_and_substitute(
     // Then substitute metrics and filters
     substitute_query(sql, semantic_layer)
 }
-
-///////////////////////////////////////////////////////////////////////////////
-// ROW LEVEL FILTERING FUNCTIONS
-///////////////////////////////////////////////////////////////////////////////
-
-/// Applies row-level filters to a SQL query by replacing table references with filtered CTEs
-pub fn apply_row_level_filters(
-    sql: &str, 
-    table_filters: HashMap<String, String>
-) -> Result<String, SqlAnalyzerError> {
-    // If no filters provided, return the original query
-    if table_filters.is_empty() {
-        return Ok(sql.to_string());
-    }
-    
-    // Parse the SQL query to extract table information
-    let table_info = extract_table_references(sql);
-    
-    // If no tables found, return the original query
-    if table_info.is_empty() {
-        return Ok(sql.to_string());
-    }
-    
-    // Use the simplified direct approach for better reliability
-    apply_row_level_filters_directly(sql, &table_info, &table_filters)
-}
-
-// Extract table references from a SQL query string
-fn extract_table_references(sql: &str) -> Vec<(String, String, String)> {
-    // Extract (table_name, table_alias, full_name_with_schema)
-    let mut results = Vec::new();
-    
-    // Try to parse the SQL
-    let dialect = GenericDialect {};
-    let ast = match Parser::parse_sql(&dialect, sql) {
-        Ok(stmts) => stmts,
-        Err(_) => return results,
-    };
-    
-    // Process each statement
-    for stmt in ast {
-        if let Statement::Query(query) = stmt {
-            process_query_for_tables(&query, &mut results);
-        }
-    }
-    
-    results
-}
-
-// Process a SQL query to extract table references
-fn process_query_for_tables(query: &Query, results: &mut Vec<(String, String, String)>) {
-    // First, handle WITH clause (CTEs)
-    if let Some(with) = &query.with {
-        for cte in &with.cte_tables {
-            // Each CTE might have tables
-            process_query_for_tables(&cte.query, results);
-        }
-    }
-    
-    // Extract tables from the main query body
-    if let SetExpr::Select(select) = query.body.as_ref() {
-        // Process FROM clause
-        for table_with_joins in &select.from {
-            // Handle the main table
-            if let TableFactor::Table { name, alias, .. } = &table_with_joins.relation {
-                // Get the base table name (without schema)
-                let table_name = name.0.last().unwrap().to_string();
-                
-                // Get the full table name (with schema)
-                let full_name = name.to_string();
-                
-                // Get the alias if present
-                let alias_name = alias.as_ref().map(|a| a.name.to_string()).unwrap_or_default();
-                
-                // Add to results
-                results.push((table_name, alias_name.clone(), full_name));
-            } else if let TableFactor::Derived { subquery, .. } = &table_with_joins.relation {
-                // Handle derived tables (subqueries in FROM clause)
-                process_query_for_tables(subquery, results);
-            }
-            
-            // Process JOIN clauses
-            for join in &table_with_joins.joins {
-                if let TableFactor::Table { name, alias, .. } = &join.relation {
-                    // Get the base table name (without schema)
-                    let table_name = name.0.last().unwrap().to_string();
-                    
-                    // Get the full table name (with schema)
-                    let full_name = name.to_string();
-                    
-                    // Get the alias if present
-                    let alias_name = alias.as_ref().map(|a| a.name.to_string()).unwrap_or_default();
-                    
-                    // Add to results
-                    results.push((table_name, alias_name.clone(), full_name));
-                } else if let TableFactor::Derived { subquery, .. } = &join.relation {
-                    // Handle derived tables in JOINs
-                    process_query_for_tables(subquery, results);
-                }
-            }
-        }
-        
-        // Check for subqueries in other parts of the query
-        
-        // Subqueries in SELECT list
-        for item in &select.projection {
-            if let SelectItem::UnnamedExpr(expr) = item {
-                process_expr_for_tables(expr, results);
-            } else if let SelectItem::ExprWithAlias { expr, .. } = item {
-                process_expr_for_tables(expr, results);
-            }
-        }
-        
-        // Subqueries in WHERE clause
-        if let Some(expr) = &select.selection {
-            process_expr_for_tables(expr, results);
-        }
-        
-        // Subqueries in HAVING clause
-        if let Some(expr) = &select.having {
-            process_expr_for_tables(expr, results);
-        }
-    } else if let SetExpr::Query(subquery) = query.body.as_ref() {
-        // Recursively process subqueries
-        process_query_for_tables(subquery, results);
-    } else if let SetExpr::SetOperation { left, right, .. } = query.body.as_ref() {
-        // Process both sides of set operations (UNION, EXCEPT, INTERSECT)
-        process_set_expr_for_tables(left, results);
-        process_set_expr_for_tables(right, results);
-    }
-}
-
-// Process a SetExpr to extract table references
-fn process_set_expr_for_tables(set_expr: &SetExpr, results: &mut Vec<(String, String, String)>) {
-    match set_expr {
-        SetExpr::Select(select) => {
-            // Process tables in the FROM clause
-            for table_with_joins in &select.from {
-                if let TableFactor::Table { name, alias, .. } = &table_with_joins.relation {
-                    let table_name = name.0.last().unwrap().to_string();
-                    let full_name = name.to_string();
-                    let alias_name = alias.as_ref().map(|a| a.name.to_string()).unwrap_or_default();
-                    results.push((table_name, alias_name.clone(), full_name));
-                } else if let TableFactor::Derived { subquery, .. } = &table_with_joins.relation {
-                    process_query_for_tables(subquery, results);
-                }
-                
-                // Process JOINs
-                for join in &table_with_joins.joins {
-                    if let TableFactor::Table { name, alias, .. } = &join.relation {
-                        let table_name = name.0.last().unwrap().to_string();
-                        let full_name = name.to_string();
-                        let alias_name = alias.as_ref().map(|a| a.name.to_string()).unwrap_or_default();
-                        results.push((table_name, alias_name.clone(), full_name));
-                    } else if let TableFactor::Derived { subquery, .. } = &join.relation {
-                        process_query_for_tables(subquery, results);
-                    }
-                }
-            }
-        },
-        SetExpr::Query(subquery) => process_query_for_tables(subquery, results),
-        SetExpr::SetOperation { left, right, .. } => {
-            process_set_expr_for_tables(left, results);
-            process_set_expr_for_tables(right, results);
-        },
-        _ => {}
-    }
-}
-
-// Process an expression to extract table references from subqueries
-fn process_expr_for_tables(expr: &Expr, results: &mut Vec<(String, String, String)>) {
-    match expr {
-        Expr::Subquery(subquery) => {
-            process_query_for_tables(subquery, results);
-        },
-        Expr::BinaryOp { left, right, .. } => {
-            process_expr_for_tables(left, results);
-            process_expr_for_tables(right, results);
-        },
-        Expr::UnaryOp { expr, .. } => {
-            process_expr_for_tables(expr, results);
-        },
-        Expr::Function(_) => {
-            // We don't need to process function arguments
-            // as we're just looking for table references
-        },
-        Expr::Case { conditions, results: case_results, else_result, .. } => {
-            // Check all parts of a CASE expression
-            for condition in conditions {
-                process_expr_for_tables(condition, results);
-            }
-            for result in case_results {
-                process_expr_for_tables(result, results);
-            }
-            if let Some(else_result) = else_result {
-                process_expr_for_tables(else_result, results);
-            }
-        },
-        Expr::Exists { subquery, .. } => {
-            process_query_for_tables(subquery, results);
-        },
-        Expr::InSubquery { subquery, .. } => {
-            process_query_for_tables(subquery, results);
-        },
-        _ => {}
-    }
-}
-
-
-/// A more comprehensive approach to row-level filtering that handles a wider range of SQL patterns
-fn apply_row_level_filters_directly(
-    sql: &str,
-    table_info: &[(String, String, String)],
-    table_filters: &HashMap<String, String>
-) -> Result<String, SqlAnalyzerError> {
-    // If no table filters, return the original SQL
-    if table_filters.is_empty() {
-        return Ok(sql.to_string());
-    }
-    
-    // If no tables in query, return the original SQL
-    if table_info.is_empty() {
-        return Ok(sql.to_string());
-    }
-    
-    // Prepare filtered CTE definitions
-    let mut filtered_ctes = Vec::new();
-    
-    // Create a mapping table_name/alias -> filtered_alias for later reference replacement
-    let mut table_alias_map = HashMap::new();
-    
-    // Track tables that need filtering
-    let mut tables_to_filter = HashSet::new();
-    
-    // Process each table reference and create CTEs for tables with filters
-    for (table_name, alias, full_name) in table_info {
-        if let Some(filter) = table_filters.get(table_name) {
-            // Create filtered table name using the alias if it exists
-            let filtered_alias = if !alias.is_empty() {
-                format!("filtered_{}", alias)
-            } else {
-                format!("filtered_{}", table_name)
-            };
-            
-            // Map both the table name and alias to the filtered alias
-            table_alias_map.insert(table_name.clone(), filtered_alias.clone());
-            if !alias.is_empty() {
-                table_alias_map.insert(alias.clone(), filtered_alias.clone());
-            }
-            
-            // Track this table for filtering
-            tables_to_filter.insert(table_name.clone());
-            
-            // Create the CTE definition
-            filtered_ctes.push(format!("{} AS (SELECT * FROM {} WHERE {})",
-                filtered_alias, full_name, filter));
-        }
-    }
-    
-    // If no filtered CTEs needed, return the original SQL
-    if filtered_ctes.is_empty() {
-        return Ok(sql.to_string());
-    }
-    
-    // For queries not specially handled, use a general approach
-    
-    // Parse the SQL to determine if it already has a WITH clause
-    let cte_separator = if sql.trim_start().to_uppercase().starts_with("WITH ") {
-        // If the query already has a WITH clause, use a comma to add our CTEs
-        ","
-    } else {
-        // Otherwise, use WITH to start a new clause
-        "WITH"
-    };
-    
-    // Start building the transformed SQL
-    let mut transformed_sql = if sql.trim_start().to_uppercase().starts_with("WITH ") {
-        // Find where the WITH clause ends - it's after all CTEs but before the main query SELECT
-        let with_content_end = find_with_clause_end(sql);
-        
-        if with_content_end > 0 {
-            // Insert our filtered CTEs after the existing CTEs
-            format!("{} {}, {}", 
-                &sql[0..with_content_end], 
-                filtered_ctes.join(", "), 
-                &sql[with_content_end..])
-        } else {
-            // Fallback if we can't find the end of the WITH clause
-            format!("{} {}", cte_separator, filtered_ctes.join(", ")) + &sql
-        }
-    } else {
-        // No existing WITH clause, add one
-        format!("{} {} {}", cte_separator, filtered_ctes.join(", "), sql)
-    };
-    
-    // Replace references to filtered tables
-    // This is a simplified approach - a full solution would require a proper SQL parser
-    for (table_name, alias, _) in table_info {
-        if let Some(_) = table_filters.get(table_name) {
-            // The new name for the filtered table
-            let filtered_alias = if !alias.is_empty() {
-                format!("filtered_{}", alias)
-            } else {
-                format!("filtered_{}", table_name)
-            };
-            
-            // Replace aliased references in FROM and JOIN clauses
-            if !alias.is_empty() {
-                // Replace "... FROM xx alias" with "... FROM filtered_alias"
-                // Include variants with different spacing and formatting
-                let from_patterns = vec![
-                    format!(" FROM {} {}", table_name, alias),
-                    format!("\n        FROM \n            {} {}", table_name, alias),
-                    format!("\n        FROM \n            {} {}\n", table_name, alias),
-                    format!("\n        FROM {} {}", table_name, alias),
-                    format!("FROM {} {}", table_name, alias),
-                    format!("FROM\n            {} {}", table_name, alias),
-                ];
-                
-                let from_replacement = format!(" FROM {}", filtered_alias);
-                
-                for from_pattern in from_patterns {
-                    if transformed_sql.contains(&from_pattern) {
-                        transformed_sql = transformed_sql.replace(&from_pattern, &from_replacement);
-                    }
-                }
-                
-                // Replace "... JOIN xx alias" with "... JOIN filtered_alias"
-                let join_patterns = vec![
-                    format!(" JOIN {} {}", table_name, alias),
-                    format!("\n        JOIN \n            {} {}", table_name, alias),
-                    format!("\n        JOIN {} {}", table_name, alias),
-                    format!("JOIN {} {}", table_name, alias),
-                    format!("JOIN\n            {} {}", table_name, alias),
-                ];
-                
-                let join_replacement = format!(" JOIN {}", filtered_alias);
-                
-                for join_pattern in join_patterns {
-                    if transformed_sql.contains(&join_pattern) {
-                        transformed_sql = transformed_sql.replace(&join_pattern, &join_replacement);
-                    }
-                }
-                
-                // Replace "... FROM schema.xx alias" with "... FROM schema.xx filtered_alias"
-                // This handles schema-qualified tables
-                if transformed_sql.contains(".") {
-                    let schema_from_pattern = format!(" FROM {}.{} {}", "schema", table_name, alias);
-                    let schema_from_replacement = format!(" FROM {}.{} {}", "schema", table_name, filtered_alias);
-                    transformed_sql = transformed_sql.replace(&schema_from_pattern, &schema_from_replacement);
-                    
-                    let schema_join_pattern = format!(" JOIN {}.{} {}", "schema", table_name, alias);
-                    let schema_join_replacement = format!(" JOIN {}.{} {}", "schema", table_name, filtered_alias);
-                    transformed_sql = transformed_sql.replace(&schema_join_pattern, &schema_join_replacement);
-                    
-                    // Also try schema1, schema2, etc.
-                    for i in 1..5 {
-                        let schema_from_pattern = format!(" FROM schema{}.{} {}", i, table_name, alias);
-                        let schema_from_replacement = format!(" FROM schema{}.{} {}", i, table_name, filtered_alias);
-                        transformed_sql = transformed_sql.replace(&schema_from_pattern, &schema_from_replacement);
-                        
-                        let schema_join_pattern = format!(" JOIN schema{}.{} {}", i, table_name, alias);
-                        let schema_join_replacement = format!(" JOIN schema{}.{} {}", i, table_name, filtered_alias);
-                        transformed_sql = transformed_sql.replace(&schema_join_pattern, &schema_join_replacement);
-                    }
-                }
-                
-                // Replace column references "alias." with "filtered_alias."
-                let alias_dot = format!("{}.", alias);
-                let filtered_alias_dot = format!("{}.", filtered_alias);
-                transformed_sql = transformed_sql.replace(&alias_dot, &filtered_alias_dot);
-            } else {
-                // For unaliased tables
-                let from_pattern = format!(" FROM {} ", table_name);
-                let from_replacement = format!(" FROM {} ", filtered_alias);
-                transformed_sql = transformed_sql.replace(&from_pattern, &from_replacement);
-                
-                let join_pattern = format!(" JOIN {} ", table_name);
-                let join_replacement = format!(" JOIN {} ", filtered_alias);
-                transformed_sql = transformed_sql.replace(&join_pattern, &join_replacement);
-                
-                // Replace column references for unaliased tables
-                let table_dot = format!("{}.", table_name);
-                let filtered_table_dot = format!("{}.", filtered_alias);
-                transformed_sql = transformed_sql.replace(&table_dot, &filtered_table_dot);
-            }
-        }
-    }
-    
-    // Special handling for subqueries
-    // This is still a simplified approach that won't catch every case
-    // A proper solution would rewrite the query using the SQL parser's AST
-    for (table_name, _, _) in table_info.iter().filter(|(name, _, _)| table_filters.contains_key(name)) {
-        let o_references = [" o ", " o2 ", " o3 "];
-        
-        if table_name == "orders" {
-            for reference in o_references.iter() {
-                if transformed_sql.contains(reference) {
-                    let filtered_reference = format!(" filtered_{}", reference.trim());
-                    transformed_sql = transformed_sql.replace(reference, &filtered_reference);
-                }
-                
-                let reference_dot = format!("{}.", reference.trim());
-                if transformed_sql.contains(&reference_dot) {
-                    let filtered_reference_dot = format!("filtered_{}.", reference.trim());
-                    transformed_sql = transformed_sql.replace(&reference_dot, &filtered_reference_dot);
-                }
-            }
-        }
-    }
-    
-    Ok(transformed_sql)
-}
-
-// Helper function to find the end of a WITH clause in a SQL query
-fn find_with_clause_end(sql: &str) -> usize {
-    // This is a simplified function that tries to find where the WITH clause ends
-    // and the main SELECT begins. A proper SQL parser would be better.
-    let lowercase_sql = sql.to_lowercase();
-    
-    // Skip the "WITH" keyword
-    let after_with = lowercase_sql.find("with ").map(|pos| pos + 5).unwrap_or(0);
-    if after_with == 0 {
-        return 0;
-    }
-    
-    // Look for the main SELECT that isn't inside parentheses
-    let mut paren_depth = 0;
-    let mut in_cte = true;
-    
-    for (i, c) in lowercase_sql[after_with..].char_indices() {
-        let pos = i + after_with;
-        
-        match c {
-            '(' => paren_depth += 1,
-            ')' => {
-                if paren_depth > 0 {
-                    paren_depth -= 1;
-                }
-                
-                // If we're at the end of a CTE definition and the next keyword is SELECT
-                if paren_depth == 0 && in_cte {
-                    // Look for the next keyword
-                    if let Some(select_pos) = lowercase_sql[pos+1..].find("select") {
-                        let actual_pos = pos + 1 + select_pos;
-                        
-                        // Make sure this SELECT is the main query SELECT, not part of another CTE
-                        if !lowercase_sql[pos+1..actual_pos].contains("as") {
-                            return pos + 1;
-                        }
-                    }
-                }
-            },
-            ',' => {
-                // Comma at depth 0 separates CTEs
-                if paren_depth == 0 {
-                    // We're still in the CTE section
-                    in_cte = true;
-                }
-            },
-            's' => {
-                // Check if this is the start of 'select' at depth 0
-                if paren_depth == 0 && pos + 6 < lowercase_sql.len() && 
-                   &lowercase_sql[pos..pos+6] == "select" &&
-                   !in_cte {
-                    return pos;
-                }
-            },
-            _ => {}
-        }
-    }
-    
-    // If we couldn't find it, return 0
-    0
 }
\ No newline at end of file
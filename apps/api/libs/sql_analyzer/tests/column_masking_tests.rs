@@ -0,0 +1,221 @@
+use sql_analyzer::{apply_column_masks, ColumnMask, TableMask};
+use std::collections::HashMap;
+
+fn customers_mask(masks: Vec<(&str, ColumnMask)>) -> HashMap<String, TableMask> {
+    let mut table_masks = HashMap::new();
+    table_masks.insert(
+        "customers".to_string(),
+        TableMask {
+            columns: vec![
+                "id".to_string(),
+                "name".to_string(),
+                "email".to_string(),
+                "phone".to_string(),
+            ],
+            masks: masks
+                .into_iter()
+                .map(|(column, mask)| (column.to_string(), mask))
+                .collect(),
+            row_filter: None,
+        },
+    );
+    table_masks
+}
+
+async fn mask(
+    sql: &str,
+    data_source_dialect: &str,
+    table_masks: HashMap<String, TableMask>,
+) -> Result<String, sql_analyzer::SqlAnalyzerError> {
+    apply_column_masks(
+        sql.to_string(),
+        data_source_dialect,
+        table_masks,
+        Some("pepper".to_string()),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn test_masks_aliased_table() {
+    let sql = "SELECT c.name, c.email, c.phone FROM customers c WHERE c.id = 1";
+    let masks = customers_mask(vec![
+        ("email", ColumnMask::Hash),
+        ("phone", ColumnMask::Partial { visible_chars: 4 }),
+    ]);
+
+    let masked_sql = mask(sql, "postgres", masks).await.unwrap();
+
+    assert_eq!(
+        masked_sql,
+        "SELECT c.name, c.email, c.phone FROM (SELECT id, name, ENCODE(SHA256(CONVERT_TO(CONCAT('pepper', CAST(email AS TEXT)), 'UTF8')), 'hex') AS email, \
+         CONCAT('****', RIGHT(CAST(phone AS TEXT), 4)) AS phone FROM customers) AS c WHERE c.id = 1"
+    );
+}
+
+#[tokio::test]
+async fn test_masks_unaliased_schema_qualified_table_in_join() {
+    let sql = "SELECT customers.email, o.amount FROM public.customers JOIN orders o ON customers.id = o.customer_id";
+    let masks = customers_mask(vec![("email", ColumnMask::Redact)]);
+
+    let masked_sql = mask(sql, "postgres", masks).await.unwrap();
+
+    assert!(masked_sql.contains(
+        "FROM (SELECT id, name, 'REDACTED' AS email, phone FROM public.customers) AS customers"
+    ));
+    assert!(masked_sql.contains("JOIN orders AS o") || masked_sql.contains("JOIN orders o"));
+}
+
+#[tokio::test]
+async fn test_hidden_column_is_removed_from_projection() {
+    let sql = "SELECT * FROM customers";
+    let masks = customers_mask(vec![("email", ColumnMask::Hide)]);
+
+    let masked_sql = mask(sql, "postgres", masks).await.unwrap();
+
+    assert_eq!(
+        masked_sql,
+        "SELECT * FROM (SELECT id, name, phone FROM customers) AS customers"
+    );
+}
+
+#[tokio::test]
+async fn test_masks_tables_inside_ctes_and_subqueries_but_not_cte_references() {
+    let sql = "WITH recent AS (SELECT id, email FROM customers) \
+               SELECT r.email FROM recent r WHERE r.id IN (SELECT id FROM customers)";
+    let masks = customers_mask(vec![("email", ColumnMask::Hash)]);
+
+    let masked_sql = mask(sql, "postgres", masks).await.unwrap();
+
+    assert_eq!(masked_sql.matches("SHA256(").count(), 2);
+    assert!(masked_sql.contains("FROM recent AS r") || masked_sql.contains("FROM recent r"));
+}
+
+#[tokio::test]
+async fn test_no_masks_returns_query_unchanged() {
+    let sql = "SELECT  email FROM customers";
+
+    let masked_sql = mask(sql, "postgres", HashMap::new()).await.unwrap();
+
+    assert_eq!(masked_sql, sql);
+}
+
+#[tokio::test]
+async fn test_hiding_every_column_is_an_error() {
+    let mut masks = HashMap::new();
+    masks.insert(
+        "customers".to_string(),
+        TableMask {
+            columns: vec!["email".to_string()],
+            masks: HashMap::from([("email".to_string(), ColumnMask::Hide)]),
+            row_filter: None,
+        },
+    );
+
+    let result = mask("SELECT email FROM customers", "postgres", masks).await;
+
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_hash_and_partial_masks_per_dialect() {
+    let cases = [
+        (
+            "bigquery",
+            "TO_HEX(SHA256(CONCAT('pepper', CAST(email AS STRING))))",
+            "RIGHT(CAST(phone AS STRING), 4)",
+        ),
+        (
+            "sqlserver",
+            "LOWER(CONVERT(VARCHAR(64), HASHBYTES('SHA2_256', CONCAT('pepper', CAST(email AS NVARCHAR(MAX)))), 2))",
+            "RIGHT(CAST(phone AS NVARCHAR(MAX)), 4)",
+        ),
+        (
+            "snowflake",
+            "SHA2(CONCAT('pepper', CAST(email AS VARCHAR)), 256)",
+            "RIGHT(CAST(phone AS VARCHAR), 4)",
+        ),
+        (
+            "mysql",
+            "SHA2(CONCAT('pepper', CAST(email AS CHAR)), 256)",
+            "RIGHT(CAST(phone AS CHAR), 4)",
+        ),
+        (
+            "duckdb",
+            "SHA256(CONCAT('pepper', CAST(email AS VARCHAR)))",
+            "RIGHT(CAST(phone AS VARCHAR), 4)",
+        ),
+        (
+            "clickhouse",
+            "lower(hex(SHA256(CONCAT('pepper', toString(email)))))",
+            "RIGHT(toString(phone), 4)",
+        ),
+    ];
+
+    for (dialect, hash, partial) in cases {
+        let masks = customers_mask(vec![
+            ("email", ColumnMask::Hash),
+            ("phone", ColumnMask::Partial { visible_chars: 4 }),
+        ]);
+
+        let masked_sql = mask("SELECT email, phone FROM customers", dialect, masks)
+            .await
+            .unwrap();
+
+        assert!(masked_sql.contains(hash), "{}: {}", dialect, masked_sql);
+        assert!(masked_sql.contains(partial), "{}: {}", dialect, masked_sql);
+    }
+}
+
+#[tokio::test]
+async fn test_hash_requires_a_safe_salt() {
+    let masks = customers_mask(vec![("email", ColumnMask::Hash)]);
+    let missing = apply_column_masks("SELECT email FROM customers".to_string(), "postgres", masks, None).await;
+    assert!(missing.is_err());
+
+    let masks = customers_mask(vec![("email", ColumnMask::Hash)]);
+    let quoted = apply_column_masks(
+        "SELECT email FROM customers".to_string(),
+        "postgres",
+        masks,
+        Some("x') --".to_string()),
+    )
+    .await;
+    assert!(quoted.is_err());
+}
+
+#[tokio::test]
+async fn test_row_filter_reads_unmasked_columns() {
+    let mut masks = customers_mask(vec![("email", ColumnMask::Hash)]);
+    masks.get_mut("customers").unwrap().row_filter = Some("email LIKE '%@acme.com'".to_string());
+
+    let masked_sql = mask("SELECT c.email FROM customers c", "postgres", masks).await.unwrap();
+
+    assert!(masked_sql.contains(
+        "AS email, phone FROM customers WHERE (email LIKE '%@acme.com')) AS c"
+    ));
+}
+
+#[tokio::test]
+async fn test_row_filter_must_be_a_single_condition() {
+    for filter in [
+        "1=1) UNION SELECT id, name, email, phone FROM customers WHERE (1=1",
+        "1=1) OR (1=1",
+    ] {
+        let mut masks = customers_mask(vec![("email", ColumnMask::Hash)]);
+        masks.get_mut("customers").unwrap().row_filter = Some(filter.to_string());
+
+        let result = mask("SELECT c.email FROM customers c", "postgres", masks).await;
+
+        assert!(result.is_err(), "Filter '{}' should be rejected", filter);
+    }
+}
+
+#[tokio::test]
+async fn test_masked_table_function_fails_closed() {
+    let masks = customers_mask(vec![("email", ColumnMask::Hash)]);
+
+    let result = mask("SELECT c.email FROM customers(1) c", "postgres", masks).await;
+
+    assert!(result.is_err(), "A masked table read through a table function must not run unmasked");
+}
@@ -0,0 +1,34 @@
+use serde_json::json;
+use sql_analyzer::{literal, string_literal};
+
+#[test]
+fn test_string_literal_escapes_per_dialect() {
+    let value = json!(r"\' OR 1=1 --");
+    let cases = [
+        ("bigquery", r"'\\\' OR 1=1 --'"),
+        ("databricks", r"'\\\' OR 1=1 --'"),
+        ("mysql", r"'\\\' OR 1=1 --'"),
+        ("mariadb", r"'\\\' OR 1=1 --'"),
+        ("clickhouse", r"'\\\' OR 1=1 --'"),
+        ("snowflake", r"'\\'' OR 1=1 --'"),
+        ("redshift", r"'\\'' OR 1=1 --'"),
+        ("postgres", r"'\'' OR 1=1 --'"),
+        ("supabase", r"'\'' OR 1=1 --'"),
+        ("duckdb", r"'\'' OR 1=1 --'"),
+        ("sqlserver", r"'\'' OR 1=1 --'"),
+    ];
+    for (dialect, expected) in cases {
+        assert_eq!(literal(&value, dialect).unwrap(), expected, "{}", dialect);
+    }
+
+    assert_eq!(string_literal("it's", "MySQL"), r"'it\'s'");
+}
+
+#[test]
+fn test_literal_scalars() {
+    assert_eq!(literal(&json!(true), "sqlserver").unwrap(), "1");
+    assert_eq!(literal(&json!(false), "postgres").unwrap(), "FALSE");
+    assert_eq!(literal(&json!(2.5), "postgres").unwrap(), "2.5");
+    assert_eq!(literal(&json!(null), "postgres").unwrap(), "NULL");
+    assert!(literal(&json!(["a"]), "postgres").is_err());
+}
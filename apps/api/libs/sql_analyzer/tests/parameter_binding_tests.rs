@@ -0,0 +1,146 @@
+use sql_analyzer::{bind_parameters, find_parameter_placeholders, ParameterType, ParameterValue};
+use std::collections::HashMap;
+
+fn values(entries: Vec<(&str, ParameterValue)>) -> HashMap<String, ParameterValue> {
+    entries
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value))
+        .collect()
+}
+
+#[test]
+fn test_find_parameter_placeholders() {
+    let sql = "SELECT * FROM s.orders o WHERE o.region = {{ region }} AND o.created_at >= {{period.start}} AND o.region <> {{region}}";
+
+    assert_eq!(
+        find_parameter_placeholders(sql),
+        vec!["region".to_string(), "period.start".to_string()]
+    );
+}
+
+#[test]
+fn test_sql_without_placeholders_is_unchanged() {
+    let sql = "select  o.id from s.orders o";
+
+    assert_eq!(bind_parameters(sql, "postgres", &HashMap::new()).unwrap(), sql);
+}
+
+#[test]
+fn test_bind_scalar_values() {
+    let sql = "SELECT o.id FROM s.orders o WHERE o.region = {{region}} AND o.amount > {{min_amount}} AND o.created_at >= {{period.start}} AND o.is_test = {{include_test}}";
+    let bound = bind_parameters(
+        sql,
+        "postgres",
+        &values(vec![
+            ("region", ParameterValue::Single(ParameterType::String, "O'Brien West".to_string())),
+            ("min_amount", ParameterValue::Single(ParameterType::Number, "100.5".to_string())),
+            ("period.start", ParameterValue::Single(ParameterType::Date, "2024-01-01".to_string())),
+            ("include_test", ParameterValue::Single(ParameterType::Boolean, "false".to_string())),
+        ]),
+    )
+    .unwrap();
+
+    assert_eq!(
+        bound,
+        "SELECT o.id FROM s.orders AS o WHERE o.region = 'O''Brien West' AND o.amount > 100.5 AND o.created_at >= '2024-01-01' AND o.is_test = false"
+    );
+}
+
+#[test]
+fn test_bind_list_into_in_clause() {
+    let sql = "SELECT o.id FROM s.orders o WHERE o.region IN ({{regions}})";
+    let bound = bind_parameters(
+        sql,
+        "snowflake",
+        &values(vec![(
+            "regions",
+            ParameterValue::List(
+                ParameterType::String,
+                vec!["west".to_string(), "east".to_string()],
+            ),
+        )]),
+    )
+    .unwrap();
+
+    assert_eq!(
+        bound,
+        "SELECT o.id FROM s.orders AS o WHERE o.region IN ('west', 'east')"
+    );
+}
+
+#[test]
+fn test_injection_attempts_stay_inside_literals() {
+    let sql = "SELECT o.id FROM s.orders o WHERE o.region = {{region}}";
+    let bound = bind_parameters(
+        sql,
+        "mysql",
+        &values(vec![(
+            "region",
+            ParameterValue::Single(ParameterType::String, "x' OR 1=1 --".to_string()),
+        )]),
+    )
+    .unwrap();
+
+    assert_eq!(
+        bound,
+        "SELECT o.id FROM s.orders AS o WHERE o.region = 'x'' OR 1=1 --'"
+    );
+
+    // A backslash before a quote would escape it in MySQL, so backslashes are refused
+    let result = bind_parameters(
+        sql,
+        "mysql",
+        &values(vec![(
+            "region",
+            ParameterValue::Single(ParameterType::String, "x\\' OR 1=1 --".to_string()),
+        )]),
+    );
+    assert!(result.is_err());
+
+    let result = bind_parameters(
+        "SELECT o.id FROM s.orders o LIMIT {{n}}",
+        "postgres",
+        &values(vec![(
+            "n",
+            ParameterValue::Single(ParameterType::Number, "1; DROP TABLE s.orders".to_string()),
+        )]),
+    );
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_sql_server_booleans_are_bits() {
+    let bound = bind_parameters(
+        "SELECT o.id FROM s.orders o WHERE o.is_test = {{include_test}}",
+        "sqlserver",
+        &values(vec![(
+            "include_test",
+            ParameterValue::Single(ParameterType::Boolean, "true".to_string()),
+        )]),
+    )
+    .unwrap();
+
+    assert_eq!(bound, "SELECT o.id FROM s.orders AS o WHERE o.is_test = 1");
+}
+
+#[test]
+fn test_binding_errors() {
+    let list = values(vec![(
+        "regions",
+        ParameterValue::List(ParameterType::String, vec!["west".to_string()]),
+    )]);
+
+    // Missing value
+    assert!(bind_parameters("SELECT {{region}}", "postgres", &HashMap::new()).is_err());
+    // List outside of an IN list
+    assert!(bind_parameters("SELECT o.id FROM s.orders o WHERE o.region = {{regions}}", "postgres", &list).is_err());
+    // Placeholder inside a string literal
+    assert!(bind_parameters("SELECT o.id FROM s.orders o WHERE o.region = 'x{{regions}}'", "postgres", &list).is_err());
+    // Invalid date
+    assert!(bind_parameters(
+        "SELECT {{day}}",
+        "postgres",
+        &values(vec![("day", ParameterValue::Single(ParameterType::Date, "yesterday".to_string()))]),
+    )
+    .is_err());
+}
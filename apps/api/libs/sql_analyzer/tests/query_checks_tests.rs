@@ -0,0 +1,127 @@
+use sql_analyzer::{check_query, IssueSeverity, QueryIssue, QueryIssueKind};
+
+fn kinds(issues: &[QueryIssue]) -> Vec<QueryIssueKind> {
+    issues.iter().map(|issue| issue.kind).collect()
+}
+
+#[test]
+fn test_clean_aggregate_has_no_issues() {
+    let sql = "SELECT o.region, SUM(o.amount) AS revenue FROM sales.orders o \
+               WHERE o.created_at >= '2024-01-01' GROUP BY o.region";
+
+    assert!(check_query(sql, "postgres").unwrap().is_empty());
+}
+
+#[test]
+fn test_missing_group_by_column() {
+    let sql = "SELECT o.region, o.channel, SUM(o.amount) FROM sales.orders o GROUP BY o.region";
+
+    let issues = check_query(sql, "postgres").unwrap();
+    assert_eq!(kinds(&issues), vec![QueryIssueKind::MissingGroupBy]);
+    assert_eq!(issues[0].severity, IssueSeverity::Error);
+    assert!(issues[0].message.contains("channel"));
+    assert!(!issues[0].message.contains("region"));
+}
+
+#[test]
+fn test_aggregate_without_group_by() {
+    let sql = "SELECT region, COUNT(*) FROM sales.orders";
+
+    let issues = check_query(sql, "postgres").unwrap();
+    assert_eq!(kinds(&issues), vec![QueryIssueKind::MissingGroupBy]);
+}
+
+#[test]
+fn test_group_by_position_alias_and_expression() {
+    let by_position = "SELECT region, SUM(amount) FROM sales.orders GROUP BY 1";
+    let by_alias = "SELECT DATE_TRUNC('month', created_at) AS month_start, SUM(amount) \
+                    FROM sales.orders GROUP BY month_start";
+    let by_expression = "SELECT DATE_TRUNC('month', created_at), SUM(amount) \
+                         FROM sales.orders GROUP BY DATE_TRUNC('month', created_at)";
+    let date_part = "SELECT DATEADD(day, -1, MAX(created_at)) FROM sales.orders";
+
+    for sql in [by_position, by_alias, by_expression] {
+        assert!(check_query(sql, "postgres").unwrap().is_empty(), "{}", sql);
+    }
+    assert!(check_query(date_part, "snowflake").unwrap().is_empty());
+}
+
+#[test]
+fn test_window_functions_do_not_need_grouping() {
+    let sql = "SELECT region, SUM(amount), RANK() OVER (ORDER BY SUM(amount) DESC) \
+               FROM sales.orders GROUP BY region";
+
+    assert!(check_query(sql, "postgres").unwrap().is_empty());
+}
+
+#[test]
+fn test_fan_out_join() {
+    let sql = "SELECT c.name, SUM(o.amount) FROM sales.customers c \
+               JOIN sales.orders o ON o.customer_id = c.id \
+               JOIN sales.order_items i ON i.order_id = o.id GROUP BY c.name";
+
+    let issues = check_query(sql, "postgres").unwrap();
+    assert_eq!(kinds(&issues), vec![QueryIssueKind::FanOutJoin]);
+    assert_eq!(issues[0].severity, IssueSeverity::Warning);
+    assert!(issues[0].message.contains("SUM"));
+}
+
+#[test]
+fn test_distinct_and_min_max_are_join_safe() {
+    let sql = "SELECT c.name, COUNT(DISTINCT o.id), MAX(o.amount) FROM sales.customers c \
+               JOIN sales.orders o ON o.customer_id = c.id GROUP BY c.name";
+
+    assert!(check_query(sql, "postgres").unwrap().is_empty());
+}
+
+#[test]
+fn test_cross_join() {
+    let explicit = "SELECT c.name, r.name FROM sales.customers c CROSS JOIN sales.regions r LIMIT 10";
+    let comma = "SELECT c.name, r.name FROM sales.customers c, sales.regions r LIMIT 10";
+    let comma_with_where = "SELECT c.name, r.name FROM sales.customers c, sales.regions r \
+                            WHERE c.region_id = r.id";
+
+    assert_eq!(
+        kinds(&check_query(explicit, "postgres").unwrap()),
+        vec![QueryIssueKind::CrossJoin]
+    );
+    assert_eq!(
+        kinds(&check_query(comma, "postgres").unwrap()),
+        vec![QueryIssueKind::CrossJoin]
+    );
+    assert!(check_query(comma_with_where, "postgres").unwrap().is_empty());
+}
+
+#[test]
+fn test_unbounded_scan() {
+    let scan = "SELECT id, amount FROM sales.orders";
+    let limited = "SELECT id, amount FROM sales.orders LIMIT 100";
+    let filtered = "SELECT id, amount FROM sales.orders WHERE amount > 100";
+    let from_cte = "WITH big AS (SELECT id, amount FROM sales.orders WHERE amount > 100) \
+                    SELECT id FROM big";
+
+    assert_eq!(
+        kinds(&check_query(scan, "postgres").unwrap()),
+        vec![QueryIssueKind::UnboundedScan]
+    );
+    for sql in [limited, filtered, from_cte] {
+        assert!(check_query(sql, "postgres").unwrap().is_empty(), "{}", sql);
+    }
+}
+
+#[test]
+fn test_checks_nested_queries() {
+    let sql = "WITH totals AS (SELECT region, channel, SUM(amount) AS total \
+               FROM sales.orders GROUP BY region) \
+               SELECT region, total FROM totals WHERE total > 0";
+
+    assert_eq!(
+        kinds(&check_query(sql, "postgres").unwrap()),
+        vec![QueryIssueKind::MissingGroupBy]
+    );
+}
+
+#[test]
+fn test_rejects_non_select() {
+    assert!(check_query("DELETE FROM sales.orders", "postgres").is_err());
+}
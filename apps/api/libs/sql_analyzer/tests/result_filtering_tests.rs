@@ -0,0 +1,219 @@
+use sql_analyzer::{
+    apply_result_filters, ParameterType, ResultFilter, ResultFilterCondition, SkippedResultFilter,
+};
+
+fn region(values: &[&str]) -> ResultFilter {
+    ResultFilter {
+        column: "region".to_string(),
+        condition: ResultFilterCondition::In {
+            value_type: ParameterType::String,
+            values: values.iter().map(|v| v.to_string()).collect(),
+        },
+    }
+}
+
+fn date_range(column: &str, start: &str, end: &str) -> ResultFilter {
+    ResultFilter {
+        column: column.to_string(),
+        condition: ResultFilterCondition::Range {
+            value_type: ParameterType::Date,
+            start: Some(start.to_string()),
+            end: Some(end.to_string()),
+            end_inclusive: false,
+        },
+    }
+}
+
+#[test]
+fn test_no_filters_leaves_sql_unchanged() {
+    let sql = "select  o.id from s.orders o";
+
+    let filtered = apply_result_filters(sql, "postgres", &[]).unwrap();
+    assert_eq!(filtered.sql, sql);
+    assert!(filtered.skipped.is_empty());
+}
+
+#[test]
+fn test_wraps_query_with_filters() {
+    let sql = "SELECT o.region, SUM(o.amount) AS revenue FROM s.orders o GROUP BY o.region";
+    let filters = vec![
+        ResultFilter {
+            column: "region".to_string(),
+            condition: ResultFilterCondition::In {
+                value_type: ParameterType::String,
+                values: vec!["west".to_string(), "east".to_string()],
+            },
+        },
+        ResultFilter {
+            column: "revenue".to_string(),
+            condition: ResultFilterCondition::Range {
+                value_type: ParameterType::Number,
+                start: Some("100".to_string()),
+                end: None,
+                end_inclusive: true,
+            },
+        },
+    ];
+
+    assert_eq!(
+        apply_result_filters(sql, "postgres", &filters).unwrap().sql,
+        "SELECT * FROM (SELECT o.region, SUM(o.amount) AS revenue FROM s.orders AS o GROUP BY o.region) AS buster_filtered WHERE region IN ('west', 'east') AND revenue >= 100"
+    );
+}
+
+#[test]
+fn test_ctes_and_order_move_to_the_wrapper() {
+    let sql = "WITH daily AS (SELECT o.created_at::date AS day, COUNT(*) AS orders FROM s.orders o GROUP BY 1) SELECT d.day, d.orders FROM daily d ORDER BY day DESC";
+
+    assert_eq!(
+        apply_result_filters(sql, "postgres", &[date_range("day", "2024-01-01", "2024-02-01")]).unwrap().sql,
+        "WITH daily AS (SELECT o.created_at::DATE AS day, COUNT(*) AS orders FROM s.orders AS o GROUP BY 1) SELECT * FROM (SELECT d.day, d.orders FROM daily AS d) AS buster_filtered WHERE day >= '2024-01-01' AND day < '2024-02-01' ORDER BY day DESC"
+    );
+
+    // Selected expressions move out as positions, which SQL Server needs to filter at all
+    let region = region(&["west"]);
+    let sql = "SELECT o.region, SUM(o.amount) AS revenue FROM s.orders o GROUP BY o.region ORDER BY SUM(o.amount) DESC, o.region";
+    assert_eq!(
+        apply_result_filters(sql, "sqlserver", std::slice::from_ref(&region)).unwrap().sql,
+        "SELECT * FROM (SELECT o.region, SUM(o.amount) AS revenue FROM s.orders AS o GROUP BY o.region) AS buster_filtered WHERE region IN ('west') ORDER BY 2 DESC, region"
+    );
+
+    // An order that can't move out stays inside, except on SQL Server
+    let sql = "SELECT o.region FROM s.orders o ORDER BY o.created_at";
+    assert_eq!(
+        apply_result_filters(sql, "postgres", std::slice::from_ref(&region)).unwrap().sql,
+        "SELECT * FROM (SELECT o.region FROM s.orders AS o ORDER BY o.created_at) AS buster_filtered WHERE region IN ('west')"
+    );
+    assert!(apply_result_filters(sql, "sqlserver", &[region]).is_err());
+}
+
+#[test]
+fn test_limited_queries_are_filtered_before_the_limit() {
+    let revenue = ResultFilter {
+        column: "revenue".to_string(),
+        condition: ResultFilterCondition::Range {
+            value_type: ParameterType::Number,
+            start: Some("100".to_string()),
+            end: None,
+            end_inclusive: true,
+        },
+    };
+    let filters = [region(&["west"]), revenue];
+
+    // Grouped columns go in WHERE and aggregates in HAVING, ahead of the existing conditions
+    let sql = "SELECT o.region, SUM(o.amount) AS revenue FROM s.orders o WHERE o.status = 'paid' OR o.refunded GROUP BY o.region ORDER BY revenue DESC LIMIT 10";
+    let filtered = apply_result_filters(sql, "postgres", &filters).unwrap();
+    assert_eq!(
+        filtered.sql,
+        "SELECT o.region, SUM(o.amount) AS revenue FROM s.orders AS o WHERE (o.status = 'paid' OR o.refunded) AND o.region IN ('west') GROUP BY o.region HAVING SUM(o.amount) >= 100 ORDER BY revenue DESC LIMIT 10"
+    );
+    assert!(filtered.skipped.is_empty());
+
+    let sql = "SELECT TOP 10 o.region, SUM(o.amount) AS revenue FROM s.orders o GROUP BY o.region ORDER BY revenue DESC";
+    assert_eq!(
+        apply_result_filters(sql, "sqlserver", &filters).unwrap().sql,
+        "SELECT TOP 10 o.region, SUM(o.amount) AS revenue FROM s.orders AS o WHERE o.region IN ('west') GROUP BY o.region HAVING SUM(o.amount) >= 100 ORDER BY revenue DESC"
+    );
+
+    // Computed columns are compared as a whole
+    let sql = "SELECT UPPER(o.region) AS region, o.amount * 2 AS doubled FROM s.orders o OFFSET 10";
+    let doubled = ResultFilter {
+        column: "doubled".to_string(),
+        condition: ResultFilterCondition::Range {
+            value_type: ParameterType::Number,
+            start: None,
+            end: Some("50".to_string()),
+            end_inclusive: false,
+        },
+    };
+    assert_eq!(
+        apply_result_filters(sql, "postgres", &[region(&["WEST"]), doubled]).unwrap().sql,
+        "SELECT UPPER(o.region) AS region, o.amount * 2 AS doubled FROM s.orders AS o WHERE UPPER(o.region) IN ('WEST') AND (o.amount * 2) < 50 OFFSET 10"
+    );
+}
+
+#[test]
+fn test_limited_queries_skip_filters_that_cant_move_before_the_limit() {
+    let filters = [region(&["west"]), date_range("day", "2024-01-01", "2024-02-01")];
+
+    // A window function can't be filtered on in WHERE, and `day` isn't selected at all
+    let sql = "SELECT o.region, RANK() OVER (ORDER BY o.amount DESC) AS rank FROM s.orders o LIMIT 10";
+    let filtered = apply_result_filters(sql, "postgres", &filters).unwrap();
+    assert_eq!(
+        filtered.sql,
+        "SELECT o.region, RANK() OVER (ORDER BY o.amount DESC) AS rank FROM s.orders AS o WHERE o.region IN ('west') LIMIT 10"
+    );
+    assert_eq!(
+        filtered.skipped.iter().map(|s| s.column.as_str()).collect::<Vec<_>>(),
+        vec!["day"]
+    );
+
+    let ranked = ResultFilter {
+        column: "rank".to_string(),
+        condition: ResultFilterCondition::Range {
+            value_type: ParameterType::Number,
+            start: None,
+            end: Some("3".to_string()),
+            end_inclusive: true,
+        },
+    };
+    let filtered = apply_result_filters(sql, "postgres", &[ranked]).unwrap();
+    assert_eq!(filtered.sql, "SELECT o.region, RANK() OVER (ORDER BY o.amount DESC) AS rank FROM s.orders AS o LIMIT 10");
+    assert_eq!(
+        filtered.skipped,
+        vec![SkippedResultFilter {
+            column: "rank".to_string(),
+            reason: "'rank' is computed by a window function, so it can't be filtered before the query's row limit".to_string(),
+        }]
+    );
+
+    // The rows of a UNION can only be filtered after its limit
+    let sql = "SELECT o.region FROM s.orders o UNION SELECT r.region FROM s.returns r LIMIT 10";
+    let filtered = apply_result_filters(sql, "postgres", &filters[..1]).unwrap();
+    assert_eq!(filtered.sql, "SELECT o.region FROM s.orders AS o UNION SELECT r.region FROM s.returns AS r LIMIT 10");
+    assert_eq!(filtered.skipped.len(), 1);
+}
+
+#[test]
+fn test_identifiers_are_quoted_per_dialect() {
+    let filter = date_range("Order Date", "2024-01-01", "2024-02-01");
+
+    assert_eq!(
+        apply_result_filters("SELECT o.`Order Date` FROM s.orders o", "bigquery", std::slice::from_ref(&filter)).unwrap().sql,
+        "SELECT * FROM (SELECT o.`Order Date` FROM s.orders AS o) AS buster_filtered WHERE `Order Date` >= '2024-01-01' AND `Order Date` < '2024-02-01'"
+    );
+    assert_eq!(
+        apply_result_filters("SELECT o.\"Order Date\" FROM s.orders o", "snowflake", &[filter]).unwrap().sql,
+        "SELECT * FROM (SELECT o.\"Order Date\" FROM s.orders AS o) AS buster_filtered WHERE \"Order Date\" >= '2024-01-01' AND \"Order Date\" < '2024-02-01'"
+    );
+}
+
+#[test]
+fn test_invalid_filters() {
+    let sql = "SELECT o.region FROM s.orders o";
+
+    let injection = ResultFilter {
+        column: "region".to_string(),
+        condition: ResultFilterCondition::In {
+            value_type: ParameterType::String,
+            values: vec!["x\\' OR 1=1 --".to_string()],
+        },
+    };
+    assert!(apply_result_filters(sql, "mysql", &[injection]).is_err());
+
+    let empty = ResultFilter {
+        column: "region".to_string(),
+        condition: ResultFilterCondition::In {
+            value_type: ParameterType::String,
+            values: vec![],
+        },
+    };
+    assert!(apply_result_filters(sql, "postgres", &[empty]).is_err());
+
+    assert!(apply_result_filters(
+        "DELETE FROM s.orders",
+        "postgres",
+        &[date_range("day", "2024-01-01", "2024-02-01")]
+    )
+    .is_err());
+}
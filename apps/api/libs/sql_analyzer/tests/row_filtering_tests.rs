@@ -2,6 +2,11 @@ use sql_analyzer::apply_row_level_filters;
 use std::collections::HashMap;
 use tokio;
 
+/// The derived table a filtered reference is rewritten into
+fn filtered(table: &str, filter: &str, alias: &str) -> String {
+    format!("(SELECT * FROM {} WHERE ({})) AS {}", table, filter, alias)
+}
+
 #[tokio::test]
 async fn test_row_level_filtering() {
     // Simple query with tables that need filtering
@@ -16,30 +21,19 @@ async fn test_row_level_filtering() {
     );
 
     // Test row level filtering
-    let result = apply_row_level_filters(sql.to_string(), table_filters).await;
+    let result = apply_row_level_filters(sql.to_string(), "generic", table_filters).await;
     assert!(result.is_ok(), "Row level filtering should succeed");
 
     let filtered_sql = result.unwrap();
 
-    // Check that CTEs were created
-    assert!(
-        filtered_sql.starts_with("WITH "),
-        "Should start with a WITH clause"
-    );
-    assert!(
-        filtered_sql.contains("filtered_u AS (SELECT * FROM users WHERE tenant_id = 123)"),
-        "Should create a CTE for filtered users"
-    );
-    assert!(
-        filtered_sql
-            .contains("filtered_o AS (SELECT * FROM orders WHERE created_at > '2023-01-01')"),
-        "Should create a CTE for filtered orders"
-    );
-
-    // Check that table references were replaced
-    assert!(
-        filtered_sql.contains("filtered_u") && filtered_sql.contains("filtered_o"),
-        "Should replace table references with filtered CTEs"
+    // Check that both table references were replaced in place
+    assert_eq!(
+        filtered_sql,
+        format!(
+            "SELECT u.id, o.amount FROM {} JOIN {} ON u.id = o.user_id",
+            filtered("users", "tenant_id = 123", "u"),
+            filtered("orders", "created_at > '2023-01-01'", "o")
+        )
     );
 }
 
@@ -57,7 +51,7 @@ async fn test_row_level_filtering_with_schema_qualified_tables() {
     );
 
     // Test row level filtering
-    let result = apply_row_level_filters(sql.to_string(), table_filters).await;
+    let result = apply_row_level_filters(sql.to_string(), "generic", table_filters).await;
     assert!(
         result.is_ok(),
         "Row level filtering should succeed with schema-qualified tables"
@@ -65,19 +59,51 @@ async fn test_row_level_filtering_with_schema_qualified_tables() {
 
     let filtered_sql = result.unwrap();
 
-    // Check that CTEs were created with fully qualified table names
+    // Check that the derived tables read the fully qualified table names
     assert!(
-        filtered_sql.contains("filtered_u AS (SELECT * FROM schema.users WHERE tenant_id = 123)"),
-        "Should create a CTE for filtered users with schema"
+        filtered_sql.contains(&filtered("schema.users", "tenant_id = 123", "u")),
+        "Should filter users with schema"
     );
     assert!(
-        filtered_sql.contains(
-            "filtered_o AS (SELECT * FROM schema.orders WHERE created_at > '2023-01-01')"
-        ),
-        "Should create a CTE for filtered orders with schema"
+        filtered_sql.contains(&filtered("schema.orders", "created_at > '2023-01-01'", "o")),
+        "Should filter orders with schema"
     );
 }
 
+#[tokio::test]
+async fn test_row_level_filtering_with_schema_qualified_alias_and_no_alias() {
+    let sql = "SELECT o.amount, customers.name FROM analytics.orders o JOIN analytics.customers ON customers.id = o.customer_id";
+
+    let mut table_filters = HashMap::new();
+    table_filters.insert("orders".to_string(), "region = 'EU'".to_string());
+    table_filters.insert("customers".to_string(), "region = 'EU'".to_string());
+
+    let filtered_sql = apply_row_level_filters(sql.to_string(), "generic", table_filters)
+        .await
+        .unwrap();
+
+    assert!(filtered_sql.contains(&filtered("analytics.orders", "region = 'EU'", "o")));
+    // Without an alias the bare table name keeps `customers.name` resolving
+    assert!(filtered_sql.contains(&filtered("analytics.customers", "region = 'EU'", "customers")));
+    assert!(!filtered_sql.contains("FROM analytics.orders o"));
+}
+
+#[tokio::test]
+async fn test_row_level_filtering_with_quoted_and_uppercase_tables() {
+    let sql = r#"SELECT "Users".id, o.amount FROM "Users" JOIN PUBLIC.ORDERS o ON "Users".id = o.user_id"#;
+
+    let mut table_filters = HashMap::new();
+    table_filters.insert("users".to_string(), "tenant_id = 123".to_string());
+    table_filters.insert("Orders".to_string(), "tenant_id = 123".to_string());
+
+    let filtered_sql = apply_row_level_filters(sql.to_string(), "generic", table_filters)
+        .await
+        .unwrap();
+
+    assert!(filtered_sql.contains(&filtered(r#""Users""#, "tenant_id = 123", r#""Users""#)));
+    assert!(filtered_sql.contains(&filtered("PUBLIC.ORDERS", "tenant_id = 123", "o")));
+}
+
 #[tokio::test]
 async fn test_row_level_filtering_with_where_clause() {
     // Query with an existing WHERE clause
@@ -88,7 +114,7 @@ async fn test_row_level_filtering_with_where_clause() {
     table_filters.insert("users".to_string(), "tenant_id = 123".to_string());
 
     // Test row level filtering
-    let result = apply_row_level_filters(sql.to_string(), table_filters).await;
+    let result = apply_row_level_filters(sql.to_string(), "generic", table_filters).await;
     assert!(
         result.is_ok(),
         "Row level filtering should work with existing WHERE clauses"
@@ -96,10 +122,14 @@ async fn test_row_level_filtering_with_where_clause() {
 
     let filtered_sql = result.unwrap();
 
-    // Check that the CTEs were created and the original WHERE clause is preserved
+    // Check that users is filtered and the original WHERE clause is preserved
+    assert!(
+        filtered_sql.contains(&filtered("users", "tenant_id = 123", "u")),
+        "Should filter users"
+    );
     assert!(
-        filtered_sql.contains("filtered_u AS (SELECT * FROM users WHERE tenant_id = 123)"),
-        "Should create a CTE for filtered users"
+        filtered_sql.contains("JOIN orders") && !filtered_sql.contains("FROM orders"),
+        "Should keep the unfiltered orders reference"
     );
     assert!(
         filtered_sql.contains("WHERE o.status = 'completed'"),
@@ -121,27 +151,44 @@ async fn test_row_level_filtering_with_no_matching_tables() {
     );
 
     // Test row level filtering
-    let result = apply_row_level_filters(sql.to_string(), table_filters).await;
+    let result = apply_row_level_filters(sql.to_string(), "generic", table_filters).await;
     assert!(
         result.is_ok(),
         "Should succeed when no tables match filters"
     );
 
     let filtered_sql = result.unwrap();
-
-    // The SQL format might be slightly different due to the SQL parser's formatting
-    // We just need to verify no CTEs were added
-    // Note: sqlparser might add a WITH clause even if no tables match, depending on version/config.
-    // A more robust check might be to see if the original table name is still present.
-    if filtered_sql.contains("WITH ") {
-        assert!(!filtered_sql.contains("filtered_"), "Should not introduce filtered CTEs if no tables match");
-    } else {
-        assert!(!filtered_sql.contains("filtered_")); // Double check no filtered CTEs
-    }
     assert!(
-        filtered_sql.contains("FROM products p"), // Check original table reference
+        filtered_sql.starts_with("SELECT p.id, p.name FROM products"),
         "Should keep the original table reference"
     );
+    assert!(!filtered_sql.contains("WHERE"), "Should not add any filter");
+}
+
+#[tokio::test]
+async fn test_row_level_filtering_fails_closed_on_unreadable_queries() {
+    let mut table_filters = HashMap::new();
+    table_filters.insert("users".to_string(), "tenant_id = 123".to_string());
+
+    // None of these can be rewritten, so running them would skip the filters
+    for sql in [
+        "SELECT * FROM users WHERE (",
+        "SELECT 1",
+        "DELETE FROM users",
+        "WITH users AS (SELECT * FROM users) SELECT * FROM users",
+    ] {
+        let result = apply_row_level_filters(sql.to_string(), "generic", table_filters.clone()).await;
+        assert!(result.is_err(), "Should refuse to return '{}' unfiltered", sql);
+    }
+}
+
+#[tokio::test]
+async fn test_row_level_filtering_rejects_filters_that_escape_their_condition() {
+    let mut table_filters = HashMap::new();
+    table_filters.insert("users".to_string(), "tenant_id = 123) OR (1 = 1".to_string());
+
+    let result = apply_row_level_filters("SELECT u.id FROM users u".to_string(), "generic", table_filters).await;
+    assert!(result.is_err(), "A filter must stay a single condition");
 }
 
 #[tokio::test]
@@ -153,19 +200,14 @@ async fn test_row_level_filtering_with_empty_filters() {
     let table_filters = HashMap::new();
 
     // Test row level filtering
-    let result = apply_row_level_filters(sql.to_string(), table_filters).await;
+    let result = apply_row_level_filters(sql.to_string(), "generic", table_filters).await;
     assert!(result.is_ok(), "Should succeed with empty filters");
 
-    let filtered_sql = result.unwrap();
-
-    // The SQL should be unchanged (or semantically equivalent after parsing/formatting)
-    // Comparing strings directly might fail due to formatting differences.
-    // A basic check is to see if it still contains the original tables.
-    assert!(
-        filtered_sql.contains("FROM users u") && filtered_sql.contains("JOIN orders o"),
-        "SQL should effectively be unchanged when no filters are provided"
+    assert_eq!(
+        result.unwrap(),
+        sql,
+        "SQL should be unchanged when no filters are provided"
     );
-    assert!(!filtered_sql.contains("filtered_"), "No filtered CTEs should be added");
 }
 
 #[tokio::test]
@@ -183,7 +225,7 @@ async fn test_row_level_filtering_with_mixed_tables() {
     );
 
     // Test row level filtering
-    let result = apply_row_level_filters(sql.to_string(), table_filters).await;
+    let result = apply_row_level_filters(sql.to_string(), "generic", table_filters).await;
     assert!(
         result.is_ok(),
         "Should succeed with mixed filtered/unfiltered tables"
@@ -193,24 +235,17 @@ async fn test_row_level_filtering_with_mixed_tables() {
 
     // Check that only tables with filters were replaced
     assert!(
-        filtered_sql.contains("filtered_u AS (SELECT * FROM users WHERE tenant_id = 123)"),
-        "Should create a CTE for filtered users"
-    );
-    assert!(
-        filtered_sql
-            .contains("filtered_o AS (SELECT * FROM orders WHERE created_at > '2023-01-01')"),
-        "Should create a CTE for filtered orders"
-    );
-    assert!(
-        filtered_sql.contains("JOIN products p"), // Check the original unfiltered table
-        "Should keep original reference for unfiltered tables"
+        filtered_sql.contains(&format!("FROM {} JOIN products", filtered("users", "tenant_id = 123", "u"))),
+        "Should filter users and keep products unfiltered"
     );
     assert!(
-        filtered_sql.contains("FROM filtered_u")
-            && filtered_sql.contains("JOIN products p")
-            && filtered_sql.contains("JOIN filtered_o"),
-        "Should mix filtered and unfiltered tables correctly"
+        filtered_sql.contains(&format!(
+            "JOIN {} ON u.id = o.user_id",
+            filtered("orders", "created_at > '2023-01-01'", "o")
+        )),
+        "Should filter orders"
     );
+    assert!(!filtered_sql.contains("FROM products"), "Should not filter products");
 }
 
 #[tokio::test]
@@ -218,28 +253,28 @@ async fn test_row_level_filtering_with_complex_query() {
     // Complex query with subqueries, CTEs, and multiple references to tables
     let sql = "
         WITH order_summary AS (
-            SELECT 
+            SELECT
                 o.user_id,
                 COUNT(*) as order_count,
                 SUM(o.amount) as total_amount
-            FROM 
+            FROM
                 orders o
-            GROUP BY 
+            GROUP BY
                 o.user_id
         )
-        SELECT 
+        SELECT
             u.id,
             u.name,
             os.order_count,
             os.total_amount,
             (SELECT MAX(o2.amount) FROM orders o2 WHERE o2.user_id = u.id) as max_order
-        FROM 
+        FROM
             users u
-        JOIN 
+        JOIN
             order_summary os ON u.id = os.user_id
-        WHERE 
+        WHERE
             u.status = 'active'
-            AND EXISTS (SELECT 1 FROM products p JOIN order_items oi ON p.id = oi.product_id 
+            AND EXISTS (SELECT 1 FROM products p JOIN order_items oi ON p.id = oi.product_id
                        JOIN orders o3 ON oi.order_id = o3.id WHERE o3.user_id = u.id)
     ";
 
@@ -254,7 +289,7 @@ async fn test_row_level_filtering_with_complex_query() {
     table_filters.insert("products".to_string(), "is_active = true".to_string());
 
     // Test row level filtering
-    let result = apply_row_level_filters(sql.to_string(), table_filters).await;
+    let result = apply_row_level_filters(sql.to_string(), "generic", table_filters).await;
     assert!(
         result.is_ok(),
         "Should succeed with complex query structure"
@@ -263,64 +298,54 @@ async fn test_row_level_filtering_with_complex_query() {
     let filtered_sql = result.unwrap();
     println!("Complex Query Filtered SQL: {}\n", filtered_sql);
 
-    // Verify all instances of filtered tables were replaced
-    assert!(
-        filtered_sql.contains("filtered_u AS (SELECT * FROM users WHERE tenant_id = 123)"),
-        "Should create CTE for filtered users"
-    );
-    assert!(
-        filtered_sql.contains("filtered_o AS (SELECT * FROM orders WHERE created_at > '2023-01-01')"),
-        "Should create CTE for filtered orders"
-    );
-     assert!(
-        filtered_sql.contains("filtered_p AS (SELECT * FROM products WHERE is_active = true)"),
-        "Should create CTE for filtered products"
-    );
-
-
     // Verify replacements in different contexts
     assert!(
-        filtered_sql.contains("FROM filtered_o o"),
+        filtered_sql.contains(&filtered("orders", "created_at > '2023-01-01'", "o")),
         "Should replace orders in order_summary CTE definition"
     );
     assert!(
-        filtered_sql.contains("FROM filtered_o o2"),
-        "Should replace orders in MAX subquery (check alias)"
+        filtered_sql.contains(&filtered("orders", "created_at > '2023-01-01'", "o2")),
+        "Should replace orders in MAX subquery"
+    );
+    assert!(
+        filtered_sql.contains(&filtered("products", "is_active = true", "p")),
+        "Should replace products in EXISTS subquery"
     );
     assert!(
-        filtered_sql.contains("FROM filtered_p p"),
-        "Should replace products in EXISTS subquery (check alias)"
+        filtered_sql.contains(&filtered("orders", "created_at > '2023-01-01'", "o3")),
+        "Should replace orders in EXISTS subquery"
     );
     assert!(
-        filtered_sql.contains("JOIN filtered_o o3"),
-        "Should replace orders in EXISTS subquery (check alias)"
+        filtered_sql.contains(&filtered("users", "tenant_id = 123", "u")),
+        "Should replace main users table"
     );
     assert!(
-        filtered_sql.contains("FROM filtered_u u"),
-        "Should replace main users table (check alias)"
+        filtered_sql.contains("JOIN order_items"),
+        "Should leave unfiltered tables alone"
     );
 
-    // The original CTE definition should also be preserved (though modified)
+    // The original CTE should be preserved (though modified) and still referenced
     assert!(
-        filtered_sql.contains("WITH order_summary AS ("),
+        filtered_sql.starts_with("WITH order_summary AS ("),
         "Should preserve original CTE structure"
     );
+    assert!(filtered_sql.contains("JOIN order_summary"));
 }
 
 #[tokio::test]
 async fn test_row_level_filtering_with_union_query() {
     // Union query
     let sql = "
-        SELECT u1.id, o1.amount 
-        FROM users u1 
-        JOIN orders o1 ON u1.id = o1.user_id 
+        SELECT u1.id, o1.amount
+        FROM users u1
+        JOIN orders o1 ON u1.id = o1.user_id
         WHERE o1.status = 'completed'
-        
+
         UNION ALL
-        
-        SELECT u2.id, o2.amount 
-        FROM users u2 
-        JOIN orders o2 ON u2.id = o2.user_id 
+
+        SELECT u2.id, o2.amount
+        FROM users u2
+        JOIN orders o2 ON u2.id = o2.user_id
         WHERE o2.status = 'pending'
     ";
 
@@ -333,42 +358,40 @@ async fn test_row_level_filtering_with_union_query() {
     );
 
     // Test row level filtering
-    let result = apply_row_level_filters(sql.to_string(), table_filters).await;
+    let result = apply_row_level_filters(sql.to_string(), "generic", table_filters).await;
     assert!(result.is_ok(), "Should succeed with UNION queries");
 
     let filtered_sql = result.unwrap();
 
     // Verify filters are applied correctly to both sides of UNION
-    assert!(
-        filtered_sql.contains("filtered_u1"),
-        "Should filter users in first query part"
-    );
-    assert!(
-        filtered_sql.contains("filtered_o1"),
-        "Should filter orders in first query part"
-    );
-    assert!(
-        filtered_sql.contains("filtered_u2"),
-        "Should filter users in second query part"
-    );
-    assert!(
-        filtered_sql.contains("filtered_o2"),
-        "Should filter orders in second query part"
-    );
+    for (table, filter, alias) in [
+        ("users", "tenant_id = 123", "u1"),
+        ("orders", "created_at > '2023-01-01'", "o1"),
+        ("users", "tenant_id = 123", "u2"),
+        ("orders", "created_at > '2023-01-01'", "o2"),
+    ] {
+        assert!(
+            filtered_sql.contains(&filtered(table, filter, alias)),
+            "Should filter {} as {}",
+            table,
+            alias
+        );
+    }
+    assert!(filtered_sql.contains("UNION ALL"));
 }
 
 #[tokio::test]
 async fn test_row_level_filtering_with_ambiguous_references() {
     // Query with multiple references to the same table using aliases
     let sql = "
-        SELECT 
-            a.id, 
+        SELECT
+            a.id,
             a.name,
             b.id as other_id,
             b.name as other_name
-        FROM 
+        FROM
             users a
-        JOIN 
+        JOIN
             users b ON a.manager_id = b.id
     ";
 
@@ -377,97 +400,73 @@ async fn test_row_level_filtering_with_ambiguous_references() {
     table_filters.insert("users".to_string(), "tenant_id = 123".to_string());
 
     // Test row level filtering
-    let result = apply_row_level_filters(sql.to_string(), table_filters).await;
+    let result = apply_row_level_filters(sql.to_string(), "generic", table_filters).await;
     assert!(result.is_ok(), "Should succeed with aliased self-join");
 
     let filtered_sql = result.unwrap();
 
-    // Verify that both instances of the users table are filtered correctly via CTEs
-     assert!(
-        filtered_sql.contains("filtered_a AS (SELECT * FROM users WHERE tenant_id = 123)"),
-        "Should create CTE for alias 'a'"
-    );
-     assert!(
-        filtered_sql.contains("filtered_b AS (SELECT * FROM users WHERE tenant_id = 123)"),
-        "Should create CTE for alias 'b'"
-    );
+    // Verify that both instances of the users table are filtered
     assert!(
-        filtered_sql.contains("FROM filtered_a a"),
-        "Should reference filtered CTE for alias 'a'"
+        filtered_sql.contains(&format!("FROM {}", filtered("users", "tenant_id = 123", "a"))),
+        "Should filter alias 'a'"
     );
     assert!(
-        filtered_sql.contains("JOIN filtered_b b"),
-        "Should reference filtered CTE for alias 'b'"
+        filtered_sql.contains(&format!("JOIN {}", filtered("users", "tenant_id = 123", "b"))),
+        "Should filter alias 'b'"
     );
 }
 
-
 #[tokio::test]
 async fn test_row_level_filtering_with_existing_ctes() {
     // Query with existing CTEs
     let sql = "
         WITH order_summary AS (
-            SELECT 
+            SELECT
                 user_id,
                 COUNT(*) as order_count,
                 SUM(amount) as total_amount
-            FROM 
+            FROM
                 orders
-            GROUP BY 
+            GROUP BY
                 user_id
         )
-        SELECT 
+        SELECT
             u.id,
             u.name,
             os.order_count,
             os.total_amount
-        FROM 
+        FROM
             users u
-        JOIN 
+        JOIN
             order_summary os ON u.id = os.user_id
     ";
 
-    // Create filter for users table only
+    // Create filter for users table
     let mut table_filters = HashMap::new();
     table_filters.insert("users".to_string(), "tenant_id = 123".to_string());
     // Add a filter for orders as well to test CTE modification
     table_filters.insert("orders".to_string(), "status = 'paid'".to_string());
 
-
     // Test row level filtering with existing CTEs
-    let result = apply_row_level_filters(sql.to_string(), table_filters).await;
+    let result = apply_row_level_filters(sql.to_string(), "generic", table_filters).await;
     assert!(result.is_ok(), "Should succeed with existing CTEs");
 
     let filtered_sql = result.unwrap();
     println!("Existing CTE Filtered SQL: {}\n", filtered_sql);
 
-    // Verify the new CTEs are added before the original CTE
-    assert!(
-        filtered_sql.contains("filtered_u AS (SELECT * FROM users WHERE tenant_id = 123)"),
-        "Should add filtered CTE for users"
-    );
-    assert!(
-        filtered_sql.contains("filtered_o AS (SELECT * FROM orders WHERE status = 'paid')"),
-        "Should add filtered CTE for orders"
-    );
-    assert!(
-        filtered_sql.contains(", order_summary AS ("), // Comma indicates it follows other CTEs
-        "Original CTE should follow filtered CTEs"
-    );
-
     // Verify the original CTE is modified to use the filtered table
     assert!(
-        filtered_sql.contains("FROM filtered_o"),
+        filtered_sql.contains(&filtered("orders", "status = 'paid'", "orders")),
         "Original CTE should now use filtered orders table"
     );
 
     // Verify the main query uses the filtered user table
     assert!(
-        filtered_sql.contains("FROM filtered_u u"),
+        filtered_sql.contains(&filtered("users", "tenant_id = 123", "u")),
         "Main query should use filtered users table"
     );
     assert!(
-        filtered_sql.contains("JOIN order_summary os"),
+        filtered_sql.contains("JOIN order_summary"),
         "Main query should still join with the original (but modified) CTE"
     );
 }
@@ -476,16 +475,16 @@ async fn test_row_level_filtering_with_existing_ctes() {
 async fn test_row_level_filtering_with_subqueries() {
     // Query with subqueries
     let sql = "
-        SELECT 
+        SELECT
             u.id,
             u.name,
             (SELECT COUNT(*) FROM orders o WHERE o.user_id = u.id) as order_count
-        FROM 
+        FROM
             users u
-        WHERE 
+        WHERE
             u.status = 'active'
             AND EXISTS (
-                SELECT 1 FROM orders o2 
+                SELECT 1 FROM orders o2
                 WHERE o2.user_id = u.id AND o2.status = 'completed'
             )
     ";
@@ -499,47 +498,74 @@ async fn test_row_level_filtering_with_subqueries() {
     );
 
     // Test row level filtering with subqueries
-    let result = apply_row_level_filters(sql.to_string(), table_filters).await;
+    let result = apply_row_level_filters(sql.to_string(), "generic", table_filters).await;
     assert!(result.is_ok(), "Should succeed with subqueries");
 
     let filtered_sql = result.unwrap();
     println!("Subquery Filtered SQL: {}\n", filtered_sql);
 
-    // Check CTEs are created
-    assert!(filtered_sql.contains("filtered_u AS"));
-    assert!(filtered_sql.contains("filtered_o AS"));
-
     // Check that the main table is filtered
     assert!(
-        filtered_sql.contains("FROM filtered_u u"),
+        filtered_sql.contains(&format!("FROM {}", filtered("users", "tenant_id = 123", "u"))),
         "Should filter the main users table"
     );
 
     // Check that subqueries are filtered
     assert!(
-        filtered_sql.contains("FROM filtered_o o WHERE"),
+        filtered_sql.contains(&format!(
+            "FROM {} WHERE",
+            filtered("orders", "created_at > '2023-01-01'", "o")
+        )),
         "Should filter orders in the scalar subquery"
     );
     assert!(
-        filtered_sql.contains("FROM filtered_o o2 WHERE"),
+        filtered_sql.contains(&format!(
+            "FROM {} WHERE",
+            filtered("orders", "created_at > '2023-01-01'", "o2")
+        )),
         "Should filter orders in the EXISTS subquery"
     );
 }
 
+#[tokio::test]
+async fn test_row_level_filtering_in_derived_tables_and_function_arguments() {
+    let sql = "
+        SELECT t.user_id, COALESCE((SELECT MAX(o.amount) FROM orders o WHERE o.user_id = t.user_id), 0) AS max_amount
+        FROM (SELECT x.user_id FROM (SELECT user_id FROM Orders) AS x) AS t
+    ";
+
+    let mut table_filters = HashMap::new();
+    table_filters.insert("orders".to_string(), "tenant_id = 123".to_string());
+
+    let filtered_sql = apply_row_level_filters(sql.to_string(), "generic", table_filters)
+        .await
+        .unwrap();
+
+    assert!(
+        filtered_sql.contains(&format!("COALESCE((SELECT MAX(o.amount) FROM {}", filtered("orders", "tenant_id = 123", "o"))),
+        "Should filter orders inside a function argument"
+    );
+    assert!(
+        filtered_sql.contains(&format!("(SELECT user_id FROM {}) AS x", filtered("Orders", "tenant_id = 123", "Orders"))),
+        "Should filter orders in nested derived tables"
+    );
+    assert!(!filtered_sql.contains("FROM Orders)"));
+}
+
 #[tokio::test]
 async fn test_row_level_filtering_with_schema_qualified_tables_and_mixed_references() {
     // Query with schema-qualified tables and mixed references
     let sql = "
-        SELECT 
+        SELECT
             u.id,
             u.name,
             o.order_id,
             p.name as product_name -- Changed from schema2.products.name
-        FROM 
+        FROM
             schema1.users u
-        JOIN 
+        JOIN
             schema1.orders o ON u.id = o.user_id
-        JOIN 
+        JOIN
             schema2.products p ON o.product_id = p.id -- Used alias p here
     ";
 
@@ -550,7 +576,7 @@ async fn test_row_level_filtering_with_schema_qualified_tables_and_mixed_referen
     table_filters.insert("products".to_string(), "company_id = 456".to_string());
 
     // Test row level filtering with schema-qualified tables
-    let result = apply_row_level_filters(sql.to_string(), table_filters).await;
+    let result = apply_row_level_filters(sql.to_string(), "generic", table_filters).await;
     assert!(
         result.is_ok(),
         "Should succeed with schema-qualified tables"
@@ -559,33 +585,18 @@ async fn test_row_level_filtering_with_schema_qualified_tables_and_mixed_referen
     let filtered_sql = result.unwrap();
     println!("Schema Qualified Filtered SQL: {}\n", filtered_sql);
 
-
-    // Check that all tables are filtered correctly with schema preserved in CTE definition
+    // Check that all tables are filtered with the schema preserved and the alias kept
     assert!(
-        filtered_sql.contains("filtered_u AS (SELECT * FROM schema1.users WHERE tenant_id = 123)"),
-        "Should include schema in the filtered users CTE"
+        filtered_sql.contains(&format!("FROM {}", filtered("schema1.users", "tenant_id = 123", "u"))),
+        "Should filter users with schema"
     );
     assert!(
-        filtered_sql.contains("filtered_o AS (SELECT * FROM schema1.orders WHERE status = 'active')"),
-        "Should include schema in the filtered orders CTE"
+        filtered_sql.contains(&format!("JOIN {}", filtered("schema1.orders", "status = 'active'", "o"))),
+        "Should filter orders with schema"
     );
     assert!(
-        filtered_sql.contains("filtered_p AS (SELECT * FROM schema2.products WHERE company_id = 456)"),
-        "Should include schema in the filtered products CTE"
-    );
-
-    // Check that references are updated correctly using the aliases
-    assert!(
-        filtered_sql.contains("FROM filtered_u u"),
-        "Should update users reference using alias u"
-    );
-    assert!(
-        filtered_sql.contains("JOIN filtered_o o"),
-        "Should update orders reference using alias o"
-    );
-    assert!(
-        filtered_sql.contains("JOIN filtered_p p"),
-        "Should update products reference using alias p"
+        filtered_sql.contains(&format!("JOIN {}", filtered("schema2.products", "company_id = 456", "p"))),
+        "Should filter products with schema"
     );
 }
 
@@ -593,19 +604,19 @@ async fn test_row_level_filtering_with_schema_qualified_tables_and_mixed_referen
 async fn test_row_level_filtering_with_nested_subqueries() {
     // Query with nested subqueries
     let sql = "
-        SELECT 
+        SELECT
             u.id,
             u.name,
             (
-                SELECT COUNT(*) 
-                FROM orders o 
+                SELECT COUNT(*)
+                FROM orders o
                 WHERE o.user_id = u.id AND o.status IN (
                     SELECT status_code -- Changed from status
                     FROM order_statuses os -- Added alias os
                     WHERE os.is_complete = true -- Used alias os
                 )
             ) as completed_orders
-        FROM 
+        FROM
             users u
     ";
 
@@ -619,42 +630,41 @@ async fn test_row_level_filtering_with_nested_subqueries() {
     table_filters.insert("order_statuses".to_string(), "company_id = 456".to_string());
 
     // Test row level filtering with nested subqueries
-    let result = apply_row_level_filters(sql.to_string(), table_filters).await;
+    let result = apply_row_level_filters(sql.to_string(), "generic", table_filters).await;
     assert!(result.is_ok(), "Should succeed with nested subqueries");
 
     let filtered_sql = result.unwrap();
     println!("Nested Subquery Filtered SQL: {}\n", filtered_sql);
 
-
     // Check all tables are filtered using their aliases
     assert!(
-        filtered_sql.contains("FROM filtered_u u"),
+        filtered_sql.contains(&filtered("users", "tenant_id = 123", "u")),
         "Should filter main users table"
     );
     assert!(
-        filtered_sql.contains("FROM filtered_o o"),
+        filtered_sql.contains(&filtered("orders", "created_at > '2023-01-01'", "o")),
         "Should filter orders in subquery"
     );
     assert!(
-        filtered_sql.contains("FROM filtered_os os"),
+        filtered_sql.contains(&filtered("order_statuses", "company_id = 456", "os")),
         "Should filter order_statuses in nested subquery"
     );
 }
 
 #[tokio::test]
-async fn test_row_level_filtering_preserves_comments() {
+async fn test_row_level_filtering_with_comments() {
     // Query with comments
     let sql = "
         -- Main query to get user data
-        SELECT 
+        SELECT
             u.id, -- User ID
             u.name, -- User name
             o.amount /* Order amount */
-        FROM 
+        FROM
             users u -- Users table
-        JOIN 
+        JOIN
             orders o ON u.id = o.user_id -- Join with orders
-        WHERE 
+        WHERE
             u.status = 'active' -- Only active users
     ";
 
@@ -667,44 +677,34 @@ async fn test_row_level_filtering_preserves_comments() {
     );
 
     // Test row level filtering with comments
-    let result = apply_row_level_filters(sql.to_string(), table_filters).await;
+    let result = apply_row_level_filters(sql.to_string(), "generic", table_filters).await;
     assert!(result.is_ok(), "Should succeed with comments");
 
     let filtered_sql = result.unwrap();
     println!("Comments Filtered SQL: {}\n", filtered_sql);
 
-
-    // Check filters are applied
-    assert!(
-        filtered_sql.contains("filtered_u AS"),
-        "Should add filtered users CTE"
-    );
-    assert!(
-        filtered_sql.contains("filtered_o AS"),
-        "Should add filtered orders CTE"
-    );
+    // Comments are dropped by the parser; what matters is that filters are applied
     assert!(
-        filtered_sql.contains("tenant_id = 123"),
+        filtered_sql.contains(&filtered("users", "tenant_id = 123", "u")),
         "Should apply users filter"
     );
     assert!(
-        filtered_sql.contains("created_at > '2023-01-01'"),
+        filtered_sql.contains(&filtered("orders", "created_at > '2023-01-01'", "o")),
         "Should apply orders filter"
     );
-    // Comment preservation depends heavily on the parser; basic check:
-    assert!(filtered_sql.contains("--") || filtered_sql.contains("/*"), "Should attempt to preserve some comments");
+    assert!(filtered_sql.contains("WHERE u.status = 'active'"));
 }
 
 #[tokio::test]
 async fn test_row_level_filtering_with_limit_offset() {
     // Query with LIMIT and OFFSET
     let sql = "
-        SELECT 
+        SELECT
             u.id,
             u.name
-        FROM 
+        FROM
             users u
-        ORDER BY 
+        ORDER BY
             u.created_at DESC
         LIMIT 10
         OFFSET 20
@@ -715,28 +715,19 @@ async fn test_row_level_filtering_with_limit_offset() {
     table_filters.insert("users".to_string(), "tenant_id = 123".to_string());
 
     // Test row level filtering with LIMIT and OFFSET
-    let result = apply_row_level_filters(sql.to_string(), table_filters).await;
+    let result = apply_row_level_filters(sql.to_string(), "generic", table_filters).await;
     assert!(result.is_ok(), "Should succeed with LIMIT and OFFSET");
 
     let filtered_sql = result.unwrap();
     println!("Limit/Offset Filtered SQL: {}\n", filtered_sql);
 
-
-    // Check that filter is applied
-    assert!(
-        filtered_sql.contains("FROM filtered_u u"),
-        "Should filter users table"
-    );
-
-    // Check that LIMIT and OFFSET are preserved
-    // Note: sqlparser might move these clauses, check for their presence anywhere
-    assert!(
-        filtered_sql.contains("LIMIT 10"),
-        "Should preserve LIMIT clause"
-    );
+    // Check that the filter is applied and LIMIT and OFFSET stay on the outer query
     assert!(
-        filtered_sql.contains("OFFSET 20"),
-        "Should preserve OFFSET clause"
+        filtered_sql.ends_with(&format!(
+            "FROM {} ORDER BY u.created_at DESC LIMIT 10 OFFSET 20",
+            filtered("users", "tenant_id = 123", "u")
+        )),
+        "Should filter users and preserve LIMIT and OFFSET"
     );
 }
 
@@ -754,7 +745,7 @@ async fn test_row_level_filtering_with_multiple_filters_per_table() {
     table_filters.insert("orders".to_string(), order_filter.to_string());
 
     // Test row level filtering
-    let result = apply_row_level_filters(sql.to_string(), table_filters).await;
+    let result = apply_row_level_filters(sql.to_string(), "generic", table_filters).await;
     assert!(
         result.is_ok(),
         "Should succeed with multiple filters per table"
@@ -763,33 +754,30 @@ async fn test_row_level_filtering_with_multiple_filters_per_table() {
     let filtered_sql = result.unwrap();
     println!("Multi-Filter SQL: {}\n", filtered_sql);
 
-    // Check that the filter conditions are correctly applied within the CTEs
+    // Check that the filter conditions are applied together within each derived table
     assert!(
-        filtered_sql.contains(&format!("SELECT * FROM users WHERE {}", user_filter)),
-        "Should apply multiple conditions for users in CTE"
+        filtered_sql.contains(&filtered("users", user_filter, "u")),
+        "Should apply multiple conditions for users"
     );
     assert!(
-        filtered_sql.contains(&format!("SELECT * FROM orders WHERE {}", order_filter)),
-        "Should apply multiple conditions for orders in CTE"
+        filtered_sql.contains(&filtered("orders", order_filter, "o")),
+        "Should apply multiple conditions for orders"
     );
-    assert!(filtered_sql.contains("FROM filtered_u u"));
-    assert!(filtered_sql.contains("JOIN filtered_o o"));
 }
 
-
 #[tokio::test]
 async fn test_row_level_filtering_with_complex_expressions() {
     // Query with complex expressions in join conditions, select list, and where clause
     let sql = "
-        SELECT 
-            u.id, 
+        SELECT
+            u.id,
             CASE WHEN o.amount > 100 THEN 'High Value' ELSE 'Standard' END as order_type,
             (SELECT COUNT(*) FROM orders o2 WHERE o2.user_id = u.id) as order_count
-        FROM 
+        FROM
             users u
-        LEFT JOIN 
+        LEFT JOIN
             orders o ON u.id = o.user_id AND o.created_at BETWEEN CURRENT_DATE - INTERVAL '30' DAY AND CURRENT_DATE
-        WHERE 
+        WHERE
             u.created_at > CURRENT_DATE - INTERVAL '1' YEAR
             AND (
                 u.status = 'active'
@@ -806,28 +794,51 @@ async fn test_row_level_filtering_with_complex_expressions() {
     );
 
     // Test row level filtering
-    let result = apply_row_level_filters(sql.to_string(), table_filters).await;
+    let result = apply_row_level_filters(sql.to_string(), "generic", table_filters).await;
     assert!(result.is_ok(), "Should succeed with complex expressions");
 
     let filtered_sql = result.unwrap();
     println!("Complex Expr Filtered SQL: {}\n", filtered_sql);
 
+    let orders = |alias: &str| filtered("orders", "created_at > '2023-01-01'", alias);
 
     // Verify that all table references are filtered correctly using aliases
     assert!(
-        filtered_sql.contains("FROM filtered_u u"),
+        filtered_sql.contains(&format!("FROM {}", filtered("users", "tenant_id = 123", "u"))),
         "Should filter main users reference"
     );
     assert!(
-        filtered_sql.contains("LEFT JOIN filtered_o o ON"),
+        filtered_sql.contains(&format!("LEFT JOIN {} ON", orders("o"))),
         "Should filter main orders reference in LEFT JOIN"
     );
     assert!(
-        filtered_sql.contains("FROM filtered_o o2 WHERE"),
+        filtered_sql.contains(&format!("FROM {} WHERE", orders("o2"))),
         "Should filter orders in subquery"
     );
     assert!(
-        filtered_sql.contains("FROM filtered_o o3 WHERE"),
+        filtered_sql.contains(&format!("FROM {} WHERE", orders("o3"))),
         "Should filter orders in EXISTS subquery"
     );
-} 
\ No newline at end of file
+}
+
+#[tokio::test]
+async fn test_row_level_filtering_parses_with_the_data_source_dialect() {
+    // Backslash-escaped quotes only tokenize as MySQL string literals
+    let sql = r"SELECT o.id FROM `orders` o WHERE o.note <> 'it\'s'";
+
+    let mut table_filters = HashMap::new();
+    table_filters.insert("orders".to_string(), "tenant_id = 123".to_string());
+
+    let filtered_sql = apply_row_level_filters(sql.to_string(), "mysql", table_filters)
+        .await
+        .expect("Row level filtering should succeed with MySQL syntax");
+
+    assert!(
+        filtered_sql.starts_with(&format!(
+            "SELECT o.id FROM {} WHERE o.note <> ",
+            filtered("`orders`", "tenant_id = 123", "o")
+        )),
+        "Should keep the MySQL-quoted table and filter it, got: {}",
+        filtered_sql
+    );
+}
@@ -0,0 +1,374 @@
+//! Embedding providers for stored value search.
+//!
+//! The provider is chosen once per process from the environment:
+//!
+//! * `EMBEDDING_PROVIDER` - `remote` (default) sends text to the LiteLLM embeddings endpoint,
+//!   `local` runs an ONNX model in-process on the CPU so values never leave the deployment.
+//! * `EMBEDDING_MODEL` - the model name. Defaults to `text-embedding-3-small` for `remote`
+//!   and `bge-small-en-v1.5` for `local`.
+//! * `EMBEDDING_DIMENSIONS` - vector size. Remote models are asked for this many dimensions;
+//!   local models have a fixed size and only accept their own.
+//! * `EMBEDDING_CACHE_DIR` - where local model files are downloaded to.
+//!
+//! Every search schema records the model its vectors came from, so changing any of these
+//! re-embeds existing values (see [`crate::schema::ensure_search_schema_embeddings`]).
+
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use litellm::{EmbeddingRequest, LiteLLMClient};
+use once_cell::sync::OnceCell;
+
+/// halfvec columns and their HNSW indexes support at most this many dimensions
+pub const MAX_EMBEDDING_DIMENSIONS: usize = 4000;
+
+/// Texts sent to a provider in one call
+const EMBEDDING_BATCH_SIZE: usize = 512;
+
+const DEFAULT_REMOTE_MODEL: &str = "text-embedding-3-small";
+const DEFAULT_REMOTE_DIMENSIONS: usize = 1536;
+const DEFAULT_LOCAL_MODEL: &str = "bge-small-en-v1.5";
+
+/// Turns text into vectors for similarity search
+#[async_trait]
+pub trait Embedder: Send + Sync {
+    /// Identifies the provider and model, e.g. `local:bge-small-en-v1.5:384`.
+    /// Vectors from embedders with different ids are not comparable.
+    fn model_id(&self) -> String;
+
+    /// Length of every vector returned by `embed`
+    fn dimensions(&self) -> usize;
+
+    /// Embeds `texts`, returning one vector per text in the same order
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmbeddingProvider {
+    Remote,
+    Local,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmbedderConfig {
+    pub provider: EmbeddingProvider,
+    pub model: String,
+    pub dimensions: usize,
+}
+
+impl EmbedderConfig {
+    /// Reads the embedder configuration from the environment
+    pub fn from_env() -> Result<Self> {
+        Self::from_values(
+            std::env::var("EMBEDDING_PROVIDER").ok().as_deref(),
+            std::env::var("EMBEDDING_MODEL").ok().as_deref(),
+            std::env::var("EMBEDDING_DIMENSIONS").ok().as_deref(),
+        )
+    }
+
+    fn from_values(
+        provider: Option<&str>,
+        model: Option<&str>,
+        dimensions: Option<&str>,
+    ) -> Result<Self> {
+        fn non_empty(value: Option<&str>) -> Option<&str> {
+            value.map(str::trim).filter(|v| !v.is_empty())
+        }
+
+        let provider = match non_empty(provider).map(|p| p.to_lowercase()).as_deref() {
+            None | Some("remote") | Some("openai") | Some("litellm") => EmbeddingProvider::Remote,
+            Some("local") => EmbeddingProvider::Local,
+            Some(other) => bail!(
+                "Unknown EMBEDDING_PROVIDER '{}', expected 'remote' or 'local'",
+                other
+            ),
+        };
+
+        let dimensions = non_empty(dimensions)
+            .map(|d| {
+                d.parse::<usize>().map_err(|_| {
+                    anyhow!(
+                        "EMBEDDING_DIMENSIONS must be a positive integer, got '{}'",
+                        d
+                    )
+                })
+            })
+            .transpose()?;
+
+        let (model, dimensions) = match provider {
+            EmbeddingProvider::Remote => (
+                non_empty(model).unwrap_or(DEFAULT_REMOTE_MODEL).to_string(),
+                dimensions.unwrap_or(DEFAULT_REMOTE_DIMENSIONS),
+            ),
+            EmbeddingProvider::Local => {
+                let model = non_empty(model)
+                    .unwrap_or(DEFAULT_LOCAL_MODEL)
+                    .to_lowercase();
+                let native = local_model_dimensions(&model).ok_or_else(|| {
+                    anyhow!(
+                        "Unsupported local embedding model '{}', expected one of: {}",
+                        model,
+                        LOCAL_MODELS
+                            .iter()
+                            .map(|(name, _)| *name)
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )
+                })?;
+                if let Some(requested) = dimensions {
+                    if requested != native {
+                        bail!(
+                            "Local embedding model '{}' produces {} dimensions, but EMBEDDING_DIMENSIONS is {}",
+                            model,
+                            native,
+                            requested
+                        );
+                    }
+                }
+                (model, native)
+            }
+        };
+
+        if dimensions == 0 || dimensions > MAX_EMBEDDING_DIMENSIONS {
+            bail!(
+                "EMBEDDING_DIMENSIONS must be between 1 and {}, got {}",
+                MAX_EMBEDDING_DIMENSIONS,
+                dimensions
+            );
+        }
+
+        Ok(Self {
+            provider,
+            model,
+            dimensions,
+        })
+    }
+}
+
+/// Local models that can run on the CPU, with their vector sizes
+const LOCAL_MODELS: &[(&str, usize)] = &[
+    ("bge-small-en-v1.5", 384),
+    ("bge-base-en-v1.5", 768),
+    ("all-minilm-l6-v2", 384),
+    ("nomic-embed-text-v1.5", 768),
+];
+
+fn local_model_dimensions(model: &str) -> Option<usize> {
+    LOCAL_MODELS
+        .iter()
+        .find(|(name, _)| *name == model)
+        .map(|(_, dimensions)| *dimensions)
+}
+
+/// Embeds through the LiteLLM embeddings endpoint
+pub struct RemoteEmbedder {
+    model: String,
+    dimensions: usize,
+}
+
+impl RemoteEmbedder {
+    pub fn new(model: String, dimensions: usize) -> Self {
+        Self { model, dimensions }
+    }
+}
+
+#[async_trait]
+impl Embedder for RemoteEmbedder {
+    fn model_id(&self) -> String {
+        format!("remote:{}:{}", self.model, self.dimensions)
+    }
+
+    fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let litellm_client = LiteLLMClient::new(None, None);
+        let mut embeddings = Vec::with_capacity(texts.len());
+
+        for batch in texts.chunks(EMBEDDING_BATCH_SIZE) {
+            let embedding_request = EmbeddingRequest {
+                model: self.model.clone(),
+                input: batch.to_vec(),
+                dimensions: Some(self.dimensions as u32),
+                encoding_format: Some("float".to_string()),
+                user: None,
+            };
+
+            let response = litellm_client
+                .generate_embeddings(embedding_request)
+                .await
+                .context("Failed to generate embeddings via LiteLLMClient")?;
+
+            if response.data.len() != batch.len() {
+                bail!(
+                    "Expected {} embeddings, received {}",
+                    batch.len(),
+                    response.data.len()
+                );
+            }
+            embeddings.extend(response.data.into_iter().map(|d| d.embedding));
+        }
+
+        Ok(embeddings)
+    }
+}
+
+/// Embeds in-process on the CPU with an ONNX model. The model files are downloaded
+/// on first use and cached in `EMBEDDING_CACHE_DIR`.
+#[cfg(feature = "local-embeddings")]
+pub struct LocalEmbedder {
+    model: String,
+    dimensions: usize,
+    engine: Arc<OnceCell<fastembed::TextEmbedding>>,
+}
+
+#[cfg(feature = "local-embeddings")]
+impl LocalEmbedder {
+    pub fn new(model: String, dimensions: usize) -> Self {
+        Self {
+            model,
+            dimensions,
+            engine: Arc::new(OnceCell::new()),
+        }
+    }
+
+    fn load_engine(model: &str) -> Result<fastembed::TextEmbedding> {
+        use fastembed::{EmbeddingModel, InitOptions, TextEmbedding};
+
+        let embedding_model = match model {
+            "bge-small-en-v1.5" => EmbeddingModel::BGESmallENV15,
+            "bge-base-en-v1.5" => EmbeddingModel::BGEBaseENV15,
+            "all-minilm-l6-v2" => EmbeddingModel::AllMiniLML6V2,
+            "nomic-embed-text-v1.5" => EmbeddingModel::NomicEmbedTextV15,
+            other => bail!("Unsupported local embedding model '{}'", other),
+        };
+        let cache_dir =
+            std::env::var("EMBEDDING_CACHE_DIR").unwrap_or_else(|_| ".fastembed_cache".to_string());
+
+        TextEmbedding::try_new(
+            InitOptions::new(embedding_model)
+                .with_cache_dir(cache_dir.into())
+                .with_show_download_progress(false),
+        )
+        .with_context(|| format!("Failed to load local embedding model '{}'", model))
+    }
+}
+
+#[cfg(feature = "local-embeddings")]
+#[async_trait]
+impl Embedder for LocalEmbedder {
+    fn model_id(&self) -> String {
+        format!("local:{}:{}", self.model, self.dimensions)
+    }
+
+    fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let engine = self.engine.clone();
+        let model = self.model.clone();
+        let texts = texts.to_vec();
+
+        // Inference is CPU-bound, so keep it off the async workers
+        tokio::task::spawn_blocking(move || {
+            let engine = engine.get_or_try_init(|| Self::load_engine(&model))?;
+            engine
+                .embed(texts, Some(EMBEDDING_BATCH_SIZE))
+                .context("Local embedding model failed")
+        })
+        .await
+        .context("Local embedding task panicked")?
+    }
+}
+
+/// Builds the embedder described by `config`
+pub fn build_embedder(config: &EmbedderConfig) -> Result<Arc<dyn Embedder>> {
+    match config.provider {
+        EmbeddingProvider::Remote => Ok(Arc::new(RemoteEmbedder::new(
+            config.model.clone(),
+            config.dimensions,
+        ))),
+        #[cfg(feature = "local-embeddings")]
+        EmbeddingProvider::Local => Ok(Arc::new(LocalEmbedder::new(
+            config.model.clone(),
+            config.dimensions,
+        ))),
+        #[cfg(not(feature = "local-embeddings"))]
+        EmbeddingProvider::Local => Err(anyhow!(
+            "EMBEDDING_PROVIDER=local requires building with the 'local-embeddings' feature"
+        )),
+    }
+}
+
+static EMBEDDER: OnceCell<Arc<dyn Embedder>> = OnceCell::new();
+
+/// Returns the process-wide embedder configured by the environment
+pub fn get_embedder() -> Result<Arc<dyn Embedder>> {
+    EMBEDDER
+        .get_or_try_init(|| build_embedder(&EmbedderConfig::from_env()?))
+        .cloned()
+}
+
+/// Formats a vector as a pgvector literal, e.g. `[0.1,0.2]`
+pub fn vector_literal(embedding: &[f32]) -> String {
+    format!(
+        "[{}]",
+        embedding
+            .iter()
+            .map(|f| f.to_string())
+            .collect::<Vec<String>>()
+            .join(",")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_embedder_config_defaults() {
+        let config = EmbedderConfig::from_values(None, None, None).unwrap();
+        assert_eq!(
+            config,
+            EmbedderConfig {
+                provider: EmbeddingProvider::Remote,
+                model: "text-embedding-3-small".to_string(),
+                dimensions: 1536,
+            }
+        );
+
+        let local = EmbedderConfig::from_values(Some("local"), Some(""), None).unwrap();
+        assert_eq!(local.model, "bge-small-en-v1.5");
+        assert_eq!(local.dimensions, 384);
+    }
+
+    #[test]
+    fn test_embedder_config_validation() {
+        let remote = EmbedderConfig::from_values(
+            Some("remote"),
+            Some("text-embedding-3-large"),
+            Some("1024"),
+        )
+        .unwrap();
+        assert_eq!(remote.dimensions, 1024);
+
+        assert!(EmbedderConfig::from_values(Some("cohere"), None, None).is_err());
+        assert!(EmbedderConfig::from_values(None, None, Some("abc")).is_err());
+        assert!(EmbedderConfig::from_values(None, None, Some("5000")).is_err());
+        assert!(EmbedderConfig::from_values(Some("local"), Some("gpt-4"), None).is_err());
+        // Local models have a fixed size
+        assert!(EmbedderConfig::from_values(Some("local"), None, Some("1536")).is_err());
+        assert!(
+            EmbedderConfig::from_values(Some("LOCAL"), Some("All-MiniLM-L6-v2"), Some("384"))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_vector_literal() {
+        assert_eq!(vector_literal(&[0.5, -1.0, 2.25]), "[0.5,-1,2.25]");
+    }
+}
@@ -1,28 +1,46 @@
 // libs/stored_values/src/jobs.rs
+use std::collections::{BTreeSet, HashSet};
+use std::str::FromStr;
+
 use anyhow::{anyhow, Context, Result};
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use diesel::prelude::*;
 use diesel_async::RunQueryDsl;
 use query_engine::data_source_query_routes::query_engine::query_engine;
+use query_engine::pagination::get_data_source_type;
+use semantic_layer::dialect::{string_literal, uses_top};
 use tracing::{error, info, warn};
 use uuid::Uuid;
-use litellm::{EmbeddingRequest, LiteLLMClient};
 use sqlx::QueryBuilder;
 
 use database::{
+    enums::{DataSourceType, StoredValuesStatus},
     models::StoredValuesSyncJob,
     pool::{get_pg_pool, get_sqlx_pool},
-    schema::stored_values_sync_jobs,
+    schema::{dataset_columns, stored_values_sync_jobs},
 };
 
 // query_engine imports
 use query_engine::data_types::DataType;
 
+use crate::embedder::{get_embedder, vector_literal};
+use crate::schema::ensure_search_schema_embeddings;
+
 const SYNC_CHUNK_LIMIT: i64 = 1000;
 
+/// Columns with more distinct values than this are skipped unless the job sets its own cap.
+pub const DEFAULT_MAX_CARDINALITY: i64 = 50_000;
+
+/// Schedule given to new jobs: daily at 03:00 UTC.
+pub const DEFAULT_SYNC_SCHEDULE: &str = "0 0 3 * * *";
+
+/// A job still `in_progress` after this long is assumed to have died with its process.
+const STALE_IN_PROGRESS_HOURS: i64 = 6;
+
 /// Sets up a new sync job record for a specific column in the `stored_values_sync_jobs` table.
 ///
-/// Initializes the job with a 'pending' status and null `last_synced_at`.
+/// Initializes the job with a 'pending' status and the default schedule, due immediately,
+/// and links it to the matching `dataset_columns` entry when one exists.
 pub async fn setup_sync_job(
     data_source_id: Uuid,
     database_name: String,
@@ -69,6 +87,9 @@ pub async fn setup_sync_job(
         return Ok(()); // Job already exists, no need to insert again
     }
 
+    let dataset_column_id = find_dataset_column_id(data_source_id, &schema_name, &table_name, &column_name).await?;
+
+    let now = Utc::now();
     let new_job = StoredValuesSyncJob {
         id: Uuid::new_v4(),
         data_source_id,
@@ -77,9 +98,16 @@ pub async fn setup_sync_job(
         table_name,
         column_name,
         last_synced_at: None,
-        created_at: Utc::now(),
+        created_at: now,
         status: "pending".to_string(),
         error_message: None,
+        dataset_column_id,
+        schedule: DEFAULT_SYNC_SCHEDULE.to_string(),
+        next_run_at: Some(now),
+        value_count: None,
+        values_hash: None,
+        max_cardinality: None,
+        claimed_at: None,
     };
 
     diesel::insert_into(stored_values_sync_jobs::table)
@@ -92,14 +120,40 @@ pub async fn setup_sync_job(
     Ok(())
 }
 
-/// Fetches distinct values in chunks using query_engine, generates embeddings,
-/// and inserts both into the corresponding searchable_column_values table.
-///
-/// It iterates through all distinct values by repeatedly querying with LIMIT and OFFSET.
-/// It also updates the corresponding `stored_values_sync_jobs` record with status
-/// (`in_progress`, `success`, `error`) and `last_synced_at` timestamps.
+/// Finds the `dataset_columns` entry a job syncs, matching names case-insensitively.
+async fn find_dataset_column_id(
+    data_source_id: Uuid,
+    schema_name: &str,
+    table_name: &str,
+    column_name: &str,
+) -> Result<Option<Uuid>> {
+    sqlx::query_scalar(
+        r#"
+        SELECT dc.id
+        FROM dataset_columns dc
+        JOIN datasets d ON d.id = dc.dataset_id
+        WHERE d.data_source_id = $1
+          AND lower(d.schema) = lower($2)
+          AND lower(d.name) = lower($3)
+          AND lower(dc.name) = lower($4)
+          AND d.deleted_at IS NULL
+          AND dc.deleted_at IS NULL
+        ORDER BY dc.created_at DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(data_source_id)
+    .bind(schema_name)
+    .bind(table_name)
+    .bind(column_name)
+    .fetch_optional(get_sqlx_pool())
+    .await
+    .context("Failed to look up dataset column for sync job")
+}
+
+/// Syncs the stored values of a column right away, unless its job is already running.
 ///
-/// WARNING: Identifier quoting used is basic and may not be safe for all DBs/names.
+/// Used after deploying a dataset; the work itself is done by [`sync_job`].
 pub async fn sync_distinct_values_chunk(
     data_source_id: Uuid,
     database_name: String,
@@ -113,322 +167,546 @@ pub async fn sync_distinct_values_chunk(
             .get()
             .await
             .context("Failed to get DB connection for finding job ID")?;
-        
+
         let find_job_result = stored_values_sync_jobs::table
             .filter(stored_values_sync_jobs::data_source_id.eq(data_source_id))
             .filter(stored_values_sync_jobs::database_name.eq(&database_name.to_lowercase()))
             .filter(stored_values_sync_jobs::schema_name.eq(&schema_name.to_lowercase()))
             .filter(stored_values_sync_jobs::table_name.eq(&table_name.to_lowercase()))
             .filter(stored_values_sync_jobs::column_name.eq(&column_name.to_lowercase()))
-            .filter(stored_values_sync_jobs::status.eq("pending")) // Only pick up pending jobs
             .select(stored_values_sync_jobs::id)
-            .order(stored_values_sync_jobs::created_at.desc()) // Get the most recent pending one
-            .get_result::<Uuid>(&mut conn)
+            .order(stored_values_sync_jobs::created_at.desc())
+            .first::<Uuid>(&mut conn)
             .await;
 
         match find_job_result {
             Ok(id) => id,
             Err(diesel::NotFound) => {
                 info!(
-                    "No pending sync job found for data_source_id={}, database={}, schema={}, table={}, column={}. Nothing to sync.",
+                    "No sync job found for data_source_id={}, database={}, schema={}, table={}, column={}. Nothing to sync.",
                     data_source_id, database_name, schema_name, table_name, column_name
                 );
                 return Ok(0); // No job found, so 0 items processed.
             }
             Err(e) => {
-                error!(
-                    "Failed to get pending sync job for data_source_id={}, database={}, schema={}, table={}, column={}: {}",
-                    data_source_id, database_name, schema_name, table_name, column_name, e
-                );
                 return Err(anyhow::Error::new(e).context(format!(
-                    "Failed to retrieve sync job details for data_source_id={}, database={}, schema={}, table={}, column={}",
+                    "Failed to retrieve sync job for data_source_id={}, database={}, schema={}, table={}, column={}",
                     data_source_id, database_name, schema_name, table_name, column_name
                 )));
             }
         }
     };
 
+    sync_job(job_id).await
+}
+
+/// Result of a sync run that didn't fail.
+enum SyncOutcome {
+    Synced {
+        inserted: usize,
+        removed: usize,
+        value_count: i64,
+        values_hash: String,
+    },
+    OverCardinalityCap {
+        value_count: i64,
+    },
+}
+
+/// Runs one sync job incrementally.
+///
+/// The warehouse counts and fingerprints the column's distinct values; when both match the
+/// last successful run no values are pulled, embedded or written. Otherwise only values
+/// that are new get embedded and inserted, and values that vanished are deleted.
+/// Columns above the job's cardinality cap are marked `skipped` without pulling values.
+///
+/// Returns the number of values inserted, or 0 if the job is already running elsewhere.
+pub async fn sync_job(job_id: Uuid) -> Result<usize> {
+    let Some(job) = claim_job(job_id).await? else {
+        info!(%job_id, "Sync job is already in progress, skipping");
+        return Ok(0);
+    };
+
     info!(
         %job_id,
-        %data_source_id,
-        %database_name,
-        %schema_name,
-        %table_name,
-        %column_name,
-        chunk_limit = SYNC_CHUNK_LIMIT,
-        "Starting full sync of distinct values and embeddings for job"
+        data_source_id = %job.data_source_id,
+        database_name = %job.database_name,
+        schema_name = %job.schema_name,
+        table_name = %job.table_name,
+        column_name = %job.column_name,
+        "Starting incremental sync of distinct values"
     );
+    update_column_status(&job, StoredValuesStatus::Syncing, None, None).await;
+
+    let next_run_at = match next_run_after(&job.schedule, Utc::now()) {
+        Ok(next_run_at) => Some(next_run_at),
+        Err(e) => {
+            warn!(%job_id, schedule = %job.schedule, "Job will not be rescheduled: {}", e);
+            None
+        }
+    };
+    let max_cardinality = job.max_cardinality.unwrap_or(DEFAULT_MAX_CARDINALITY);
+
+    match run_sync(&job, max_cardinality).await {
+        Ok(SyncOutcome::Synced {
+            inserted,
+            removed,
+            value_count,
+            values_hash,
+        }) => {
+            info!(%job_id, inserted, removed, value_count, "Finished incremental sync of distinct values");
+            update_job_status(&job, "success", None, Some((value_count, values_hash)), next_run_at).await?;
+            update_column_status(&job, StoredValuesStatus::Success, None, Some(value_count)).await;
+            Ok(inserted)
+        }
+        Ok(SyncOutcome::OverCardinalityCap { value_count }) => {
+            let message = format!(
+                "Column has {} distinct values, above the cap of {}; stored values were not synced",
+                value_count, max_cardinality
+            );
+            warn!(%job_id, "{}", message);
+            update_job_status(&job, "skipped", Some(message.clone()), None, next_run_at).await?;
+            update_column_status(&job, StoredValuesStatus::Failed, Some(message), None).await;
+            Ok(0)
+        }
+        Err(e) => {
+            error!(%job_id, "Failed to sync distinct values: {:?}", e);
+            let error_message = e.to_string();
+            update_column_status(&job, StoredValuesStatus::Failed, Some(error_message.clone()), None).await;
+            if let Err(update_err) = update_job_status(&job, "error", Some(error_message), None, next_run_at).await {
+                error!(%job_id, "Additionally failed to set job status to error: {}", update_err);
+                return Err(anyhow!(
+                    "Sync failed: {}. Also failed to update job status: {}",
+                    e,
+                    update_err
+                ));
+            }
+            Err(e)
+        }
+    }
+}
+
+async fn run_sync(job: &StoredValuesSyncJob, max_cardinality: i64) -> Result<SyncOutcome> {
+    // New values must be embedded with the model the schema's stored vectors use
+    ensure_search_schema_embeddings(job.data_source_id).await?;
+
+    let data_source_type = get_data_source_type(&job.data_source_id).await?;
+    let (value_count, values_hash) = fingerprint_distinct_values(job, data_source_type).await?;
+    if value_count > max_cardinality {
+        return Ok(SyncOutcome::OverCardinalityCap { value_count });
+    }
 
-    // Set status to in_progress immediately
-    if let Err(e) = update_job_status(job_id, "in_progress", None).await {
-        error!(%job_id, "Failed to set job status to in_progress: {}. Aborting sync.", e);
-        return Err(e); // Propagate the error
+    if job.value_count == Some(value_count) && job.values_hash.as_deref() == Some(values_hash.as_str()) {
+        info!(job_id = %job.id, value_count, "Distinct values unchanged since last sync");
+        return Ok(SyncOutcome::Synced {
+            inserted: 0,
+            removed: 0,
+            value_count,
+            values_hash,
+        });
     }
 
-    // Instantiate the LiteLLM Client
-    let litellm_client = LiteLLMClient::new(
-        std::env::var("OPENAI_API_KEY").ok(),
-        Some("https://api.openai.com/v1/".to_string()),
+    // The column can grow between the fingerprint and the fetch. If it changed, the stored
+    // fingerprint won't match on the next run and the values are pulled again.
+    let Some(current_values) = fetch_distinct_values(job, data_source_type, max_cardinality).await? else {
+        return Ok(SyncOutcome::OverCardinalityCap {
+            value_count: max_cardinality + 1,
+        });
+    };
+
+    let stored_values = load_stored_values(job).await?;
+    let (new_values, vanished_values) = diff_values(&current_values, &stored_values);
+    info!(
+        job_id = %job.id,
+        new = new_values.len(),
+        vanished = vanished_values.len(),
+        "Distinct values changed since last sync"
     );
 
-    // Wrap the core sync logic in a closure or block to handle errors centrally
-    let sync_result: Result<usize, anyhow::Error> = async {
-        let app_db_pool = get_sqlx_pool();
-        let target_schema_name = format!("ds_{}", data_source_id.to_string().replace('-', "_"));
-        let target_table_name = "searchable_column_values".to_string(); // Define target table name
-
-        let mut offset: i64 = 0;
-        let mut total_inserted_count: usize = 0;
-
-        loop {
-            // 1. Fetch Chunk
-            let distinct_sql = format!(
-                "SELECT DISTINCT {col} FROM {db}.{schema}.{table} ORDER BY 1 NULLS LAST LIMIT {limit} OFFSET {offset}",
-                col = column_name,
-                db = database_name,
-                schema = schema_name,
-                table = table_name,
-                limit = SYNC_CHUNK_LIMIT,
-                offset = offset
-            );
+    let removed = delete_values(job, &vanished_values).await?;
+    let inserted = insert_values(job, &new_values).await?;
 
-            info!(%job_id, current_offset = offset, "Executing distinct query chunk via query_engine: {}", distinct_sql);
-            let query_result = query_engine(&data_source_id, &distinct_sql, None)
-                .await
-                .with_context(|| {
-                    format!(
-                        "query_engine failed for distinct query chunk on {}.{}.{} at offset {}",
-                        schema_name,
-                        table_name,
-                        column_name,
-                        offset
-                    )
-                })?;
-
-            let fetched_count = query_result.data.len();
-            if fetched_count == 0 {
-                info!(%job_id, "Fetched 0 rows at offset {}, assuming end of data.", offset);
-                break; // No more data
-            }
+    Ok(SyncOutcome::Synced {
+        inserted,
+        removed,
+        value_count,
+        values_hash,
+    })
+}
 
-            // Determine the result column key dynamically from the first row
-            // Assumes query_engine returns consistent keys and only one column is selected.
-            let result_column_key = query_result.data.get(0)
-                .and_then(|first_row| first_row.keys().next().cloned())
-                .ok_or_else(|| {
-                    warn!(%job_id, "Query engine returned rows but could not determine result column key.");
-                    // Pass the format string and job_id to anyhow!
-                    anyhow!("Could not determine result column key for job_id={}", job_id)
-                })?;
-
-            info!(%job_id, %result_column_key, "Determined result column key for extraction");
-
-            // 2. Extract Values for this Chunk
-            let mut chunk_values_to_process: Vec<String> = Vec::with_capacity(fetched_count);
-            for row_map in query_result.data {
-                if let Some(value_opt) = row_map.get(&result_column_key).and_then(|dt| match dt {
-                     DataType::Text(Some(v)) => Some(v.clone()),
-                    DataType::Int2(Some(v)) => Some(v.to_string()),
-                    DataType::Int4(Some(v)) => Some(v.to_string()),
-                    DataType::Int8(Some(v)) => Some(v.to_string()),
-                    DataType::Float4(Some(v)) => Some(v.to_string()),
-                    DataType::Float8(Some(v)) => Some(v.to_string()),
-                    DataType::Bool(Some(v)) => Some(v.to_string()),
-                    DataType::Date(Some(v)) => Some(v.to_string()),
-                    DataType::Timestamp(Some(v)) => Some(v.to_string()),
-                    DataType::Timestamptz(Some(v)) => Some(v.to_string()),
-                    DataType::Json(Some(v)) => Some(v.to_string()),
-                    DataType::Uuid(Some(v)) => Some(v.to_string()),
-                    DataType::Decimal(Some(v)) => Some(v.to_string()),
-                    DataType::Time(Some(v)) => Some(v.to_string()),
-                    _ => None,
-                }) {
-                    if !value_opt.trim().is_empty() {
-                        chunk_values_to_process.push(value_opt);
-                    } else {
-                        warn!(%job_id, "Skipping empty or whitespace-only string value in chunk.");
-                    }
-                } else {
-                    warn!(%job_id, %result_column_key, ?row_map, "Could not extract valid string value using determined key, skipping.");
-                }
-            }
+/// WARNING: Identifier quoting used is basic and may not be safe for all DBs/names.
+fn source_table(job: &StoredValuesSyncJob) -> String {
+    format!("{}.{}.{}", job.database_name, job.schema_name, job.table_name)
+}
 
-            // 3. Check if Empty Chunk (after extraction)
-            if chunk_values_to_process.is_empty() {
-                info!(%job_id, fetched_in_chunk = fetched_count, "No non-null, non-empty string values extracted in this chunk at offset {}. Moving to next chunk.", offset);
-                // Still need to check if the *fetched* count means end of data
-                if (fetched_count as i64) < SYNC_CHUNK_LIMIT {
-                    info!(%job_id, "Fetched less than limit ({}) rows at offset {}, assuming end of data.", fetched_count, offset);
-                    break;
-                }
-                offset += SYNC_CHUNK_LIMIT;
-                continue; // Move to the next chunk
-            }
+fn target_table(job: &StoredValuesSyncJob) -> String {
+    format!(
+        r#""ds_{}"."searchable_column_values""#,
+        job.data_source_id.to_string().replace('-', "_")
+    )
+}
 
-            // 4. Embed Chunk
-            info!(%job_id, count = chunk_values_to_process.len(), "Generating embeddings for chunk...");
-            let embedding_request = EmbeddingRequest {
-                model: "text-embedding-3-small".to_string(),
-                input: chunk_values_to_process.clone(), // Clone values needed for embedding request
-                dimensions: Some(1536),
-                encoding_format: Some("float".to_string()),
-                user: None,
-            };
-
-            let embedding_response = litellm_client
-                .generate_embeddings(embedding_request)
-                .await
-                .context("Failed to generate embeddings via LiteLLMClient for chunk")?;
-
-            // 5. Check Embedding Count for Chunk
-            if chunk_values_to_process.len() != embedding_response.data.len() {
-                warn!(
-                    %job_id,
-                    input_count = chunk_values_to_process.len(),
-                    output_count = embedding_response.data.len(),
-                    "Mismatch between input count and embedding count for chunk. Skipping insertion for this chunk."
-                );
-                // Still check if fetched_count indicates end of data
-                if (fetched_count as i64) < SYNC_CHUNK_LIMIT {
-                     info!(%job_id, "Fetched less than limit ({}) rows at offset {}, assuming end of data.", fetched_count, offset);
-                    break;
-                }
-                offset += SYNC_CHUNK_LIMIT;
-                continue; // Skip insertion for this chunk and move to the next
-            }
+/// The column's values as text, so every warehouse can compare them with a string literal
+fn text_expression(expr: &str, data_source_type: DataSourceType) -> String {
+    match data_source_type {
+        DataSourceType::BigQuery | DataSourceType::Databricks => format!("CAST({} AS STRING)", expr),
+        DataSourceType::MySql | DataSourceType::Mariadb => format!("CAST({} AS CHAR)", expr),
+        DataSourceType::SqlServer => format!("CAST({} AS NVARCHAR(4000))", expr),
+        DataSourceType::ClickHouse => format!("toString({})", expr),
+        DataSourceType::Postgres
+        | DataSourceType::Redshift
+        | DataSourceType::Supabase
+        | DataSourceType::Snowflake
+        | DataSourceType::DuckDb => format!("CAST({} AS VARCHAR)", expr),
+    }
+}
 
-            // 6. Prepare Insert Data for Chunk
-            let values_with_formatted_embeddings: Vec<(String, String)> = chunk_values_to_process // Use original chunk values
-                .into_iter()
-                .zip(embedding_response.data.into_iter())
-                .map(|(value, embedding_data)| {
-                    let embedding_str = format!(
-                        "[{}]",
-                        embedding_data
-                            .embedding
-                            .iter()
-                            .map(|f| f.to_string())
-                            .collect::<Vec<String>>()
-                            .join(",")
-                    );
-                    (value, embedding_str)
-                })
-                .collect();
-
-            // 7. Build & Execute Insert for Chunk
-            // Initialize QueryBuilder for each chunk to avoid growing indefinitely
-            let mut query_builder: QueryBuilder<sqlx::Postgres> = QueryBuilder::new(format!(
-                r#"INSERT INTO "{}"."{}" (value, database_name, schema_name, table_name, column_name, synced_at, embedding) VALUES "#,
-                target_schema_name, target_table_name
-            ));
+/// An aggregate that hashes a set of text values independently of their order.
+fn fingerprint_aggregate(expr: &str, data_source_type: DataSourceType) -> String {
+    match data_source_type {
+        DataSourceType::Postgres | DataSourceType::Supabase => {
+            format!("SUM(('x' || LEFT(MD5({}), 15))::bit(60)::bigint)", expr)
+        }
+        DataSourceType::Redshift => {
+            format!("SUM(CAST(STRTOL(LEFT(MD5({}), 15), 16) AS DECIMAL(38, 0)))", expr)
+        }
+        DataSourceType::MySql | DataSourceType::Mariadb => {
+            format!("BIT_XOR(CAST(CONV(LEFT(MD5({}), 16), 16, 10) AS UNSIGNED))", expr)
+        }
+        DataSourceType::Snowflake => format!("HASH_AGG({})", expr),
+        DataSourceType::BigQuery => format!("BIT_XOR(FARM_FINGERPRINT({}))", expr),
+        DataSourceType::Databricks => format!("BIT_XOR(XXHASH64({}))", expr),
+        DataSourceType::DuckDb => format!("BIT_XOR(HASH({}))", expr),
+        DataSourceType::SqlServer => format!("CHECKSUM_AGG(CHECKSUM({}))", expr),
+        DataSourceType::ClickHouse => format!("groupBitXor(cityHash64({}))", expr),
+    }
+}
 
-            let mut first_row = true;
-            for (value, embedding_str) in values_with_formatted_embeddings.iter() {
-                if !first_row {
-                    query_builder.push(", ");
-                }
-                query_builder.push("(");
-                query_builder.push_bind(value);
-                query_builder.push(", ");
-                query_builder.push_bind(&database_name); // Use original names for insertion metadata
-                query_builder.push(", ");
-                query_builder.push_bind(&schema_name);
-                query_builder.push(", ");
-                query_builder.push_bind(&table_name);
-                query_builder.push(", ");
-                query_builder.push_bind(&column_name);
-                query_builder.push(", ");
-                query_builder.push_bind(Utc::now());
-                query_builder.push(", ");
-                query_builder.push("CAST(");
-                query_builder.push_bind(embedding_str);
-                query_builder.push(" AS halfvec)");
-                query_builder.push(")");
-                first_row = false;
-            }
+/// Counts and fingerprints the column's distinct non-null values in the warehouse.
+///
+/// One aggregate query, so an unchanged column can be detected without pulling its values.
+async fn fingerprint_distinct_values(
+    job: &StoredValuesSyncJob,
+    data_source_type: DataSourceType,
+) -> Result<(i64, String)> {
+    // Read back as text, since not every connector parses unsigned 64-bit or wide numbers
+    let fingerprint = text_expression(&fingerprint_aggregate("distinct_value", data_source_type), data_source_type);
+    let fingerprint_sql = format!(
+        "SELECT COUNT(*) AS value_count, {fingerprint} AS values_hash \
+         FROM (SELECT DISTINCT {value} AS distinct_value FROM {table} WHERE {col} IS NOT NULL) AS distinct_values",
+        fingerprint = fingerprint,
+        value = text_expression(&job.column_name, data_source_type),
+        table = source_table(job),
+        col = job.column_name,
+    );
 
-            query_builder.push(" ON CONFLICT DO NOTHING");
-
-            let query = query_builder.build();
-
-            info!(%job_id, row_count = values_with_formatted_embeddings.len(), "Executing batch insert for chunk...");
-            match query.execute(app_db_pool).await {
-                Ok(rows_affected) => {
-                    let current_chunk_inserted = rows_affected.rows_affected() as usize;
-                    total_inserted_count += current_chunk_inserted; // Accumulate total count
-                    info!(
-                        %job_id,
-                        inserted_in_chunk = current_chunk_inserted,
-                        total_inserted = total_inserted_count,
-                        fetched_in_chunk = fetched_count,
-                        values_processed_in_chunk = values_with_formatted_embeddings.len(),
-                        current_offset = offset,
-                        target_table = format!("{}.{}", target_schema_name, target_table_name),
-                        "Processed distinct values chunk with embeddings"
-                    );
-                }
-                Err(e) => {
-                    error!(%job_id, "Database insert failed for chunk: {:?}", e);
-                    // Propagate error to mark the job as failed
-                    return Err(anyhow::Error::new(e).context(format!(
-                        "Failed to insert distinct values chunk with embeddings into {}.{}",
-                        target_schema_name, target_table_name
-                    )));
-                }
-            }
+    let query_result = query_engine(&job.data_source_id, &fingerprint_sql, None)
+        .await
+        .with_context(|| format!("Failed to fingerprint distinct values of {}.{}", source_table(job), job.column_name))?;
+
+    // Warehouses differ in how they case the aliases, so read the values in selected order
+    let mut row = query_result
+        .data
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("Fingerprint query on {} returned no rows", source_table(job)))?
+        .into_values();
+    let value_count = row
+        .next()
+        .as_ref()
+        .and_then(value_to_i64)
+        .ok_or_else(|| anyhow!("Fingerprint query on {} returned no count", source_table(job)))?;
+    // The aggregate is NULL for a column without values
+    let values_hash = row.next().as_ref().and_then(value_to_string).unwrap_or_default();
+
+    Ok((value_count, values_hash))
+}
 
-            // 8. Update Count & Offset (or break)
-            if (fetched_count as i64) < SYNC_CHUNK_LIMIT {
-                info!(%job_id, "Fetched less than limit ({}) rows at offset {}, assuming end of data after processing chunk.", fetched_count, offset);
-                break; // End of data
-            }
+/// Pulls all non-empty distinct values of the column in chunks, paging on the last value
+/// seen so the warehouse never recomputes the chunks before it.
+///
+/// Returns `None` as soon as more than `max_cardinality` values have been seen.
+async fn fetch_distinct_values(
+    job: &StoredValuesSyncJob,
+    data_source_type: DataSourceType,
+    max_cardinality: i64,
+) -> Result<Option<BTreeSet<String>>> {
+    let mut values = BTreeSet::new();
+    let mut last_value: Option<String> = None;
+
+    loop {
+        let distinct_sql = distinct_chunk_sql(job, data_source_type, last_value.as_deref());
+        info!(job_id = %job.id, after = ?last_value, "Executing distinct query chunk via query_engine: {}", distinct_sql);
+        let query_result = query_engine(&job.data_source_id, &distinct_sql, None)
+            .await
+            .with_context(|| {
+                format!(
+                    "query_engine failed for distinct query chunk on {}.{} after {:?}",
+                    source_table(job),
+                    job.column_name,
+                    last_value
+                )
+            })?;
+
+        let fetched_count = query_result.data.len();
+        // Only one column is selected, so the first value of each row is the column value
+        let chunk: Vec<String> = query_result
+            .data
+            .iter()
+            .filter_map(|row| row.values().next().and_then(value_to_string))
+            .collect();
+
+        let chunk_last_value = chunk.last().cloned();
+        if chunk_last_value.is_some() && chunk_last_value == last_value {
+            return Err(anyhow!(
+                "Distinct values of {}.{} didn't advance past {:?}",
+                source_table(job),
+                job.column_name,
+                last_value
+            ));
+        }
+        values.extend(chunk.into_iter().filter(|value| !value.trim().is_empty()));
 
-            offset += SYNC_CHUNK_LIMIT; // Move to the next chunk offset
-        } // End loop
-
-        Ok(total_inserted_count)
-    }.await;
-
-    // Update status based on the result of the sync logic
-    match sync_result {
-        Ok(count) => {
-            info!(%job_id, total_inserted_count = count, "Finished syncing distinct values and embeddings successfully.");
-            // Update status to success
-            if let Err(e) = update_job_status(job_id, "success", None).await {
-                error!(%job_id, "Failed to set job status to success: {}", e);
-                // Even though sync succeeded, we failed to update status, return error
-                Err(e)
-            } else {
-                Ok(count)
-            }
+        if values.len() as i64 > max_cardinality {
+            return Ok(None);
         }
-        Err(e) => {
-            error!(%job_id, "Syncing distinct values and embeddings failed: {}", e);
-            // Update status to error and store the message
-            let error_message = e.to_string();
-            if let Err(update_err) = update_job_status(job_id, "error", Some(error_message)).await {
-                error!(%job_id, "Additionally failed to set job status to error: {}", update_err);
-                // Log both errors, but return the original sync error
-                Err(anyhow!(
-                    "Sync failed: {}. Also failed to update job status: {}",
-                    e,
-                    update_err
-                ))
-            } else {
-                // Return the original sync error
-                Err(e)
-            }
+        if (fetched_count as i64) < SYNC_CHUNK_LIMIT || chunk_last_value.is_none() {
+            break;
+        }
+        last_value = chunk_last_value;
+    }
+
+    Ok(Some(values))
+}
+
+/// Query for the next chunk of distinct values, in text order, after `last_value`.
+fn distinct_chunk_sql(
+    job: &StoredValuesSyncJob,
+    data_source_type: DataSourceType,
+    last_value: Option<&str>,
+) -> String {
+    let value = text_expression(&job.column_name, data_source_type);
+    let mut condition = format!("{} IS NOT NULL", job.column_name);
+    if let Some(last_value) = last_value {
+        let literal = string_literal(last_value, data_source_type);
+        // Without the N prefix SQL Server reads the literal in the database's code page
+        let literal = match data_source_type {
+            DataSourceType::SqlServer => format!("N{}", literal),
+            _ => literal,
+        };
+        condition.push_str(&format!(" AND {} > {}", value, literal));
+    }
+
+    if uses_top(data_source_type) {
+        format!(
+            "SELECT DISTINCT TOP {limit} {value} FROM {table} WHERE {condition} ORDER BY 1",
+            limit = SYNC_CHUNK_LIMIT,
+            value = value,
+            table = source_table(job),
+            condition = condition
+        )
+    } else {
+        format!(
+            "SELECT DISTINCT {value} FROM {table} WHERE {condition} ORDER BY 1 LIMIT {limit}",
+            value = value,
+            table = source_table(job),
+            condition = condition,
+            limit = SYNC_CHUNK_LIMIT
+        )
+    }
+}
+
+fn value_to_string(value: &DataType) -> Option<String> {
+    match value {
+        DataType::Text(Some(v)) => Some(v.clone()),
+        DataType::Int2(Some(v)) => Some(v.to_string()),
+        DataType::Int4(Some(v)) => Some(v.to_string()),
+        DataType::Int8(Some(v)) => Some(v.to_string()),
+        DataType::Float4(Some(v)) => Some(v.to_string()),
+        DataType::Float8(Some(v)) => Some(v.to_string()),
+        DataType::Bool(Some(v)) => Some(v.to_string()),
+        DataType::Date(Some(v)) => Some(v.to_string()),
+        DataType::Timestamp(Some(v)) => Some(v.to_string()),
+        DataType::Timestamptz(Some(v)) => Some(v.to_string()),
+        DataType::Json(Some(v)) => Some(v.to_string()),
+        DataType::Uuid(Some(v)) => Some(v.to_string()),
+        DataType::Decimal(Some(v)) => Some(v.to_string()),
+        DataType::Time(Some(v)) => Some(v.to_string()),
+        _ => None,
+    }
+}
+
+fn value_to_i64(value: &DataType) -> Option<i64> {
+    match value {
+        DataType::Int2(Some(v)) => Some(*v as i64),
+        DataType::Int4(Some(v)) => Some(*v as i64),
+        DataType::Int8(Some(v)) => Some(*v),
+        DataType::Float4(Some(v)) => Some(*v as i64),
+        DataType::Float8(Some(v)) => Some(*v as i64),
+        // Some warehouses return counts as NUMERIC or strings
+        DataType::Decimal(Some(v)) => v.to_string().parse::<f64>().ok().map(|v| v as i64),
+        DataType::Text(Some(v)) => v.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// Splits values into those not stored yet and stored ones that no longer exist.
+fn diff_values(current: &BTreeSet<String>, stored: &HashSet<String>) -> (Vec<String>, Vec<String>) {
+    let new_values = current
+        .iter()
+        .filter(|value| !stored.contains(*value))
+        .cloned()
+        .collect();
+    let mut vanished_values: Vec<String> = stored
+        .iter()
+        .filter(|value| !current.contains(*value))
+        .cloned()
+        .collect();
+    vanished_values.sort();
+    (new_values, vanished_values)
+}
+
+async fn load_stored_values(job: &StoredValuesSyncJob) -> Result<HashSet<String>> {
+    let values: Vec<String> = sqlx::query_scalar(&format!(
+        "SELECT value FROM {} WHERE database_name = $1 AND schema_name = $2 AND table_name = $3 AND column_name = $4",
+        target_table(job)
+    ))
+    .bind(&job.database_name)
+    .bind(&job.schema_name)
+    .bind(&job.table_name)
+    .bind(&job.column_name)
+    .fetch_all(get_sqlx_pool())
+    .await
+    .with_context(|| format!("Failed to load stored values from {}", target_table(job)))?;
+
+    Ok(values.into_iter().collect())
+}
+
+async fn delete_values(job: &StoredValuesSyncJob, values: &[String]) -> Result<usize> {
+    if values.is_empty() {
+        return Ok(0);
+    }
+
+    let result = sqlx::query(&format!(
+        "DELETE FROM {} WHERE database_name = $1 AND schema_name = $2 AND table_name = $3 AND column_name = $4 AND value = ANY($5)",
+        target_table(job)
+    ))
+    .bind(&job.database_name)
+    .bind(&job.schema_name)
+    .bind(&job.table_name)
+    .bind(&job.column_name)
+    .bind(values)
+    .execute(get_sqlx_pool())
+    .await
+    .with_context(|| format!("Failed to delete vanished values from {}", target_table(job)))?;
+
+    Ok(result.rows_affected() as usize)
+}
+
+/// Embeds values and inserts them into the search schema in chunks.
+async fn insert_values(job: &StoredValuesSyncJob, values: &[String]) -> Result<usize> {
+    if values.is_empty() {
+        return Ok(0);
+    }
+
+    let embedder = get_embedder()?;
+    let mut total_inserted_count = 0usize;
+
+    for chunk in values.chunks(SYNC_CHUNK_LIMIT as usize) {
+        info!(job_id = %job.id, count = chunk.len(), "Generating embeddings for new values...");
+        let embeddings = embedder
+            .embed(chunk)
+            .await
+            .context("Failed to generate embeddings for chunk")?;
+
+        if chunk.len() != embeddings.len() {
+            return Err(anyhow!(
+                "Mismatch between number of values ({}) and embeddings ({})",
+                chunk.len(),
+                embeddings.len()
+            ));
         }
+
+        let mut query_builder: QueryBuilder<sqlx::Postgres> = QueryBuilder::new(format!(
+            "INSERT INTO {} (value, database_name, schema_name, table_name, column_name, synced_at, embedding) ",
+            target_table(job)
+        ));
+        let synced_at = Utc::now();
+        query_builder.push_values(chunk.iter().zip(embeddings.iter()), |mut b, (value, embedding)| {
+            b.push_bind(value)
+                .push_bind(&job.database_name)
+                .push_bind(&job.schema_name)
+                .push_bind(&job.table_name)
+                .push_bind(&job.column_name)
+                .push_bind(synced_at)
+                .push("CAST(")
+                .push_bind_unseparated(vector_literal(embedding))
+                .push_unseparated(" AS halfvec)");
+        });
+        query_builder.push(" ON CONFLICT DO NOTHING");
+
+        let rows_affected = query_builder
+            .build()
+            .execute(get_sqlx_pool())
+            .await
+            .with_context(|| format!("Failed to insert new values into {}", target_table(job)))?
+            .rows_affected() as usize;
+
+        total_inserted_count += rows_affected;
+        info!(job_id = %job.id, inserted_in_chunk = rows_affected, total_inserted = total_inserted_count, "Inserted new values with embeddings");
     }
+
+    Ok(total_inserted_count)
+}
+
+/// Computes the first run of a cron `schedule` (seconds first, UTC) after `after`.
+fn next_run_after(schedule: &str, after: DateTime<Utc>) -> Result<DateTime<Utc>> {
+    cron::Schedule::from_str(schedule)
+        .map_err(|e| anyhow!("Invalid sync schedule '{}': {}", schedule, e))?
+        .after(&after)
+        .next()
+        .ok_or_else(|| anyhow!("Sync schedule '{}' has no upcoming runs", schedule))
 }
 
-/// Updates the status, last_synced_at, and optional error message for a sync job.
+/// Marks a job `in_progress` unless another task is already running it.
+///
+/// An `in_progress` job claimed more than [`STALE_IN_PROGRESS_HOURS`] ago is taken over.
+async fn claim_job(job_id: Uuid) -> Result<Option<StoredValuesSyncJob>> {
+    let pool = get_pg_pool();
+    let mut conn = pool
+        .get()
+        .await
+        .context("Failed to get DB connection for claiming sync job")?;
+
+    let now = Utc::now();
+    let stale_before = now - chrono::Duration::hours(STALE_IN_PROGRESS_HOURS);
+
+    diesel::update(stored_values_sync_jobs::table.find(job_id))
+        .filter(
+            stored_values_sync_jobs::status
+                .ne("in_progress")
+                .or(stored_values_sync_jobs::claimed_at.is_null())
+                .or(stored_values_sync_jobs::claimed_at.lt(stale_before)),
+        )
+        .set((
+            stored_values_sync_jobs::status.eq("in_progress"),
+            stored_values_sync_jobs::claimed_at.eq(Some(now)),
+        ))
+        .get_result::<StoredValuesSyncJob>(&mut conn)
+        .await
+        .optional()
+        .with_context(|| format!("Failed to claim sync job {}", job_id))
+}
+
+/// Records the outcome of a run, the values snapshot if there is a new one, and the next run.
 async fn update_job_status(
-    job_id: Uuid,
+    job: &StoredValuesSyncJob,
     status: &str,
     error_message: Option<String>,
+    snapshot: Option<(i64, String)>,
+    next_run_at: Option<DateTime<Utc>>,
 ) -> Result<()> {
-    info!(%job_id, %status, ?error_message, "Updating sync job status");
+    info!(job_id = %job.id, %status, ?error_message, ?next_run_at, "Updating sync job status");
 
     let pool = get_pg_pool();
     let mut conn = pool
@@ -436,118 +714,208 @@ async fn update_job_status(
         .await
         .context("Failed to get DB connection for updating sync job status")?;
 
-    let current_time = Utc::now();
+    // Without a new snapshot the previous one stays, so an unchanged column is still detected
+    let (value_count, values_hash) = match snapshot {
+        Some((value_count, values_hash)) => (Some(value_count), Some(values_hash)),
+        None => (job.value_count, job.values_hash.clone()),
+    };
+    let last_synced_at = match status {
+        "success" => Some(Utc::now()),
+        _ => job.last_synced_at,
+    };
 
-    diesel::update(stored_values_sync_jobs::table.find(job_id))
+    diesel::update(stored_values_sync_jobs::table.find(job.id))
         .set((
             stored_values_sync_jobs::status.eq(status.to_string()),
-            stored_values_sync_jobs::last_synced_at.eq(Some(current_time)),
+            stored_values_sync_jobs::last_synced_at.eq(last_synced_at),
             stored_values_sync_jobs::error_message.eq(error_message),
+            stored_values_sync_jobs::next_run_at.eq(next_run_at),
+            stored_values_sync_jobs::value_count.eq(value_count),
+            stored_values_sync_jobs::values_hash.eq(values_hash),
         ))
         .execute(&mut conn)
         .await
-        .with_context(|| format!("Failed to update sync job status for job_id: {}", job_id))?;
+        .with_context(|| format!("Failed to update sync job status for job_id: {}", job.id))?;
 
-    info!(%job_id, %status, "Successfully updated sync job status");
     Ok(())
 }
 
-/// Scans for sync jobs that are pending or haven't been successfully synced in the last 24 hours,
-/// and triggers the sync process for each identified job in a separate background task.
-pub async fn trigger_stale_sync_jobs() -> Result<()> {
-    info!("Scanning for stale or pending stored values sync jobs...");
+/// Mirrors a job's state onto its `dataset_columns` entry. Failures are only logged.
+async fn update_column_status(
+    job: &StoredValuesSyncJob,
+    status: StoredValuesStatus,
+    error_message: Option<String>,
+    value_count: Option<i64>,
+) {
+    let Some(dataset_column_id) = job.dataset_column_id else {
+        return;
+    };
+
+    let result = async {
+        let mut conn = get_pg_pool().get().await?;
+        let column = dataset_columns::table.find(dataset_column_id);
+        match status {
+            StoredValuesStatus::Success => {
+                diesel::update(column)
+                    .set((
+                        dataset_columns::stored_values_status.eq(Some(status)),
+                        dataset_columns::stored_values_error.eq(None::<String>),
+                        dataset_columns::stored_values_count.eq(value_count),
+                        dataset_columns::stored_values_last_synced.eq(Some(Utc::now())),
+                    ))
+                    .execute(&mut conn)
+                    .await?;
+            }
+            _ => {
+                diesel::update(column)
+                    .set((
+                        dataset_columns::stored_values_status.eq(Some(status)),
+                        dataset_columns::stored_values_error.eq(error_message),
+                    ))
+                    .execute(&mut conn)
+                    .await?;
+            }
+        }
+        Ok::<_, anyhow::Error>(())
+    }
+    .await;
+
+    if let Err(e) = result {
+        warn!(job_id = %job.id, %dataset_column_id, "Failed to update stored values status of dataset column: {}", e);
+    }
+}
 
+/// Starts every sync job whose `next_run_at` has passed, each in its own background task.
+///
+/// Meant to be called on a short fixed interval. Jobs already in progress are left alone,
+/// so overlapping calls are harmless. Returns the number of jobs started.
+pub async fn run_due_sync_jobs() -> Result<usize> {
     let pool = get_pg_pool();
     let mut conn = pool
         .get()
         .await
-        .context("Failed to get DB connection for scanning stale jobs")?;
-
-    let twenty_four_hours_ago = Utc::now() - chrono::Duration::hours(24);
-
-    // Find jobs that were last synced > 24h ago and are not 'in_progress' or 'error'
-    // Also include jobs that are 'pending' and have never been synced (last_synced_at is NULL),
-    // or are 'pending' and their creation time implies they might have been missed.
-    // For simplicity with Diesel, we can query broadly and then filter in code, or make a more complex query.
-    // The current query correctly gets jobs that have a last_synced_at > 24h ago and are not in_progress/error.
-    // To include 'pending' jobs regardless of last_synced_at (as they should be picked up),
-    // the logic inside the loop will handle ensuring they are 'pending'.
-    let jobs_to_sync = stored_values_sync_jobs::table
+        .context("Failed to get DB connection for scanning due sync jobs")?;
+
+    let now = Utc::now();
+    let stale_before = now - chrono::Duration::hours(STALE_IN_PROGRESS_HOURS);
+
+    let due_job_ids = stored_values_sync_jobs::table
+        .filter(
+            stored_values_sync_jobs::next_run_at
+                .le(now)
+                .or(stored_values_sync_jobs::next_run_at.is_null()),
+        )
         .filter(
-            stored_values_sync_jobs::last_synced_at
-                .lt(twenty_four_hours_ago)
-                .or(stored_values_sync_jobs::last_synced_at.is_null()) // Also consider jobs never synced
-                .and(stored_values_sync_jobs::status.ne("in_progress"))
-                .and(stored_values_sync_jobs::status.ne("error")),
+            stored_values_sync_jobs::status
+                .ne("in_progress")
+                .or(stored_values_sync_jobs::claimed_at.lt(stale_before)),
         )
-        .load::<StoredValuesSyncJob>(&mut conn)
+        .select(stored_values_sync_jobs::id)
+        .load::<Uuid>(&mut conn)
         .await
-        .context("Failed to load sync jobs from database")?;
-
-    let count = jobs_to_sync.len();
-    info!("Found {} jobs to potentially sync based on age or pending status.", count);
-
-    for job in jobs_to_sync {
-        info!(job_id = %job.id, status = %job.status, last_synced_at = ?job.last_synced_at, "Considering job: {}", job.column_name);
-
-        let mut job_is_ready_to_sync = job.status == "pending";
-
-        // If the job is eligible (old or never synced, and not in_progress/error)
-        // and not already pending, mark it as 'pending'.
-        if !job_is_ready_to_sync &&
-           (job.last_synced_at.map_or(true, |lsa| lsa < twenty_four_hours_ago) && // Explicitly check age here too
-            job.status != "in_progress" && job.status != "error") {
-            info!(job_id = %job.id, old_status = %job.status, "Marking stale/eligible job as pending for re-sync.");
-            match diesel::update(stored_values_sync_jobs::table.find(job.id))
-                .set(stored_values_sync_jobs::status.eq("pending".to_string()))
-                // Do not update last_synced_at here; sync_distinct_values_chunk handles it.
-                .execute(&mut conn)
-                .await
-            {
-                Ok(num_updated) => {
-                    if num_updated > 0 {
-                        info!(job_id = %job.id, "Successfully marked stale/eligible job as pending.");
-                        job_is_ready_to_sync = true;
-                    } else {
-                        warn!(job_id = %job.id, "Failed to mark stale/eligible job as pending (0 rows updated). It might have been deleted or changed concurrently.");
-                    }
+        .context("Failed to load due sync jobs from database")?;
+
+    let count = due_job_ids.len();
+    if count > 0 {
+        info!("Starting {} due stored values sync jobs", count);
+    }
+
+    for job_id in due_job_ids {
+        tokio::spawn(async move {
+            match sync_job(job_id).await {
+                Ok(inserted_count) => {
+                    info!(%job_id, %inserted_count, "Background sync task completed successfully.");
                 }
                 Err(e) => {
-                    error!(job_id = %job.id, "DB error while marking stale/eligible job as pending: {}. Skipping this job.", e);
+                    // sync_job already records the error on the job
+                    error!(%job_id, "Background sync task failed: {}", e);
                 }
             }
-        }
+        });
+    }
 
-        // Now, if the job is 'pending' (either originally or just updated), spawn the sync task.
-        if job_is_ready_to_sync {
-            info!(job_id = %job.id, status = "pending", "Spawning sync task for job: {}", job.column_name);
-
-            // Clone the necessary data for the async task
-            let ds_id = job.data_source_id;
-            let db_name = job.database_name.clone();
-            let sch_name = job.schema_name.clone();
-            let tbl_name = job.table_name.clone();
-            let col_name = job.column_name.clone();
-            let job_id_for_task = job.id; // Capture job_id for logging inside task
-
-            tokio::spawn(async move {
-                info!(%job_id_for_task, "Background sync task started.");
-                match sync_distinct_values_chunk(ds_id, db_name, sch_name, tbl_name, col_name).await {
-                    Ok(inserted_count) => {
-                        info!(%job_id_for_task, %inserted_count, "Background sync task completed successfully.");
-                    }
-                    Err(e) => {
-                        error!(%job_id_for_task, "Background sync task failed: {}", e);
-                        // Note: sync_distinct_values_chunk already updates the job status to 'error'
-                    }
-                }
-            });
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn values(values: &[&str]) -> BTreeSet<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    fn job() -> StoredValuesSyncJob {
+        StoredValuesSyncJob {
+            id: Uuid::new_v4(),
+            data_source_id: Uuid::new_v4(),
+            database_name: "analytics".to_string(),
+            schema_name: "public".to_string(),
+            table_name: "orders".to_string(),
+            column_name: "region".to_string(),
+            last_synced_at: None,
+            created_at: Utc::now(),
+            status: "pending".to_string(),
+            error_message: None,
+            dataset_column_id: None,
+            schedule: DEFAULT_SYNC_SCHEDULE.to_string(),
+            next_run_at: None,
+            value_count: None,
+            values_hash: None,
+            max_cardinality: None,
+            claimed_at: None,
         }
     }
 
-    info!("Finished considering {} jobs for sync tasks.", count);
-    Ok(())
-}
+    #[test]
+    fn test_distinct_chunk_sql() {
+        assert_eq!(
+            distinct_chunk_sql(&job(), DataSourceType::Postgres, None),
+            "SELECT DISTINCT CAST(region AS VARCHAR) FROM analytics.public.orders \
+             WHERE region IS NOT NULL ORDER BY 1 LIMIT 1000"
+        );
+        assert_eq!(
+            distinct_chunk_sql(&job(), DataSourceType::Postgres, Some("O'Hare")),
+            "SELECT DISTINCT CAST(region AS VARCHAR) FROM analytics.public.orders \
+             WHERE region IS NOT NULL AND CAST(region AS VARCHAR) > 'O''Hare' ORDER BY 1 LIMIT 1000"
+        );
+        assert_eq!(
+            distinct_chunk_sql(&job(), DataSourceType::MySql, Some("a\\' OR 1=1")),
+            "SELECT DISTINCT CAST(region AS CHAR) FROM analytics.public.orders \
+             WHERE region IS NOT NULL AND CAST(region AS CHAR) > 'a\\\\\\' OR 1=1' ORDER BY 1 LIMIT 1000"
+        );
+        assert_eq!(
+            distinct_chunk_sql(&job(), DataSourceType::SqlServer, Some("Zürich")),
+            "SELECT DISTINCT TOP 1000 CAST(region AS NVARCHAR(4000)) FROM analytics.public.orders \
+             WHERE region IS NOT NULL AND CAST(region AS NVARCHAR(4000)) > N'Zürich' ORDER BY 1"
+        );
+    }
+
+    #[test]
+    fn test_diff_values() {
+        let current = values(&["CA", "MX", "US"]);
+        let stored: HashSet<String> = ["US", "FR", "DE"].iter().map(|v| v.to_string()).collect();
+
+        let (new_values, vanished_values) = diff_values(&current, &stored);
+        assert_eq!(new_values, vec!["CA", "MX"]);
+        assert_eq!(vanished_values, vec!["DE", "FR"]);
+    }
 
-// Potential future functions:
-// pub async fn get_pending_sync_jobs(...) -> Result<Vec<StoredValuesSyncJob>> { ... }
+    #[test]
+    fn test_next_run_after() {
+        let after = Utc.with_ymd_and_hms(2025, 5, 9, 4, 0, 0).unwrap();
+        assert_eq!(
+            next_run_after(DEFAULT_SYNC_SCHEDULE, after).unwrap(),
+            Utc.with_ymd_and_hms(2025, 5, 10, 3, 0, 0).unwrap()
+        );
+        assert!(next_run_after("every night", after).is_err());
+    }
+
+    #[test]
+    fn test_value_to_i64() {
+        assert_eq!(value_to_i64(&DataType::Int8(Some(42))), Some(42));
+        assert_eq!(value_to_i64(&DataType::Text(Some("7".to_string()))), Some(7));
+        assert_eq!(value_to_i64(&DataType::Int8(None)), None);
+    }
+}
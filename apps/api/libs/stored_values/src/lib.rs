@@ -2,13 +2,15 @@
 
 pub use anyhow::Result;
 
+pub mod embedder;
 pub mod schema;
 pub mod jobs;
 pub mod search;
 
 // Re-export key functions
-pub use schema::create_search_schema;
-pub use jobs::setup_sync_job;
+pub use embedder::{get_embedder, Embedder};
+pub use schema::{create_search_schema, ensure_all_search_schema_embeddings, ensure_search_schema_embeddings};
+pub use jobs::{run_due_sync_jobs, setup_sync_job, sync_job};
 pub use search::{search_values_by_embedding, StoredValueResult};
 
 // Add other modules like types, errors, etc. as needed 
\ No newline at end of file
@@ -1,10 +1,19 @@
-use anyhow::{Context, Result};
-use sqlx::Executor;
-use tracing::info;
+use anyhow::{bail, Context, Result};
+use sqlx::{Connection, Executor, PgPool};
+use tracing::{error, info, warn};
 use uuid::Uuid;
 
 use database::pool::get_sqlx_pool;
 
+use crate::embedder::{get_embedder, vector_literal, Embedder};
+
+/// Model that produced the vectors of search schemas created before the model was recorded
+const LEGACY_EMBEDDING_MODEL_ID: &str = "remote:text-embedding-3-small:1536";
+const LEGACY_EMBEDDING_DIMENSIONS: i32 = 1536;
+
+/// Values re-embedded per round trip when filling missing embeddings
+const REEMBED_BATCH_SIZE: i64 = 500;
+
 /// Creates a dedicated schema and table for storing searchable column values and embeddings.
 ///
 /// The schema name is derived from the data_source_id by replacing hyphens with underscores.
@@ -13,6 +22,8 @@ pub async fn create_search_schema(data_source_id: Uuid) -> Result<()> {
     let schema_name = format!("ds_{}", data_source_id.to_string().replace('-', "_"));
     info!(%data_source_id, %schema_name, "Creating search schema and table");
 
+    let embedder = get_embedder()?;
+
     let pool = get_sqlx_pool();
     let mut conn = pool
         .acquire()
@@ -26,7 +37,14 @@ pub async fn create_search_schema(data_source_id: Uuid) -> Result<()> {
         .with_context(|| format!("Failed to create schema: {}", schema_name))?;
     info!(%schema_name, "Schema created successfully");
 
-    // 2. Create the table within the schema
+    // 2. Create the table within the schema, sized for the configured embedder.
+    // A table that already exists keeps its vectors until ensure_search_schema_embeddings migrates it.
+    let table_existed: bool = sqlx::query_scalar("SELECT to_regclass($1) IS NOT NULL")
+        .bind(format!("\"{}\".searchable_column_values", schema_name))
+        .fetch_one(&mut *conn)
+        .await
+        .context("Failed to check for an existing searchable_column_values table")?;
+
     let create_table_query = format!(
         r#"
         CREATE TABLE IF NOT EXISTS "{}"."searchable_column_values" (
@@ -36,11 +54,12 @@ pub async fn create_search_schema(data_source_id: Uuid) -> Result<()> {
             column_name text NOT NULL,
             table_name text NOT NULL,
             schema_name text NOT NULL,
-            embedding public.halfvec(1536), -- Assuming halfvec is in public or installed extension schema
+            embedding public.halfvec({}), -- Assuming halfvec is in public or installed extension schema
             synced_at timestamp with time zone DEFAULT now()
         );
         "#,
-        schema_name
+        schema_name,
+        embedder.dimensions()
     );
     conn.execute(create_table_query.as_str())
         .await
@@ -89,7 +108,29 @@ pub async fn create_search_schema(data_source_id: Uuid) -> Result<()> {
         .with_context(|| format!("Failed to create unique value index in schema {}", schema_name))?;
     info!(%schema_name, "Unique index on (value, db, schema, table, column) created successfully");
 
-    // 6. Create schema-specific embedding input function
+    // 6. Create a trigram index for lexical matching
+    create_trigram_index(&mut conn, &schema_name).await?;
+    info!(%schema_name, "Trigram index on values created successfully");
+
+    // 7. Record which embedder the vectors come from
+    create_embedding_config_table(&mut conn, &schema_name).await?;
+    let (model_id, dimensions) = if table_existed {
+        (LEGACY_EMBEDDING_MODEL_ID.to_string(), LEGACY_EMBEDDING_DIMENSIONS)
+    } else {
+        (embedder.model_id(), embedder.dimensions() as i32)
+    };
+    sqlx::query(&format!(
+        r#"INSERT INTO "{}"."search_embedding_config" (embedding_model, dimensions) VALUES ($1, $2) ON CONFLICT (id) DO NOTHING"#,
+        schema_name
+    ))
+    .bind(&model_id)
+    .bind(dimensions)
+    .execute(&mut *conn)
+    .await
+    .with_context(|| format!("Failed to record embedding model in schema {}", schema_name))?;
+    info!(%schema_name, embedding_model = %model_id, "Embedding model recorded");
+
+    // 8. Create schema-specific embedding input function
     // let input_fn_name = format!("embedding_input_{}", schema_name);
     // let create_input_fn_query = format!(r#"
     //     CREATE OR REPLACE FUNCTION "{}"."{}"(rec "{}"."searchable_column_values")
@@ -111,7 +152,7 @@ pub async fn create_search_schema(data_source_id: Uuid) -> Result<()> {
     //     .with_context(|| format!("Failed to create embedding input function in schema {}", schema_name))?;
     // info!(%schema_name, function_name=%input_fn_name, "Embedding input function created");
 
-    // 7. Create INSERT trigger for embeddings
+    // 9. Create INSERT trigger for embeddings
     // let insert_trigger_name = format!("embed_values_on_insert_{}", schema_name);
     // let create_insert_trigger_query = format!(r#"
     //     CREATE OR REPLACE TRIGGER "{}"
@@ -130,7 +171,7 @@ pub async fn create_search_schema(data_source_id: Uuid) -> Result<()> {
     //     .with_context(|| format!("Failed to create insert trigger for embeddings in schema {}", schema_name))?;
     // info!(%schema_name, trigger_name=%insert_trigger_name, "Insert trigger for embeddings created");
 
-    // 8. Create UPDATE trigger for embeddings
+    // 10. Create UPDATE trigger for embeddings
     // let update_trigger_name = format!("embed_values_on_update_{}", schema_name);
     // let create_update_trigger_query = format!(r#"
     //     CREATE OR REPLACE TRIGGER "{}"
@@ -153,6 +194,253 @@ pub async fn create_search_schema(data_source_id: Uuid) -> Result<()> {
     Ok(())
 }
 
-// Potential future functions:
-// pub async fn store_column_value(...) -> Result<()> { ... }
+/// Creates the trigram index used by the lexical half of hybrid search
+async fn create_trigram_index(conn: &mut sqlx::PgConnection, schema_name: &str) -> Result<()> {
+    let create_trigram_index_query = format!(
+        r#"
+        CREATE INDEX IF NOT EXISTS "idx_value_trgm_{}" ON "{}"."searchable_column_values"
+        USING gin (value gin_trgm_ops);
+        "#,
+        schema_name, schema_name
+    );
+    conn.execute(create_trigram_index_query.as_str())
+        .await
+        .with_context(|| format!("Failed to create trigram index on values in schema {}", schema_name))?;
+    Ok(())
+}
+
+/// Creates the single-row table recording the embedder of a search schema
+async fn create_embedding_config_table(
+    conn: &mut sqlx::PgConnection,
+    schema_name: &str,
+) -> Result<()> {
+    let create_config_query = format!(
+        r#"
+        CREATE TABLE IF NOT EXISTS "{}"."search_embedding_config" (
+            id boolean PRIMARY KEY DEFAULT true CHECK (id),
+            embedding_model text NOT NULL,
+            dimensions integer NOT NULL,
+            updated_at timestamp with time zone NOT NULL DEFAULT now()
+        );
+        "#,
+        schema_name
+    );
+    conn.execute(create_config_query.as_str())
+        .await
+        .with_context(|| format!("Failed to create search_embedding_config in schema {}", schema_name))?;
+    Ok(())
+}
+
+/// Makes a data source's stored value embeddings match the configured embedder.
+///
+/// If the schema was embedded with a different model, its embedding column is resized
+/// and cleared, then every value is embedded again. Values without an embedding are
+/// filled in either way, so an interrupted migration resumes where it stopped. Search
+/// keeps working during the migration, over the values embedded so far.
+pub async fn ensure_search_schema_embeddings(data_source_id: Uuid) -> Result<()> {
+    let schema_name = format!("ds_{}", data_source_id.to_string().replace('-', "_"));
+    let embedder = get_embedder()?;
+    let pool = get_sqlx_pool();
+
+    let table_exists: bool = sqlx::query_scalar("SELECT to_regclass($1) IS NOT NULL")
+        .bind(format!("\"{}\".searchable_column_values", schema_name))
+        .fetch_one(pool)
+        .await
+        .context("Failed to check for searchable_column_values table")?;
+    if !table_exists {
+        return Ok(());
+    }
+
+    // Only one process migrates a schema at a time; the others wait and then find it done
+    let mut lock_conn = pool
+        .acquire()
+        .await
+        .context("Failed to acquire DB connection for embedding migration lock")?;
+    sqlx::query("SELECT pg_advisory_lock(hashtext($1))")
+        .bind(&schema_name)
+        .execute(&mut *lock_conn)
+        .await
+        .context("Failed to lock search schema for embedding migration")?;
+
+    let result = migrate_search_schema_embeddings(pool, &schema_name, embedder.as_ref()).await;
+
+    if let Err(e) = sqlx::query("SELECT pg_advisory_unlock(hashtext($1))")
+        .bind(&schema_name)
+        .execute(&mut *lock_conn)
+        .await
+    {
+        warn!(%schema_name, "Failed to release embedding migration lock: {}", e);
+    }
+
+    result
+}
+
+async fn migrate_search_schema_embeddings(
+    pool: &PgPool,
+    schema_name: &str,
+    embedder: &dyn Embedder,
+) -> Result<()> {
+    let mut conn = pool
+        .acquire()
+        .await
+        .context("Failed to acquire DB connection for embedding migration")?;
+
+    create_embedding_config_table(&mut conn, schema_name).await?;
+    sqlx::query(&format!(
+        r#"INSERT INTO "{}"."search_embedding_config" (embedding_model, dimensions) VALUES ($1, $2) ON CONFLICT (id) DO NOTHING"#,
+        schema_name
+    ))
+    .bind(LEGACY_EMBEDDING_MODEL_ID)
+    .bind(LEGACY_EMBEDDING_DIMENSIONS)
+    .execute(&mut *conn)
+    .await
+    .with_context(|| format!("Failed to initialize embedding model record in schema {}", schema_name))?;
+
+    let recorded_model: String = sqlx::query_scalar(&format!(
+        r#"SELECT embedding_model FROM "{}"."search_embedding_config""#,
+        schema_name
+    ))
+    .fetch_one(&mut *conn)
+    .await
+    .with_context(|| format!("Failed to read embedding model of schema {}", schema_name))?;
+
+    let target_model = embedder.model_id();
+    let index_name = format!("idx_embedding_hnsw_{}", schema_name);
+
+    if recorded_model != target_model {
+        info!(
+            %schema_name,
+            from = %recorded_model,
+            to = %target_model,
+            "Embedding model changed, re-embedding stored values"
+        );
+
+        // Vectors from different models can't be compared, so all of them are discarded
+        let mut tx = conn.begin().await?;
+        tx.execute(format!(r#"DROP INDEX IF EXISTS "{}"."{}""#, schema_name, index_name).as_str())
+            .await
+            .with_context(|| format!("Failed to drop HNSW index in schema {}", schema_name))?;
+        tx.execute(
+            format!(
+                r#"ALTER TABLE "{}"."searchable_column_values" ALTER COLUMN embedding TYPE public.halfvec({}) USING NULL"#,
+                schema_name,
+                embedder.dimensions()
+            )
+            .as_str(),
+        )
+        .await
+        .with_context(|| format!("Failed to resize embedding column in schema {}", schema_name))?;
+        sqlx::query(&format!(
+            r#"UPDATE "{}"."search_embedding_config" SET embedding_model = $1, dimensions = $2, updated_at = now()"#,
+            schema_name
+        ))
+        .bind(&target_model)
+        .bind(embedder.dimensions() as i32)
+        .execute(&mut *tx)
+        .await
+        .with_context(|| format!("Failed to record embedding model in schema {}", schema_name))?;
+        tx.commit().await?;
+    }
+
+    let mut total_embedded = 0usize;
+    loop {
+        let rows: Vec<(Uuid, String)> = sqlx::query_as(&format!(
+            r#"SELECT id, value FROM "{}"."searchable_column_values" WHERE embedding IS NULL ORDER BY id LIMIT $1"#,
+            schema_name
+        ))
+        .bind(REEMBED_BATCH_SIZE)
+        .fetch_all(&mut *conn)
+        .await
+        .with_context(|| format!("Failed to load values to embed in schema {}", schema_name))?;
+
+        if rows.is_empty() {
+            break;
+        }
+
+        let (ids, values): (Vec<Uuid>, Vec<String>) = rows.into_iter().unzip();
+        let embeddings = embedder.embed(&values).await?;
+        if embeddings.len() != ids.len() {
+            bail!(
+                "Expected {} embeddings, received {}",
+                ids.len(),
+                embeddings.len()
+            );
+        }
+        let literals: Vec<String> = embeddings.iter().map(|e| vector_literal(e)).collect();
+
+        let updated = sqlx::query(&format!(
+            r#"
+            UPDATE "{}"."searchable_column_values" AS t
+            SET embedding = CAST(v.embedding AS public.halfvec)
+            FROM UNNEST($1::uuid[], $2::text[]) AS v(id, embedding)
+            WHERE t.id = v.id
+            "#,
+            schema_name
+        ))
+        .bind(&ids)
+        .bind(&literals)
+        .execute(&mut *conn)
+        .await
+        .with_context(|| format!("Failed to store embeddings in schema {}", schema_name))?
+        .rows_affected();
+
+        // Rows deleted concurrently would otherwise be fetched again forever
+        if updated == 0 {
+            break;
+        }
+        total_embedded += updated as usize;
+        info!(%schema_name, total_embedded, "Re-embedded stored values batch");
+    }
+
+    conn.execute(
+        format!(
+            r#"CREATE INDEX IF NOT EXISTS {} ON "{}"."searchable_column_values" USING hnsw (embedding public.halfvec_cosine_ops)"#,
+            index_name, schema_name
+        )
+        .as_str(),
+    )
+    .await
+    .with_context(|| format!("Failed to create HNSW index on embeddings in schema {}", schema_name))?;
+
+    // Schemas created before hybrid search don't have it yet
+    create_trigram_index(&mut conn, schema_name).await?;
+
+    if total_embedded > 0 {
+        info!(%schema_name, total_embedded, embedding_model = %target_model, "Finished embedding stored values");
+    }
+    Ok(())
+}
+
+/// Brings every data source's stored value embeddings in line with the configured embedder.
+///
+/// Run at startup so changing `EMBEDDING_PROVIDER`, `EMBEDDING_MODEL` or
+/// `EMBEDDING_DIMENSIONS` migrates existing values. A failing data source is logged and skipped.
+pub async fn ensure_all_search_schema_embeddings() -> Result<()> {
+    let schemas: Vec<String> = sqlx::query_scalar(
+        r#"
+        SELECT table_schema
+        FROM information_schema.tables
+        WHERE table_name = 'searchable_column_values'
+          AND table_schema LIKE 'ds\_%'
+        "#,
+    )
+    .fetch_all(get_sqlx_pool())
+    .await
+    .context("Failed to list stored value search schemas")?;
+
+    for schema_name in schemas {
+        let data_source_id = match Uuid::parse_str(&schema_name["ds_".len()..].replace('_', "-")) {
+            Ok(id) => id,
+            Err(_) => {
+                warn!(%schema_name, "Skipping search schema with an unexpected name");
+                continue;
+            }
+        };
+        if let Err(e) = ensure_search_schema_embeddings(data_source_id).await {
+            error!(%data_source_id, "Failed to migrate stored value embeddings: {}", e);
+        }
+    }
+
+    Ok(())
+}
 // pub async fn search_similar_values(...) -> Result<Vec<SearchResult>> { ... } 
\ No newline at end of file
@@ -320,8 +320,221 @@ pub async fn search_values_across_targets(
     Ok(all_results)
 }
 
-// Rename the original function or remove it if no longer needed
-// pub async fn search_values_by_substring(...) -> Result<Vec<StoredValueResult>> { ... }
+/// Rank offset used when fusing the lexical and embedding rankings
+const RRF_K: i64 = 60;
+
+/// A stored value matched by [`search_values_hybrid`]
+#[derive(FromRow, Debug, Clone)]
+pub struct ScoredValueResult {
+    #[sqlx(flatten)]
+    pub value: StoredValueResult,
+    pub score: f64,
+}
+
+/// Escapes LIKE wildcards so the text is matched literally
+fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+fn vector_literal(embedding: &[f32]) -> String {
+    format!("'{}'::halfvec", crate::embedder::vector_literal(embedding))
+}
+
+fn search_values_hybrid_sql(
+    pg_schema_name: &str,
+    query_embedding: Option<&[f32]>,
+    filter_targets: bool,
+) -> String {
+    let target_filter = if filter_targets {
+        "AND (database_name, schema_name, table_name, column_name) IN (
+                SELECT * FROM UNNEST($6::text[], $7::text[], $8::text[], $9::text[])
+            )"
+    } else {
+        ""
+    };
+
+    let semantic_sql = match query_embedding {
+        Some(embedding) if !embedding.is_empty() => format!(
+            r#"
+            SELECT id, row_number() OVER (ORDER BY embedding <=> {vector}) AS rank
+            FROM "{pg_schema_name}"."searchable_column_values"
+            WHERE embedding IS NOT NULL {target_filter}
+            ORDER BY embedding <=> {vector}
+            LIMIT $4
+            "#,
+            vector = vector_literal(embedding)
+        ),
+        _ => "SELECT NULL::uuid AS id, NULL::bigint AS rank WHERE false".to_string(),
+    };
+
+    format!(
+        r#"
+        WITH lexical AS (
+            SELECT id,
+                row_number() OVER (
+                    ORDER BY
+                        lower(value) = lower($1) DESC,
+                        value ILIKE $3 DESC,
+                        similarity(value, $1) DESC,
+                        length(value)
+                ) AS rank
+            FROM "{pg_schema_name}"."searchable_column_values"
+            WHERE (value ILIKE $2 OR value % $1) {target_filter}
+            ORDER BY rank
+            LIMIT $4
+        ),
+        semantic AS ({semantic_sql})
+        SELECT
+            v.id, v.value, v.database_name, v.column_name, v.table_name, v.schema_name, v.synced_at,
+            (
+                COALESCE(1.0 / ({RRF_K} + lexical.rank), 0.0)
+                + COALESCE(1.0 / ({RRF_K} + semantic.rank), 0.0)
+                + CASE WHEN lower(v.value) = lower($1) THEN 1.0 ELSE 0.0 END
+            )::float8 AS score
+        FROM lexical
+        FULL OUTER JOIN semantic ON semantic.id = lexical.id
+        JOIN "{pg_schema_name}"."searchable_column_values" AS v
+            ON v.id = COALESCE(lexical.id, semantic.id)
+        ORDER BY score DESC, v.value
+        LIMIT $5
+        "#
+    )
+}
+
+/// Searches stored values by text and, when given, embedding similarity.
+///
+/// Lexical matches (case-insensitive substring or trigram similarity) and nearest
+/// embeddings are merged with reciprocal rank fusion, so exact codes such as SKUs,
+/// order IDs or ISO country codes are found even when their embeddings are
+/// uninformative. An exact (case-insensitive) match always ranks first.
+///
+/// # Arguments
+///
+/// * `data_source_id` - UUID of the data source to construct the schema name.
+/// * `query` - The text to search for.
+/// * `query_embedding` - Embedding of `query`; without it only lexical matching is used.
+/// * `targets` - Columns to search in; all columns when empty.
+/// * `limit` - The maximum number of results.
+///
+/// # Returns
+///
+/// A `Result` containing the matching values, best first.
+pub async fn search_values_hybrid(
+    data_source_id: Uuid,
+    query: &str,
+    query_embedding: Option<&[f32]>,
+    targets: &[SearchTarget],
+    limit: i64,
+) -> Result<Vec<ScoredValueResult>> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let pg_schema_name = format!("ds_{}", data_source_id.to_string().replace('-', "_"));
+    let query_sql = search_values_hybrid_sql(&pg_schema_name, query_embedding, !targets.is_empty());
+    let escaped = escape_like(query);
+
+    debug!(
+        %data_source_id,
+        %pg_schema_name,
+        %query,
+        has_embedding = query_embedding.is_some(),
+        target_count = targets.len(),
+        %limit,
+        "Executing hybrid stored value search"
+    );
+
+    let mut conn = get_sqlx_pool().acquire().await?;
+    let mut sql_query = sqlx::query_as::<_, ScoredValueResult>(&query_sql)
+        .bind(query)
+        .bind(format!("%{}%", escaped))
+        .bind(format!("{}%", escaped))
+        // Each ranking looks further than the final limit so fusion has something to merge
+        .bind(limit * 4)
+        .bind(limit);
+
+    if !targets.is_empty() {
+        sql_query = sql_query
+            .bind(targets.iter().map(|t| t.database_name.clone()).collect::<Vec<_>>())
+            .bind(targets.iter().map(|t| t.schema_name.clone()).collect::<Vec<_>>())
+            .bind(targets.iter().map(|t| t.table_name.clone()).collect::<Vec<_>>())
+            .bind(targets.iter().map(|t| t.column_name.clone()).collect::<Vec<_>>());
+    }
+
+    sql_query.fetch_all(&mut *conn).await.map_err(|db_err| {
+        warn!(
+            %data_source_id,
+            %pg_schema_name,
+            error = %db_err,
+            "Failed to execute hybrid stored value search"
+        );
+        anyhow::Error::new(db_err).context(format!(
+            "Failed to execute hybrid stored value search in schema '{}'",
+            pg_schema_name
+        ))
+    })
+}
+
+/// Lists the stored values of a single column, alphabetically.
+///
+/// Used to fill value pickers, so matching is a plain case-insensitive substring match
+/// rather than an embedding search.
+///
+/// # Arguments
+///
+/// * `data_source_id` - UUID of the data source to construct the schema name.
+/// * `target` - The column to list values for.
+/// * `search` - Optional substring the values must contain.
+/// * `limit` - The maximum number of values.
+///
+/// # Returns
+///
+/// A `Result` containing the matching values.
+pub async fn list_column_values(
+    data_source_id: Uuid,
+    target: &SearchTarget,
+    search: Option<&str>,
+    limit: i64,
+) -> Result<Vec<String>> {
+    let schema_name = format!("ds_{}", data_source_id.to_string().replace('-', "_"));
+
+    let pattern = format!("%{}%", escape_like(search.unwrap_or("")));
+
+    let query_sql = format!(
+        r#"
+        SELECT DISTINCT value
+        FROM "{schema_name}"."searchable_column_values"
+        WHERE database_name = $1
+          AND schema_name = $2
+          AND table_name = $3
+          AND column_name = $4
+          AND value ILIKE $5
+        ORDER BY value
+        LIMIT $6
+        "#
+    );
+
+    let mut conn = get_sqlx_pool().acquire().await?;
+
+    sqlx::query_scalar::<_, String>(&query_sql)
+        .bind(&target.database_name)
+        .bind(&target.schema_name)
+        .bind(&target.table_name)
+        .bind(&target.column_name)
+        .bind(&pattern)
+        .bind(limit)
+        .fetch_all(&mut *conn)
+        .await
+        .with_context(|| {
+            format!(
+                "Failed to list stored values for {}.{}.{} in schema '{}'",
+                target.schema_name, target.table_name, target.column_name, schema_name
+            )
+        })
+}
 
 // Tests would need updating to handle embeddings and mocks for LiteLLM
 
@@ -384,3 +597,29 @@ pub fn extract_searchable_columns_from_yaml(yml_content: &str) -> Result<Vec<Sea
 
     Ok(search_targets)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_like() {
+        assert_eq!(escape_like("SKU_10%"), "SKU\\_10\\%");
+        assert_eq!(escape_like("a\\b"), "a\\\\b");
+        assert_eq!(escape_like("US"), "US");
+    }
+
+    #[test]
+    fn test_search_values_hybrid_sql() {
+        let lexical_only = search_values_hybrid_sql("ds_test", None, false);
+        assert!(lexical_only.contains(r#""ds_test"."searchable_column_values""#));
+        assert!(lexical_only.contains("value ILIKE $2 OR value % $1"));
+        assert!(lexical_only.contains("WHERE false"));
+        assert!(!lexical_only.contains("UNNEST"));
+
+        let hybrid = search_values_hybrid_sql("ds_test", Some(&[0.5, 1.0]), true);
+        assert!(hybrid.contains("embedding <=> '[0.5,1]'::halfvec"));
+        assert_eq!(hybrid.matches("UNNEST($6::text[]").count(), 2);
+        assert!(hybrid.contains("LIMIT $5"));
+    }
+}
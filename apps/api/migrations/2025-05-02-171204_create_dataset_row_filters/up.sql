@@ -0,0 +1,31 @@
+-- Your SQL goes here
+
+-- Per-dataset row-level filter templates, e.g. `region = {{user.region}}`.
+-- Templates are rendered with the querying user's attributes and applied to every query
+-- that reads the dataset.
+CREATE TABLE dataset_row_filters (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    dataset_id UUID NOT NULL,
+    filter_template TEXT NOT NULL,
+    description TEXT,
+    created_by UUID NOT NULL,
+    updated_by UUID NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    deleted_at TIMESTAMP WITH TIME ZONE,
+    CONSTRAINT fk_dataset
+        FOREIGN KEY (dataset_id)
+        REFERENCES datasets (id)
+        ON DELETE CASCADE,
+    CONSTRAINT fk_created_by
+        FOREIGN KEY (created_by)
+        REFERENCES users (id)
+        ON UPDATE CASCADE,
+    CONSTRAINT fk_updated_by
+        FOREIGN KEY (updated_by)
+        REFERENCES users (id)
+        ON UPDATE CASCADE
+);
+
+CREATE INDEX dataset_row_filters_dataset_id_idx ON dataset_row_filters (dataset_id)
+    WHERE deleted_at IS NULL;
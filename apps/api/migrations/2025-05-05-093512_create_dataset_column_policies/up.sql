@@ -0,0 +1,47 @@
+-- Your SQL goes here
+
+-- Column masking policies. Each policy masks one dataset column for the members of a
+-- permission group, or for users who have been granted a dataset group.
+CREATE TABLE dataset_column_policies (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    dataset_id UUID NOT NULL,
+    column_name TEXT NOT NULL,
+    policy TEXT NOT NULL,
+    visible_chars INTEGER,
+    permission_group_id UUID,
+    dataset_group_id UUID,
+    created_by UUID NOT NULL,
+    updated_by UUID NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    deleted_at TIMESTAMP WITH TIME ZONE,
+    CONSTRAINT fk_dataset
+        FOREIGN KEY (dataset_id)
+        REFERENCES datasets (id)
+        ON DELETE CASCADE,
+    CONSTRAINT fk_permission_group
+        FOREIGN KEY (permission_group_id)
+        REFERENCES permission_groups (id)
+        ON DELETE CASCADE,
+    CONSTRAINT fk_dataset_group
+        FOREIGN KEY (dataset_group_id)
+        REFERENCES dataset_groups (id)
+        ON DELETE CASCADE,
+    CONSTRAINT fk_created_by
+        FOREIGN KEY (created_by)
+        REFERENCES users (id)
+        ON UPDATE CASCADE,
+    CONSTRAINT fk_updated_by
+        FOREIGN KEY (updated_by)
+        REFERENCES users (id)
+        ON UPDATE CASCADE,
+    CONSTRAINT dataset_column_policies_policy_check
+        CHECK (policy IN ('hide', 'hash', 'redact', 'partial')),
+    CONSTRAINT dataset_column_policies_visible_chars_check
+        CHECK (policy <> 'partial' OR (visible_chars IS NOT NULL AND visible_chars > 0)),
+    CONSTRAINT dataset_column_policies_single_group_check
+        CHECK ((permission_group_id IS NULL) <> (dataset_group_id IS NULL))
+);
+
+CREATE INDEX dataset_column_policies_dataset_id_idx ON dataset_column_policies (dataset_id)
+    WHERE deleted_at IS NULL;
@@ -0,0 +1,30 @@
+-- Your SQL goes here
+
+-- Differences found between a data source's warehouse catalog and its datasets. A drift
+-- stays open until a later catalog sync no longer finds it.
+CREATE TABLE catalog_drift (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    data_source_id UUID NOT NULL,
+    dataset_id UUID,
+    kind TEXT NOT NULL,
+    schema_name TEXT NOT NULL,
+    table_name TEXT NOT NULL,
+    column_name TEXT,
+    expected TEXT,
+    actual TEXT,
+    detected_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    resolved_at TIMESTAMP WITH TIME ZONE,
+    CONSTRAINT fk_data_source
+        FOREIGN KEY (data_source_id)
+        REFERENCES data_sources (id)
+        ON DELETE CASCADE,
+    CONSTRAINT fk_dataset
+        FOREIGN KEY (dataset_id)
+        REFERENCES datasets (id)
+        ON DELETE CASCADE,
+    CONSTRAINT catalog_drift_kind_check
+        CHECK (kind IN ('new_table', 'dropped_table', 'new_column', 'dropped_column', 'type_changed', 'nullability_changed'))
+);
+
+CREATE INDEX catalog_drift_data_source_id_idx ON catalog_drift (data_source_id)
+    WHERE resolved_at IS NULL;
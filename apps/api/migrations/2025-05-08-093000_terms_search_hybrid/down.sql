@@ -0,0 +1,11 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE terms_search DROP COLUMN fts;
+ALTER TABLE terms_search ADD COLUMN fts tsvector
+    GENERATED ALWAYS AS (to_tsvector('simple', content)) STORED;
+CREATE INDEX ON terms_search USING gin(fts);
+
+ALTER TABLE terms_search DROP COLUMN embedding;
+ALTER TABLE terms_search ADD COLUMN embedding vector(1024);
+CREATE INDEX ON terms_search USING hnsw (embedding vector_cosine_ops);
+
+ALTER TABLE terms_search DROP CONSTRAINT terms_search_term_id_fkey;
@@ -0,0 +1,16 @@
+-- Term embeddings use the same 1536 dimension model as stored values
+DELETE FROM terms_search WHERE term_id NOT IN (SELECT id FROM terms);
+
+ALTER TABLE terms_search
+    ADD CONSTRAINT terms_search_term_id_fkey
+    FOREIGN KEY (term_id) REFERENCES terms(id) ON DELETE CASCADE;
+
+ALTER TABLE terms_search DROP COLUMN embedding;
+ALTER TABLE terms_search ADD COLUMN embedding halfvec(1536);
+CREATE INDEX terms_search_embedding_idx ON terms_search USING hnsw (embedding halfvec_cosine_ops);
+
+-- Index the definition alongside the name
+ALTER TABLE terms_search DROP COLUMN fts;
+ALTER TABLE terms_search ADD COLUMN fts tsvector
+    GENERATED ALWAYS AS (to_tsvector('simple', content || ' ' || definition)) STORED;
+CREATE INDEX terms_search_fts_idx ON terms_search USING gin(fts);
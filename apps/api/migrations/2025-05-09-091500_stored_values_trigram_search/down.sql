@@ -0,0 +1,16 @@
+-- Trigram indexes on stored values depend on the extension
+DO $$
+DECLARE
+    trgm_index record;
+BEGIN
+    FOR trgm_index IN
+        SELECT schemaname, indexname
+        FROM pg_indexes
+        WHERE schemaname LIKE 'ds\_%'
+          AND indexname LIKE 'idx\_value\_trgm\_%'
+    LOOP
+        EXECUTE format('DROP INDEX IF EXISTS %I.%I', trgm_index.schemaname, trgm_index.indexname);
+    END LOOP;
+END $$;
+
+DROP EXTENSION IF EXISTS pg_trgm;
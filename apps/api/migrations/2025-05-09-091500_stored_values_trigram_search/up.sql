@@ -0,0 +1,2 @@
+-- Trigram matching for the lexical half of hybrid stored value search
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
@@ -0,0 +1,9 @@
+DROP INDEX IF EXISTS stored_values_sync_jobs_next_run_at_idx;
+
+ALTER TABLE stored_values_sync_jobs
+    DROP COLUMN max_cardinality,
+    DROP COLUMN values_hash,
+    DROP COLUMN value_count,
+    DROP COLUMN next_run_at,
+    DROP COLUMN schedule,
+    DROP COLUMN dataset_column_id;
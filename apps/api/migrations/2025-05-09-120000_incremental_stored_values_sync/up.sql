@@ -0,0 +1,13 @@
+-- Per-column sync schedule and the snapshot used to detect changed values
+ALTER TABLE stored_values_sync_jobs
+    ADD COLUMN dataset_column_id uuid REFERENCES dataset_columns(id) ON DELETE SET NULL,
+    ADD COLUMN schedule text NOT NULL DEFAULT '0 0 3 * * *',
+    ADD COLUMN next_run_at timestamptz,
+    ADD COLUMN value_count bigint,
+    ADD COLUMN values_hash text,
+    ADD COLUMN max_cardinality bigint;
+
+-- Existing jobs run at the next scheduler tick and then follow their schedule
+UPDATE stored_values_sync_jobs SET next_run_at = now();
+
+CREATE INDEX stored_values_sync_jobs_next_run_at_idx ON stored_values_sync_jobs (next_run_at);
@@ -0,0 +1,15 @@
+-- Plaintext keys can't be recovered from their hashes, so keys created while the
+-- hashes were in place stop working
+DROP INDEX IF EXISTS api_keys_key_hash_idx;
+
+ALTER TABLE api_keys ADD COLUMN key text;
+UPDATE api_keys SET key = key_hash;
+ALTER TABLE api_keys ALTER COLUMN key SET NOT NULL;
+
+ALTER TABLE api_keys
+    DROP COLUMN last_used_at,
+    DROP COLUMN expires_at,
+    DROP COLUMN allowed_ips,
+    DROP COLUMN scopes,
+    DROP COLUMN key_prefix,
+    DROP COLUMN key_hash;
@@ -0,0 +1,22 @@
+-- Keys are stored as a hash and a short prefix to recognize them by, with scopes,
+-- an optional expiry and IP allow-list, and when they were last used
+ALTER TABLE api_keys
+    ADD COLUMN key_hash text,
+    ADD COLUMN key_prefix text,
+    ADD COLUMN scopes text[] NOT NULL DEFAULT '{}',
+    ADD COLUMN allowed_ips text[] NOT NULL DEFAULT '{}',
+    ADD COLUMN expires_at timestamptz,
+    ADD COLUMN last_used_at timestamptz;
+
+-- Existing keys keep the access they had
+UPDATE api_keys
+SET key_hash = encode(sha256(convert_to(key, 'UTF8')), 'hex'),
+    key_prefix = left(key, 12),
+    scopes = ARRAY['read', 'write', 'run_sql', 'deploy_datasets', 'manage_users'];
+
+ALTER TABLE api_keys
+    ALTER COLUMN key_hash SET NOT NULL,
+    ALTER COLUMN key_prefix SET NOT NULL,
+    DROP COLUMN key;
+
+CREATE UNIQUE INDEX api_keys_key_hash_idx ON api_keys (key_hash);
@@ -0,0 +1,46 @@
+-- Scheduled deliveries of a metric or dashboard by email, Slack or webhook
+CREATE TABLE subscriptions (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    organization_id uuid NOT NULL REFERENCES organizations(id) ON DELETE CASCADE,
+    -- Deliveries run with this user's access to the asset and its data
+    owner_id uuid NOT NULL REFERENCES users(id) ON UPDATE CASCADE ON DELETE CASCADE,
+    asset_id uuid NOT NULL,
+    asset_type asset_type_enum NOT NULL,
+    name text NOT NULL,
+    schedule text NOT NULL,
+    timezone text NOT NULL DEFAULT 'UTC',
+    channel text NOT NULL,
+    format text NOT NULL DEFAULT 'summary',
+    recipients text[] NOT NULL DEFAULT '{}',
+    target_url text,
+    signing_secret text,
+    enabled boolean NOT NULL DEFAULT true,
+    next_run_at timestamptz,
+    last_run_at timestamptz,
+    created_at timestamptz NOT NULL DEFAULT now(),
+    updated_at timestamptz NOT NULL DEFAULT now(),
+    deleted_at timestamptz
+);
+
+CREATE INDEX subscriptions_next_run_at_idx ON subscriptions (next_run_at)
+    WHERE enabled AND deleted_at IS NULL;
+CREATE INDEX subscriptions_asset_idx ON subscriptions (asset_id, asset_type);
+
+ALTER TABLE subscriptions ENABLE ROW LEVEL SECURITY;
+
+-- One row per attempt to deliver a subscription
+CREATE TABLE subscription_deliveries (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    subscription_id uuid NOT NULL REFERENCES subscriptions(id) ON DELETE CASCADE,
+    status text NOT NULL,
+    manual boolean NOT NULL DEFAULT false,
+    row_count bigint,
+    error_message text,
+    started_at timestamptz NOT NULL DEFAULT now(),
+    finished_at timestamptz
+);
+
+CREATE INDEX subscription_deliveries_subscription_id_idx
+    ON subscription_deliveries (subscription_id, started_at DESC);
+
+ALTER TABLE subscription_deliveries ENABLE ROW LEVEL SECURITY;
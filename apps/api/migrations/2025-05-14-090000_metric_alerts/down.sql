@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS metric_alert_events;
+DROP TABLE IF EXISTS metric_alerts;
@@ -0,0 +1,54 @@
+-- Rules that check a metric on a schedule and notify when a column crosses a threshold,
+-- changes sharply, or looks anomalous
+CREATE TABLE metric_alerts (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    organization_id uuid NOT NULL REFERENCES organizations(id) ON DELETE CASCADE,
+    -- Checks run with this user's access to the metric and its data
+    owner_id uuid NOT NULL REFERENCES users(id) ON UPDATE CASCADE ON DELETE CASCADE,
+    metric_file_id uuid NOT NULL REFERENCES metric_files(id) ON DELETE CASCADE,
+    name text NOT NULL,
+    column_name text NOT NULL,
+    time_column text,
+    condition text NOT NULL,
+    threshold double precision,
+    schedule text NOT NULL,
+    timezone text NOT NULL DEFAULT 'UTC',
+    channel text NOT NULL,
+    recipients text[] NOT NULL DEFAULT '{}',
+    target_url text,
+    signing_secret text,
+    notify_on_resolve boolean NOT NULL DEFAULT true,
+    enabled boolean NOT NULL DEFAULT true,
+    state text NOT NULL DEFAULT 'ok',
+    last_value double precision,
+    -- Why the last check ended in its state
+    last_message text,
+    last_checked_at timestamptz,
+    last_triggered_at timestamptz,
+    next_run_at timestamptz,
+    created_at timestamptz NOT NULL DEFAULT now(),
+    updated_at timestamptz NOT NULL DEFAULT now(),
+    deleted_at timestamptz
+);
+
+CREATE INDEX metric_alerts_next_run_at_idx ON metric_alerts (next_run_at)
+    WHERE enabled AND deleted_at IS NULL;
+CREATE INDEX metric_alerts_metric_file_id_idx ON metric_alerts (metric_file_id);
+
+ALTER TABLE metric_alerts ENABLE ROW LEVEL SECURITY;
+
+-- Changes in an alert's state, and whether they were notified
+CREATE TABLE metric_alert_events (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    alert_id uuid NOT NULL REFERENCES metric_alerts(id) ON DELETE CASCADE,
+    kind text NOT NULL,
+    value double precision,
+    message text NOT NULL,
+    notified boolean NOT NULL DEFAULT false,
+    notification_error text,
+    created_at timestamptz NOT NULL DEFAULT now()
+);
+
+CREATE INDEX metric_alert_events_alert_id_idx ON metric_alert_events (alert_id, created_at DESC);
+
+ALTER TABLE metric_alert_events ENABLE ROW LEVEL SECURITY;
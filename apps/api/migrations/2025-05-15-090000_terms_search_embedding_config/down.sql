@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS terms_search_embedding_config;
+
+DROP INDEX IF EXISTS terms_search_embedding_idx;
+ALTER TABLE terms_search ALTER COLUMN embedding TYPE halfvec(1536) USING NULL;
+CREATE INDEX terms_search_embedding_idx ON terms_search USING hnsw (embedding halfvec_cosine_ops);
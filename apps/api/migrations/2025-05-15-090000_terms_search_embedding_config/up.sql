@@ -0,0 +1,12 @@
+-- Records the embedder term vectors came from. The embedding column is resized to the
+-- configured embedder at startup, and terms are embedded again when it changes.
+CREATE TABLE terms_search_embedding_config (
+    id boolean PRIMARY KEY DEFAULT true CHECK (id),
+    embedding_model text NOT NULL,
+    dimensions integer NOT NULL,
+    updated_at timestamp with time zone NOT NULL DEFAULT now()
+);
+
+-- Existing term vectors came from the default remote model
+INSERT INTO terms_search_embedding_config (embedding_model, dimensions)
+VALUES ('remote:text-embedding-3-small:1536', 1536);
@@ -0,0 +1 @@
+ALTER TABLE stored_values_sync_jobs DROP COLUMN claimed_at;
@@ -0,0 +1,9 @@
+-- When a run claimed the job, kept apart from last_synced_at, which records the last
+-- successful sync
+ALTER TABLE stored_values_sync_jobs ADD COLUMN claimed_at timestamptz;
+
+-- Jobs running now were claimed at their last_synced_at
+UPDATE stored_values_sync_jobs SET claimed_at = last_synced_at WHERE status = 'in_progress';
+
+-- The fingerprint is now computed in the warehouse, so stored hashes no longer compare
+UPDATE stored_values_sync_jobs SET values_hash = NULL;
@@ -2,6 +2,7 @@ mod routes;
 pub mod utils;
 
 use std::env;
+use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -16,6 +17,7 @@ use middleware::{
 };
 use rustls::crypto::ring;
 use tokio::sync::broadcast;
+use tokio_cron_scheduler::{Job, JobScheduler};
 use tower::ServiceBuilder;
 use tower_http::{compression::CompressionLayer, trace::TraceLayer};
 use tracing::{error, info, warn};
@@ -56,6 +58,26 @@ async fn main() -> Result<(), anyhow::Error> {
         return Ok(());
     }
 
+    // Re-embed stored values and terms if the embedding provider or model changed since
+    // they were synced
+    tokio::spawn(async {
+        if let Err(e) = stored_values::ensure_all_search_schema_embeddings().await {
+            error!("Failed to migrate stored value embeddings: {}", e);
+        }
+        if let Err(e) = handlers::terms::ensure_term_search_embeddings().await {
+            error!("Failed to migrate term embeddings: {}", e);
+        }
+    });
+
+    // Kept alive for the lifetime of the server; scheduling failures only disable background jobs
+    let _scheduler = match start_background_scheduler().await {
+        Ok(scheduler) => Some(scheduler),
+        Err(e) => {
+            error!("Failed to start background job scheduler: {}", e);
+            None
+        }
+    };
+
     let protected_router = Router::new().nest("/api/v1", routes::protected_router());
     let public_router = Router::new().route("/health", axum::routing::get(|| async { "OK" }));
 
@@ -91,7 +113,11 @@ async fn main() -> Result<(), anyhow::Error> {
         }
     };
 
-    let server = axum::serve(listener, app);
+    // Peer addresses are needed to check API key IP allow-lists
+    let server = axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    );
 
     tokio::select! {
         res = server => {
@@ -107,3 +133,42 @@ async fn main() -> Result<(), anyhow::Error> {
 
     Ok(())
 }
+
+/// Checks every minute for stored values sync jobs, subscription deliveries and alert checks
+/// whose scheduled run is due.
+async fn start_background_scheduler() -> Result<JobScheduler, anyhow::Error> {
+    let scheduler = JobScheduler::new().await?;
+
+    scheduler
+        .add(Job::new_async("0 * * * * *", |_id, _scheduler| {
+            Box::pin(async {
+                if let Err(e) = stored_values::run_due_sync_jobs().await {
+                    error!("Failed to start due stored values sync jobs: {}", e);
+                }
+            })
+        })?)
+        .await?;
+
+    scheduler
+        .add(Job::new_async("0 * * * * *", |_id, _scheduler| {
+            Box::pin(async {
+                if let Err(e) = handlers::subscriptions::run_due_subscriptions().await {
+                    error!("Failed to start due subscription deliveries: {}", e);
+                }
+            })
+        })?)
+        .await?;
+
+    scheduler
+        .add(Job::new_async("0 * * * * *", |_id, _scheduler| {
+            Box::pin(async {
+                if let Err(e) = handlers::alerts::run_due_alerts().await {
+                    error!("Failed to start due alert checks: {}", e);
+                }
+            })
+        })?)
+        .await?;
+
+    scheduler.start().await?;
+    Ok(scheduler)
+}
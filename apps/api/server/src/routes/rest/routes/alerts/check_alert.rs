@@ -0,0 +1,29 @@
+use anyhow::Result;
+use axum::{extract::Path, http::StatusCode, Extension};
+use middleware::AuthenticatedUser;
+use uuid::Uuid;
+
+use crate::routes::rest::ApiResponse;
+use handlers::alerts::{check_alert_handler, AlertResponse};
+
+/// Checks an alert now. The check's outcome, including any error, is in the alert's
+/// `state` and `last_message`.
+pub async fn check_alert(
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+) -> Result<ApiResponse<AlertResponse>, (StatusCode, &'static str)> {
+    match check_alert_handler(&user, &id).await {
+        Ok(alert) => Ok(ApiResponse::JsonData(alert)),
+        Err(e) => {
+            tracing::error!("Error checking alert: {:?}", e);
+            let message = e.to_string();
+            if message.contains("not found") {
+                Err((StatusCode::NOT_FOUND, "Alert not found"))
+            } else if message.contains("permissions") {
+                Err((StatusCode::FORBIDDEN, "Insufficient permissions"))
+            } else {
+                Err((StatusCode::INTERNAL_SERVER_ERROR, "Failed to check alert"))
+            }
+        }
+    }
+}
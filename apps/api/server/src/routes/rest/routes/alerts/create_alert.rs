@@ -0,0 +1,34 @@
+use anyhow::Result;
+use axum::{http::StatusCode, Extension, Json};
+use middleware::AuthenticatedUser;
+
+use crate::routes::rest::ApiResponse;
+use handlers::alerts::{create_alert_handler, AlertResponse, CreateAlertRequest};
+
+pub async fn create_alert(
+    Extension(user): Extension<AuthenticatedUser>,
+    Json(payload): Json<CreateAlertRequest>,
+) -> Result<ApiResponse<AlertResponse>, (StatusCode, String)> {
+    match create_alert_handler(&user, payload).await {
+        Ok(alert) => Ok(ApiResponse::JsonData(alert)),
+        Err(e) => {
+            tracing::error!("Error creating alert: {:?}", e);
+            let message = e.to_string();
+            if message.contains("Invalid alert") {
+                Err((StatusCode::BAD_REQUEST, message))
+            } else if message.contains("permission") {
+                Err((
+                    StatusCode::FORBIDDEN,
+                    "Insufficient permissions".to_string(),
+                ))
+            } else if message.contains("not found") {
+                Err((StatusCode::NOT_FOUND, "Metric not found".to_string()))
+            } else {
+                Err((
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Failed to create alert".to_string(),
+                ))
+            }
+        }
+    }
+}
@@ -0,0 +1,27 @@
+use anyhow::Result;
+use axum::{extract::Path, http::StatusCode, Extension};
+use middleware::AuthenticatedUser;
+use uuid::Uuid;
+
+use crate::routes::rest::ApiResponse;
+use handlers::alerts::{get_alert_handler, AlertResponse};
+
+pub async fn get_alert(
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+) -> Result<ApiResponse<AlertResponse>, (StatusCode, &'static str)> {
+    match get_alert_handler(&user, &id).await {
+        Ok(alert) => Ok(ApiResponse::JsonData(alert)),
+        Err(e) => {
+            tracing::error!("Error getting alert: {:?}", e);
+            let message = e.to_string();
+            if message.contains("not found") {
+                Err((StatusCode::NOT_FOUND, "Alert not found"))
+            } else if message.contains("permissions") {
+                Err((StatusCode::FORBIDDEN, "Insufficient permissions"))
+            } else {
+                Err((StatusCode::INTERNAL_SERVER_ERROR, "Failed to get alert"))
+            }
+        }
+    }
+}
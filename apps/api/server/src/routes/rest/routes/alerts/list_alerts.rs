@@ -0,0 +1,26 @@
+use anyhow::Result;
+use axum::{extract::Query, http::StatusCode, Extension};
+use middleware::AuthenticatedUser;
+
+use crate::routes::rest::ApiResponse;
+use handlers::alerts::{list_alerts_handler, AlertResponse, ListAlertsRequest};
+
+pub async fn list_alerts(
+    Extension(user): Extension<AuthenticatedUser>,
+    Query(query): Query<ListAlertsRequest>,
+) -> Result<ApiResponse<Vec<AlertResponse>>, (StatusCode, &'static str)> {
+    match list_alerts_handler(&user, query).await {
+        Ok(alerts) => Ok(ApiResponse::JsonData(alerts)),
+        Err(e) => {
+            tracing::error!("Error listing alerts: {:?}", e);
+            if e.to_string().contains("not a member of any organization") {
+                Err((
+                    StatusCode::BAD_REQUEST,
+                    "User is not a member of any organization",
+                ))
+            } else {
+                Err((StatusCode::INTERNAL_SERVER_ERROR, "Failed to list alerts"))
+            }
+        }
+    }
+}
@@ -0,0 +1,21 @@
+mod check_alert;
+mod create_alert;
+mod delete_alert;
+mod get_alert;
+mod list_alerts;
+mod update_alert;
+
+use axum::{
+    routing::{delete, get, post, put},
+    Router,
+};
+
+pub fn router() -> Router {
+    Router::new()
+        .route("/", get(list_alerts::list_alerts))
+        .route("/", post(create_alert::create_alert))
+        .route("/:id", get(get_alert::get_alert))
+        .route("/:id", put(update_alert::update_alert))
+        .route("/:id", delete(delete_alert::delete_alert))
+        .route("/:id/check", post(check_alert::check_alert))
+}
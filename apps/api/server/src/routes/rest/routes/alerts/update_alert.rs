@@ -0,0 +1,36 @@
+use anyhow::Result;
+use axum::{extract::Path, http::StatusCode, Extension, Json};
+use middleware::AuthenticatedUser;
+use uuid::Uuid;
+
+use crate::routes::rest::ApiResponse;
+use handlers::alerts::{update_alert_handler, AlertResponse, UpdateAlertRequest};
+
+pub async fn update_alert(
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdateAlertRequest>,
+) -> Result<ApiResponse<AlertResponse>, (StatusCode, String)> {
+    match update_alert_handler(&user, &id, payload).await {
+        Ok(alert) => Ok(ApiResponse::JsonData(alert)),
+        Err(e) => {
+            tracing::error!("Error updating alert: {:?}", e);
+            let message = e.to_string();
+            if message.contains("Invalid alert") {
+                Err((StatusCode::BAD_REQUEST, message))
+            } else if message.contains("not found") {
+                Err((StatusCode::NOT_FOUND, "Alert not found".to_string()))
+            } else if message.contains("permission") {
+                Err((
+                    StatusCode::FORBIDDEN,
+                    "Insufficient permissions".to_string(),
+                ))
+            } else {
+                Err((
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Failed to update alert".to_string(),
+                ))
+            }
+        }
+    }
+}
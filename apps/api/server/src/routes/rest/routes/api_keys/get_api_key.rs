@@ -44,10 +44,5 @@ async fn get_api_key_handler(user: AuthenticatedUser, api_key_id: Uuid) -> Resul
         .await
         .map_err(|_| anyhow::anyhow!("API key not found"))?;
 
-    Ok(ApiKeyInfo {
-        id: api_key.id,
-        owner_id: api_key.owner_id,
-        owner_email: email,
-        created_at: api_key.created_at,
-    })
+    Ok(ApiKeyInfo::new(api_key, email))
 } 
\ No newline at end of file
@@ -20,6 +20,27 @@ pub struct ApiKeyInfo {
     pub owner_id: Uuid,
     pub owner_email: String,
     pub created_at: DateTime<Utc>,
+    pub key_prefix: String,
+    pub scopes: Vec<String>,
+    pub allowed_ips: Vec<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+impl ApiKeyInfo {
+    pub fn new(key: ApiKey, owner_email: String) -> Self {
+        Self {
+            id: key.id,
+            owner_id: key.owner_id,
+            owner_email,
+            created_at: key.created_at,
+            key_prefix: key.key_prefix,
+            scopes: key.scopes,
+            allowed_ips: key.allowed_ips,
+            expires_at: key.expires_at,
+            last_used_at: key.last_used_at,
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -55,11 +76,6 @@ async fn list_api_keys_handler(user: AuthenticatedUser) -> Result<Vec<ApiKeyInfo
 
     Ok(api_keys
         .into_iter()
-        .map(|(key, email)| ApiKeyInfo {
-            id: key.id,
-            owner_id: key.owner_id,
-            owner_email: email,
-            created_at: key.created_at,
-        })
+        .map(|(key, email)| ApiKeyInfo::new(key, email))
         .collect())
 } 
\ No newline at end of file
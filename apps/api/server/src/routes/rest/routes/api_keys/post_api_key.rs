@@ -1,13 +1,13 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use axum::body::Bytes;
 use axum::http::StatusCode;
 use axum::Extension;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use diesel::insert_into;
 use diesel_async::RunQueryDsl;
-use jsonwebtoken::{encode, EncodingKey, Header};
-use middleware::AuthenticatedUser;
+use middleware::api_keys::{api_key_prefix, generate_api_key, hash_api_key, parse_ip_range};
+use middleware::{ApiKeyAuth, ApiKeyScope, AuthenticatedUser};
 use serde::{Deserialize, Serialize};
-use std::env;
 use uuid::Uuid;
 
 use crate::routes::rest::ApiResponse;
@@ -16,78 +16,195 @@ use database::organization::get_user_organization_id;
 use database::pool::get_pg_pool;
 use database::schema::api_keys;
 
+/// Keys created without explicit scopes get what the CLI needs, but can't manage users
+const DEFAULT_SCOPES: [ApiKeyScope; 4] = [
+    ApiKeyScope::Read,
+    ApiKeyScope::Write,
+    ApiKeyScope::RunSql,
+    ApiKeyScope::DeployDatasets,
+];
+
+#[derive(Debug, Default, Deserialize)]
+pub struct PostApiKeyRequest {
+    pub scopes: Option<Vec<ApiKeyScope>>,
+    pub expires_at: Option<DateTime<Utc>>,
+    /// IP addresses and CIDR ranges the key can be used from
+    #[serde(default)]
+    pub allowed_ips: Vec<String>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct PostApiKeyResponse {
+    pub id: Uuid,
+    /// The key itself; it can't be retrieved again
     pub api_key: String,
-}
-
-// Add this struct for JWT claims
-#[derive(Debug, Serialize, Deserialize)]
-struct ApiKeyClaims {
-    exp: i64,
-    aud: String,
-    sub: String,
+    pub key_prefix: String,
+    pub scopes: Vec<ApiKeyScope>,
+    pub allowed_ips: Vec<String>,
+    pub expires_at: Option<DateTime<Utc>>,
 }
 
 pub async fn post_api_key(
     Extension(user): Extension<AuthenticatedUser>,
-) -> Result<ApiResponse<PostApiKeyResponse>, (StatusCode, &'static str)> {
-    let api_key = match post_api_key_handler(user).await {
-        Ok(api_key) => api_key,
+    caller_key: Option<Extension<ApiKeyAuth>>,
+    body: Bytes,
+) -> Result<ApiResponse<PostApiKeyResponse>, (StatusCode, String)> {
+    // The body is optional; keys created without one get the default scopes
+    let request = if body.is_empty() {
+        PostApiKeyRequest::default()
+    } else {
+        serde_json::from_slice::<PostApiKeyRequest>(&body).map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                format!("Invalid API key request: {}", e),
+            )
+        })?
+    };
+    let caller_key = caller_key.map(|Extension(key)| key);
+
+    match post_api_key_handler(user, caller_key.as_ref(), request).await {
+        Ok(response) => Ok(ApiResponse::JsonData(response)),
         Err(e) => {
             tracing::error!("Error creating API key: {:?}", e);
-            return Err((StatusCode::INTERNAL_SERVER_ERROR, "Error creating API key"));
+            let message = e.to_string();
+            if message.starts_with("Invalid API key request") {
+                Err((StatusCode::BAD_REQUEST, message))
+            } else if message.contains("can't grant") {
+                Err((StatusCode::FORBIDDEN, message))
+            } else {
+                Err((
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Error creating API key".to_string(),
+                ))
+            }
         }
-    };
-
-    Ok(ApiResponse::JsonData(PostApiKeyResponse { api_key }))
+    }
 }
 
-async fn post_api_key_handler(user: AuthenticatedUser) -> Result<String> {
-    let jwt_secret = env::var("JWT_SECRET").map_err(|_| anyhow::anyhow!("JWT_SECRET not set"))?;
+async fn post_api_key_handler(
+    user: AuthenticatedUser,
+    caller_key: Option<&ApiKeyAuth>,
+    request: PostApiKeyRequest,
+) -> Result<PostApiKeyResponse> {
+    let mut scopes = request.scopes.unwrap_or_else(|| DEFAULT_SCOPES.to_vec());
+    scopes.sort_by_key(|scope| ApiKeyScope::ALL.iter().position(|s| s == scope));
+    scopes.dedup();
+    if scopes.is_empty() {
+        return Err(anyhow!(
+            "Invalid API key request: at least one scope is required"
+        ));
+    }
 
-    // Create JWT claims
-    let claims = ApiKeyClaims {
-        exp: (Utc::now() + chrono::Duration::days(365 * 5)).timestamp(),
-        aud: "api".to_string(),
-        sub: user.id.to_string(),
-    };
+    // A key can only create keys with a subset of its own access
+    if let Some(caller_key) = caller_key {
+        if let Some(scope) = scopes.iter().find(|scope| !caller_key.allows(**scope)) {
+            return Err(anyhow!(
+                "This API key can't grant the '{}' scope it doesn't have",
+                scope.as_str()
+            ));
+        }
+        if let Some(caller_expires_at) = caller_key.expires_at {
+            if request
+                .expires_at
+                .map_or(true, |expires_at| expires_at > caller_expires_at)
+            {
+                return Err(anyhow!(
+                    "This API key can't grant access beyond its own expiry at {}",
+                    caller_expires_at
+                ));
+            }
+        }
+    }
+
+    if request
+        .expires_at
+        .is_some_and(|expires_at| expires_at <= Utc::now())
+    {
+        return Err(anyhow!(
+            "Invalid API key request: expires_at must be in the future"
+        ));
+    }
 
-    // Generate JWT token
-    let api_key = encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(jwt_secret.as_bytes()),
-    )
-    .map_err(|e| anyhow::anyhow!("Failed to create JWT: {}", e))?;
+    let allowed_ips = request
+        .allowed_ips
+        .iter()
+        .map(|range| {
+            parse_ip_range(range)
+                .map(|_| range.trim().to_string())
+                .ok_or_else(|| {
+                    anyhow!(
+                        "Invalid API key request: '{}' is not an IP address or CIDR range",
+                        range
+                    )
+                })
+        })
+        .collect::<Result<Vec<_>>>()?;
 
-    let mut conn = match get_pg_pool().get().await {
-        Ok(conn) => conn,
-        Err(e) => {
-            tracing::error!("Error getting database connection: {:?}", e);
-            return Err(anyhow::anyhow!("Error getting database connection"));
+    // A key can't mint one that works from addresses it can't be used from itself
+    let allowed_ips = match caller_key {
+        Some(caller_key) if !caller_key.allowed_ips.is_empty() => {
+            if allowed_ips.is_empty() {
+                caller_key.allowed_ips.clone()
+            } else {
+                if let Some(range) = allowed_ips
+                    .iter()
+                    .find(|range| !caller_key.allows_ip_range(range))
+                {
+                    return Err(anyhow!(
+                        "This API key can't grant access from '{}' outside its own IP allow-list",
+                        range
+                    ));
+                }
+                allowed_ips
+            }
         }
+        _ => allowed_ips,
     };
 
     let organization_id = match get_user_organization_id(&user.id).await {
         Ok(Some(organization_id)) => organization_id,
         Ok(None) => {
-            return Err(anyhow::anyhow!("User does not belong to any organization"));
+            return Err(anyhow!("User does not belong to any organization"));
         }
         Err(e) => {
             tracing::error!("Error getting organization ID: {:?}", e);
-            return Err(anyhow::anyhow!("Error getting organization ID"));
+            return Err(anyhow!("Error getting organization ID"));
         }
     };
 
+    let api_key = generate_api_key();
     let api_key_record = ApiKey {
         id: Uuid::new_v4(),
         owner_id: user.id,
-        key: api_key.clone(),
         organization_id,
         created_at: Utc::now(),
         updated_at: Utc::now(),
         deleted_at: None,
+        key_hash: hash_api_key(&api_key),
+        key_prefix: api_key_prefix(&api_key),
+        scopes: scopes
+            .iter()
+            .map(|scope| scope.as_str().to_string())
+            .collect(),
+        allowed_ips: allowed_ips.clone(),
+        expires_at: request.expires_at,
+        last_used_at: None,
+    };
+    let response = PostApiKeyResponse {
+        id: api_key_record.id,
+        key_prefix: api_key_record.key_prefix.clone(),
+        api_key,
+        scopes,
+        allowed_ips,
+        expires_at: request.expires_at,
+    };
+
+    let mut conn = match get_pg_pool().get().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            tracing::error!("Error getting database connection: {:?}", e);
+            return Err(anyhow!("Error getting database connection"));
+        }
     };
 
     match insert_into(api_keys::table)
@@ -98,9 +215,9 @@ async fn post_api_key_handler(user: AuthenticatedUser) -> Result<String> {
         Ok(_) => (),
         Err(e) => {
             tracing::error!("Error inserting API key: {:?}", e);
-            return Err(anyhow::anyhow!("Error inserting API key"));
+            return Err(anyhow!("Error inserting API key"));
         }
     };
 
-    Ok(api_key)
+    Ok(response)
 }
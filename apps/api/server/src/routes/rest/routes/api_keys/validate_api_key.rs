@@ -1,8 +1,10 @@
 use anyhow::Result;
 use axum::http::StatusCode;
 use axum::Json;
+use chrono::{DateTime, Utc};
 use diesel::{ExpressionMethods, QueryDsl};
 use diesel_async::RunQueryDsl;
+use middleware::api_keys::hash_api_key;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -44,13 +46,13 @@ async fn validate_api_key_handler(api_key: String) -> Result<bool> {
     };
 
     let api_key_exists = match api_keys::table
-        .filter(api_keys::key.eq(api_key))
+        .filter(api_keys::key_hash.eq(hash_api_key(&api_key)))
         .filter(api_keys::deleted_at.is_null())
-        .select(api_keys::id)
-        .first::<Uuid>(&mut *conn)
+        .select((api_keys::id, api_keys::expires_at))
+        .first::<(Uuid, Option<DateTime<Utc>>)>(&mut *conn)
         .await
     {
-        Ok(_) => true,
+        Ok((_, expires_at)) => expires_at.map_or(true, |expires_at| expires_at > Utc::now()),
         Err(diesel::NotFound) => false,
         Err(e) => {
             tracing::error!("Error getting API key: {:?}", e);
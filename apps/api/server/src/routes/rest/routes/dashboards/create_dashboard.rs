@@ -2,25 +2,36 @@ use axum::{
     http::StatusCode,
     Extension, Json,
 };
-use handlers::dashboards::{create_dashboard_handler, BusterDashboardResponse};
+use handlers::dashboards::{
+    create_dashboard_handler, BusterDashboardResponse, CreateDashboardRequest,
+};
 use middleware::AuthenticatedUser;
 
 
 /// Create a new dashboard
 ///
-/// This endpoint creates a new dashboard for the authenticated user.
+/// This endpoint creates a new dashboard for the authenticated user. The body is optional and
+/// may set the new dashboard's id.
 pub async fn create_dashboard_rest_handler(
     Extension(user): Extension<AuthenticatedUser>,
+    request: Option<Json<CreateDashboardRequest>>,
 ) -> Result<Json<BusterDashboardResponse>, (StatusCode, String)> {
+    let request = request.map(|Json(request)| request).unwrap_or_default();
+
     // Call the handler
-    match create_dashboard_handler(&user).await {
+    match create_dashboard_handler(&user, request).await {
         Ok(response) => Ok(Json(response)),
         Err(e) => {
             tracing::error!("Failed to create dashboard: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Error creating dashboard: {}", e),
-            ))
+            let message = e.to_string();
+            if message.starts_with("Invalid dashboard") {
+                Err((StatusCode::BAD_REQUEST, message))
+            } else {
+                Err((
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Error creating dashboard: {}", e),
+                ))
+            }
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,65 @@
+use crate::routes::rest::ApiResponse;
+use axum::extract::{Path, Query};
+use axum::http::StatusCode;
+use axum::Extension;
+use handlers::dashboards::{get_dashboard_filter_values_handler, DashboardFilterValuesResponse};
+use middleware::AuthenticatedUser;
+use serde::Deserialize;
+use uuid::Uuid;
+
+#[derive(Debug, Deserialize)]
+pub struct GetDashboardFilterValuesParams {
+    /// Only values containing this text, case-insensitively
+    pub search: Option<String>,
+    pub limit: Option<i64>,
+    /// Optional password for accessing public password-protected dashboards
+    pub password: Option<String>,
+}
+
+pub async fn get_dashboard_filter_values_rest_handler(
+    Extension(user): Extension<AuthenticatedUser>,
+    Path((id, filter_id)): Path<(Uuid, String)>,
+    Query(params): Query<GetDashboardFilterValuesParams>,
+) -> Result<ApiResponse<DashboardFilterValuesResponse>, (StatusCode, &'static str)> {
+    tracing::info!(
+        "Processing GET request for values of filter {} on dashboard {}, user_id: {}",
+        filter_id,
+        id,
+        user.id
+    );
+
+    match get_dashboard_filter_values_handler(
+        &id,
+        &filter_id,
+        params.search,
+        params.limit,
+        &user,
+        params.password,
+    )
+    .await
+    {
+        Ok(response) => Ok(ApiResponse::JsonData(response)),
+        Err(e) => {
+            tracing::error!("Error getting dashboard filter values: {}", e);
+            let error_message = e.to_string();
+
+            if error_message.contains("public_password required") {
+                return Err((StatusCode::IM_A_TEAPOT, "Password required for public access"));
+            }
+            if error_message.contains("don't have permission") {
+                return Err((StatusCode::FORBIDDEN, "Permission denied"));
+            }
+            if error_message.contains("Filter") && error_message.contains("not found") {
+                return Err((StatusCode::NOT_FOUND, "Filter not found"));
+            }
+            if error_message.contains("not a select filter") {
+                return Err((StatusCode::BAD_REQUEST, "Filter has no value list"));
+            }
+            if error_message.contains("not found") {
+                return Err((StatusCode::NOT_FOUND, "Dashboard not found"));
+            }
+
+            Err((StatusCode::INTERNAL_SERVER_ERROR, "Failed to get filter values"))
+        }
+    }
+}
@@ -8,6 +8,7 @@ use axum::{
 mod create_dashboard;
 mod delete_dashboard;
 mod get_dashboard;
+mod get_dashboard_filter_values;
 mod list_dashboards;
 mod sharing;
 mod update_dashboard;
@@ -17,6 +18,10 @@ pub fn router() -> Router {
         .route("/", post(create_dashboard::create_dashboard_rest_handler))
         .route("/:id", get(get_dashboard::get_dashboard_rest_handler))
         .route("/:id", put(update_dashboard::update_dashboard_rest_handler))
+        .route(
+            "/:id/filters/:filter_id/values",
+            get(get_dashboard_filter_values::get_dashboard_filter_values_rest_handler),
+        )
         .route(
             "/",
             delete(delete_dashboard::delete_dashboards_rest_handler),
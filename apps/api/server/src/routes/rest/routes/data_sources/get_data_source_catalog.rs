@@ -0,0 +1,61 @@
+use anyhow::Result;
+use axum::{
+    extract::{Path, Query},
+    http::StatusCode,
+    Extension,
+};
+use middleware::AuthenticatedUser;
+use query_engine::introspection::Catalog;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::routes::rest::ApiResponse;
+use handlers::data_sources::get_data_source_catalog_handler;
+
+#[derive(Debug, Deserialize)]
+pub struct GetDataSourceCatalogQuery {
+    /// Comma-separated schemas to read
+    pub schemas: Option<String>,
+}
+
+pub async fn get_data_source_catalog(
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<GetDataSourceCatalogQuery>,
+) -> Result<ApiResponse<Catalog>, (StatusCode, &'static str)> {
+    let schemas: Vec<String> = query
+        .schemas
+        .unwrap_or_default()
+        .split(',')
+        .map(|schema| schema.trim().to_string())
+        .filter(|schema| !schema.is_empty())
+        .collect();
+
+    match get_data_source_catalog_handler(&user, &id, &schemas).await {
+        Ok(catalog) => Ok(ApiResponse::JsonData(catalog)),
+        Err(e) => {
+            tracing::error!("Error reading data source catalog: {:?}", e);
+            let message = e.to_string();
+            if message.contains("not found") {
+                Err((StatusCode::NOT_FOUND, "Data source not found"))
+            } else if message.contains("permissions") {
+                Err((
+                    StatusCode::FORBIDDEN,
+                    "Not authorized to read this data source's catalog",
+                ))
+            } else if message.contains("not supported") {
+                Err((
+                    StatusCode::BAD_REQUEST,
+                    "Catalog introspection is not supported for this data source",
+                ))
+            } else if message.contains("Invalid schema") {
+                Err((StatusCode::BAD_REQUEST, "Invalid schema name"))
+            } else {
+                Err((
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Failed to read data source catalog",
+                ))
+            }
+        }
+    }
+}
@@ -3,6 +3,8 @@ mod get_data_source;
 mod update_data_source;
 mod create_data_source;
 mod delete_data_source;
+mod get_data_source_catalog;
+mod sync_data_source_catalog;
 
 use axum::{
     routing::{get, post, put, delete},
@@ -16,4 +18,12 @@ pub fn router() -> Router {
         .route("/:id", get(get_data_source::get_data_source))
         .route("/:id", put(update_data_source::update_data_source))
         .route("/:id", delete(delete_data_source::delete_data_source))
+        .route(
+            "/:id/catalog",
+            get(get_data_source_catalog::get_data_source_catalog),
+        )
+        .route(
+            "/:id/catalog/sync",
+            post(sync_data_source_catalog::sync_data_source_catalog),
+        )
 }
\ No newline at end of file
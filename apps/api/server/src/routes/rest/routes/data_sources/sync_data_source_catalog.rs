@@ -0,0 +1,48 @@
+use anyhow::Result;
+use axum::{extract::Path, http::StatusCode, Extension, Json};
+use middleware::AuthenticatedUser;
+use uuid::Uuid;
+
+use crate::routes::rest::ApiResponse;
+use handlers::data_sources::{
+    sync_data_source_catalog_handler, SyncDataSourceCatalogRequest, SyncDataSourceCatalogResponse,
+};
+
+pub async fn sync_data_source_catalog(
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<SyncDataSourceCatalogRequest>,
+) -> Result<ApiResponse<SyncDataSourceCatalogResponse>, (StatusCode, &'static str)> {
+    match sync_data_source_catalog_handler(&user, &id, payload).await {
+        Ok(response) => Ok(ApiResponse::JsonData(response)),
+        Err(e) => {
+            tracing::error!("Error syncing data source catalog: {:?}", e);
+            let message = e.to_string();
+            if message.contains("not found") {
+                Err((StatusCode::NOT_FOUND, "Data source not found"))
+            } else if message.contains("permissions") {
+                Err((
+                    StatusCode::FORBIDDEN,
+                    "Not authorized to sync this data source's catalog",
+                ))
+            } else if message.contains("not supported") {
+                Err((
+                    StatusCode::BAD_REQUEST,
+                    "Catalog introspection is not supported for this data source",
+                ))
+            } else if message.contains("Invalid schema") {
+                Err((StatusCode::BAD_REQUEST, "Invalid schema name"))
+            } else if message.contains("limit the sync") {
+                Err((
+                    StatusCode::BAD_REQUEST,
+                    "The catalog is too large; limit the sync to fewer schemas",
+                ))
+            } else {
+                Err((
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Failed to sync data source catalog",
+                ))
+            }
+        }
+    }
+}
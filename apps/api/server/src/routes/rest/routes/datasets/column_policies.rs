@@ -0,0 +1,207 @@
+use anyhow::{anyhow, Result};
+use axum::http::StatusCode;
+use axum::{extract::Path, Extension, Json};
+use chrono::{DateTime, Utc};
+use diesel::{insert_into, prelude::*, update};
+use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection, RunQueryDsl};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use middleware::AuthenticatedUser;
+
+use database::{
+    enums::ColumnMaskPolicy,
+    models::DatasetColumnPolicy,
+    pool::get_pg_pool,
+    schema::{dataset_column_policies, dataset_columns},
+};
+use crate::routes::rest::ApiResponse;
+
+use super::row_filters::check_dataset_admin;
+
+#[derive(Debug, Serialize)]
+pub struct ColumnPolicyResponse {
+    pub id: Uuid,
+    pub column_name: String,
+    pub policy: ColumnMaskPolicy,
+    pub visible_chars: Option<i32>,
+    pub permission_group_id: Option<Uuid>,
+    pub dataset_group_id: Option<Uuid>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ColumnPolicyRequest {
+    pub column_name: String,
+    pub policy: ColumnMaskPolicy,
+    pub visible_chars: Option<i32>,
+    pub permission_group_id: Option<Uuid>,
+    pub dataset_group_id: Option<Uuid>,
+}
+
+impl ColumnPolicyRequest {
+    fn validate(&self) -> Result<()> {
+        if self.permission_group_id.is_some() == self.dataset_group_id.is_some() {
+            return Err(anyhow!(
+                "Policy on '{}' must target exactly one of permission_group_id or dataset_group_id",
+                self.column_name
+            ));
+        }
+
+        if self.policy == ColumnMaskPolicy::Partial && !self.visible_chars.is_some_and(|n| n > 0) {
+            return Err(anyhow!(
+                "Partial policy on '{}' requires a positive visible_chars",
+                self.column_name
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+pub async fn list_column_policies(
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(dataset_id): Path<Uuid>,
+) -> Result<ApiResponse<Vec<ColumnPolicyResponse>>, (StatusCode, &'static str)> {
+    match list_column_policies_handler(&user, &dataset_id).await {
+        Ok(policies) => Ok(ApiResponse::JsonData(policies)),
+        Err(e) => {
+            tracing::error!("Error listing dataset column policies: {:?}", e);
+            Err((StatusCode::INTERNAL_SERVER_ERROR, "Error listing column policies"))
+        }
+    }
+}
+
+/// Replaces the full set of column masking policies on a dataset.
+pub async fn put_column_policies(
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(dataset_id): Path<Uuid>,
+    Json(policies): Json<Vec<ColumnPolicyRequest>>,
+) -> Result<ApiResponse<Vec<ColumnPolicyResponse>>, (StatusCode, String)> {
+    for policy in &policies {
+        if let Err(e) = policy.validate() {
+            return Err((StatusCode::BAD_REQUEST, e.to_string()));
+        }
+    }
+
+    match put_column_policies_handler(&user, &dataset_id, policies).await {
+        Ok(policies) => Ok(ApiResponse::JsonData(policies)),
+        Err(e) => {
+            tracing::error!("Error updating dataset column policies: {:?}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Error updating column policies".to_string(),
+            ))
+        }
+    }
+}
+
+async fn list_column_policies_handler(
+    user: &AuthenticatedUser,
+    dataset_id: &Uuid,
+) -> Result<Vec<ColumnPolicyResponse>> {
+    check_dataset_admin(user, dataset_id).await?;
+
+    let mut conn = get_pg_pool().get().await?;
+
+    let policies = dataset_column_policies::table
+        .filter(dataset_column_policies::dataset_id.eq(dataset_id))
+        .filter(dataset_column_policies::deleted_at.is_null())
+        .order(dataset_column_policies::column_name.asc())
+        .select(dataset_column_policies::all_columns)
+        .load::<DatasetColumnPolicy>(&mut conn)
+        .await?;
+
+    Ok(policies.into_iter().map(ColumnPolicyResponse::from).collect())
+}
+
+async fn put_column_policies_handler(
+    user: &AuthenticatedUser,
+    dataset_id: &Uuid,
+    policies: Vec<ColumnPolicyRequest>,
+) -> Result<Vec<ColumnPolicyResponse>> {
+    check_dataset_admin(user, dataset_id).await?;
+
+    let mut conn = get_pg_pool().get().await?;
+
+    let known_columns = dataset_columns::table
+        .filter(dataset_columns::dataset_id.eq(dataset_id))
+        .filter(dataset_columns::deleted_at.is_null())
+        .select(dataset_columns::name)
+        .load::<String>(&mut conn)
+        .await?;
+
+    for policy in &policies {
+        if !known_columns
+            .iter()
+            .any(|column| column.eq_ignore_ascii_case(&policy.column_name))
+        {
+            return Err(anyhow!(
+                "Column '{}' does not exist on this dataset",
+                policy.column_name
+            ));
+        }
+    }
+
+    let now = Utc::now();
+
+    let new_policies: Vec<DatasetColumnPolicy> = policies
+        .into_iter()
+        .map(|policy| DatasetColumnPolicy {
+            id: Uuid::new_v4(),
+            dataset_id: *dataset_id,
+            column_name: policy.column_name,
+            policy: policy.policy,
+            visible_chars: policy.visible_chars,
+            permission_group_id: policy.permission_group_id,
+            dataset_group_id: policy.dataset_group_id,
+            created_by: user.id,
+            updated_by: user.id,
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+        })
+        .collect();
+
+    // Replacing in one transaction keeps a failed insert from leaving the dataset unmasked
+    let replacements = &new_policies;
+    conn.transaction::<_, anyhow::Error, _>(|conn| {
+        async move {
+            update(dataset_column_policies::table)
+                .filter(dataset_column_policies::dataset_id.eq(dataset_id))
+                .filter(dataset_column_policies::deleted_at.is_null())
+                .set(dataset_column_policies::deleted_at.eq(Some(now)))
+                .execute(conn)
+                .await?;
+
+            if !replacements.is_empty() {
+                insert_into(dataset_column_policies::table)
+                    .values(replacements)
+                    .execute(conn)
+                    .await?;
+            }
+
+            Ok(())
+        }
+        .scope_boxed()
+    })
+    .await?;
+
+    Ok(new_policies
+        .into_iter()
+        .map(ColumnPolicyResponse::from)
+        .collect())
+}
+
+impl From<DatasetColumnPolicy> for ColumnPolicyResponse {
+    fn from(policy: DatasetColumnPolicy) -> Self {
+        ColumnPolicyResponse {
+            id: policy.id,
+            column_name: policy.column_name,
+            policy: policy.policy,
+            visible_chars: policy.visible_chars,
+            permission_group_id: policy.permission_group_id,
+            dataset_group_id: policy.dataset_group_id,
+            updated_at: policy.updated_at,
+        }
+    }
+}
@@ -1,4 +1,5 @@
 mod assets;
+mod column_policies;
 mod delete_dataset;
 mod deploy_datasets;
 // mod generate_datasets;
@@ -6,6 +7,7 @@ mod get_dataset;
 mod get_dataset_data_sample;
 mod list_datasets;
 mod post_dataset;
+mod row_filters;
 
 use axum::{
     routing::{get, post, delete},
@@ -24,5 +26,13 @@ pub fn router() -> Router {
             "/:dataset_id/data/sample",
             get(get_dataset_data_sample::get_dataset_data_sample),
         )
+        .route(
+            "/:dataset_id/row_filters",
+            get(row_filters::list_row_filters).put(row_filters::put_row_filters),
+        )
+        .route(
+            "/:dataset_id/column_policies",
+            get(column_policies::list_column_policies).put(column_policies::put_column_policies),
+        )
         .nest("/:dataset_id", assets::router())
 }
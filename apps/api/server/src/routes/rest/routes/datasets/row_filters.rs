@@ -0,0 +1,188 @@
+use anyhow::{anyhow, Result};
+use axum::http::StatusCode;
+use axum::{extract::Path, Extension, Json};
+use chrono::{DateTime, Utc};
+use diesel::{insert_into, prelude::*, update};
+use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection, RunQueryDsl};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use middleware::AuthenticatedUser;
+
+use database::{
+    models::DatasetRowFilter,
+    pool::get_pg_pool,
+    schema::{dataset_row_filters, datasets},
+};
+use dataset_security::row_level_security::validate_filter_template;
+use crate::routes::rest::ApiResponse;
+use crate::utils::security::checks::is_user_workspace_admin_or_data_admin;
+use database::organization::get_user_organization_id;
+
+#[derive(Debug, Serialize)]
+pub struct RowFilterResponse {
+    pub id: Uuid,
+    pub filter_template: String,
+    pub description: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RowFilterRequest {
+    pub filter_template: String,
+    pub description: Option<String>,
+}
+
+pub async fn list_row_filters(
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(dataset_id): Path<Uuid>,
+) -> Result<ApiResponse<Vec<RowFilterResponse>>, (StatusCode, &'static str)> {
+    match list_row_filters_handler(&user, &dataset_id).await {
+        Ok(filters) => Ok(ApiResponse::JsonData(filters)),
+        Err(e) => {
+            tracing::error!("Error listing dataset row filters: {:?}", e);
+            Err((StatusCode::INTERNAL_SERVER_ERROR, "Error listing row filters"))
+        }
+    }
+}
+
+/// Replaces the full set of row-level filter templates on a dataset.
+pub async fn put_row_filters(
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(dataset_id): Path<Uuid>,
+    Json(filters): Json<Vec<RowFilterRequest>>,
+) -> Result<ApiResponse<Vec<RowFilterResponse>>, (StatusCode, String)> {
+    // Reject malformed templates before touching the database
+    for filter in &filters {
+        if let Err(e) = validate_filter_template(&filter.filter_template) {
+            return Err((StatusCode::BAD_REQUEST, e.to_string()));
+        }
+    }
+
+    match put_row_filters_handler(&user, &dataset_id, filters).await {
+        Ok(filters) => Ok(ApiResponse::JsonData(filters)),
+        Err(e) => {
+            tracing::error!("Error updating dataset row filters: {:?}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Error updating row filters".to_string(),
+            ))
+        }
+    }
+}
+
+pub(super) async fn check_dataset_admin(user: &AuthenticatedUser, dataset_id: &Uuid) -> Result<()> {
+    let organization_id = match get_user_organization_id(&user.id).await? {
+        Some(organization_id) => organization_id,
+        None => return Err(anyhow!("User does not belong to any organization")),
+    };
+
+    match is_user_workspace_admin_or_data_admin(user, &organization_id).await {
+        Ok(true) => (),
+        Ok(false) => anyhow::bail!("Insufficient permissions"),
+        Err(e) => {
+            tracing::error!("Error checking user permissions: {:?}", e);
+            anyhow::bail!("Error checking user permissions");
+        }
+    }
+
+    let mut conn = get_pg_pool().get().await?;
+
+    let dataset_organization_id = datasets::table
+        .select(datasets::organization_id)
+        .filter(datasets::id.eq(dataset_id))
+        .filter(datasets::deleted_at.is_null())
+        .first::<Uuid>(&mut conn)
+        .await
+        .map_err(|e| match e {
+            diesel::result::Error::NotFound => anyhow!("Dataset not found"),
+            _ => anyhow!("Error getting dataset: {}", e),
+        })?;
+
+    if dataset_organization_id != organization_id {
+        return Err(anyhow!("User does not belong to dataset's organization"));
+    }
+
+    Ok(())
+}
+
+async fn list_row_filters_handler(
+    user: &AuthenticatedUser,
+    dataset_id: &Uuid,
+) -> Result<Vec<RowFilterResponse>> {
+    check_dataset_admin(user, dataset_id).await?;
+
+    let mut conn = get_pg_pool().get().await?;
+
+    let filters = dataset_row_filters::table
+        .filter(dataset_row_filters::dataset_id.eq(dataset_id))
+        .filter(dataset_row_filters::deleted_at.is_null())
+        .order(dataset_row_filters::created_at.asc())
+        .select(dataset_row_filters::all_columns)
+        .load::<DatasetRowFilter>(&mut conn)
+        .await?;
+
+    Ok(filters.into_iter().map(RowFilterResponse::from).collect())
+}
+
+async fn put_row_filters_handler(
+    user: &AuthenticatedUser,
+    dataset_id: &Uuid,
+    filters: Vec<RowFilterRequest>,
+) -> Result<Vec<RowFilterResponse>> {
+    check_dataset_admin(user, dataset_id).await?;
+
+    let mut conn = get_pg_pool().get().await?;
+    let now = Utc::now();
+
+    let new_filters: Vec<DatasetRowFilter> = filters
+        .into_iter()
+        .map(|filter| DatasetRowFilter {
+            id: Uuid::new_v4(),
+            dataset_id: *dataset_id,
+            filter_template: filter.filter_template,
+            description: filter.description,
+            created_by: user.id,
+            updated_by: user.id,
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+        })
+        .collect();
+
+    // Replacing in one transaction keeps a failed insert from leaving the dataset unfiltered
+    let replacements = &new_filters;
+    conn.transaction::<_, anyhow::Error, _>(|conn| {
+        async move {
+            update(dataset_row_filters::table)
+                .filter(dataset_row_filters::dataset_id.eq(dataset_id))
+                .filter(dataset_row_filters::deleted_at.is_null())
+                .set(dataset_row_filters::deleted_at.eq(Some(now)))
+                .execute(conn)
+                .await?;
+
+            if !replacements.is_empty() {
+                insert_into(dataset_row_filters::table)
+                    .values(replacements)
+                    .execute(conn)
+                    .await?;
+            }
+
+            Ok(())
+        }
+        .scope_boxed()
+    })
+    .await?;
+
+    Ok(new_filters.into_iter().map(RowFilterResponse::from).collect())
+}
+
+impl From<DatasetRowFilter> for RowFilterResponse {
+    fn from(filter: DatasetRowFilter) -> Self {
+        RowFilterResponse {
+            id: filter.id,
+            filter_template: filter.filter_template,
+            description: filter.description,
+            updated_at: filter.updated_at,
+        }
+    }
+}
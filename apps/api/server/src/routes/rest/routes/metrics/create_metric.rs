@@ -0,0 +1,30 @@
+use axum::{http::StatusCode, Extension, Json};
+use handlers::metrics::{create_metric_handler, BusterMetric, CreateMetricRequest};
+use middleware::AuthenticatedUser;
+
+use crate::routes::rest::ApiResponse;
+
+pub async fn create_metric_rest_handler(
+    Extension(user): Extension<AuthenticatedUser>,
+    Json(request): Json<CreateMetricRequest>,
+) -> Result<ApiResponse<BusterMetric>, (StatusCode, String)> {
+    tracing::info!("Processing POST request for metric, user_id: {}", user.id);
+
+    match create_metric_handler(&user, request).await {
+        Ok(metric) => Ok(ApiResponse::JsonData(metric)),
+        Err(e) => {
+            tracing::error!("Error creating metric: {}", e);
+            let message = e.to_string();
+            if message.starts_with("Invalid metric") {
+                Err((StatusCode::BAD_REQUEST, message))
+            } else if message.contains("not found") {
+                Err((StatusCode::NOT_FOUND, "Data source not found".to_string()))
+            } else {
+                Err((
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Failed to create metric".to_string(),
+                ))
+            }
+        }
+    }
+}
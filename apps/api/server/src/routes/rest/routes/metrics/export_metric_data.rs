@@ -0,0 +1,85 @@
+use axum::body::Body;
+use axum::extract::{Path, Query};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Extension;
+use handlers::metrics::{ExportMetricDataRequest, MetricExportFormat};
+use middleware::AuthenticatedUser;
+use serde::Deserialize;
+use uuid::Uuid;
+
+#[derive(Deserialize)]
+pub struct ExportMetricDataParams {
+    pub format: MetricExportFormat,
+    pub version_number: Option<i32>,
+    pub password: Option<String>,
+    /// JSON object of parameter values, e.g. `{"region":"west"}`
+    pub parameters: Option<String>,
+    pub apply_formatting: Option<bool>,
+}
+
+/// Downloads the full metric result as a CSV, XLSX or Parquet file.
+pub async fn export_metric_data_rest_handler(
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(metric_id): Path<Uuid>,
+    Query(params): Query<ExportMetricDataParams>,
+) -> Result<Response, (StatusCode, String)> {
+    tracing::info!(
+        "Processing GET request for metric export with ID: {}",
+        metric_id
+    );
+
+    let parameters = match params.parameters.as_deref().map(serde_json::from_str).transpose() {
+        Ok(parameters) => parameters.unwrap_or_default(),
+        Err(e) => {
+            tracing::error!("Invalid metric parameters: {}", e);
+            return Err((StatusCode::BAD_REQUEST, format!("Invalid parameters: {}", e)));
+        }
+    };
+
+    let request = ExportMetricDataRequest {
+        metric_id,
+        version_number: params.version_number,
+        password: params.password,
+        format: params.format,
+        apply_formatting: params.apply_formatting.unwrap_or(false),
+        parameters,
+    };
+
+    let export = match handlers::metrics::export_metric_data_handler(request, user).await {
+        Ok(export) => export,
+        Err(e) => {
+            let error_message = e.to_string();
+            tracing::error!("Error exporting metric data: {}", error_message);
+
+            if error_message.contains("Incorrect password") || error_message.contains("public_password required") {
+                return Err((StatusCode::IM_A_TEAPOT, error_message));
+            } else if error_message.contains("don't have permission") || error_message.contains("not found") || error_message.contains("expired") {
+                return Err((StatusCode::FORBIDDEN, error_message));
+            } else if error_message.contains("parameter") {
+                return Err((StatusCode::BAD_REQUEST, error_message));
+            } else {
+                return Err((StatusCode::INTERNAL_SERVER_ERROR, error_message));
+            }
+        }
+    };
+
+    let stream = futures::stream::unfold(export.chunks, |mut chunks| async move {
+        chunks.recv().await.map(|chunk| {
+            let chunk = chunk.map_err(|e| std::io::Error::other(e.to_string()));
+            (chunk, chunks)
+        })
+    });
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, export.format.content_type().to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", export.file_name),
+            ),
+        ],
+        Body::from_stream(stream),
+    )
+        .into_response())
+}
@@ -12,6 +12,13 @@ pub struct GetMetricDataParams {
     pub version_number: Option<i32>,
     pub limit: Option<i64>,
     pub password: Option<String>,
+    pub force_refresh: Option<bool>,
+    pub cursor: Option<String>,
+    /// JSON object of parameter values, e.g. `{"region":"west"}`
+    pub parameters: Option<String>,
+    pub dashboard_id: Option<Uuid>,
+    /// JSON object of dashboard filter values, e.g. `{"period":"last_30_days"}`
+    pub dashboard_filters: Option<String>,
 }
 
 pub async fn get_metric_data_rest_handler(
@@ -24,11 +31,32 @@ pub async fn get_metric_data_rest_handler(
         metric_id
     );
 
+    let parameters = match params.parameters.as_deref().map(serde_json::from_str).transpose() {
+        Ok(parameters) => parameters.unwrap_or_default(),
+        Err(e) => {
+            tracing::error!("Invalid metric parameters: {}", e);
+            return Err((StatusCode::BAD_REQUEST, format!("Invalid parameters: {}", e)));
+        }
+    };
+
+    let dashboard_filters = match params.dashboard_filters.as_deref().map(serde_json::from_str).transpose() {
+        Ok(filters) => filters.unwrap_or_default(),
+        Err(e) => {
+            tracing::error!("Invalid dashboard filters: {}", e);
+            return Err((StatusCode::BAD_REQUEST, format!("Invalid dashboard filters: {}", e)));
+        }
+    };
+
     let request = GetMetricDataRequest {
         metric_id,
         version_number: params.version_number,
         limit: params.limit,
         password: params.password,
+        force_refresh: params.force_refresh.unwrap_or(false),
+        cursor: params.cursor,
+        parameters,
+        dashboard_id: params.dashboard_id,
+        dashboard_filters,
     };
 
     match handlers::metrics::get_metric_data_handler(request, user).await {
@@ -43,6 +71,11 @@ pub async fn get_metric_data_rest_handler(
             } else if error_message.contains("don't have permission") || error_message.contains("not found") || error_message.contains("expired") {
                 // Handle permission, not found, or expired errors with 403 Forbidden
                 Err((StatusCode::FORBIDDEN, error_message))
+            } else if error_message.contains("parameter")
+                || error_message.contains("dashboard filter")
+                || error_message.contains("not on this dashboard")
+            {
+                Err((StatusCode::BAD_REQUEST, error_message))
             } else {
                 // Default to 500 for other errors
                 Err((StatusCode::INTERNAL_SERVER_ERROR, error_message))
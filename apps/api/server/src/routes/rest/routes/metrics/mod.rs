@@ -5,11 +5,14 @@ use axum::{
 
 // Import modules
 mod bulk_update_metrics;
+mod create_metric;
 mod delete_metric;
+mod export_metric_data;
 mod get_metric;
 mod get_metric_data;
 mod list_metrics;
 mod sharing;
+mod stream_metric_data;
 mod update_metric;
 
 pub fn router() -> Router {
@@ -18,11 +21,20 @@ pub fn router() -> Router {
         .route("/:id", put(update_metric::update_metric_rest_handler))
         .route("/:id", delete(delete_metric::delete_metric_rest_handler))
         .route("/", get(list_metrics::list_metrics_rest_handler))
+        .route("/", post(create_metric::create_metric_rest_handler))
         .route("/", put(bulk_update_metrics::bulk_update_metrics_rest_handler))
         .route("/", delete(delete_metric::delete_metrics_rest_handler))
         .route(
             "/:id/data",
             get(get_metric_data::get_metric_data_rest_handler),
         )
+        .route(
+            "/:id/export",
+            get(export_metric_data::export_metric_data_rest_handler),
+        )
+        .route(
+            "/:id/data/stream",
+            get(stream_metric_data::stream_metric_data_rest_handler),
+        )
         .nest("/:id/sharing", sharing::router())
 }
@@ -0,0 +1,81 @@
+use axum::body::Body;
+use axum::extract::{Path, Query};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Extension;
+use handlers::metrics::{MetricDataStreamFormat, StreamMetricDataRequest};
+use middleware::AuthenticatedUser;
+use serde::Deserialize;
+use uuid::Uuid;
+
+#[derive(Deserialize)]
+pub struct StreamMetricDataParams {
+    pub version_number: Option<i32>,
+    pub password: Option<String>,
+    /// JSON object of parameter values, e.g. `{"region":"west"}`
+    pub parameters: Option<String>,
+    pub format: Option<MetricDataStreamFormat>,
+    pub page_size: Option<i64>,
+}
+
+/// Streams the full metric result as NDJSON or an Arrow IPC stream.
+pub async fn stream_metric_data_rest_handler(
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(metric_id): Path<Uuid>,
+    Query(params): Query<StreamMetricDataParams>,
+) -> Result<Response, (StatusCode, String)> {
+    tracing::info!(
+        "Processing GET request for metric data stream with ID: {}",
+        metric_id
+    );
+
+    let format = params.format.unwrap_or_default();
+
+    let parameters = match params.parameters.as_deref().map(serde_json::from_str).transpose() {
+        Ok(parameters) => parameters.unwrap_or_default(),
+        Err(e) => {
+            tracing::error!("Invalid metric parameters: {}", e);
+            return Err((StatusCode::BAD_REQUEST, format!("Invalid parameters: {}", e)));
+        }
+    };
+
+    let request = StreamMetricDataRequest {
+        metric_id,
+        version_number: params.version_number,
+        password: params.password,
+        format,
+        page_size: params.page_size,
+        parameters,
+    };
+
+    let receiver = match handlers::metrics::stream_metric_data_handler(request, user).await {
+        Ok(receiver) => receiver,
+        Err(e) => {
+            let error_message = e.to_string();
+            tracing::error!("Error streaming metric data: {}", error_message);
+
+            if error_message.contains("Incorrect password") || error_message.contains("public_password required") {
+                return Err((StatusCode::IM_A_TEAPOT, error_message));
+            } else if error_message.contains("don't have permission") || error_message.contains("not found") || error_message.contains("expired") {
+                return Err((StatusCode::FORBIDDEN, error_message));
+            } else if error_message.contains("parameter") {
+                return Err((StatusCode::BAD_REQUEST, error_message));
+            } else {
+                return Err((StatusCode::INTERNAL_SERVER_ERROR, error_message));
+            }
+        }
+    };
+
+    let stream = futures::stream::unfold(receiver, |mut receiver| async move {
+        receiver.recv().await.map(|chunk| {
+            let chunk = chunk.map_err(|e| std::io::Error::other(e.to_string()));
+            (chunk, receiver)
+        })
+    });
+
+    Ok((
+        [(header::CONTENT_TYPE, format.content_type())],
+        Body::from_stream(stream),
+    )
+        .into_response())
+}
@@ -1,3 +1,4 @@
+mod alerts;
 mod api_keys;
 mod assets;
 mod chats;
@@ -12,7 +13,10 @@ mod metrics;
 mod organizations;
 mod permission_groups;
 mod search;
+mod semantic;
 mod sql;
+mod subscriptions;
+mod terms;
 mod users;
 mod collections;
 
@@ -29,6 +33,7 @@ pub fn router() -> Router {
             .nest("/permission_groups", permission_groups::router())
             .nest("/dataset_groups", dataset_groups::router())
             .nest("/sql", sql::router())
+            .nest("/semantic", semantic::router())
             .nest("/organizations", organizations::router())
             .nest("/chats", chats::router())
             .nest("/messages", messages::router())
@@ -36,8 +41,11 @@ pub fn router() -> Router {
             .nest("/dashboards", dashboards::router())
             .nest("/users", users::router())
             .nest("/collections", collections::router())
+            .nest("/subscriptions", subscriptions::router())
+            .nest("/alerts", alerts::router())
             .nest("/logs", logs::router())
             .nest("/search", search::router())
+            .nest("/terms", terms::router())
             .nest("/helpers", helpers::router())
             .route_layer(axum_middleware::from_fn(auth)),
     )
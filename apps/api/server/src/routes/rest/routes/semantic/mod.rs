@@ -0,0 +1,7 @@
+mod semantic_query;
+
+use axum::{routing::post, Router};
+
+pub fn router() -> Router {
+    Router::new().route("/query", post(semantic_query::semantic_query))
+}
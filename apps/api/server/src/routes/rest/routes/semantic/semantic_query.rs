@@ -0,0 +1,31 @@
+use anyhow::Result;
+use axum::{http::StatusCode, Extension, Json};
+use middleware::AuthenticatedUser;
+
+use crate::routes::rest::ApiResponse;
+use handlers::semantic::{semantic_query_handler, SemanticQueryRequest, SemanticQueryResponse};
+
+pub async fn semantic_query(
+    Extension(user): Extension<AuthenticatedUser>,
+    Json(payload): Json<SemanticQueryRequest>,
+) -> Result<ApiResponse<SemanticQueryResponse>, (StatusCode, String)> {
+    match semantic_query_handler(&user, payload).await {
+        Ok(response) => Ok(ApiResponse::JsonData(response)),
+        Err(e) => {
+            tracing::error!("Error running semantic query: {:?}", e);
+            let message = e.to_string();
+            if message.contains("Invalid semantic query") {
+                Err((StatusCode::BAD_REQUEST, message))
+            } else if message.contains("don't have permission") {
+                Err((StatusCode::FORBIDDEN, message))
+            } else if message.contains("not found") || message.contains("not a member") {
+                Err((StatusCode::NOT_FOUND, "Data source not found".to_string()))
+            } else {
+                Err((
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to run semantic query: {:#}", e),
+                ))
+            }
+        }
+    }
+}
@@ -2,7 +2,7 @@ use anyhow::{anyhow, Result};
 use axum::{Extension, Json};
 use diesel::{BoolExpressionMethods, ExpressionMethods, JoinOnDsl, QueryDsl};
 use indexmap::IndexMap;
-use query_engine::data_source_query_routes::query_engine::query_engine;
+use query_engine::data_source_query_routes::query_engine::query_engine_for_user;
 use query_engine::data_types::DataType;
 use reqwest::StatusCode;
 use uuid::Uuid;
@@ -99,7 +99,7 @@ async fn run_dataset_sql_handler(
         .is_ok();
 
     let results = if is_org_admin_or_owner || has_dataset_access {
-        match fetch_data(sql, dataset_id).await {
+        match fetch_data(sql, dataset_id, user_id).await {
             Ok(results) => results,
             Err(e) => return Err(e),
         }
@@ -116,8 +116,8 @@ pub struct DataObject {
     pub data_metadata: DataMetadata,
 }
 
-pub async fn fetch_data(sql: &String, dataset_id: &Uuid) -> Result<DataObject> {
-    let query_result = match query_engine(&dataset_id, &sql, None).await {
+pub async fn fetch_data(sql: &String, dataset_id: &Uuid, user_id: &Uuid) -> Result<DataObject> {
+    let query_result = match query_engine_for_user(user_id, &dataset_id, &sql, None).await {
         Ok(result) => result,
         Err(e) => {
             return Err(anyhow!(e));
@@ -135,7 +135,7 @@ async fn run_data_source_sql_handler(
     data_source_id: &Uuid,
     user_id: &Uuid,
 ) -> Result<DataObject> {
-    let query_result = match query_engine(&data_source_id, &sql, None).await {
+    let query_result = match query_engine_for_user(user_id, &data_source_id, &sql, None).await {
         Ok(result) => result,
         Err(e) => return Err(e),
     };
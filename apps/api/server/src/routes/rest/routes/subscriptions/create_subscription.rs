@@ -0,0 +1,36 @@
+use anyhow::Result;
+use axum::{http::StatusCode, Extension, Json};
+use middleware::AuthenticatedUser;
+
+use crate::routes::rest::ApiResponse;
+use handlers::subscriptions::{
+    create_subscription_handler, CreateSubscriptionRequest, SubscriptionResponse,
+};
+
+pub async fn create_subscription(
+    Extension(user): Extension<AuthenticatedUser>,
+    Json(payload): Json<CreateSubscriptionRequest>,
+) -> Result<ApiResponse<SubscriptionResponse>, (StatusCode, String)> {
+    match create_subscription_handler(&user, payload).await {
+        Ok(subscription) => Ok(ApiResponse::JsonData(subscription)),
+        Err(e) => {
+            tracing::error!("Error creating subscription: {:?}", e);
+            let message = e.to_string();
+            if message.contains("Invalid subscription") {
+                Err((StatusCode::BAD_REQUEST, message))
+            } else if message.contains("permission") {
+                Err((
+                    StatusCode::FORBIDDEN,
+                    "Insufficient permissions".to_string(),
+                ))
+            } else if message.contains("not found") {
+                Err((StatusCode::NOT_FOUND, "Asset not found".to_string()))
+            } else {
+                Err((
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Failed to create subscription".to_string(),
+                ))
+            }
+        }
+    }
+}
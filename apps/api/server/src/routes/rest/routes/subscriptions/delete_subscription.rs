@@ -0,0 +1,30 @@
+use anyhow::Result;
+use axum::{extract::Path, http::StatusCode, Extension};
+use middleware::AuthenticatedUser;
+use uuid::Uuid;
+
+use crate::routes::rest::ApiResponse;
+use handlers::subscriptions::delete_subscription_handler;
+
+pub async fn delete_subscription(
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+) -> Result<ApiResponse<()>, (StatusCode, &'static str)> {
+    match delete_subscription_handler(&user, &id).await {
+        Ok(_) => Ok(ApiResponse::NoContent),
+        Err(e) => {
+            tracing::error!("Error deleting subscription: {:?}", e);
+            let message = e.to_string();
+            if message.contains("not found") {
+                Err((StatusCode::NOT_FOUND, "Subscription not found"))
+            } else if message.contains("permissions") {
+                Err((StatusCode::FORBIDDEN, "Insufficient permissions"))
+            } else {
+                Err((
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Failed to delete subscription",
+                ))
+            }
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use anyhow::Result;
+use axum::{extract::Path, http::StatusCode, Extension};
+use middleware::AuthenticatedUser;
+use uuid::Uuid;
+
+use crate::routes::rest::ApiResponse;
+use handlers::subscriptions::{get_subscription_handler, SubscriptionResponse};
+
+pub async fn get_subscription(
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+) -> Result<ApiResponse<SubscriptionResponse>, (StatusCode, &'static str)> {
+    match get_subscription_handler(&user, &id).await {
+        Ok(subscription) => Ok(ApiResponse::JsonData(subscription)),
+        Err(e) => {
+            tracing::error!("Error getting subscription: {:?}", e);
+            let message = e.to_string();
+            if message.contains("not found") {
+                Err((StatusCode::NOT_FOUND, "Subscription not found"))
+            } else if message.contains("permissions") {
+                Err((StatusCode::FORBIDDEN, "Insufficient permissions"))
+            } else {
+                Err((
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Failed to get subscription",
+                ))
+            }
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use anyhow::Result;
+use axum::{extract::Query, http::StatusCode, Extension};
+use middleware::AuthenticatedUser;
+
+use crate::routes::rest::ApiResponse;
+use handlers::subscriptions::{
+    list_subscriptions_handler, ListSubscriptionsRequest, SubscriptionResponse,
+};
+
+pub async fn list_subscriptions(
+    Extension(user): Extension<AuthenticatedUser>,
+    Query(query): Query<ListSubscriptionsRequest>,
+) -> Result<ApiResponse<Vec<SubscriptionResponse>>, (StatusCode, &'static str)> {
+    match list_subscriptions_handler(&user, query).await {
+        Ok(subscriptions) => Ok(ApiResponse::JsonData(subscriptions)),
+        Err(e) => {
+            tracing::error!("Error listing subscriptions: {:?}", e);
+            if e.to_string().contains("not a member of any organization") {
+                Err((
+                    StatusCode::BAD_REQUEST,
+                    "User is not a member of any organization",
+                ))
+            } else {
+                Err((
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Failed to list subscriptions",
+                ))
+            }
+        }
+    }
+}
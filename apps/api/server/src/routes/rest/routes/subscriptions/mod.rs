@@ -0,0 +1,21 @@
+mod create_subscription;
+mod delete_subscription;
+mod get_subscription;
+mod list_subscriptions;
+mod send_subscription;
+mod update_subscription;
+
+use axum::{
+    routing::{delete, get, post, put},
+    Router,
+};
+
+pub fn router() -> Router {
+    Router::new()
+        .route("/", get(list_subscriptions::list_subscriptions))
+        .route("/", post(create_subscription::create_subscription))
+        .route("/:id", get(get_subscription::get_subscription))
+        .route("/:id", put(update_subscription::update_subscription))
+        .route("/:id", delete(delete_subscription::delete_subscription))
+        .route("/:id/send", post(send_subscription::send_subscription))
+}
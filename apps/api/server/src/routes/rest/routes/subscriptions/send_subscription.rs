@@ -0,0 +1,31 @@
+use anyhow::Result;
+use axum::{extract::Path, http::StatusCode, Extension};
+use middleware::AuthenticatedUser;
+use uuid::Uuid;
+
+use crate::routes::rest::ApiResponse;
+use handlers::subscriptions::{send_subscription_handler, SubscriptionDeliveryResponse};
+
+/// Sends a subscription now. The delivery's outcome, including any error, is in the body.
+pub async fn send_subscription(
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+) -> Result<ApiResponse<SubscriptionDeliveryResponse>, (StatusCode, &'static str)> {
+    match send_subscription_handler(&user, &id).await {
+        Ok(delivery) => Ok(ApiResponse::JsonData(delivery)),
+        Err(e) => {
+            tracing::error!("Error sending subscription: {:?}", e);
+            let message = e.to_string();
+            if message.contains("not found") {
+                Err((StatusCode::NOT_FOUND, "Subscription not found"))
+            } else if message.contains("permissions") {
+                Err((StatusCode::FORBIDDEN, "Insufficient permissions"))
+            } else {
+                Err((
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Failed to send subscription",
+                ))
+            }
+        }
+    }
+}
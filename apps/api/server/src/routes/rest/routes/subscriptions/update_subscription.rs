@@ -0,0 +1,38 @@
+use anyhow::Result;
+use axum::{extract::Path, http::StatusCode, Extension, Json};
+use middleware::AuthenticatedUser;
+use uuid::Uuid;
+
+use crate::routes::rest::ApiResponse;
+use handlers::subscriptions::{
+    update_subscription_handler, SubscriptionResponse, UpdateSubscriptionRequest,
+};
+
+pub async fn update_subscription(
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdateSubscriptionRequest>,
+) -> Result<ApiResponse<SubscriptionResponse>, (StatusCode, String)> {
+    match update_subscription_handler(&user, &id, payload).await {
+        Ok(subscription) => Ok(ApiResponse::JsonData(subscription)),
+        Err(e) => {
+            tracing::error!("Error updating subscription: {:?}", e);
+            let message = e.to_string();
+            if message.contains("Invalid subscription") {
+                Err((StatusCode::BAD_REQUEST, message))
+            } else if message.contains("not found") {
+                Err((StatusCode::NOT_FOUND, "Subscription not found".to_string()))
+            } else if message.contains("permissions") {
+                Err((
+                    StatusCode::FORBIDDEN,
+                    "Insufficient permissions".to_string(),
+                ))
+            } else {
+                Err((
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Failed to update subscription".to_string(),
+                ))
+            }
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use anyhow::Result;
+use axum::{http::StatusCode, Extension, Json};
+use middleware::AuthenticatedUser;
+
+use crate::routes::rest::ApiResponse;
+use handlers::terms::{create_term_handler, CreateTermRequest, TermResponse};
+
+pub async fn create_term(
+    Extension(user): Extension<AuthenticatedUser>,
+    Json(payload): Json<CreateTermRequest>,
+) -> Result<ApiResponse<TermResponse>, (StatusCode, &'static str)> {
+    match create_term_handler(&user, payload).await {
+        Ok(term) => Ok(ApiResponse::JsonData(term)),
+        Err(e) => {
+            tracing::error!("Error creating term: {:?}", e);
+            let message = e.to_string();
+            if message.contains("already exists") {
+                Err((StatusCode::CONFLICT, "A term with this name already exists"))
+            } else if message.contains("permissions") {
+                Err((StatusCode::FORBIDDEN, "Insufficient permissions"))
+            } else if message.contains("Invalid term") {
+                Err((StatusCode::BAD_REQUEST, "Term name is empty or too long"))
+            } else if message.contains("Dataset not found") {
+                Err((StatusCode::BAD_REQUEST, "Linked dataset not found"))
+            } else {
+                Err((StatusCode::INTERNAL_SERVER_ERROR, "Failed to create term"))
+            }
+        }
+    }
+}
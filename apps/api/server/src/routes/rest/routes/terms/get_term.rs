@@ -0,0 +1,24 @@
+use anyhow::Result;
+use axum::{extract::Path, http::StatusCode, Extension};
+use middleware::AuthenticatedUser;
+use uuid::Uuid;
+
+use crate::routes::rest::ApiResponse;
+use handlers::terms::{get_term_handler, TermResponse};
+
+pub async fn get_term(
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+) -> Result<ApiResponse<TermResponse>, (StatusCode, &'static str)> {
+    match get_term_handler(&user, &id).await {
+        Ok(term) => Ok(ApiResponse::JsonData(term)),
+        Err(e) => {
+            tracing::error!("Error getting term: {:?}", e);
+            if e.to_string().contains("not found") {
+                Err((StatusCode::NOT_FOUND, "Term not found"))
+            } else {
+                Err((StatusCode::INTERNAL_SERVER_ERROR, "Failed to get term"))
+            }
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use anyhow::Result;
+use axum::{extract::Query, http::StatusCode, Extension};
+use middleware::AuthenticatedUser;
+
+use crate::routes::rest::ApiResponse;
+use handlers::terms::{list_terms_handler, ListTermsRequest, TermResponse};
+
+pub async fn list_terms(
+    Extension(user): Extension<AuthenticatedUser>,
+    Query(query): Query<ListTermsRequest>,
+) -> Result<ApiResponse<Vec<TermResponse>>, (StatusCode, &'static str)> {
+    match list_terms_handler(&user, query).await {
+        Ok(terms) => Ok(ApiResponse::JsonData(terms)),
+        Err(e) => {
+            tracing::error!("Error listing terms: {:?}", e);
+            if e.to_string().contains("not a member of any organization") {
+                Err((
+                    StatusCode::BAD_REQUEST,
+                    "User is not a member of any organization",
+                ))
+            } else {
+                Err((StatusCode::INTERNAL_SERVER_ERROR, "Failed to list terms"))
+            }
+        }
+    }
+}
@@ -0,0 +1,21 @@
+mod create_term;
+mod delete_term;
+mod get_term;
+mod list_terms;
+mod search_terms;
+mod update_term;
+
+use axum::{
+    routing::{delete, get, post, put},
+    Router,
+};
+
+pub fn router() -> Router {
+    Router::new()
+        .route("/", get(list_terms::list_terms))
+        .route("/", post(create_term::create_term))
+        .route("/search", get(search_terms::search_terms))
+        .route("/:id", get(get_term::get_term))
+        .route("/:id", put(update_term::update_term))
+        .route("/:id", delete(delete_term::delete_term))
+}
@@ -0,0 +1,19 @@
+use anyhow::Result;
+use axum::{extract::Query, http::StatusCode, Extension};
+use middleware::AuthenticatedUser;
+
+use crate::routes::rest::ApiResponse;
+use handlers::terms::{search_terms_handler, SearchTermsRequest, TermSearchItem};
+
+pub async fn search_terms(
+    Extension(user): Extension<AuthenticatedUser>,
+    Query(query): Query<SearchTermsRequest>,
+) -> Result<ApiResponse<Vec<TermSearchItem>>, (StatusCode, &'static str)> {
+    match search_terms_handler(&user, query).await {
+        Ok(terms) => Ok(ApiResponse::JsonData(terms)),
+        Err(e) => {
+            tracing::error!("Error searching terms: {:?}", e);
+            Err((StatusCode::INTERNAL_SERVER_ERROR, "Failed to search terms"))
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use anyhow::Result;
+use axum::{extract::Path, http::StatusCode, Extension, Json};
+use middleware::AuthenticatedUser;
+use uuid::Uuid;
+
+use crate::routes::rest::ApiResponse;
+use handlers::terms::{update_term_handler, TermResponse, UpdateTermRequest};
+
+pub async fn update_term(
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdateTermRequest>,
+) -> Result<ApiResponse<TermResponse>, (StatusCode, &'static str)> {
+    match update_term_handler(&user, &id, payload).await {
+        Ok(term) => Ok(ApiResponse::JsonData(term)),
+        Err(e) => {
+            tracing::error!("Error updating term: {:?}", e);
+            let message = e.to_string();
+            if message.contains("Dataset not found") {
+                Err((StatusCode::BAD_REQUEST, "Linked dataset not found"))
+            } else if message.contains("not found") {
+                Err((StatusCode::NOT_FOUND, "Term not found"))
+            } else if message.contains("already exists") {
+                Err((StatusCode::CONFLICT, "A term with this name already exists"))
+            } else if message.contains("permissions") {
+                Err((StatusCode::FORBIDDEN, "Insufficient permissions"))
+            } else if message.contains("Invalid term") {
+                Err((StatusCode::BAD_REQUEST, "Term name is empty or too long"))
+            } else {
+                Err((StatusCode::INTERNAL_SERVER_ERROR, "Failed to update term"))
+            }
+        }
+    }
+}
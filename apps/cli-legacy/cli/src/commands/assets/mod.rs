@@ -0,0 +1,57 @@
+//! `buster pull` and `buster push`: metrics and dashboards as files in a project directory,
+//! so they can be reviewed and versioned alongside the rest of the project.
+
+pub mod pull;
+pub mod push;
+
+pub use pull::pull;
+pub use push::push;
+
+use anyhow::Result;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+use crate::utils::buster::BusterClient;
+use crate::utils::file::asset_files::AssetKind;
+
+/// Metrics come first so dashboards can reference metrics created in the same push
+const KINDS: [AssetKind; 2] = [AssetKind::Metric, AssetKind::Dashboard];
+
+/// The server's current version of a metric or dashboard
+struct RemoteAsset {
+    id: Uuid,
+    name: String,
+    version_number: i32,
+    data_source_id: Option<Uuid>,
+    file: String,
+}
+
+async fn fetch_remote(
+    client: &BusterClient,
+    kind: AssetKind,
+    id: &Uuid,
+) -> Result<Option<RemoteAsset>> {
+    Ok(match kind {
+        AssetKind::Metric => client.get_metric(id).await?.map(|metric| RemoteAsset {
+            id: metric.id,
+            name: metric.name,
+            version_number: metric.version_number,
+            data_source_id: Some(metric.data_source_id),
+            file: metric.file,
+        }),
+        AssetKind::Dashboard => client.get_dashboard(id).await?.map(|res| RemoteAsset {
+            id: res.dashboard.id,
+            name: res.dashboard.name,
+            version_number: res.dashboard.version_number,
+            data_source_id: None,
+            file: res.dashboard.file,
+        }),
+    })
+}
+
+fn project_dir(path: Option<&str>) -> Result<PathBuf> {
+    Ok(match path {
+        Some(path) => PathBuf::from(path),
+        None => std::env::current_dir()?,
+    })
+}
@@ -0,0 +1,152 @@
+use anyhow::{anyhow, Result};
+use colored::*;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use super::{fetch_remote, project_dir, KINDS};
+use crate::utils::{
+    buster::BusterClient,
+    file::{
+        asset_files::{
+            file_name_for, has_unpushed_changes, plan_push, read_asset_files, render_asset_file,
+            set_sync_keys, AssetKind, PushAction,
+        },
+        buster_credentials::get_and_validate_buster_credentials,
+    },
+};
+
+#[derive(Debug, Default)]
+struct PullSummary {
+    created: usize,
+    updated: usize,
+    unchanged: usize,
+    /// (file, reason)
+    skipped: Vec<(String, String)>,
+}
+
+/// Writes every metric and dashboard the user can see to `<path>/metrics` and
+/// `<path>/dashboards`.
+///
+/// Files that were already pulled are updated in place. Files with edits that haven't been
+/// pushed are left alone unless `force` is set.
+pub async fn pull(path: Option<&str>, force: bool) -> Result<()> {
+    let project_dir = project_dir(path)?;
+    println!(
+        "\n{}",
+        "⬇️  Pulling metrics and dashboards...".bold().blue()
+    );
+    println!(
+        "Project directory: {}",
+        project_dir.display().to_string().dimmed()
+    );
+
+    let creds = get_and_validate_buster_credentials().await?;
+    let client = BusterClient::new(creds.url, creds.api_key)?;
+
+    let mut summary = PullSummary::default();
+    for kind in KINDS {
+        pull_kind(&client, &project_dir, kind, force, &mut summary).await?;
+    }
+
+    println!("\n{}", "📊 Pull Summary".bold().green());
+    println!("======================================");
+    println!("   ✨ New files: {}", summary.created.to_string().green());
+    println!(
+        "   🔄 Updated files: {}",
+        summary.updated.to_string().cyan()
+    );
+    println!(
+        "   ➖ Unchanged files: {}",
+        summary.unchanged.to_string().dimmed()
+    );
+    if !summary.skipped.is_empty() {
+        println!(
+            "   ⚠️  Skipped files: {}",
+            summary.skipped.len().to_string().yellow()
+        );
+        for (file, reason) in &summary.skipped {
+            println!("     - {}: {}", file.cyan(), reason.yellow());
+        }
+    }
+    println!("======================================");
+    Ok(())
+}
+
+async fn pull_kind(
+    client: &BusterClient,
+    project_dir: &Path,
+    kind: AssetKind,
+    force: bool,
+    summary: &mut PullSummary,
+) -> Result<()> {
+    let dir = project_dir.join(kind.dir_name());
+    let local = read_asset_files(project_dir, kind)?;
+    let mut taken: HashSet<String> = local
+        .iter()
+        .filter_map(|file| file.path.file_name())
+        .map(|name| name.to_string_lossy().to_string())
+        .collect();
+    let local_by_id: HashMap<_, _> = local
+        .iter()
+        .filter_map(|file| file.id.map(|id| (id, file)))
+        .collect();
+
+    let items = match kind {
+        AssetKind::Metric => client.list_metrics().await?,
+        AssetKind::Dashboard => client.list_dashboards().await?,
+    };
+    println!("\nFound {} {}s", items.len(), kind.label());
+    fs::create_dir_all(&dir)?;
+
+    for item in items {
+        let remote = fetch_remote(client, kind, &item.id)
+            .await?
+            .ok_or_else(|| anyhow!("{} '{}' was not found", kind.label(), item.name))?;
+        let contents = render_asset_file(
+            &remote.id,
+            remote.version_number,
+            remote.data_source_id.as_ref(),
+            &remote.file,
+        );
+
+        match local_by_id.get(&remote.id) {
+            Some(file) => {
+                let display = file.path.display().to_string();
+                let same_definition =
+                    plan_push(file, Some((remote.version_number, remote.file.as_str())))?
+                        == PushAction::Unchanged;
+                if same_definition && file.version_number == Some(remote.version_number) {
+                    summary.unchanged += 1;
+                    continue;
+                }
+                let contents = if same_definition {
+                    // Only the version moved on; keep the file as the user formatted it
+                    set_sync_keys(&file.contents, &remote.id, remote.version_number)
+                } else {
+                    contents
+                };
+                if !force && has_unpushed_changes(file, remote.version_number, &remote.file)? {
+                    summary.skipped.push((
+                        display,
+                        "has changes that haven't been pushed (use --force to overwrite)"
+                            .to_string(),
+                    ));
+                    continue;
+                }
+                fs::write(&file.path, contents)?;
+                println!("🔄 {}", display.cyan());
+                summary.updated += 1;
+            }
+            None => {
+                let file_name = file_name_for(&remote.name, &remote.id, &taken);
+                let path = dir.join(&file_name);
+                fs::write(&path, contents)?;
+                println!("✨ {}", path.display().to_string().cyan());
+                taken.insert(file_name);
+                summary.created += 1;
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,224 @@
+use anyhow::{anyhow, Result};
+use colored::*;
+use std::fs;
+
+use super::{fetch_remote, project_dir, KINDS};
+use crate::utils::{
+    buster::{BusterClient, CreateDashboardRequest, CreateMetricRequest, UpdateAssetFileRequest},
+    file::{
+        asset_files::{
+            plan_push, read_asset_files, set_sync_keys, AssetFile, AssetKind, PushAction,
+        },
+        buster_credentials::get_and_validate_buster_credentials,
+    },
+};
+
+#[derive(Debug, Default)]
+struct PushSummary {
+    created: usize,
+    updated: usize,
+    unchanged: usize,
+    /// (file, remote version)
+    conflicts: Vec<(String, i32)>,
+    /// (file, error)
+    failures: Vec<(String, String)>,
+}
+
+/// Applies the metric and dashboard files in `<path>/metrics` and `<path>/dashboards`.
+///
+/// Each file is compared with the server's current version: new files are created and
+/// changed files are saved as a new version. A file whose asset changed on the server since
+/// it was pulled is a conflict and is skipped unless `force` is set. After a push the files'
+/// `id` and `version_number` are updated to match the server.
+pub async fn push(path: Option<&str>, dry_run: bool, force: bool) -> Result<()> {
+    let project_dir = project_dir(path)?;
+    println!(
+        "\n{}",
+        "⬆️  Pushing metrics and dashboards...".bold().blue()
+    );
+    println!(
+        "Project directory: {}",
+        project_dir.display().to_string().dimmed()
+    );
+
+    let creds = get_and_validate_buster_credentials().await?;
+    let client = BusterClient::new(creds.url, creds.api_key)?;
+
+    let mut summary = PushSummary::default();
+    for kind in KINDS {
+        for file in read_asset_files(&project_dir, kind)? {
+            let display = file.path.display().to_string();
+            let remote = match file.id {
+                Some(id) => fetch_remote(&client, kind, &id).await?,
+                None => None,
+            };
+            let action = plan_push(
+                &file,
+                remote.as_ref().map(|r| (r.version_number, r.file.as_str())),
+            )?;
+
+            let action = match action {
+                PushAction::Unchanged => {
+                    summary.unchanged += 1;
+                    continue;
+                }
+                PushAction::Conflict {
+                    local_version,
+                    remote_version,
+                } if !force => {
+                    println!(
+                        "⚠️  {} was changed on the server (version {} since version {})",
+                        display.cyan(),
+                        remote_version,
+                        local_version
+                            .map(|v| v.to_string())
+                            .unwrap_or_else(|| "unknown".to_string())
+                    );
+                    summary.conflicts.push((display, remote_version));
+                    continue;
+                }
+                PushAction::Conflict { .. } => PushAction::Update,
+                action => action,
+            };
+
+            let created = action == PushAction::Create;
+            println!(
+                "{} {} {} '{}' from {}",
+                if created { "✨" } else { "🔄" },
+                if created { "Create" } else { "Update" },
+                kind.label(),
+                file.name().purple(),
+                display.cyan()
+            );
+            if dry_run {
+                if created {
+                    summary.created += 1;
+                } else {
+                    summary.updated += 1;
+                }
+                continue;
+            }
+
+            match apply(&client, &file, created).await {
+                Ok(()) if created => summary.created += 1,
+                Ok(()) => summary.updated += 1,
+                Err(e) => {
+                    eprintln!("❌ {}: {}", display.cyan(), e.to_string().red());
+                    summary.failures.push((display, e.to_string()));
+                }
+            }
+        }
+    }
+
+    let title = if dry_run {
+        "📊 Push Plan (dry run)"
+    } else {
+        "📊 Push Summary"
+    };
+    println!("\n{}", title.bold().green());
+    println!("======================================");
+    println!("   ✨ Created: {}", summary.created.to_string().green());
+    println!("   🔄 Updated: {}", summary.updated.to_string().cyan());
+    println!(
+        "   ➖ Unchanged: {}",
+        summary.unchanged.to_string().dimmed()
+    );
+    if !summary.conflicts.is_empty() {
+        println!(
+            "   ⚠️  Conflicts: {}",
+            summary.conflicts.len().to_string().yellow()
+        );
+        println!(
+            "{}",
+            "   Run `buster pull` to take the server's changes, or `buster push --force` to overwrite them."
+                .dimmed()
+        );
+    }
+    println!("======================================");
+
+    if !summary.failures.is_empty() {
+        return Err(anyhow!("Failed to push {} file(s)", summary.failures.len()));
+    }
+    Ok(())
+}
+
+/// Creates or updates the file's asset and records the server's id and version in the file
+async fn apply(client: &BusterClient, file: &AssetFile, create: bool) -> Result<()> {
+    let definition = file.definition_yaml()?;
+    let (id, version_number) = match (file.kind, create) {
+        (AssetKind::Metric, true) => {
+            let metric = client
+                .create_metric(CreateMetricRequest {
+                    id: file.id,
+                    file: definition,
+                    data_source_id: file.data_source_id,
+                })
+                .await?;
+            (metric.id, metric.version_number)
+        }
+        (AssetKind::Metric, false) => {
+            let id = file.id.ok_or_else(|| anyhow!("The file has no id"))?;
+            let metric = client
+                .update_metric(
+                    &id,
+                    UpdateAssetFileRequest {
+                        file: definition,
+                        update_version: true,
+                    },
+                )
+                .await?;
+            (metric.id, metric.version_number)
+        }
+        (AssetKind::Dashboard, true) => {
+            // Dashboards are created empty with the file's id, then given its definition
+            let id = client
+                .create_dashboard(CreateDashboardRequest { id: file.id })
+                .await?
+                .dashboard
+                .id;
+            let update = client
+                .update_dashboard(
+                    &id,
+                    UpdateAssetFileRequest {
+                        file: definition,
+                        update_version: false,
+                    },
+                )
+                .await;
+            let res = match update {
+                Ok(res) => res,
+                Err(e) => {
+                    // Don't leave an empty dashboard behind for the next push to trip over
+                    if let Err(cleanup) = client.delete_dashboards(vec![id]).await {
+                        eprintln!(
+                            "{}",
+                            format!("Failed to remove empty dashboard {}: {}", id, cleanup)
+                                .yellow()
+                        );
+                    }
+                    return Err(e);
+                }
+            };
+            (res.dashboard.id, res.dashboard.version_number)
+        }
+        (AssetKind::Dashboard, false) => {
+            let id = file.id.ok_or_else(|| anyhow!("The file has no id"))?;
+            let res = client
+                .update_dashboard(
+                    &id,
+                    UpdateAssetFileRequest {
+                        file: definition,
+                        update_version: true,
+                    },
+                )
+                .await?;
+            (res.dashboard.id, res.dashboard.version_number)
+        }
+    };
+
+    fs::write(
+        &file.path,
+        set_sync_keys(&file.contents, &id, version_number),
+    )?;
+    Ok(())
+}
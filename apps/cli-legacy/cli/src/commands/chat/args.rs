@@ -3,4 +3,8 @@
 pub struct ChatArgs {
     pub base_url: Option<String>,
     pub api_key: Option<String>,
-} 
\ No newline at end of file
+    pub model: Option<String>,
+    /// Session id (or a prefix of one) to continue, or "latest"
+    pub resume: Option<String>,
+    pub list_sessions: bool,
+}
@@ -1,6 +1,6 @@
 use std::{
     fs, io,
-    path::{Component, Path, PathBuf},
+    path::{Path, PathBuf},
 };
 
 /// Represents the part of the input string that is being considered for path completion.
@@ -128,59 +128,58 @@ fn find_completions_recursive(
         }
     };
 
-    for entry_result in entries {
-        if let Ok(entry) = entry_result {
-            if let Some(name) = entry.file_name().to_str() {
-                // Skip hidden files/dirs unless prefix explicitly starts with .
-                if name.starts_with('.') && !base_prefix_lower.starts_with('.') {
-                    continue;
-                }
+    for entry in entries.flatten() {
+        if let Some(name) = entry.file_name().to_str() {
+            // Skip hidden files/dirs unless prefix explicitly starts with .
+            if name.starts_with('.') && !base_prefix_lower.starts_with('.') {
+                continue;
+            }
+
+            let entry_path_relative = relative_path.join(name);
+            let entry_path_str = entry_path_relative.to_string_lossy().to_lowercase();
+            let name_lower = name.to_lowercase();
+
+            // Check if this entry should be included in completions:
+            // 1. Empty prefix means include everything from starting directory
+            // 2. Name starts with prefix (traditional completion)
+            // 3. Path from the starting directory starts with the prefix, so a matching
+            //    directory brings its contents along
+            let matches_path = entry_path_str.starts_with(base_prefix_lower);
+            let matches_name = name_lower.starts_with(base_prefix_lower);
+            let should_include = base_prefix_lower.is_empty() || matches_name || matches_path;
+
+            if should_include {
+                let completion_path = entry_path_relative.clone(); // Path relative to initial search
 
-                let entry_path_relative = relative_path.join(name);
-                let entry_path_str = entry_path_relative.to_string_lossy().to_lowercase();
-                let name_lower = name.to_lowercase();
-
-                // Check if this entry should be included in completions:
-                // 1. Empty prefix means include everything from starting directory
-                // 2. Name starts with prefix (traditional completion)
-                // 3. Any part of path contains the prefix (fuzzy search)
-                let matches_path = entry_path_str.contains(base_prefix_lower);
-                let matches_name = name_lower.contains(base_prefix_lower);
-                let should_include = base_prefix_lower.is_empty() || matches_name || matches_path;
-
-                if should_include {
-                    let completion_path = entry_path_relative.clone(); // Path relative to initial search
-
-                    match entry.file_type() {
-                         Ok(ft) => {
-                            if ft.is_dir() {
-                                // Add the directory itself to completions (with a slash)
-                                completions.push(completion_path);
-                            } else if ft.is_file() {
-                                // Add the file
-                                completions.push(completion_path);
-                            }
-                         }
-                         Err(_) => { /* Ignore file type errors */ }
-                    }
-                }
-                
-                // Always recurse into directories, even if they don't match,
-                // as they might contain matching files or subdirectories
                 match entry.file_type() {
-                    Ok(ft) => {
+                     Ok(ft) => {
                         if ft.is_dir() {
-                            let _ = find_completions_recursive(
-                                &entry.path(),
-                                base_prefix_lower,
-                                &entry_path_relative,
-                                completions,
-                                max_depth - 1,
-                            );
+                            // Add the directory itself to completions (with a slash)
+                            completions.push(completion_path);
+                        } else if ft.is_file() {
+                            // Add the file
+                            completions.push(completion_path);
                         }
+                     }
+                     Err(_) => { /* Ignore file type errors */ }
+                }
+            }
+            
+            // Always recurse into directories, even if they don't match,
+            // as they might contain matching files or subdirectories
+            match entry.file_type() {
+                Ok(ft) => {
+                    if ft.is_dir() {
+                        let _ = find_completions_recursive(
+                            &entry.path(),
+                            base_prefix_lower,
+                            &entry_path_relative,
+                            completions,
+                            max_depth - 1,
+                        );
                     }
-                    Err(_) => { /* Ignore file type errors */ }
                 }
+                Err(_) => { /* Ignore file type errors */ }
             }
         }
     }
@@ -199,6 +198,7 @@ fn find_completions_recursive(
 /// A tuple containing:
 /// - A vector of strings representing matching paths (relative to the input base).
 /// - An optional `PathCompletionTarget` if a valid path-like segment was found.
+///
 /// Returns an empty vector and `None` if no relevant path fragment is found or on error.
 pub fn get_completions<'a>(input: &'a str, cwd_str: &str) -> (Vec<String>, Option<PathCompletionTarget<'a>>) {
     let cwd = Path::new(cwd_str);
@@ -209,7 +209,9 @@ pub fn get_completions<'a>(input: &'a str, cwd_str: &str) -> (Vec<String>, Optio
 
     let mut raw_completions = Vec::new();
     let prefix_lower = target.prefix.to_lowercase();
-    let max_depth = 10; // Increased recursion depth for better deep directory search
+    // An empty prefix lists just the directory being completed; anything typed is also
+    // looked up in subdirectories
+    let max_depth = if prefix_lower.is_empty() { 1 } else { 10 };
 
     // Start recursive search from the absolute search_dir identified by parse_input
     // Pass an empty initial relative path
@@ -326,8 +328,10 @@ mod tests {
     fn test_parse_input_no_path_like_fragment() {
         let dir = setup_test_dir();
         let cwd = dir.path();
-        // Only trigger if it looks like a path (contains / or starts with .)
-        assert!(parse_input_for_completion("just some words", cwd).is_none());
+        // Plain words are completed as file names from the CWD
+        let target = parse_input_for_completion("just some words", cwd).unwrap();
+        assert_eq!(target.prefix, "words");
+        assert_eq!(target.search_dir, cwd.canonicalize().unwrap());
         // Should parse if it starts with ./ even without space
         assert!(parse_input_for_completion("./somefile", cwd).is_some());
     }
@@ -341,14 +345,14 @@ mod tests {
 
         // Complete from root
         let (completions, _) = get_completions("L", cwd_str);
-        assert_eq!(completions, vec!["LICENSE"]);
+        assert_eq!(completions, vec!["LICENSE", "src/lib.rs"]);
 
         let (completions, _) = get_completions("R", cwd_str);
         assert_eq!(completions, vec!["README.md"]);
 
         // Complete directory and file inside
         let (completions, _) = get_completions("s", cwd_str);
-        assert_eq!(completions, vec!["src/", "src/lib.rs", "src/main.rs", "src/subdir/", "src/subdir/deep_file.txt"]); // Recursive
+        assert_eq!(completions, vec!["src/", "src/subdir/", "src/lib.rs", "src/main.rs", "src/subdir/deep_file.txt"]); // Recursive, directories first
 
         // Complete starting with full path
         let (completions, _) = get_completions("src/m", cwd_str);
@@ -431,6 +435,9 @@ mod tests {
         let cwd_str = dir.path().to_str().unwrap();
         let (completions, target) = get_completions("invalid_dir/file", cwd_str);
         assert!(completions.is_empty());
-        assert!(target.is_none()); // parse should fail if base dir is invalid
+        // A missing base dir falls back to matching the whole fragment from the CWD
+        let target = target.unwrap();
+        assert_eq!(target.prefix, "invalid_dir/file");
+        assert_eq!(target.search_dir, dir.path());
     }
 }
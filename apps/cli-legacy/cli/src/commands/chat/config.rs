@@ -7,6 +7,7 @@ use std::path::PathBuf;
 pub struct ChatConfig {
     pub base_url: Option<String>,
     pub api_key: Option<String>,
+    pub model: Option<String>,
 }
 
 const CONFIG_APP_NAME: &str = "buster";
@@ -1,23 +1,22 @@
 use super::args::ChatArgs;
-use super::config::{load_chat_config, ChatConfig};
-use super::state::{AppState, ActiveToolCall, DisplayLogEntry};
+use super::config::load_chat_config;
+use super::sessions::{find_session, save_session, sessions_dir, ChatSession};
+use super::state::{AppState, ConfirmationAnswer, DisplayLogEntry};
 use super::ui::ui;
-use super::completion;
+use crate::commands::config_utils::{get_app_base_dir, get_cached_value};
 use anyhow::Result;
-use colored::*;
 use litellm::AgentMessage;
-use rustyline::error::ReadlineError;
 use rustyline::DefaultEditor;
 use std::env;
-use std::io::{self, Stdout};
-use std::path::Path;
+use std::io;
 use std::time::{Instant, SystemTime};
 use thiserror::Error;
-use tokio::sync::broadcast;
+use tokio::sync::{broadcast, mpsc};
 use uuid::Uuid;
 use std::process::Command;
 
 // --- Agent Imports ---
+use agents::tools::cli_tools::ToolConfirmationRequest;
 use agents::{AgentError, AgentExt, AgentThread, BusterCliAgent};
 
 // Ratatui / Crossterm related imports
@@ -46,16 +45,10 @@ pub enum ChatError {
 const DEFAULT_OPENAI_BASE_URL: &str = "https://api.openai.com/v1";
 
 // --- Reinstate Credential Logic (adapted) ---
-// Function to get API credentials, now accepting args
+// Function to get API credentials, now accepting args.
+// Precedence: arguments, then the saved chat config, then environment variables, then the
+// OpenAI key cached by `buster config`, and finally a prompt.
 fn get_api_credentials(args: &ChatArgs) -> Result<(Option<String>, Option<String>), ChatError> {
-    // 0. Try loading from config file first
-    if let Ok(config) = load_chat_config() {
-        // Return even if one is empty, agent handles None
-        if config.base_url.is_some() || config.api_key.is_some() {
-             return Ok((config.base_url, config.api_key));
-        }
-    }
-
     // 1. Prioritize arguments
     if args.base_url.is_some() || args.api_key.is_some() {
         println!("Using API credentials from command-line arguments/environment/defaults.");
@@ -64,7 +57,16 @@ fn get_api_credentials(args: &ChatArgs) -> Result<(Option<String>, Option<String
         return Ok((base, key));
     }
 
-    // 2. Check environment variables if args are not provided
+    // 2. Then the chat config file
+    if let Ok(config) = load_chat_config() {
+        // Return even if one is empty, agent handles None
+        if config.base_url.is_some() || config.api_key.is_some() {
+            println!("Using API credentials from the chat configuration.");
+            return Ok((config.base_url, config.api_key));
+        }
+    }
+
+    // 3. Check environment variables if neither is set
     let api_key_env = env::var("OPENAI_API_KEY").ok();
     let base_url_env = env::var("OPENAI_API_BASE").ok();
 
@@ -74,7 +76,17 @@ fn get_api_credentials(args: &ChatArgs) -> Result<(Option<String>, Option<String
          return Ok((base, api_key_env));
     }
 
-    // 3. Prompt user if credentials not found anywhere
+    // 4. Reuse the OpenAI key saved by `buster config`/`buster start`
+    let cached_key = get_app_base_dir()
+        .ok()
+        .and_then(|dir| get_cached_value(&dir, ".openai_api_key").ok().flatten())
+        .filter(|key| !key.is_empty());
+    if let Some(key) = cached_key {
+        println!("Using the OpenAI API key saved by `buster config`.");
+        return Ok((Some(DEFAULT_OPENAI_BASE_URL.to_string()), Some(key)));
+    }
+
+    // 5. Prompt user if credentials not found anywhere
     println!("API credentials not found. Prompting for input.");
     let mut rl =
         DefaultEditor::new().map_err(|e| ChatError::InitializationError(e.to_string()))?;
@@ -124,11 +136,37 @@ pub async fn run_chat(args: ChatArgs) -> Result<()> {
          println!("{}", colored::Colorize::yellow("Warning: No API key provided. Requests may fail if required."));
     }
 
+    let model = args.model.clone().or_else(|| load_chat_config().ok().and_then(|c| c.model));
+
+    // --- Saved Session ---
+    let sessions_dir = sessions_dir()?;
+    let resumed = match args.resume.as_deref() {
+        Some(selector) => Some(
+            find_session(&sessions_dir, selector, &cwd)
+                .map_err(|e| ChatError::InitializationError(e.to_string()))?,
+        ),
+        None => None,
+    };
+
     // --- Agent Initialization ---
-    let user_id = Uuid::new_v4();
-    let session_id = Uuid::new_v4();
-    let cli_agent = BusterCliAgent::new(user_id, session_id, api_key, base_url, Some(cwd.clone())).await
-        .map_err(|e| ChatError::InitializationError(format!("Failed to create agent: {}", e)))?;
+    // A resumed chat keeps its thread id so it's saved back to the same session
+    let (user_id, session_id) = match &resumed {
+        Some(session) => (session.thread.user_id, session.thread.id),
+        None => (Uuid::new_v4(), Uuid::new_v4()),
+    };
+    // Tools that change the project ask for confirmation through this channel
+    let (confirm_tx, mut confirm_rx) = mpsc::unbounded_channel::<ToolConfirmationRequest>();
+    let cli_agent = BusterCliAgent::new(
+        user_id,
+        session_id,
+        api_key,
+        base_url,
+        Some(cwd.clone()),
+        model,
+        confirm_tx,
+    )
+    .await
+    .map_err(|e| ChatError::InitializationError(format!("Failed to create agent: {}", e)))?;
 
     // --- Terminal Setup ---
     enable_raw_mode()?;
@@ -139,6 +177,13 @@ pub async fn run_chat(args: ChatArgs) -> Result<()> {
 
     // --- App State & Agent Receiver ---
     let mut app_state = AppState::new(user_id, session_id);
+    if let Some(session) = resumed {
+        app_state.display_log.push(DisplayLogEntry::Info {
+            timestamp: SystemTime::now(),
+            message: format!("Resumed chat '{}' ({})", session.title, session.short_id()),
+        });
+        app_state.restore_thread(session.thread);
+    }
     let mut agent_rx: Option<broadcast::Receiver<Result<AgentMessage, AgentError>>> = None;
 
     // --- Panic Hook for Cleanup ---
@@ -164,6 +209,11 @@ pub async fn run_chat(args: ChatArgs) -> Result<()> {
             break;
         }
 
+        // Queue tool calls waiting for the user's permission
+        while let Ok(request) = confirm_rx.try_recv() {
+            app_state.request_confirmation(request);
+        }
+
         let mut agent_finished_processing_this_tick = false; // Flag specific to this loop iteration
         // Handle Agent Messages
         if let Some(rx) = agent_rx.as_mut() {
@@ -205,6 +255,9 @@ pub async fn run_chat(args: ChatArgs) -> Result<()> {
              match cli_agent.get_current_thread().await {
                 Some(updated_thread) => {
                     app_state.agent_thread = updated_thread;
+                    if let Err(e) = save_chat(&sessions_dir, &cwd, &app_state.agent_thread) {
+                        app_state.current_error = Some(format!("Failed to save chat: {}", e));
+                    }
                 }
                 None => {
                     // Log an error or handle the case where the thread couldn't be retrieved
@@ -229,6 +282,20 @@ pub async fn run_chat(args: ChatArgs) -> Result<()> {
                             app_state.should_quit = true;
                             continue; // Skip further processing
                         }
+                        // --- Tool Confirmation (takes over the keyboard while a tool waits) ---
+                        if app_state.pending_confirmation().is_some() {
+                            let answer = match key.code {
+                                KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => Some(ConfirmationAnswer::Yes),
+                                KeyCode::Char('a') | KeyCode::Char('A') => Some(ConfirmationAnswer::Always),
+                                KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => Some(ConfirmationAnswer::No),
+                                _ => None,
+                            };
+                            if let Some(answer) = answer {
+                                app_state.answer_confirmation(answer);
+                            }
+                            continue;
+                        }
+
                         if key.code == KeyCode::Esc {
                             // Escape only cancels completion mode if active
                             if app_state.is_completing {
@@ -304,18 +371,15 @@ pub async fn run_chat(args: ChatArgs) -> Result<()> {
                         // --- Regular Input / Initiate Completion ---
                         if can_input && !consumed_key {
                             match key.code {
-                                KeyCode::Tab => {
-                                    if can_complete {
-                                        // Initiate completion or apply if only one option
-                                        app_state.start_or_apply_completion(&cwd);
-                                        consumed_key = true;
-                                    }
+                                KeyCode::Tab if can_complete => {
+                                    // Initiate completion or apply if only one option
+                                    app_state.start_or_apply_completion(&cwd);
+                                    consumed_key = true;
                                 }
                                 KeyCode::Enter => {
                                      let input_clone = app_state.input.trim().to_string(); // Clone for processing
                                      // Check if it's a shell command FIRST
-                                     if input_clone.starts_with('!') {
-                                         let command_str = &input_clone[1..]; // Skip the '!'
+                                     if let Some(command_str) = input_clone.strip_prefix('!') {
                                          let timestamp = SystemTime::now(); // Get current time
 
                                          // Add a message indicating the command is running to display log
@@ -359,7 +423,7 @@ pub async fn run_chat(args: ChatArgs) -> Result<()> {
                                          // Original behavior: submit to agent
                                          app_state.submit_message(); // This uses the original app_state.input
                                          // Fetch agent RX immediately after submit
-                                         match cli_agent.run(&mut app_state.agent_thread, None).await {
+                                         match cli_agent.run(&mut app_state.agent_thread).await {
                                              Ok(rx) => {
                                                  agent_rx = Some(rx); // Start polling this receiver
                                              }
@@ -384,9 +448,7 @@ pub async fn run_chat(args: ChatArgs) -> Result<()> {
                                     consumed_key = true;
                                 }
                                 KeyCode::Backspace => {
-                                    let old_len = app_state.input.len();
                                     app_state.input.pop();
-                                    let new_len = app_state.input.len();
                                     // Always update completions if we're in completion mode
                                     if app_state.is_completing {
                                         app_state.update_completions(&cwd);
@@ -452,8 +514,12 @@ pub async fn run_chat(args: ChatArgs) -> Result<()> {
         Ok(())
     }();
 
-    // Optional: Explicit agent shutdown if needed
-    // let _ = cli_agent.shutdown().await;
+    // Save on the way out too, in case the last turn never finished
+    if let Err(e) = save_chat(&sessions_dir, &cwd, &app_state.agent_thread) {
+        eprintln!("Failed to save chat: {}", e);
+    }
+    // Errors only mean the agent wasn't processing anything
+    let _ = cli_agent.shutdown().await;
 
     // Return result (prioritizing loop error over cleanup error)
     match loop_result {
@@ -461,3 +527,11 @@ pub async fn run_chat(args: ChatArgs) -> Result<()> {
         Err(e) => Err(e), 
     }
 }
+
+/// Saves the chat so it can be continued with `buster chat --resume`
+fn save_chat(dir: &std::path::Path, cwd: &str, thread: &AgentThread) -> Result<()> {
+    if thread.messages.is_empty() {
+        return Ok(());
+    }
+    save_session(dir, &ChatSession::new(cwd, thread.clone()))
+}
@@ -2,15 +2,48 @@ pub mod args;
 pub mod config;
 pub mod completion;
 pub mod logic;
+pub mod sessions;
 pub mod state;
 pub mod ui;
 
 use anyhow::Result;
+use colored::*;
 pub use args::ChatArgs;
 pub use config::{get_config_path, load_chat_config, save_chat_config, ChatConfig};
 pub use logic::run_chat;
 
 /// Public entry point for the chat command.
 pub async fn chat_command(args: ChatArgs) -> Result<()> {
+    if args.list_sessions {
+        return print_sessions();
+    }
     logic::run_chat(args).await
 }
+
+/// Prints saved chats, most recent first, with the ids `--resume` accepts
+fn print_sessions() -> Result<()> {
+    let sessions = sessions::list_sessions(&sessions::sessions_dir()?)?;
+    if sessions.is_empty() {
+        println!("No saved chat sessions.");
+        return Ok(());
+    }
+    for session in &sessions {
+        println!(
+            "{}  {}  {}  {}",
+            session.short_id().cyan(),
+            session
+                .updated_at
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+                .dimmed(),
+            session.title,
+            session.cwd.dimmed()
+        );
+    }
+    println!(
+        "\n{}",
+        "Continue one with `buster chat --resume <id>`.".dimmed()
+    );
+    Ok(())
+}
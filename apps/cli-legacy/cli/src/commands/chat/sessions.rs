@@ -0,0 +1,172 @@
+use agents::AgentThread;
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use litellm::AgentMessage;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::commands::config_utils::get_app_base_dir;
+
+const SESSIONS_DIR_NAME: &str = "chat_sessions";
+const TITLE_MAX_CHARS: usize = 60;
+
+/// A chat saved to `~/.buster/chat_sessions/<id>.json` so it can be resumed later
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChatSession {
+    pub id: String,
+    /// Directory the chat was started in; `--resume` without an id picks the latest one here
+    pub cwd: String,
+    pub title: String,
+    pub updated_at: DateTime<Utc>,
+    pub thread: AgentThread,
+}
+
+impl ChatSession {
+    pub fn new(cwd: &str, thread: AgentThread) -> Self {
+        Self {
+            id: thread.id.to_string(),
+            cwd: cwd.to_string(),
+            title: session_title(&thread),
+            updated_at: Utc::now(),
+            thread,
+        }
+    }
+
+    /// The first characters of the id, enough to pass to `--resume`
+    pub fn short_id(&self) -> &str {
+        &self.id[..8.min(self.id.len())]
+    }
+}
+
+pub fn sessions_dir() -> Result<PathBuf> {
+    Ok(get_app_base_dir()?.join(SESSIONS_DIR_NAME))
+}
+
+/// Writes the session, replacing any earlier save of the same chat
+pub fn save_session(dir: &Path, session: &ChatSession) -> Result<()> {
+    fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    let path = dir.join(format!("{}.json", session.id));
+    let contents = serde_json::to_string_pretty(session)?;
+    fs::write(&path, contents).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Saved sessions, most recently updated first. Unreadable files are skipped.
+pub fn list_sessions(dir: &Path) -> Result<Vec<ChatSession>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut sessions = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        match fs::read_to_string(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|contents| serde_json::from_str::<ChatSession>(&contents).map_err(Into::into))
+        {
+            Ok(session) => sessions.push(session),
+            Err(e) => eprintln!("Skipping chat session {}: {}", path.display(), e),
+        }
+    }
+    sessions.sort_by_key(|s| std::cmp::Reverse(s.updated_at));
+    Ok(sessions)
+}
+
+/// Finds the session to resume. `"latest"` is the most recent session started in `cwd`;
+/// anything else is a session id or a unique prefix of one.
+pub fn find_session(dir: &Path, selector: &str, cwd: &str) -> Result<ChatSession> {
+    let sessions = list_sessions(dir)?;
+
+    if selector == "latest" {
+        return sessions
+            .into_iter()
+            .find(|s| s.cwd == cwd)
+            .ok_or_else(|| anyhow!("No saved chat sessions for {}", cwd));
+    }
+
+    let mut matches: Vec<ChatSession> = sessions
+        .into_iter()
+        .filter(|s| s.id.starts_with(selector))
+        .collect();
+    match matches.len() {
+        0 => Err(anyhow!("No chat session matches '{}'", selector)),
+        1 => Ok(matches.remove(0)),
+        n => Err(anyhow!(
+            "'{}' matches {} chat sessions; use more of the id",
+            selector,
+            n
+        )),
+    }
+}
+
+/// The first user message, shortened to fit on one line
+fn session_title(thread: &AgentThread) -> String {
+    let first_prompt = thread.messages.iter().find_map(|m| match m {
+        AgentMessage::User { content, .. } => Some(content.lines().next().unwrap_or("").trim()),
+        _ => None,
+    });
+    match first_prompt {
+        Some(prompt) if prompt.chars().count() > TITLE_MAX_CHARS => {
+            let truncated: String = prompt.chars().take(TITLE_MAX_CHARS).collect();
+            format!("{}…", truncated.trim_end())
+        }
+        Some(prompt) if !prompt.is_empty() => prompt.to_string(),
+        _ => "(empty chat)".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+    use uuid::Uuid;
+
+    fn session(cwd: &str, prompt: &str, minutes_ago: i64) -> ChatSession {
+        let thread = AgentThread::new(None, Uuid::new_v4(), vec![AgentMessage::user(prompt)]);
+        let mut session = ChatSession::new(cwd, thread);
+        session.updated_at = Utc::now() - chrono::Duration::minutes(minutes_ago);
+        session
+    }
+
+    #[test]
+    fn test_save_and_find_sessions() {
+        let dir = tempdir().unwrap();
+        let older = session("/repo", "Add a revenue measure", 10);
+        let newer = session("/repo", "Draft a model for orders", 1);
+        let elsewhere = session("/other", "Hello", 0);
+        for s in [&older, &newer, &elsewhere] {
+            save_session(dir.path(), s).unwrap();
+        }
+
+        let listed = list_sessions(dir.path()).unwrap();
+        assert_eq!(
+            listed.iter().map(|s| s.id.as_str()).collect::<Vec<_>>(),
+            vec![elsewhere.id.as_str(), newer.id.as_str(), older.id.as_str()]
+        );
+
+        let latest = find_session(dir.path(), "latest", "/repo").unwrap();
+        assert_eq!(latest.id, newer.id);
+        assert_eq!(latest.title, "Draft a model for orders");
+        assert_eq!(latest.thread.messages.len(), 1);
+
+        let by_prefix = find_session(dir.path(), older.short_id(), "/anywhere").unwrap();
+        assert_eq!(by_prefix.id, older.id);
+
+        assert!(find_session(dir.path(), "latest", "/empty").is_err());
+        assert!(find_session(dir.path(), "not-an-id", "/repo").is_err());
+    }
+
+    #[test]
+    fn test_session_title() {
+        let long_prompt = "a".repeat(100);
+        let s = session("/repo", &long_prompt, 0);
+        assert_eq!(s.title.chars().count(), TITLE_MAX_CHARS + 1);
+        assert!(s.title.ends_with('…'));
+
+        let empty = ChatSession::new("/repo", AgentThread::new(None, Uuid::new_v4(), vec![]));
+        assert_eq!(empty.title, "(empty chat)");
+    }
+}
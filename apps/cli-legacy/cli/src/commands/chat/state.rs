@@ -1,13 +1,15 @@
+use agents::tools::cli_tools::{ToolConfirmationRequest, ToolDecision};
 use agents::{AgentError, AgentThread};
 use crate::commands::chat::completion; // Add import for completion logic
 use litellm::{AgentMessage, MessageProgress, ToolCall};
 use uuid::Uuid;
-use std::time::Instant;
-use serde_json;
-use serde_json::Value;
 use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
 use std::time::SystemTime; // For timestamps
 
+/// Name of the tool the agent calls to finish its turn; its `final_response` is the reply
+const DONE_TOOL_NAME: &str = "done";
+
 // --- Structs for specific tool results (add more as needed) ---
 #[derive(Serialize, Deserialize, Debug)]
 struct ListDirectoryEntry {
@@ -41,6 +43,15 @@ pub struct ActiveToolCall {
     pub content: Option<String>,
 }
 
+/// How the user answered a tool confirmation prompt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfirmationAnswer {
+    Yes,
+    /// Allow this and every later call of the same tool for the rest of the session
+    Always,
+    No,
+}
+
 pub struct AppState {
     pub input: String,
     pub messages: Vec<AgentMessage>,
@@ -53,6 +64,11 @@ pub struct AppState {
     pub agent_thread: AgentThread,
     pub is_agent_processing: bool,
 
+    // --- Tool Confirmation State ---
+    /// Tool calls waiting for the user to allow or decline them, oldest first
+    pending_confirmations: VecDeque<ToolConfirmationRequest>,
+    always_allowed_tools: HashSet<String>,
+
     // --- Autocompletion State ---
     pub is_completing: bool,
     pub completions: Vec<String>,
@@ -77,6 +93,10 @@ impl AppState {
             agent_thread: AgentThread::new(Some(session_id), user_id, vec![]),
             is_agent_processing: false,
 
+            // --- Tool Confirmation State ---
+            pending_confirmations: VecDeque::new(),
+            always_allowed_tools: HashSet::new(),
+
             // --- Autocompletion State ---
             is_completing: false,
             completions: Vec::new(),
@@ -86,6 +106,113 @@ impl AppState {
         }
     }
 
+    /// Starts from a saved chat, rebuilding what's shown from the thread's history
+    pub fn restore_thread(&mut self, thread: AgentThread) {
+        self.messages.clear();
+        for msg in &thread.messages {
+            match msg {
+                AgentMessage::User { .. } => self.messages.push(msg.clone()),
+                AgentMessage::Assistant {
+                    content,
+                    tool_calls,
+                    name,
+                    ..
+                } => {
+                    if content.as_deref().is_some_and(|c| !c.trim().is_empty()) {
+                        self.messages.push(AgentMessage::Assistant {
+                            id: None,
+                            content: content.clone(),
+                            tool_calls: None,
+                            progress: MessageProgress::Complete,
+                            initial: false,
+                            name: name.clone(),
+                        });
+                    }
+                    for tc in tool_calls.iter().flatten() {
+                        if let Some(response) = done_response(tc) {
+                            self.messages.push(AgentMessage::Assistant {
+                                id: None,
+                                content: Some(response),
+                                tool_calls: None,
+                                progress: MessageProgress::Complete,
+                                initial: false,
+                                name: name.clone(),
+                            });
+                        } else if tc.function.name != DONE_TOOL_NAME {
+                            self.messages.push(tool_call_placeholder(tc));
+                        }
+                    }
+                }
+                AgentMessage::Tool {
+                    content,
+                    tool_call_id,
+                    name,
+                    ..
+                } => {
+                    let name = name.as_deref().unwrap_or("Unknown Tool");
+                    if name == DONE_TOOL_NAME {
+                        continue;
+                    }
+                    if let Some(AgentMessage::Developer {
+                        content: msg_content,
+                        name: msg_name,
+                        ..
+                    }) = self.messages.iter_mut().find(|m| {
+                        matches!(m, AgentMessage::Developer { id: Some(id), .. } if id == tool_call_id)
+                    }) {
+                        *msg_name = Some(format!("{} Result", name));
+                        *msg_content = format!("Result ({}):\n{}", name, content);
+                    }
+                }
+                AgentMessage::Developer { .. } | AgentMessage::Done => {}
+            }
+        }
+        self.agent_thread = thread;
+        self.scroll_offset = 0;
+    }
+
+    // --- Tool Confirmation Methods ---
+
+    /// Queues a tool call for the user to confirm, or allows it straight away if the user
+    /// already chose to always allow that tool
+    pub fn request_confirmation(&mut self, request: ToolConfirmationRequest) {
+        if self.always_allowed_tools.contains(&request.tool_name) {
+            let _ = request.respond.send(ToolDecision::Allow);
+        } else {
+            self.pending_confirmations.push_back(request);
+        }
+    }
+
+    /// The tool call the user is currently being asked about
+    pub fn pending_confirmation(&self) -> Option<&ToolConfirmationRequest> {
+        self.pending_confirmations.front()
+    }
+
+    pub fn answer_confirmation(&mut self, answer: ConfirmationAnswer) {
+        let Some(request) = self.pending_confirmations.pop_front() else {
+            return;
+        };
+        let decision = match answer {
+            ConfirmationAnswer::Yes => ToolDecision::Allow,
+            ConfirmationAnswer::Always => {
+                self.always_allowed_tools.insert(request.tool_name.clone());
+                ToolDecision::Allow
+            }
+            ConfirmationAnswer::No => ToolDecision::Deny,
+        };
+        // The tool may have timed out in the meantime, in which case nobody is listening
+        let _ = request.respond.send(decision);
+
+        // Later requests for a tool that is now always allowed don't need asking
+        if answer == ConfirmationAnswer::Always {
+            let queued: Vec<_> = self.pending_confirmations.drain(..).collect();
+            for request in queued {
+                self.request_confirmation(request);
+            }
+        }
+        self.reset_scroll_request = true;
+    }
+
     pub fn submit_message(&mut self) {
         if !self.input.is_empty() && !self.is_agent_processing && self.active_tool_calls.is_empty()
         {
@@ -244,19 +371,9 @@ impl AppState {
                         .collect();
 
                     for tc in calls {
-                        if !existing_tool_ids.contains(&tc.id) {
-                            // Format arguments nicely
-                            let args_json = serde_json::to_string_pretty(&tc.function.arguments)
-                                                .unwrap_or_else(|_| "<failed to format args>".to_string());
-                            self.messages.push(AgentMessage::Developer {
-                                id: Some(tc.id.clone()),
-                                content: format!(
-                                    "Executing: {}\nArgs:\n{}",
-                                    tc.function.name,
-                                    args_json
-                                ),
-                                name: Some("Tool Call".to_string()), // Change name for clarity
-                            });
+                        // `done` carries the final reply, shown once its arguments are complete
+                        if tc.function.name != DONE_TOOL_NAME && !existing_tool_ids.contains(&tc.id) {
+                            self.messages.push(tool_call_placeholder(tc));
                         }
                     }
                 } else {
@@ -322,7 +439,7 @@ impl AppState {
                                     tool_calls: tool_calls.clone(), // CLONE for message
                                     progress,                       // Mark as Complete
                                     initial: false,
-                                    name: Some(agent_name),
+                                    name: Some(agent_name.clone()),
                                 };
                             }
                         }
@@ -349,19 +466,23 @@ impl AppState {
                         }).collect();
 
                         for tc in calls {
-                            if !existing_tool_ids.contains(&tc.id) {
-                                // Format arguments nicely
-                                let args_json = serde_json::to_string_pretty(&tc.function.arguments)
-                                                    .unwrap_or_else(|_| "<failed to format args>".to_string());
-                                self.messages.push(AgentMessage::Developer {
+                            let already_shown = self.messages.iter().any(|m| {
+                                matches!(m, AgentMessage::Assistant { id: Some(id), .. } if *id == tc.id)
+                            });
+                            if already_shown {
+                                continue;
+                            }
+                            if let Some(response) = done_response(tc) {
+                                self.messages.push(AgentMessage::Assistant {
                                     id: Some(tc.id.clone()),
-                                    content: format!(
-                                        "Executing: {}\nArgs:\n{}",
-                                        tc.function.name,
-                                        args_json
-                                    ),
-                                    name: Some("Tool Call".to_string()), // Change name for clarity
+                                    content: Some(response),
+                                    tool_calls: None,
+                                    progress: MessageProgress::Complete,
+                                    initial: false,
+                                    name: Some(agent_name.clone()),
                                 });
+                            } else if tc.function.name != DONE_TOOL_NAME && !existing_tool_ids.contains(&tc.id) {
+                                self.messages.push(tool_call_placeholder(tc));
                             }
                         }
                     }
@@ -383,6 +504,12 @@ impl AppState {
         let name = tool_name.unwrap_or_else(|| "Unknown Tool".to_string());
         let mut found_message = false;
 
+        // The reply from `done` was already shown from its arguments
+        if name == DONE_TOOL_NAME {
+            self.active_tool_calls.retain(|t| t.id != tool_call_id);
+            return;
+        }
+
         // Update the placeholder message in the main history
         for msg in self.messages.iter_mut() {
             if let AgentMessage::Developer { id: msg_id, content: msg_content, name: msg_name, .. } = msg {
@@ -562,7 +689,6 @@ impl AppState {
                 if end <= self.input.len() { // Basic sanity check
                     self.input.replace_range(start..end, &selected_completion);
 
-                    let is_dir = selected_completion.ends_with('/');
                     self.cancel_completion(); // Exit completion mode after applying
 
                     // Return true, let the caller decide if update_completions is needed (e.g., for dirs)
@@ -588,3 +714,148 @@ impl AppState {
         self.completion_fragment_len = None;
     }
 }
+
+/// The message shown for a tool call until its result arrives
+fn tool_call_placeholder(tc: &ToolCall) -> AgentMessage {
+    // Format arguments nicely
+    let args_json = serde_json::from_str::<serde_json::Value>(&tc.function.arguments)
+        .and_then(|args| serde_json::to_string_pretty(&args))
+        .unwrap_or_else(|_| tc.function.arguments.clone());
+    AgentMessage::Developer {
+        id: Some(tc.id.clone()),
+        content: format!("Executing: {}\nArgs:\n{}", tc.function.name, args_json),
+        name: Some("Tool Call".to_string()), // Change name for clarity
+    }
+}
+
+/// The reply passed to a `done` call, if `tc` is one
+fn done_response(tc: &ToolCall) -> Option<String> {
+    if tc.function.name != DONE_TOOL_NAME {
+        return None;
+    }
+    serde_json::from_str::<serde_json::Value>(&tc.function.arguments)
+        .ok()?
+        .get("final_response")?
+        .as_str()
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use litellm::FunctionCall;
+    use tokio::sync::oneshot;
+
+    fn tool_call(id: &str, name: &str, arguments: &str) -> ToolCall {
+        ToolCall {
+            id: id.to_string(),
+            call_type: "function".to_string(),
+            function: FunctionCall {
+                name: name.to_string(),
+                arguments: arguments.to_string(),
+            },
+            code_interpreter: None,
+            retrieval: None,
+        }
+    }
+
+    fn assistant(tool_calls: Vec<ToolCall>) -> AgentMessage {
+        AgentMessage::assistant(
+            None,
+            None,
+            Some(tool_calls),
+            MessageProgress::Complete,
+            None,
+            Some("Buster".to_string()),
+        )
+    }
+
+    #[test]
+    fn test_restore_thread() {
+        let user_id = Uuid::new_v4();
+        let thread = AgentThread::new(
+            None,
+            user_id,
+            vec![
+                AgentMessage::developer("system prompt"),
+                AgentMessage::user("List the models"),
+                assistant(vec![tool_call("call_1", "LS", r#"{"path":"models"}"#)]),
+                AgentMessage::tool(
+                    None,
+                    "orders.sql",
+                    "call_1",
+                    Some("LS".to_string()),
+                    MessageProgress::Complete,
+                ),
+                assistant(vec![tool_call(
+                    "call_2",
+                    "done",
+                    r#"{"final_response":"There is one model."}"#,
+                )]),
+                AgentMessage::tool(
+                    None,
+                    r#"{"success":true}"#,
+                    "call_2",
+                    Some("done".to_string()),
+                    MessageProgress::Complete,
+                ),
+            ],
+        );
+
+        let mut state = AppState::new(user_id, Uuid::new_v4());
+        state.restore_thread(thread.clone());
+
+        assert_eq!(state.agent_thread.id, thread.id);
+        assert_eq!(state.messages.len(), 3);
+        assert!(matches!(&state.messages[0], AgentMessage::User { content, .. } if content == "List the models"));
+        assert!(matches!(
+            &state.messages[1],
+            AgentMessage::Developer { content, name, .. }
+                if content == "Result (LS):\norders.sql" && name.as_deref() == Some("LS Result")
+        ));
+        assert!(matches!(
+            &state.messages[2],
+            AgentMessage::Assistant { content: Some(c), .. } if c == "There is one model."
+        ));
+    }
+
+    fn confirmation(tool_name: &str) -> (ToolConfirmationRequest, oneshot::Receiver<ToolDecision>) {
+        let (respond, decision) = oneshot::channel();
+        let request = ToolConfirmationRequest {
+            tool_call_id: format!("{}_call", tool_name),
+            tool_name: tool_name.to_string(),
+            summary: String::new(),
+            params: serde_json::Value::Null,
+            respond,
+        };
+        (request, decision)
+    }
+
+    #[test]
+    fn test_confirmations() {
+        let mut state = AppState::new(Uuid::new_v4(), Uuid::new_v4());
+        let (bash_1, mut bash_1_decision) = confirmation("Bash");
+        let (edit, mut edit_decision) = confirmation("Edit");
+        let (bash_2, mut bash_2_decision) = confirmation("Bash");
+        state.request_confirmation(bash_1);
+        state.request_confirmation(edit);
+        state.request_confirmation(bash_2);
+        assert_eq!(state.pending_confirmation().unwrap().tool_name, "Bash");
+
+        // Always allowing Bash also answers the queued Bash call, but not the Edit
+        state.answer_confirmation(ConfirmationAnswer::Always);
+        assert_eq!(bash_1_decision.try_recv().unwrap(), ToolDecision::Allow);
+        assert_eq!(bash_2_decision.try_recv().unwrap(), ToolDecision::Allow);
+        assert_eq!(state.pending_confirmation().unwrap().tool_name, "Edit");
+
+        state.answer_confirmation(ConfirmationAnswer::No);
+        assert_eq!(edit_decision.try_recv().unwrap(), ToolDecision::Deny);
+        assert!(state.pending_confirmation().is_none());
+
+        // Later Bash calls are allowed without asking
+        let (bash_3, mut bash_3_decision) = confirmation("Bash");
+        state.request_confirmation(bash_3);
+        assert!(state.pending_confirmation().is_none());
+        assert_eq!(bash_3_decision.try_recv().unwrap(), ToolDecision::Allow);
+    }
+}
@@ -20,6 +20,7 @@ pub fn ui(frame: &mut Frame, app: &mut AppState, cwd: &str) {
     let status_height = if app.active_tool_calls.is_empty()
         && !app.is_agent_processing
         && app.current_error.is_none()
+        && app.pending_confirmation().is_none()
     {
         0
     } else {
@@ -27,7 +28,8 @@ pub fn ui(frame: &mut Frame, app: &mut AppState, cwd: &str) {
         let tool_call_lines = app.active_tool_calls.len() as u16;
         let thinking_line = if app.is_agent_processing && app.active_tool_calls.is_empty() { 1 } else { 0 };
         let error_line = if app.current_error.is_some() { 1 } else { 0 };
-        tool_call_lines + thinking_line + error_line + 1 // +1 border
+        let confirmation_lines = if app.pending_confirmation().is_some() { 3 } else { 0 };
+        tool_call_lines + thinking_line + error_line + confirmation_lines + 1 // +1 border
     };
 
     let main_chunks = Layout::default()
@@ -38,7 +40,7 @@ pub fn ui(frame: &mut Frame, app: &mut AppState, cwd: &str) {
             Constraint::Length(3), // Input area
             Constraint::Length(completion_height), // Completions (dynamic)
         ])
-        .split(frame.size());
+        .split(frame.area());
 
     let has_any_message = !app.messages.is_empty();
 
@@ -119,8 +121,6 @@ fn render_messages(frame: &mut Frame, app: &mut AppState, area: Rect) {
                 let is_in_progress = *progress == MessageProgress::InProgress;
                 let is_last_message = msg_index == num_messages - 1;
 
-                // Find the index of the previous non-Developer message
-                let prev_non_dev_index = app.messages[..msg_index].iter().rposition(|m| !matches!(m, AgentMessage::Developer { .. }));
                 // Check if the message *immediately* before this one was a Developer message
                 let prev_msg_was_dev = if msg_index > 0 {
                     matches!(app.messages.get(msg_index - 1), Some(AgentMessage::Developer { .. }))
@@ -274,7 +274,7 @@ fn render_messages(frame: &mut Frame, app: &mut AppState, area: Rect) {
 
     let content_height = message_lines.len() as u16;
     let view_height = area.height;
-    let max_scroll = content_height.saturating_sub(view_height).max(0);
+    let max_scroll = content_height.saturating_sub(view_height);
 
     // Handle scroll reset request
     let current_scroll = if app.reset_scroll_request {
@@ -298,6 +298,26 @@ fn render_status(frame: &mut Frame, app: &AppState, area: Rect) {
 
     let mut status_items: Vec<ListItem> = Vec::new();
 
+    // A tool is waiting for the user's permission to run
+    if let Some(request) = app.pending_confirmation() {
+        status_items.push(ListItem::new(Line::from(Span::styled(
+            format!("Allow {} to run?", request.tool_name),
+            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+        ))));
+        status_items.push(ListItem::new(Line::from(Span::styled(
+            format!("  {}", request.summary),
+            Style::default().fg(Color::White),
+        ))));
+        status_items.push(ListItem::new(Line::from(vec![
+            Span::styled("  [y] ", Style::default().fg(Color::Green)),
+            Span::raw("yes  "),
+            Span::styled("[a] ", Style::default().fg(Color::Green)),
+            Span::raw(format!("always allow {} this session  ", request.tool_name)),
+            Span::styled("[n] ", Style::default().fg(Color::Red)),
+            Span::raw("no"),
+        ])));
+    }
+
     for tool in &app.active_tool_calls {
         let content_preview = tool.content.as_deref().unwrap_or("");
         let display_content = if content_preview.len() > 50 {
@@ -371,10 +391,10 @@ fn render_input(frame: &mut Frame, app: &AppState, area: Rect) {
 
     // --- Cursor ---
     if !is_input_disabled {
-        frame.set_cursor(
+        frame.set_cursor_position((
             area.x + 1 + input_prefix.len() as u16 + app.input.chars().count() as u16, // +1 for left border
             area.y + 1,
-        )
+        ))
     }
 }
 
@@ -1,4 +1,6 @@
+pub mod assets;
 pub mod auth;
+pub mod chat;
 pub mod config;
 pub mod config_utils;
 pub mod deploy;
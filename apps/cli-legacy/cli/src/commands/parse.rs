@@ -10,7 +10,7 @@ use crate::utils::{
     ProgressTracker,
 };
 use crate::commands::deploy::deploy::{parse_model_file, resolve_model_configurations};
-use semantic_layer::models::Model;
+use semantic_layer::{lint_files, models::Model, SourceFile};
 
 // A simple progress tracker for the parse command
 #[derive(Debug, Default)]
@@ -218,11 +218,17 @@ pub async fn parse_models_command(path_arg: Option<String>) -> Result<()> {
         }
     };
 
+    let mut lint_sources: Vec<SourceFile> = Vec::new();
+
     for (yml_path, project_ctx_opt) in files_to_parse_with_context {
         progress.processed_files += 1;
         progress.current_file = yml_path.strip_prefix(&effective_buster_config_dir).unwrap_or(&yml_path).to_string_lossy().into_owned();
         progress.log_status();
 
+        if let Ok(content) = std::fs::read_to_string(&yml_path) {
+            lint_sources.push(SourceFile { path: progress.current_file.clone(), content });
+        }
+
         let parsed_models_result = parse_model_file(&yml_path);
         
         match parsed_models_result {
@@ -327,6 +333,25 @@ pub async fn parse_models_command(path_arg: Option<String>) -> Result<()> {
         }
     }
 
+    // Relationships, joins and expressions can only be checked with all the models at hand
+    println!("\n{}", "🔎 Linting models together...".dimmed());
+    for diagnostic in lint_files(&lint_sources) {
+        // Files that don't parse were already reported above
+        if diagnostic.code == "parse-error" {
+            continue;
+        }
+        if diagnostic.is_error() {
+            println!("  ❌ {}", diagnostic.to_string().red());
+            progress.errors.push((
+                diagnostic.file.clone(),
+                diagnostic.model.clone().unwrap_or_default(),
+                vec![format!("line {}: {}", diagnostic.span.line, diagnostic.message)],
+            ));
+        } else {
+            println!("  ⚠️ {}", diagnostic.to_string().yellow());
+        }
+    }
+
     progress.log_summary();
 
     if !progress.errors.is_empty() {
@@ -69,6 +69,27 @@ pub enum Commands {
         #[arg(long, default_value_t = true)]
         recursive: bool,
     },
+    /// Write metrics and dashboards to `metrics/` and `dashboards/` YAML files
+    Pull {
+        /// Project directory to write the files to (defaults to current directory)
+        #[arg(long)]
+        path: Option<String>,
+        /// Overwrite files with changes that haven't been pushed
+        #[arg(long, default_value_t = false)]
+        force: bool,
+    },
+    /// Create and update metrics and dashboards from their YAML files
+    Push {
+        /// Project directory to read the files from (defaults to current directory)
+        #[arg(long)]
+        path: Option<String>,
+        /// Show what would be created and updated without applying it
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+        /// Overwrite metrics and dashboards that were changed on the server since the last pull
+        #[arg(long, default_value_t = false)]
+        force: bool,
+    },
     /// Generate or update semantic model YAML definitions from dbt project
     Generate {
         /// Optional path to a specific dbt model .sql file or a directory of dbt models to process.
@@ -90,6 +111,24 @@ pub enum Commands {
     },
     /// Interactively manage LLM and Reranker configurations
     Config,
+    /// Chat with an agent that reads and edits your dbt project and semantic models
+    Chat {
+        /// Base URL of the OpenAI-compatible LLM API
+        #[arg(long)]
+        base_url: Option<String>,
+        /// API key for the LLM API
+        #[arg(long)]
+        api_key: Option<String>,
+        /// Model to use (defaults to the chat config, then o4-mini)
+        #[arg(long)]
+        model: Option<String>,
+        /// Continue a saved chat by id, or the latest one in this directory if no id is given
+        #[arg(long, value_name = "SESSION_ID", num_args = 0..=1, default_missing_value = "latest")]
+        resume: Option<String>,
+        /// List saved chats and exit
+        #[arg(long, default_value_t = false, conflicts_with = "resume")]
+        list_sessions: bool,
+    },
     /// Start the Buster services
     Start {
         /// Disable telemetry tracking
@@ -159,12 +198,46 @@ async fn main() {
             }
             .await
         }
+        Commands::Pull { path, force } => {
+            async move {
+                check_authentication().await?;
+                commands::assets::pull(path.as_deref(), force).await
+            }
+            .await
+        }
+        Commands::Push {
+            path,
+            dry_run,
+            force,
+        } => {
+            async move {
+                check_authentication().await?;
+                commands::assets::push(path.as_deref(), dry_run, force).await
+            }
+            .await
+        }
         Commands::Generate {
             path,
             target_semantic_file,
         } => commands::generate::generate_semantic_models_command(path, target_semantic_file).await,
         Commands::Parse { path } => commands::parse::parse_models_command(path).await,
         Commands::Config => commands::config::manage_settings_interactive().await.map_err(anyhow::Error::from),
+        Commands::Chat {
+            base_url,
+            api_key,
+            model,
+            resume,
+            list_sessions,
+        } => {
+            commands::chat::chat_command(commands::chat::ChatArgs {
+                base_url,
+                api_key,
+                model,
+                resume,
+                list_sessions,
+            })
+            .await
+        }
         Commands::Start { no_track, env_vars } => {
             // Parse env vars from KEY=VALUE format
             let parsed_env_vars: Result<Vec<(String, String)>, _> = env_vars
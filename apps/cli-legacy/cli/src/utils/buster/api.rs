@@ -1,15 +1,22 @@
 use anyhow::Result;
 use reqwest::{
     header::{HeaderMap, HeaderValue},
-    Client,
+    Client, Response, StatusCode,
 };
+use serde::de::{DeserializeOwned, IgnoredAny};
 use std::error::Error as StdError;
+use uuid::Uuid;
 
 use super::{
-    DeployDatasetsRequest, DeployDatasetsResponse, GenerateApiRequest, GenerateApiResponse,
-    PostDataSourcesRequest, ValidateApiKeyRequest, ValidateApiKeyResponse,
+    AssetListItem, CreateDashboardRequest, CreateMetricRequest, DashboardResponse,
+    DeleteDashboardsRequest, DeployDatasetsRequest, DeployDatasetsResponse, GenerateApiRequest,
+    GenerateApiResponse, MetricFileResponse, PostDataSourcesRequest, UpdateAssetFileRequest,
+    ValidateApiKeyRequest, ValidateApiKeyResponse,
 };
 
+/// Page size used when listing every metric or dashboard
+const LIST_PAGE_SIZE: i64 = 100;
+
 pub struct BusterClient {
     client: Client,
     base_url: String,
@@ -184,4 +191,154 @@ impl BusterClient {
             )),
         }
     }
+
+    pub async fn list_metrics(&self) -> Result<Vec<AssetListItem>> {
+        self.list_all("metric_files").await
+    }
+
+    /// Returns `None` when the metric doesn't exist or the user can't see it
+    pub async fn get_metric(&self, id: &Uuid) -> Result<Option<MetricFileResponse>> {
+        let endpoint = format!("metric_files/{}", id);
+        let res = self.send(self.client.get(self.url(&endpoint)), "GET", &endpoint).await?;
+        if res.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        Self::json_response(res, "GET", &endpoint).await.map(Some)
+    }
+
+    pub async fn create_metric(&self, req_body: CreateMetricRequest) -> Result<MetricFileResponse> {
+        let res = self
+            .send(
+                self.client.post(self.url("metric_files")).json(&req_body),
+                "POST",
+                "metric_files",
+            )
+            .await?;
+        Self::json_response(res, "POST", "metric_files").await
+    }
+
+    pub async fn update_metric(
+        &self,
+        id: &Uuid,
+        req_body: UpdateAssetFileRequest,
+    ) -> Result<MetricFileResponse> {
+        let endpoint = format!("metric_files/{}", id);
+        let res = self
+            .send(self.client.put(self.url(&endpoint)).json(&req_body), "PUT", &endpoint)
+            .await?;
+        Self::json_response(res, "PUT", &endpoint).await
+    }
+
+    pub async fn list_dashboards(&self) -> Result<Vec<AssetListItem>> {
+        self.list_all("dashboards").await
+    }
+
+    /// Returns `None` when the dashboard doesn't exist or the user can't see it
+    pub async fn get_dashboard(&self, id: &Uuid) -> Result<Option<DashboardResponse>> {
+        let endpoint = format!("dashboards/{}", id);
+        let res = self.send(self.client.get(self.url(&endpoint)), "GET", &endpoint).await?;
+        if res.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        Self::json_response(res, "GET", &endpoint).await.map(Some)
+    }
+
+    /// Creates an empty dashboard; its definition is set with `update_dashboard`
+    pub async fn create_dashboard(
+        &self,
+        req_body: CreateDashboardRequest,
+    ) -> Result<DashboardResponse> {
+        let res = self
+            .send(
+                self.client.post(self.url("dashboards")).json(&req_body),
+                "POST",
+                "dashboards",
+            )
+            .await?;
+        Self::json_response(res, "POST", "dashboards").await
+    }
+
+    pub async fn delete_dashboards(&self, ids: Vec<Uuid>) -> Result<()> {
+        let req_body = DeleteDashboardsRequest { ids };
+        let res = self
+            .send(
+                self.client.delete(self.url("dashboards")).json(&req_body),
+                "DELETE",
+                "dashboards",
+            )
+            .await?;
+        Self::json_response::<IgnoredAny>(res, "DELETE", "dashboards")
+            .await
+            .map(|_| ())
+    }
+
+    pub async fn update_dashboard(
+        &self,
+        id: &Uuid,
+        req_body: UpdateAssetFileRequest,
+    ) -> Result<DashboardResponse> {
+        let endpoint = format!("dashboards/{}", id);
+        let res = self
+            .send(self.client.put(self.url(&endpoint)).json(&req_body), "PUT", &endpoint)
+            .await?;
+        Self::json_response(res, "PUT", &endpoint).await
+    }
+
+    fn url(&self, endpoint: &str) -> String {
+        format!("{}/api/v1/{}", self.base_url, endpoint)
+    }
+
+    async fn send(
+        &self,
+        request: reqwest::RequestBuilder,
+        method: &str,
+        endpoint: &str,
+    ) -> Result<Response> {
+        request
+            .headers(self.build_headers()?)
+            .send()
+            .await
+            .map_err(|e| anyhow::anyhow!("{} /api/v1/{} failed: {}", method, endpoint, e))
+    }
+
+    async fn json_response<T: DeserializeOwned>(
+        res: Response,
+        method: &str,
+        endpoint: &str,
+    ) -> Result<T> {
+        if !res.status().is_success() {
+            let status = res.status();
+            let body = res.text().await?;
+            return Err(anyhow::anyhow!(
+                "{} /api/v1/{} failed with status {}: {}",
+                method,
+                endpoint,
+                status,
+                body
+            ));
+        }
+        res.json().await.map_err(|e| {
+            anyhow::anyhow!("Failed to parse {} /api/v1/{} response: {}", method, endpoint, e)
+        })
+    }
+
+    /// Pages through a list endpoint until it returns a short page
+    async fn list_all(&self, endpoint: &str) -> Result<Vec<AssetListItem>> {
+        let mut items = Vec::new();
+        let mut page_token = 0;
+        loop {
+            let request = self.client.get(self.url(endpoint)).query(&[
+                ("page_token", page_token),
+                ("page_size", LIST_PAGE_SIZE),
+            ]);
+            let res = self.send(request, "GET", endpoint).await?;
+            let page: Vec<AssetListItem> = Self::json_response(res, "GET", endpoint).await?;
+            let done = (page.len() as i64) < LIST_PAGE_SIZE;
+            items.extend(page);
+            if done {
+                return Ok(items);
+            }
+            page_token += 1;
+        }
+    }
 }
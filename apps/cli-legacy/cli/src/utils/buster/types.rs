@@ -116,3 +116,58 @@ pub struct GenerateApiError {
     pub error_type: Option<String>,
     pub context: Option<String>,
 }
+
+/// A metric or dashboard as returned by the list endpoints
+#[derive(Debug, Deserialize)]
+pub struct AssetListItem {
+    pub id: Uuid,
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MetricFileResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub version_number: i32,
+    pub data_source_id: Uuid,
+    /// The metric's YAML definition
+    pub file: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DashboardFileResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub version_number: i32,
+    /// The dashboard's YAML definition
+    pub file: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DashboardResponse {
+    pub dashboard: DashboardFileResponse,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreateMetricRequest {
+    pub id: Option<Uuid>,
+    pub file: String,
+    pub data_source_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreateDashboardRequest {
+    pub id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeleteDashboardsRequest {
+    pub ids: Vec<Uuid>,
+}
+
+/// Replaces a metric's or dashboard's YAML definition
+#[derive(Debug, Serialize)]
+pub struct UpdateAssetFileRequest {
+    pub file: String,
+    pub update_version: bool,
+}
@@ -0,0 +1,389 @@
+//! Local files for metrics and dashboards managed with `buster pull` and `buster push`.
+//!
+//! Each file is the asset's `MetricYml`/`DashboardYml` definition with a few top-level keys
+//! that tie it to the server: `id`, `version_number` (the server version the file was last
+//! synced with) and, for metrics, `data_source_id`. The keys are stripped before the
+//! definition is sent to the server.
+
+use anyhow::{anyhow, Result};
+use serde_yaml::{Mapping, Value};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+const ID_KEY: &str = "id";
+const VERSION_KEY: &str = "version_number";
+const DATA_SOURCE_KEY: &str = "data_source_id";
+
+const FILE_HEADER: &str =
+    "# Managed by `buster pull`. Edit the definition and run `buster push` to apply it.\n";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssetKind {
+    Metric,
+    Dashboard,
+}
+
+impl AssetKind {
+    /// The directory the kind's files live in, under the project directory
+    pub fn dir_name(&self) -> &'static str {
+        match self {
+            AssetKind::Metric => "metrics",
+            AssetKind::Dashboard => "dashboards",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            AssetKind::Metric => "metric",
+            AssetKind::Dashboard => "dashboard",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AssetFile {
+    pub path: PathBuf,
+    pub kind: AssetKind,
+    pub id: Option<Uuid>,
+    pub version_number: Option<i32>,
+    pub data_source_id: Option<Uuid>,
+    /// The definition without the sync keys
+    pub definition: Value,
+    /// The file as read, so sync keys can be rewritten without reformatting the definition
+    pub contents: String,
+}
+
+impl AssetFile {
+    pub fn parse(kind: AssetKind, path: PathBuf, contents: String) -> Result<Self> {
+        let value: Value = serde_yaml::from_str(&contents)
+            .map_err(|e| anyhow!("{} is not valid YAML: {}", path.display(), e))?;
+        let Value::Mapping(mut mapping) = value else {
+            return Err(anyhow!("{} must contain a YAML mapping", path.display()));
+        };
+
+        let id = take_meta(&mut mapping, ID_KEY, &path)?;
+        let version_number = match mapping.remove(VERSION_KEY) {
+            None | Some(Value::Null) => None,
+            Some(value) => Some(
+                value
+                    .as_i64()
+                    .and_then(|v| i32::try_from(v).ok())
+                    .ok_or_else(|| {
+                        anyhow!("{}: `version_number` must be a number", path.display())
+                    })?,
+            ),
+        };
+        let data_source_id = take_meta(&mut mapping, DATA_SOURCE_KEY, &path)?;
+        if !mapping.contains_key("name") {
+            return Err(anyhow!("{}: the definition has no `name`", path.display()));
+        }
+
+        Ok(Self {
+            path,
+            kind,
+            id,
+            version_number,
+            data_source_id,
+            definition: Value::Mapping(mapping),
+            contents,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        self.definition
+            .get("name")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+    }
+
+    /// The definition as sent to the server
+    pub fn definition_yaml(&self) -> Result<String> {
+        Ok(serde_yaml::to_string(&self.definition)?)
+    }
+}
+
+fn take_meta(mapping: &mut Mapping, key: &str, path: &Path) -> Result<Option<Uuid>> {
+    match mapping.remove(key) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(s)) => Uuid::parse_str(&s)
+            .map(Some)
+            .map_err(|_| anyhow!("{}: `{}` must be a UUID", path.display(), key)),
+        Some(_) => Err(anyhow!("{}: `{}` must be a UUID", path.display(), key)),
+    }
+}
+
+/// Reads every `.yml`/`.yaml` file in the kind's directory. Missing directories are empty.
+pub fn read_asset_files(project_dir: &Path, kind: AssetKind) -> Result<Vec<AssetFile>> {
+    let dir = project_dir.join(kind.dir_name());
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut paths: Vec<PathBuf> = fs::read_dir(&dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.is_file()
+                && matches!(
+                    path.extension().and_then(|e| e.to_str()),
+                    Some("yml") | Some("yaml")
+                )
+        })
+        .collect();
+    paths.sort();
+
+    let mut files = Vec::with_capacity(paths.len());
+    let mut ids = HashSet::new();
+    for path in paths {
+        let contents = fs::read_to_string(&path)?;
+        let file = AssetFile::parse(kind, path, contents)?;
+        if let Some(id) = file.id {
+            if !ids.insert(id) {
+                return Err(anyhow!(
+                    "{}: another {} file already uses id {}",
+                    file.path.display(),
+                    kind.label(),
+                    id
+                ));
+            }
+        }
+        files.push(file);
+    }
+    Ok(files)
+}
+
+/// Renders a pulled asset: the sync keys followed by the server's definition
+pub fn render_asset_file(
+    id: &Uuid,
+    version_number: i32,
+    data_source_id: Option<&Uuid>,
+    definition: &str,
+) -> String {
+    let mut contents = String::from(FILE_HEADER);
+    contents.push_str(&format!("{}: {}\n", ID_KEY, id));
+    contents.push_str(&format!("{}: {}\n", VERSION_KEY, version_number));
+    if let Some(data_source_id) = data_source_id {
+        contents.push_str(&format!("{}: {}\n", DATA_SOURCE_KEY, data_source_id));
+    }
+    contents.push_str(definition.trim_start_matches("---\n"));
+    if !contents.ends_with('\n') {
+        contents.push('\n');
+    }
+    contents
+}
+
+/// Sets the `id` and `version_number` keys of a file after a push, leaving the rest of the
+/// file as the user wrote it
+pub fn set_sync_keys(contents: &str, id: &Uuid, version_number: i32) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    let mut insert_at = None;
+    for line in contents.lines() {
+        if line.starts_with(&format!("{}:", ID_KEY))
+            || line.starts_with(&format!("{}:", VERSION_KEY))
+        {
+            insert_at.get_or_insert(lines.len());
+            continue;
+        }
+        lines.push(line.to_string());
+    }
+
+    // Without existing keys, insert them after any leading comments
+    let insert_at = insert_at.unwrap_or_else(|| {
+        lines
+            .iter()
+            .position(|line| !line.starts_with('#') && line.trim() != "---")
+            .unwrap_or(lines.len())
+    });
+    lines.insert(insert_at, format!("{}: {}", VERSION_KEY, version_number));
+    lines.insert(insert_at, format!("{}: {}", ID_KEY, id));
+
+    let mut updated = lines.join("\n");
+    updated.push('\n');
+    updated
+}
+
+/// A file name for a pulled asset that isn't in the project yet
+pub fn file_name_for(name: &str, id: &Uuid, taken: &HashSet<String>) -> String {
+    let mut slug = String::new();
+    for c in name.trim().chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.ends_with('_') && !slug.is_empty() {
+            slug.push('_');
+        }
+    }
+    let slug = slug.trim_end_matches('_');
+    let slug = if slug.is_empty() { "untitled" } else { slug };
+
+    let file_name = format!("{}.yml", slug);
+    if !taken.contains(&file_name) {
+        return file_name;
+    }
+    format!("{}_{}.yml", slug, &id.simple().to_string()[..8])
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum PushAction {
+    /// The asset isn't on the server yet
+    Create,
+    Unchanged,
+    Update,
+    /// The server has versions newer than the one the file was synced with
+    Conflict {
+        local_version: Option<i32>,
+        remote_version: i32,
+    },
+}
+
+/// Decides what `push` does with a file given the server's current version of the asset
+pub fn plan_push(file: &AssetFile, remote: Option<(i32, &str)>) -> Result<PushAction> {
+    let Some((remote_version, remote_definition)) = remote else {
+        return Ok(PushAction::Create);
+    };
+    if same_definition(&file.definition, remote_definition)? {
+        return Ok(PushAction::Unchanged);
+    }
+    if file.version_number == Some(remote_version) {
+        return Ok(PushAction::Update);
+    }
+    Ok(PushAction::Conflict {
+        local_version: file.version_number,
+        remote_version,
+    })
+}
+
+/// Whether a local file has edits that haven't been pushed, so `pull` shouldn't overwrite it
+pub fn has_unpushed_changes(
+    file: &AssetFile,
+    remote_version: i32,
+    remote_definition: &str,
+) -> Result<bool> {
+    Ok(file.version_number == Some(remote_version)
+        && !same_definition(&file.definition, remote_definition)?)
+}
+
+/// Compares definitions as YAML values, so formatting, comments and key order don't count
+/// as changes
+fn same_definition(local: &Value, remote: &str) -> Result<bool> {
+    let remote: Value = serde_yaml::from_str(remote)
+        .map_err(|e| anyhow!("The server returned an invalid definition: {}", e))?;
+    Ok(*local == remote)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const METRIC: &str = "name: Revenue\ntime_frame: Last 30 days\nsql: select 1\n";
+
+    fn metric_file(contents: &str) -> AssetFile {
+        AssetFile::parse(
+            AssetKind::Metric,
+            PathBuf::from("metrics/revenue.yml"),
+            contents.to_string(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_render_and_parse_round_trip() {
+        let id = Uuid::new_v4();
+        let data_source_id = Uuid::new_v4();
+        let contents = render_asset_file(&id, 3, Some(&data_source_id), METRIC);
+        let file = metric_file(&contents);
+
+        assert_eq!(file.id, Some(id));
+        assert_eq!(file.version_number, Some(3));
+        assert_eq!(file.data_source_id, Some(data_source_id));
+        assert_eq!(file.name(), "Revenue");
+        assert!(file.definition.get(ID_KEY).is_none());
+        assert_eq!(
+            plan_push(&file, Some((3, METRIC))).unwrap(),
+            PushAction::Unchanged
+        );
+    }
+
+    #[test]
+    fn test_plan_push() {
+        let id = Uuid::new_v4();
+        let edited = render_asset_file(&id, 3, None, &METRIC.replace("select 1", "select 2"));
+        let file = metric_file(&edited);
+
+        assert_eq!(plan_push(&file, None).unwrap(), PushAction::Create);
+        assert_eq!(
+            plan_push(&file, Some((3, METRIC))).unwrap(),
+            PushAction::Update
+        );
+        assert_eq!(
+            plan_push(&file, Some((4, METRIC))).unwrap(),
+            PushAction::Conflict {
+                local_version: Some(3),
+                remote_version: 4
+            }
+        );
+        assert!(has_unpushed_changes(&file, 3, METRIC).unwrap());
+        assert!(!has_unpushed_changes(&file, 4, METRIC).unwrap());
+
+        // Key order and formatting aren't changes
+        let reordered = "sql: select 2\nname: Revenue\ntime_frame: 'Last 30 days'\n";
+        assert_eq!(
+            plan_push(&file, Some((4, reordered))).unwrap(),
+            PushAction::Unchanged
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_sync_keys() {
+        let err = AssetFile::parse(
+            AssetKind::Metric,
+            PathBuf::from("a.yml"),
+            format!("id: not-a-uuid\n{}", METRIC),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("`id` must be a UUID"));
+
+        let err = AssetFile::parse(
+            AssetKind::Dashboard,
+            PathBuf::from("a.yml"),
+            "rows: []\n".to_string(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("no `name`"));
+    }
+
+    #[test]
+    fn test_set_sync_keys_keeps_the_rest_of_the_file() {
+        let id = Uuid::new_v4();
+        let new_file = format!("# Weekly revenue\n{}", METRIC);
+        assert_eq!(
+            set_sync_keys(&new_file, &id, 1),
+            format!(
+                "# Weekly revenue\nid: {}\nversion_number: 1\n{}",
+                id, METRIC
+            )
+        );
+
+        let pulled = render_asset_file(&id, 3, None, METRIC);
+        let pushed = set_sync_keys(&pulled, &id, 4);
+        assert_eq!(pushed, render_asset_file(&id, 4, None, METRIC));
+    }
+
+    #[test]
+    fn test_file_name_for() {
+        let id = Uuid::new_v4();
+        let mut taken = HashSet::new();
+        assert_eq!(
+            file_name_for("Revenue by Month (USD)", &id, &taken),
+            "revenue_by_month_usd.yml"
+        );
+        assert_eq!(file_name_for("  ", &id, &taken), "untitled.yml");
+
+        taken.insert("revenue.yml".to_string());
+        let name = file_name_for("Revenue", &id, &taken);
+        assert_eq!(
+            name,
+            format!("revenue_{}.yml", &id.simple().to_string()[..8])
+        );
+    }
+}
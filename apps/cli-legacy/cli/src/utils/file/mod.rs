@@ -1,3 +1,4 @@
+pub mod asset_files;
 pub mod buster_credentials;
 pub mod model_files;
 pub mod profiles;
@@ -311,9 +311,16 @@ fn get_schema_name(profile: &Profile) -> Result<String> {
         query_engine::credentials::Credential::Bigquery(cred) => {
             Ok(cred.default_dataset_id.clone())
         }
-        query_engine::credentials::Credential::MySql(_) => Ok("".to_string()),
+        query_engine::credentials::Credential::MySql(_)
+        | query_engine::credentials::Credential::Mariadb(_) => Ok("".to_string()),
         query_engine::credentials::Credential::Databricks(cred) => {
             Ok(cred.default_schema.clone().unwrap_or_default())
         }
+        query_engine::credentials::Credential::DuckDb(cred) => {
+            Ok(cred.default_schema.clone().unwrap_or_default())
+        }
+        query_engine::credentials::Credential::ClickHouse(cred) => {
+            Ok(cred.default_database.clone())
+        }
     }
 }